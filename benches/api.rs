@@ -0,0 +1,143 @@
+//! Criterion benchmarks for the hot API paths, driven through Rocket's local client.
+//!
+//!     cargo bench --bench api
+//!
+//! Each group runs against a pre-seeded database so numbers reflect a realistically sized
+//! history rather than an empty table. Compare runs with criterion's saved baselines
+//! (`--save-baseline before` / `--baseline before`) when changing DB or FTS code.
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use local_agent_chat::db::Db;
+use local_agent_chat::rate_limit::RateLimitConfig;
+use rocket::http::{ContentType, Status};
+use rocket::local::blocking::Client;
+
+/// Messages seeded into the benchmark room before measuring.
+const SEED_MESSAGES: usize = 10_000;
+
+/// Rooms seeded for the list_rooms benchmark.
+const SEED_ROOMS: usize = 200;
+
+struct Bench {
+    client: Option<Client>,
+    db_path: String,
+    room_id: String,
+}
+
+impl Drop for Bench {
+    fn drop(&mut self) {
+        drop(self.client.take());
+        let _ = std::fs::remove_file(&self.db_path);
+        let _ = std::fs::remove_file(format!("{}-wal", self.db_path));
+        let _ = std::fs::remove_file(format!("{}-shm", self.db_path));
+    }
+}
+
+impl Bench {
+    fn client(&self) -> &Client {
+        self.client.as_ref().unwrap()
+    }
+}
+
+/// Seed rooms and messages directly in SQLite (the API would rate-limit the seeding),
+/// including the FTS rows search relies on.
+fn setup() -> Bench {
+    let db_path = format!(
+        "/tmp/chat_bench_{}.db",
+        uuid::Uuid::new_v4().to_string().split('-').next().unwrap()
+    );
+    let room_id = "bench-room-0".to_string();
+    {
+        let db = Db::new(&db_path);
+        let mut conn = db.conn();
+        let tx = conn.transaction().unwrap();
+        let mut seq = 0i64;
+        for r in 0..SEED_ROOMS {
+            tx.execute(
+                "INSERT INTO rooms (id, name, created_by, created_at, updated_at) VALUES (?1, ?2, 'bench', '2026-01-01T00:00:00Z', '2026-01-01T00:00:00Z')",
+                rusqlite::params![format!("bench-room-{r}"), format!("bench-{r:04}")],
+            )
+            .unwrap();
+        }
+        for m in 0..SEED_MESSAGES {
+            seq += 1;
+            let id = uuid::Uuid::new_v4().to_string();
+            let content = format!("status update {m}: deployment of service-{} finished", m % 37);
+            tx.execute(
+                "INSERT INTO messages (id, room_id, sender, content, metadata, created_at, seq) VALUES (?1, ?2, ?3, ?4, '{}', ?5, ?6)",
+                rusqlite::params![&id, &room_id, format!("agent-{}", m % 11), &content, "2026-01-01T00:00:00Z", seq],
+            )
+            .unwrap();
+            local_agent_chat::db::upsert_fts(&tx, &id);
+        }
+        tx.commit().unwrap();
+    }
+
+    let unlimited = RateLimitConfig {
+        messages_max: usize::MAX,
+        reactions_max: usize::MAX,
+        ..Default::default()
+    };
+    let rocket = local_agent_chat::rocket_with_db_and_config(&db_path, unlimited);
+    let client = Client::tracked(rocket).expect("valid rocket instance");
+    Bench {
+        client: Some(client),
+        db_path,
+        room_id,
+    }
+}
+
+fn bench_api(c: &mut Criterion) {
+    let bench = setup();
+    let client = bench.client();
+    let room = &bench.room_id;
+
+    c.bench_function("send_message", |b| {
+        b.iter(|| {
+            let res = client
+                .post(format!("/api/v1/rooms/{room}/messages"))
+                .header(ContentType::JSON)
+                .body(r#"{"sender": "bench", "content": "hello from the benchmark"}"#)
+                .dispatch();
+            assert_eq!(res.status(), Status::Ok);
+        })
+    });
+
+    let mut group = c.benchmark_group("get_messages");
+    for limit in [50, 500] {
+        group.bench_with_input(BenchmarkId::new("latest", limit), &limit, |b, &limit| {
+            b.iter(|| {
+                let res = client
+                    .get(format!("/api/v1/rooms/{room}/messages?latest={limit}"))
+                    .dispatch();
+                assert_eq!(res.status(), Status::Ok);
+            })
+        });
+    }
+    group.bench_function("after_cursor", |b| {
+        b.iter(|| {
+            let res = client
+                .get(format!("/api/v1/rooms/{room}/messages?after=5000&limit=100"))
+                .dispatch();
+            assert_eq!(res.status(), Status::Ok);
+        })
+    });
+    group.finish();
+
+    c.bench_function("search", |b| {
+        b.iter(|| {
+            let res = client.get("/api/v1/search?q=deployment&limit=20").dispatch();
+            assert_eq!(res.status(), Status::Ok);
+        })
+    });
+
+    c.bench_function("list_rooms", |b| {
+        b.iter(|| {
+            let res = client.get("/api/v1/rooms").dispatch();
+            assert_eq!(res.status(), Status::Ok);
+        })
+    });
+}
+
+criterion_group!(benches, bench_api);
+criterion_main!(benches);
@@ -0,0 +1,30 @@
+// Web Push service worker: shows mention/DM notifications while the tab is closed,
+// and focuses (or opens) the chat on click.
+
+self.addEventListener('push', (event) => {
+  let data = {};
+  try { data = event.data ? event.data.json() : {}; } catch { /* ignore */ }
+  event.waitUntil(
+    self.registration.showNotification(data.title || 'Local Agent Chat', {
+      body: data.body || '',
+      icon: '/favicon.svg',
+      tag: data.message_id,
+      data: { room_id: data.room_id },
+    })
+  );
+});
+
+self.addEventListener('notificationclick', (event) => {
+  event.notification.close();
+  const roomId = event.notification.data && event.notification.data.room_id;
+  event.waitUntil((async () => {
+    const windows = await self.clients.matchAll({ type: 'window', includeUncontrolled: true });
+    for (const client of windows) {
+      if (new URL(client.url).origin === self.location.origin) {
+        client.postMessage({ type: 'open-room', room_id: roomId });
+        return client.focus();
+      }
+    }
+    return self.clients.openWindow(roomId ? `/?room=${encodeURIComponent(roomId)}` : '/');
+  })());
+});
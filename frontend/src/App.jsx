@@ -2,7 +2,7 @@ import React, { useState, useEffect, useRef, useCallback } from 'react';
 import { styles, injectGlobalStyles } from './styles';
 import { API, senderColor } from './utils';
 import { RoomList, ChatArea, SenderModal, AdminKeyModal, ProfileModal } from './components';
-import { useSSE, useChatAPI } from './hooks';
+import { useSSE, useChatAPI, usePushNotifications } from './hooks';
 
 // Inject global CSS on load
 injectGlobalStyles();
@@ -46,6 +46,8 @@ export default function App() {
 
   // --- Hooks ---
 
+  const push = usePushNotifications(sender);
+
   const api = useChatAPI({
     senderRef,
     lastSeqRef,
@@ -85,6 +87,19 @@ export default function App() {
     localStorage.setItem('chat-sound', soundEnabled ? 'on' : 'off');
   }, [soundEnabled]);
 
+  // --- Push notification clicks while the app is open ---
+
+  useEffect(() => {
+    if (!('serviceWorker' in navigator)) return;
+    const onMessage = (event) => {
+      if (event.data?.type !== 'open-room' || !event.data.room_id) return;
+      const room = rooms.find(r => r.id === event.data.room_id);
+      if (room) setActiveRoom(room);
+    };
+    navigator.serviceWorker.addEventListener('message', onMessage);
+    return () => navigator.serviceWorker.removeEventListener('message', onMessage);
+  }, [rooms]);
+
   // --- Tab title with unread count ---
 
   useEffect(() => {
@@ -110,7 +125,9 @@ export default function App() {
   useEffect(() => {
     api.fetchRooms().then(data => {
       if (data.length > 0 && !activeRoom) {
-        const general = data.find(r => r.name === 'general') || data[0];
+        // ?room=<id> comes from a push notification click
+        const requested = new URLSearchParams(window.location.search).get('room');
+        const general = data.find(r => r.id === requested) || data.find(r => r.name === 'general') || data[0];
         setActiveRoom(general);
       }
     });
@@ -310,6 +327,7 @@ export default function App() {
           onRoomArchived={api.handleRoomArchived}
           soundEnabled={soundEnabled}
           onToggleSound={() => setSoundEnabled(prev => !prev)}
+          push={push}
           hasMore={api.hasMore}
           onLoadOlder={() => api.loadOlderMessages(activeRoom, messages)}
           onlineUsers={onlineUsers}
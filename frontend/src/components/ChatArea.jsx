@@ -16,7 +16,7 @@ import FileCard from './FileCard';
 import MessageGroup from './MessageGroup';
 import useFileUpload from '../hooks/useFileUpload';
 
-export default function ChatArea({ room, messages, files, sender, reactions, profiles, onSend, onEditMessage, onDeleteMessage, onDeleteFile, onUploadFile, onReact, onPin, onUnpin, adminKey, onTyping, typingUsers, loading, connected, rooms, onSelectRoom, onRoomUpdate, onRoomArchived, soundEnabled, onToggleSound, hasMore, onLoadOlder, onlineUsers }) {
+export default function ChatArea({ room, messages, files, sender, reactions, profiles, onSend, onEditMessage, onDeleteMessage, onDeleteFile, onUploadFile, onReact, onPin, onUnpin, adminKey, onTyping, typingUsers, loading, connected, rooms, onSelectRoom, onRoomUpdate, onRoomArchived, soundEnabled, onToggleSound, push, hasMore, onLoadOlder, onlineUsers }) {
   const [replyTo, setReplyTo] = useState(null);
   const messagesEndRef = useRef(null);
   const containerRef = useRef(null);
@@ -196,6 +196,7 @@ export default function ChatArea({ room, messages, files, sender, reactions, pro
         onOpenSettings={() => setShowSettings(true)}
         soundEnabled={soundEnabled}
         onToggleSound={onToggleSound}
+        push={push}
       />
 
       {showSearch && (
@@ -9,7 +9,7 @@ export default function ChatHeader({
   showPins, onTogglePins,
   showParticipants, onToggleParticipants, onlineUsers,
   onOpenSettings, showSettings,
-  soundEnabled, onToggleSound,
+  soundEnabled, onToggleSound, push,
 }) {
   const [isMobile, setIsMobile] = useState(window.innerWidth <= 768);
   const [showOverflow, setShowOverflow] = useState(false);
@@ -136,6 +136,16 @@ export default function ChatHeader({
     </button>
   );
 
+  const pushBtn = push?.supported && (
+    <button
+      onClick={() => { push.toggle(); setShowOverflow(false); }}
+      style={iconBtnStyle(push.enabled)}
+      title={push.enabled ? 'Disable desktop notifications for mentions and DMs' : 'Enable desktop notifications for mentions and DMs'}
+    >
+      📲
+    </button>
+  );
+
   // Desktop: show all buttons inline
   if (!isMobile) {
     return (
@@ -154,6 +164,7 @@ export default function ChatHeader({
           {pinsBtn}
           {participantsBtn}
           {settingsBtn}
+          {pushBtn}
           {soundBtn}
         </div>
       </div>
@@ -218,6 +229,11 @@ export default function ChatHeader({
               <OverflowItem onClick={() => { onToggleSound(); setShowOverflow(false); }}>
                 {soundEnabled ? '🔔 Mute' : '🔕 Unmute'}
               </OverflowItem>
+              {push?.supported && (
+                <OverflowItem onClick={() => { push.toggle(); setShowOverflow(false); }} active={push.enabled}>
+                  📲 {push.enabled ? 'Notifications on' : 'Notifications off'}
+                </OverflowItem>
+              )}
             </div>
           )}
         </div>
@@ -58,7 +58,7 @@ export default function RoomList({ rooms, activeRoom, onSelect, onCreateRoom, on
               style={{
                 ...styles.roomItem,
                 background: activeRoom?.id === room.id ? '#1e293b' : 'transparent',
-                borderLeft: activeRoom?.id === room.id ? '3px solid #3b82f6' : '3px solid transparent',
+                borderLeft: activeRoom?.id === room.id ? `3px solid ${room.color || '#3b82f6'}` : `3px solid ${room.color ? room.color + '66' : 'transparent'}`,
               }}
             >
               <div style={{ display: 'flex', alignItems: 'center', justifyContent: 'space-between' }}>
@@ -78,6 +78,11 @@ export default function RoomList({ rooms, activeRoom, onSelect, onCreateRoom, on
                       className="room-bookmark-icon"
                     >☆</span>
                   )}
+                  {room.icon_url ? (
+                    <img src={room.icon_url} alt="" style={{ width: 16, height: 16, borderRadius: 4, objectFit: 'cover', flexShrink: 0 }} />
+                  ) : room.icon && (
+                    <span style={{ fontSize: '0.85rem', flexShrink: 0, lineHeight: 1 }}>{room.icon}</span>
+                  )}
                   <span style={{ fontWeight: unread > 0 ? 700 : 500, color: activeRoom?.id === room.id ? '#f1f5f9' : unread > 0 ? '#f1f5f9' : '#cbd5e1', overflow: 'hidden', textOverflow: 'ellipsis', whiteSpace: 'nowrap' }}>
                     #{room.name}
                   </span>
@@ -1,2 +1,3 @@
 export { default as useSSE } from './useSSE';
 export { default as useChatAPI } from './useChatAPI';
+export { default as usePushNotifications } from './usePushNotifications';
@@ -0,0 +1,67 @@
+import { useState, useEffect, useCallback } from 'react';
+import { API } from '../utils';
+
+const STORAGE_KEY = 'chat-push-subscription';
+
+function urlBase64ToUint8Array(base64) {
+  const padded = (base64 + '='.repeat((4 - (base64.length % 4)) % 4)).replace(/-/g, '+').replace(/_/g, '/');
+  const raw = atob(padded);
+  return Uint8Array.from(raw, c => c.charCodeAt(0));
+}
+
+/**
+ * OS notifications for mentions and DMs via Web Push, so they arrive with the tab closed.
+ * `enabled` is per browser; re-registering after a sender change points the subscription at the new name.
+ */
+export default function usePushNotifications(sender) {
+  const supported = typeof window !== 'undefined'
+    && 'serviceWorker' in navigator && 'PushManager' in window && 'Notification' in window;
+  const [subscriptionId, setSubscriptionId] = useState(() => localStorage.getItem(STORAGE_KEY));
+
+  const register = useCallback(async () => {
+    const keyRes = await fetch(`${API}/push/vapid-public-key`);
+    const { enabled, public_key } = await keyRes.json();
+    if (!enabled) throw new Error('Web Push is not available on this server');
+    if (await Notification.requestPermission() !== 'granted') throw new Error('Notification permission denied');
+    const registration = await navigator.serviceWorker.register('/sw.js');
+    await navigator.serviceWorker.ready;
+    const subscription = await registration.pushManager.getSubscription()
+      || await registration.pushManager.subscribe({
+        userVisibleOnly: true,
+        applicationServerKey: urlBase64ToUint8Array(public_key),
+      });
+    const res = await fetch(`${API}/push/subscriptions`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ ...subscription.toJSON(), sender }),
+    });
+    if (!res.ok) throw new Error('Failed to register push subscription');
+    const { id } = await res.json();
+    localStorage.setItem(STORAGE_KEY, id);
+    setSubscriptionId(id);
+  }, [sender]);
+
+  const unregister = useCallback(async () => {
+    const id = localStorage.getItem(STORAGE_KEY);
+    if (id) await fetch(`${API}/push/subscriptions/${id}`, { method: 'DELETE' }).catch(() => {});
+    const registration = await navigator.serviceWorker.getRegistration('/sw.js');
+    const subscription = registration && await registration.pushManager.getSubscription();
+    if (subscription) await subscription.unsubscribe();
+    localStorage.removeItem(STORAGE_KEY);
+    setSubscriptionId(null);
+  }, []);
+
+  // Keep the subscription's sender in sync with the current name
+  useEffect(() => {
+    if (supported && subscriptionId && sender && Notification.permission === 'granted') {
+      register().catch(() => {});
+    }
+  }, [sender]); // eslint-disable-line react-hooks/exhaustive-deps
+
+  const toggle = useCallback(() => {
+    const action = subscriptionId ? unregister() : register();
+    action.catch(e => alert(e.message));
+  }, [subscriptionId, register, unregister]);
+
+  return { supported, enabled: !!subscriptionId, toggle };
+}
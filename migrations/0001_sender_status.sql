@@ -0,0 +1,13 @@
+-- Structured per-sender status updates. Every PUT appends a row; the latest row is
+-- the sender's current status and the rest is its history.
+CREATE TABLE IF NOT EXISTS sender_status (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    sender TEXT NOT NULL,
+    state TEXT,
+    task TEXT,
+    progress REAL,
+    eta TEXT,
+    details TEXT NOT NULL DEFAULT '{}',
+    created_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_sender_status_sender ON sender_status(sender, id);
@@ -0,0 +1,19 @@
+-- Per-room FIFO work queues. An item is claimed by one sender at a time; a claim whose
+-- lease lapses before completion goes back to pending.
+CREATE TABLE IF NOT EXISTS queue_items (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    id TEXT NOT NULL UNIQUE,
+    room_id TEXT NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+    task TEXT NOT NULL,
+    data TEXT NOT NULL DEFAULT '{}',
+    created_by TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    claimed_by TEXT,
+    claimed_at TEXT,
+    lease_expires_at TEXT,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    completed_at TEXT,
+    result TEXT,
+    created_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_queue_items_room_status ON queue_items(room_id, status, seq);
@@ -0,0 +1,9 @@
+-- Named locks for agents coordinating access to shared resources. Rows outlive their
+-- holders so the fencing token keeps increasing across acquisitions.
+CREATE TABLE IF NOT EXISTS locks (
+    name TEXT PRIMARY KEY,
+    holder TEXT,
+    token INTEGER NOT NULL DEFAULT 0,
+    acquired_at TEXT,
+    expires_at TEXT
+);
@@ -0,0 +1,14 @@
+-- Server-level webhooks: not tied to a room, they fire on room lifecycle changes
+-- anywhere on the server. Only the outcome of the latest delivery is kept.
+CREATE TABLE IF NOT EXISTS server_webhooks (
+    id TEXT PRIMARY KEY,
+    url TEXT NOT NULL,
+    events TEXT NOT NULL DEFAULT '*',
+    secret TEXT,
+    created_by TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    active INTEGER NOT NULL DEFAULT 1,
+    last_delivery_at TEXT,
+    last_status TEXT,
+    last_error TEXT
+);
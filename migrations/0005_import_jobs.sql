@@ -0,0 +1,20 @@
+-- Background imports of Slack/Discord export archives. One row per upload; counters are
+-- updated as each channel is committed so clients can poll progress.
+CREATE TABLE IF NOT EXISTS import_jobs (
+    id TEXT PRIMARY KEY,
+    format TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'queued',
+    created_by TEXT NOT NULL,
+    archive_size INTEGER NOT NULL,
+    rooms_total INTEGER NOT NULL DEFAULT 0,
+    rooms_done INTEGER NOT NULL DEFAULT 0,
+    messages_imported INTEGER NOT NULL DEFAULT 0,
+    files_imported INTEGER NOT NULL DEFAULT 0,
+    attachments_missing INTEGER NOT NULL DEFAULT 0,
+    profiles_imported INTEGER NOT NULL DEFAULT 0,
+    rooms TEXT NOT NULL DEFAULT '[]',
+    error TEXT,
+    created_at TEXT NOT NULL,
+    started_at TEXT,
+    finished_at TEXT
+);
@@ -0,0 +1,11 @@
+-- Per-sender daily budgets. One row per canonical sender that has posted, uploaded or been
+-- given an override; counters roll over when usage_day (a UTC date) changes.
+CREATE TABLE IF NOT EXISTS sender_quotas (
+    sender TEXT PRIMARY KEY,
+    daily_messages INTEGER,
+    daily_upload_bytes INTEGER,
+    usage_day TEXT,
+    messages_today INTEGER NOT NULL DEFAULT 0,
+    upload_bytes_today INTEGER NOT NULL DEFAULT 0,
+    updated_at TEXT
+);
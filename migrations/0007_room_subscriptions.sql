@@ -0,0 +1,8 @@
+-- Rooms a sender follows. Keyed by canonical sender; a sender with no rows follows everything.
+CREATE TABLE IF NOT EXISTS room_subscriptions (
+    sender TEXT NOT NULL,
+    room_id TEXT NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (sender, room_id)
+);
+CREATE INDEX IF NOT EXISTS idx_room_subscriptions_room ON room_subscriptions(room_id);
@@ -0,0 +1,7 @@
+-- Messages sent with `sensitive: true` and when their content is (or was) redacted.
+CREATE TABLE IF NOT EXISTS sensitive_messages (
+    message_id TEXT PRIMARY KEY REFERENCES messages(id) ON DELETE CASCADE,
+    redact_at TEXT NOT NULL,
+    redacted_at TEXT
+);
+CREATE INDEX IF NOT EXISTS idx_sensitive_messages_due ON sensitive_messages(redact_at) WHERE redacted_at IS NULL;
@@ -0,0 +1,2 @@
+-- Free-form room tags (a JSON array of lowercase strings), for grouping and filtering rooms.
+ALTER TABLE rooms ADD COLUMN tags TEXT NOT NULL DEFAULT '[]';
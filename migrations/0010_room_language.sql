@@ -0,0 +1,19 @@
+-- Per-room search language. search_tokenizer is derived from language and names the FTS5
+-- table the room's messages are indexed in: porter -> messages_fts (the original index),
+-- unicode61 -> messages_fts_unicode61, trigram -> messages_fts_trigram.
+ALTER TABLE rooms ADD COLUMN language TEXT;
+ALTER TABLE rooms ADD COLUMN search_tokenizer TEXT NOT NULL DEFAULT 'porter';
+
+CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts_unicode61 USING fts5(
+    message_id UNINDEXED,
+    sender,
+    content,
+    tokenize='unicode61 remove_diacritics 2'
+);
+
+CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts_trigram USING fts5(
+    message_id UNINDEXED,
+    sender,
+    content,
+    tokenize='trigram'
+);
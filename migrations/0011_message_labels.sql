@@ -0,0 +1,9 @@
+-- Labels senders attach to messages ("decision", "action-item", "bug"), for filtering and retros.
+CREATE TABLE IF NOT EXISTS message_labels (
+    message_id TEXT NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+    label TEXT NOT NULL,
+    added_by TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (message_id, label)
+);
+CREATE INDEX IF NOT EXISTS idx_message_labels_label ON message_labels(label, message_id);
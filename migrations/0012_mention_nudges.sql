@@ -0,0 +1,9 @@
+-- Per-profile opt-in: DM a pointer to a mention left unread this many minutes while offline.
+ALTER TABLE profiles ADD COLUMN mention_nudge_minutes INTEGER;
+-- Mentions already nudged, so each is DMed at most once per target.
+CREATE TABLE IF NOT EXISTS mention_nudges (
+    message_id TEXT NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+    target TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (message_id, target)
+);
@@ -0,0 +1,2 @@
+-- Per-room reaction allow-list: a JSON array of normalized emoji. NULL allows any reaction.
+ALTER TABLE rooms ADD COLUMN allowed_reactions TEXT;
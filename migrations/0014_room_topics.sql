@@ -0,0 +1,10 @@
+-- Room topics, IRC-style: any participant can set one. Every change appends a row; the
+-- latest row is the room's current topic (an empty topic clears it) and the rest is history.
+CREATE TABLE IF NOT EXISTS room_topics (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    room_id TEXT NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+    topic TEXT NOT NULL,
+    set_by TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_room_topics_room ON room_topics(room_id, id);
@@ -0,0 +1,7 @@
+-- Per-webhook transport settings for endpoints behind auth. `headers` (a JSON object of static
+-- headers) and `client_cert` (PEM certificate chain plus private key for mTLS) hold credentials,
+-- so both are stored sealed by the server's secrets key. `ca_cert` is a public PEM certificate
+-- to trust for the endpoint and is stored as-is.
+ALTER TABLE webhooks ADD COLUMN headers TEXT;
+ALTER TABLE webhooks ADD COLUMN client_cert TEXT;
+ALTER TABLE webhooks ADD COLUMN ca_cert TEXT;
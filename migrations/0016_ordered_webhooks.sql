@@ -0,0 +1,2 @@
+-- Ordered webhooks get their deliveries one at a time, in event order, instead of concurrently.
+ALTER TABLE webhooks ADD COLUMN ordered INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,13 @@
+-- Transactional outbox: events written in the same transaction as the change they describe.
+-- `published_at` is set once the event has gone out on the bus; rows left NULL by a crash are
+-- published by the relay on the next start. Published rows are kept a day for SSE resume.
+CREATE TABLE IF NOT EXISTS event_outbox (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    room_id TEXT,
+    event TEXT NOT NULL,
+    request_id TEXT,
+    created_at TEXT NOT NULL,
+    published_at TEXT
+);
+CREATE INDEX IF NOT EXISTS idx_event_outbox_room ON event_outbox(room_id, id);
+CREATE INDEX IF NOT EXISTS idx_event_outbox_unpublished ON event_outbox(created_at) WHERE published_at IS NULL;
@@ -0,0 +1,10 @@
+-- Roles granted in a room by its admin, keyed by canonical sender. Senders without a row are
+-- members, except the room's creator, who is its owner.
+CREATE TABLE IF NOT EXISTS room_roles (
+    room_id TEXT NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+    sender TEXT NOT NULL,
+    role TEXT NOT NULL CHECK (role IN ('owner', 'moderator', 'member', 'guest')),
+    granted_at TEXT NOT NULL,
+    granted_by TEXT,
+    PRIMARY KEY (room_id, sender)
+);
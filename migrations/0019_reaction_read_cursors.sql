@@ -0,0 +1,7 @@
+-- How far each sender has read their reaction notifications, as a message_reactions rowid.
+-- Keyed by canonical sender; reactions past the cursor are unread.
+CREATE TABLE IF NOT EXISTS reaction_read_cursors (
+    sender TEXT PRIMARY KEY,
+    last_read_cursor INTEGER NOT NULL,
+    updated_at TEXT NOT NULL
+);
@@ -0,0 +1,13 @@
+-- Replies a message is waiting on (`requires_response_from`), one row per responder, keyed by
+-- canonical sender. Cleared by a reply in the message's thread; escalated once past due_at.
+CREATE TABLE IF NOT EXISTS pending_responses (
+    message_id TEXT NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+    responder TEXT NOT NULL,
+    requested_at TEXT NOT NULL,
+    due_at TEXT NOT NULL,
+    responded_at TEXT,
+    response_id TEXT,
+    escalated_at TEXT,
+    PRIMARY KEY (message_id, responder)
+);
+CREATE INDEX IF NOT EXISTS idx_pending_responses_open ON pending_responses(responder, due_at) WHERE responded_at IS NULL;
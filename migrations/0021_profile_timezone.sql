@@ -0,0 +1,2 @@
+-- Per-profile IANA timezone, used by `?tz=@sender` for localized display timestamps.
+ALTER TABLE profiles ADD COLUMN timezone TEXT;
@@ -0,0 +1,27 @@
+-- Per-room quiet hours: a daily window, in the room's timezone, during which agent posts are
+-- held back. start_time/end_time are HH:MM; a window with end before start runs past midnight.
+CREATE TABLE IF NOT EXISTS room_quiet_hours (
+    room_id TEXT PRIMARY KEY REFERENCES rooms(id) ON DELETE CASCADE,
+    start_time TEXT NOT NULL,
+    end_time TEXT NOT NULL,
+    timezone TEXT NOT NULL DEFAULT 'UTC',
+    updated_at TEXT NOT NULL
+);
+
+-- Agent posts held for the end of their room's quiet hours, delivered in queue order. The
+-- redaction and response timers are kept as durations and start when the message is delivered.
+CREATE TABLE IF NOT EXISTS deferred_messages (
+    id TEXT PRIMARY KEY,
+    room_id TEXT NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+    sender TEXT NOT NULL,
+    sender_type TEXT,
+    content TEXT NOT NULL,
+    metadata TEXT NOT NULL DEFAULT '{}',
+    reply_to TEXT,
+    redact_after_secs INTEGER,
+    requires_response_from TEXT NOT NULL DEFAULT '[]',
+    response_timeout_secs INTEGER,
+    queued_at TEXT NOT NULL,
+    deliver_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_deferred_messages_room ON deferred_messages(room_id, queued_at);
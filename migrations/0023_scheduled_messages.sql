@@ -0,0 +1,4 @@
+-- Posts sent with `scheduled_at` wait in deferred_messages alongside those held by quiet hours;
+-- scheduled_at is when they are due. Rows without it belong to quiet hours.
+ALTER TABLE deferred_messages ADD COLUMN scheduled_at TEXT;
+CREATE INDEX IF NOT EXISTS idx_deferred_messages_scheduled ON deferred_messages(scheduled_at) WHERE scheduled_at IS NOT NULL;
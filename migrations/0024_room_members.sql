@@ -0,0 +1,11 @@
+-- Private rooms: only members (and the room's admin key) can read or post. Each membership has
+-- its own token, presented as X-Member-Token.
+ALTER TABLE rooms ADD COLUMN visibility TEXT NOT NULL DEFAULT 'public';
+CREATE TABLE IF NOT EXISTS room_members (
+    room_id TEXT NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+    sender TEXT NOT NULL,
+    token TEXT NOT NULL UNIQUE,
+    added_by TEXT,
+    added_at TEXT NOT NULL,
+    PRIMARY KEY (room_id, sender)
+);
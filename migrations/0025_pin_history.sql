@@ -0,0 +1,17 @@
+-- Every pin and unpin, with who and when. Sender and content are copied so the archive outlives
+-- the message itself.
+CREATE TABLE IF NOT EXISTS pin_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    room_id TEXT NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+    message_id TEXT NOT NULL,
+    action TEXT NOT NULL,
+    actor TEXT NOT NULL,
+    sender TEXT NOT NULL,
+    content TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_pin_history_room ON pin_history(room_id, id);
+-- Pins made before the archive existed
+INSERT INTO pin_history (room_id, message_id, action, actor, sender, content, created_at)
+SELECT room_id, id, 'pinned', COALESCE(pinned_by, 'admin'), sender, content, pinned_at
+FROM messages WHERE pinned_at IS NOT NULL ORDER BY pinned_at;
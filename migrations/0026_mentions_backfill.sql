@@ -0,0 +1,4 @@
+-- Parse @mentions out of messages written before the mentions index existed. The parsing is
+-- done in Rust (db::rebuild_mentions_index) right after this, in the same savepoint; it used to
+-- run on every startup.
+DELETE FROM mentions;
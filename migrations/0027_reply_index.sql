@@ -0,0 +1,2 @@
+-- Thread walks (unread thread counts, thread views) follow reply_to from parent to replies
+CREATE INDEX IF NOT EXISTS idx_messages_reply_to ON messages(reply_to);
@@ -0,0 +1,6 @@
+-- Streamed messages hold a lease that each chunk renews. Once it lapses the retention sweep
+-- finalizes the message as it stands (or removes an empty placeholder). Streams already open
+-- get a fresh five minutes.
+ALTER TABLE message_streams ADD COLUMN lease_expires_at TEXT;
+UPDATE message_streams SET lease_expires_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '+300 seconds');
+CREATE INDEX IF NOT EXISTS idx_message_streams_lease ON message_streams(lease_expires_at);
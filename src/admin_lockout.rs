@@ -0,0 +1,178 @@
+//! Brute-force protection for room admin keys. Wrong keys are counted per client IP and room in
+//! the [`RateLimiter`]; after `ADMIN_KEY_MAX_FAILURES` of them that IP is locked out of the
+//! room's admin key for `ADMIN_KEY_LOCKOUT_SECS`, doubling with every further failure. A
+//! locked-out request gets a 429 before any route runs, even with the right key, and each
+//! lockout is written to the room's audit log.
+
+use std::io::Cursor;
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::uri::Origin;
+use rocket::http::{Method, Status};
+use rocket::request::{FromRequest, Outcome};
+use rocket::{Data, Request, Response};
+
+use crate::ip_policy::IpPolicy;
+use crate::namespaces::ScopedDb;
+use crate::rate_limit::{RateLimitConfig, RateLimiter};
+
+/// Where locked-out requests are rerouted; only reachable through [`AdminKeyLockoutFairing`].
+pub const LOCKED_PATH: &str = "/api/v1/admin-key/locked";
+
+/// A room-scoped request carrying an admin key, remembered until the response.
+struct AdminKeyAttempt {
+    /// "ip|room_id", the unit failures are counted in
+    key: String,
+    room_id: String,
+    ip: String,
+    presented: String,
+}
+
+/// Marks a request the fairing rerouted to [`LOCKED_PATH`], with the seconds left on the lockout.
+#[derive(Debug, Clone, Copy)]
+pub struct AdminKeyLocked(pub u64);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AdminKeyLocked {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match req.local_cache(|| None::<AdminKeyLocked>) {
+            Some(locked) => Outcome::Success(*locked),
+            None => Outcome::Forward(Status::NotFound),
+        }
+    }
+}
+
+/// The admin key a request carries, read the same way as [`crate::routes::AdminKey`].
+fn presented_key(req: &Request<'_>) -> Option<String> {
+    req.headers()
+        .get_one("Authorization")
+        .and_then(|auth| auth.strip_prefix("Bearer "))
+        .or_else(|| req.headers().get_one("X-Admin-Key"))
+        .map(str::to_string)
+}
+
+/// Fairing that refuses admin keys from locked-out IPs and counts wrong ones. Attach after the
+/// fairings that rewrite paths (namespaces, API versions, by-name addressing) so the room id is
+/// known, and after the IP policy so refused addresses are never counted.
+pub struct AdminKeyLockoutFairing;
+
+#[rocket::async_trait]
+impl Fairing for AdminKeyLockoutFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Admin Key Lockout",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _data: &mut Data<'_>) {
+        let Some(config) = req.rocket().state::<RateLimitConfig>() else {
+            return;
+        };
+        if config.admin_key_max_failures == 0 {
+            return;
+        }
+        let Some(presented) = presented_key(req) else {
+            return;
+        };
+        let path = req.uri().path().as_str().to_string();
+        let Some(rest) = path.strip_prefix("/api/v1/rooms/") else {
+            return;
+        };
+        let room_id = rest.split('/').next().unwrap_or_default();
+        if room_id.is_empty() {
+            return;
+        }
+        let ip = req
+            .rocket()
+            .state::<IpPolicy>()
+            .and_then(|policy| policy.client_ip(req))
+            .or_else(|| req.remote().map(|r| r.ip()))
+            .map_or_else(|| "unknown".to_string(), |ip| ip.to_string());
+        let key = format!("{ip}|{room_id}");
+
+        if let Some(retry_after) = req.rocket().state::<RateLimiter>().and_then(|l| l.admin_key_locked(&key)) {
+            req.local_cache(|| Some(AdminKeyLocked(retry_after)));
+            req.set_method(Method::Get);
+            req.set_uri(Origin::parse(LOCKED_PATH).expect("valid locked path"));
+            return;
+        }
+        let room_id = room_id.to_string();
+        req.local_cache(|| {
+            Some(AdminKeyAttempt {
+                key,
+                room_id,
+                ip,
+                presented,
+            })
+        });
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        let Some(attempt) = req.local_cache(|| None::<AdminKeyAttempt>) else {
+            return;
+        };
+        let (Some(config), Some(limiter)) = (req.rocket().state::<RateLimitConfig>(), req.rocket().state::<RateLimiter>())
+        else {
+            return;
+        };
+        if res.status().class().is_success() {
+            // Any bearer token passes on open routes, so only the room's own key wipes the slate
+            if limiter.admin_key_has_failures(&attempt.key)
+                && let Some(db) = ScopedDb::of(req)
+                && db
+                    .conn()
+                    .query_row("SELECT admin_key FROM rooms WHERE id = ?1", [&attempt.room_id], |r| {
+                        r.get::<_, String>(0)
+                    })
+                    .is_ok_and(|key| key == attempt.presented)
+            {
+                limiter.admin_key_succeeded(&attempt.key);
+            }
+            return;
+        }
+        if res.status() != Status::Forbidden {
+            return;
+        }
+        // Only the room's "wrong admin key" error counts; other 403s (protected senders,
+        // read-only rooms, a missing server token) aren't guesses at the key. This runs before
+        // LocalizeErrors, so the body is still in English.
+        let Ok(body) = res.body_mut().to_string().await else {
+            return;
+        };
+        let wrong_key = serde_json::from_str::<serde_json::Value>(&body)
+            .ok()
+            .and_then(|v| v.get("error").and_then(|e| e.as_str()).and_then(crate::i18n::error_key))
+            == Some("error.invalid_admin_key");
+        res.set_sized_body(body.len(), Cursor::new(body));
+        if !wrong_key {
+            return;
+        }
+        let Some(locked_for) =
+            limiter.admin_key_failed(&attempt.key, config.admin_key_max_failures, config.admin_key_lockout_secs)
+        else {
+            return;
+        };
+        let locked_until = chrono::Utc::now() + chrono::Duration::seconds(locked_for as i64);
+        eprintln!(
+            "⚠️ Locked {} out of the admin key for room {} for {locked_for}s after repeated wrong keys",
+            attempt.ip, attempt.room_id
+        );
+        let Some(db) = ScopedDb::of(req) else {
+            return;
+        };
+        crate::db::record_audit(
+            &db.conn(),
+            "admin_key_lockout",
+            &attempt.room_id,
+            None,
+            &serde_json::json!({
+                "ip": attempt.ip,
+                "locked_for_secs": locked_for,
+                "locked_until": locked_until.to_rfc3339(),
+            }),
+        );
+    }
+}
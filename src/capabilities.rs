@@ -0,0 +1,103 @@
+//! What this particular instance has switched on, for clients to feature-detect before calling
+//! endpoints that may be unmounted or refuse them. Served as `features` from `/api/v1/discover`
+//! and advertised in the mDNS TXT record.
+
+use serde::Serialize;
+
+use crate::regex_search::{RegexAccess, RegexSearchConfig};
+use crate::senders::SenderPolicy;
+
+/// How requests are authorized on this instance.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthMode {
+    /// Trust-based: no server token, so reserved sender names and `/api/v1/admin/*` are refused
+    Open,
+    /// `SERVER_TOKEN` is set: it unlocks reserved sender names and the admin API
+    ServerToken,
+}
+
+impl AuthMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AuthMode::Open => "open",
+            AuthMode::ServerToken => "server_token",
+        }
+    }
+}
+
+/// Feature flags for this instance, computed once at startup.
+#[derive(Debug, Clone, Serialize)]
+pub struct Capabilities {
+    pub version: &'static str,
+    pub auth_mode: AuthMode,
+    /// Sender names that need the server token (see `PROTECTED_SENDERS`)
+    pub protected_senders: Vec<String>,
+    pub webhooks: bool,
+    pub incoming_webhooks: bool,
+    pub files: bool,
+    /// Uploads are scanned by ClamAV
+    pub file_scanning: bool,
+    /// The database (messages and file contents) is encrypted with SQLCipher
+    pub encrypted_at_rest: bool,
+    /// Search backend (`fts5`)
+    pub search: &'static str,
+    /// `search?mode=regex` is available (`REGEX_SEARCH`); it may still need the server token
+    pub regex_search: bool,
+    pub push: bool,
+    pub email_gateway: bool,
+    pub namespaces: bool,
+    pub api_docs: bool,
+    pub dev_routes: bool,
+}
+
+impl Capabilities {
+    pub fn detect(sender_policy: &SenderPolicy, regex_search: &RegexSearchConfig, namespaces: bool) -> Self {
+        let env_flag = |name: &str| {
+            std::env::var(name)
+                .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+                .unwrap_or(false)
+        };
+        Capabilities {
+            version: env!("CARGO_PKG_VERSION"),
+            auth_mode: if sender_policy.server_token.is_some() {
+                AuthMode::ServerToken
+            } else {
+                AuthMode::Open
+            },
+            protected_senders: sender_policy.protected.clone(),
+            webhooks: true,
+            incoming_webhooks: true,
+            files: true,
+            file_scanning: std::env::var("CLAMAV_ADDRESS").is_ok_and(|v| !v.trim().is_empty()),
+            encrypted_at_rest: crate::db::encryption_enabled(),
+            search: "fts5",
+            regex_search: match regex_search.access {
+                RegexAccess::Off => false,
+                RegexAccess::Admin => sender_policy.server_token.is_some(),
+                RegexAccess::Open => true,
+            },
+            push: true,
+            email_gateway: env_flag("EMAIL_GATEWAY_ENABLED"),
+            namespaces,
+            api_docs: crate::routes::api_docs_enabled(),
+            dev_routes: crate::seed::enabled(),
+        }
+    }
+
+    /// Key/value pairs for the mDNS TXT record. Booleans are `1`/`0`; keys stay short because
+    /// each TXT string is capped at 255 bytes.
+    pub fn txt_properties(&self) -> Vec<(&'static str, String)> {
+        let flag = |on: bool| if on { "1" } else { "0" }.to_string();
+        vec![
+            ("version", self.version.to_string()),
+            ("auth", self.auth_mode.as_str().to_string()),
+            ("webhooks", flag(self.webhooks)),
+            ("files", flag(self.files)),
+            ("search", self.search.to_string()),
+            ("push", flag(self.push)),
+            ("email", flag(self.email_gateway)),
+            ("namespaces", flag(self.namespaces)),
+        ]
+    }
+}
@@ -1,8 +1,18 @@
-use rusqlite::{Connection, params};
-use std::sync::{Mutex, MutexGuard};
+use rusqlite::{Connection, OpenFlags, OptionalExtension, params};
+use std::collections::VecDeque;
+use std::env;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::Duration;
+
+use crate::models::{Message, SearchIndexReport, SlowQuery};
+use crate::telemetry::{self, Span, SpanContext, SpanKind};
 
 pub struct Db {
     pub conn: Mutex<Connection>,
+    /// Database file, for opening side connections (see [`Db::open_reader`])
+    pub path: String,
 }
 
 impl Db {
@@ -18,6 +28,78 @@ impl Db {
             poisoned.into_inner()
         })
     }
+
+    /// [`Db::conn`] covered by a `db.sqlite` span, a child of `parent` (the request's server span),
+    /// from before the lock is taken until the connection is released. No span when tracing is off.
+    pub fn traced_conn(&self, parent: Option<&SpanContext>, namespace: Option<&str>) -> TracedConn<'_> {
+        let mut span = telemetry::start_span("db.sqlite", SpanKind::Client, parent);
+        let waiting = std::time::Instant::now();
+        let conn = self.conn();
+        if let Some(span) = span.as_mut() {
+            span.set_attribute("db.system", "sqlite");
+            span.set_attribute("db.namespace", namespace.unwrap_or("main"));
+            span.set_attribute("db.lock_wait_ms", waiting.elapsed().as_secs_f64() * 1000.0);
+        }
+        TracedConn { conn, _span: span }
+    }
+
+    /// Open a separate read-only connection, for long reads (streamed exports) that shouldn't
+    /// hold the shared connection's lock. WAL lets it read while the main connection writes.
+    pub fn open_reader(&self) -> rusqlite::Result<Connection> {
+        let conn = open_with_flags(&self.path, OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)?;
+        conn.busy_timeout(Duration::from_secs(5))?;
+        Ok(conn)
+    }
+}
+
+/// A locked connection, traced while held (see [`Db::traced_conn`]). Derefs to [`Connection`].
+pub struct TracedConn<'a> {
+    // Declared first so the lock is released before the span ends
+    conn: MutexGuard<'a, Connection>,
+    _span: Option<Span>,
+}
+
+impl Deref for TracedConn<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        &self.conn
+    }
+}
+
+impl DerefMut for TracedConn<'_> {
+    fn deref_mut(&mut self) -> &mut Connection {
+        &mut self.conn
+    }
+}
+
+/// Max entries kept in the slow-query log (oldest are dropped first).
+const SLOW_QUERY_LOG_CAP: usize = 100;
+
+// SQLite's profile hook is a plain fn pointer, so the log has to live in a static.
+static SLOW_QUERY_THRESHOLD_MS: AtomicU64 = AtomicU64::new(0);
+static SLOW_QUERIES: Mutex<VecDeque<SlowQuery>> = Mutex::new(VecDeque::new());
+
+fn record_slow_query(sql: &str, duration: Duration) {
+    let threshold = SLOW_QUERY_THRESHOLD_MS.load(Ordering::Relaxed);
+    if duration < Duration::from_millis(threshold) {
+        return;
+    }
+    let mut log = SLOW_QUERIES.lock().unwrap_or_else(|e| e.into_inner());
+    if log.len() >= SLOW_QUERY_LOG_CAP {
+        log.pop_front();
+    }
+    log.push_back(SlowQuery {
+        sql: sql.chars().take(1000).collect(),
+        duration_ms: duration.as_secs_f64() * 1000.0,
+        recorded_at: chrono::Utc::now().to_rfc3339(),
+    });
+}
+
+/// Slow queries recorded so far, newest first. Empty unless `DB_SLOW_QUERY_MS` is set.
+pub fn slow_queries() -> Vec<SlowQuery> {
+    let log = SLOW_QUERIES.lock().unwrap_or_else(|e| e.into_inner());
+    log.iter().rev().cloned().collect()
 }
 
 /// Generate a room admin key: `chat_<32 hex chars>`
@@ -30,13 +112,176 @@ pub fn generate_webhook_token() -> String {
     format!("whk_{:032x}", uuid::Uuid::new_v4().as_u128())
 }
 
+/// Passphrase for an encrypted database: `DB_ENCRYPTION_KEY`, or the first line of the file
+/// named by `DB_ENCRYPTION_KEY_FILE` (keeps the key out of the environment). Read once.
+fn encryption_key() -> Result<Option<&'static str>, &'static str> {
+    static KEY: OnceLock<Result<Option<String>, String>> = OnceLock::new();
+    let key = KEY.get_or_init(|| {
+        if let Ok(key) = env::var("DB_ENCRYPTION_KEY") {
+            return Ok(Some(key));
+        }
+        let Ok(path) = env::var("DB_ENCRYPTION_KEY_FILE") else {
+            return Ok(None);
+        };
+        let contents =
+            std::fs::read_to_string(&path).map_err(|e| format!("failed to read DB_ENCRYPTION_KEY_FILE {path}: {e}"))?;
+        Ok(contents.lines().next().map(str::to_string))
+    });
+    match key {
+        Ok(key) => Ok(key.as_deref().filter(|k| !k.is_empty())),
+        Err(e) => Err(e),
+    }
+}
+
+/// Whether databases are opened encrypted (a key is configured).
+pub fn encryption_enabled() -> bool {
+    matches!(encryption_key(), Ok(Some(_)))
+}
+
+fn open_error(msg: String) -> rusqlite::Error {
+    rusqlite::Error::SqliteFailure(rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_NOTADB), Some(msg))
+}
+
+/// Open the database at `path` with default flags. Every connection the server makes goes
+/// through here or [`open_with_flags`], so an encrypted database is unlocked everywhere.
+pub fn open(path: &str) -> rusqlite::Result<Connection> {
+    open_with_flags(path, OpenFlags::default())
+}
+
+/// Open the database at `path`, applying the SQLCipher key when one is configured. With a key
+/// set, a build without SQLCipher (the `sqlcipher` feature) refuses to open rather than
+/// writing plaintext, and a wrong key fails here instead of on the first query.
+pub fn open_with_flags(path: &str, flags: OpenFlags) -> rusqlite::Result<Connection> {
+    let conn = Connection::open_with_flags(path, flags)?;
+    let Some(key) = encryption_key().map_err(|e| open_error(e.to_string()))? else {
+        return Ok(conn);
+    };
+    let cipher: Option<String> = conn.query_row("PRAGMA cipher_version", [], |r| r.get(0)).optional()?;
+    if cipher.is_none() {
+        return Err(open_error(
+            "DB_ENCRYPTION_KEY is set but this build has no SQLCipher support (build with --features sqlcipher)".to_string(),
+        ));
+    }
+    conn.pragma_update(None, "key", key)?;
+    conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |r| r.get::<_, i64>(0))
+        .map_err(|e| open_error(format!("cannot unlock {path}: wrong DB_ENCRYPTION_KEY or not an encrypted database ({e})")))?;
+    Ok(conn)
+}
+
+/// SQLite connection tuning. All read from environment variables with sensible defaults.
+///
+/// Environment variables:
+/// - `DB_JOURNAL_MODE` — DELETE, TRUNCATE, PERSIST, MEMORY, WAL, or OFF (default: WAL)
+/// - `DB_SYNCHRONOUS` — OFF, NORMAL, FULL, or EXTRA (default: NORMAL)
+/// - `DB_CACHE_SIZE` — page cache size; negative values are KiB, positive are pages (default: -16000, ~16MB)
+/// - `DB_MMAP_SIZE` — bytes of the DB file to memory-map, 0 disables (default: 0)
+/// - `DB_BUSY_TIMEOUT_MS` — how long a writer waits on a locked DB before failing (default: 5000)
+/// - `DB_SLOW_QUERY_MS` — record statements slower than this to `/api/v1/diagnostics/slow-queries` (default: unset, profiling off)
+///
+/// Invalid values are ignored with a warning and the default is kept.
+#[derive(Debug, Clone)]
+pub struct DbConfig {
+    pub journal_mode: String,
+    pub synchronous: String,
+    pub cache_size: i64,
+    pub mmap_size: i64,
+    pub busy_timeout_ms: u64,
+    pub slow_query_ms: Option<u64>,
+}
+
+const JOURNAL_MODES: &[&str] = &["DELETE", "TRUNCATE", "PERSIST", "MEMORY", "WAL", "OFF"];
+const SYNCHRONOUS_MODES: &[&str] = &["OFF", "NORMAL", "FULL", "EXTRA"];
+
+impl Default for DbConfig {
+    fn default() -> Self {
+        Self {
+            journal_mode: "WAL".to_string(),
+            synchronous: "NORMAL".to_string(),
+            cache_size: -16000,
+            mmap_size: 0,
+            busy_timeout_ms: 5000,
+            slow_query_ms: None,
+        }
+    }
+}
+
+impl DbConfig {
+    /// Create a new DbConfig from environment variables, with defaults.
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+
+        if let Ok(val) = env::var("DB_JOURNAL_MODE") {
+            let val = val.trim().to_uppercase();
+            if JOURNAL_MODES.contains(&val.as_str()) {
+                config.journal_mode = val;
+            } else {
+                eprintln!("⚠️ Ignoring invalid DB_JOURNAL_MODE={val}, using {}", config.journal_mode);
+            }
+        }
+        if let Ok(val) = env::var("DB_SYNCHRONOUS") {
+            let val = val.trim().to_uppercase();
+            if SYNCHRONOUS_MODES.contains(&val.as_str()) {
+                config.synchronous = val;
+            } else {
+                eprintln!("⚠️ Ignoring invalid DB_SYNCHRONOUS={val}, using {}", config.synchronous);
+            }
+        }
+        if let Ok(val) = env::var("DB_CACHE_SIZE")
+            && let Ok(n) = val.trim().parse::<i64>()
+        {
+            config.cache_size = n;
+        }
+        if let Ok(val) = env::var("DB_MMAP_SIZE")
+            && let Ok(n) = val.trim().parse::<i64>()
+            && n >= 0
+        {
+            config.mmap_size = n;
+        }
+        if let Ok(val) = env::var("DB_BUSY_TIMEOUT_MS")
+            && let Ok(n) = val.trim().parse::<u64>()
+        {
+            config.busy_timeout_ms = n;
+        }
+        if let Ok(val) = env::var("DB_SLOW_QUERY_MS")
+            && let Ok(n) = val.trim().parse::<u64>()
+        {
+            config.slow_query_ms = Some(n);
+        }
+
+        config
+    }
+
+    /// Apply these settings (plus `foreign_keys=ON`) to an open connection.
+    /// Used by the main Db and by background tasks that open their own connections.
+    pub fn apply(&self, conn: &Connection) -> rusqlite::Result<()> {
+        // busy_timeout first so the journal_mode switch itself can wait out a concurrent writer
+        conn.busy_timeout(std::time::Duration::from_millis(self.busy_timeout_ms))?;
+        // Modes are validated against a fixed list and sizes are integers, so formatting is safe
+        conn.execute_batch(&format!(
+            "PRAGMA journal_mode={}; PRAGMA synchronous={}; PRAGMA cache_size={}; PRAGMA mmap_size={}; PRAGMA foreign_keys=ON;",
+            self.journal_mode, self.synchronous, self.cache_size, self.mmap_size
+        ))
+    }
+}
+
 impl Db {
+    /// Open (and migrate) the database using tuning from the environment (see [`DbConfig`]).
     pub fn new(path: &str) -> Self {
-        let conn = Connection::open(path).expect("Failed to open database");
-        conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA foreign_keys=ON;")
-            .expect("Failed to set pragmas");
+        Self::with_config(path, &DbConfig::from_env())
+    }
+
+    pub fn with_config(path: &str, config: &DbConfig) -> Self {
+        let mut conn = open(path).unwrap_or_else(|e| panic!("Failed to open database {path}: {e}"));
+        config.apply(&conn).expect("Failed to set pragmas");
+        // Hot paths use prepare_cached; room for the dynamic filter variants of list queries
+        conn.set_prepared_statement_cache_capacity(64);
+        if let Some(threshold) = config.slow_query_ms {
+            SLOW_QUERY_THRESHOLD_MS.store(threshold, Ordering::Relaxed);
+            conn.profile(Some(record_slow_query as fn(&str, Duration)));
+        }
         let db = Db {
             conn: Mutex::new(conn),
+            path: path.to_string(),
         };
         db.migrate();
         db
@@ -44,328 +289,1018 @@ impl Db {
 
     fn migrate(&self) {
         let conn = self.conn();
-        conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS rooms (
-                id TEXT PRIMARY KEY,
-                name TEXT NOT NULL UNIQUE,
-                description TEXT DEFAULT '',
-                created_by TEXT DEFAULT 'anonymous',
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL
-            );
-
-            CREATE TABLE IF NOT EXISTS messages (
-                id TEXT PRIMARY KEY,
-                room_id TEXT NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
-                sender TEXT NOT NULL,
-                content TEXT NOT NULL,
-                metadata TEXT DEFAULT '{}',
-                created_at TEXT NOT NULL,
-                edited_at TEXT
-            );
-
-            CREATE INDEX IF NOT EXISTS idx_messages_room_created ON messages(room_id, created_at);
-            CREATE INDEX IF NOT EXISTS idx_messages_sender ON messages(sender);
-
-            -- Migration: add edited_at column if it doesn't exist
-            ",
-        )
-        .expect("Failed to run migrations");
+        match crate::migrations::run(&conn) {
+            Ok(applied) => {
+                for m in applied {
+                    println!("🗄️ Applied migration {:04}_{}", m.version, m.name);
+                }
+            }
+            Err(e) => panic!("Database migration failed for {}: {e}", self.path),
+        }
+        // Repair the FTS indexes if they drifted from the messages (e.g. an unclean shutdown).
+        // Runs after migrations so rooms' search tokenizers and the per-tokenizer tables exist.
+        match check_fts_index(&conn, true) {
+            Ok(report) if report.repaired => println!(
+                "🔎 Repaired search index: {} missing, {} outdated, {} orphaned entries{}",
+                report.missing,
+                report.outdated,
+                report.orphaned,
+                if report.corrupt_tables.is_empty() {
+                    String::new()
+                } else {
+                    format!(", rebuilt {}", report.corrupt_tables.join(", "))
+                }
+            ),
+            Ok(_) => {}
+            Err(e) => eprintln!("⚠️ Search index check failed for {}: {e}", self.path),
+        }
+        for name in crate::provision::provision(&conn, &crate::provision::DefaultRooms::from_env()) {
+            println!("🏠 Created default room #{name}");
+        }
+    }
+}
 
-        // Add edited_at column (idempotent — .ok() ignores "duplicate column" errors)
-        conn.execute_batch("ALTER TABLE messages ADD COLUMN edited_at TEXT;")
-            .ok();
+/// The schema as it stood before versioned migrations. Every statement is idempotent and it
+/// runs on each startup ahead of the files in `migrations/`; it is frozen — schema changes go
+/// in a new numbered migration instead (see [`crate::migrations`]).
+pub(crate) fn baseline_schema(conn: &Connection) {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS rooms (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE,
+            description TEXT DEFAULT '',
+            created_by TEXT DEFAULT 'anonymous',
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
 
-        // Add reply_to column for message threading
-        conn.execute_batch("ALTER TABLE messages ADD COLUMN reply_to TEXT;")
-            .ok();
+        CREATE TABLE IF NOT EXISTS messages (
+            id TEXT PRIMARY KEY,
+            room_id TEXT NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+            sender TEXT NOT NULL,
+            content TEXT NOT NULL,
+            metadata TEXT DEFAULT '{}',
+            created_at TEXT NOT NULL,
+            edited_at TEXT
+        );
 
-        // Add admin_key column for room-scoped admin keys
-        conn.execute_batch("ALTER TABLE rooms ADD COLUMN admin_key TEXT;")
-            .ok();
+        CREATE INDEX IF NOT EXISTS idx_messages_room_created ON messages(room_id, created_at);
+        CREATE INDEX IF NOT EXISTS idx_messages_sender ON messages(sender);
 
-        // Add sender_type column for persistent sender type tracking (agent/human)
-        conn.execute_batch("ALTER TABLE messages ADD COLUMN sender_type TEXT;")
-            .ok();
+        -- Migration: add edited_at column if it doesn't exist
+        ",
+    )
+    .expect("Failed to run migrations");
 
-        // Add monotonic seq column for cursor-based pagination
-        conn.execute_batch("ALTER TABLE messages ADD COLUMN seq INTEGER;")
-            .ok();
-        conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_messages_seq ON messages(seq);")
-            .ok();
-        conn.execute_batch(
-            "CREATE INDEX IF NOT EXISTS idx_messages_room_seq ON messages(room_id, seq);",
-        )
+    // Add edited_at column (idempotent — .ok() ignores "duplicate column" errors)
+    conn.execute_batch("ALTER TABLE messages ADD COLUMN edited_at TEXT;")
         .ok();
 
-        // Files table for attachments
-        conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS files (
-                id TEXT PRIMARY KEY,
-                room_id TEXT NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
-                sender TEXT NOT NULL,
-                filename TEXT NOT NULL,
-                content_type TEXT NOT NULL DEFAULT 'application/octet-stream',
-                size INTEGER NOT NULL,
-                data BLOB NOT NULL,
-                created_at TEXT NOT NULL
-            );
-            CREATE INDEX IF NOT EXISTS idx_files_room ON files(room_id);
-            CREATE INDEX IF NOT EXISTS idx_files_sender ON files(sender);",
-        )
-        .expect("Failed to create files table");
+    // Add reply_to column for message threading
+    conn.execute_batch("ALTER TABLE messages ADD COLUMN reply_to TEXT;")
+        .ok();
 
-        // Add pinned_at and pinned_by columns for message pinning
-        conn.execute_batch("ALTER TABLE messages ADD COLUMN pinned_at TEXT;")
-            .ok();
-        conn.execute_batch("ALTER TABLE messages ADD COLUMN pinned_by TEXT;")
-            .ok();
+    // Add admin_key column for room-scoped admin keys
+    conn.execute_batch("ALTER TABLE rooms ADD COLUMN admin_key TEXT;")
+        .ok();
 
-        // Message reactions table
-        conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS message_reactions (
-                id TEXT PRIMARY KEY,
-                message_id TEXT NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
-                sender TEXT NOT NULL,
-                emoji TEXT NOT NULL,
-                created_at TEXT NOT NULL,
-                UNIQUE(message_id, sender, emoji)
-            );
-            CREATE INDEX IF NOT EXISTS idx_reactions_message ON message_reactions(message_id);
-            CREATE INDEX IF NOT EXISTS idx_reactions_sender ON message_reactions(sender);",
-        )
-        .expect("Failed to create message_reactions table");
-
-        // Read positions table for server-side unread tracking
-        conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS read_positions (
-                room_id TEXT NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
-                sender TEXT NOT NULL,
-                last_read_seq INTEGER NOT NULL,
-                updated_at TEXT NOT NULL,
-                PRIMARY KEY (room_id, sender)
-            );
-            CREATE INDEX IF NOT EXISTS idx_read_positions_sender ON read_positions(sender);",
-        )
-        .expect("Failed to create read_positions table");
-
-        // User profiles table
-        conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS profiles (
-                sender TEXT PRIMARY KEY,
-                display_name TEXT,
-                sender_type TEXT DEFAULT 'agent',
-                avatar_url TEXT,
-                bio TEXT,
-                status_text TEXT,
-                metadata TEXT DEFAULT '{}',
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL
-            );",
-        )
-        .expect("Failed to create profiles table");
-
-        // Webhooks table for event notifications
-        conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS webhooks (
-                id TEXT PRIMARY KEY,
-                room_id TEXT NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
-                url TEXT NOT NULL,
-                events TEXT NOT NULL DEFAULT '*',
-                secret TEXT,
-                created_by TEXT NOT NULL,
-                created_at TEXT NOT NULL,
-                active INTEGER NOT NULL DEFAULT 1
-            );
-            CREATE INDEX IF NOT EXISTS idx_webhooks_room ON webhooks(room_id);
-            CREATE INDEX IF NOT EXISTS idx_webhooks_active ON webhooks(active);",
-        )
-        .expect("Failed to create webhooks table");
-
-        // Webhook delivery audit log (retry tracking)
-        conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS webhook_deliveries (
-                id TEXT PRIMARY KEY,
-                delivery_group TEXT NOT NULL,
-                webhook_id TEXT NOT NULL REFERENCES webhooks(id) ON DELETE CASCADE,
-                event TEXT NOT NULL,
-                url TEXT NOT NULL,
-                attempt INTEGER NOT NULL,
-                status TEXT NOT NULL,
-                status_code INTEGER,
-                error_message TEXT,
-                response_time_ms INTEGER,
-                created_at TEXT NOT NULL DEFAULT (datetime('now'))
-            );
-            CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_webhook ON webhook_deliveries(webhook_id, created_at DESC);
-            CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_group ON webhook_deliveries(delivery_group);",
-        )
-        .expect("Failed to create webhook_deliveries table");
+    // Add sender_type column for persistent sender type tracking (agent/human)
+    conn.execute_batch("ALTER TABLE messages ADD COLUMN sender_type TEXT;")
+        .ok();
 
-        // Backfill seq for existing messages that don't have one
-        let needs_seq_backfill: i64 = conn
-            .query_row("SELECT COUNT(*) FROM messages WHERE seq IS NULL", [], |r| {
-                r.get(0)
-            })
-            .unwrap_or(0);
-        if needs_seq_backfill > 0 {
-            let mut stmt = conn
-                .prepare(
-                    "SELECT id FROM messages WHERE seq IS NULL ORDER BY created_at ASC, id ASC",
-                )
-                .unwrap();
-            let ids: Vec<String> = stmt
-                .query_map([], |row| row.get(0))
-                .unwrap()
-                .filter_map(|r| r.ok())
-                .collect();
-            drop(stmt);
-            let max_seq: i64 = conn
-                .query_row("SELECT COALESCE(MAX(seq), 0) FROM messages", [], |r| {
-                    r.get(0)
-                })
-                .unwrap_or(0);
-            for (i, id) in ids.iter().enumerate() {
-                conn.execute(
-                    "UPDATE messages SET seq = ?1 WHERE id = ?2",
-                    params![max_seq + (i as i64) + 1, &id],
-                )
-                .ok();
-            }
-        }
+    // Add monotonic seq column for cursor-based pagination
+    conn.execute_batch("ALTER TABLE messages ADD COLUMN seq INTEGER;")
+        .ok();
+    conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_messages_seq ON messages(seq);")
+        .ok();
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_messages_room_seq ON messages(room_id, seq);",
+    )
+    .ok();
+    // Covering index for list_rooms' per-room aggregates (count, last seq, last activity)
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_messages_room_stats ON messages(room_id, seq, created_at);",
+    )
+    .ok();
 
-        // Add room_type column for DMs (default 'room' for existing rooms)
-        conn.execute_batch("ALTER TABLE rooms ADD COLUMN room_type TEXT DEFAULT 'room';")
-            .ok();
+    // Files table for attachments
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS files (
+            id TEXT PRIMARY KEY,
+            room_id TEXT NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+            sender TEXT NOT NULL,
+            filename TEXT NOT NULL,
+            content_type TEXT NOT NULL DEFAULT 'application/octet-stream',
+            size INTEGER NOT NULL,
+            data BLOB NOT NULL,
+            created_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_files_room ON files(room_id);
+        CREATE INDEX IF NOT EXISTS idx_files_sender ON files(sender);",
+    )
+    .expect("Failed to create files table");
 
-        // Add archived_at column for room archiving
-        conn.execute_batch("ALTER TABLE rooms ADD COLUMN archived_at TEXT;")
-            .ok();
+    // Add pinned_at and pinned_by columns for message pinning
+    conn.execute_batch("ALTER TABLE messages ADD COLUMN pinned_at TEXT;")
+        .ok();
+    conn.execute_batch("ALTER TABLE messages ADD COLUMN pinned_by TEXT;")
+        .ok();
 
-        // Incoming webhooks table (external systems post messages into rooms via token URL)
-        conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS incoming_webhooks (
-                id TEXT PRIMARY KEY,
-                room_id TEXT NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
-                name TEXT NOT NULL,
-                token TEXT NOT NULL UNIQUE,
-                created_by TEXT NOT NULL,
-                created_at TEXT NOT NULL,
-                active INTEGER NOT NULL DEFAULT 1
-            );
-            CREATE INDEX IF NOT EXISTS idx_incoming_webhooks_token ON incoming_webhooks(token);
-            CREATE INDEX IF NOT EXISTS idx_incoming_webhooks_room ON incoming_webhooks(room_id);",
-        )
-        .expect("Failed to create incoming_webhooks table");
-
-        // Bookmarks table for room favorites
-        conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS bookmarks (
-                room_id TEXT NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
-                sender TEXT NOT NULL,
-                created_at TEXT NOT NULL,
-                PRIMARY KEY (room_id, sender)
-            );
-            CREATE INDEX IF NOT EXISTS idx_bookmarks_sender ON bookmarks(sender);",
-        )
-        .expect("Failed to create bookmarks table");
-
-        // Message edit history table
-        conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS message_edits (
-                id TEXT PRIMARY KEY,
-                message_id TEXT NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
-                previous_content TEXT NOT NULL,
-                edited_at TEXT NOT NULL,
-                editor TEXT NOT NULL
-            );
-            CREATE INDEX IF NOT EXISTS idx_message_edits_message_id ON message_edits(message_id, edited_at);",
-        )
-        .expect("Failed to create message_edits table");
+    // Message reactions table
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS message_reactions (
+            id TEXT PRIMARY KEY,
+            message_id TEXT NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+            sender TEXT NOT NULL,
+            emoji TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            UNIQUE(message_id, sender, emoji)
+        );
+        CREATE INDEX IF NOT EXISTS idx_reactions_message ON message_reactions(message_id);
+        CREATE INDEX IF NOT EXISTS idx_reactions_sender ON message_reactions(sender);",
+    )
+    .expect("Failed to create message_reactions table");
 
-        // Add retention columns for room-level message pruning
-        conn.execute_batch("ALTER TABLE rooms ADD COLUMN max_messages INTEGER;")
-            .ok();
-        conn.execute_batch("ALTER TABLE rooms ADD COLUMN max_message_age_hours INTEGER;")
-            .ok();
+    // Read positions table for server-side unread tracking
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS read_positions (
+            room_id TEXT NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+            sender TEXT NOT NULL,
+            last_read_seq INTEGER NOT NULL,
+            updated_at TEXT NOT NULL,
+            PRIMARY KEY (room_id, sender)
+        );
+        CREATE INDEX IF NOT EXISTS idx_read_positions_sender ON read_positions(sender);",
+    )
+    .expect("Failed to create read_positions table");
+
+    // Per-thread read positions (independent of the room-level position)
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS thread_read_positions (
+            room_id TEXT NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+            root_id TEXT NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+            sender TEXT NOT NULL,
+            last_read_seq INTEGER NOT NULL,
+            updated_at TEXT NOT NULL,
+            PRIMARY KEY (root_id, sender)
+        );
+        CREATE INDEX IF NOT EXISTS idx_thread_read_positions_sender ON thread_read_positions(sender);",
+    )
+    .expect("Failed to create thread_read_positions table");
 
-        // Backfill admin_key for existing rooms that don't have one
+    // User profiles table
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS profiles (
+            sender TEXT PRIMARY KEY,
+            display_name TEXT,
+            sender_type TEXT DEFAULT 'agent',
+            avatar_url TEXT,
+            bio TEXT,
+            status_text TEXT,
+            metadata TEXT DEFAULT '{}',
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );",
+    )
+    .expect("Failed to create profiles table");
+
+    // Webhooks table for event notifications
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS webhooks (
+            id TEXT PRIMARY KEY,
+            room_id TEXT NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+            url TEXT NOT NULL,
+            events TEXT NOT NULL DEFAULT '*',
+            secret TEXT,
+            created_by TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            active INTEGER NOT NULL DEFAULT 1
+        );
+        CREATE INDEX IF NOT EXISTS idx_webhooks_room ON webhooks(room_id);
+        CREATE INDEX IF NOT EXISTS idx_webhooks_active ON webhooks(active);",
+    )
+    .expect("Failed to create webhooks table");
+
+    // Webhook delivery audit log (retry tracking)
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS webhook_deliveries (
+            id TEXT PRIMARY KEY,
+            delivery_group TEXT NOT NULL,
+            webhook_id TEXT NOT NULL REFERENCES webhooks(id) ON DELETE CASCADE,
+            event TEXT NOT NULL,
+            url TEXT NOT NULL,
+            attempt INTEGER NOT NULL,
+            status TEXT NOT NULL,
+            status_code INTEGER,
+            error_message TEXT,
+            response_time_ms INTEGER,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_webhook ON webhook_deliveries(webhook_id, created_at DESC);
+        CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_group ON webhook_deliveries(delivery_group);",
+    )
+    .expect("Failed to create webhook_deliveries table");
+
+    // Backfill seq for existing messages that don't have one
+    let needs_seq_backfill: i64 = conn
+        .query_row("SELECT COUNT(*) FROM messages WHERE seq IS NULL", [], |r| {
+            r.get(0)
+        })
+        .unwrap_or(0);
+    if needs_seq_backfill > 0 {
         let mut stmt = conn
-            .prepare("SELECT id FROM rooms WHERE admin_key IS NULL")
+            .prepare(
+                "SELECT id FROM messages WHERE seq IS NULL ORDER BY created_at ASC, id ASC",
+            )
             .unwrap();
-        let room_ids: Vec<String> = stmt
+        let ids: Vec<String> = stmt
             .query_map([], |row| row.get(0))
             .unwrap()
             .filter_map(|r| r.ok())
             .collect();
         drop(stmt);
-        for room_id in room_ids {
-            let key = generate_admin_key();
+        let max_seq: i64 = conn
+            .query_row("SELECT COALESCE(MAX(seq), 0) FROM messages", [], |r| {
+                r.get(0)
+            })
+            .unwrap_or(0);
+        for (i, id) in ids.iter().enumerate() {
             conn.execute(
-                "UPDATE rooms SET admin_key = ?1 WHERE id = ?2",
-                params![&key, &room_id],
+                "UPDATE messages SET seq = ?1 WHERE id = ?2",
+                params![max_seq + (i as i64) + 1, &id],
             )
             .ok();
         }
+    }
+
+    // Add room_type column for DMs (default 'room' for existing rooms)
+    conn.execute_batch("ALTER TABLE rooms ADD COLUMN room_type TEXT DEFAULT 'room';")
+        .ok();
+
+    // Add archived_at column for room archiving
+    conn.execute_batch("ALTER TABLE rooms ADD COLUMN archived_at TEXT;")
+        .ok();
+
+    // Incoming webhooks table (external systems post messages into rooms via token URL)
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS incoming_webhooks (
+            id TEXT PRIMARY KEY,
+            room_id TEXT NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+            name TEXT NOT NULL,
+            token TEXT NOT NULL UNIQUE,
+            created_by TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            active INTEGER NOT NULL DEFAULT 1
+        );
+        CREATE INDEX IF NOT EXISTS idx_incoming_webhooks_token ON incoming_webhooks(token);
+        CREATE INDEX IF NOT EXISTS idx_incoming_webhooks_room ON incoming_webhooks(room_id);",
+    )
+    .expect("Failed to create incoming_webhooks table");
+
+    // Bookmarks table for room favorites
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS bookmarks (
+            room_id TEXT NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+            sender TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            PRIMARY KEY (room_id, sender)
+        );
+        CREATE INDEX IF NOT EXISTS idx_bookmarks_sender ON bookmarks(sender);",
+    )
+    .expect("Failed to create bookmarks table");
+
+    // Message edit history table
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS message_edits (
+            id TEXT PRIMARY KEY,
+            message_id TEXT NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+            previous_content TEXT NOT NULL,
+            edited_at TEXT NOT NULL,
+            editor TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_message_edits_message_id ON message_edits(message_id, edited_at);",
+    )
+    .expect("Failed to create message_edits table");
+
+    // In-progress streamed messages (row removed on finalize)
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS message_streams (
+            message_id TEXT PRIMARY KEY REFERENCES messages(id) ON DELETE CASCADE,
+            room_id TEXT NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+            sender TEXT NOT NULL,
+            started_at TEXT NOT NULL,
+            chunk_count INTEGER NOT NULL DEFAULT 0
+        );",
+    )
+    .expect("Failed to create message_streams table");
+
+    // Add retention columns for room-level message pruning
+    conn.execute_batch("ALTER TABLE rooms ADD COLUMN max_messages INTEGER;")
+        .ok();
+    conn.execute_batch("ALTER TABLE rooms ADD COLUMN max_message_age_hours INTEGER;")
+        .ok();
 
-        // FTS5 full-text search index for messages
-        conn.execute_batch(
-            "CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5(
-                message_id UNINDEXED,
-                sender,
-                content,
-                tokenize='porter unicode61'
-            );",
+    // Backfill admin_key for existing rooms that don't have one
+    let mut stmt = conn
+        .prepare("SELECT id FROM rooms WHERE admin_key IS NULL")
+        .unwrap();
+    let room_ids: Vec<String> = stmt
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .filter_map(|r| r.ok())
+        .collect();
+    drop(stmt);
+    for room_id in room_ids {
+        let key = generate_admin_key();
+        conn.execute(
+            "UPDATE rooms SET admin_key = ?1 WHERE id = ?2",
+            params![&key, &room_id],
         )
-        .expect("Failed to create FTS5 table");
+        .ok();
+    }
+
+    // FTS5 full-text search index for messages
+    conn.execute_batch(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5(
+            message_id UNINDEXED,
+            sender,
+            content,
+            tokenize='porter unicode61'
+        );",
+    )
+    .expect("Failed to create FTS5 table");
 
-        // Rebuild FTS index from existing messages (idempotent)
-        rebuild_fts_index(&conn);
+    // Parsed @mentions, one row per (message, target). Targets are stored lowercased.
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS mentions (
+            message_id TEXT NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+            target TEXT NOT NULL,
+            PRIMARY KEY (message_id, target)
+        );
+        CREATE INDEX IF NOT EXISTS idx_mentions_target ON mentions(target);",
+    )
+    .expect("Failed to create mentions table");
 
-        // Seed #general room if it doesn't exist
-        let count: i64 = conn
-            .query_row(
-                "SELECT COUNT(*) FROM rooms WHERE name = 'general'",
-                [],
-                |r| r.get(0),
-            )
-            .unwrap_or(0);
-        if count == 0 {
-            let now = chrono::Utc::now().to_rfc3339();
-            let admin_key = generate_admin_key();
+    // Filled from existing messages once, by migration 0026
+
+    // Administrative actions (merges, etc.), newest looked up per room
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS audit_log (
+            id TEXT PRIMARY KEY,
+            action TEXT NOT NULL,
+            room_id TEXT NOT NULL,
+            actor TEXT,
+            details TEXT NOT NULL DEFAULT '{}',
+            created_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_audit_log_room ON audit_log(room_id, created_at);",
+    )
+    .expect("Failed to create audit_log table");
+
+    // Where a merged-away room now lives, so old room ids keep resolving
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS room_redirects (
+            room_id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            target_room_id TEXT NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+            reason TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_room_redirects_target ON room_redirects(target_room_id);",
+    )
+    .expect("Failed to create room_redirects table");
+
+    // Former names of live rooms (renames and merges). A live room's current name always wins.
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS room_name_aliases (
+            name TEXT PRIMARY KEY,
+            room_id TEXT NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+            created_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_room_name_aliases_room ON room_name_aliases(room_id);",
+    )
+    .expect("Failed to create room_name_aliases table");
+
+    // Per-room welcome template, and who has already been welcomed
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS room_welcomes (
+            room_id TEXT PRIMARY KEY REFERENCES rooms(id) ON DELETE CASCADE,
+            template TEXT NOT NULL,
+            delivery TEXT NOT NULL DEFAULT 'dm',
+            from_sender TEXT NOT NULL DEFAULT 'system',
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS room_welcome_deliveries (
+            room_id TEXT NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+            sender TEXT NOT NULL,
+            delivered_at TEXT NOT NULL,
+            PRIMARY KEY (room_id, sender)
+        );",
+    )
+    .expect("Failed to create room_welcomes tables");
+
+    // Per-room upload restrictions (comma-separated lists)
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS room_upload_policies (
+            room_id TEXT PRIMARY KEY REFERENCES rooms(id) ON DELETE CASCADE,
+            allowed_types TEXT NOT NULL DEFAULT '',
+            blocked_types TEXT NOT NULL DEFAULT '',
+            allowed_extensions TEXT NOT NULL DEFAULT '',
+            blocked_extensions TEXT NOT NULL DEFAULT '',
+            verify_content_type INTEGER NOT NULL DEFAULT 0,
+            updated_at TEXT NOT NULL
+        );",
+    )
+    .expect("Failed to create room_upload_policies table");
+
+    // Message kind: 'message' for posts, 'system' for server-written lifecycle notes
+    conn.execute_batch("ALTER TABLE messages ADD COLUMN kind TEXT NOT NULL DEFAULT 'message';")
+        .ok();
+    conn.execute_batch(
+        "UPDATE messages SET kind = 'system'
+         WHERE kind = 'message' AND sender = 'system' AND sender_type = 'system'
+           AND json_extract(metadata, '$.moved_to') IS NOT NULL;",
+    )
+    .ok();
+
+    // Content-addressed file storage: files rows keep per-room metadata and point at a shared
+    // blob by SHA-256. Rows from before this migration carry their bytes inline until hashed.
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS file_blobs (
+            sha256 TEXT PRIMARY KEY,
+            data BLOB NOT NULL,
+            size INTEGER NOT NULL,
+            created_at TEXT NOT NULL
+        );",
+    )
+    .expect("Failed to create file_blobs table");
+    conn.execute_batch("ALTER TABLE files ADD COLUMN sha256 TEXT;").ok();
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_files_sha256 ON files(sha256);
+         CREATE TRIGGER IF NOT EXISTS files_release_blob AFTER DELETE ON files
+         WHEN OLD.sha256 IS NOT NULL AND NOT EXISTS (SELECT 1 FROM files WHERE sha256 = OLD.sha256)
+         BEGIN
+             DELETE FROM file_blobs WHERE sha256 = OLD.sha256;
+         END;",
+    )
+    .expect("Failed to create file blob trigger");
+    migrate_inline_files(conn);
+
+    // File expiry: per-upload `expires_in` or the room's default TTL, enforced by the retention task
+    conn.execute_batch("ALTER TABLE files ADD COLUMN expires_at TEXT;").ok();
+    conn.execute_batch("ALTER TABLE rooms ADD COLUMN file_ttl_secs INTEGER;").ok();
+    conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_files_expires ON files(expires_at) WHERE expires_at IS NOT NULL;")
+        .ok();
+
+    // Room theming: icon (emoji or image file id) and accent color
+    conn.execute_batch("ALTER TABLE rooms ADD COLUMN icon TEXT;").ok();
+    conn.execute_batch("ALTER TABLE rooms ADD COLUMN color TEXT;").ok();
+
+    // Retention notice: rooms with a notice period announce purges (`retention_pending`) before running them
+    conn.execute_batch("ALTER TABLE rooms ADD COLUMN retention_notice_secs INTEGER;").ok();
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS retention_notices (
+            room_id TEXT PRIMARY KEY REFERENCES rooms(id) ON DELETE CASCADE,
+            pending_count INTEGER NOT NULL,
+            cutoff_seq INTEGER NOT NULL,
+            cutoff_at TEXT NOT NULL,
+            notified_at TEXT NOT NULL,
+            purge_after TEXT NOT NULL,
+            postponed INTEGER NOT NULL DEFAULT 0
+        );",
+    )
+    .expect("Failed to create retention_notices table");
+
+    // Scheduled room snapshots (JSONL checkpoints in the file store or SNAPSHOT_DIR)
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS room_snapshot_schedules (
+            room_id TEXT PRIMARY KEY REFERENCES rooms(id) ON DELETE CASCADE,
+            cron TEXT NOT NULL,
+            destination TEXT NOT NULL DEFAULT 'files',
+            keep INTEGER NOT NULL DEFAULT 7,
+            last_run_at TEXT,
+            next_run_at TEXT,
+            created_at TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS room_snapshots (
+            id TEXT PRIMARY KEY,
+            room_id TEXT NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+            taken_at TEXT NOT NULL,
+            trigger TEXT NOT NULL,
+            message_count INTEGER NOT NULL,
+            first_seq INTEGER,
+            last_seq INTEGER,
+            size INTEGER NOT NULL,
+            sha256 TEXT NOT NULL,
+            file_id TEXT,
+            path TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_room_snapshots_room ON room_snapshots(room_id, taken_at);",
+    )
+    .expect("Failed to create snapshot tables");
+
+    // Bot command registry, surfaced by the room help document and llms.txt
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS room_commands (
+            room_id TEXT NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+            sender TEXT NOT NULL,
+            name TEXT NOT NULL,
+            description TEXT NOT NULL,
+            usage TEXT,
+            updated_at TEXT NOT NULL,
+            PRIMARY KEY (room_id, sender, name)
+        );",
+    )
+    .expect("Failed to create room_commands table");
+
+    // Web Push: browser subscriptions per sender, and the generated VAPID key (unless VAPID_PRIVATE_KEY is set)
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS push_subscriptions (
+            id TEXT PRIMARY KEY,
+            sender TEXT NOT NULL,
+            endpoint TEXT NOT NULL UNIQUE,
+            p256dh TEXT NOT NULL,
+            auth TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_push_subscriptions_sender ON push_subscriptions(sender COLLATE NOCASE);
+        CREATE TABLE IF NOT EXISTS vapid_keys (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            private_key TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );",
+    )
+    .expect("Failed to create push tables");
+
+    // Merge reactions stored as shortcodes or selector variants into their canonical emoji
+    crate::emoji::normalize_stored_reactions(conn);
+
+    // Preferred locale for server-generated text (system messages, errors)
+    conn.execute_batch("ALTER TABLE profiles ADD COLUMN locale TEXT;")
+        .ok();
+
+    // Moderation: flags raised against messages. The message's sender and content are copied
+    // so the queue still makes sense after the message is deleted.
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS message_flags (
+            id TEXT PRIMARY KEY,
+            room_id TEXT NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+            message_id TEXT NOT NULL,
+            message_sender TEXT NOT NULL,
+            message_content TEXT NOT NULL,
+            reporter TEXT NOT NULL,
+            reason TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'open',
+            created_at TEXT NOT NULL,
+            resolved_at TEXT,
+            resolved_by TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_message_flags_room ON message_flags(room_id, status, created_at);
+        CREATE INDEX IF NOT EXISTS idx_message_flags_message ON message_flags(message_id);",
+    )
+    .expect("Failed to create message_flags table");
+
+    // Webhook health: delivery streaks for the circuit breaker
+    conn.execute_batch("ALTER TABLE webhooks ADD COLUMN last_success_at TEXT;")
+        .ok();
+    conn.execute_batch("ALTER TABLE webhooks ADD COLUMN last_failure_at TEXT;")
+        .ok();
+    conn.execute_batch("ALTER TABLE webhooks ADD COLUMN failure_streak INTEGER NOT NULL DEFAULT 0;")
+        .ok();
+    conn.execute_batch("ALTER TABLE webhooks ADD COLUMN failing_since TEXT;")
+        .ok();
+    conn.execute_batch("ALTER TABLE webhooks ADD COLUMN circuit_opened_at TEXT;")
+        .ok();
+
+    // Incoming webhook limits (NULL = server default) and usage counters; the daily
+    // counters reset when usage_day (UTC date) rolls over
+    conn.execute_batch("ALTER TABLE incoming_webhooks ADD COLUMN rate_limit INTEGER;")
+        .ok();
+    conn.execute_batch("ALTER TABLE incoming_webhooks ADD COLUMN daily_quota INTEGER;")
+        .ok();
+    conn.execute_batch("ALTER TABLE incoming_webhooks ADD COLUMN usage_day TEXT;")
+        .ok();
+    conn.execute_batch("ALTER TABLE incoming_webhooks ADD COLUMN messages_today INTEGER NOT NULL DEFAULT 0;")
+        .ok();
+    conn.execute_batch("ALTER TABLE incoming_webhooks ADD COLUMN rejected_today INTEGER NOT NULL DEFAULT 0;")
+        .ok();
+    conn.execute_batch("ALTER TABLE incoming_webhooks ADD COLUMN messages_total INTEGER NOT NULL DEFAULT 0;")
+        .ok();
+    conn.execute_batch("ALTER TABLE incoming_webhooks ADD COLUMN last_used_at TEXT;")
+        .ok();
+
+    // Sender aliases: other names a profile has posted under. alias_key is the lowercased
+    // alias, so each name (in any casing) belongs to at most one canonical sender.
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS sender_aliases (
+            alias_key TEXT PRIMARY KEY,
+            alias TEXT NOT NULL,
+            sender TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_sender_aliases_sender ON sender_aliases(sender);",
+    )
+    .expect("Failed to create sender_aliases table");
+
+    // Edits made with PATCH keep the patch that produced them
+    conn.execute_batch("ALTER TABLE message_edits ADD COLUMN patch_format TEXT;")
+        .ok();
+    conn.execute_batch("ALTER TABLE message_edits ADD COLUMN patch TEXT;")
+        .ok();
+}
+
+/// Where a room id that no longer exists now lives (after a merge), if anywhere.
+pub fn resolve_room_redirect(conn: &Connection, room_id: &str) -> Option<String> {
+    conn.prepare_cached("SELECT target_room_id FROM room_redirects WHERE room_id = ?1")
+        .and_then(|mut s| s.query_row(params![room_id], |r| r.get(0)))
+        .ok()
+}
+
+/// The live room a former name now refers to, if any.
+pub fn resolve_room_alias(conn: &Connection, name: &str) -> Option<String> {
+    conn.prepare_cached("SELECT room_id FROM room_name_aliases WHERE name = ?1")
+        .and_then(|mut s| s.query_row(params![name], |r| r.get(0)))
+        .ok()
+}
+
+/// The canonical sender a name refers to: the profile that declared it as an alias
+/// (case-insensitive), or the name itself.
+pub fn resolve_sender(conn: &Connection, name: &str) -> String {
+    conn.prepare_cached("SELECT sender FROM sender_aliases WHERE alias_key = ?1")
+        .and_then(|mut s| s.query_row(params![name.to_lowercase()], |r| r.get(0)))
+        .unwrap_or_else(|_| name.to_string())
+}
+
+/// Aliases declared by a profile, in the casing they were given.
+pub fn sender_aliases(conn: &Connection, sender: &str) -> Vec<String> {
+    conn.prepare_cached("SELECT alias FROM sender_aliases WHERE sender = ?1 ORDER BY alias_key")
+        .and_then(|mut s| {
+            s.query_map(params![sender], |r| r.get(0))
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .unwrap_or_default()
+}
+
+/// SQL condition matching `column` against canonical sender `?idx` or any of its aliases.
+/// Bind the result of [`resolve_sender`] to `?idx`.
+pub fn sender_identity_sql(column: &str, idx: usize) -> String {
+    format!("({column} = ?{idx} OR LOWER({column}) IN (SELECT alias_key FROM sender_aliases WHERE sender = ?{idx}))")
+}
+
+/// Append an entry to the audit log.
+pub fn record_audit(conn: &Connection, action: &str, room_id: &str, actor: Option<&str>, details: &serde_json::Value) {
+    conn.execute(
+        "INSERT INTO audit_log (id, action, room_id, actor, details, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            uuid::Uuid::new_v4().to_string(),
+            action,
+            room_id,
+            actor,
+            details.to_string(),
+            chrono::Utc::now().to_rfc3339()
+        ],
+    )
+    .ok();
+}
+
+/// Write a `kind = 'system'` message into a room describing a lifecycle event (rename, pin,
+/// join, retention purge). `details` is merged into the metadata alongside `event`.
+/// Returns the stored message so callers can publish it; None if the insert failed.
+pub fn insert_system_message(
+    conn: &Connection,
+    room_id: &str,
+    event: &str,
+    content: &str,
+    details: serde_json::Value,
+) -> Option<Message> {
+    let id = uuid::Uuid::new_v4().to_string();
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut metadata = serde_json::json!({"event": event});
+    if let serde_json::Value::Object(extra) = details {
+        for (k, v) in extra {
+            metadata[k] = v;
+        }
+    }
+    let seq: i64 = conn
+        .prepare_cached("SELECT COALESCE(MAX(seq), 0) + 1 FROM messages")
+        .and_then(|mut s| s.query_row([], |r| r.get(0)))
+        .ok()?;
+    conn.execute(
+        "INSERT INTO messages (id, room_id, sender, content, metadata, created_at, sender_type, seq, kind) \
+         VALUES (?1, ?2, 'system', ?3, ?4, ?5, 'system', ?6, 'system')",
+        params![&id, room_id, content, metadata.to_string(), &now, seq],
+    )
+    .ok()?;
+    upsert_fts(conn, &id);
+    Some(Message {
+        id,
+        room_id: room_id.to_string(),
+        sender: "system".to_string(),
+        content: content.to_string(),
+        metadata,
+        created_at: now,
+        edited_at: None,
+        reply_to: None,
+        sender_type: Some("system".to_string()),
+        seq,
+        pinned_at: None,
+        pinned_by: None,
+        edit_count: 0,
+        kind: "system".to_string(),
+        local_time: None,
+    })
+}
+
+/// [`insert_system_message`] with the content rendered from the i18n catalog in the server's
+/// default locale. Readers get it re-rendered in their own locale (see `i18n::localize_message`).
+pub fn insert_localized_system_message(
+    conn: &Connection,
+    room_id: &str,
+    event: &str,
+    details: serde_json::Value,
+) -> Option<Message> {
+    let content = crate::i18n::system_text(crate::i18n::default_locale(), event, &details)?;
+    insert_system_message(conn, room_id, event, &content, details)
+}
+
+/// Store file bytes in `file_blobs` keyed by their SHA-256 (a no-op if the same bytes are already
+/// stored) and return the hex digest for the `files.sha256` column.
+pub fn store_file_blob(conn: &Connection, data: &[u8]) -> rusqlite::Result<String> {
+    use sha2::{Digest, Sha256};
+    let sha256 = hex::encode(Sha256::digest(data));
+    conn.execute(
+        "INSERT OR IGNORE INTO file_blobs (sha256, data, size, created_at) VALUES (?1, ?2, ?3, ?4)",
+        params![&sha256, data, data.len() as i64, chrono::Utc::now().to_rfc3339()],
+    )?;
+    Ok(sha256)
+}
+
+/// Move bytes of files uploaded before deduplication into `file_blobs`.
+fn migrate_inline_files(conn: &Connection) {
+    let ids: Vec<String> = conn
+        .prepare("SELECT id FROM files WHERE sha256 IS NULL")
+        .and_then(|mut s| {
+            s.query_map([], |r| r.get::<_, String>(0))
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .unwrap_or_default();
+    for id in ids {
+        let Ok(data) = conn.query_row("SELECT data FROM files WHERE id = ?1", params![&id], |r| {
+            r.get::<_, Vec<u8>>(0)
+        }) else {
+            continue;
+        };
+        if let Ok(sha256) = store_file_blob(conn, &data) {
             conn.execute(
-                "INSERT INTO rooms (id, name, description, created_by, created_at, updated_at, admin_key) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-                params![uuid::Uuid::new_v4().to_string(), "general", "Default chat room", "system", &now, &now, &admin_key],
+                "UPDATE files SET sha256 = ?1, data = x'' WHERE id = ?2",
+                params![&sha256, &id],
             )
             .ok();
         }
     }
 }
 
-/// Rebuild the FTS5 index from all messages. Called on startup.
-pub fn rebuild_fts_index(conn: &Connection) {
-    conn.execute("DELETE FROM messages_fts", []).ok();
-    conn.execute_batch(
-        "INSERT INTO messages_fts (message_id, sender, content)
-         SELECT id, sender, content FROM messages;",
-    )
-    .ok();
+/// FTS5 index tables by tokenizer. Each message is indexed in exactly one of them, the one
+/// matching its room's `search_tokenizer`; search queries all three.
+pub const FTS_TABLES: [(&str, &str); 3] = [
+    ("porter", "messages_fts"),
+    ("unicode61", "messages_fts_unicode61"),
+    ("trigram", "messages_fts_trigram"),
+];
+
+/// The FTS5 table for a tokenizer name (unknown names use the default porter index).
+pub fn fts_table(tokenizer: &str) -> &'static str {
+    FTS_TABLES
+        .iter()
+        .find(|(t, _)| *t == tokenizer)
+        .map(|(_, table)| *table)
+        .unwrap_or("messages_fts")
+}
+
+/// Search tokenizer for a room language: stemming for English, plain unicode word-splitting
+/// (diacritics folded) for other space-delimited languages, and trigrams for languages
+/// written without spaces between words. Returns None for a malformed language code.
+pub fn search_tokenizer_for_language(language: &str) -> Option<&'static str> {
+    let mut parts = language.split(['-', '_']);
+    let primary = parts.next().unwrap_or_default().to_ascii_lowercase();
+    let valid = (2..=3).contains(&primary.len())
+        && primary.chars().all(|c| c.is_ascii_alphabetic())
+        && parts.all(|p| (1..=8).contains(&p.len()) && p.chars().all(|c| c.is_ascii_alphanumeric()));
+    if !valid {
+        return None;
+    }
+    Some(match primary.as_str() {
+        "en" => "porter",
+        "ja" | "zh" | "ko" | "th" | "lo" | "km" | "my" => "trigram",
+        _ => "unicode61",
+    })
+}
+
+/// Compare the FTS5 indexes with the messages table: messages missing from their room's index
+/// (or filed under another tokenizer), entries whose text no longer matches the message, and
+/// entries for messages that are gone. With `repair`, fix what was found; a table failing FTS5's
+/// own integrity check is rebuilt first. Runs on startup and from the admin API.
+pub fn check_fts_index(conn: &Connection, repair: bool) -> rusqlite::Result<SearchIndexReport> {
+    let started = std::time::Instant::now();
+    let messages: i64 = conn.query_row("SELECT COUNT(*) FROM messages", [], |r| r.get(0))?;
+    let mut indexed = 0;
+    let mut corrupt_tables = Vec::new();
+    let mut reindex: Vec<String> = Vec::new();
+    let mut missing = 0;
+    let mut outdated = 0;
+    let mut orphaned: Vec<(&str, i64)> = Vec::new();
+    let ids = |sql: &str, params: &[&dyn rusqlite::ToSql]| -> rusqlite::Result<Vec<String>> {
+        conn.prepare(sql)?.query_map(params, |r| r.get(0))?.collect()
+    };
+
+    for (tokenizer, table) in FTS_TABLES {
+        if conn
+            .execute(&format!("INSERT INTO {table}({table}) VALUES('integrity-check')"), [])
+            .is_err()
+        {
+            corrupt_tables.push(table.to_string());
+            if !repair {
+                continue;
+            }
+            conn.execute(&format!("INSERT INTO {table}({table}) VALUES('rebuild')"), [])?;
+        }
+        indexed += conn.query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |r| r.get::<_, i64>(0))?;
+
+        let absent = ids(
+            &format!(
+                "SELECT m.id FROM messages m JOIN rooms r ON r.id = m.room_id
+                 WHERE r.search_tokenizer = ?1 AND m.id NOT IN (SELECT message_id FROM {table})"
+            ),
+            &[&tokenizer],
+        )?;
+        missing += absent.len();
+        reindex.extend(absent);
+
+        let stale = ids(
+            &format!(
+                "SELECT f.message_id FROM {table} f JOIN messages m ON m.id = f.message_id
+                 WHERE f.sender IS NOT m.sender OR f.content IS NOT m.content"
+            ),
+            &[],
+        )?;
+        outdated += stale.len();
+        reindex.extend(stale);
+
+        // Entries for messages that are gone or belong in another table, and duplicates
+        let mut stmt = conn.prepare(&format!(
+            "SELECT rowid FROM {table}
+             WHERE message_id NOT IN (SELECT m.id FROM messages m JOIN rooms r ON r.id = m.room_id
+                                      WHERE r.search_tokenizer = ?1)
+                OR rowid NOT IN (SELECT MIN(rowid) FROM {table} GROUP BY message_id)"
+        ))?;
+        let rowids = stmt.query_map([tokenizer], |r| r.get::<_, i64>(0))?;
+        for rowid in rowids {
+            orphaned.push((table, rowid?));
+        }
+    }
+
+    let found = missing + outdated + orphaned.len() + corrupt_tables.len() > 0;
+    let repaired = repair && found;
+    if repaired {
+        let tx = conn.unchecked_transaction()?;
+        for (table, rowid) in &orphaned {
+            tx.execute(&format!("DELETE FROM {table} WHERE rowid = ?1"), [rowid])?;
+        }
+        reindex.sort();
+        reindex.dedup();
+        for message_id in &reindex {
+            upsert_fts(&tx, message_id);
+        }
+        tx.commit()?;
+    }
+
+    Ok(SearchIndexReport {
+        messages,
+        indexed,
+        missing,
+        outdated,
+        orphaned: orphaned.len(),
+        corrupt_tables,
+        repaired,
+        ok: !found || repaired,
+        checked_at: chrono::Utc::now().to_rfc3339(),
+        duration_ms: started.elapsed().as_secs_f64() * 1000.0,
+    })
+}
+
+/// Move a room's messages into the FTS table for its current tokenizer (after a language change).
+pub fn reindex_room_fts(conn: &Connection, room_id: &str) -> rusqlite::Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    for (_, table) in FTS_TABLES {
+        tx.execute(
+            &format!("DELETE FROM {table} WHERE message_id IN (SELECT id FROM messages WHERE room_id = ?1)"),
+            [room_id],
+        )?;
+    }
+    let tokenizer: String = tx.query_row("SELECT search_tokenizer FROM rooms WHERE id = ?1", [room_id], |r| r.get(0))?;
+    tx.execute(
+        &format!(
+            "INSERT INTO {} (message_id, sender, content)
+             SELECT id, sender, content FROM messages WHERE room_id = ?1",
+            fts_table(&tokenizer)
+        ),
+        [room_id],
+    )?;
+    tx.commit()
 }
 
 /// Insert or update a message in the FTS index (call after create/edit).
 pub fn upsert_fts(conn: &Connection, message_id: &str) {
-    conn.execute("DELETE FROM messages_fts WHERE message_id = ?1", [message_id])
-        .ok();
-    conn.execute(
-        "INSERT INTO messages_fts (message_id, sender, content)
+    delete_fts(conn, message_id);
+    let tokenizer: String = conn
+        .prepare_cached(
+            "SELECT r.search_tokenizer FROM messages m JOIN rooms r ON r.id = m.room_id WHERE m.id = ?1",
+        )
+        .and_then(|mut s| s.query_row([message_id], |r| r.get(0)))
+        .unwrap_or_else(|_| "porter".to_string());
+    conn.prepare_cached(&format!(
+        "INSERT INTO {} (message_id, sender, content)
          SELECT id, sender, content FROM messages WHERE id = ?1",
-        [message_id],
-    )
+        fts_table(&tokenizer)
+    ))
+    .and_then(|mut s| s.execute([message_id]))
     .ok();
 }
 
-/// Remove a message from the FTS index (call after delete).
+/// Remove a message from the FTS indexes (call after delete).
 pub fn delete_fts(conn: &Connection, message_id: &str) {
-    conn.execute("DELETE FROM messages_fts WHERE message_id = ?1", [message_id])
+    for (_, table) in FTS_TABLES {
+        conn.prepare_cached(&format!("DELETE FROM {table} WHERE message_id = ?1"))
+            .and_then(|mut s| s.execute([message_id]))
+            .ok();
+    }
+}
+
+/// Extract @mention targets from message content (lowercased, deduplicated).
+/// An `@` only starts a mention at the beginning of the text or after a character
+/// that can't be part of a name, so email addresses like `ops@example.com` are ignored.
+pub fn parse_mentions(content: &str) -> Vec<String> {
+    fn is_name_char(c: char) -> bool {
+        c.is_alphanumeric() || c == '_' || c == '-' || c == '.'
+    }
+
+    let mut targets: Vec<String> = Vec::new();
+    let mut prev: Option<char> = None;
+    for (i, c) in content.char_indices() {
+        if c == '@' && !prev.is_some_and(|p| is_name_char(p) || p == '@') {
+            let rest = &content[i + 1..];
+            let end = rest
+                .char_indices()
+                .find(|&(_, ch)| !is_name_char(ch))
+                .map(|(j, _)| j)
+                .unwrap_or(rest.len());
+            // Trailing punctuation ("@nanook." / "@forge-") isn't part of the name
+            let name = rest[..end].trim_end_matches(['.', '-']);
+            if !name.is_empty() && name.len() <= 100 {
+                let name = name.to_lowercase();
+                if !targets.contains(&name) {
+                    targets.push(name);
+                }
+            }
+        }
+        prev = Some(c);
+    }
+    targets
+}
+
+/// Re-parse and store @mentions for a message (call after create/edit).
+pub fn index_mentions(conn: &Connection, message_id: &str) {
+    conn.prepare_cached("DELETE FROM mentions WHERE message_id = ?1")
+        .and_then(|mut s| s.execute([message_id]))
+        .ok();
+    let content: Option<String> = conn
+        .prepare_cached("SELECT content FROM messages WHERE id = ?1")
+        .and_then(|mut s| s.query_row([message_id], |r| r.get(0)))
         .ok();
+    if let Some(content) = content {
+        for target in parse_mentions(&content) {
+            conn.prepare_cached("INSERT OR IGNORE INTO mentions (message_id, target) VALUES (?1, ?2)")
+                .and_then(|mut s| s.execute(params![message_id, &target]))
+                .ok();
+        }
+    }
+}
+
+/// Rebuild the mentions table from all messages. Run once by migration 0026 for databases
+/// that predate the index; new messages are indexed as they are written.
+pub fn rebuild_mentions_index(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM mentions", [])?;
+    let mut stmt = conn.prepare("SELECT id, content FROM messages WHERE content LIKE '%@%'")?;
+    let rows: Vec<(String, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+    drop(stmt);
+    let mut insert = conn.prepare("INSERT OR IGNORE INTO mentions (message_id, target) VALUES (?1, ?2)")?;
+    for (id, content) in rows {
+        for target in parse_mentions(&content) {
+            insert.execute(params![&id, &target])?;
+        }
+    }
+    Ok(())
 }
@@ -0,0 +1,605 @@
+use crate::events::{ChatEvent, Published};
+use crate::maintenance::Maintenance;
+use crate::models::{FileInfo, Message};
+use crate::senders::{SenderPolicy, ServerToken};
+use rusqlite::{params, Connection, Transaction, TransactionBehavior};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::broadcast;
+
+/// Max accepted email size (raw DATA section), including encoded attachments.
+const MAX_EMAIL_SIZE: usize = 10 * 1024 * 1024;
+
+/// Max attachment size stored as a room file (same limit as the upload endpoint).
+const MAX_ATTACHMENT_SIZE: usize = 5 * 1024 * 1024;
+
+/// Max message content length (same limit as send_message).
+const MAX_CONTENT_CHARS: usize = 10_000;
+
+/// Longest command or data line accepted (RFC 5321 allows 1000 octets; this leaves slack for
+/// sloppy senders). A longer line ends the session instead of growing the buffer.
+const MAX_LINE_BYTES: u64 = 4096;
+
+/// Temporary failure while the server is read-only; MTAs queue the mail and retry.
+const MAINTENANCE_REPLY: &[u8] = b"451 Server in maintenance mode, try again later\r\n";
+
+#[derive(Debug, Clone)]
+pub struct EmailAttachment {
+    pub filename: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ParsedEmail {
+    /// Raw From header value, e.g. `Disk Monitor <monitor@host>`.
+    pub from: String,
+    pub subject: String,
+    pub body: String,
+    pub attachments: Vec<EmailAttachment>,
+}
+
+/// Spawns a minimal SMTP listener that turns mail for `<room-name>@<domain>` into room messages.
+///
+/// Only the commands a relaying MTA or monitoring tool needs are implemented
+/// (HELO/EHLO, MAIL, RCPT, DATA, RSET, NOOP, QUIT). There is no auth or TLS —
+/// bind it to a trusted interface. Recipients for unknown rooms are rejected at RCPT time, and
+/// mail can't claim a reserved sender name (there is no way to present the server token).
+/// During maintenance mode mail is deferred with a 451 so the sending MTA retries later.
+pub fn spawn_smtp_listener(
+    bind_addr: String,
+    db_path: String,
+    events: broadcast::Sender<Published>,
+    domain: String,
+    senders: SenderPolicy,
+    maintenance: Maintenance,
+) {
+    tokio::spawn(async move {
+        let conn = match crate::db::open(&db_path) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("⚠️ Email gateway: failed to open DB: {e}");
+                return;
+            }
+        };
+        crate::db::DbConfig::from_env().apply(&conn).ok();
+        let conn = Arc::new(Mutex::new(conn));
+
+        let listener = match tokio::net::TcpListener::bind(&bind_addr).await {
+            Ok(l) => l,
+            Err(e) => {
+                eprintln!("⚠️ Email gateway: failed to bind {bind_addr}: {e}");
+                return;
+            }
+        };
+
+        loop {
+            let (stream, _) = match listener.accept().await {
+                Ok(s) => s,
+                Err(e) => {
+                    eprintln!("⚠️ Email gateway: accept failed: {e}");
+                    continue;
+                }
+            };
+            let conn = conn.clone();
+            let events = events.clone();
+            let domain = domain.clone();
+            let senders = senders.clone();
+            let maintenance = maintenance.clone();
+            tokio::spawn(async move {
+                if let Err(e) = handle_session(stream, conn, events, domain, senders, maintenance).await {
+                    eprintln!("⚠️ Email gateway: session error: {e}");
+                }
+            });
+        }
+    });
+}
+
+async fn handle_session(
+    stream: tokio::net::TcpStream,
+    conn: Arc<Mutex<Connection>>,
+    events: broadcast::Sender<Published>,
+    domain: String,
+    senders: SenderPolicy,
+    maintenance: Maintenance,
+) -> std::io::Result<()> {
+    let (read_half, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(read_half);
+    writer.write_all(b"220 local-agent-chat ESMTP\r\n").await?;
+
+    let mut rooms: Vec<String> = Vec::new();
+    let mut buf: Vec<u8> = Vec::new();
+
+    loop {
+        match read_line(&mut reader, &mut buf).await? {
+            Line::End => return Ok(()),
+            Line::TooLong => {
+                writer.write_all(b"500 Line too long\r\n").await?;
+                return Ok(());
+            }
+            Line::Read => {}
+        }
+        let line = String::from_utf8_lossy(&buf).into_owned();
+        let cmd = line.trim_end();
+        let upper = cmd.to_ascii_uppercase();
+
+        if upper.starts_with("EHLO") || upper.starts_with("HELO") {
+            writer.write_all(b"250 local-agent-chat\r\n").await?;
+        } else if upper.starts_with("MAIL FROM:") {
+            rooms.clear();
+            if maintenance.is_enabled() {
+                writer.write_all(MAINTENANCE_REPLY).await?;
+            } else {
+                writer.write_all(b"250 OK\r\n").await?;
+            }
+        } else if upper.starts_with("RCPT TO:") {
+            let addr = address_of(&cmd["RCPT TO:".len()..]);
+            let room = room_name_from_address(&addr, &domain).filter(|name| {
+                let db = conn.lock().unwrap_or_else(|e| e.into_inner());
+                room_id_by_name(&db, name).is_some()
+            });
+            match room {
+                Some(name) => {
+                    if !rooms.contains(&name) {
+                        rooms.push(name);
+                    }
+                    writer.write_all(b"250 OK\r\n").await?;
+                }
+                None => writer.write_all(b"550 No such room\r\n").await?,
+            }
+        } else if upper == "DATA" {
+            if rooms.is_empty() {
+                writer.write_all(b"503 Need RCPT first\r\n").await?;
+                continue;
+            }
+            writer
+                .write_all(b"354 End data with <CR><LF>.<CR><LF>\r\n")
+                .await?;
+
+            let mut raw = String::new();
+            let mut too_large = false;
+            loop {
+                match read_line(&mut reader, &mut buf).await? {
+                    Line::End => return Ok(()),
+                    Line::TooLong => {
+                        writer.write_all(b"500 Line too long\r\n").await?;
+                        return Ok(());
+                    }
+                    Line::Read => {}
+                }
+                let data_line = String::from_utf8_lossy(&buf);
+                let trimmed = data_line.trim_end_matches(['\r', '\n']);
+                if trimmed == "." {
+                    break;
+                }
+                if too_large {
+                    continue;
+                }
+                // Undo SMTP dot-stuffing
+                let unstuffed = trimmed.strip_prefix('.').unwrap_or(trimmed);
+                raw.push_str(unstuffed);
+                raw.push('\n');
+                if raw.len() > MAX_EMAIL_SIZE {
+                    too_large = true;
+                    raw.clear();
+                }
+            }
+
+            let email = parse_email(&raw);
+            let sender = sender_from_address(&email.from);
+            if too_large {
+                writer.write_all(b"552 Message too large\r\n").await?;
+            } else if maintenance.is_enabled() {
+                writer.write_all(MAINTENANCE_REPLY).await?;
+            } else if senders.check(&sender, &ServerToken::default()).is_err() {
+                // Permanent: retrying won't make a reserved name available
+                let reply = format!("550 Sender name '{sender}' is reserved\r\n");
+                writer.write_all(reply.as_bytes()).await?;
+            } else {
+                let delivered = {
+                    let db = conn.lock().unwrap_or_else(|e| e.into_inner());
+                    rooms
+                        .iter()
+                        .filter(|name| deliver_email(&db, &events, &senders, &email, name).is_ok())
+                        .count()
+                };
+                if delivered > 0 {
+                    writer.write_all(b"250 OK\r\n").await?;
+                } else {
+                    writer.write_all(b"451 Delivery failed\r\n").await?;
+                }
+            }
+            rooms.clear();
+        } else if upper == "RSET" {
+            rooms.clear();
+            writer.write_all(b"250 OK\r\n").await?;
+        } else if upper == "NOOP" {
+            writer.write_all(b"250 OK\r\n").await?;
+        } else if upper == "QUIT" {
+            writer.write_all(b"221 Bye\r\n").await?;
+            return Ok(());
+        } else {
+            writer.write_all(b"502 Command not implemented\r\n").await?;
+        }
+    }
+}
+
+enum Line {
+    Read,
+    TooLong,
+    End,
+}
+
+/// Read one line (with its terminator) into `buf`, reading at most [`MAX_LINE_BYTES`].
+async fn read_line<R: AsyncBufRead + Unpin>(reader: &mut R, buf: &mut Vec<u8>) -> std::io::Result<Line> {
+    buf.clear();
+    let n = (&mut *reader).take(MAX_LINE_BYTES).read_until(b'\n', buf).await?;
+    Ok(if n == 0 {
+        Line::End
+    } else if !buf.ends_with(b"\n") && n as u64 == MAX_LINE_BYTES {
+        Line::TooLong
+    } else {
+        Line::Read
+    })
+}
+
+/// Post a parsed email into a room as a message, storing attachments as room files.
+/// The From header maps to the sender (display name if present, else the address local part).
+/// Files and the message are written in one immediate transaction, so the seq can't race
+/// another writer, and the message goes through the event outbox like API posts.
+/// Reserved sender names are refused, since mail can't carry the server token.
+pub fn deliver_email(
+    conn: &Connection,
+    events: &broadcast::Sender<Published>,
+    senders: &SenderPolicy,
+    email: &ParsedEmail,
+    room_name: &str,
+) -> Result<Message, String> {
+    let room_id = room_id_by_name(conn, room_name).ok_or("Room not found")?;
+    let sender = sender_from_address(&email.from);
+    senders
+        .check(&sender, &ServerToken::default())
+        .map_err(|(_, body)| body.0["error"].as_str().unwrap_or("Reserved sender name").to_string())?;
+    let now = chrono::Utc::now().to_rfc3339();
+
+    // Attachments follow the room's default file TTL, if any
+    let expires_at: Option<String> = conn
+        .query_row("SELECT file_ttl_secs FROM rooms WHERE id = ?1", params![&room_id], |r| r.get::<_, Option<i64>>(0))
+        .ok()
+        .flatten()
+        .map(|secs| (chrono::Utc::now() + chrono::Duration::seconds(secs)).to_rfc3339());
+
+    // Attachments that fail the upload policy are dropped, like oversized ones
+    let upload_config = crate::uploads::UploadConfig::from_env();
+    let policy = crate::uploads::room_policy(conn, &room_id);
+
+    let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate).map_err(|e| e.to_string())?;
+    let mut files: Vec<FileInfo> = Vec::new();
+    let mut file_ids: Vec<String> = Vec::new();
+    for attachment in &email.attachments {
+        if attachment.data.is_empty() || attachment.data.len() > MAX_ATTACHMENT_SIZE {
+            continue;
+        }
+        if let Err(rejection) = crate::uploads::screen(
+            &upload_config,
+            policy.as_ref(),
+            &attachment.filename,
+            &attachment.content_type,
+            &attachment.data,
+        ) {
+            eprintln!("⚠️ Email gateway: dropped attachment {}: {}", attachment.filename, rejection.message);
+            continue;
+        }
+        let file_id = uuid::Uuid::new_v4().to_string();
+        let size = attachment.data.len() as i64;
+        let inserted = crate::db::store_file_blob(&tx, &attachment.data).and_then(|sha256| {
+            tx.execute(
+                "INSERT INTO files (id, room_id, sender, filename, content_type, size, data, created_at, sha256, expires_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, x'', ?7, ?8, ?9)",
+                params![&file_id, &room_id, &sender, &attachment.filename, &attachment.content_type, size, &now, &sha256, &expires_at],
+            )?;
+            Ok(sha256)
+        });
+        if let Ok(sha256) = inserted {
+            files.push(FileInfo {
+                id: file_id.clone(),
+                room_id: room_id.clone(),
+                sender: sender.clone(),
+                filename: attachment.filename.clone(),
+                content_type: attachment.content_type.clone(),
+                size,
+                sha256: Some(sha256),
+                url: format!("/api/v1/files/{file_id}"),
+                created_at: now.clone(),
+                expires_at: expires_at.clone(),
+            });
+            file_ids.push(file_id);
+        }
+    }
+
+    let subject = email.subject.trim();
+    let body = email.body.trim();
+    let mut content = match (subject.is_empty(), body.is_empty()) {
+        (false, false) => format!("**{subject}**\n\n{body}"),
+        (false, true) => format!("**{subject}**"),
+        (true, false) => body.to_string(),
+        (true, true) => "(empty email)".to_string(),
+    };
+    if content.chars().count() > MAX_CONTENT_CHARS {
+        content = content.chars().take(MAX_CONTENT_CHARS).collect();
+    }
+
+    let metadata = serde_json::json!({
+        "source": "email",
+        "from": address_of(&email.from),
+        "subject": subject,
+        "attachments": file_ids,
+    });
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let seq: i64 = tx
+        .query_row("SELECT COALESCE(MAX(seq), 0) + 1 FROM messages", [], |r| r.get(0))
+        .map_err(|e| e.to_string())?;
+
+    tx.execute(
+        "INSERT INTO messages (id, room_id, sender, content, metadata, created_at, seq) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![&id, &room_id, &sender, &content, metadata.to_string(), &now, seq],
+    )
+    .map_err(|e| e.to_string())?;
+
+    tx.execute(
+        "UPDATE rooms SET updated_at = ?1 WHERE id = ?2",
+        params![&now, &room_id],
+    )
+    .ok();
+
+    crate::db::upsert_fts(&tx, &id);
+    crate::db::index_mentions(&tx, &id);
+
+    let msg = Message {
+        id,
+        room_id,
+        sender,
+        content,
+        metadata,
+        created_at: now,
+        edited_at: None,
+        reply_to: None,
+        sender_type: None,
+        seq,
+        pinned_at: None,
+        pinned_by: None,
+        edit_count: 0,
+        kind: "message".to_string(),
+        local_time: None,
+    };
+    let event = ChatEvent::NewMessage(msg.clone());
+    let event_id = crate::outbox::record(&tx, &event, None).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    for file in files {
+        let _ = events.send(ChatEvent::FileUploaded(file).into());
+    }
+    let _ = events.send(Published {
+        event_id: Some(event_id),
+        ..event.into()
+    });
+    crate::outbox::mark_published(conn, event_id);
+    Ok(msg)
+}
+
+/// Private rooms are invisible here: mail carries no member token, and a 550 for them reads the
+/// same as for a room that doesn't exist.
+fn room_id_by_name(conn: &Connection, name: &str) -> Option<String> {
+    conn.query_row(
+        "SELECT id FROM rooms WHERE name = ?1 COLLATE NOCASE AND visibility <> 'private'",
+        params![name],
+        |r| r.get(0),
+    )
+    .ok()
+}
+
+/// Extract the bare address from a header value: `Name <a@b>` → `a@b`.
+pub fn address_of(value: &str) -> String {
+    let value = value.trim();
+    let addr = match (value.rfind('<'), value.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &value[start + 1..end],
+        _ => value,
+    };
+    addr.trim().to_lowercase()
+}
+
+/// Map a recipient address to a room name if it belongs to the gateway domain.
+pub fn room_name_from_address(addr: &str, domain: &str) -> Option<String> {
+    let (local, addr_domain) = addr.rsplit_once('@')?;
+    if local.is_empty() || !addr_domain.eq_ignore_ascii_case(domain) {
+        return None;
+    }
+    Some(local.to_string())
+}
+
+/// Map a From header to a sender name: the display name if present, else the address local part.
+pub fn sender_from_address(from: &str) -> String {
+    let from = from.trim();
+    let display = from
+        .find('<')
+        .map(|i| from[..i].trim().trim_matches('"').trim())
+        .unwrap_or("");
+    let sender = if !display.is_empty() {
+        display.to_string()
+    } else {
+        let addr = address_of(from);
+        addr.split('@').next().unwrap_or("").to_string()
+    };
+    let sender: String = sender.chars().take(100).collect();
+    if sender.is_empty() {
+        "email".to_string()
+    } else {
+        sender
+    }
+}
+
+/// Parse a raw RFC 5322 message into sender, subject, text body, and attachments.
+///
+/// Handles nested multipart bodies and base64 / quoted-printable transfer encodings.
+/// The first text/plain part becomes the body (falling back to text/html).
+pub fn parse_email(raw: &str) -> ParsedEmail {
+    let raw = raw.replace("\r\n", "\n");
+    let (headers, body) = split_headers(&raw);
+
+    let mut email = ParsedEmail {
+        from: header(&headers, "From").unwrap_or("").to_string(),
+        subject: header(&headers, "Subject").unwrap_or("").to_string(),
+        ..Default::default()
+    };
+    let mut html_body: Option<String> = None;
+    parse_part(&headers, body, &mut email, &mut html_body);
+    if email.body.is_empty()
+        && let Some(html) = html_body
+    {
+        email.body = html;
+    }
+    email
+}
+
+fn parse_part(
+    headers: &[(String, String)],
+    body: &str,
+    email: &mut ParsedEmail,
+    html_body: &mut Option<String>,
+) {
+    let content_type = header(headers, "Content-Type").unwrap_or("text/plain");
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_lowercase();
+
+    if mime.starts_with("multipart/") {
+        if let Some(boundary) = header_param(content_type, "boundary") {
+            let delimiter = format!("--{boundary}");
+            for segment in body.split(delimiter.as_str()).skip(1) {
+                if segment.starts_with("--") {
+                    break;
+                }
+                let segment = segment.strip_prefix('\n').unwrap_or(segment);
+                let (part_headers, part_body) = split_headers(segment);
+                parse_part(&part_headers, part_body, email, html_body);
+            }
+        }
+        return;
+    }
+
+    let encoding = header(headers, "Content-Transfer-Encoding")
+        .unwrap_or("")
+        .trim()
+        .to_lowercase();
+    let data = decode_transfer(body, &encoding);
+
+    let disposition = header(headers, "Content-Disposition").unwrap_or("");
+    let filename = header_param(disposition, "filename")
+        .or_else(|| header_param(content_type, "name"));
+    let is_attachment = disposition.trim().to_lowercase().starts_with("attachment")
+        || filename.is_some();
+
+    if is_attachment {
+        email.attachments.push(EmailAttachment {
+            filename: filename.unwrap_or_else(|| "attachment".to_string()),
+            content_type: mime,
+            data,
+        });
+    } else if mime == "text/plain" && email.body.is_empty() {
+        email.body = String::from_utf8_lossy(&data).trim().to_string();
+    } else if mime == "text/html" && html_body.is_none() {
+        *html_body = Some(String::from_utf8_lossy(&data).trim().to_string());
+    }
+}
+
+/// Split a message (or MIME part) into unfolded headers and body.
+fn split_headers(raw: &str) -> (Vec<(String, String)>, &str) {
+    let (head, body) = match raw.find("\n\n") {
+        Some(i) => (&raw[..i], &raw[i + 2..]),
+        None => (raw, ""),
+    };
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for line in head.lines() {
+        if line.starts_with(' ') || line.starts_with('\t') {
+            // Folded continuation of the previous header
+            if let Some(last) = headers.last_mut() {
+                last.1.push(' ');
+                last.1.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    (headers, body)
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.as_str())
+}
+
+/// Read a `key=value` parameter from a structured header like Content-Type.
+fn header_param(value: &str, key: &str) -> Option<String> {
+    value.split(';').skip(1).find_map(|param| {
+        let (k, v) = param.split_once('=')?;
+        if k.trim().eq_ignore_ascii_case(key) {
+            Some(v.trim().trim_matches('"').to_string())
+        } else {
+            None
+        }
+    })
+}
+
+fn decode_transfer(body: &str, encoding: &str) -> Vec<u8> {
+    use base64::Engine;
+    match encoding {
+        "base64" => {
+            let compact: String = body.chars().filter(|c| !c.is_whitespace()).collect();
+            base64::engine::general_purpose::STANDARD
+                .decode(compact)
+                .unwrap_or_default()
+        }
+        "quoted-printable" => decode_quoted_printable(body),
+        _ => body.as_bytes().to_vec(),
+    }
+}
+
+fn decode_quoted_printable(body: &str) -> Vec<u8> {
+    fn hex_val(b: u8) -> Option<u8> {
+        (b as char).to_digit(16).map(|d| d as u8)
+    }
+
+    let mut out = Vec::with_capacity(body.len());
+    let mut lines = body.split('\n').peekable();
+    while let Some(line) = lines.next() {
+        let line = line.trim_end_matches([' ', '\t', '\r']);
+        // A trailing '=' is a soft line break: join with the next line
+        let (line, soft_break) = match line.strip_suffix('=') {
+            Some(l) => (l, true),
+            None => (line, false),
+        };
+        let bytes = line.as_bytes();
+        let mut j = 0;
+        while j < bytes.len() {
+            if bytes[j] == b'='
+                && j + 3 <= bytes.len()
+                && let (Some(hi), Some(lo)) = (hex_val(bytes[j + 1]), hex_val(bytes[j + 2]))
+            {
+                out.push((hi << 4) | lo);
+                j += 3;
+            } else {
+                out.push(bytes[j]);
+                j += 1;
+            }
+        }
+        if !soft_break && lines.peek().is_some() {
+            out.push(b'\n');
+        }
+    }
+    out
+}
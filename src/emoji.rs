@@ -0,0 +1,199 @@
+//! Emoji shortcode normalization for reactions.
+//!
+//! Agent libraries disagree on encoding: some send `👍`, some `:thumbsup:` or `:+1:`, some `❤` with
+//! or without the U+FE0F variation selector. Reactions are stored in one canonical form — the
+//! unicode emoji when the shortcode is known — so counts aggregate, and summaries report both.
+
+/// Shortcodes (without colons) and their emoji. The first shortcode listed for an emoji is the
+/// canonical one reported back; later entries are accepted aliases.
+const SHORTCODES: &[(&str, &str)] = &[
+    ("thumbsup", "👍"),
+    ("+1", "👍"),
+    ("thumbs_up", "👍"),
+    ("thumbsdown", "👎"),
+    ("-1", "👎"),
+    ("thumbs_down", "👎"),
+    ("heart", "❤️"),
+    ("red_heart", "❤️"),
+    ("broken_heart", "💔"),
+    ("smile", "😄"),
+    ("smiley", "😃"),
+    ("grin", "😁"),
+    ("grinning", "😀"),
+    ("laughing", "😆"),
+    ("joy", "😂"),
+    ("rofl", "🤣"),
+    ("wink", "😉"),
+    ("blush", "😊"),
+    ("slightly_smiling_face", "🙂"),
+    ("upside_down_face", "🙃"),
+    ("heart_eyes", "😍"),
+    ("thinking", "🤔"),
+    ("thinking_face", "🤔"),
+    ("neutral_face", "😐"),
+    ("expressionless", "😑"),
+    ("unamused", "😒"),
+    ("roll_eyes", "🙄"),
+    ("grimacing", "😬"),
+    ("relieved", "😌"),
+    ("sleeping", "😴"),
+    ("sweat_smile", "😅"),
+    ("sweat", "😓"),
+    ("confused", "😕"),
+    ("worried", "😟"),
+    ("cry", "😢"),
+    ("sob", "😭"),
+    ("scream", "😱"),
+    ("angry", "😠"),
+    ("rage", "😡"),
+    ("exploding_head", "🤯"),
+    ("sunglasses", "😎"),
+    ("nerd_face", "🤓"),
+    ("skull", "💀"),
+    ("robot", "🤖"),
+    ("robot_face", "🤖"),
+    ("eyes", "👀"),
+    ("clap", "👏"),
+    ("pray", "🙏"),
+    ("raised_hands", "🙌"),
+    ("wave", "👋"),
+    ("ok_hand", "👌"),
+    ("muscle", "💪"),
+    ("point_up", "☝️"),
+    ("point_right", "👉"),
+    ("handshake", "🤝"),
+    ("fire", "🔥"),
+    ("tada", "🎉"),
+    ("party_popper", "🎉"),
+    ("rocket", "🚀"),
+    ("star", "⭐"),
+    ("sparkles", "✨"),
+    ("zap", "⚡"),
+    ("boom", "💥"),
+    ("100", "💯"),
+    ("white_check_mark", "✅"),
+    ("check", "✅"),
+    ("heavy_check_mark", "✔️"),
+    ("x", "❌"),
+    ("warning", "⚠️"),
+    ("no_entry", "⛔"),
+    ("question", "❓"),
+    ("exclamation", "❗"),
+    ("bulb", "💡"),
+    ("memo", "📝"),
+    ("pencil", "📝"),
+    ("bug", "🐛"),
+    ("wrench", "🔧"),
+    ("hammer", "🔨"),
+    ("gear", "⚙️"),
+    ("lock", "🔒"),
+    ("key", "🔑"),
+    ("link", "🔗"),
+    ("pushpin", "📌"),
+    ("hourglass", "⌛"),
+    ("stopwatch", "⏱️"),
+    ("calendar", "📅"),
+    ("chart_with_upwards_trend", "📈"),
+    ("chart_with_downwards_trend", "📉"),
+    ("package", "📦"),
+    ("mag", "🔍"),
+    ("speech_balloon", "💬"),
+    ("bell", "🔔"),
+    ("coffee", "☕"),
+    ("pizza", "🍕"),
+    ("beer", "🍺"),
+    ("trophy", "🏆"),
+    ("medal", "🏅"),
+    ("crown", "👑"),
+    ("gem", "💎"),
+    ("moneybag", "💰"),
+    ("heavy_plus_sign", "➕"),
+    ("heavy_minus_sign", "➖"),
+    ("arrow_up", "⬆️"),
+    ("arrow_down", "⬇️"),
+    ("arrows_counterclockwise", "🔄"),
+    ("repeat", "🔁"),
+    ("stop_sign", "🛑"),
+    ("construction", "🚧"),
+    ("ship", "🚢"),
+    ("see_no_evil", "🙈"),
+    ("shrug", "🤷"),
+    ("facepalm", "🤦"),
+    ("saluting_face", "🫡"),
+    ("brain", "🧠"),
+    ("green_heart", "💚"),
+    ("blue_heart", "💙"),
+    ("yellow_heart", "💛"),
+    ("purple_heart", "💜"),
+    ("sunny", "☀️"),
+    ("cloud", "☁️"),
+    ("snowflake", "❄️"),
+    ("rainbow", "🌈"),
+    ("seedling", "🌱"),
+    ("dart", "🎯"),
+    ("checkered_flag", "🏁"),
+];
+
+const VARIATION_SELECTOR: char = '\u{FE0F}';
+
+fn strip_selector(s: &str) -> String {
+    s.chars().filter(|c| *c != VARIATION_SELECTOR).collect()
+}
+
+/// Canonical stored form of a reaction: known shortcodes and bare/variant unicode map to the
+/// table's emoji; unknown `:custom:` codes are lowercased; anything else is kept as given.
+pub fn normalize(input: &str) -> String {
+    let input = input.trim();
+    if let Some(code) = input.strip_prefix(':').and_then(|s| s.strip_suffix(':'))
+        && !code.is_empty()
+    {
+        let code = code.to_lowercase();
+        return match SHORTCODES.iter().find(|(name, _)| *name == code) {
+            Some((_, emoji)) => emoji.to_string(),
+            None => format!(":{code}:"),
+        };
+    }
+    let bare = strip_selector(input);
+    SHORTCODES
+        .iter()
+        .find(|(_, emoji)| strip_selector(emoji) == bare)
+        .map(|(_, emoji)| emoji.to_string())
+        .unwrap_or_else(|| input.to_string())
+}
+
+/// The `:shortcode:` for a stored reaction: the canonical name for known emoji, or the value
+/// itself when it is a custom shortcode. None for unicode with no known name.
+pub fn shortcode(stored: &str) -> Option<String> {
+    if stored.starts_with(':') && stored.ends_with(':') && stored.len() > 2 {
+        return Some(stored.to_string());
+    }
+    SHORTCODES
+        .iter()
+        .find(|(_, emoji)| *emoji == stored)
+        .map(|(name, _)| format!(":{name}:"))
+}
+
+/// Rewrite reactions stored before normalization so old and new encodings merge. Where a sender
+/// already has the canonical form on a message, the duplicate is dropped.
+pub fn normalize_stored_reactions(conn: &rusqlite::Connection) {
+    let stored: Vec<String> = conn
+        .prepare("SELECT DISTINCT emoji FROM message_reactions")
+        .and_then(|mut s| {
+            s.query_map([], |r| r.get::<_, String>(0))
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .unwrap_or_default();
+    for old in stored {
+        let new = normalize(&old);
+        if new == old {
+            continue;
+        }
+        conn.execute(
+            "UPDATE OR IGNORE message_reactions SET emoji = ?1 WHERE emoji = ?2",
+            rusqlite::params![&new, &old],
+        )
+        .ok();
+        conn.execute("DELETE FROM message_reactions WHERE emoji = ?1", rusqlite::params![&old])
+            .ok();
+    }
+}
@@ -1,15 +1,34 @@
-use crate::models::{FileInfo, Message, PinnedMessage, Profile, Reaction, ReadPosition, RoomWithStats};
+use crate::models::{
+    FileInfo, Lock, Message, MessageAppend, MessageChunk, MessageFlag, MessageLabels, PendingResponse, PinnedMessage, Profile, Reaction, ReadPosition, RetentionNotice, RoomTopic, RoomWithStats,
+    QueueItem, SenderStatus, WebhookCircuitOpened,
+};
+use crate::telemetry::SpanContext;
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome, Request};
+use serde::{Deserialize, Serialize};
 use tokio::sync::broadcast;
 
-#[derive(Debug, Clone)]
+/// Serializable so events can be stored in the [`crate::outbox`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
 pub enum ChatEvent {
     NewMessage(Message),
     MessageEdited(Message),
     MessageDeleted { id: String, room_id: String },
+    MessageRedacted { id: String, room_id: String, redacted_at: String },
+    RoomCreated(RoomWithStats),
     RoomUpdated(RoomWithStats),
-    Typing { sender: String, room_id: String },
+    RoomDeleted { id: String, name: String },
+    Typing {
+        sender: String,
+        room_id: String,
+        /// Client session (device) the sender is typing on
+        #[serde(default)]
+        session: Option<String>,
+    },
     FileUploaded(FileInfo),
     FileDeleted { id: String, room_id: String },
+    FileExpired { id: String, room_id: String },
     ReactionAdded(Reaction),
     ReactionRemoved(Reaction),
     MessagePinned(PinnedMessage),
@@ -19,14 +38,74 @@ pub enum ChatEvent {
     ReadPositionUpdated(ReadPosition),
     ProfileUpdated(Profile),
     ProfileDeleted { sender: String },
+    StatusUpdated(SenderStatus),
+    StatusCleared { sender: String },
+    LockAcquired(Lock),
+    LockReleased { name: String, holder: String, token: i64 },
     RoomArchived(RoomWithStats),
     RoomUnarchived(RoomWithStats),
     RoomBookmarked { room_id: String, sender: String },
     RoomUnbookmarked { room_id: String, sender: String },
+    TopicChanged(RoomTopic),
+    MessageChunk(MessageChunk),
+    MessageFinalized(Message),
+    MessageAppended(MessageAppend),
+    RetentionPending(RetentionNotice),
+    ResponseOverdue(PendingResponse),
+    MessageFlagged(MessageFlag),
+    FlagResolved(MessageFlag),
+    MessageLabeled(MessageLabels),
+    WebhookDisabled(WebhookCircuitOpened),
+    QueueItemAdded(QueueItem),
+    QueueItemClaimed(QueueItem),
+    QueueItemCompleted(QueueItem),
+    QueueItemReleased(QueueItem),
+}
+
+/// A ChatEvent as it travels over the bus, tagged with the `X-Request-Id` of the
+/// HTTP call that caused it (None for background work like the email gateway),
+/// and with that call's trace context so webhook deliveries join the same trace.
+/// Events stored in the outbox carry their outbox id, which SSE sends as the event `id`.
+/// The bus is shared by every namespace, so each event names the one it happened in (None for
+/// the main database) and consumers drop the others.
+#[derive(Debug, Clone)]
+pub struct Published {
+    pub event: ChatEvent,
+    pub request_id: Option<String>,
+    pub trace_context: Option<SpanContext>,
+    pub event_id: Option<i64>,
+    pub namespace: Option<String>,
+}
+
+impl From<ChatEvent> for Published {
+    fn from(event: ChatEvent) -> Self {
+        Published {
+            event,
+            request_id: None,
+            trace_context: None,
+            event_id: None,
+            namespace: None,
+        }
+    }
+}
+
+impl Published {
+    /// An event from a background job working on `namespace`'s database.
+    pub fn in_namespace(event: ChatEvent, namespace: Option<&str>) -> Self {
+        Published {
+            namespace: namespace.map(String::from),
+            ..event.into()
+        }
+    }
+
+    /// Whether the event happened in `namespace` (None for the main database).
+    pub fn is_in(&self, namespace: Option<&str>) -> bool {
+        self.namespace.as_deref() == namespace
+    }
 }
 
 pub struct EventBus {
-    pub sender: broadcast::Sender<ChatEvent>,
+    pub sender: broadcast::Sender<Published>,
 }
 
 impl Default for EventBus {
@@ -41,8 +120,66 @@ impl EventBus {
         EventBus { sender }
     }
 
+    /// Publish an event that happened in the main database.
     pub fn publish(&self, event: ChatEvent) {
         // Ignore send errors (no subscribers)
-        let _ = self.sender.send(event);
+        let _ = self.sender.send(event.into());
+    }
+}
+
+/// Request guard for publishing from route handlers: everything published through it
+/// carries the current request's `X-Request-Id`, so SSE clients and webhook receivers
+/// can correlate an event with the call that triggered it.
+pub struct Events<'r> {
+    bus: &'r EventBus,
+    request_id: String,
+    trace_context: Option<SpanContext>,
+    namespace: Option<String>,
+}
+
+impl Events<'_> {
+    pub fn publish(&self, event: ChatEvent) {
+        let _ = self.bus.sender.send(Published {
+            event,
+            request_id: Some(self.request_id.clone()),
+            trace_context: self.trace_context.clone(),
+            event_id: None,
+            namespace: self.namespace.clone(),
+        });
+    }
+
+    /// Publish an event already committed to the outbox with [`crate::outbox::record`], and
+    /// mark it published so the relay doesn't send it again.
+    pub fn publish_recorded(&self, conn: &rusqlite::Connection, event_id: i64, event: ChatEvent) {
+        let _ = self.bus.sender.send(Published {
+            event,
+            request_id: Some(self.request_id.clone()),
+            trace_context: self.trace_context.clone(),
+            event_id: Some(event_id),
+            namespace: self.namespace.clone(),
+        });
+        crate::outbox::mark_published(conn, event_id);
+    }
+
+    /// The `X-Request-Id` events are tagged with, for recording them in the outbox.
+    pub fn request_id(&self) -> &str {
+        &self.request_id
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Events<'r> {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match req.rocket().state::<EventBus>() {
+            Some(bus) => Outcome::Success(Events {
+                bus,
+                request_id: crate::request_id::RequestId::of(req),
+                trace_context: crate::telemetry::TraceContext::of(req),
+                namespace: crate::namespaces::Namespace::of(req).0,
+            }),
+            None => Outcome::Error((Status::InternalServerError, ())),
+        }
     }
 }
@@ -0,0 +1,126 @@
+//! Sparse fieldsets: `?fields=id,sender,content,seq` on list endpoints trims each item to the
+//! named fields, so agents polling every few seconds don't pay for metadata they never read.
+
+use std::sync::Arc;
+
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use serde::{Serialize, Serializer};
+
+/// Selectable fields of a `Message`.
+pub const MESSAGE_FIELDS: &[&str] = &[
+    "id",
+    "room_id",
+    "sender",
+    "content",
+    "metadata",
+    "created_at",
+    "edited_at",
+    "reply_to",
+    "sender_type",
+    "seq",
+    "pinned_at",
+    "pinned_by",
+    "edit_count",
+    "kind",
+    "local_time",
+];
+
+/// Selectable fields of a `RoomWithStats`.
+pub const ROOM_FIELDS: &[&str] = &[
+    "id",
+    "name",
+    "description",
+    "created_by",
+    "created_at",
+    "updated_at",
+    "message_count",
+    "last_activity",
+    "last_message_sender",
+    "last_message_preview",
+    "archived_at",
+    "bookmarked",
+    "max_messages",
+    "max_message_age_hours",
+    "file_ttl_secs",
+    "retention_notice_secs",
+    "icon",
+    "icon_url",
+    "color",
+    "tags",
+    "language",
+    "search_tokenizer",
+    "topic",
+    "allowed_reactions",
+    "visibility",
+];
+
+/// A parsed `?fields=` list. None from `parse` means "all fields".
+pub type FieldSet = Arc<[String]>;
+
+/// Parse a comma-separated field list against the endpoint's valid fields. An empty or absent
+/// list selects everything; any unknown name is a 400 listing the valid ones.
+pub fn parse(fields: Option<&str>, valid: &[&str]) -> Result<Option<FieldSet>, (Status, Json<serde_json::Value>)> {
+    let requested: Vec<String> = fields
+        .unwrap_or("")
+        .split(',')
+        .map(str::trim)
+        .filter(|f| !f.is_empty())
+        .map(str::to_string)
+        .collect();
+    if requested.is_empty() {
+        return Ok(None);
+    }
+    let unknown: Vec<&str> = requested
+        .iter()
+        .map(String::as_str)
+        .filter(|f| !valid.contains(f))
+        .collect();
+    if !unknown.is_empty() {
+        return Err((
+            Status::BadRequest,
+            Json(serde_json::json!({
+                "error": format!("Unknown field(s): {}. Valid fields: {}", unknown.join(", "), valid.join(", ")),
+                "valid_fields": valid,
+            })),
+        ));
+    }
+    Ok(Some(requested.into()))
+}
+
+/// An item serialized in full, or trimmed to a field set. Fields that the full form would omit
+/// (unset optionals) stay omitted.
+#[derive(Debug)]
+pub struct Sparse<T> {
+    pub item: T,
+    fields: Option<FieldSet>,
+}
+
+impl<T> Sparse<T> {
+    pub fn new(item: T, fields: &Option<FieldSet>) -> Self {
+        Self { item, fields: fields.clone() }
+    }
+
+    /// Wrap every item in a list with the same field set.
+    pub fn all(items: Vec<T>, fields: &Option<FieldSet>) -> Vec<Self> {
+        items.into_iter().map(|item| Self::new(item, fields)).collect()
+    }
+}
+
+impl<T: Serialize> Serialize for Sparse<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let Some(ref fields) = self.fields else {
+            return self.item.serialize(serializer);
+        };
+        let mut full = serde_json::to_value(&self.item).map_err(serde::ser::Error::custom)?;
+        let mut trimmed = serde_json::Map::new();
+        if let Some(obj) = full.as_object_mut() {
+            for field in fields.iter() {
+                if let Some(value) = obj.remove(field) {
+                    trimmed.insert(field.clone(), value);
+                }
+            }
+        }
+        trimmed.serialize(serializer)
+    }
+}
@@ -0,0 +1,282 @@
+//! Localization of server-generated text.
+//!
+//! System messages and common API errors come from small static catalogs. The locale for a request
+//! is negotiated from `Accept-Language`, then the profile locale of the `sender`/`reader` named in
+//! the query string, then `DEFAULT_LOCALE`, then English.
+//!
+//! System messages are stored in the server's default locale with their inputs in `metadata`, so
+//! they can be re-rendered per reader. Error bodies are rewritten by [`LocalizeErrors`] on the way
+//! out, which also tags recognized errors with a stable `error_code`.
+
+use std::env;
+use std::io::Cursor;
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::Header;
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::Response;
+use rusqlite::params;
+
+use crate::namespaces::ScopedDb;
+use crate::models::Message;
+
+/// Locales with a catalog. The first entry is the fallback for missing keys.
+pub const SUPPORTED: &[&str] = &["en", "es", "de", "fr"];
+
+const EN: &[(&str, &str)] = &[
+    ("room_renamed", "Room renamed from #{old_name} to #{new_name}"),
+    ("message_pinned", "A message from {author} was pinned"),
+    ("member_joined", "{sender} joined the room"),
+    ("retention_purged", "Retention removed {pruned} older messages"),
+    ("retention_purged_one", "Retention removed 1 older message"),
+    ("mention_nudge", "{sender} mentioned you in #{room_name}: {excerpt}"),
+    ("error.not_found", "Not found"),
+    ("error.too_many_requests", "Too many requests"),
+    ("error.room_not_found", "Room not found"),
+    ("error.message_not_found", "Message not found"),
+    ("error.message_not_in_room", "Message not found in this room"),
+    ("error.file_not_found", "File not found"),
+    ("error.webhook_not_found", "Webhook not found"),
+    ("error.invalid_admin_key", "Invalid admin key for this room"),
+    ("error.sender_length", "Sender must be 1-100 characters"),
+    ("error.content_length", "Content must be 1-10000 characters"),
+    ("error.sender_required", "Sender parameter is required"),
+    ("error.internal", "Internal server error"),
+    ("error.database", "Database error"),
+];
+
+const ES: &[(&str, &str)] = &[
+    ("room_renamed", "Sala renombrada de #{old_name} a #{new_name}"),
+    ("message_pinned", "Se fijó un mensaje de {author}"),
+    ("member_joined", "{sender} se unió a la sala"),
+    ("retention_purged", "La retención eliminó {pruned} mensajes antiguos"),
+    ("retention_purged_one", "La retención eliminó 1 mensaje antiguo"),
+    ("mention_nudge", "{sender} te mencionó en #{room_name}: {excerpt}"),
+    ("error.not_found", "No encontrado"),
+    ("error.too_many_requests", "Demasiadas solicitudes"),
+    ("error.room_not_found", "Sala no encontrada"),
+    ("error.message_not_found", "Mensaje no encontrado"),
+    ("error.message_not_in_room", "Mensaje no encontrado en esta sala"),
+    ("error.file_not_found", "Archivo no encontrado"),
+    ("error.webhook_not_found", "Webhook no encontrado"),
+    ("error.invalid_admin_key", "Clave de administrador no válida para esta sala"),
+    ("error.sender_length", "El remitente debe tener entre 1 y 100 caracteres"),
+    ("error.content_length", "El contenido debe tener entre 1 y 10000 caracteres"),
+    ("error.sender_required", "El parámetro sender es obligatorio"),
+    ("error.internal", "Error interno del servidor"),
+    ("error.database", "Error de base de datos"),
+];
+
+const DE: &[(&str, &str)] = &[
+    ("room_renamed", "Raum umbenannt von #{old_name} in #{new_name}"),
+    ("message_pinned", "Eine Nachricht von {author} wurde angeheftet"),
+    ("member_joined", "{sender} ist dem Raum beigetreten"),
+    ("retention_purged", "Die Aufbewahrung hat {pruned} ältere Nachrichten entfernt"),
+    ("retention_purged_one", "Die Aufbewahrung hat 1 ältere Nachricht entfernt"),
+    ("mention_nudge", "{sender} hat dich in #{room_name} erwähnt: {excerpt}"),
+    ("error.not_found", "Nicht gefunden"),
+    ("error.too_many_requests", "Zu viele Anfragen"),
+    ("error.room_not_found", "Raum nicht gefunden"),
+    ("error.message_not_found", "Nachricht nicht gefunden"),
+    ("error.message_not_in_room", "Nachricht in diesem Raum nicht gefunden"),
+    ("error.file_not_found", "Datei nicht gefunden"),
+    ("error.webhook_not_found", "Webhook nicht gefunden"),
+    ("error.invalid_admin_key", "Ungültiger Admin-Schlüssel für diesen Raum"),
+    ("error.sender_length", "Absender muss 1-100 Zeichen lang sein"),
+    ("error.content_length", "Inhalt muss 1-10000 Zeichen lang sein"),
+    ("error.sender_required", "Der Parameter sender ist erforderlich"),
+    ("error.internal", "Interner Serverfehler"),
+    ("error.database", "Datenbankfehler"),
+];
+
+const FR: &[(&str, &str)] = &[
+    ("room_renamed", "Salon renommé de #{old_name} en #{new_name}"),
+    ("message_pinned", "Un message de {author} a été épinglé"),
+    ("member_joined", "{sender} a rejoint le salon"),
+    ("retention_purged", "La rétention a supprimé {pruned} anciens messages"),
+    ("retention_purged_one", "La rétention a supprimé 1 ancien message"),
+    ("mention_nudge", "{sender} vous a mentionné dans #{room_name} : {excerpt}"),
+    ("error.not_found", "Introuvable"),
+    ("error.too_many_requests", "Trop de requêtes"),
+    ("error.room_not_found", "Salon introuvable"),
+    ("error.message_not_found", "Message introuvable"),
+    ("error.message_not_in_room", "Message introuvable dans ce salon"),
+    ("error.file_not_found", "Fichier introuvable"),
+    ("error.webhook_not_found", "Webhook introuvable"),
+    ("error.invalid_admin_key", "Clé d'administration invalide pour ce salon"),
+    ("error.sender_length", "L'expéditeur doit comporter de 1 à 100 caractères"),
+    ("error.content_length", "Le contenu doit comporter de 1 à 10000 caractères"),
+    ("error.sender_required", "Le paramètre sender est obligatoire"),
+    ("error.internal", "Erreur interne du serveur"),
+    ("error.database", "Erreur de base de données"),
+];
+
+fn catalog(locale: &str) -> &'static [(&'static str, &'static str)] {
+    match locale {
+        "es" => ES,
+        "de" => DE,
+        "fr" => FR,
+        _ => EN,
+    }
+}
+
+/// Map a language tag (`fr`, `fr-CA`, `DE_de`) to a supported locale.
+pub fn supported(tag: &str) -> Option<&'static str> {
+    let primary = tag.trim().split(['-', '_']).next()?.to_ascii_lowercase();
+    SUPPORTED.iter().copied().find(|l| *l == primary)
+}
+
+/// Server-wide fallback from `DEFAULT_LOCALE` (default: `en`).
+pub fn default_locale() -> &'static str {
+    env::var("DEFAULT_LOCALE")
+        .ok()
+        .and_then(|v| supported(&v))
+        .unwrap_or("en")
+}
+
+/// Pick the best supported locale from an `Accept-Language` header, honouring q-values.
+pub fn negotiate(accept_language: &str) -> Option<&'static str> {
+    let mut best: Option<(&'static str, f32)> = None;
+    for part in accept_language.split(',') {
+        let mut pieces = part.split(';');
+        let tag = pieces.next().unwrap_or("").trim();
+        let q = pieces
+            .find_map(|p| p.trim().strip_prefix("q=").and_then(|q| q.parse::<f32>().ok()))
+            .unwrap_or(1.0);
+        if q <= 0.0 {
+            continue;
+        }
+        let Some(locale) = supported(tag) else { continue };
+        if best.is_none_or(|(_, best_q)| q > best_q) {
+            best = Some((locale, q));
+        }
+    }
+    best.map(|(l, _)| l)
+}
+
+/// Look up `key`, falling back to English.
+pub fn lookup(locale: &str, key: &str) -> Option<&'static str> {
+    let find = |cat: &'static [(&'static str, &'static str)]| cat.iter().find(|(k, _)| *k == key).map(|(_, v)| *v);
+    find(catalog(locale)).or_else(|| find(EN))
+}
+
+/// Render `key` with `{name}` placeholders filled from the fields of `args`.
+pub fn render(locale: &str, key: &str, args: &serde_json::Value) -> Option<String> {
+    let mut text = lookup(locale, key)?.to_string();
+    if let Some(fields) = args.as_object() {
+        for (name, value) in fields {
+            let value = match value {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            text = text.replace(&format!("{{{name}}}"), &value);
+        }
+    }
+    Some(text)
+}
+
+/// Text for a system message `event`, or `None` if the event has no catalog entry.
+pub fn system_text(locale: &str, event: &str, details: &serde_json::Value) -> Option<String> {
+    if event.starts_with("error.") {
+        return None;
+    }
+    let singular = format!("{event}_one");
+    let key = if details.get("pruned").and_then(|v| v.as_i64()) == Some(1) && lookup(locale, &singular).is_some() {
+        singular.as_str()
+    } else {
+        event
+    };
+    render(locale, key, details)
+}
+
+/// Re-render a stored system message in `locale`. Other messages are left alone.
+pub fn localize_message(msg: &mut Message, locale: &str) {
+    if msg.kind != "system" {
+        return;
+    }
+    let Some(event) = msg.metadata.get("event").and_then(|e| e.as_str()) else { return };
+    if let Some(text) = system_text(locale, event, &msg.metadata) {
+        msg.content = text;
+    }
+}
+
+/// Catalog key for an English error string emitted by a handler.
+pub fn error_key(english: &str) -> Option<&'static str> {
+    EN.iter()
+        .find(|(k, v)| k.starts_with("error.") && *v == english)
+        .map(|(k, _)| *k)
+}
+
+/// Negotiate the locale for a request (see the module docs for precedence).
+pub fn request_locale(req: &Request<'_>) -> &'static str {
+    if let Some(locale) = req.headers().get_one("Accept-Language").and_then(negotiate) {
+        return locale;
+    }
+    let sender = ["sender", "reader"]
+        .iter()
+        .find_map(|name| req.query_value::<&str>(name).and_then(|v| v.ok()));
+    if let Some(sender) = sender
+        && let Some(db) = ScopedDb::of(req)
+    {
+        let locale: Option<String> = db
+            .conn()
+            .query_row(
+                "SELECT locale FROM profiles WHERE sender = ?1",
+                params![sender.trim()],
+                |r| r.get(0),
+            )
+            .ok()
+            .flatten();
+        if let Some(locale) = locale.as_deref().and_then(supported) {
+            return locale;
+        }
+    }
+    default_locale()
+}
+
+/// The negotiated locale for this request. Never fails.
+#[derive(Debug, Clone, Copy)]
+pub struct Locale(pub &'static str);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Locale {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(Locale(request_locale(req)))
+    }
+}
+
+/// Translates JSON error bodies whose `error` string is in the catalog and adds `error_code`.
+pub struct LocalizeErrors;
+
+#[rocket::async_trait]
+impl Fairing for LocalizeErrors {
+    fn info(&self) -> Info {
+        Info {
+            name: "Localize Errors",
+            kind: Kind::Response,
+        }
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        if res.status().code < 400 || res.content_type().is_none_or(|ct| !ct.is_json()) {
+            return;
+        }
+        let Ok(body) = res.body_mut().to_string().await else { return };
+        let rewritten = serde_json::from_str::<serde_json::Value>(&body).ok().and_then(|mut value| {
+            let key = value.get("error").and_then(|e| e.as_str()).and_then(error_key)?;
+            let locale = request_locale(req);
+            value["error"] = serde_json::json!(lookup(locale, key)?);
+            value["error_code"] = serde_json::json!(key.trim_start_matches("error."));
+            Some((value.to_string(), locale))
+        });
+        match rewritten {
+            Some((text, locale)) => {
+                res.set_header(Header::new("Content-Language", locale));
+                res.set_sized_body(text.len(), Cursor::new(text));
+            }
+            None => res.set_sized_body(body.len(), Cursor::new(body)),
+        }
+    }
+}
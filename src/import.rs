@@ -0,0 +1,693 @@
+//! Import of chat history from other tools' export archives (`POST /api/v1/admin/import`).
+//!
+//! - **Slack**: the workspace export zip — `users.json`, `channels.json` (plus `groups.json` for
+//!   private channels) and a directory of daily `YYYY-MM-DD.json` files per channel. Slack exports
+//!   only link to attached files; the bytes are imported when the archive also carries them under
+//!   `__uploads/<file id>/<name>`, otherwise the link is kept in the message metadata.
+//! - **Discord**: a zip of per-channel JSON exports in the DiscordChatExporter format. Attachments
+//!   whose `url` is a path inside the archive (exports made with `--media`) are imported as files.
+//!
+//! Channels become new rooms (a `-2`, `-3`, … suffix avoids name clashes), users become senders
+//! with profiles, thread replies keep their `reply_to`, and every message's metadata records where
+//! it came from. Imports run on a background thread, one transaction per channel, and report
+//! progress through `import_jobs`. Imported history is not replayed as events.
+
+use crate::models::{ImportJob, ImportedRoom};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::io::{Cursor, Read};
+
+/// Accepted `format` values.
+pub const FORMATS: [&str; 2] = ["slack", "discord"];
+
+/// Largest single archive entry that will be read (guards against zip bombs).
+const MAX_ENTRY_BYTES: u64 = 100 * 1024 * 1024;
+
+/// Largest accepted archive upload (`IMPORT_MAX_BYTES`, default 512 MiB).
+pub fn max_archive_bytes() -> u64 {
+    std::env::var("IMPORT_MAX_BYTES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(512 * 1024 * 1024)
+}
+
+pub type Archive = zip::ZipArchive<Cursor<Vec<u8>>>;
+
+/// Open an uploaded archive, checking that it is a zip that looks like a `format` export.
+pub fn open_archive(format: &str, bytes: Vec<u8>) -> Result<Archive, String> {
+    let zip = zip::ZipArchive::new(Cursor::new(bytes)).map_err(|e| format!("Not a valid zip archive: {e}"))?;
+    let looks_right = match format {
+        "slack" => zip.file_names().any(|n| n.ends_with("channels.json") || n.ends_with("groups.json")),
+        _ => zip.file_names().any(|n| n.ends_with(".json")),
+    };
+    if !looks_right {
+        return Err(match format {
+            "slack" => "Archive has no channels.json; expected a Slack workspace export".to_string(),
+            _ => "Archive has no .json channel exports; expected a DiscordChatExporter JSON export".to_string(),
+        });
+    }
+    Ok(zip)
+}
+
+// --- Parsed export ---
+
+struct SourceProfile {
+    sender: String,
+    display_name: Option<String>,
+    sender_type: &'static str,
+    avatar_url: Option<String>,
+    bio: Option<String>,
+}
+
+struct SourceChannel {
+    name: String,
+    description: String,
+    created_at: Option<DateTime<Utc>>,
+    messages: Vec<SourceMessage>,
+}
+
+struct SourceMessage {
+    source_id: String,
+    /// Source id of the message this one replies to
+    reply_to: Option<String>,
+    sender: String,
+    sender_type: &'static str,
+    content: String,
+    created_at: DateTime<Utc>,
+    edited_at: Option<DateTime<Utc>>,
+    attachments: Vec<SourceAttachment>,
+}
+
+struct SourceAttachment {
+    filename: String,
+    content_type: Option<String>,
+    /// Entry holding the bytes, when the archive has them
+    path: Option<String>,
+    url: Option<String>,
+}
+
+struct Export {
+    profiles: Vec<SourceProfile>,
+    channels: Vec<SourceChannel>,
+}
+
+fn clip(s: &str, max_chars: usize) -> String {
+    s.chars().take(max_chars).collect()
+}
+
+fn non_empty(s: Option<&str>) -> Option<String> {
+    s.map(str::trim).filter(|s| !s.is_empty()).map(str::to_string)
+}
+
+fn read_entry(zip: &mut Archive, name: &str) -> Option<Vec<u8>> {
+    let entry = zip.by_name(name).ok()?;
+    let mut data = Vec::new();
+    entry.take(MAX_ENTRY_BYTES).read_to_end(&mut data).ok()?;
+    Some(data)
+}
+
+fn read_json(zip: &mut Archive, name: &str) -> Result<Value, String> {
+    let data = read_entry(zip, name).ok_or_else(|| format!("Could not read {name}"))?;
+    serde_json::from_slice(&data).map_err(|e| format!("{name}: invalid JSON: {e}"))
+}
+
+// --- Slack ---
+
+/// Slack timestamps are `"<unix seconds>.<microseconds>"`.
+fn slack_time(ts: &str) -> Option<DateTime<Utc>> {
+    let (secs, frac) = ts.split_once('.').unwrap_or((ts, "0"));
+    let micros: u32 = format!("{frac:0<6}").get(..6)?.parse().ok()?;
+    DateTime::from_timestamp(secs.parse().ok()?, micros * 1000)
+}
+
+/// Turn Slack's `<...>` markup into plain text: user and channel references become `@name` and
+/// `#name`, `<!here>`-style broadcasts become `@here`, and links keep their label.
+fn slack_text(text: &str, users: &HashMap<String, (String, &'static str)>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        let Some(len) = rest[start..].find('>') else { break };
+        out.push_str(&rest[..start]);
+        let inner = &rest[start + 1..start + len];
+        let (target, label) = match inner.split_once('|') {
+            Some((target, label)) => (target, Some(label)),
+            None => (inner, None),
+        };
+        let replaced = if let Some(id) = target.strip_prefix('@') {
+            format!("@{}", users.get(id).map(|u| u.0.as_str()).or(label).unwrap_or(id))
+        } else if let Some(id) = target.strip_prefix('#') {
+            format!("#{}", label.unwrap_or(id))
+        } else if let Some(special) = target.strip_prefix('!') {
+            match special {
+                "here" | "channel" | "everyone" => format!("@{special}"),
+                _ => label.unwrap_or(special).to_string(),
+            }
+        } else {
+            match label {
+                Some(label) if label != target => format!("{label} ({target})"),
+                _ => target.to_string(),
+            }
+        };
+        out.push_str(&replaced);
+        rest = &rest[start + len + 1..];
+    }
+    out.push_str(rest);
+    out.replace("&lt;", "<").replace("&gt;", ">").replace("&amp;", "&")
+}
+
+fn slack_message(
+    m: &Value,
+    users: &HashMap<String, (String, &'static str)>,
+    uploads: &HashMap<String, String>,
+) -> Option<SourceMessage> {
+    if m["type"].as_str().is_some_and(|t| t != "message") {
+        return None;
+    }
+    if matches!(
+        m["subtype"].as_str(),
+        Some("channel_join" | "channel_leave" | "group_join" | "group_leave")
+    ) {
+        return None;
+    }
+    let ts = m["ts"].as_str()?;
+    let created_at = slack_time(ts)?;
+    let (sender, sender_type) = match m["user"].as_str() {
+        Some(id) if m["bot_id"].is_null() => users.get(id).cloned().unwrap_or_else(|| (id.to_string(), "human")),
+        _ => {
+            let name = m["username"]
+                .as_str()
+                .or(m["bot_profile"]["name"].as_str())
+                .or(m["user"].as_str())
+                .or(m["bot_id"].as_str())
+                .unwrap_or("unknown");
+            (clip(name, 100), "agent")
+        }
+    };
+    let attachments: Vec<SourceAttachment> = m["files"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|f| {
+            let filename = f["name"].as_str().or(f["title"].as_str())?;
+            Some(SourceAttachment {
+                filename: clip(filename, 255),
+                content_type: f["mimetype"].as_str().map(str::to_string),
+                path: f["id"].as_str().and_then(|id| uploads.get(id).cloned()),
+                url: f["url_private"].as_str().map(str::to_string),
+            })
+        })
+        .collect();
+    let mut content = slack_text(m["text"].as_str().unwrap_or(""), users).trim().to_string();
+    if content.is_empty() {
+        if attachments.is_empty() {
+            return None;
+        }
+        content = attachments.iter().map(|a| a.filename.as_str()).collect::<Vec<_>>().join(", ");
+    }
+    Some(SourceMessage {
+        source_id: ts.to_string(),
+        reply_to: m["thread_ts"].as_str().filter(|t| *t != ts).map(str::to_string),
+        sender,
+        sender_type,
+        content,
+        created_at,
+        edited_at: m["edited"]["ts"].as_str().and_then(slack_time),
+        attachments,
+    })
+}
+
+fn parse_slack(zip: &mut Archive) -> Result<Export, String> {
+    let names: Vec<String> = zip.file_names().map(str::to_string).collect();
+    // Re-zipped exports often nest everything under one top-level folder
+    let root = names
+        .iter()
+        .filter_map(|n| n.strip_suffix("channels.json").or_else(|| n.strip_suffix("groups.json")))
+        .find(|prefix| prefix.is_empty() || prefix.ends_with('/'))
+        .unwrap_or("")
+        .to_string();
+
+    let mut users: HashMap<String, (String, &'static str)> = HashMap::new();
+    let mut profiles = Vec::new();
+    let users_path = format!("{root}users.json");
+    if names.contains(&users_path) {
+        for u in read_json(zip, &users_path)?.as_array().into_iter().flatten() {
+            let (Some(id), Some(handle)) = (u["id"].as_str(), u["name"].as_str()) else {
+                continue;
+            };
+            let sender = clip(handle, 100);
+            let sender_type = if u["is_bot"].as_bool() == Some(true) { "agent" } else { "human" };
+            users.insert(id.to_string(), (sender.clone(), sender_type));
+            let p = &u["profile"];
+            profiles.push(SourceProfile {
+                sender,
+                display_name: non_empty(p["display_name"].as_str())
+                    .or_else(|| non_empty(p["real_name"].as_str()))
+                    .or_else(|| non_empty(u["real_name"].as_str())),
+                sender_type,
+                avatar_url: non_empty(p["image_192"].as_str()).or_else(|| non_empty(p["image_72"].as_str())),
+                bio: non_empty(p["title"].as_str()),
+            });
+        }
+    }
+
+    // __uploads/<file id>/<name>
+    let uploads_dir = format!("{root}__uploads/");
+    let uploads: HashMap<String, String> = names
+        .iter()
+        .filter_map(|n| {
+            let (id, file) = n.strip_prefix(&uploads_dir)?.split_once('/')?;
+            (!file.is_empty()).then(|| (id.to_string(), n.clone()))
+        })
+        .collect();
+
+    let mut listed = Vec::new();
+    for list in ["channels.json", "groups.json"] {
+        let path = format!("{root}{list}");
+        if names.contains(&path)
+            && let Some(channels) = read_json(zip, &path)?.as_array()
+        {
+            listed.extend(channels.iter().cloned());
+        }
+    }
+
+    let mut channels = Vec::new();
+    for c in &listed {
+        let Some(name) = c["name"].as_str() else { continue };
+        let dir = format!("{root}{name}/");
+        let mut days: Vec<&String> = names
+            .iter()
+            .filter(|n| n.starts_with(&dir) && n.ends_with(".json") && !n[dir.len()..].contains('/'))
+            .collect();
+        days.sort();
+        let mut messages = Vec::new();
+        for day in days {
+            for m in read_json(zip, day)?.as_array().into_iter().flatten() {
+                messages.extend(slack_message(m, &users, &uploads));
+            }
+        }
+        messages.sort_by_key(|m| m.created_at);
+        channels.push(SourceChannel {
+            name: name.to_string(),
+            description: non_empty(c["purpose"]["value"].as_str())
+                .or_else(|| non_empty(c["topic"]["value"].as_str()))
+                .unwrap_or_default(),
+            created_at: c["created"].as_i64().and_then(|secs| DateTime::from_timestamp(secs, 0)),
+            messages,
+        });
+    }
+    Ok(Export { profiles, channels })
+}
+
+// --- Discord (DiscordChatExporter JSON) ---
+
+fn parse_discord_time(value: &Value) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value.as_str()?).ok().map(|t| t.with_timezone(&Utc))
+}
+
+fn parse_discord(zip: &mut Archive) -> Result<Export, String> {
+    let names: Vec<String> = zip.file_names().map(str::to_string).collect();
+    let entries: HashSet<&str> = names.iter().map(String::as_str).collect();
+    let mut files: Vec<&String> = names.iter().filter(|n| n.ends_with(".json")).collect();
+    files.sort();
+
+    let mut profiles = Vec::new();
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut channels = Vec::new();
+    for path in files {
+        let doc = read_json(zip, path)?;
+        // Anything that isn't a channel export (e.g. a stray metadata file) is skipped
+        let (Some(channel), Some(raw)) = (doc["channel"].as_object(), doc["messages"].as_array()) else {
+            continue;
+        };
+        let Some(name) = channel.get("name").and_then(Value::as_str) else { continue };
+        // Media paths are relative to the export file
+        let base = path.rsplit_once('/').map(|(dir, _)| format!("{dir}/")).unwrap_or_default();
+
+        let mut messages = Vec::new();
+        for m in raw {
+            if !matches!(m["type"].as_str(), Some("Default" | "Reply") | None) {
+                continue;
+            }
+            let (Some(id), Some(created_at)) = (m["id"].as_str(), parse_discord_time(&m["timestamp"])) else {
+                continue;
+            };
+            let author = &m["author"];
+            let sender = clip(author["name"].as_str().unwrap_or("unknown"), 100);
+            let sender_type = if author["isBot"].as_bool() == Some(true) { "agent" } else { "human" };
+            if seen.insert(sender.clone()) {
+                profiles.push(SourceProfile {
+                    sender: sender.clone(),
+                    display_name: non_empty(author["nickname"].as_str()),
+                    sender_type,
+                    avatar_url: non_empty(author["avatarUrl"].as_str()).filter(|u| u.starts_with("http")),
+                    bio: None,
+                });
+            }
+
+            let mut content = m["content"].as_str().unwrap_or("").to_string();
+            for mention in m["mentions"].as_array().into_iter().flatten() {
+                if let (Some(mid), Some(mname)) = (mention["id"].as_str(), mention["name"].as_str()) {
+                    content = content
+                        .replace(&format!("<@{mid}>"), &format!("@{mname}"))
+                        .replace(&format!("<@!{mid}>"), &format!("@{mname}"));
+                }
+            }
+            let attachments: Vec<SourceAttachment> = m["attachments"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|a| {
+                    let url = a["url"].as_str()?;
+                    let local = (!url.starts_with("http://") && !url.starts_with("https://"))
+                        .then(|| format!("{base}{url}"))
+                        .filter(|p| entries.contains(p.as_str()));
+                    Some(SourceAttachment {
+                        filename: clip(a["fileName"].as_str().unwrap_or("attachment"), 255),
+                        content_type: None,
+                        url: local.is_none().then(|| url.to_string()),
+                        path: local,
+                    })
+                })
+                .collect();
+            let mut content = content.trim().to_string();
+            if content.is_empty() {
+                if attachments.is_empty() {
+                    continue;
+                }
+                content = attachments.iter().map(|a| a.filename.as_str()).collect::<Vec<_>>().join(", ");
+            }
+            messages.push(SourceMessage {
+                source_id: id.to_string(),
+                reply_to: m["reference"]["messageId"].as_str().map(str::to_string),
+                sender,
+                sender_type,
+                content,
+                created_at,
+                edited_at: parse_discord_time(&m["timestampEdited"]),
+                attachments,
+            });
+        }
+        messages.sort_by_key(|m| m.created_at);
+        channels.push(SourceChannel {
+            name: name.to_string(),
+            description: channel.get("topic").and_then(Value::as_str).unwrap_or("").trim().to_string(),
+            created_at: None,
+            messages,
+        });
+    }
+    Ok(Export { profiles, channels })
+}
+
+// --- Writing ---
+
+/// A name for the new room: the channel's own, or with the first free `-N` suffix.
+fn unique_room_name(conn: &Connection, source: &str) -> String {
+    let base = clip(source.trim().trim_start_matches('#'), 90);
+    let base = if base.is_empty() { "imported".to_string() } else { base };
+    let taken = |name: &str| {
+        conn.query_row("SELECT 1 FROM rooms WHERE name = ?1", params![name], |_| Ok(()))
+            .is_ok()
+    };
+    if !taken(&base) {
+        return base;
+    }
+    let mut n = 2;
+    loop {
+        let name = format!("{base}-{n}");
+        if !taken(&name) {
+            return name;
+        }
+        n += 1;
+    }
+}
+
+fn import_profiles(conn: &mut Connection, profiles: &[SourceProfile]) -> rusqlite::Result<usize> {
+    let now = Utc::now().to_rfc3339();
+    let tx = conn.transaction()?;
+    let mut created = 0;
+    for p in profiles {
+        created += tx.execute(
+            "INSERT OR IGNORE INTO profiles (sender, display_name, sender_type, avatar_url, bio, metadata, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, '{}', ?6, ?6)",
+            params![&p.sender, &p.display_name, p.sender_type, &p.avatar_url, &p.bio, &now],
+        )?;
+    }
+    tx.commit()?;
+    Ok(created)
+}
+
+struct ChannelResult {
+    room: ImportedRoom,
+    files: i64,
+    missing: i64,
+}
+
+fn import_channel(
+    conn: &mut Connection,
+    format: &str,
+    created_by: &str,
+    channel: &SourceChannel,
+    zip: &mut Archive,
+) -> rusqlite::Result<ChannelResult> {
+    let tx = conn.transaction()?;
+    let now = Utc::now();
+    let first = channel.messages.first().map(|m| m.created_at);
+    let last = channel.messages.last().map(|m| m.created_at);
+    let room_id = uuid::Uuid::new_v4().to_string();
+    let name = unique_room_name(&tx, &channel.name);
+    let admin_key = crate::db::generate_admin_key();
+    tx.execute(
+        "INSERT INTO rooms (id, name, description, created_by, created_at, updated_at, admin_key)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            &room_id,
+            &name,
+            &channel.description,
+            created_by,
+            channel.created_at.or(first).unwrap_or(now).to_rfc3339(),
+            last.unwrap_or(now).to_rfc3339(),
+            &admin_key
+        ],
+    )?;
+    tx.execute("DELETE FROM room_name_aliases WHERE name = ?1", params![&name])?;
+
+    let mut seq: i64 = tx.query_row("SELECT COALESCE(MAX(seq), 0) FROM messages", [], |r| r.get(0))?;
+    let mut ids: HashMap<&str, String> = HashMap::new();
+    let (mut files, mut missing) = (0, 0);
+    for m in &channel.messages {
+        let id = uuid::Uuid::new_v4().to_string();
+        let created_at = m.created_at.to_rfc3339();
+        let mut file_ids = Vec::new();
+        let mut links = Vec::new();
+        for a in &m.attachments {
+            match a.path.as_deref().and_then(|p| read_entry(zip, p)).filter(|d| !d.is_empty()) {
+                Some(data) => {
+                    let content_type = a
+                        .content_type
+                        .clone()
+                        .or_else(|| crate::uploads::sniff(&data).map(str::to_string))
+                        .unwrap_or_else(|| "application/octet-stream".to_string());
+                    let file_id = uuid::Uuid::new_v4().to_string();
+                    let sha256 = crate::db::store_file_blob(&tx, &data)?;
+                    tx.execute(
+                        "INSERT INTO files (id, room_id, sender, filename, content_type, size, data, created_at, sha256) VALUES (?1, ?2, ?3, ?4, ?5, ?6, x'', ?7, ?8)",
+                        params![&file_id, &room_id, &m.sender, &a.filename, &content_type, data.len() as i64, &created_at, &sha256],
+                    )?;
+                    file_ids.push(file_id);
+                    files += 1;
+                }
+                None => {
+                    links.push(serde_json::json!({"filename": a.filename, "url": a.url}));
+                    missing += 1;
+                }
+            }
+        }
+
+        let mut metadata = serde_json::json!({"source": format, "source_id": m.source_id});
+        if !file_ids.is_empty() {
+            metadata["attachments"] = serde_json::json!(file_ids);
+        }
+        if !links.is_empty() {
+            metadata["missing_attachments"] = Value::Array(links);
+        }
+        // Replies to messages outside the export (or skipped ones) import as top-level messages
+        let reply_to = m.reply_to.as_deref().and_then(|r| ids.get(r)).cloned();
+        seq += 1;
+        tx.execute(
+            "INSERT INTO messages (id, room_id, sender, content, metadata, created_at, edited_at, reply_to, sender_type, seq)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                &id,
+                &room_id,
+                &m.sender,
+                &m.content,
+                metadata.to_string(),
+                &created_at,
+                m.edited_at.map(|t| t.to_rfc3339()),
+                &reply_to,
+                m.sender_type,
+                seq
+            ],
+        )?;
+        crate::db::upsert_fts(&tx, &id);
+        crate::db::index_mentions(&tx, &id);
+        ids.insert(&m.source_id, id);
+    }
+    tx.commit()?;
+    Ok(ChannelResult {
+        room: ImportedRoom {
+            source: channel.name.clone(),
+            room_id,
+            name,
+            admin_key,
+            messages: channel.messages.len() as i64,
+        },
+        files,
+        missing,
+    })
+}
+
+fn import(conn: &mut Connection, job_id: &str, format: &str, created_by: &str, zip: &mut Archive) -> Result<(), String> {
+    let export = match format {
+        "slack" => parse_slack(zip)?,
+        _ => parse_discord(zip)?,
+    };
+    let db_err = |e: rusqlite::Error| format!("Database error: {e}");
+    let profiles = import_profiles(conn, &export.profiles).map_err(db_err)?;
+    conn.execute(
+        "UPDATE import_jobs SET rooms_total = ?1, profiles_imported = ?2 WHERE id = ?3",
+        params![export.channels.len() as i64, profiles as i64, job_id],
+    )
+    .map_err(db_err)?;
+
+    let mut rooms: Vec<ImportedRoom> = Vec::new();
+    let (mut messages, mut files, mut missing) = (0, 0, 0);
+    for channel in &export.channels {
+        let result = import_channel(conn, format, created_by, channel, zip)
+            .map_err(|e| format!("Importing #{}: {e}", channel.name))?;
+        messages += result.room.messages;
+        files += result.files;
+        missing += result.missing;
+        rooms.push(result.room);
+        conn.execute(
+            "UPDATE import_jobs SET rooms_done = ?1, messages_imported = ?2, files_imported = ?3, attachments_missing = ?4, rooms = ?5
+             WHERE id = ?6",
+            params![
+                rooms.len() as i64,
+                messages,
+                files,
+                missing,
+                serde_json::to_string(&rooms).unwrap_or_else(|_| "[]".to_string()),
+                job_id
+            ],
+        )
+        .map_err(db_err)?;
+    }
+    Ok(())
+}
+
+/// Run a queued import to the end on the calling thread, recording progress and the outcome
+/// in its `import_jobs` row. Channels committed before a failure stay imported.
+pub fn run_job(db_path: &str, job_id: &str, mut zip: Archive) {
+    let mut conn = match crate::db::open(db_path) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("⚠️ Import {job_id}: failed to open DB: {e}");
+            return;
+        }
+    };
+    crate::db::DbConfig::from_env().apply(&conn).ok();
+    let Some(job) = load_job(&conn, job_id) else { return };
+    conn.execute(
+        "UPDATE import_jobs SET status = 'running', started_at = ?1 WHERE id = ?2",
+        params![Utc::now().to_rfc3339(), job_id],
+    )
+    .ok();
+    let result = import(&mut conn, job_id, &job.format, &job.created_by, &mut zip);
+    let finished_at = Utc::now().to_rfc3339();
+    match result {
+        Ok(()) => {
+            conn.execute(
+                "UPDATE import_jobs SET status = 'completed', finished_at = ?1 WHERE id = ?2",
+                params![&finished_at, job_id],
+            )
+            .ok();
+        }
+        Err(e) => {
+            eprintln!("⚠️ Import {job_id} failed: {e}");
+            conn.execute(
+                "UPDATE import_jobs SET status = 'failed', error = ?1, finished_at = ?2 WHERE id = ?3",
+                params![&e, &finished_at, job_id],
+            )
+            .ok();
+        }
+    }
+}
+
+// --- Jobs ---
+
+const JOB_COLUMNS: &str = "id, format, status, created_by, archive_size, rooms_total, rooms_done, messages_imported, \
+     files_imported, attachments_missing, profiles_imported, rooms, error, created_at, started_at, finished_at";
+
+fn job_from_row(r: &rusqlite::Row) -> rusqlite::Result<ImportJob> {
+    let rooms: String = r.get(11)?;
+    Ok(ImportJob {
+        id: r.get(0)?,
+        format: r.get(1)?,
+        status: r.get(2)?,
+        created_by: r.get(3)?,
+        archive_size: r.get(4)?,
+        rooms_total: r.get(5)?,
+        rooms_done: r.get(6)?,
+        messages_imported: r.get(7)?,
+        files_imported: r.get(8)?,
+        attachments_missing: r.get(9)?,
+        profiles_imported: r.get(10)?,
+        rooms: serde_json::from_str(&rooms).unwrap_or_default(),
+        error: r.get(12)?,
+        created_at: r.get(13)?,
+        started_at: r.get(14)?,
+        finished_at: r.get(15)?,
+    })
+}
+
+pub fn create_job(conn: &Connection, format: &str, created_by: &str, archive_size: i64) -> rusqlite::Result<ImportJob> {
+    let id = uuid::Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT INTO import_jobs (id, format, created_by, archive_size, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![&id, format, created_by, archive_size, Utc::now().to_rfc3339()],
+    )?;
+    load_job(conn, &id).ok_or(rusqlite::Error::QueryReturnedNoRows)
+}
+
+pub fn load_job(conn: &Connection, id: &str) -> Option<ImportJob> {
+    conn.query_row(
+        &format!("SELECT {JOB_COLUMNS} FROM import_jobs WHERE id = ?1"),
+        params![id],
+        job_from_row,
+    )
+    .ok()
+}
+
+/// Every import, newest first.
+pub fn list_jobs(conn: &Connection) -> Vec<ImportJob> {
+    conn.prepare(&format!("SELECT {JOB_COLUMNS} FROM import_jobs ORDER BY created_at DESC"))
+        .and_then(|mut s| {
+            s.query_map([], job_from_row)
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .unwrap_or_default()
+}
+
+/// Imports still queued or running when the server stopped will never finish; mark them failed.
+pub fn fail_interrupted(conn: &Connection) {
+    conn.execute(
+        "UPDATE import_jobs SET status = 'failed', error = 'Server restarted before the import finished', finished_at = ?1
+         WHERE status IN ('queued', 'running')",
+        params![Utc::now().to_rfc3339()],
+    )
+    .ok();
+}
@@ -0,0 +1,226 @@
+//! Network access policy on top of the bind address: allow and deny lists of IPs and CIDR
+//! ranges, kept separately for reads, writes and the admin API, so an instance can be readable
+//! from the whole LAN but writable only from the agent hosts. Refused requests get a 403
+//! before any route runs.
+
+use std::env;
+use std::net::IpAddr;
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::uri::Origin;
+use rocket::http::{Method, Status};
+use rocket::request::{FromRequest, Outcome};
+use rocket::{Data, Request};
+
+/// Where refused requests are rerouted; only reachable through [`IpPolicyFairing`].
+pub const DENIED_PATH: &str = "/api/v1/ip-policy/denied";
+
+/// An IP address or CIDR range (`10.0.0.0/8`, `fd00::/8`, `192.168.1.20`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpRange {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl IpRange {
+    pub fn parse(entry: &str) -> Option<Self> {
+        let entry = entry.trim();
+        let (addr, prefix) = match entry.split_once('/') {
+            Some((addr, prefix)) => (addr.trim().parse::<IpAddr>().ok()?, Some(prefix.trim().parse::<u8>().ok()?)),
+            None => (entry.parse::<IpAddr>().ok()?, None),
+        };
+        let addr = addr.to_canonical();
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = prefix.unwrap_or(max);
+        (prefix <= max).then_some(IpRange { addr, prefix })
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.addr, ip.to_canonical()) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Allow and deny lists for one kind of request. A listed deny always wins; a non-empty allow
+/// list admits only the addresses on it.
+#[derive(Debug, Clone, Default)]
+pub struct IpRules {
+    pub allow: Vec<IpRange>,
+    pub deny: Vec<IpRange>,
+}
+
+impl IpRules {
+    pub fn permits(&self, ip: Option<IpAddr>) -> bool {
+        if self.allow.is_empty() && self.deny.is_empty() {
+            return true;
+        }
+        // An unknown peer can't be on an allow list, and can't be vouched for against a deny list
+        let Some(ip) = ip else {
+            return false;
+        };
+        !self.deny.iter().any(|r| r.contains(ip)) && (self.allow.is_empty() || self.allow.iter().any(|r| r.contains(ip)))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.allow.is_empty() && self.deny.is_empty()
+    }
+}
+
+/// What a request is checked against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+    Admin,
+}
+
+impl Access {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Access::Read => "read",
+            Access::Write => "write",
+            Access::Admin => "admin",
+        }
+    }
+
+    /// `/api/v1/admin/*` is admin whatever the method; other POST, PUT, PATCH and DELETE are
+    /// writes; everything else (including the frontend and SSE streams) is a read.
+    fn of(method: Method, path: &str) -> Self {
+        if path.starts_with("/api/v1/admin/") || path == "/api/v1/admin" {
+            Access::Admin
+        } else if matches!(method, Method::Post | Method::Put | Method::Patch | Method::Delete) {
+            Access::Write
+        } else {
+            Access::Read
+        }
+    }
+}
+
+/// The server's IP policy. Empty (the default) lets everything through.
+#[derive(Debug, Clone, Default)]
+pub struct IpPolicy {
+    pub read: IpRules,
+    pub write: IpRules,
+    pub admin: IpRules,
+    /// Take the client address from the last `X-Forwarded-For` hop (the one the reverse proxy
+    /// added) instead of the socket. Only turn on behind a proxy that sets it.
+    pub trust_proxy: bool,
+}
+
+fn ranges_from_env(name: &str) -> Vec<IpRange> {
+    let Ok(list) = env::var(name) else {
+        return Vec::new();
+    };
+    list.split(',')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .filter_map(|entry| {
+            let range = IpRange::parse(entry);
+            if range.is_none() {
+                eprintln!("⚠️ Ignoring invalid {name} entry '{entry}' (expected an IP or CIDR range)");
+            }
+            range
+        })
+        .collect()
+}
+
+impl IpPolicy {
+    /// `IP_{READ,WRITE,ADMIN}_{ALLOW,DENY}` (comma-separated IPs and CIDR ranges) and
+    /// `IP_POLICY_TRUST_PROXY`.
+    pub fn from_env() -> Self {
+        let rules = |kind: &str| IpRules {
+            allow: ranges_from_env(&format!("IP_{kind}_ALLOW")),
+            deny: ranges_from_env(&format!("IP_{kind}_DENY")),
+        };
+        IpPolicy {
+            read: rules("READ"),
+            write: rules("WRITE"),
+            admin: rules("ADMIN"),
+            trust_proxy: env::var("IP_POLICY_TRUST_PROXY").is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true")),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.read.is_empty() && self.write.is_empty() && self.admin.is_empty()
+    }
+
+    /// The address the policy is checked against.
+    pub fn client_ip(&self, req: &Request<'_>) -> Option<IpAddr> {
+        if self.trust_proxy
+            && let Some(hop) = req
+                .headers()
+                .get_one("X-Forwarded-For")
+                .and_then(|h| h.rsplit(',').next())
+                .and_then(|ip| ip.trim().parse().ok())
+        {
+            return Some(hop);
+        }
+        req.remote().map(|r| r.ip())
+    }
+
+    /// Whether `ip` may make a request of this kind; each kind is checked against its own lists.
+    pub fn permits(&self, access: Access, ip: Option<IpAddr>) -> bool {
+        match access {
+            Access::Read => self.read.permits(ip),
+            Access::Write => self.write.permits(ip),
+            Access::Admin => self.admin.permits(ip),
+        }
+    }
+}
+
+/// Marks a request the fairing rerouted to [`DENIED_PATH`], with what it was refused.
+#[derive(Debug, Clone)]
+pub struct IpDenied {
+    pub access: Access,
+    pub ip: Option<IpAddr>,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for IpDenied {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match req.local_cache(|| None::<IpDenied>) {
+            Some(denied) => Outcome::Success(denied.clone()),
+            None => Outcome::Forward(Status::NotFound),
+        }
+    }
+}
+
+/// Fairing that reroutes requests the [`IpPolicy`] refuses to [`DENIED_PATH`]. Attach after the
+/// fairings that rewrite paths (namespaces, API versions) so admin routes are recognized, and
+/// before maintenance mode so a refused write is a 403 rather than a 503.
+pub struct IpPolicyFairing;
+
+#[rocket::async_trait]
+impl Fairing for IpPolicyFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "IP Policy",
+            kind: Kind::Request,
+        }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _data: &mut Data<'_>) {
+        let Some(policy) = req.rocket().state::<IpPolicy>().filter(|p| !p.is_empty()) else {
+            return;
+        };
+        let access = Access::of(req.method(), req.uri().path().as_str());
+        let ip = policy.client_ip(req);
+        if policy.permits(access, ip) {
+            return;
+        }
+        req.local_cache(|| Some(IpDenied { access, ip }));
+        req.set_method(Method::Get);
+        req.set_uri(Origin::parse(DENIED_PATH).expect("valid denied path"));
+    }
+}
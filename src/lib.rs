@@ -1,51 +1,166 @@
+pub mod admin_lockout;
+pub mod capabilities;
 pub mod db;
+pub mod email;
+pub mod emoji;
 pub mod events;
+pub mod fields;
+pub mod i18n;
+pub mod import;
+pub mod ip_policy;
+pub mod maintenance;
+pub mod membership;
 pub mod mdns;
+pub mod migrations;
 pub mod models;
+pub mod namespaces;
+pub mod nudges;
+pub mod outbox;
+pub mod patch;
+pub mod provision;
+pub mod push;
+pub mod quiet_hours;
+pub mod quotas;
 pub mod rate_limit;
+pub mod redaction;
+pub mod redirects;
+pub mod regex_search;
+pub mod request_id;
+pub mod responses;
 pub mod retention;
 pub mod routes;
+pub mod scheduler;
+pub mod secrets;
+pub mod seed;
+pub mod senders;
+pub mod sessions;
+pub mod subscriptions;
+pub mod snapshots;
+pub mod sse;
+pub mod telemetry;
+pub mod timezones;
+pub mod uploads;
+pub mod versioning;
 pub mod webhooks;
 
-use db::Db;
+use db::{Db, DbConfig};
 use events::EventBus;
+use ip_policy::IpPolicy;
 use rate_limit::{RateLimitConfig, RateLimiter};
 use rocket::fs::{FileServer, Options};
 use rocket_cors::CorsOptions;
 use routes::{PresenceTracker, TypingTracker};
+use senders::SenderPolicy;
 use std::env;
 use std::path::PathBuf;
 
 pub fn rocket() -> rocket::Rocket<rocket::Build> {
     let db_path = env::var("DATABASE_PATH").unwrap_or_else(|_| "data/chat.db".to_string());
+    if env::var("DB_MIGRATE_DRY_RUN").is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true")) {
+        migrate_dry_run(&db_path);
+    }
     rocket_with_db(&db_path)
 }
 
+/// `DB_MIGRATE_DRY_RUN=true`: report the migrations startup would apply, roll them back, and exit
+/// (status 1 if any would fail or the database is newer than this build).
+fn migrate_dry_run(db_path: &str) -> ! {
+    let conn = match db::open(db_path) {
+        Ok(conn) => conn,
+        Err(e) => {
+            eprintln!("❌ Migration dry run: failed to open {db_path}: {e}");
+            std::process::exit(1);
+        }
+    };
+    match migrations::dry_run(&conn) {
+        Ok(would_apply) if would_apply.is_empty() => {
+            println!("🗄️ Migration dry run: {db_path} is up to date (schema version {})", migrations::latest_version());
+            std::process::exit(0);
+        }
+        Ok(would_apply) => {
+            println!("🗄️ Migration dry run: {} migration(s) would be applied to {db_path}:", would_apply.len());
+            for m in would_apply {
+                println!("   {:04}_{}", m.version, m.name);
+            }
+            std::process::exit(0);
+        }
+        Err(e) => {
+            eprintln!("❌ Migration dry run: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
 pub fn rocket_with_db_and_config(db_path: &str, rate_config: RateLimitConfig) -> rocket::Rocket<rocket::Build> {
-    build_rocket(db_path, rate_config)
+    build_rocket(db_path, rate_config, SenderPolicy::from_env(), namespaces::names_from_env(), IpPolicy::from_env())
+}
+
+pub fn rocket_with_db_and_sender_policy(db_path: &str, sender_policy: SenderPolicy) -> rocket::Rocket<rocket::Build> {
+    build_rocket(db_path, RateLimitConfig::from_env(), sender_policy, namespaces::names_from_env(), IpPolicy::from_env())
+}
+
+pub fn rocket_with_db_and_namespaces(db_path: &str, namespaces: Vec<String>) -> rocket::Rocket<rocket::Build> {
+    build_rocket(db_path, RateLimitConfig::from_env(), SenderPolicy::from_env(), namespaces, IpPolicy::from_env())
+}
+
+pub fn rocket_with_db_and_ip_policy(db_path: &str, ip_policy: IpPolicy) -> rocket::Rocket<rocket::Build> {
+    build_rocket(db_path, RateLimitConfig::from_env(), SenderPolicy::from_env(), namespaces::names_from_env(), ip_policy)
 }
 
 pub fn rocket_with_db(db_path: &str) -> rocket::Rocket<rocket::Build> {
     let rate_limit_config = RateLimitConfig::from_env();
-    build_rocket(db_path, rate_limit_config)
+    build_rocket(db_path, rate_limit_config, SenderPolicy::from_env(), namespaces::names_from_env(), IpPolicy::from_env())
 }
 
-fn build_rocket(db_path: &str, rate_limit_config: RateLimitConfig) -> rocket::Rocket<rocket::Build> {
+fn build_rocket(
+    db_path: &str,
+    rate_limit_config: RateLimitConfig,
+    sender_policy: SenderPolicy,
+    namespace_names: Vec<String>,
+    ip_policy: IpPolicy,
+) -> rocket::Rocket<rocket::Build> {
     // Ensure data directory exists
     if let Some(parent) = std::path::Path::new(db_path).parent() {
         std::fs::create_dir_all(parent).ok();
     }
 
-    let db = Db::new(db_path);
+    let db_config = DbConfig::from_env();
+    let db = Db::with_config(db_path, &db_config);
+    if db::encryption_enabled() {
+        println!("🔒 Database encrypted at rest (SQLCipher)");
+    }
+    import::fail_interrupted(&db.conn());
+    let regex_search_config = regex_search::RegexSearchConfig::from_env();
+    let capabilities =
+        capabilities::Capabilities::detect(&sender_policy, &regex_search_config, !namespace_names.is_empty());
+    let namespace_dbs = namespaces::Namespaces::new(namespace_names, db_path, &db_config);
+    // Background jobs run once per database: the main one and each namespace's
+    let databases = namespace_dbs.databases();
+    let secret_box = secrets::SecretBox::load(db_path).unwrap_or_else(|e| panic!("Failed to load secrets key: {e}"));
     let events = EventBus::new();
 
     // Subscribe webhook dispatcher BEFORE handing EventBus to Rocket
-    let webhook_receiver = events.sender.subscribe();
-    let webhook_db_path = db_path.to_string();
+    let webhook_receivers: Vec<_> = databases
+        .iter()
+        .map(|(namespace, path)| (events.sender.subscribe(), path.clone(), namespace.clone()))
+        .collect();
+    let webhook_secrets = secret_box.clone();
+    let webhook_events = events.sender.clone();
+    let email_events = events.sender.clone();
+    let retention_events = events.sender.clone();
+    let snapshot_events = events.sender.clone();
+    let nudge_events = events.sender.clone();
+    let outbox_events = events.sender.clone();
+    let response_events = events.sender.clone();
+    let quiet_hours_events = events.sender.clone();
+    let scheduler_events = events.sender.clone();
+    let push_receiver = events.sender.subscribe();
+    let push_config = push::PushConfig::load(&db.conn());
 
     let rate_limiter = RateLimiter::new();
     let typing_tracker = TypingTracker::default();
     let presence_tracker = PresenceTracker::default();
+    let nudge_presence = presence_tracker.clone();
 
     let cors = CorsOptions::default()
         .to_cors()
@@ -71,12 +186,39 @@ fn build_rocket(db_path: &str, rate_limit_config: RateLimitConfig) -> rocket::Ro
 
     let mut build = rocket::custom(figment)
         .manage(db)
+        .manage(db_config)
+        .manage(namespace_dbs)
         .manage(events)
         .manage(rate_limit_config)
         .manage(rate_limiter)
+        .manage(sender_policy)
         .manage(typing_tracker)
         .manage(presence_tracker)
+        .manage(uploads::UploadConfig::from_env())
+        .manage(sse::StreamConfig::from_env())
+        .manage(sse::SseConnections::default())
+        .manage(redaction::RedactionConfig::from_env())
+        .manage(responses::ResponseConfig::from_env())
+        .manage(regex_search_config)
+        .manage(maintenance::Maintenance::from_env())
+        .manage(ip_policy)
+        .manage(secret_box)
+        .manage(capabilities)
+        .manage(push_config.clone())
         .attach(cors)
+        .attach(request_id::RequestIdFairing)
+        .attach(sessions::ClientSessionFairing)
+        .attach(telemetry::TracingFairing)
+        .attach(namespaces::NamespacePathFairing)
+        .attach(versioning::ApiVersionPaths)
+        .attach(redirects::RoomByNameFairing)
+        .attach(ip_policy::IpPolicyFairing)
+        .attach(admin_lockout::AdminKeyLockoutFairing)
+        .attach(membership::RoomMembershipFairing)
+        .attach(maintenance::MaintenanceFairing)
+        .attach(redirects::RoomRedirectFairing)
+        .attach(i18n::LocalizeErrors)
+        .attach(versioning::ApiVersionResponses)
         .register(
             "/",
             rocket::catchers![routes::too_many_requests, routes::not_found],
@@ -86,23 +228,86 @@ fn build_rocket(db_path: &str, rate_limit_config: RateLimitConfig) -> rocket::Ro
             rocket::routes![
                 routes::health,
                 routes::stats,
+                routes::slow_queries,
                 routes::create_room,
                 routes::list_rooms,
                 routes::get_room,
+                routes::room_aliases,
+                routes::list_room_members,
+                routes::add_room_member,
+                routes::remove_room_member,
+                routes::get_room_welcome,
+                routes::set_room_welcome,
+                routes::delete_room_welcome,
+                routes::get_upload_policy,
+                routes::set_upload_policy,
+                routes::delete_upload_policy,
+                routes::get_quiet_hours,
+                routes::set_quiet_hours,
+                routes::delete_quiet_hours,
+                routes::quiet_hours_queue,
+                routes::list_scheduled,
+                routes::room_calendar,
+                routes::cancel_scheduled,
+                routes::export_server_config,
+                routes::import_server_config,
+                routes::get_retention_notice,
+                routes::postpone_retention,
+                routes::list_snapshots,
+                routes::create_snapshot,
+                routes::get_snapshot_schedule,
+                routes::set_snapshot_schedule,
+                routes::delete_snapshot_schedule,
+                routes::register_commands,
+                routes::list_commands,
+                routes::delete_commands,
+                routes::room_help,
+                routes::vapid_public_key,
+                routes::create_push_subscription,
+                routes::delete_push_subscription,
+                routes::flag_message,
+                routes::list_flags,
+                routes::resolve_flag,
+                routes::add_labels,
+                routes::get_labels,
+                routes::remove_label,
+                routes::room_label_summary,
+                routes::room_decisions,
+                routes::set_topic,
+                routes::get_topic,
+                routes::topic_history,
                 routes::update_room,
                 routes::archive_room,
                 routes::unarchive_room,
                 routes::delete_room,
+                routes::merge_rooms,
+                routes::room_audit_log,
                 routes::send_message,
                 routes::edit_message,
+                routes::patch_message,
                 routes::get_edit_history,
                 routes::delete_message,
+                routes::move_message,
                 routes::get_messages,
+                routes::start_message_stream,
+                routes::append_message_stream,
+                routes::append_to_message,
+                routes::finalize_message_stream,
                 routes::activity_feed,
+                routes::activity_heatmap,
+                routes::cost_stats,
+                routes::room_stats,
+                routes::sample_messages,
+                routes::room_conversations,
                 routes::search_messages,
                 routes::room_participants,
+                routes::room_mentionables,
+                routes::list_room_roles,
+                routes::set_room_role,
+                routes::delete_room_role,
                 routes::notify_typing,
                 routes::message_stream,
+                routes::firehose_stream,
                 routes::upload_file,
                 routes::download_file,
                 routes::file_info,
@@ -112,29 +317,75 @@ fn build_rocket(db_path: &str, rate_limit_config: RateLimitConfig) -> rocket::Ro
                 routes::remove_reaction,
                 routes::get_reactions,
                 routes::get_room_reactions,
+                routes::bulk_reactions,
                 routes::pin_message,
                 routes::unpin_message,
                 routes::list_pins,
+                routes::pin_history,
                 routes::room_presence,
                 routes::global_presence,
+                routes::report_device_state,
+                routes::get_device_state,
                 routes::create_webhook,
                 routes::list_webhooks,
                 routes::update_webhook,
                 routes::delete_webhook,
                 routes::get_webhook_deliveries,
                 routes::get_thread,
+                routes::get_thread_stats,
                 routes::update_read_position,
                 routes::get_read_positions,
+                routes::get_message_read_by,
                 routes::get_unread,
+                routes::update_thread_read_position,
+                routes::get_unread_threads,
                 routes::upsert_profile,
                 routes::get_profile,
                 routes::list_profiles,
                 routes::delete_profile,
+                routes::update_status,
+                routes::list_statuses,
+                routes::get_status,
+                routes::status_history,
+                routes::clear_status,
+                routes::enqueue_item,
+                routes::list_queue,
+                routes::claim_queue_item,
+                routes::complete_queue_item,
+                routes::release_queue_item,
+                routes::acquire_lock,
+                routes::renew_lock,
+                routes::release_lock,
+                routes::get_lock,
+                routes::list_locks,
+                routes::create_server_webhook,
+                routes::list_server_webhooks,
+                routes::delete_server_webhook,
+                routes::migration_status,
+                routes::maintenance_status,
+                routes::set_maintenance,
+                routes::maintenance_blocked,
+                routes::ip_policy_denied,
+                routes::admin_key_locked,
+                routes::membership_denied,
+                routes::search_index_status,
+                routes::repair_search_index,
+                routes::list_stream_connections,
+                routes::start_import,
+                routes::get_import,
+                routes::list_imports,
+                routes::get_quota,
+                routes::set_quota,
+                routes::get_room_subscriptions,
+                routes::set_room_subscriptions,
                 routes::send_dm,
                 routes::list_dm_conversations,
                 routes::get_dm_conversation,
                 routes::get_mentions,
                 routes::get_unread_mentions,
+                routes::reaction_notifications,
+                routes::mark_reactions_read,
+                routes::pending_responses,
                 routes::create_incoming_webhook,
                 routes::list_incoming_webhooks,
                 routes::update_incoming_webhook,
@@ -150,36 +401,177 @@ fn build_rocket(db_path: &str, rate_limit_config: RateLimitConfig) -> rocket::Ro
                 routes::openapi_json,
                 routes::skills_index,
                 routes::skills_skill_md,
+                routes::skills_json,
                 routes::api_skills_skill_md,
                 routes::run_retention_now,
                 routes::export_room,
+                routes::export_sender,
+                routes::import_room,
                 routes::broadcast_message,
             ],
         )
+        .attach(rocket::fairing::AdHoc::on_liftoff(
+            "OTLP Exporter",
+            |_rocket| {
+                Box::pin(async move {
+                    if let Some(endpoint) = telemetry::init_from_env() {
+                        println!("🔭 OTLP trace export to {endpoint}");
+                    }
+                })
+            },
+        ))
         .attach(rocket::fairing::AdHoc::on_liftoff(
             "Webhook Dispatcher",
             move |_rocket| {
                 Box::pin(async move {
-                    webhooks::spawn_dispatcher(webhook_receiver, webhook_db_path);
+                    for (receiver, path, namespace) in webhook_receivers {
+                        webhooks::spawn_dispatcher(receiver, webhook_events.clone(), path, webhook_secrets.clone(), namespace);
+                    }
                     println!("🔗 Webhook dispatcher started");
                 })
             },
         ))
+        .attach(rocket::fairing::AdHoc::on_liftoff(
+            "Event Outbox Relay",
+            {
+                let outbox_databases = databases.clone();
+                move |_rocket| {
+                    Box::pin(async move {
+                        for (namespace, path) in outbox_databases {
+                            outbox::spawn_relay(path, outbox_events.clone(), namespace);
+                        }
+                        println!("📬 Event outbox relay started");
+                    })
+                }
+            },
+        ))
         .attach(rocket::fairing::AdHoc::on_liftoff(
             "Message Retention",
             {
-                let retention_db_path = db_path.to_string();
+                let retention_databases = databases.clone();
                 move |_rocket| {
                     Box::pin(async move {
-                        retention::spawn_retention_task(retention_db_path);
+                        for (namespace, path) in retention_databases {
+                            retention::spawn_retention_task(path, retention_events.clone(), namespace);
+                        }
                         println!("🧹 Message retention task started");
                     })
                 }
             },
         ))
+        .attach(rocket::fairing::AdHoc::on_liftoff(
+            "Web Push",
+            {
+                let push_db_path = db_path.to_string();
+                move |_rocket| {
+                    Box::pin(async move {
+                        if let Some(config) = push_config {
+                            push::spawn_push_dispatcher(push_receiver, push_db_path, config);
+                            println!("🔔 Web Push dispatcher started");
+                        }
+                    })
+                }
+            },
+        ))
+        .attach(rocket::fairing::AdHoc::on_liftoff(
+            "Room Snapshots",
+            {
+                let snapshot_db_path = db_path.to_string();
+                move |_rocket| {
+                    Box::pin(async move {
+                        snapshots::spawn_snapshot_task(snapshot_db_path, snapshot_events);
+                        println!("📸 Room snapshot scheduler started");
+                    })
+                }
+            },
+        ))
+        .attach(rocket::fairing::AdHoc::on_liftoff(
+            "Response Escalation",
+            {
+                let response_databases = databases.clone();
+                move |_rocket| {
+                    Box::pin(async move {
+                        for (namespace, path) in response_databases {
+                            responses::spawn_escalator(path, response_events.clone(), namespace);
+                        }
+                        println!("⏰ Response escalator started");
+                    })
+                }
+            },
+        ))
+        .attach(rocket::fairing::AdHoc::on_liftoff(
+            "Quiet Hours",
+            {
+                let quiet_hours_databases = databases.clone();
+                move |_rocket| {
+                    Box::pin(async move {
+                        for (namespace, path) in quiet_hours_databases {
+                            quiet_hours::spawn_releaser(path, quiet_hours_events.clone(), namespace);
+                        }
+                        println!("🌙 Quiet hours releaser started");
+                    })
+                }
+            },
+        ))
+        .attach(rocket::fairing::AdHoc::on_liftoff(
+            "Message Scheduler",
+            {
+                let scheduler_databases = databases.clone();
+                move |_rocket| {
+                    Box::pin(async move {
+                        for (namespace, path) in scheduler_databases {
+                            scheduler::spawn_scheduler(path, scheduler_events.clone(), namespace);
+                        }
+                        println!("⏱️ Message scheduler started");
+                    })
+                }
+            },
+        ))
+        .attach(rocket::fairing::AdHoc::on_liftoff(
+            "Mention Nudges",
+            {
+                let nudge_databases = databases.clone();
+                move |_rocket| {
+                    Box::pin(async move {
+                        for (namespace, path) in nudge_databases {
+                            nudges::spawn_nudger(path, nudge_events.clone(), nudge_presence.clone(), namespace);
+                        }
+                        println!("📨 Mention nudger started");
+                    })
+                }
+            },
+        ))
+        .attach(rocket::fairing::AdHoc::on_liftoff(
+            "Email Gateway",
+            {
+                let email_db_path = db_path.to_string();
+                move |rocket| {
+                    let senders = rocket.state::<SenderPolicy>().cloned().unwrap_or_default();
+                    let maintenance = rocket.state::<maintenance::Maintenance>().cloned().unwrap_or_default();
+                    Box::pin(async move {
+                        let enabled = env::var("EMAIL_GATEWAY_ENABLED")
+                            .map(|v| v == "1" || v.to_lowercase() == "true")
+                            .unwrap_or(false);
+                        if !enabled {
+                            return;
+                        }
+                        let bind = env::var("EMAIL_GATEWAY_BIND")
+                            .unwrap_or_else(|_| "127.0.0.1:2525".to_string());
+                        let domain = env::var("EMAIL_GATEWAY_DOMAIN")
+                            .unwrap_or_else(|_| "chat.local".to_string());
+                        println!("📧 Email gateway listening on {bind} (rooms as <name>@{domain})");
+                        email::spawn_smtp_listener(bind, email_db_path, email_events, domain, senders, maintenance);
+                    })
+                }
+            },
+        ))
         .attach(rocket::fairing::AdHoc::on_liftoff(
             "mDNS Service Discovery",
-            |_rocket| {
+            |rocket| {
+                let txt = rocket
+                    .state::<capabilities::Capabilities>()
+                    .map(|c| c.txt_properties())
+                    .unwrap_or_default();
                 Box::pin(async move {
                     let mdns_enabled = env::var("MDNS_ENABLED")
                         .map(|v| v != "0" && v.to_lowercase() != "false")
@@ -198,7 +590,7 @@ fn build_rocket(db_path: &str, rate_limit_config: RateLimitConfig) -> rocket::Ro
                     let instance_name = env::var("MDNS_INSTANCE_NAME")
                         .unwrap_or_else(|_| "local-agent-chat".to_string());
 
-                    match mdns::start_mdns(port, &instance_name) {
+                    match mdns::start_mdns(port, &instance_name, &txt) {
                         Ok(handle) => {
                             println!(
                                 "📡 mDNS advertising: {} on port {}",
@@ -219,6 +611,17 @@ fn build_rocket(db_path: &str, rate_limit_config: RateLimitConfig) -> rocket::Ro
             },
         ));
 
+    // Fixture generator for local development; never mounted unless explicitly enabled
+    if seed::enabled() {
+        println!("🧪 Dev routes enabled (POST /api/v1/dev/seed)");
+        build = build.mount("/", rocket::routes![routes::dev_seed]);
+    }
+
+    // Interactive API console; locked-down deployments can switch it off
+    if routes::api_docs_enabled() {
+        build = build.mount("/", rocket::routes![routes::api_docs]);
+    }
+
     // Serve frontend static files if the directory exists
     if static_dir.is_dir() {
         println!("📦 Serving frontend from: {}", static_dir.display());
@@ -0,0 +1,142 @@
+//! Maintenance mode: a server-wide read-only switch for backups and migrations. While it is on,
+//! writes (POST, PUT, PATCH, DELETE) get a 503 carrying the operator's message; reads and SSE
+//! streams keep working, so connected agents ride it out instead of reconnecting.
+
+use std::env;
+use std::sync::{Arc, RwLock};
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::uri::Origin;
+use rocket::http::{Method, Status};
+use rocket::request::{FromRequest, Outcome};
+use rocket::{Data, Request};
+
+use crate::models::MaintenanceStatus;
+
+const DEFAULT_MESSAGE: &str = "The server is in maintenance mode: writes are paused, reads and streams still work";
+
+/// Where blocked writes are rerouted; only reachable through [`MaintenanceFairing`].
+pub const BLOCKED_PATH: &str = "/api/v1/maintenance/blocked";
+
+/// Writes that stay open during maintenance, so it can be switched off again.
+const EXEMPT_PATHS: &[&str] = &["/api/v1/admin/maintenance"];
+
+/// Current maintenance state, shared by the fairing, the admin endpoints and the email gateway.
+/// Clones share the same switch.
+#[derive(Clone)]
+pub struct Maintenance {
+    default_message: String,
+    state: Arc<RwLock<Option<(String, String)>>>,
+}
+
+impl Maintenance {
+    /// `MAINTENANCE_MESSAGE` sets the default 503 message; `MAINTENANCE_MODE=true` starts the
+    /// server read-only (e.g. to bring it up on a database mid-restore).
+    pub fn from_env() -> Self {
+        let default_message = env::var("MAINTENANCE_MESSAGE")
+            .ok()
+            .map(|m| m.trim().to_string())
+            .filter(|m| !m.is_empty())
+            .unwrap_or_else(|| DEFAULT_MESSAGE.to_string());
+        let maintenance = Maintenance {
+            default_message,
+            state: Arc::new(RwLock::new(None)),
+        };
+        if env::var("MAINTENANCE_MODE").is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true")) {
+            maintenance.enable(None);
+        }
+        maintenance
+    }
+
+    /// Go read-only. `message` overrides the default for this window.
+    pub fn enable(&self, message: Option<String>) {
+        let message = message
+            .map(|m| m.trim().to_string())
+            .filter(|m| !m.is_empty())
+            .unwrap_or_else(|| self.default_message.clone());
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        let since = match state.take() {
+            Some((_, since)) => since,
+            None => chrono::Utc::now().to_rfc3339(),
+        };
+        *state = Some((message, since));
+    }
+
+    pub fn disable(&self) {
+        *self.state.write().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.state.read().unwrap_or_else(|e| e.into_inner()).is_some()
+    }
+
+    pub fn status(&self) -> MaintenanceStatus {
+        match &*self.state.read().unwrap_or_else(|e| e.into_inner()) {
+            Some((message, since)) => MaintenanceStatus {
+                enabled: true,
+                message: Some(message.clone()),
+                since: Some(since.clone()),
+            },
+            None => MaintenanceStatus {
+                enabled: false,
+                message: None,
+                since: None,
+            },
+        }
+    }
+}
+
+impl Default for Maintenance {
+    fn default() -> Self {
+        Maintenance {
+            default_message: DEFAULT_MESSAGE.to_string(),
+            state: Arc::new(RwLock::new(None)),
+        }
+    }
+}
+
+/// Marks a request the fairing rerouted to [`BLOCKED_PATH`].
+#[derive(Debug, Clone, Copy)]
+pub struct BlockedWrite(bool);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for BlockedWrite {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match *req.local_cache(|| BlockedWrite(false)) {
+            BlockedWrite(true) => Outcome::Success(BlockedWrite(true)),
+            BlockedWrite(false) => Outcome::Forward(Status::NotFound),
+        }
+    }
+}
+
+/// Fairing that reroutes writes to [`BLOCKED_PATH`] while maintenance is on. Attach after the
+/// fairings that rewrite paths (namespaces, API versions, by-name rooms) so exemptions match
+/// the canonical `/api/v1` path.
+pub struct MaintenanceFairing;
+
+#[rocket::async_trait]
+impl Fairing for MaintenanceFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Maintenance Mode",
+            kind: Kind::Request,
+        }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _data: &mut Data<'_>) {
+        if !matches!(req.method(), Method::Post | Method::Put | Method::Patch | Method::Delete) {
+            return;
+        }
+        if !req.rocket().state::<Maintenance>().is_some_and(Maintenance::is_enabled) {
+            return;
+        }
+        if EXEMPT_PATHS.contains(&req.uri().path().as_str()) {
+            return;
+        }
+        req.local_cache(|| BlockedWrite(true));
+        req.set_method(Method::Get);
+        req.set_uri(Origin::parse(BLOCKED_PATH).expect("valid blocked path"));
+    }
+}
@@ -21,9 +21,14 @@ impl Drop for MdnsHandle {
     }
 }
 
-/// Start mDNS service advertisement.
+/// Start mDNS service advertisement. `capabilities` are added to the TXT record alongside
+/// `path` and `protocol` (see [`crate::capabilities::Capabilities::txt_properties`]).
 /// Returns a handle that keeps the service registered until dropped.
-pub fn start_mdns(port: u16, instance_name: &str) -> Result<MdnsHandle, String> {
+pub fn start_mdns(
+    port: u16,
+    instance_name: &str,
+    capabilities: &[(&'static str, String)],
+) -> Result<MdnsHandle, String> {
     let mdns = mdns_sd::ServiceDaemon::new().map_err(|e| format!("mDNS daemon: {e}"))?;
 
     // Detect local hostname and IP
@@ -48,6 +53,9 @@ pub fn start_mdns(port: u16, instance_name: &str) -> Result<MdnsHandle, String>
     properties.insert("version".to_string(), env!("CARGO_PKG_VERSION").to_string());
     properties.insert("path".to_string(), "/api/v1".to_string());
     properties.insert("protocol".to_string(), "http".to_string());
+    for (key, value) in capabilities {
+        properties.insert(key.to_string(), value.clone());
+    }
 
     let service_info = mdns_sd::ServiceInfo::new(
         SERVICE_TYPE,
@@ -0,0 +1,202 @@
+//! Room membership and private rooms. A room's `visibility` is `public` (the default: anyone can
+//! read and post) or `private`: only its members can reach anything under `/api/v1/rooms/<id>`.
+//! The room admin adds members, and each one gets a token to send as `X-Member-Token` (or
+//! `?member_token=` where headers can't be set, like `EventSource`). The room's admin key and the
+//! server token always get in. Private rooms are also left out of everything that spans rooms
+//! (the room list, search, activity, unread counts, notifications, exports, the all-rooms
+//! stream, ...) unless the request carries a member token for them.
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::uri::Origin;
+use rocket::http::{Method, Status};
+use rocket::request::{FromRequest, Outcome};
+use rocket::{Data, Request};
+use rusqlite::{params, Connection};
+
+use crate::namespaces::ScopedDb;
+use crate::senders::{SenderPolicy, ServerToken};
+
+pub const MEMBER_HEADER: &str = "X-Member-Token";
+
+/// Where requests for a private room from non-members are rerouted; only reachable through
+/// [`RoomMembershipFairing`].
+pub const DENIED_PATH: &str = "/api/v1/membership/denied";
+
+pub const VISIBILITIES: &[&str] = &["public", "private"];
+
+/// Max tokens read from one request.
+const MAX_TOKENS: usize = 50;
+
+pub fn generate_member_token() -> String {
+    format!("mem_{}", uuid::Uuid::new_v4().simple())
+}
+
+pub fn is_private(conn: &Connection, room_id: &str) -> bool {
+    conn.query_row(
+        "SELECT visibility = 'private' FROM rooms WHERE id = ?1",
+        params![room_id],
+        |r| r.get::<_, bool>(0),
+    )
+    .unwrap_or(false)
+}
+
+/// Member tokens a request carries: `X-Member-Token` (comma-separated for several rooms) and
+/// `?member_token=`.
+fn presented_tokens(req: &Request<'_>) -> Vec<String> {
+    req.headers()
+        .get(MEMBER_HEADER)
+        .chain(req.query_value::<&str>("member_token").and_then(|v| v.ok()))
+        .flat_map(|v| v.split(','))
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .take(MAX_TOKENS)
+        .map(str::to_string)
+        .collect()
+}
+
+fn has_server_token(req: &Request<'_>, token: &ServerToken) -> bool {
+    req.rocket()
+        .state::<SenderPolicy>()
+        .and_then(|p| p.server_token.as_deref())
+        .is_some_and(|expected| token.0.iter().any(|t| t == expected))
+}
+
+/// The private rooms a request may see, for endpoints that span rooms. Never fails.
+#[derive(Debug, Clone, Default)]
+pub struct MemberAccess {
+    /// Server token holders see every room
+    pub all: bool,
+    /// Private rooms the presented member tokens belong to
+    pub rooms: Vec<String>,
+}
+
+impl MemberAccess {
+    pub fn can_see(&self, conn: &Connection, room_id: &str) -> bool {
+        self.all || self.rooms.iter().any(|r| r == room_id) || !is_private(conn, room_id)
+    }
+
+    /// SQL condition keeping `column` (a room id) to rooms this request may see.
+    pub fn visible_sql(&self, column: &str) -> String {
+        if self.all {
+            return "1=1".to_string();
+        }
+        let allowed: Vec<String> = self.rooms.iter().map(|id| format!("'{}'", id.replace('\'', "''"))).collect();
+        format!(
+            "{column} NOT IN (SELECT id FROM rooms WHERE visibility = 'private' AND id NOT IN ({}))",
+            allowed.join(",")
+        )
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for MemberAccess {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let token = req.guard::<ServerToken>().await.succeeded().unwrap_or_default();
+        if has_server_token(req, &token) {
+            return Outcome::Success(MemberAccess { all: true, rooms: Vec::new() });
+        }
+        let tokens = presented_tokens(req);
+        let rooms = match (tokens.is_empty(), ScopedDb::of(req)) {
+            (false, Some(db)) => {
+                let conn = db.conn();
+                tokens
+                    .iter()
+                    .filter_map(|t| {
+                        conn.query_row("SELECT room_id FROM room_members WHERE token = ?1", params![t], |r| r.get(0))
+                            .ok()
+                    })
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+        Outcome::Success(MemberAccess { all: false, rooms })
+    }
+}
+
+/// Marks a request the fairing rerouted to [`DENIED_PATH`].
+#[derive(Debug, Clone, Copy)]
+pub struct MembersOnly {
+    /// The request carried an admin key, and it was wrong
+    pub wrong_admin_key: bool,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for MembersOnly {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match req.local_cache(|| None::<MembersOnly>) {
+            Some(denied) => Outcome::Success(*denied),
+            None => Outcome::Forward(Status::NotFound),
+        }
+    }
+}
+
+/// Fairing that keeps non-members out of private rooms. Attach after the fairings that rewrite
+/// paths (namespaces, API versions, by-name addressing) so the room id is known, and after the
+/// admin key lockout so wrong keys refused here are still counted.
+pub struct RoomMembershipFairing;
+
+#[rocket::async_trait]
+impl Fairing for RoomMembershipFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Room Membership",
+            kind: Kind::Request,
+        }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _data: &mut Data<'_>) {
+        let path = req.uri().path().as_str().to_string();
+        let Some(rest) = path.strip_prefix("/api/v1/rooms/") else {
+            return;
+        };
+        let room_id = rest.split('/').next().unwrap_or_default();
+        if room_id.is_empty() {
+            return;
+        }
+        let token = req.guard::<ServerToken>().await.succeeded().unwrap_or_default();
+        if has_server_token(req, &token) {
+            return;
+        }
+        let admin_key = req
+            .headers()
+            .get_one("Authorization")
+            .and_then(|auth| auth.strip_prefix("Bearer "))
+            .or_else(|| req.headers().get_one("X-Admin-Key"))
+            .map(str::to_string);
+        let tokens = presented_tokens(req);
+        let denied = {
+            let Some(db) = ScopedDb::of(req) else {
+                return;
+            };
+            let conn = db.conn();
+            let Ok(room_key) = conn.query_row(
+                "SELECT admin_key FROM rooms WHERE id = ?1 AND visibility = 'private'",
+                params![room_id],
+                |r| r.get::<_, String>(0),
+            ) else {
+                return; // public, or no such room
+            };
+            let member = tokens.iter().any(|t| {
+                conn.query_row(
+                    "SELECT 1 FROM room_members WHERE room_id = ?1 AND token = ?2",
+                    params![room_id, t],
+                    |_| Ok(()),
+                )
+                .is_ok()
+            });
+            if member || admin_key.as_deref() == Some(room_key.as_str()) {
+                return;
+            }
+            MembersOnly {
+                wrong_admin_key: admin_key.is_some(),
+            }
+        };
+        req.local_cache(|| Some(denied));
+        req.set_method(Method::Get);
+        req.set_uri(Origin::parse(DENIED_PATH).expect("valid denied path"));
+    }
+}
@@ -0,0 +1,347 @@
+//! Versioned schema migrations. Each file in `migrations/` is numbered, applied once in order
+//! on startup after the frozen baseline schema, and recorded in `schema_version` with a checksum
+//! of its SQL. A database that has seen a newer build than this one is refused rather than
+//! silently run against a schema the code doesn't know.
+
+use rusqlite::{params, Connection};
+use std::fmt;
+
+/// One numbered migration. `sql` is run as a batch inside a savepoint together with its
+/// `schema_version` row, so a failure leaves nothing half-applied. `backfill` is for data the
+/// SQL can't compute (e.g. parsing message content); it runs after `sql` in the same savepoint.
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub sql: &'static str,
+    pub backfill: Option<fn(&Connection) -> rusqlite::Result<()>>,
+}
+
+/// Every migration this build knows, in version order. Append only: never edit or renumber a
+/// migration that has shipped.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "sender_status",
+        sql: include_str!("../migrations/0001_sender_status.sql"),
+        backfill: None,
+    },
+    Migration {
+        version: 2,
+        name: "queue_items",
+        sql: include_str!("../migrations/0002_queue_items.sql"),
+        backfill: None,
+    },
+    Migration {
+        version: 3,
+        name: "locks",
+        sql: include_str!("../migrations/0003_locks.sql"),
+        backfill: None,
+    },
+    Migration {
+        version: 4,
+        name: "server_webhooks",
+        sql: include_str!("../migrations/0004_server_webhooks.sql"),
+        backfill: None,
+    },
+    Migration {
+        version: 5,
+        name: "import_jobs",
+        sql: include_str!("../migrations/0005_import_jobs.sql"),
+        backfill: None,
+    },
+    Migration {
+        version: 6,
+        name: "sender_quotas",
+        sql: include_str!("../migrations/0006_sender_quotas.sql"),
+        backfill: None,
+    },
+    Migration {
+        version: 7,
+        name: "room_subscriptions",
+        sql: include_str!("../migrations/0007_room_subscriptions.sql"),
+        backfill: None,
+    },
+    Migration {
+        version: 8,
+        name: "sensitive_messages",
+        sql: include_str!("../migrations/0008_sensitive_messages.sql"),
+        backfill: None,
+    },
+    Migration {
+        version: 9,
+        name: "room_tags",
+        sql: include_str!("../migrations/0009_room_tags.sql"),
+        backfill: None,
+    },
+    Migration {
+        version: 10,
+        name: "room_language",
+        sql: include_str!("../migrations/0010_room_language.sql"),
+        backfill: None,
+    },
+    Migration {
+        version: 11,
+        name: "message_labels",
+        sql: include_str!("../migrations/0011_message_labels.sql"),
+        backfill: None,
+    },
+    Migration {
+        version: 12,
+        name: "mention_nudges",
+        sql: include_str!("../migrations/0012_mention_nudges.sql"),
+        backfill: None,
+    },
+    Migration {
+        version: 13,
+        name: "allowed_reactions",
+        sql: include_str!("../migrations/0013_allowed_reactions.sql"),
+        backfill: None,
+    },
+    Migration {
+        version: 14,
+        name: "room_topics",
+        sql: include_str!("../migrations/0014_room_topics.sql"),
+        backfill: None,
+    },
+    Migration {
+        version: 15,
+        name: "webhook_transport",
+        sql: include_str!("../migrations/0015_webhook_transport.sql"),
+        backfill: None,
+    },
+    Migration {
+        version: 16,
+        name: "ordered_webhooks",
+        sql: include_str!("../migrations/0016_ordered_webhooks.sql"),
+        backfill: None,
+    },
+    Migration {
+        version: 17,
+        name: "event_outbox",
+        sql: include_str!("../migrations/0017_event_outbox.sql"),
+        backfill: None,
+    },
+    Migration {
+        version: 18,
+        name: "room_roles",
+        sql: include_str!("../migrations/0018_room_roles.sql"),
+        backfill: None,
+    },
+    Migration {
+        version: 19,
+        name: "reaction_read_cursors",
+        sql: include_str!("../migrations/0019_reaction_read_cursors.sql"),
+        backfill: None,
+    },
+    Migration {
+        version: 20,
+        name: "pending_responses",
+        sql: include_str!("../migrations/0020_pending_responses.sql"),
+        backfill: None,
+    },
+    Migration {
+        version: 21,
+        name: "profile_timezone",
+        sql: include_str!("../migrations/0021_profile_timezone.sql"),
+        backfill: None,
+    },
+    Migration {
+        version: 22,
+        name: "quiet_hours",
+        sql: include_str!("../migrations/0022_quiet_hours.sql"),
+        backfill: None,
+    },
+    Migration {
+        version: 23,
+        name: "scheduled_messages",
+        sql: include_str!("../migrations/0023_scheduled_messages.sql"),
+        backfill: None,
+    },
+    Migration {
+        version: 24,
+        name: "room_members",
+        sql: include_str!("../migrations/0024_room_members.sql"),
+        backfill: None,
+    },
+    Migration {
+        version: 25,
+        name: "pin_history",
+        sql: include_str!("../migrations/0025_pin_history.sql"),
+        backfill: None,
+    },
+    Migration {
+        version: 26,
+        name: "mentions_backfill",
+        sql: include_str!("../migrations/0026_mentions_backfill.sql"),
+        backfill: Some(crate::db::rebuild_mentions_index),
+    },
+    Migration {
+        version: 27,
+        name: "reply_index",
+        sql: include_str!("../migrations/0027_reply_index.sql"),
+        backfill: None,
+    },
+    Migration {
+        version: 28,
+        name: "stream_leases",
+        sql: include_str!("../migrations/0028_stream_leases.sql"),
+        backfill: None,
+    },
+];
+
+/// The newest schema version this build can run against.
+pub fn latest_version() -> i64 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+#[derive(Debug)]
+pub enum MigrationError {
+    /// The database was migrated by a newer build. Downgrades are not supported.
+    TooNew { current: i64, latest: i64 },
+    /// A migration's SQL failed; it was rolled back and later ones were not attempted.
+    Failed { version: i64, name: String, error: String },
+    Sqlite(rusqlite::Error),
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationError::TooNew { current, latest } => write!(
+                f,
+                "schema version {current} is newer than this build supports ({latest}); refusing to start. \
+                 Downgrades are not supported — run a build at least as new as the one that last opened \
+                 this database, or restore a backup taken before the upgrade"
+            ),
+            MigrationError::Failed { version, name, error } => {
+                write!(f, "migration {version:04}_{name} failed and was rolled back: {error}")
+            }
+            MigrationError::Sqlite(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+impl From<rusqlite::Error> for MigrationError {
+    fn from(e: rusqlite::Error) -> Self {
+        MigrationError::Sqlite(e)
+    }
+}
+
+/// A migration recorded in `schema_version`.
+#[derive(Debug, Clone)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub name: String,
+    pub checksum: String,
+    pub applied_at: String,
+}
+
+pub fn checksum(sql: &str) -> String {
+    use sha2::{Digest, Sha256};
+    hex::encode(Sha256::digest(sql.as_bytes()))
+}
+
+fn ensure_version_table(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            checksum TEXT NOT NULL,
+            applied_at TEXT NOT NULL
+        );",
+    )
+}
+
+/// Highest applied version (0 for a database that predates versioned migrations).
+pub fn current_version(conn: &Connection) -> rusqlite::Result<i64> {
+    conn.query_row("SELECT COALESCE(MAX(version), 0) FROM schema_version", [], |r| r.get(0))
+}
+
+/// Migrations recorded in `schema_version`, oldest first.
+pub fn applied(conn: &Connection) -> rusqlite::Result<Vec<AppliedMigration>> {
+    let mut stmt = conn.prepare("SELECT version, name, checksum, applied_at FROM schema_version ORDER BY version")?;
+    let rows = stmt.query_map([], |r| {
+        Ok(AppliedMigration {
+            version: r.get(0)?,
+            name: r.get(1)?,
+            checksum: r.get(2)?,
+            applied_at: r.get(3)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Migrations this build knows that the database hasn't applied yet, in order.
+pub fn pending(conn: &Connection) -> rusqlite::Result<Vec<Migration>> {
+    let done: Vec<i64> = applied(conn)?.iter().map(|m| m.version).collect();
+    Ok(MIGRATIONS.iter().filter(|m| !done.contains(&m.version)).copied().collect())
+}
+
+/// Bring the database up to date: the baseline schema, then every pending migration in order.
+/// Returns the migrations that were applied.
+pub fn run(conn: &Connection) -> Result<Vec<Migration>, MigrationError> {
+    ensure_version_table(conn)?;
+    // Checked before anything else touches the schema: an older build must not "repair" a
+    // database a newer one has already moved on.
+    let current = current_version(conn)?;
+    let latest = latest_version();
+    if current > latest {
+        return Err(MigrationError::TooNew { current, latest });
+    }
+
+    crate::db::baseline_schema(conn);
+
+    for m in applied(conn)? {
+        if let Some(known) = MIGRATIONS.iter().find(|k| k.version == m.version)
+            && checksum(known.sql) != m.checksum
+        {
+            eprintln!(
+                "⚠️ Migration {:04}_{} was edited after it was applied (checksum mismatch); the database keeps the old version",
+                m.version, m.name
+            );
+        }
+    }
+
+    let todo = pending(conn)?;
+    for m in &todo {
+        apply(conn, m)?;
+    }
+    Ok(todo)
+}
+
+fn apply(conn: &Connection, m: &Migration) -> Result<(), MigrationError> {
+    let failed = |e: rusqlite::Error| MigrationError::Failed {
+        version: m.version,
+        name: m.name.to_string(),
+        error: e.to_string(),
+    };
+    conn.execute_batch("SAVEPOINT schema_migration;")?;
+    let result = conn.execute_batch(m.sql).and_then(|_| m.backfill.map_or(Ok(()), |f| f(conn))).and_then(|_| {
+        conn.execute(
+            "INSERT INTO schema_version (version, name, checksum, applied_at) VALUES (?1, ?2, ?3, ?4)",
+            params![m.version, m.name, checksum(m.sql), chrono::Utc::now().to_rfc3339()],
+        )
+    });
+    match result {
+        Ok(_) => {
+            conn.execute_batch("RELEASE schema_migration;")?;
+            Ok(())
+        }
+        Err(e) => {
+            conn.execute_batch("ROLLBACK TO schema_migration; RELEASE schema_migration;")
+                .ok();
+            Err(failed(e))
+        }
+    }
+}
+
+/// Work out what [`run`] would apply without keeping any of it: everything runs inside a
+/// transaction that is rolled back, so SQL errors surface exactly as they would for real.
+pub fn dry_run(conn: &Connection) -> Result<Vec<Migration>, MigrationError> {
+    let tx = conn.unchecked_transaction()?;
+    let result = run(&tx);
+    tx.rollback()?;
+    result
+}
@@ -36,6 +36,46 @@ pub struct RoomWithStats {
     pub max_messages: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_message_age_hours: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_ttl_secs: Option<i64>,
+    /// Seconds between a `retention_pending` notice and the purge it announces
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retention_notice_secs: Option<i64>,
+    /// Emoji, or the id of an image file uploaded to this room
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    /// Download URL when `icon` is an uploaded image
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon_url: Option<String>,
+    /// Accent color as lowercase `#rrggbb`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// Seq range and participant count, for planning pagination (room detail only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_seq: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest_seq: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_message_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub participant_count: Option<i64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Language code that picks the search tokenizer (unset = English stemming)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// FTS5 tokenizer the room's messages are indexed with; shown when `language` is set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search_tokenizer: Option<String>,
+    /// Current topic, set by any participant (see `PUT /rooms/{id}/topic`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topic: Option<String>,
+    /// Emoji that reactions in this room are restricted to (unset = any emoji)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_reactions: Vec<String>,
+    /// `public`, or `private` for members only
+    #[serde(default = "default_visibility")]
+    pub visibility: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -57,8 +97,18 @@ pub struct Message {
     pub pinned_at: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pinned_by: Option<String>,
-    #[serde(skip_serializing_if = "is_zero")]
+    #[serde(default, skip_serializing_if = "is_zero")]
     pub edit_count: i64,
+    /// `message` for posts, `system` for server-written lifecycle notes (renames, pins, joins, purges)
+    #[serde(default = "default_message_kind")]
+    pub kind: String,
+    /// Timestamps in the zone asked for with `?tz=`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local_time: Option<crate::timezones::LocalTime>,
+}
+
+fn default_message_kind() -> String {
+    "message".to_string()
 }
 
 #[derive(Debug, Deserialize)]
@@ -72,6 +122,17 @@ pub struct CreateRoom {
     pub max_messages: Option<i64>,
     #[serde(default)]
     pub max_message_age_hours: Option<i64>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Language code selecting the search tokenizer (see `UpdateRoom::language`)
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Restrict reactions to these emoji (see `UpdateRoom::allowed_reactions`)
+    #[serde(default)]
+    pub allowed_reactions: Vec<String>,
+    /// `public` (default) or `private` (see `UpdateRoom::visibility`)
+    #[serde(default)]
+    pub visibility: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -86,6 +147,31 @@ pub struct UpdateRoom {
     /// Set to a number to enable age-based retention. Set to null to disable.
     #[serde(default, deserialize_with = "deserialize_optional_nullable_i64")]
     pub max_message_age_hours: Option<Option<i64>>,
+    /// Default lifetime in seconds for files uploaded without `expires_in`. Set to null to disable.
+    #[serde(default, deserialize_with = "deserialize_optional_nullable_i64")]
+    pub file_ttl_secs: Option<Option<i64>>,
+    /// Announce retention purges this many seconds ahead with `retention_pending`. Set to null to purge immediately.
+    #[serde(default, deserialize_with = "deserialize_optional_nullable_i64")]
+    pub retention_notice_secs: Option<Option<i64>>,
+    /// Emoji (unicode or `:shortcode:`) or an image file id from this room. Set to null to clear.
+    #[serde(default, deserialize_with = "deserialize_optional_nullable_string")]
+    pub icon: Option<Option<String>>,
+    /// Accent color, `#rgb` or `#rrggbb`. Set to null to clear.
+    #[serde(default, deserialize_with = "deserialize_optional_nullable_string")]
+    pub color: Option<Option<String>>,
+    /// Replaces the room's tags; `[]` clears them
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    /// Language code (`de`, `ja`, `pt-BR`...) selecting the search tokenizer; the room's
+    /// messages are reindexed when it changes. Set to null for the default English stemming.
+    #[serde(default, deserialize_with = "deserialize_optional_nullable_string")]
+    pub language: Option<Option<String>>,
+    /// Restrict reactions to these emoji (unicode or `:shortcode:`). Set to null or `[]` to allow any.
+    #[serde(default, deserialize_with = "deserialize_optional_nullable_list")]
+    pub allowed_reactions: Option<Option<Vec<String>>>,
+    /// `public`, or `private` to let only members (and the admin key) read or post
+    #[serde(default)]
+    pub visibility: Option<String>,
 }
 
 /// Deserializer for double-option fields: absent = None (skip), null = Some(None) (clear), value = Some(Some(v)).
@@ -99,8 +185,29 @@ where
     Ok(Some(v))
 }
 
+/// String counterpart of `deserialize_optional_nullable_i64`.
+fn deserialize_optional_nullable_string<'de, D>(deserializer: D) -> Result<Option<Option<String>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let v: Option<String> = Option::deserialize(deserializer)?;
+    Ok(Some(v))
+}
+
+/// List counterpart of `deserialize_optional_nullable_i64`.
+fn deserialize_optional_nullable_list<'de, D>(deserializer: D) -> Result<Option<Option<Vec<String>>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let v: Option<Vec<String>> = Option::deserialize(deserializer)?;
+    Ok(Some(v))
+}
+
 #[derive(Debug, Deserialize)]
 pub struct SendMessage {
+    /// Optional client-supplied UUID (lets agents correlate and safely retry sends)
+    #[serde(default)]
+    pub id: Option<String>,
     pub sender: String,
     pub content: String,
     #[serde(default)]
@@ -109,6 +216,96 @@ pub struct SendMessage {
     pub reply_to: Option<String>,
     #[serde(default)]
     pub sender_type: Option<String>,
+    /// Redact the content (the message itself stays) after `redact_after_secs`, or the server default
+    #[serde(default)]
+    pub sensitive: bool,
+    #[serde(default)]
+    pub redact_after_secs: Option<i64>,
+    /// Senders expected to reply in this message's thread; tracked until they do
+    #[serde(default)]
+    pub requires_response_from: Vec<String>,
+    /// How long they have before `response_overdue` fires, or the server default
+    #[serde(default)]
+    pub response_timeout_secs: Option<i64>,
+    /// Deliver at this time (RFC 3339) instead of now; the post is queued until then
+    #[serde(default)]
+    pub scheduled_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MoveMessage {
+    pub target_room_id: String,
+    /// The target room's admin key (a member token in `X-Member-Token` also works for a
+    /// private target)
+    #[serde(default)]
+    pub target_admin_key: Option<String>,
+    /// Move the whole thread (root and all replies) instead of just this message
+    #[serde(default)]
+    pub include_thread: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MoveMessageResponse {
+    pub target_room_id: String,
+    /// The relocated messages, now in the target room with fresh seqs
+    pub moved: Vec<Message>,
+    /// Placeholders left in the source room at the original seqs, pointing at the new location
+    pub tombstones: Vec<Message>,
+}
+
+// --- Streaming Compose ---
+
+#[derive(Debug, Deserialize)]
+pub struct StartMessageStream {
+    pub sender: String,
+    /// Optional initial content (may be empty; chunks are appended later)
+    #[serde(default)]
+    pub content: String,
+    #[serde(default)]
+    pub metadata: Option<serde_json::Value>,
+    #[serde(default)]
+    pub reply_to: Option<String>,
+    #[serde(default)]
+    pub sender_type: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AppendMessageChunk {
+    pub sender: String,
+    pub chunk: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FinalizeMessageStream {
+    pub sender: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AppendToMessage {
+    pub sender: String,
+    pub content: String,
+}
+
+/// Broadcast as `message_appended`: only the appended text, not the whole message.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MessageAppend {
+    pub message_id: String,
+    pub room_id: String,
+    pub sender: String,
+    pub delta: String,
+    /// Length of the full content after the append, in bytes
+    pub length: usize,
+    pub appended_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MessageChunk {
+    pub message_id: String,
+    pub room_id: String,
+    pub sender: String,
+    pub chunk: String,
+    /// 1-based position of this chunk within the stream
+    pub index: i64,
 }
 
 #[derive(Debug, Deserialize)]
@@ -119,6 +316,21 @@ pub struct EditMessage {
     pub metadata: Option<serde_json::Value>,
 }
 
+/// Body of `PATCH /messages/<id>`: exactly one of `json_patch` or `diff`.
+#[derive(Debug, Deserialize)]
+pub struct PatchMessage {
+    pub sender: String,
+    /// RFC 6902 operations against `{"content": ..., "metadata": {...}}`
+    #[serde(default)]
+    pub json_patch: Option<Vec<serde_json::Value>>,
+    /// Unified diff against the current content
+    #[serde(default)]
+    pub diff: Option<String>,
+    /// Optimistic concurrency: reject with 409 unless the message has exactly this many edits
+    #[serde(default)]
+    pub base_edit_count: Option<i64>,
+}
+
 #[derive(Debug, Serialize, Clone)]
 pub struct MessageEdit {
     pub id: String,
@@ -126,6 +338,12 @@ pub struct MessageEdit {
     pub previous_content: String,
     pub edited_at: String,
     pub editor: String,
+    /// "json-patch" or "diff" for edits made with PATCH
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub patch_format: Option<String>,
+    /// The patch as submitted (JSON Patch serialized as a JSON string)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub patch: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -166,6 +384,9 @@ pub struct ActivityEvent {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_to: Option<String>,
     pub seq: i64,
+    /// Timestamps in the zone asked for with `?tz=`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local_time: Option<crate::timezones::LocalTime>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -185,6 +406,9 @@ pub struct FileUpload {
     #[serde(default = "default_content_type")]
     pub content_type: String,
     pub data: String, // base64-encoded
+    /// Seconds until the file is removed by the retention task (overrides the room's file TTL)
+    #[serde(default)]
+    pub expires_in: Option<i64>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -195,14 +419,23 @@ pub struct FileInfo {
     pub filename: String,
     pub content_type: String,
     pub size: i64,
+    /// Hex SHA-256 of the contents; identical uploads share storage and this value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
     pub url: String,
     pub created_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
 }
 
 fn default_anonymous() -> String {
     "anonymous".to_string()
 }
 
+pub(crate) fn default_visibility() -> String {
+    "public".to_string()
+}
+
 fn default_content_type() -> String {
     "application/octet-stream".to_string()
 }
@@ -236,6 +469,23 @@ pub struct SearchResult {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_to: Option<String>,
     pub seq: i64,
+    /// Where the pattern matched (regex mode only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matches: Option<Vec<RegexMatch>>,
+}
+
+/// One match of a regex search pattern inside a message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegexMatch {
+    pub text: String,
+    /// Byte offsets into `content`
+    pub start: usize,
+    pub end: usize,
+    /// Capture groups 1..n in order; null where a group didn't participate
+    pub groups: Vec<Option<String>>,
+    /// Named groups
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub named: std::collections::BTreeMap<String, Option<String>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -248,6 +498,17 @@ pub struct SearchResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub before_date: Option<String>,
     pub has_more: bool,
+    /// `regex` for regex searches (absent for full-text)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    /// Messages a regex search examined
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scanned: Option<usize>,
+    /// A regex search stopped at its time limit or scan cap; continue with `next_before_seq`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub truncated: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_before_seq: Option<i64>,
 }
 
 // --- Pins ---
@@ -271,6 +532,24 @@ pub struct PinnedMessage {
     pub pinned_by: String,
 }
 
+/// One pin or unpin in a room's pin history. `sender` and `content` are as they were when the
+/// event happened; `seq` is null once the message is gone.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PinHistoryEntry {
+    pub id: i64,
+    pub room_id: String,
+    pub message_id: String,
+    /// `pinned` or `unpinned`
+    pub action: String,
+    pub actor: String,
+    pub at: String,
+    pub sender: String,
+    pub content: String,
+    pub seq: Option<i64>,
+    /// Whether the message is pinned right now
+    pub pinned: bool,
+}
+
 // --- Presence ---
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -279,6 +558,62 @@ pub struct PresenceEntry {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sender_type: Option<String>,
     pub connected_at: String,
+    /// "active" or "idle", from the sender's device-state reports.
+    pub availability: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idle_since: Option<String>,
+    /// The client sessions (devices or instances) behind the connection
+    #[serde(default)]
+    pub sessions: Vec<PresenceSession>,
+}
+
+/// One of a sender's client sessions, told apart by its `X-Client-Session` token.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PresenceSession {
+    pub session: String,
+    /// Name the client gave its device with `X-Client-Device`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+    /// When its earliest open stream connected (absent without one)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connected_at: Option<String>,
+    /// Open stream connections
+    pub connections: usize,
+    pub last_seen: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReportDeviceState {
+    pub sender: String,
+    /// Distinguishes a sender's devices; defaults to "default".
+    #[serde(default)]
+    pub device: Option<String>,
+    /// "active" or "idle".
+    pub state: String,
+    /// Seconds since the last OS input event, if known.
+    #[serde(default)]
+    pub idle_secs: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeviceState {
+    pub device: String,
+    pub state: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idle_since: Option<String>,
+    pub reported_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeviceAvailability {
+    pub sender: String,
+    pub availability: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idle_since: Option<String>,
+    pub devices: Vec<DeviceState>,
+    /// Live client sessions, connected to a stream or not, most recently seen first
+    #[serde(default)]
+    pub sessions: Vec<PresenceSession>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -305,6 +640,34 @@ pub struct Webhook {
     pub created_by: String,
     pub created_at: String,
     pub active: bool,
+    /// "healthy", "failing", "open" (auto-disabled by the circuit breaker) or "disabled"
+    pub state: String,
+    /// Consecutive deliveries that failed after all retries
+    pub failure_streak: i64,
+    pub last_success_at: Option<String>,
+    pub last_failure_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub circuit_opened_at: Option<String>,
+    /// Names of the custom headers sent with each delivery; their values are never returned
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub header_names: Vec<String>,
+    /// Whether deliveries present a client certificate (mTLS)
+    pub has_client_cert: bool,
+    /// Whether a custom CA certificate is trusted for this endpoint
+    pub has_ca_cert: bool,
+    /// Deliveries are sent one at a time, in event order
+    pub ordered: bool,
+}
+
+/// Sent when the circuit breaker disables a webhook that kept failing.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WebhookCircuitOpened {
+    pub webhook_id: String,
+    pub room_id: String,
+    pub url: String,
+    pub failure_streak: i64,
+    pub failing_since: String,
+    pub circuit_opened_at: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -316,6 +679,18 @@ pub struct CreateWebhook {
     pub secret: Option<String>,
     #[serde(default = "default_anonymous")]
     pub created_by: String,
+    /// Static headers sent with every delivery (e.g. `Authorization`). Stored encrypted.
+    #[serde(default)]
+    pub headers: std::collections::BTreeMap<String, String>,
+    /// PEM certificate chain and private key presented to mTLS endpoints. Stored encrypted.
+    #[serde(default)]
+    pub client_cert: Option<String>,
+    /// PEM CA certificate to trust for the endpoint, in addition to the system roots
+    #[serde(default)]
+    pub ca_cert: Option<String>,
+    /// Deliver strictly in event order, one at a time, instead of concurrently
+    #[serde(default)]
+    pub ordered: bool,
 }
 
 fn default_webhook_events() -> String {
@@ -332,6 +707,17 @@ pub struct UpdateWebhook {
     pub secret: Option<String>,
     #[serde(default)]
     pub active: Option<bool>,
+    /// Replaces all custom headers; `{}` removes them
+    #[serde(default)]
+    pub headers: Option<std::collections::BTreeMap<String, String>>,
+    /// Replaces the client certificate; `""` removes it
+    #[serde(default)]
+    pub client_cert: Option<String>,
+    /// Replaces the trusted CA certificate; `""` removes it
+    #[serde(default)]
+    pub ca_cert: Option<String>,
+    #[serde(default)]
+    pub ordered: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -341,6 +727,105 @@ pub struct WebhookPayload {
     pub room_name: String,
     pub data: serde_json::Value,
     pub timestamp: String,
+    /// Latest message seq in the room when the event was dispatched (room webhooks only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub room_seq: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateServerWebhook {
+    pub url: String,
+    #[serde(default = "default_webhook_events")]
+    pub events: String,
+    #[serde(default)]
+    pub secret: Option<String>,
+    #[serde(default = "default_anonymous")]
+    pub created_by: String,
+}
+
+/// A server-level webhook: fires on room lifecycle events across the whole server.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServerWebhook {
+    pub id: String,
+    pub url: String,
+    pub events: String,
+    pub has_secret: bool,
+    pub created_by: String,
+    pub created_at: String,
+    pub active: bool,
+    pub last_delivery_at: Option<String>,
+    /// "success" or "failed" for the most recent delivery (retries included)
+    pub last_status: Option<String>,
+    pub last_error: Option<String>,
+}
+
+// --- Schema Migrations ---
+
+/// Where the database stands against the migrations this build ships.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MigrationStatus {
+    pub current_version: i64,
+    pub latest_version: i64,
+    pub applied: Vec<AppliedMigrationInfo>,
+    pub pending: Vec<PendingMigrationInfo>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AppliedMigrationInfo {
+    pub version: i64,
+    pub name: String,
+    pub applied_at: String,
+    /// False when the migration's SQL has changed since it was applied (or this build doesn't know it)
+    pub checksum_ok: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PendingMigrationInfo {
+    pub version: i64,
+    pub name: String,
+}
+
+// --- Imports ---
+
+/// A background import of a Slack or Discord export archive (`POST /api/v1/admin/import`).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImportJob {
+    pub id: String,
+    /// "slack" or "discord"
+    pub format: String,
+    /// "queued", "running", "completed", or "failed"
+    pub status: String,
+    pub created_by: String,
+    /// Size of the uploaded archive in bytes
+    pub archive_size: i64,
+    /// Channels found in the archive; `rooms_done` of them are committed so far
+    pub rooms_total: i64,
+    pub rooms_done: i64,
+    pub messages_imported: i64,
+    pub files_imported: i64,
+    /// Attachments the archive only linked to (kept as URLs in message metadata)
+    pub attachments_missing: i64,
+    /// Profiles newly created (existing ones are left alone)
+    pub profiles_imported: i64,
+    /// Channel → room mapping for the rooms committed so far
+    pub rooms: Vec<ImportedRoom>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub created_at: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImportedRoom {
+    /// Channel name in the source workspace
+    pub source: String,
+    pub room_id: String,
+    /// Room name here (suffixed when the source name was taken)
+    pub name: String,
+    /// The new room's admin key; only ever shown to server-token holders
+    pub admin_key: String,
+    pub messages: i64,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -381,6 +866,9 @@ pub struct UnreadInfo {
     pub unread_count: i64,
     pub last_read_seq: i64,
     pub latest_seq: i64,
+    /// Thread replies in this room the sender hasn't read (tracked per thread, not by `last_read_seq`)
+    #[serde(default)]
+    pub thread_unread_count: i64,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -388,6 +876,60 @@ pub struct UnreadResponse {
     pub sender: String,
     pub rooms: Vec<UnreadInfo>,
     pub total_unread: i64,
+    #[serde(default)]
+    pub total_thread_unread: i64,
+    /// True when `rooms` was narrowed to the sender's subscriptions
+    #[serde(default)]
+    pub subscribed_only: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ThreadReadPosition {
+    pub room_id: String,
+    pub root_id: String,
+    pub sender: String,
+    pub last_read_seq: i64,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ThreadUnreadInfo {
+    pub room_id: String,
+    pub room_name: String,
+    pub root_id: String,
+    pub unread_count: i64,
+    pub last_read_seq: i64,
+    pub latest_seq: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ThreadUnreadResponse {
+    pub sender: String,
+    pub threads: Vec<ThreadUnreadInfo>,
+    pub total_unread: i64,
+    /// True when `threads` was narrowed to the sender's subscriptions
+    #[serde(default)]
+    pub subscribed_only: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MessageReader {
+    pub sender: String,
+    /// The furthest read position (room or thread) the sender has marked
+    pub last_read_seq: i64,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MessageReadBy {
+    pub message_id: String,
+    pub room_id: String,
+    pub seq: i64,
+    pub sender: String,
+    /// Senders whose read position is at or past the message, earliest first
+    pub read_by: Vec<MessageReader>,
+    /// Known participants who haven't read that far yet
+    pub unread_by: Vec<String>,
 }
 
 // --- Reactions ---
@@ -405,12 +947,16 @@ pub struct Reaction {
     pub room_id: String,
     pub sender: String,
     pub emoji: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shortcode: Option<String>,
     pub created_at: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ReactionSummary {
     pub emoji: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shortcode: Option<String>,
     pub count: i64,
     pub senders: Vec<String>,
 }
@@ -427,36 +973,562 @@ pub struct RoomReactionsResponse {
     pub reactions: std::collections::HashMap<String, Vec<ReactionSummary>>,
 }
 
-// --- Direct Messages ---
+// --- Conversation clustering ---
 
-#[derive(Debug, Deserialize)]
-pub struct SendDm {
-    pub sender: String,
-    pub recipient: String,
-    pub content: String,
-    #[serde(default)]
-    pub metadata: Option<serde_json::Value>,
-    #[serde(default)]
-    pub sender_type: Option<String>,
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Conversation {
+    pub first_seq: i64,
+    pub last_seq: i64,
+    pub started_at: String,
+    pub ended_at: String,
+    pub message_count: usize,
+    /// Senders in order of first appearance
+    pub participants: Vec<String>,
+    /// Opening message, truncated to 140 characters
+    pub preview: String,
+    /// In seq order
+    pub message_ids: Vec<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct DmConversation {
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConversationsResponse {
     pub room_id: String,
-    pub other_participant: String,
-    pub last_message_content: Option<String>,
-    pub last_message_sender: Option<String>,
-    pub last_message_at: Option<String>,
-    pub message_count: i64,
-    pub unread_count: i64,
-    pub created_at: String,
+    pub gap_secs: i64,
+    pub count: usize,
+    /// Ordered by when each conversation started
+    pub conversations: Vec<Conversation>,
 }
 
+// --- Cost accounting ---
+
+/// LLM spend reported by agents in `metadata.usage`, summed per sender, room or UTC day.
 #[derive(Debug, Serialize, Deserialize)]
-pub struct DmConversationsResponse {
-    pub sender: String,
-    pub conversations: Vec<DmConversation>,
-    pub count: usize,
+pub struct CostReport {
+    /// "sender", "room" or "day"
+    pub group_by: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
+    pub totals: CostTotals,
+    pub groups: Vec<CostGroup>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CostGroup {
+    /// Canonical sender, room id or `YYYY-MM-DD`
+    pub key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub room_name: Option<String>,
+    #[serde(flatten)]
+    pub totals: CostTotals,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CostTotals {
+    /// Messages that carried `metadata.usage`
+    pub messages: i64,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub total_tokens: i64,
+    pub cost_usd: f64,
+}
+
+// --- Activity heatmap ---
+
+/// Message counts bucketed as `counts[day][hour]`, day 0 = Monday.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ActivityHeatmap {
+    pub room_id: String,
+    pub days: i64,
+    /// Start of the window (UTC)
+    pub since: String,
+    /// Minutes east of UTC the buckets were computed in
+    pub tz_offset: i32,
+    pub day_labels: Vec<String>,
+    pub total: i64,
+    pub counts: Vec<Vec<i64>>,
+    /// Same grid, only messages with `sender_type: agent`
+    pub agents: Vec<Vec<i64>>,
+    /// Same grid, only messages with `sender_type: human`
+    pub humans: Vec<Vec<i64>>,
+    /// Busiest slot, if there was any activity
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peak: Option<HeatmapPeak>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HeatmapPeak {
+    pub day: String,
+    pub hour: u32,
+    pub count: i64,
+}
+
+/// How busy one room is: volume over time, who talks, and what it stores.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoomStats {
+    pub room_id: String,
+    pub room_name: String,
+    /// Non-system messages, all time
+    pub messages: i64,
+    pub system_messages: i64,
+    pub by_sender_type: SenderTypeCounts,
+    pub first_message_at: Option<String>,
+    pub last_message_at: Option<String>,
+    /// Length of the `by_day` window
+    pub days: i64,
+    /// Messages per UTC day over the window, oldest first, days without messages included
+    pub by_day: Vec<DayCount>,
+    /// Per-sender breakdown, most messages first
+    pub senders: Vec<RoomSenderStats>,
+    pub files: RoomFileStats,
+    pub reactions: RoomReactionStats,
+    pub threads: RoomThreadStats,
+    pub pinned: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SenderTypeCounts {
+    pub agent: i64,
+    pub human: i64,
+    pub unspecified: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DayCount {
+    /// YYYY-MM-DD (UTC)
+    pub date: String,
+    pub messages: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoomSenderStats {
+    pub sender: String,
+    pub sender_type: Option<String>,
+    pub messages: i64,
+    pub first_message_at: String,
+    pub last_message_at: String,
+    /// Reactions others left on this sender's messages
+    pub reactions_received: i64,
+    pub files: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoomFileStats {
+    pub count: i64,
+    pub total_bytes: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoomReactionStats {
+    pub total: i64,
+    /// Most-used emoji first (up to 10)
+    pub top: Vec<EmojiCount>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EmojiCount {
+    pub emoji: String,
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoomThreadStats {
+    /// Messages with at least one direct reply
+    pub threads: i64,
+    /// Messages that reply to another message
+    pub replies: i64,
+}
+
+/// A reproducible random sample of a room's messages.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MessageSample {
+    pub room_id: String,
+    /// "uniform" or "recent-weighted"
+    pub strategy: String,
+    /// Pass back as `seed` to draw the same sample again (while the room is unchanged)
+    pub seed: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub half_life_hours: Option<f64>,
+    /// Messages eligible for sampling after filters
+    pub population: usize,
+    /// Sampled messages in seq order
+    pub messages: Vec<Message>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BulkReactionsRequest {
+    pub message_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BulkReactionsResponse {
+    /// Every requested message that exists, keyed by id (empty list when it has no reactions)
+    pub reactions: std::collections::HashMap<String, Vec<ReactionSummary>>,
+    /// Requested ids that don't match any message
+    pub missing: Vec<String>,
+}
+
+/// An announced retention purge, for rooms with `retention_notice_secs`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionNotice {
+    pub room_id: String,
+    /// Messages that will be deleted
+    pub pending_count: i64,
+    /// Newest message covered; nothing after this seq is purged by this notice
+    pub cutoff_seq: i64,
+    /// `created_at` of the cutoff message
+    pub cutoff_at: String,
+    pub notified_at: String,
+    /// Earliest time the purge runs
+    pub purge_after: String,
+    /// Whether the one allowed postponement has been used
+    pub postponed: bool,
+}
+
+/// A room's snapshot schedule.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SnapshotSchedule {
+    pub room_id: String,
+    /// Five-field cron expression, evaluated in UTC
+    pub cron: String,
+    /// "files" (the room's file store) or "directory" (under `SNAPSHOT_DIR`)
+    pub destination: String,
+    /// Snapshots kept; older ones are deleted after each scheduled run
+    pub keep: i64,
+    pub last_run_at: Option<String>,
+    pub next_run_at: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetSnapshotSchedule {
+    pub cron: String,
+    #[serde(default)]
+    pub destination: Option<String>,
+    #[serde(default)]
+    pub keep: Option<i64>,
+}
+
+/// A JSONL checkpoint of a room's history.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoomSnapshot {
+    pub id: String,
+    pub room_id: String,
+    pub taken_at: String,
+    /// "schedule" or "manual"
+    pub trigger: String,
+    pub message_count: i64,
+    pub first_seq: Option<i64>,
+    pub last_seq: Option<i64>,
+    pub size: i64,
+    pub sha256: String,
+    /// Set when stored in the room's file store
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Set when written under `SNAPSHOT_DIR`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SnapshotsResponse {
+    pub room_id: String,
+    pub schedule: Option<SnapshotSchedule>,
+    /// Newest first
+    pub snapshots: Vec<RoomSnapshot>,
+}
+
+/// A command a bot answers to in a room.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CommandSpec {
+    /// Command name without the leading `/`
+    pub name: String,
+    pub description: String,
+    /// Argument synopsis, e.g. `<issue-number> [--verbose]`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RegisterCommands {
+    pub commands: Vec<CommandSpec>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RoomCommand {
+    pub sender: String,
+    #[serde(flatten)]
+    pub command: CommandSpec,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RoomCommandsResponse {
+    pub room_id: String,
+    pub commands: Vec<RoomCommand>,
+}
+
+/// `PushSubscription.toJSON()` from the browser, plus who it notifies.
+#[derive(Debug, Deserialize)]
+pub struct CreatePushSubscription {
+    pub sender: String,
+    pub endpoint: String,
+    pub keys: PushSubscriptionKeys,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PushSubscriptionKeys {
+    pub p256dh: String,
+    pub auth: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PushSubscription {
+    pub id: String,
+    pub sender: String,
+    pub endpoint: String,
+    pub created_at: String,
+}
+
+// --- Moderation flags ---
+
+#[derive(Debug, Deserialize)]
+pub struct CreateFlag {
+    pub reporter: String,
+    pub reason: String,
+}
+
+/// A report against a message, awaiting or past moderator review.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MessageFlag {
+    pub id: String,
+    pub room_id: String,
+    pub message_id: String,
+    /// Author and content of the message when it was flagged
+    pub message_sender: String,
+    pub message_content: String,
+    pub reporter: String,
+    pub reason: String,
+    /// "open", "dismissed" or "deleted"
+    pub status: String,
+    pub created_at: String,
+    pub resolved_at: Option<String>,
+    pub resolved_by: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResolveFlag {
+    /// "dismiss" keeps the message; "delete" removes it
+    pub action: String,
+    #[serde(default)]
+    pub moderator: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FlagQueueResponse {
+    pub room_id: String,
+    pub status: String,
+    pub flags: Vec<MessageFlag>,
+    pub count: usize,
+}
+
+// --- Message labels ---
+
+#[derive(Debug, Deserialize)]
+pub struct AddLabels {
+    pub sender: String,
+    pub labels: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MessageLabel {
+    pub label: String,
+    pub added_by: String,
+    pub created_at: String,
+}
+
+/// Every label currently on a message.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MessageLabels {
+    pub message_id: String,
+    pub room_id: String,
+    pub labels: Vec<MessageLabel>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LabelCount {
+    pub label: String,
+    /// Labeled messages in the window
+    pub count: i64,
+    /// Creation time of the oldest and newest labeled message
+    pub first_message_at: String,
+    pub last_message_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LabelSummary {
+    pub room_id: String,
+    pub labels: Vec<LabelCount>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before_date: Option<String>,
+}
+
+/// An approving reaction (👍 ✅ ✔️ 💯) on a decision.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DecisionApproval {
+    pub sender: String,
+    pub emoji: String,
+    pub created_at: String,
+}
+
+/// A message labeled `decision`, with the thread it was made in.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Decision {
+    pub message: Message,
+    pub labeled_by: String,
+    pub labeled_at: String,
+    /// Root of the thread when the decision is a reply
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread_root: Option<Message>,
+    /// Replies in the decision's thread, root excluded
+    pub thread_replies: i64,
+    pub approvals: Vec<DecisionApproval>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DecisionLog {
+    pub room_id: String,
+    pub room_name: String,
+    pub generated_at: String,
+    pub count: usize,
+    pub decisions: Vec<Decision>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before_date: Option<String>,
+}
+
+// --- Room topics ---
+
+#[derive(Debug, Deserialize)]
+pub struct SetTopic {
+    pub sender: String,
+    /// New topic; empty clears it
+    #[serde(default)]
+    pub topic: String,
+}
+
+/// A room's topic as set at one point in time. `topic` is None where it was cleared.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RoomTopic {
+    pub room_id: String,
+    pub topic: Option<String>,
+    pub set_by: String,
+    pub set_at: String,
+}
+
+// --- Work Queues ---
+
+/// A unit of work in a room's FIFO queue.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QueueItem {
+    pub id: String,
+    pub room_id: String,
+    pub task: String,
+    pub data: serde_json::Value,
+    pub created_by: String,
+    /// "pending", "claimed" or "done"
+    pub status: String,
+    pub claimed_by: Option<String>,
+    pub claimed_at: Option<String>,
+    /// When an unfinished claim lapses and the item returns to the queue
+    pub lease_expires_at: Option<String>,
+    /// How many times the item has been claimed
+    pub attempts: i64,
+    pub completed_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EnqueueItem {
+    pub sender: String,
+    pub task: String,
+    #[serde(default)]
+    pub data: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ClaimQueueItem {
+    pub sender: String,
+    /// Seconds the claim holds before the item is handed to someone else (default 300)
+    #[serde(default)]
+    pub lease_seconds: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompleteQueueItem {
+    pub sender: String,
+    #[serde(default)]
+    pub result: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReleaseQueueItem {
+    pub sender: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueueListResponse {
+    pub room_id: String,
+    pub items: Vec<QueueItem>,
+    pub count: usize,
+}
+
+/// What a claim got: the oldest pending item, or none when the queue is empty.
+#[derive(Debug, Serialize)]
+pub struct QueueClaim {
+    pub item: Option<QueueItem>,
+    /// Items still pending after this claim
+    pub pending: i64,
+}
+
+// --- Direct Messages ---
+
+#[derive(Debug, Deserialize)]
+pub struct SendDm {
+    pub sender: String,
+    pub recipient: String,
+    pub content: String,
+    #[serde(default)]
+    pub metadata: Option<serde_json::Value>,
+    #[serde(default)]
+    pub sender_type: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DmConversation {
+    pub room_id: String,
+    pub other_participant: String,
+    pub last_message_content: Option<String>,
+    pub last_message_sender: Option<String>,
+    pub last_message_at: Option<String>,
+    pub message_count: i64,
+    pub unread_count: i64,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DmConversationsResponse {
+    pub sender: String,
+    pub conversations: Vec<DmConversation>,
+    pub count: usize,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -508,6 +1580,79 @@ pub struct UnreadMentionsResponse {
     pub total_unread: i64,
 }
 
+// --- Reaction Notifications ---
+
+/// A reaction someone left on one of the sender's messages.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReactionNotification {
+    /// Position in the feed; pass to `after=` or mark read up to it
+    pub cursor: i64,
+    pub reaction_id: String,
+    pub emoji: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shortcode: Option<String>,
+    pub reactor: String,
+    pub reacted_at: String,
+    pub message_id: String,
+    pub message_seq: i64,
+    pub message_preview: String,
+    pub room_id: String,
+    pub room_name: String,
+    pub unread: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReactionNotificationsResponse {
+    pub sender: String,
+    pub notifications: Vec<ReactionNotification>,
+    pub count: usize,
+    /// Reactions past `read_cursor`, across all rooms
+    pub unread_count: i64,
+    pub read_cursor: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MarkReactionsRead {
+    pub sender: String,
+    /// Defaults to the newest reaction in the feed
+    #[serde(default)]
+    pub cursor: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReactionReadCursor {
+    pub sender: String,
+    pub read_cursor: i64,
+    pub unread_count: i64,
+}
+
+// --- Pending Responses ---
+
+/// A message still waiting on a reply from `responder`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PendingResponse {
+    pub message_id: String,
+    pub room_id: String,
+    pub room_name: String,
+    pub seq: i64,
+    /// Sender of the message, who is waiting
+    pub requested_by: String,
+    pub responder: String,
+    pub content: String,
+    pub requested_at: String,
+    pub due_at: String,
+    pub overdue: bool,
+    /// When `response_overdue` was sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub escalated_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PendingResponsesResponse {
+    pub pending: Vec<PendingResponse>,
+    pub count: usize,
+}
+
 // --- Bookmarks ---
 
 #[derive(Debug, Deserialize)]
@@ -519,18 +1664,101 @@ pub struct BookmarkAction {
 pub struct BookmarkedRoom {
     pub room_id: String,
     pub room_name: String,
-    pub description: String,
-    pub created_at: String,
-    pub bookmarked_at: String,
-    pub message_count: i64,
-    pub last_activity: Option<String>,
+    pub description: String,
+    pub created_at: String,
+    pub bookmarked_at: String,
+    pub message_count: i64,
+    pub last_activity: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BookmarksResponse {
+    pub sender: String,
+    pub bookmarks: Vec<BookmarkedRoom>,
+    pub count: usize,
+}
+
+// --- SSE connections ---
+
+/// An open SSE connection (GET /api/v1/admin/streams).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SseConnection {
+    pub id: u64,
+    pub room_id: String,
+    /// Presence identity, when the client connected with `sender`
+    pub sender: Option<String>,
+    pub sender_type: Option<String>,
+    pub connected_at: String,
+    /// When the server will close the connection with a `reconnect` event, if ever
+    pub expires_at: Option<String>,
+    pub heartbeat_secs: u64,
+    /// The `events` filter the client subscribed with, if any
+    pub events: Option<String>,
+    /// Events sent so far, not counting heartbeats
+    pub events_delivered: u64,
+    pub last_event_at: Option<String>,
+    /// Events this connection fell too far behind to receive (each loss is reported as a `gap`)
+    pub lagged_events: u64,
+}
+
+// --- Sender Quotas ---
+
+/// A sender's daily budgets and what is left of them today.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SenderQuota {
+    /// Canonical sender (aliases share their profile's budget)
+    pub sender: String,
+    pub messages: QuotaBudget,
+    pub upload_bytes: QuotaBudget,
+    /// Next UTC midnight, when the daily counters reset
+    pub resets_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QuotaBudget {
+    /// Effective daily limit; null when unlimited
+    pub limit: Option<i64>,
+    pub used: i64,
+    /// null when unlimited
+    pub remaining: Option<i64>,
+    /// True when this sender has its own limit instead of the server default
+    pub overridden: bool,
+}
+
+/// Body for PUT /api/v1/quota/<sender>. A missing or null field falls back to the server
+/// default; 0 means unlimited for this sender.
+#[derive(Debug, Deserialize)]
+pub struct SetSenderQuota {
+    #[serde(default)]
+    pub daily_messages: Option<i64>,
+    #[serde(default)]
+    pub daily_upload_bytes: Option<i64>,
+}
+
+// --- Room Subscriptions ---
+
+/// The rooms a sender follows (GET/PUT /api/v1/subscriptions/rooms).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoomSubscriptions {
+    /// Canonical sender (aliases share their profile's subscriptions)
+    pub sender: String,
+    /// Empty when the sender follows every room
+    pub rooms: Vec<RoomSubscription>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoomSubscription {
+    pub room_id: String,
+    pub room_name: String,
+    pub subscribed_at: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct BookmarksResponse {
+/// Body for PUT /api/v1/subscriptions/rooms. Replaces the whole set; `[]` unsubscribes from
+/// everything, so the sender goes back to following every room.
+#[derive(Debug, Deserialize)]
+pub struct SetRoomSubscriptions {
     pub sender: String,
-    pub bookmarks: Vec<BookmarkedRoom>,
-    pub count: usize,
+    pub room_ids: Vec<String>,
 }
 
 // --- Incoming Webhooks ---
@@ -546,6 +1774,27 @@ pub struct IncomingWebhook {
     pub active: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub url: Option<String>, // Computed: /api/v1/hook/{token}
+    /// Messages per minute for this hook; null uses the server default
+    pub rate_limit: Option<i64>,
+    /// Messages per UTC day for this hook (0 = unlimited); null uses the server default
+    pub daily_quota: Option<i64>,
+    pub usage: IncomingWebhookUsage,
+}
+
+/// Limits in force for an incoming webhook and how much of them it has used.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IncomingWebhookUsage {
+    /// Effective per-minute limit
+    pub rate_limit: i64,
+    /// Effective daily quota; null when unlimited
+    pub daily_quota: Option<i64>,
+    pub messages_today: i64,
+    /// Posts refused today by the rate limit or quota
+    pub rejected_today: i64,
+    pub messages_total: i64,
+    pub last_used_at: Option<String>,
+    /// Next UTC midnight, when the daily counters reset
+    pub resets_at: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -553,6 +1802,10 @@ pub struct CreateIncomingWebhook {
     pub name: String,
     #[serde(default = "default_anonymous")]
     pub created_by: String,
+    #[serde(default)]
+    pub rate_limit: Option<i64>,
+    #[serde(default)]
+    pub daily_quota: Option<i64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -561,6 +1814,11 @@ pub struct UpdateIncomingWebhook {
     pub name: Option<String>,
     #[serde(default)]
     pub active: Option<bool>,
+    /// Absent = unchanged, null = back to the server default
+    #[serde(default, deserialize_with = "deserialize_optional_nullable_i64")]
+    pub rate_limit: Option<Option<i64>>,
+    #[serde(default, deserialize_with = "deserialize_optional_nullable_i64")]
+    pub daily_quota: Option<Option<i64>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -589,6 +1847,17 @@ pub struct Profile {
     pub bio: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub status_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// DM a pointer to mentions left unread this many minutes while offline
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mention_nudge_minutes: Option<i64>,
+    /// IANA timezone, used when listings are asked for `?tz=@sender`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    /// Other names this sender has posted under; resolved to `sender` by mentions, search and participants
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
     pub metadata: serde_json::Value,
     pub created_at: String,
     pub updated_at: String,
@@ -606,10 +1875,90 @@ pub struct UpsertProfile {
     pub bio: Option<String>,
     #[serde(default)]
     pub status_text: Option<String>,
+    /// Preferred locale (`en`, `es`, `de`, `fr`); empty string clears it.
+    #[serde(default)]
+    pub locale: Option<String>,
+    /// Minutes (1-1440) before an unread mention is DMed to this sender; 0 turns nudges off.
+    #[serde(default)]
+    pub mention_nudge_minutes: Option<i64>,
+    /// IANA timezone (`Europe/Berlin`); empty string clears it.
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// Replaces the alias list when present; `[]` clears it.
+    #[serde(default)]
+    pub aliases: Option<Vec<String>>,
     #[serde(default)]
     pub metadata: Option<serde_json::Value>,
 }
 
+/// A sender's structured status: what it's doing, how far along, and when it expects to finish.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SenderStatus {
+    pub sender: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task: Option<String>,
+    /// Percent complete, 0-100
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<f64>,
+    /// Expected completion (RFC 3339)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub eta: Option<String>,
+    pub details: serde_json::Value,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateSenderStatus {
+    #[serde(default)]
+    pub state: Option<String>,
+    #[serde(default)]
+    pub task: Option<String>,
+    #[serde(default)]
+    pub progress: Option<f64>,
+    #[serde(default)]
+    pub eta: Option<String>,
+    #[serde(default)]
+    pub details: Option<serde_json::Value>,
+}
+
+// --- Locks ---
+
+/// A named lock. `token` is the fencing token: it increases with every acquisition, so a
+/// resource can reject writes carrying a token older than the newest it has seen.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Lock {
+    pub name: String,
+    pub held: bool,
+    pub holder: Option<String>,
+    pub token: i64,
+    pub acquired_at: Option<String>,
+    pub expires_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AcquireLock {
+    pub holder: String,
+    /// Seconds until the lock lapses unless renewed (default 60)
+    #[serde(default)]
+    pub ttl_seconds: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RenewLock {
+    pub holder: String,
+    pub token: i64,
+    #[serde(default)]
+    pub ttl_seconds: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReleaseLock {
+    pub holder: String,
+    pub token: i64,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct EnrichedParticipant {
     pub sender: String,
@@ -626,6 +1975,67 @@ pub struct EnrichedParticipant {
     pub bio: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub status_text: Option<String>,
+    /// Aliases this sender posted under in the room (counted in message_count)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub posted_as: Vec<String>,
+    /// owner, moderator, member or guest
+    pub role: String,
+    /// When the sender first joined the room's stream or, failing that, first posted
+    pub joined_at: String,
+}
+
+/// A role granted in a room by its admin.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RoomRole {
+    pub sender: String,
+    pub role: String,
+    pub granted_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub granted_by: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetRoomRole {
+    pub role: String,
+    #[serde(default)]
+    pub granted_by: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RoomMember {
+    pub room_id: String,
+    pub sender: String,
+    /// Sent as `X-Member-Token`; only shown to the room admin
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub added_by: Option<String>,
+    pub added_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddRoomMember {
+    pub sender: String,
+    #[serde(default)]
+    pub added_by: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Mentionable {
+    pub sender: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sender_type: Option<String>,
+    pub last_seen: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MentionablesResponse {
+    pub room_id: String,
+    pub prefix: String,
+    pub candidates: Vec<Mentionable>,
+    pub count: usize,
 }
 
 // --- Broadcast ---
@@ -658,3 +2068,427 @@ pub struct BroadcastResponse {
     pub failed: usize,
     pub results: Vec<BroadcastDelivery>,
 }
+
+// --- Maintenance ---
+
+/// Whether the server is read-only (GET /api/v1/maintenance).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceStatus {
+    pub enabled: bool,
+    /// Shown to writers while enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetMaintenance {
+    pub enabled: bool,
+    /// Replaces `MAINTENANCE_MESSAGE` for this maintenance window
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+// --- Diagnostics ---
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlowQuery {
+    pub sql: String,
+    pub duration_ms: f64,
+    pub recorded_at: String,
+}
+
+/// Result of comparing the search (FTS5) indexes with the messages table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchIndexReport {
+    /// Messages in the database
+    pub messages: i64,
+    /// Index entries across all tokenizer tables, before any repair
+    pub indexed: i64,
+    /// Messages absent from their room's index (including ones filed under another tokenizer)
+    pub missing: usize,
+    /// Entries whose sender or content no longer match the message (missed edits)
+    pub outdated: usize,
+    /// Entries for deleted messages, misfiled or duplicated entries
+    pub orphaned: usize,
+    /// Index tables that failed FTS5's own integrity check
+    pub corrupt_tables: Vec<String>,
+    /// Whether the problems found were fixed
+    pub repaired: bool,
+    /// True when the index matches the messages (after repair, if one ran)
+    pub ok: bool,
+    pub checked_at: String,
+    pub duration_ms: f64,
+}
+
+/// What `POST /api/v1/dev/seed` generated.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SeedSummary {
+    /// Names of the created rooms
+    pub rooms: Vec<String>,
+    pub messages: usize,
+    /// Messages posted as thread replies (`reply_to` set)
+    pub replies: usize,
+    pub reactions: usize,
+    pub pins: usize,
+    pub files: usize,
+    /// Profiles newly created (existing ones are left alone)
+    pub profiles: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MergeRooms {
+    /// Room that survives (the `Authorization` admin key must be this room's)
+    pub target_room_id: String,
+    /// Room that is folded in and removed
+    pub source_room_id: String,
+    pub source_admin_key: String,
+    #[serde(default)]
+    pub merged_by: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MergeRoomsResponse {
+    pub room: RoomWithStats,
+    pub source_room_id: String,
+    pub source_room_name: String,
+    pub messages_merged: usize,
+    pub files_moved: usize,
+    pub read_positions_remapped: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoomAlias {
+    pub name: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoomRedirect {
+    /// Id of the room that no longer exists
+    pub room_id: String,
+    /// Its name at the time
+    pub name: String,
+    /// Why it redirects (`merged`)
+    pub reason: String,
+    pub created_at: String,
+}
+
+/// Old names and old room ids that resolve to a room.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoomAliasesResponse {
+    pub room_id: String,
+    pub names: Vec<RoomAlias>,
+    pub redirects: Vec<RoomRedirect>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub id: String,
+    pub action: String,
+    pub room_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actor: Option<String>,
+    pub details: serde_json::Value,
+    pub created_at: String,
+}
+
+/// Greeting sent to a sender the first time they post in or join a room.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoomWelcome {
+    pub room_id: String,
+    /// Supports {sender}, {room_name}, {room_description}, {pinned}, {pinned_links}
+    pub template: String,
+    /// `dm` (private DM from `from`) or `room` (system message in the room)
+    pub delivery: String,
+    pub from: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetRoomWelcome {
+    pub template: String,
+    #[serde(default)]
+    pub delivery: Option<String>,
+    #[serde(default)]
+    pub from: Option<String>,
+}
+
+/// Per-room restrictions on uploaded files, applied on top of the server-wide `UPLOAD_*` settings.
+/// Types accept wildcards (`image/*`); extensions are matched case-insensitively without the dot.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RoomUploadPolicy {
+    pub room_id: String,
+    /// When non-empty, only these MIME types may be uploaded
+    pub allowed_types: Vec<String>,
+    pub blocked_types: Vec<String>,
+    /// When non-empty, only these extensions may be uploaded
+    pub allowed_extensions: Vec<String>,
+    pub blocked_extensions: Vec<String>,
+    /// Reject files whose magic bytes contradict the declared content_type
+    pub verify_content_type: bool,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetUploadPolicy {
+    #[serde(default)]
+    pub allowed_types: Vec<String>,
+    #[serde(default)]
+    pub blocked_types: Vec<String>,
+    #[serde(default)]
+    pub allowed_extensions: Vec<String>,
+    #[serde(default)]
+    pub blocked_extensions: Vec<String>,
+    #[serde(default)]
+    pub verify_content_type: bool,
+}
+
+/// A room's quiet hours: agent posts made inside the daily window are held until it ends.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoomQuietHours {
+    pub room_id: String,
+    /// Window start, `HH:MM` in `timezone`
+    pub start: String,
+    /// Window end, `HH:MM`; before `start` means the window runs past midnight
+    pub end: String,
+    pub timezone: String,
+    /// Whether the window is in effect right now
+    pub active: bool,
+    /// When the current window ends (only while active)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ends_at: Option<String>,
+    /// Agent posts waiting for delivery
+    pub queued: i64,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetQuietHours {
+    pub start: String,
+    pub end: String,
+    /// IANA timezone (default: UTC)
+    #[serde(default)]
+    pub timezone: Option<String>,
+}
+
+/// A post held by quiet hours or scheduled for later. Returned with 202 in place of the message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeferredMessage {
+    /// The id the message will have once delivered
+    pub id: String,
+    pub room_id: String,
+    pub sender: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sender_type: Option<String>,
+    pub content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_to: Option<String>,
+    pub queued_at: String,
+    /// When the window ends, as of queueing; delivery follows the room's current quiet hours
+    pub deliver_at: String,
+    /// When a scheduled post is due (absent for quiet hours)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scheduled_at: Option<String>,
+    pub deferred: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeferredMessagesResponse {
+    pub room_id: String,
+    pub messages: Vec<DeferredMessage>,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SlowQueriesResponse {
+    /// False unless the server was started with `DB_SLOW_QUERY_MS`
+    pub enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threshold_ms: Option<u64>,
+    pub queries: Vec<SlowQuery>,
+    pub count: usize,
+}
+
+// --- Server configuration ---
+
+/// What an operator sets up on a server, as one document for `GET/PUT /api/v1/admin/config/export`.
+/// Rooms are matched by name, webhooks by URL and incoming webhooks by name, so applying the
+/// same document again changes nothing.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ServerConfig {
+    /// Document format version
+    pub version: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exported_at: Option<String>,
+    /// Rooms to create or bring in line with the document; rooms not listed are left alone
+    #[serde(default)]
+    pub rooms: Vec<RoomConfig>,
+    /// Replaces the server-level webhooks when present; left alone when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_webhooks: Option<Vec<ServerWebhookConfig>>,
+}
+
+/// A room and everything configured on it. Applying it sets the room to exactly this state:
+/// a missing welcome, policy or webhook is removed.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoomConfig {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Emoji icon; image icons are uploaded files and not part of the configuration
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_reactions: Vec<String>,
+    #[serde(default)]
+    pub retention: RoomRetentionConfig,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub welcome: Option<SetRoomWelcome>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upload_policy: Option<SetUploadPolicy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quiet_hours: Option<SetQuietHours>,
+    #[serde(default)]
+    pub webhooks: Vec<WebhookConfig>,
+    #[serde(default)]
+    pub incoming_webhooks: Vec<IncomingWebhookConfig>,
+    /// `public` or `private`
+    #[serde(default = "default_visibility")]
+    pub visibility: String,
+    /// Members of a private room
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<RoomMemberConfig>,
+}
+
+/// A room's retention settings; unset means off.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RoomRetentionConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_messages: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_message_age_hours: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retention_notice_secs: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_ttl_secs: Option<i64>,
+}
+
+/// A room webhook. Credentials are only exported with `include_secrets=true`; leaving one out
+/// keeps the webhook's current value.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub url: String,
+    #[serde(default = "default_webhook_events")]
+    pub events: String,
+    #[serde(default = "default_true")]
+    pub active: bool,
+    #[serde(default)]
+    pub ordered: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_cert: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub headers: Option<std::collections::BTreeMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_cert: Option<String>,
+}
+
+/// An incoming webhook. Without `token`, an existing hook keeps its own and a new one gets a
+/// fresh one; tokens are only exported with `include_secrets=true`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IncomingWebhookConfig {
+    pub name: String,
+    #[serde(default = "default_true")]
+    pub active: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_quota: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+/// A room member, matched by sender. Without `token`, an existing member keeps its own and a new
+/// one gets a fresh one; tokens are only exported with `include_secrets=true`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoomMemberConfig {
+    pub sender: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub added_by: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+/// A server-level webhook; `secret` as for [`WebhookConfig`].
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ServerWebhookConfig {
+    pub url: String,
+    #[serde(default = "default_webhook_events")]
+    pub events: String,
+    #[serde(default = "default_true")]
+    pub active: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// What applying a configuration document did.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigApplyResult {
+    pub rooms_created: Vec<String>,
+    pub rooms_updated: Vec<String>,
+    /// Admin keys of the rooms created, by name
+    pub admin_keys: std::collections::BTreeMap<String, String>,
+    pub webhooks: usize,
+    pub incoming_webhooks: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server_webhooks: Option<usize>,
+}
+
+// --- List envelope ---
+
+/// Opt-in (`?envelope=true`) wrapper for list endpoints with explicit pagination state.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListEnvelope<T> {
+    pub items: Vec<T>,
+    /// Seq to pass back to continue paging; null when there is nothing more (or the list isn't cursor-paged)
+    pub next_cursor: Option<i64>,
+    pub has_more: bool,
+}
+
+/// A list endpoint's response: its legacy shape `P`, or the envelope when requested.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum ListResponse<P, T> {
+    Plain(P),
+    Envelope(ListEnvelope<T>),
+}
+
+/// The common case where the legacy shape is a bare array.
+pub type ListOf<T> = ListResponse<Vec<T>, T>;
+
+impl<T> ListOf<T> {
+    /// Wrap a complete (unpaginated) list, honoring `?envelope=true`.
+    pub fn complete(items: Vec<T>, envelope: Option<bool>) -> Self {
+        if envelope.unwrap_or(false) {
+            ListResponse::Envelope(ListEnvelope { items, next_cursor: None, has_more: false })
+        } else {
+            ListResponse::Plain(items)
+        }
+    }
+}
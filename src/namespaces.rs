@@ -0,0 +1,256 @@
+//! Multi-tenant namespaces: several independent chat deployments in one process.
+//!
+//! Each namespace listed in `NAMESPACES` gets its own SQLite file next to the main
+//! database (`data/chat.db` → `data/chat.<namespace>.db`), so rooms, profiles, DMs,
+//! search and everything else stored are isolated by construction. A request picks its
+//! namespace with the `X-Namespace` header or the `/ns/<namespace>/...` path prefix
+//! (for clients like `EventSource` that can't set headers); without either it uses the
+//! main database. Namespace databases are opened and migrated at startup, and the
+//! background jobs (retention, scheduling, ...) run once per database.
+
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::uri::Origin;
+use rocket::http::{Header, Status};
+use rocket::request::{FromRequest, Outcome};
+use rocket::{Data, Request};
+
+use crate::db::{Db, DbConfig, TracedConn};
+use crate::telemetry::{self, SpanContext, TraceContext};
+
+/// Header selecting the namespace for a request.
+pub const NAMESPACE_HEADER: &str = "X-Namespace";
+
+/// Path prefix selecting the namespace: `/ns/<namespace>/api/v1/...`.
+const PATH_PREFIX: &str = "/ns/";
+
+/// Namespace names: 1-32 characters of `a-z`, `0-9`, `-` and `_`.
+pub fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 32
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+/// Namespaces configured via `NAMESPACES` (comma-separated). Invalid names are skipped
+/// with a warning; unset or empty disables namespacing.
+pub fn names_from_env() -> Vec<String> {
+    std::env::var("NAMESPACES")
+        .unwrap_or_default()
+        .split(',')
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+        .filter(|n| {
+            let ok = valid_name(n);
+            if !ok {
+                eprintln!("WARN: ignoring invalid namespace '{n}' (use 1-32 of a-z, 0-9, '-', '_')");
+            }
+            ok
+        })
+        .collect()
+}
+
+/// The configured namespaces and their (lazily opened) databases.
+pub struct Namespaces {
+    names: Vec<String>,
+    db_path: String,
+    config: DbConfig,
+    open: Mutex<HashMap<String, Arc<Db>>>,
+}
+
+impl Namespaces {
+    pub fn new(names: Vec<String>, db_path: &str, config: &DbConfig) -> Self {
+        Namespaces {
+            names,
+            db_path: db_path.to_string(),
+            config: config.clone(),
+            open: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Database file for a namespace: the main file's stem with `.<namespace>` inserted.
+    pub fn path_for(&self, name: &str) -> String {
+        let path = Path::new(&self.db_path);
+        let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("chat");
+        let file = match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) => format!("{stem}.{name}.{ext}"),
+            None => format!("{stem}.{name}"),
+        };
+        path.with_file_name(file).to_string_lossy().into_owned()
+    }
+
+    /// Every database the background jobs cover: the main one (None) and each namespace's,
+    /// opened and migrated here so a job started before the first request finds its tables.
+    pub fn databases(&self) -> Vec<(Option<String>, String)> {
+        let mut databases = vec![(None, self.db_path.clone())];
+        for name in &self.names {
+            self.get(name);
+            databases.push((Some(name.clone()), self.path_for(name)));
+        }
+        databases
+    }
+
+    /// The namespace's database, opening and migrating it on first use.
+    /// None if the namespace isn't configured.
+    pub fn get(&self, name: &str) -> Option<Arc<Db>> {
+        if !self.names.iter().any(|n| n == name) {
+            return None;
+        }
+        let mut open = self.open.lock().unwrap_or_else(|p| p.into_inner());
+        let db = open
+            .entry(name.to_string())
+            .or_insert_with(|| Arc::new(Db::with_config(&self.path_for(name), &self.config)));
+        Some(db.clone())
+    }
+}
+
+/// The database for the current request's namespace. Route handlers take this in place of
+/// `&State<Db>`; it derefs to [`Db`]. Fails with 404 for a namespace that isn't configured.
+/// As a guard it also carries the request's trace context, so [`ScopedDb::conn`] shows up as
+/// a child span of the request.
+pub struct ScopedDb<'r> {
+    db: DbRef<'r>,
+    namespace: Option<String>,
+    trace: Option<SpanContext>,
+}
+
+enum DbRef<'r> {
+    Default(&'r Db),
+    Namespace(Arc<Db>),
+}
+
+/// The name of the request's namespace; None for the main database.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Namespace(pub Option<String>);
+
+impl Namespace {
+    pub fn of(req: &Request<'_>) -> Namespace {
+        Namespace(
+            req.headers()
+                .get_one(NAMESPACE_HEADER)
+                .map(str::trim)
+                .filter(|n| !n.is_empty())
+                .map(String::from),
+        )
+    }
+
+    pub fn as_deref(&self) -> Option<&str> {
+        self.0.as_deref()
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Namespace {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(Namespace::of(req))
+    }
+}
+
+/// Set when a request named a namespace that isn't configured, for the 404 catcher.
+pub struct UnknownNamespace(pub Option<String>);
+
+impl<'r> ScopedDb<'r> {
+    /// Resolve the request's database. Also used by fairings, which run outside the guard machinery.
+    /// Fairings get no trace context: the request span may not have been opened yet.
+    pub fn of(req: &'r Request<'_>) -> Option<ScopedDb<'r>> {
+        let (db, namespace) = match req.headers().get_one(NAMESPACE_HEADER).map(str::trim) {
+            None | Some("") => (req.rocket().state::<Db>().map(DbRef::Default)?, None),
+            Some(name) => {
+                let db = req.rocket().state::<Namespaces>().and_then(|ns| ns.get(name));
+                if db.is_none() {
+                    req.local_cache(|| UnknownNamespace(Some(name.to_string())));
+                }
+                (DbRef::Namespace(db?), Some(name.to_string()))
+            }
+        };
+        Some(ScopedDb { db, namespace, trace: None })
+    }
+
+    /// The locked connection, traced as a child of the request span when tracing is on.
+    pub fn conn(&self) -> TracedConn<'_> {
+        self.traced_conn(self.trace.as_ref(), self.namespace.as_deref())
+    }
+}
+
+impl Deref for ScopedDb<'_> {
+    type Target = Db;
+
+    fn deref(&self) -> &Db {
+        match &self.db {
+            DbRef::Default(db) => db,
+            DbRef::Namespace(db) => db,
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ScopedDb<'r> {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match ScopedDb::of(req) {
+            Some(mut db) => {
+                if telemetry::enabled() {
+                    db.trace = TraceContext::of(req);
+                }
+                Outcome::Success(db)
+            }
+            None => Outcome::Error((Status::NotFound, ())),
+        }
+    }
+}
+
+/// The `/ns/<namespace>` prefix a request arrived with, which [`NamespacePathFairing`] strips;
+/// responses that point back into the API (redirects) put it back. Empty without one.
+#[derive(Default)]
+pub struct PathPrefix(String);
+
+impl PathPrefix {
+    pub fn of<'r>(req: &'r Request<'_>) -> &'r str {
+        &req.local_cache(PathPrefix::default).0
+    }
+}
+
+/// Fairing that turns `/ns/<namespace>/...` into `/...` with an `X-Namespace` header,
+/// before any other rewriting (by-name room addressing) or routing happens.
+pub struct NamespacePathFairing;
+
+#[rocket::async_trait]
+impl Fairing for NamespacePathFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Namespace Path Prefix",
+            kind: Kind::Request,
+        }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _data: &mut Data<'_>) {
+        let path = req.uri().path().as_str().to_string();
+        let Some(rest) = path.strip_prefix(PATH_PREFIX) else {
+            return;
+        };
+        let (name, tail) = rest.split_once('/').unwrap_or((rest, ""));
+        if name.is_empty() {
+            return;
+        }
+        let mut uri = format!("/{tail}");
+        if let Some(query) = req.uri().query() {
+            uri.push('?');
+            uri.push_str(query.as_str());
+        }
+        if let Ok(origin) = Origin::parse_owned(uri) {
+            req.replace_header(Header::new(NAMESPACE_HEADER, name.to_string()));
+            req.local_cache(|| PathPrefix(format!("{PATH_PREFIX}{name}")));
+            req.set_uri(origin);
+        }
+    }
+}
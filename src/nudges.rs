@@ -0,0 +1,153 @@
+//! Mention nudges: a sender whose profile sets `mention_nudge_minutes` gets a DM from `system`
+//! pointing at any @mention they have neither read nor been online to see within that many
+//! minutes. Each mention is nudged at most once.
+
+use crate::events::{ChatEvent, Published};
+use crate::models::Message;
+use crate::routes::PresenceTracker;
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{params, Connection};
+use tokio::sync::broadcast;
+
+/// Interval between checks for mentions to nudge (seconds).
+const NUDGE_INTERVAL_SECS: u64 = 30;
+
+/// Mentions older than their delay plus this are left alone, so turning nudges on doesn't
+/// DM a backlog of old mentions.
+const NUDGE_LOOKBACK_HOURS: i64 = 24;
+
+/// Longest excerpt of the mentioning message quoted in the DM (characters).
+const EXCERPT_CHARS: usize = 140;
+
+/// Nudges unread mentions in one database; `namespace` tags the DMs it publishes.
+pub fn spawn_nudger(
+    db_path: String,
+    events: broadcast::Sender<Published>,
+    presence: PresenceTracker,
+    namespace: Option<String>,
+) {
+    tokio::spawn(async move {
+        let conn = match crate::db::open(&db_path) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("⚠️ Mention nudger: failed to open DB: {e}");
+                return;
+            }
+        };
+        crate::db::DbConfig::from_env().apply(&conn).ok();
+
+        loop {
+            tokio::time::sleep(std::time::Duration::from_secs(NUDGE_INTERVAL_SECS)).await;
+            for msg in run_due(&conn, &presence, Utc::now()) {
+                let _ = events.send(Published::in_namespace(ChatEvent::NewMessage(msg), namespace.as_deref()));
+            }
+        }
+    });
+}
+
+struct PendingMention {
+    message_id: String,
+    target: String,
+    delay_minutes: i64,
+    room_id: String,
+    room_name: String,
+    sender: String,
+    content: String,
+    seq: i64,
+    created_at: String,
+}
+
+fn excerpt(content: &str) -> String {
+    let line = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    if line.chars().count() > EXCERPT_CHARS {
+        format!("{}…", line.chars().take(EXCERPT_CHARS).collect::<String>())
+    } else {
+        line
+    }
+}
+
+/// DM every opted-in sender about mentions that have waited past their delay unread while the
+/// sender was offline. Returns the DMs so the caller can publish them.
+pub fn run_due(conn: &Connection, presence: &PresenceTracker, now: DateTime<Utc>) -> Vec<Message> {
+    // Widest possible window: the longest delay (1440 minutes) plus the lookback
+    let oldest = (now - Duration::minutes(1440) - Duration::hours(NUDGE_LOOKBACK_HOURS)).to_rfc3339();
+    let pending: Vec<PendingMention> = conn
+        .prepare(
+            "SELECT m.id, p.sender, p.mention_nudge_minutes, m.room_id, r.name, m.sender, m.content, m.seq, m.created_at
+             FROM mentions mn
+             JOIN profiles p ON p.mention_nudge_minutes IS NOT NULL
+                AND (mn.target = LOWER(p.sender)
+                     OR mn.target IN (SELECT alias_key FROM sender_aliases WHERE sender = p.sender))
+             JOIN messages m ON m.id = mn.message_id
+             JOIN rooms r ON r.id = m.room_id
+             WHERE m.kind = 'message'
+               AND COALESCE(r.room_type, 'room') != 'dm'
+               AND m.created_at > ?1
+               AND m.sender != p.sender
+               AND LOWER(m.sender) NOT IN (SELECT alias_key FROM sender_aliases WHERE sender = p.sender)
+               AND m.seq > COALESCE(
+                   (SELECT last_read_seq FROM read_positions WHERE room_id = m.room_id AND sender = p.sender), 0)
+               AND NOT EXISTS (SELECT 1 FROM mention_nudges n WHERE n.message_id = m.id AND n.target = p.sender)
+             ORDER BY m.seq",
+        )
+        .and_then(|mut s| {
+            s.query_map(params![oldest], |r| {
+                Ok(PendingMention {
+                    message_id: r.get(0)?,
+                    target: r.get(1)?,
+                    delay_minutes: r.get(2)?,
+                    room_id: r.get(3)?,
+                    room_name: r.get(4)?,
+                    sender: r.get(5)?,
+                    content: r.get(6)?,
+                    seq: r.get(7)?,
+                    created_at: r.get(8)?,
+                })
+            })
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .unwrap_or_default();
+
+    let mut sent = Vec::new();
+    for mention in pending {
+        let Ok(created_at) = DateTime::parse_from_rfc3339(&mention.created_at) else {
+            continue;
+        };
+        let due_at = created_at.with_timezone(&Utc) + Duration::minutes(mention.delay_minutes);
+        if due_at > now || due_at + Duration::hours(NUDGE_LOOKBACK_HOURS) < now {
+            continue;
+        }
+        // Connected somewhere: they'll see it, and get nudged later if they drop off unread
+        if presence.is_present(&mention.target) {
+            continue;
+        }
+        let claimed = conn
+            .execute(
+                "INSERT OR IGNORE INTO mention_nudges (message_id, target, created_at) VALUES (?1, ?2, ?3)",
+                params![&mention.message_id, &mention.target, now.to_rfc3339()],
+            )
+            .unwrap_or(0);
+        if claimed == 0 {
+            continue;
+        }
+        let Ok((dm_room_id, _)) = crate::routes::get_or_create_dm_room(conn, "system", &mention.target) else {
+            continue;
+        };
+        if let Some(msg) = crate::db::insert_localized_system_message(
+            conn,
+            &dm_room_id,
+            "mention_nudge",
+            serde_json::json!({
+                "sender": mention.sender,
+                "room_name": mention.room_name,
+                "room_id": mention.room_id,
+                "message_id": mention.message_id,
+                "seq": mention.seq,
+                "excerpt": excerpt(&mention.content),
+            }),
+        ) {
+            sent.push(msg);
+        }
+    }
+    sent
+}
@@ -0,0 +1,141 @@
+//! Transactional outbox for events that must not be lost. A write records its event in
+//! `event_outbox` inside the same transaction as the change itself; once committed, the route
+//! publishes it and marks the row published. If the process dies in between, the relay finds
+//! the row on the next start and publishes it then, so SSE clients and webhooks still see it.
+//! Delivery is at least once: consumers dedupe on the event id (SSE `id:`).
+
+use crate::events::{ChatEvent, Published};
+use rusqlite::{params, Connection};
+use tokio::sync::broadcast;
+
+/// How often the relay looks for events that were committed but never published.
+pub const RELAY_INTERVAL_SECS: u64 = 5;
+
+/// Unpublished rows younger than this belong to requests that are still publishing them.
+const RELAY_GRACE_SECS: i64 = 10;
+
+/// Published events are kept this long so SSE clients can resume with `Last-Event-ID`.
+const RETAIN_HOURS: i64 = 24;
+
+/// An event read back from the outbox.
+#[derive(Debug, Clone)]
+pub struct StoredEvent {
+    pub id: i64,
+    pub event: ChatEvent,
+    pub request_id: Option<String>,
+}
+
+/// The room an event belongs to, for room-scoped replay. None for server-wide events.
+fn room_of(event: &ChatEvent) -> Option<&str> {
+    match event {
+        ChatEvent::NewMessage(m) | ChatEvent::MessageEdited(m) | ChatEvent::MessageFinalized(m) => Some(&m.room_id),
+        ChatEvent::MessageDeleted { room_id, .. } | ChatEvent::MessageRedacted { room_id, .. } => Some(room_id),
+        ChatEvent::ReactionAdded(r) | ChatEvent::ReactionRemoved(r) => Some(&r.room_id),
+        ChatEvent::MessageAppended(a) => Some(&a.room_id),
+        _ => None,
+    }
+}
+
+/// Record `event` for publishing. Call inside the transaction of the write it describes, then
+/// publish with [`crate::events::Events::publish_recorded`] after the commit.
+pub fn record(conn: &Connection, event: &ChatEvent, request_id: Option<&str>) -> rusqlite::Result<i64> {
+    let json = serde_json::to_string(event).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+    conn.execute(
+        "INSERT INTO event_outbox (room_id, event, request_id, created_at) VALUES (?1, ?2, ?3, ?4)",
+        params![room_of(event), json, request_id, chrono::Utc::now().to_rfc3339()],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn mark_published(conn: &Connection, id: i64) {
+    conn.execute(
+        "UPDATE event_outbox SET published_at = ?1 WHERE id = ?2 AND published_at IS NULL",
+        params![chrono::Utc::now().to_rfc3339(), id],
+    )
+    .ok();
+}
+
+fn read(conn: &Connection, sql: &str, params: &[&dyn rusqlite::ToSql]) -> Vec<StoredEvent> {
+    let Ok(mut stmt) = conn.prepare(sql) else {
+        return Vec::new();
+    };
+    stmt.query_map(params, |r| Ok((r.get::<_, i64>(0)?, r.get::<_, String>(1)?, r.get(2)?)))
+        .map(|rows| {
+            rows.filter_map(|r| r.ok())
+                .filter_map(|(id, json, request_id)| match serde_json::from_str(&json) {
+                    Ok(event) => Some(StoredEvent { id, event, request_id }),
+                    Err(e) => {
+                        eprintln!("⚠️ Outbox event {id} could not be read: {e}");
+                        None
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// A room's committed events after `after_id`, oldest first, for SSE resume.
+pub fn room_events_after(conn: &Connection, room_id: &str, after_id: i64, limit: i64) -> Vec<StoredEvent> {
+    read(
+        conn,
+        "SELECT id, event, request_id FROM event_outbox WHERE room_id = ?1 AND id > ?2 ORDER BY id LIMIT ?3",
+        &[&room_id, &after_id, &limit],
+    )
+}
+
+/// When event `id` was recorded, while it's still retained.
+pub fn recorded_at(conn: &Connection, id: i64) -> Option<String> {
+    conn.query_row("SELECT created_at FROM event_outbox WHERE id = ?1", params![id], |r| r.get(0))
+        .ok()
+}
+
+/// Publish events that were committed but never published (the process stopped between the
+/// commit and the broadcast), oldest first, and drop published ones past retention.
+/// Returns how many were republished. `namespace` names the database `conn` is on.
+pub fn relay(conn: &Connection, events: &broadcast::Sender<Published>, namespace: Option<&str>) -> usize {
+    let cutoff = (chrono::Utc::now() - chrono::Duration::seconds(RELAY_GRACE_SECS)).to_rfc3339();
+    let pending = read(
+        conn,
+        "SELECT id, event, request_id FROM event_outbox WHERE published_at IS NULL AND created_at < ?1 ORDER BY id",
+        &[&cutoff],
+    );
+    for stored in &pending {
+        let _ = events.send(Published {
+            event: stored.event.clone(),
+            request_id: stored.request_id.clone(),
+            trace_context: None,
+            event_id: Some(stored.id),
+            namespace: namespace.map(String::from),
+        });
+        mark_published(conn, stored.id);
+    }
+    let expired = (chrono::Utc::now() - chrono::Duration::hours(RETAIN_HOURS)).to_rfc3339();
+    conn.execute(
+        "DELETE FROM event_outbox WHERE published_at IS NOT NULL AND published_at < ?1",
+        params![expired],
+    )
+    .ok();
+    pending.len()
+}
+
+/// Spawns the relay: one pass at startup to recover events a crash left behind, then one
+/// every [`RELAY_INTERVAL_SECS`]. One relay runs per database.
+pub fn spawn_relay(db_path: String, events: broadcast::Sender<Published>, namespace: Option<String>) {
+    tokio::spawn(async move {
+        let conn = match crate::db::open(&db_path) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("⚠️ Outbox relay: failed to open DB: {e}");
+                return;
+            }
+        };
+        crate::db::DbConfig::from_env().apply(&conn).ok();
+        loop {
+            let relayed = relay(&conn, &events, namespace.as_deref());
+            if relayed > 0 {
+                println!("📬 Outbox relay published {relayed} event(s) that missed the broadcast");
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(RELAY_INTERVAL_SECS)).await;
+        }
+    });
+}
@@ -0,0 +1,239 @@
+//! Incremental message edits: a JSON Patch (RFC 6902) against `{"content", "metadata"}` or a
+//! unified diff against the content, so agents correcting a long message send only the change.
+
+use serde_json::Value;
+
+/// Why a patch couldn't be applied.
+#[derive(Debug)]
+pub enum PatchError {
+    /// The patch itself is malformed (bad op, bad pointer, unparsable hunk header).
+    Invalid(String),
+    /// The patch is well-formed but doesn't match the current text (context mismatch, failed `test`).
+    Conflict(String),
+}
+
+/// Apply JSON Patch operations to `doc` in order. All-or-nothing: on error `doc` is untouched.
+pub fn apply_json_patch(doc: &mut Value, ops: &[Value]) -> Result<(), PatchError> {
+    let mut work = doc.clone();
+    for (i, op) in ops.iter().enumerate() {
+        apply_op(&mut work, op).map_err(|e| match e {
+            PatchError::Invalid(m) => PatchError::Invalid(format!("operation {i}: {m}")),
+            PatchError::Conflict(m) => PatchError::Conflict(format!("operation {i}: {m}")),
+        })?;
+    }
+    *doc = work;
+    Ok(())
+}
+
+fn apply_op(doc: &mut Value, op: &Value) -> Result<(), PatchError> {
+    let field = |name: &str| {
+        op.get(name)
+            .and_then(Value::as_str)
+            .ok_or_else(|| PatchError::Invalid(format!("missing string field '{name}'")))
+    };
+    let value = || {
+        op.get("value")
+            .cloned()
+            .ok_or_else(|| PatchError::Invalid("missing field 'value'".to_string()))
+    };
+    let path = parse_pointer(field("path")?)?;
+    match field("op")? {
+        "add" => add(doc, &path, value()?),
+        "remove" => remove(doc, &path).map(|_| ()),
+        "replace" => {
+            remove(doc, &path)?;
+            add(doc, &path, value()?)
+        }
+        "move" => {
+            let from = parse_pointer(field("from")?)?;
+            if path.len() > from.len() && path[..from.len()] == from[..] {
+                return Err(PatchError::Invalid("cannot move a value into itself".to_string()));
+            }
+            let moved = remove(doc, &from)?;
+            add(doc, &path, moved)
+        }
+        "copy" => {
+            let from = parse_pointer(field("from")?)?;
+            let copied = get(doc, &from)
+                .cloned()
+                .ok_or_else(|| PatchError::Conflict(format!("no value at '{}'", field("from").unwrap_or(""))))?;
+            add(doc, &path, copied)
+        }
+        "test" => {
+            let expected = value()?;
+            if get(doc, &path) == Some(&expected) {
+                Ok(())
+            } else {
+                Err(PatchError::Conflict(format!("test failed at '{}'", field("path")?)))
+            }
+        }
+        other => Err(PatchError::Invalid(format!("unknown op '{other}'"))),
+    }
+}
+
+/// Split a JSON Pointer (RFC 6901) into unescaped reference tokens.
+fn parse_pointer(pointer: &str) -> Result<Vec<String>, PatchError> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(rest) = pointer.strip_prefix('/') else {
+        return Err(PatchError::Invalid(format!("pointer '{pointer}' must start with '/'")));
+    };
+    Ok(rest.split('/').map(|t| t.replace("~1", "/").replace("~0", "~")).collect())
+}
+
+fn array_index(token: &str, len: usize, allow_end: bool) -> Result<usize, PatchError> {
+    if allow_end && token == "-" {
+        return Ok(len);
+    }
+    let valid = !token.is_empty() && token.chars().all(|c| c.is_ascii_digit()) && (token == "0" || !token.starts_with('0'));
+    let index: usize = token
+        .parse()
+        .ok()
+        .filter(|_| valid)
+        .ok_or_else(|| PatchError::Invalid(format!("'{token}' is not an array index")))?;
+    let max = if allow_end { len } else { len.saturating_sub(1) };
+    if index > max || (!allow_end && len == 0) {
+        return Err(PatchError::Conflict(format!("array index {index} out of bounds")));
+    }
+    Ok(index)
+}
+
+fn get<'a>(doc: &'a Value, path: &[String]) -> Option<&'a Value> {
+    path.iter().try_fold(doc, |node, token| match node {
+        Value::Object(map) => map.get(token),
+        Value::Array(items) => array_index(token, items.len(), false).ok().and_then(|i| items.get(i)),
+        _ => None,
+    })
+}
+
+fn parent<'a>(doc: &'a mut Value, path: &[String]) -> Result<&'a mut Value, PatchError> {
+    let mut node = doc;
+    for token in path {
+        node = match node {
+            Value::Object(map) => map.get_mut(token),
+            Value::Array(items) => {
+                let i = array_index(token, items.len(), false)?;
+                items.get_mut(i)
+            }
+            _ => None,
+        }
+        .ok_or_else(|| PatchError::Conflict(format!("no value at '/{}'", path.join("/"))))?;
+    }
+    Ok(node)
+}
+
+fn add(doc: &mut Value, path: &[String], value: Value) -> Result<(), PatchError> {
+    let Some((last, init)) = path.split_last() else {
+        *doc = value;
+        return Ok(());
+    };
+    match parent(doc, init)? {
+        Value::Object(map) => {
+            map.insert(last.clone(), value);
+            Ok(())
+        }
+        Value::Array(items) => {
+            let i = array_index(last, items.len(), true)?;
+            items.insert(i, value);
+            Ok(())
+        }
+        _ => Err(PatchError::Conflict(format!("cannot add below a scalar at '/{}'", init.join("/")))),
+    }
+}
+
+fn remove(doc: &mut Value, path: &[String]) -> Result<Value, PatchError> {
+    let Some((last, init)) = path.split_last() else {
+        return Err(PatchError::Invalid("cannot remove the whole document".to_string()));
+    };
+    let missing = || PatchError::Conflict(format!("no value at '/{}'", path.join("/")));
+    match parent(doc, init)? {
+        Value::Object(map) => map.remove(last).ok_or_else(missing),
+        Value::Array(items) => {
+            let i = array_index(last, items.len(), false)?;
+            Ok(items.remove(i))
+        }
+        _ => Err(missing()),
+    }
+}
+
+struct Hunk {
+    old_start: usize,
+    old: Vec<String>,
+    new: Vec<String>,
+}
+
+/// Parse `@@ -l[,s] +l[,s] @@` and return the old start line and length.
+fn parse_hunk_header(line: &str) -> Option<(usize, usize)> {
+    let old = line.strip_prefix("@@ -")?.split_whitespace().next()?;
+    let (start, len) = match old.split_once(',') {
+        Some((s, l)) => (s.parse().ok()?, l.parse().ok()?),
+        None => (old.parse().ok()?, 1),
+    };
+    Some((start, len))
+}
+
+fn parse_unified_diff(diff: &str) -> Result<Vec<Hunk>, PatchError> {
+    let mut hunks: Vec<Hunk> = Vec::new();
+    for line in diff.lines() {
+        if line.starts_with("@@") {
+            let (start, len) = parse_hunk_header(line)
+                .ok_or_else(|| PatchError::Invalid(format!("bad hunk header '{line}'")))?;
+            // A zero-length old range names the line *after which* to insert
+            let old_start = if len == 0 { start } else { start.saturating_sub(1) };
+            hunks.push(Hunk { old_start, old: Vec::new(), new: Vec::new() });
+            continue;
+        }
+        let Some(hunk) = hunks.last_mut() else {
+            // File headers (`---`, `+++`, `diff`, `index`) before the first hunk
+            continue;
+        };
+        match line.chars().next() {
+            Some('+') => hunk.new.push(line[1..].to_string()),
+            Some('-') => hunk.old.push(line[1..].to_string()),
+            Some(' ') => {
+                hunk.old.push(line[1..].to_string());
+                hunk.new.push(line[1..].to_string());
+            }
+            // Editors and agents often strip the space off blank context lines
+            None => {
+                hunk.old.push(String::new());
+                hunk.new.push(String::new());
+            }
+            Some('\\') => {}
+            Some(_) => return Err(PatchError::Invalid(format!("unexpected diff line '{line}'"))),
+        }
+    }
+    if hunks.is_empty() {
+        return Err(PatchError::Invalid("diff has no hunks".to_string()));
+    }
+    Ok(hunks)
+}
+
+/// Apply a unified diff to `text` line by line. Each hunk is tried at its stated line first
+/// and otherwise at the first later position where its context matches exactly.
+pub fn apply_unified_diff(text: &str, diff: &str) -> Result<String, PatchError> {
+    let hunks = parse_unified_diff(diff)?;
+    let lines: Vec<&str> = text.split('\n').collect();
+    let mut out: Vec<String> = Vec::with_capacity(lines.len());
+    let mut cursor = 0;
+    for (n, hunk) in hunks.iter().enumerate() {
+        let matches_at = |pos: usize| {
+            pos >= cursor
+                && pos + hunk.old.len() <= lines.len()
+                && hunk.old.iter().zip(&lines[pos..]).all(|(a, b)| a == b)
+        };
+        let pos = if matches_at(hunk.old_start) {
+            hunk.old_start
+        } else {
+            (cursor..=lines.len().saturating_sub(hunk.old.len()))
+                .find(|&p| matches_at(p))
+                .ok_or_else(|| PatchError::Conflict(format!("hunk {} does not match the current content", n + 1)))?
+        };
+        out.extend(lines[cursor..pos].iter().map(|l| l.to_string()));
+        out.extend(hunk.new.iter().cloned());
+        cursor = pos + hunk.old.len();
+    }
+    out.extend(lines[cursor..].iter().map(|l| l.to_string()));
+    Ok(out.join("\n"))
+}
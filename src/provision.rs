@@ -0,0 +1,159 @@
+//! Rooms created on first boot. By default that is just `#general`; an operator standing up an
+//! instance for a project can describe a ready-made structure instead (names, descriptions, tags
+//! and pinned welcome messages) so agents land in the right rooms from the start.
+
+use rusqlite::{params, Connection};
+use serde::Deserialize;
+use std::env;
+
+/// One room to create on first boot.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DefaultRoom {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Language code selecting the search tokenizer (unset = English stemming)
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Messages posted by `system` and pinned, oldest first
+    #[serde(default)]
+    pub pins: Vec<String>,
+}
+
+/// The rooms provisioned into an empty database.
+///
+/// Environment variables:
+/// - `DEFAULT_ROOMS_FILE` — Path to a JSON array of rooms (`[{"name", "description", "tags",
+///   "language", "pins"}]`); takes precedence over `DEFAULT_ROOMS`
+/// - `DEFAULT_ROOMS` — The same JSON array inline (default: a single `general` room)
+#[derive(Debug, Clone)]
+pub struct DefaultRooms {
+    pub rooms: Vec<DefaultRoom>,
+}
+
+impl Default for DefaultRooms {
+    fn default() -> Self {
+        Self {
+            rooms: vec![DefaultRoom {
+                name: "general".to_string(),
+                description: "Default chat room".to_string(),
+                tags: Vec::new(),
+                language: None,
+                pins: Vec::new(),
+            }],
+        }
+    }
+}
+
+impl DefaultRooms {
+    pub fn from_env() -> Self {
+        let (source, json) = if let Ok(path) = env::var("DEFAULT_ROOMS_FILE") {
+            match std::fs::read_to_string(&path) {
+                Ok(json) => (path, json),
+                Err(e) => {
+                    eprintln!("⚠️ Could not read DEFAULT_ROOMS_FILE {path}: {e}; using the default rooms");
+                    return Self::default();
+                }
+            }
+        } else if let Ok(json) = env::var("DEFAULT_ROOMS") {
+            ("DEFAULT_ROOMS".to_string(), json)
+        } else {
+            return Self::default();
+        };
+        match Self::parse(&json) {
+            Ok(rooms) => rooms,
+            Err(e) => {
+                eprintln!("⚠️ Invalid default rooms in {source}: {e}; using the default rooms");
+                Self::default()
+            }
+        }
+    }
+
+    /// Parse and validate a JSON array of rooms.
+    pub fn parse(json: &str) -> Result<Self, String> {
+        let mut rooms: Vec<DefaultRoom> = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        let mut names: Vec<String> = Vec::new();
+        for room in &mut rooms {
+            room.name = room.name.trim().to_string();
+            if room.name.is_empty() || room.name.len() > 100 {
+                return Err("Room names must be 1-100 characters".to_string());
+            }
+            if names.contains(&room.name) {
+                return Err(format!("Room '{}' is listed more than once", room.name));
+            }
+            names.push(room.name.clone());
+            room.tags = crate::routes::normalize_tags(&room.tags)?;
+            if let Some(lang) = &room.language
+                && crate::db::search_tokenizer_for_language(lang).is_none()
+            {
+                return Err(format!("Room '{}' has an invalid language '{lang}'", room.name));
+            }
+            room.pins.retain(|p| !p.trim().is_empty());
+        }
+        Ok(Self { rooms })
+    }
+}
+
+/// Create `defaults` if the database has no rooms yet (first boot). Returns the names created.
+pub fn provision(conn: &Connection, defaults: &DefaultRooms) -> Vec<String> {
+    let existing: i64 = conn
+        .query_row("SELECT COUNT(*) FROM rooms", [], |r| r.get(0))
+        .unwrap_or(1);
+    if existing > 0 {
+        return Vec::new();
+    }
+    defaults
+        .rooms
+        .iter()
+        .filter(|room| match create_room(conn, room) {
+            Ok(()) => true,
+            Err(e) => {
+                eprintln!("⚠️ Could not create default room {}: {e}", room.name);
+                false
+            }
+        })
+        .map(|room| room.name.clone())
+        .collect()
+}
+
+fn create_room(conn: &Connection, room: &DefaultRoom) -> rusqlite::Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    let room_id = uuid::Uuid::new_v4().to_string();
+    let now = chrono::Utc::now().to_rfc3339();
+    tx.execute(
+        "INSERT INTO rooms (id, name, description, created_by, created_at, updated_at, admin_key, tags,
+                            language, search_tokenizer)
+         VALUES (?1, ?2, ?3, 'system', ?4, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            &room_id,
+            &room.name,
+            &room.description,
+            &now,
+            crate::db::generate_admin_key(),
+            serde_json::to_string(&room.tags).unwrap_or_else(|_| "[]".to_string()),
+            &room.language,
+            room.language
+                .as_deref()
+                .and_then(crate::db::search_tokenizer_for_language)
+                .unwrap_or("porter")
+        ],
+    )?;
+    for pin in &room.pins {
+        let id = uuid::Uuid::new_v4().to_string();
+        let seq: i64 = tx.query_row("SELECT COALESCE(MAX(seq), 0) + 1 FROM messages", [], |r| r.get(0))?;
+        tx.execute(
+            "INSERT INTO messages (id, room_id, sender, content, metadata, created_at, sender_type, seq, pinned_at, pinned_by)
+             VALUES (?1, ?2, 'system', ?3, '{}', ?4, 'system', ?5, ?4, 'system')",
+            params![&id, &room_id, pin, &now, seq],
+        )?;
+        tx.execute(
+            "INSERT INTO pin_history (room_id, message_id, action, actor, sender, content, created_at)
+             VALUES (?1, ?2, 'pinned', 'system', 'system', ?3, ?4)",
+            params![&room_id, &id, pin, &now],
+        )?;
+        crate::db::upsert_fts(&tx, &id);
+    }
+    tx.commit()
+}
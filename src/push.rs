@@ -0,0 +1,280 @@
+//! Web Push (RFC 8030) for the bundled frontend: browsers subscribe per sender, and mentions and
+//! DMs addressed to that sender are pushed to the browser's push service so the OS shows a
+//! notification even with the tab closed.
+//!
+//! Payloads are encrypted per RFC 8291 (`aes128gcm`) and requests are signed with a VAPID key
+//! (RFC 8292): `VAPID_PRIVATE_KEY` if set, otherwise one generated on first start and kept in the
+//! database so existing subscriptions stay valid across restarts.
+
+use crate::events::{ChatEvent, Published};
+use crate::models::Message;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use p256::ecdsa::signature::Signer;
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+use p256::{PublicKey, SecretKey};
+use rand_core::{OsRng, RngCore};
+use rusqlite::{params, Connection};
+use sha2::Sha256;
+use tokio::sync::broadcast;
+
+/// How long push services hold an undelivered notification (seconds).
+const PUSH_TTL_SECS: u32 = 86_400;
+/// Longest message excerpt in a notification body.
+const BODY_PREVIEW_CHARS: usize = 140;
+
+/// The server's VAPID identity.
+#[derive(Clone)]
+pub struct PushConfig {
+    key: SecretKey,
+    /// Uncompressed public key, base64url — the browser's `applicationServerKey`
+    pub public_key: String,
+    /// `sub` claim: a `mailto:` or `https:` contact for push services
+    pub subject: String,
+}
+
+impl PushConfig {
+    /// Load the VAPID key from `VAPID_PRIVATE_KEY` (base64url, 32 bytes), or from the database,
+    /// generating and storing one the first time. None only if the key can't be read or stored.
+    pub fn load(conn: &Connection) -> Option<PushConfig> {
+        let subject = std::env::var("VAPID_SUBJECT").unwrap_or_else(|_| "mailto:admin@localhost".to_string());
+        let key = match std::env::var("VAPID_PRIVATE_KEY") {
+            Ok(encoded) => match URL_SAFE_NO_PAD
+                .decode(encoded.trim().trim_end_matches('='))
+                .ok()
+                .and_then(|bytes| SecretKey::from_slice(&bytes).ok())
+            {
+                Some(key) => key,
+                None => {
+                    eprintln!("⚠️ VAPID_PRIVATE_KEY is not a base64url P-256 private key; Web Push disabled");
+                    return None;
+                }
+            },
+            Err(_) => stored_key(conn)?,
+        };
+        let public_key = URL_SAFE_NO_PAD.encode(key.public_key().to_encoded_point(false).as_bytes());
+        Some(PushConfig { key, public_key, subject })
+    }
+}
+
+fn stored_key(conn: &Connection) -> Option<SecretKey> {
+    let existing: Option<String> = conn
+        .query_row("SELECT private_key FROM vapid_keys WHERE id = 1", [], |r| r.get(0))
+        .ok();
+    if let Some(key) = existing.and_then(|k| URL_SAFE_NO_PAD.decode(k).ok()).and_then(|b| SecretKey::from_slice(&b).ok()) {
+        return Some(key);
+    }
+    let key = SecretKey::random(&mut OsRng);
+    conn.execute(
+        "INSERT OR REPLACE INTO vapid_keys (id, private_key, created_at) VALUES (1, ?1, ?2)",
+        params![URL_SAFE_NO_PAD.encode(key.to_bytes()), chrono::Utc::now().to_rfc3339()],
+    )
+    .ok()?;
+    Some(key)
+}
+
+fn hmac_sha256(key: &[u8], parts: &[&[u8]]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    for part in parts {
+        mac.update(part);
+    }
+    mac.finalize().into_bytes().into()
+}
+
+/// Encrypt `payload` for a subscription (RFC 8291, single `aes128gcm` record).
+pub fn encrypt(p256dh: &[u8], auth: &[u8], payload: &[u8]) -> Result<Vec<u8>, String> {
+    use aes_gcm::aead::Aead;
+    use aes_gcm::{Aes128Gcm, KeyInit, Nonce};
+
+    let ua_public = PublicKey::from_sec1_bytes(p256dh).map_err(|_| "invalid p256dh key")?;
+    let ua_bytes = ua_public.to_encoded_point(false);
+    let as_secret = SecretKey::random(&mut OsRng);
+    let as_bytes = as_secret.public_key().to_encoded_point(false);
+    let shared = p256::ecdh::diffie_hellman(as_secret.to_nonzero_scalar(), ua_public.as_affine());
+
+    // IKM = HKDF(auth, ecdh_secret, "WebPush: info" || 0x00 || ua_public || as_public), one block
+    let prk_key = hmac_sha256(auth, &[shared.raw_secret_bytes().as_slice()]);
+    let ikm = hmac_sha256(&prk_key, &[b"WebPush: info\0", ua_bytes.as_bytes(), as_bytes.as_bytes(), &[1]]);
+
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    let prk = hmac_sha256(&salt, &[&ikm]);
+    let cek = hmac_sha256(&prk, &[b"Content-Encoding: aes128gcm\0", &[1]]);
+    let nonce = hmac_sha256(&prk, &[b"Content-Encoding: nonce\0", &[1]]);
+
+    // Padding delimiter 0x02 marks the last (only) record
+    let mut plaintext = payload.to_vec();
+    plaintext.push(2);
+    let cipher = Aes128Gcm::new_from_slice(&cek[..16]).map_err(|e| e.to_string())?;
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce[..12]), plaintext.as_slice())
+        .map_err(|e| e.to_string())?;
+
+    // Record size only has to cover the one record (plaintext + 16-byte tag)
+    let record_size = (ciphertext.len() as u32).max(4096);
+    let mut body = Vec::with_capacity(16 + 4 + 1 + 65 + ciphertext.len());
+    body.extend_from_slice(&salt);
+    body.extend_from_slice(&record_size.to_be_bytes());
+    body.push(as_bytes.as_bytes().len() as u8);
+    body.extend_from_slice(as_bytes.as_bytes());
+    body.extend_from_slice(&ciphertext);
+    Ok(body)
+}
+
+/// `Authorization: vapid t=<jwt>, k=<public key>` for a push service endpoint.
+fn vapid_header(config: &PushConfig, endpoint: &str) -> Option<String> {
+    let url = reqwest::Url::parse(endpoint).ok()?;
+    let audience = url.origin().ascii_serialization();
+    let exp = chrono::Utc::now().timestamp() + 12 * 3600;
+    let header = URL_SAFE_NO_PAD.encode(br#"{"typ":"JWT","alg":"ES256"}"#);
+    let claims = URL_SAFE_NO_PAD.encode(
+        serde_json::json!({"aud": audience, "exp": exp, "sub": config.subject}).to_string(),
+    );
+    let signing_input = format!("{header}.{claims}");
+    let signature: p256::ecdsa::Signature = p256::ecdsa::SigningKey::from(&config.key).sign(signing_input.as_bytes());
+    let jwt = format!("{signing_input}.{}", URL_SAFE_NO_PAD.encode(signature.to_bytes()));
+    Some(format!("vapid t={jwt}, k={}", config.public_key))
+}
+
+/// Who should be notified about a message: @mentioned senders, and the other side of a DM.
+/// The author is never notified of their own message.
+fn recipients(conn: &Connection, msg: &Message) -> Vec<(String, &'static str)> {
+    if msg.kind == "system" {
+        return Vec::new();
+    }
+    let mut out: Vec<(String, &'static str)> = Vec::new();
+    let dm_name: Option<String> = conn
+        .query_row(
+            "SELECT name FROM rooms WHERE id = ?1 AND room_type = 'dm'",
+            params![&msg.room_id],
+            |r| r.get(0),
+        )
+        .ok();
+    if let Some(name) = dm_name {
+        // DM rooms are named dm:{a}:{b}
+        for member in name.splitn(3, ':').skip(1) {
+            if !member.eq_ignore_ascii_case(&msg.sender) {
+                out.push((member.to_string(), "dm"));
+            }
+        }
+    }
+    for mention in crate::db::parse_mentions(&msg.content) {
+        if !mention.eq_ignore_ascii_case(&msg.sender) && !out.iter().any(|(s, _)| s.eq_ignore_ascii_case(&mention)) {
+            out.push((mention, "mention"));
+        }
+    }
+    out
+}
+
+/// Spawns the dispatcher that turns new messages into Web Push notifications for subscribed
+/// recipients. Subscriptions the push service reports as gone (404/410) are removed.
+pub fn spawn_push_dispatcher(mut receiver: broadcast::Receiver<Published>, db_path: String, config: PushConfig) {
+    tokio::spawn(async move {
+        let client = match reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(10))
+            .build()
+        {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("⚠️ Push dispatcher: failed to create HTTP client: {e}");
+                return;
+            }
+        };
+        let conn = match crate::db::open(&db_path) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("⚠️ Push dispatcher: failed to open DB: {e}");
+                return;
+            }
+        };
+        crate::db::DbConfig::from_env().apply(&conn).ok();
+
+        loop {
+            let msg = match receiver.recv().await {
+                // Push subscriptions are stored in the main database, so only its messages notify
+                Ok(Published { event: ChatEvent::NewMessage(msg), namespace: None, .. }) => msg,
+                Ok(_) => continue,
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    eprintln!("⚠️ Push dispatcher lagged, missed {} events", n);
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+            for (id, endpoint, body) in notifications(&conn, &msg) {
+                let Some(auth) = vapid_header(&config, &endpoint) else {
+                    continue;
+                };
+                let res = client
+                    .post(&endpoint)
+                    .header("Authorization", auth)
+                    .header("Content-Encoding", "aes128gcm")
+                    .header("Content-Type", "application/octet-stream")
+                    .header("TTL", PUSH_TTL_SECS.to_string())
+                    .header("Urgency", "high")
+                    .body(body)
+                    .send()
+                    .await;
+                match res {
+                    Ok(r) if r.status().as_u16() == 404 || r.status().as_u16() == 410 => {
+                        conn.execute("DELETE FROM push_subscriptions WHERE id = ?1", params![id]).ok();
+                    }
+                    Ok(r) if !r.status().is_success() => {
+                        eprintln!("⚠️ Push to {} failed: {}", endpoint, r.status());
+                    }
+                    Ok(_) => {}
+                    Err(e) => eprintln!("⚠️ Push to {} failed: {}", endpoint, e),
+                }
+            }
+        }
+    });
+}
+
+/// Encrypted notification bodies for every subscription of every recipient, as (subscription id, endpoint, body).
+fn notifications(conn: &Connection, msg: &Message) -> Vec<(String, String, Vec<u8>)> {
+    let recipients = recipients(conn, msg);
+    if recipients.is_empty() {
+        return Vec::new();
+    }
+    let room_name: String = conn
+        .query_row("SELECT name FROM rooms WHERE id = ?1", params![&msg.room_id], |r| r.get(0))
+        .unwrap_or_default();
+    let preview = match msg.content.char_indices().nth(BODY_PREVIEW_CHARS) {
+        Some((i, _)) => format!("{}…", &msg.content[..i]),
+        None => msg.content.clone(),
+    };
+
+    let mut out = Vec::new();
+    for (recipient, reason) in recipients {
+        let title = match reason {
+            "dm" => format!("{} (direct message)", msg.sender),
+            _ => format!("{} mentioned you in #{room_name}", msg.sender),
+        };
+        let payload = serde_json::json!({
+            "title": title,
+            "body": preview,
+            "reason": reason,
+            "room_id": msg.room_id,
+            "message_id": msg.id,
+            "seq": msg.seq,
+        })
+        .to_string();
+        let subs: Vec<(String, String, String, String)> = conn
+            .prepare("SELECT id, endpoint, p256dh, auth FROM push_subscriptions WHERE sender = ?1 COLLATE NOCASE")
+            .and_then(|mut s| {
+                s.query_map(params![&recipient], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)))
+                    .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            })
+            .unwrap_or_default();
+        for (id, endpoint, p256dh, auth) in subs {
+            let (Ok(p256dh), Ok(auth)) = (URL_SAFE_NO_PAD.decode(&p256dh), URL_SAFE_NO_PAD.decode(&auth)) else {
+                continue;
+            };
+            match encrypt(&p256dh, &auth, payload.as_bytes()) {
+                Ok(body) => out.push((id, endpoint, body)),
+                Err(e) => eprintln!("⚠️ Push encryption failed for subscription {id}: {e}"),
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,351 @@
+//! Per-room quiet hours. While a room's daily window is in effect, posts from agents are held
+//! in `deferred_messages` instead of being sent; once it ends they are delivered in the order
+//! they were made, so overnight automation lands after the morning conversation, not inside it.
+//! Humans post as usual. The window is read in the room's timezone, so it follows DST.
+//! Scheduled posts (see [`crate::scheduler`]) share the table; rows with `scheduled_at` are
+//! theirs and left alone here.
+
+use crate::events::{ChatEvent, Published};
+use crate::models::{DeferredMessage, Message, RoomQuietHours};
+use chrono::{DateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use rusqlite::{params, Connection};
+use tokio::sync::broadcast;
+
+/// Interval between checks for rooms whose quiet hours have ended (seconds).
+const RELEASE_INTERVAL_SECS: u64 = 30;
+
+/// Parse an `HH:MM` time of day.
+pub fn parse_time(s: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(s.trim(), "%H:%M").ok()
+}
+
+/// When the window that `now` falls in ends, or None if `now` is outside it. A window whose
+/// end is before its start runs past midnight.
+pub fn window_end(start: NaiveTime, end: NaiveTime, tz: Tz, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let local = now.with_timezone(&tz);
+    let (time, today) = (local.time(), local.date_naive());
+    let end_date = if start < end {
+        if time < start || time >= end {
+            return None;
+        }
+        today
+    } else if time >= start {
+        today.succ_opt()?
+    } else if time < end {
+        today
+    } else {
+        return None;
+    };
+    let end_local = end_date.and_time(end);
+    // An end time skipped by a DST jump falls back to the first valid instant after it
+    tz.from_local_datetime(&end_local)
+        .earliest()
+        .or_else(|| tz.from_local_datetime(&(end_local + chrono::Duration::hours(1))).earliest())
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// A room's window as stored: (start, end, timezone, updated_at).
+fn load(conn: &Connection, room_id: &str) -> Option<(String, String, String, String)> {
+    conn.query_row(
+        "SELECT start_time, end_time, timezone, updated_at FROM room_quiet_hours WHERE room_id = ?1",
+        params![room_id],
+        |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)),
+    )
+    .ok()
+}
+
+/// When the room's quiet hours end, if they are in effect at `now`.
+pub fn active_until(conn: &Connection, room_id: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let (start, end, timezone, _) = load(conn, room_id)?;
+    window_end(parse_time(&start)?, parse_time(&end)?, crate::timezones::parse(&timezone)?, now)
+}
+
+/// The room's quiet hours with their current state, or None if it has none.
+pub fn room_quiet_hours(conn: &Connection, room_id: &str) -> Option<RoomQuietHours> {
+    let (start, end, timezone, updated_at) = load(conn, room_id)?;
+    let ends_at = active_until(conn, room_id, Utc::now()).map(|t| t.to_rfc3339());
+    let queued: i64 = conn
+        .query_row("SELECT COUNT(*) FROM deferred_messages WHERE room_id = ?1 AND scheduled_at IS NULL", params![room_id], |r| r.get(0))
+        .unwrap_or(0);
+    Some(RoomQuietHours {
+        room_id: room_id.to_string(),
+        start,
+        end,
+        timezone,
+        active: ends_at.is_some(),
+        ends_at,
+        queued,
+        updated_at,
+    })
+}
+
+/// Whether quiet hours apply to a post: it is from an agent, by the message's `sender_type`
+/// or, failing that, the sender's profile.
+pub fn is_agent(conn: &Connection, sender: &str, sender_type: Option<&str>) -> bool {
+    match sender_type {
+        Some(t) => t == "agent",
+        None => conn
+            .query_row("SELECT sender_type FROM profiles WHERE sender = ?1", params![sender], |r| {
+                r.get::<_, Option<String>>(0)
+            })
+            .ok()
+            .flatten()
+            .is_some_and(|t| t == "agent"),
+    }
+}
+
+/// Hold a post for delivery. `metadata` is the client's own; the redaction and response
+/// timers are stored as durations and applied at delivery.
+pub fn defer(
+    conn: &Connection,
+    held: &DeferredMessage,
+    metadata: &serde_json::Value,
+    redact_after_secs: Option<i64>,
+    responders: &[String],
+    response_timeout_secs: Option<i64>,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO deferred_messages (id, room_id, sender, sender_type, content, metadata, reply_to, redact_after_secs,
+             requires_response_from, response_timeout_secs, queued_at, deliver_at, scheduled_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+        params![
+            &held.id,
+            &held.room_id,
+            &held.sender,
+            &held.sender_type,
+            &held.content,
+            serde_json::to_string(metadata).unwrap_or_default(),
+            &held.reply_to,
+            redact_after_secs,
+            serde_json::to_string(responders).unwrap_or_default(),
+            response_timeout_secs,
+            &held.queued_at,
+            &held.deliver_at,
+            &held.scheduled_at
+        ],
+    )?;
+    Ok(())
+}
+
+/// Posts waiting in a room for its quiet hours to end, in delivery order.
+pub fn queued(conn: &Connection, room_id: &str) -> Vec<DeferredMessage> {
+    held_where(
+        conn,
+        "room_id = ?1 AND scheduled_at IS NULL ORDER BY queued_at, rowid",
+        params![room_id],
+    )
+}
+
+/// Held posts matching `filter` (a WHERE clause, optionally with ORDER BY).
+pub(crate) fn held_where(conn: &Connection, filter: &str, args: impl rusqlite::Params) -> Vec<DeferredMessage> {
+    let Ok(mut stmt) = conn.prepare(&format!(
+        "SELECT id, room_id, sender, sender_type, content, reply_to, queued_at, deliver_at, scheduled_at
+         FROM deferred_messages WHERE {filter}"
+    )) else {
+        return Vec::new();
+    };
+    stmt.query_map(args, |r| {
+        Ok(DeferredMessage {
+            id: r.get(0)?,
+            room_id: r.get(1)?,
+            sender: r.get(2)?,
+            sender_type: r.get(3)?,
+            content: r.get(4)?,
+            reply_to: r.get(5)?,
+            queued_at: r.get(6)?,
+            deliver_at: r.get(7)?,
+            scheduled_at: r.get(8)?,
+            deferred: true,
+        })
+    })
+    .map(|rows| rows.filter_map(|r| r.ok()).collect())
+    .unwrap_or_default()
+}
+
+/// A held post as stored, read back for delivery.
+struct Held {
+    room_id: String,
+    sender: String,
+    sender_type: Option<String>,
+    content: String,
+    metadata: String,
+    reply_to: Option<String>,
+    redact_after_secs: Option<i64>,
+    responders: String,
+    response_timeout_secs: Option<i64>,
+    queued_at: String,
+    scheduled_at: Option<String>,
+}
+
+/// Send one held post as a new message, now. Returns it with its outbox event id.
+pub(crate) fn deliver(conn: &Connection, id: &str) -> rusqlite::Result<(Message, i64)> {
+    let Held {
+        room_id,
+        sender,
+        sender_type,
+        content,
+        metadata,
+        reply_to,
+        redact_after_secs,
+        responders,
+        response_timeout_secs,
+        queued_at,
+        scheduled_at,
+    } = conn.query_row(
+        "SELECT room_id, sender, sender_type, content, metadata, reply_to, redact_after_secs, requires_response_from,
+             response_timeout_secs, queued_at, scheduled_at
+         FROM deferred_messages WHERE id = ?1",
+        params![id],
+        |r| {
+            Ok(Held {
+                room_id: r.get(0)?,
+                sender: r.get(1)?,
+                sender_type: r.get(2)?,
+                content: r.get(3)?,
+                metadata: r.get(4)?,
+                reply_to: r.get(5)?,
+                redact_after_secs: r.get(6)?,
+                responders: r.get(7)?,
+                response_timeout_secs: r.get(8)?,
+                queued_at: r.get(9)?,
+                scheduled_at: r.get(10)?,
+            })
+        },
+    )?;
+
+    let created = Utc::now();
+    let mut metadata: serde_json::Value = serde_json::from_str(&metadata).unwrap_or_else(|_| serde_json::json!({}));
+    if !metadata.is_object() {
+        metadata = serde_json::json!({});
+    }
+    match scheduled_at {
+        Some(at) => metadata["scheduled"] = serde_json::json!({"scheduled_at": at, "queued_at": &queued_at}),
+        None => metadata["deferred"] = serde_json::json!({"queued_at": &queued_at}),
+    }
+    let redact_at = redact_after_secs.map(|secs| (created + chrono::Duration::seconds(secs)).to_rfc3339());
+    if let Some(ref at) = redact_at {
+        metadata["sensitive"] = serde_json::json!({"redact_at": at});
+    }
+    let responders: Vec<String> = serde_json::from_str(&responders).unwrap_or_default();
+    let respond_by = (!responders.is_empty()).then(|| {
+        let secs = response_timeout_secs.unwrap_or(crate::responses::ResponseConfig::default().default_timeout_secs);
+        (created + chrono::Duration::seconds(secs)).to_rfc3339()
+    });
+    if let Some(ref due) = respond_by {
+        metadata["requires_response"] = serde_json::json!({"from": &responders, "due_at": due});
+    }
+    // The message it replied to may have been deleted while this one waited
+    let reply_to = reply_to.filter(|parent| {
+        conn.query_row("SELECT COUNT(*) FROM messages WHERE id = ?1", params![parent], |r| r.get::<_, i64>(0))
+            .is_ok_and(|c| c > 0)
+    });
+
+    let tx = conn.unchecked_transaction()?;
+    let seq: i64 = tx.query_row("SELECT COALESCE(MAX(seq), 0) + 1 FROM messages", [], |r| r.get(0))?;
+    let msg = Message {
+        id: id.to_string(),
+        room_id,
+        sender,
+        content,
+        metadata,
+        created_at: created.to_rfc3339(),
+        edited_at: None,
+        reply_to,
+        sender_type,
+        seq,
+        pinned_at: None,
+        pinned_by: None,
+        edit_count: 0,
+        kind: "message".to_string(),
+        local_time: None,
+    };
+    tx.execute(
+        "INSERT INTO messages (id, room_id, sender, content, metadata, created_at, reply_to, sender_type, seq) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            &msg.id,
+            &msg.room_id,
+            &msg.sender,
+            &msg.content,
+            serde_json::to_string(&msg.metadata).unwrap_or_default(),
+            &msg.created_at,
+            &msg.reply_to,
+            &msg.sender_type,
+            seq
+        ],
+    )?;
+    if let Some(ref at) = redact_at {
+        crate::redaction::schedule(&tx, &msg.id, at)?;
+    }
+    if let Some(ref due) = respond_by {
+        crate::responses::request(&tx, &msg.id, &responders, &msg.created_at, due)?;
+    }
+    tx.execute("DELETE FROM deferred_messages WHERE id = ?1", params![id])?;
+    let event_id = crate::outbox::record(&tx, &ChatEvent::NewMessage(msg.clone()), None)?;
+    tx.commit()?;
+
+    crate::responses::clear_for_reply(conn, &msg);
+    crate::quotas::record(conn, &msg.sender, 1, 0);
+    conn.execute("UPDATE rooms SET updated_at = ?1 WHERE id = ?2", params![&msg.created_at, &msg.room_id])
+        .ok();
+    crate::db::upsert_fts(conn, &msg.id);
+    crate::db::index_mentions(conn, &msg.id);
+    Ok((msg, event_id))
+}
+
+/// Deliver the held posts of every room whose quiet hours are over or were removed, oldest
+/// first. Returns each delivered message with its outbox event id, for the caller to publish.
+pub fn release_due(conn: &Connection) -> Vec<(Message, i64)> {
+    let now = Utc::now();
+    let held: Vec<(String, String)> = conn
+        .prepare("SELECT id, room_id FROM deferred_messages WHERE scheduled_at IS NULL ORDER BY queued_at, rowid")
+        .and_then(|mut s| {
+            s.query_map([], |r| Ok((r.get(0)?, r.get(1)?)))
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .unwrap_or_default();
+    let mut delivered = Vec::new();
+    let mut still_quiet: Vec<String> = Vec::new();
+    for (id, room_id) in held {
+        if still_quiet.contains(&room_id) {
+            continue;
+        }
+        if active_until(conn, &room_id, now).is_some() {
+            still_quiet.push(room_id);
+            continue;
+        }
+        match deliver(conn, &id) {
+            Ok(sent) => delivered.push(sent),
+            Err(e) => eprintln!("⚠️ Quiet hours: failed to deliver held message {id}: {e}"),
+        }
+    }
+    delivered
+}
+
+/// Releases held messages from one database once quiet hours end; `namespace` tags the events.
+pub fn spawn_releaser(db_path: String, events: broadcast::Sender<Published>, namespace: Option<String>) {
+    tokio::spawn(async move {
+        let conn = match crate::db::open(&db_path) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("⚠️ Quiet hours releaser: failed to open DB: {e}");
+                return;
+            }
+        };
+        crate::db::DbConfig::from_env().apply(&conn).ok();
+
+        loop {
+            tokio::time::sleep(std::time::Duration::from_secs(RELEASE_INTERVAL_SECS)).await;
+            for (msg, event_id) in release_due(&conn) {
+                let _ = events.send(Published {
+                    event: ChatEvent::NewMessage(msg),
+                    request_id: None,
+                    trace_context: None,
+                    event_id: Some(event_id),
+                    namespace: namespace.clone(),
+                });
+                crate::outbox::mark_published(&conn, event_id);
+            }
+        }
+    });
+}
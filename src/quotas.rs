@@ -0,0 +1,171 @@
+//! Per-sender daily budgets for messages and uploaded bytes, so one runaway agent can't eat
+//! the whole instance. Limits come from [`RateLimitConfig`] unless a sender has its own row in
+//! `sender_quotas`; usage is counted per canonical sender and rolls over at UTC midnight.
+
+use crate::models::{QuotaBudget, SenderQuota};
+use crate::rate_limit::RateLimitConfig;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rusqlite::{params, Connection};
+
+/// The current UTC date (the daily-quota bucket) and the instant it ends.
+pub fn quota_day(now: chrono::DateTime<chrono::Utc>) -> (String, chrono::DateTime<chrono::Utc>) {
+    let today = now.date_naive();
+    let resets_at = (today + chrono::Days::new(1)).and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+    (today.to_string(), resets_at)
+}
+
+/// A sender's overrides and today's counters (zero when the row is from an earlier day).
+struct Usage {
+    daily_messages: Option<i64>,
+    daily_upload_bytes: Option<i64>,
+    messages_today: i64,
+    upload_bytes_today: i64,
+}
+
+fn load_usage(conn: &Connection, sender: &str, today: &str) -> Usage {
+    conn.query_row(
+        "SELECT daily_messages, daily_upload_bytes, usage_day, messages_today, upload_bytes_today
+         FROM sender_quotas WHERE sender = ?1",
+        params![sender],
+        |r| {
+            let current = r.get::<_, Option<String>>(2)?.as_deref() == Some(today);
+            Ok(Usage {
+                daily_messages: r.get(0)?,
+                daily_upload_bytes: r.get(1)?,
+                messages_today: if current { r.get(3)? } else { 0 },
+                upload_bytes_today: if current { r.get(4)? } else { 0 },
+            })
+        },
+    )
+    .unwrap_or(Usage {
+        daily_messages: None,
+        daily_upload_bytes: None,
+        messages_today: 0,
+        upload_bytes_today: 0,
+    })
+}
+
+/// Effective limit: the sender's override, else the server default. 0 means unlimited.
+fn effective(limit: Option<i64>, default: i64) -> Option<i64> {
+    Some(limit.unwrap_or(default)).filter(|&n| n > 0)
+}
+
+fn budget(limit: Option<i64>, default: i64, used: i64) -> QuotaBudget {
+    let effective = effective(limit, default);
+    QuotaBudget {
+        limit: effective,
+        used,
+        remaining: effective.map(|l| (l - used).max(0)),
+        overridden: limit.is_some(),
+    }
+}
+
+/// Today's budgets for `sender` (resolved to its canonical name).
+pub fn status(conn: &Connection, config: &RateLimitConfig, sender: &str) -> SenderQuota {
+    let sender = crate::db::resolve_sender(conn, sender);
+    let (today, resets_at) = quota_day(chrono::Utc::now());
+    let usage = load_usage(conn, &sender, &today);
+    SenderQuota {
+        messages: budget(usage.daily_messages, config.sender_daily_messages as i64, usage.messages_today),
+        upload_bytes: budget(
+            usage.daily_upload_bytes,
+            config.sender_daily_upload_bytes as i64,
+            usage.upload_bytes_today,
+        ),
+        sender,
+        resets_at: resets_at.to_rfc3339(),
+    }
+}
+
+/// Refuse with 429 if `messages` more messages or `upload_bytes` more bytes would take
+/// `sender` past today's budget. Nothing is counted; call [`record`] once the write succeeds.
+pub fn check(
+    conn: &Connection,
+    config: &RateLimitConfig,
+    sender: &str,
+    messages: i64,
+    upload_bytes: i64,
+) -> Result<(), (Status, Json<serde_json::Value>)> {
+    if config.sender_daily_messages == 0
+        && config.sender_daily_upload_bytes == 0
+        && !has_override(conn, sender)
+    {
+        return Ok(());
+    }
+    let sender = crate::db::resolve_sender(conn, sender);
+    let now = chrono::Utc::now();
+    let (today, resets_at) = quota_day(now);
+    let usage = load_usage(conn, &sender, &today);
+
+    let exceeded = |what: &str, kind: &str, limit: i64, used: i64| {
+        (
+            Status::TooManyRequests,
+            Json(serde_json::json!({
+                "error": format!("Daily quota exhausted: max {limit} {what} per day for sender '{sender}'"),
+                "quota": kind,
+                "limit": limit,
+                "used": used,
+                "retry_after_secs": (resets_at - now).num_seconds().max(1),
+                "resets_at": resets_at.to_rfc3339()
+            })),
+        )
+    };
+    if messages > 0
+        && let Some(limit) = effective(usage.daily_messages, config.sender_daily_messages as i64)
+        && usage.messages_today + messages > limit
+    {
+        return Err(exceeded("messages", "messages", limit, usage.messages_today));
+    }
+    if upload_bytes > 0
+        && let Some(limit) = effective(usage.daily_upload_bytes, config.sender_daily_upload_bytes as i64)
+        && usage.upload_bytes_today + upload_bytes > limit
+    {
+        return Err(exceeded("uploaded bytes", "upload_bytes", limit, usage.upload_bytes_today));
+    }
+    Ok(())
+}
+
+fn has_override(conn: &Connection, sender: &str) -> bool {
+    let sender = crate::db::resolve_sender(conn, sender);
+    conn.prepare_cached(
+        "SELECT COUNT(*) FROM sender_quotas
+         WHERE sender = ?1 AND (daily_messages IS NOT NULL OR daily_upload_bytes IS NOT NULL)",
+    )
+    .and_then(|mut s| s.query_row(params![sender], |r| r.get::<_, i64>(0)))
+    .map(|c| c > 0)
+    .unwrap_or(false)
+}
+
+/// Count `messages` and `upload_bytes` against `sender`'s budget for today.
+pub fn record(conn: &Connection, sender: &str, messages: i64, upload_bytes: i64) {
+    let sender = crate::db::resolve_sender(conn, sender);
+    let now = chrono::Utc::now();
+    let (today, _) = quota_day(now);
+    conn.execute(
+        "INSERT INTO sender_quotas (sender, usage_day, messages_today, upload_bytes_today, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(sender) DO UPDATE SET
+             messages_today = CASE WHEN usage_day = ?2 THEN messages_today + ?3 ELSE ?3 END,
+             upload_bytes_today = CASE WHEN usage_day = ?2 THEN upload_bytes_today + ?4 ELSE ?4 END,
+             usage_day = ?2, updated_at = ?5",
+        params![&sender, &today, messages, upload_bytes, now.to_rfc3339()],
+    )
+    .ok();
+}
+
+/// Set (or with `None`, clear) `sender`'s own daily limits.
+pub fn set_limits(
+    conn: &Connection,
+    sender: &str,
+    daily_messages: Option<i64>,
+    daily_upload_bytes: Option<i64>,
+) -> rusqlite::Result<()> {
+    let sender = crate::db::resolve_sender(conn, sender);
+    conn.execute(
+        "INSERT INTO sender_quotas (sender, daily_messages, daily_upload_bytes, updated_at) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(sender) DO UPDATE SET daily_messages = ?2, daily_upload_bytes = ?3, updated_at = ?4",
+        params![&sender, daily_messages, daily_upload_bytes, chrono::Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
@@ -16,6 +16,13 @@ use rocket::Request;
 /// - `RATE_LIMIT_FILES` — Max file uploads per minute per IP (default: 10)
 /// - `RATE_LIMIT_DMS` — Max DMs per minute per IP (default: 60)
 /// - `RATE_LIMIT_WEBHOOKS` — Max incoming webhook messages per minute per token (default: 60)
+/// - `RATE_LIMIT_WEBHOOKS_DAILY` — Max incoming webhook messages per UTC day per token (default: 0 = unlimited)
+/// - `RATE_LIMIT_REACTIONS` — Max reaction toggles per minute per sender (default: 30)
+/// - `MAX_REACTION_EMOJI_PER_MESSAGE` — Max distinct emoji on a single message (default: 20)
+/// - `RATE_LIMIT_SENDER_MESSAGES_DAILY` — Max messages per UTC day per sender (default: 0 = unlimited)
+/// - `RATE_LIMIT_SENDER_UPLOAD_BYTES_DAILY` — Max uploaded bytes per UTC day per sender (default: 0 = unlimited)
+/// - `ADMIN_KEY_MAX_FAILURES` — Wrong admin keys per IP and room before a lockout (default: 5, 0 = off)
+/// - `ADMIN_KEY_LOCKOUT_SECS` — First lockout; each further failure doubles it, up to a day (default: 60)
 pub struct RateLimitConfig {
     /// Messages per minute per IP
     pub messages_max: usize,
@@ -32,6 +39,21 @@ pub struct RateLimitConfig {
     /// Incoming webhook messages per minute per token
     pub webhooks_max: usize,
     pub webhooks_window_secs: u64,
+    /// Incoming webhook messages per UTC day per token (0 = unlimited); hooks can override
+    pub webhooks_daily_quota: usize,
+    /// Reaction toggles per minute per sender
+    pub reactions_max: usize,
+    pub reactions_window_secs: u64,
+    /// Distinct emoji allowed on a single message (not time-windowed)
+    pub reaction_emoji_max: usize,
+    /// Messages per UTC day per sender (0 = unlimited); senders can be given their own limit
+    pub sender_daily_messages: usize,
+    /// Uploaded file bytes per UTC day per sender (0 = unlimited)
+    pub sender_daily_upload_bytes: u64,
+    /// Wrong admin keys per IP and room before a lockout (0 = never lock out)
+    pub admin_key_max_failures: usize,
+    /// Length of the first lockout
+    pub admin_key_lockout_secs: u64,
 }
 
 impl Default for RateLimitConfig {
@@ -47,6 +69,14 @@ impl Default for RateLimitConfig {
             dms_window_secs: 60,
             webhooks_max: 60,
             webhooks_window_secs: 60,
+            webhooks_daily_quota: 0,
+            reactions_max: 30,
+            reactions_window_secs: 60,
+            reaction_emoji_max: 20,
+            sender_daily_messages: 0,
+            sender_daily_upload_bytes: 0,
+            admin_key_max_failures: 5,
+            admin_key_lockout_secs: 60,
         }
     }
 }
@@ -81,6 +111,42 @@ impl RateLimitConfig {
         {
             config.webhooks_max = n;
         }
+        if let Ok(val) = env::var("RATE_LIMIT_WEBHOOKS_DAILY")
+            && let Ok(n) = val.parse::<usize>()
+        {
+            config.webhooks_daily_quota = n;
+        }
+        if let Ok(val) = env::var("RATE_LIMIT_REACTIONS")
+            && let Ok(n) = val.parse::<usize>()
+        {
+            config.reactions_max = n;
+        }
+        if let Ok(val) = env::var("MAX_REACTION_EMOJI_PER_MESSAGE")
+            && let Ok(n) = val.parse::<usize>()
+        {
+            config.reaction_emoji_max = n;
+        }
+        if let Ok(val) = env::var("RATE_LIMIT_SENDER_MESSAGES_DAILY")
+            && let Ok(n) = val.parse::<usize>()
+        {
+            config.sender_daily_messages = n;
+        }
+        if let Ok(val) = env::var("RATE_LIMIT_SENDER_UPLOAD_BYTES_DAILY")
+            && let Ok(n) = val.parse::<u64>()
+        {
+            config.sender_daily_upload_bytes = n;
+        }
+        if let Ok(val) = env::var("ADMIN_KEY_MAX_FAILURES")
+            && let Ok(n) = val.parse::<usize>()
+        {
+            config.admin_key_max_failures = n;
+        }
+        if let Ok(val) = env::var("ADMIN_KEY_LOCKOUT_SECS")
+            && let Ok(n) = val.parse::<u64>()
+            && n > 0
+        {
+            config.admin_key_lockout_secs = n;
+        }
 
         config
     }
@@ -88,6 +154,19 @@ impl RateLimitConfig {
 
 pub struct RateLimiter {
     limits: Mutex<HashMap<String, Vec<Instant>>>,
+    admin_key_failures: Mutex<HashMap<String, AdminKeyFailures>>,
+}
+
+/// Longest admin key lockout, however many times it has doubled.
+const MAX_ADMIN_KEY_LOCKOUT_SECS: u64 = 86_400;
+
+/// Wrong admin keys from one IP against one room.
+#[derive(Debug, Default)]
+struct AdminKeyFailures {
+    failures: usize,
+    /// Lockouts so far; each one is twice as long as the last
+    lockouts: u32,
+    locked_until: Option<Instant>,
 }
 
 /// Wrapper that adds standard rate limit headers to any JSON response.
@@ -187,7 +266,48 @@ impl RateLimiter {
     pub fn new() -> Self {
         RateLimiter {
             limits: Mutex::new(HashMap::new()),
+            admin_key_failures: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Seconds left on the lockout for `key` ("ip|room_id"), if it is locked out.
+    pub fn admin_key_locked(&self, key: &str) -> Option<u64> {
+        let failures = self.admin_key_failures.lock().unwrap_or_else(|e| e.into_inner());
+        let until = failures.get(key)?.locked_until?;
+        let left = until.checked_duration_since(Instant::now())?;
+        Some(left.as_secs() + 1)
+    }
+
+    /// Count a wrong admin key for `key`. After `max` failures it is locked out for
+    /// `lockout_secs`; once it has been locked out, every further failure locks it again for
+    /// twice as long as the last time. Returns the lockout just started, in seconds.
+    pub fn admin_key_failed(&self, key: &str, max: usize, lockout_secs: u64) -> Option<u64> {
+        if max == 0 {
+            return None;
+        }
+        let mut failures = self.admin_key_failures.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = failures.entry(key.to_string()).or_default();
+        entry.failures += 1;
+        if entry.failures < max && entry.lockouts == 0 {
+            return None;
         }
+        let secs = lockout_secs
+            .saturating_mul(1u64 << entry.lockouts.min(20))
+            .min(MAX_ADMIN_KEY_LOCKOUT_SECS);
+        entry.lockouts += 1;
+        entry.failures = 0;
+        entry.locked_until = Some(Instant::now() + std::time::Duration::from_secs(secs));
+        Some(secs)
+    }
+
+    /// Whether `key` has wrong admin keys or lockouts on record.
+    pub fn admin_key_has_failures(&self, key: &str) -> bool {
+        self.admin_key_failures.lock().unwrap_or_else(|e| e.into_inner()).contains_key(key)
+    }
+
+    /// Forget the failures for `key` after the right admin key was used.
+    pub fn admin_key_succeeded(&self, key: &str) {
+        self.admin_key_failures.lock().unwrap_or_else(|e| e.into_inner()).remove(key);
     }
 
     /// Check if a request is allowed. Returns true if allowed, false if rate limited.
@@ -0,0 +1,92 @@
+//! Messages sent with `sensitive: true` (temporary credentials shared during an incident, say)
+//! have their content replaced by a tombstone once their redaction time passes. The row, seq and
+//! reply links stay so threads keep their shape; edit history and the search index lose the text.
+
+use rusqlite::{params, Connection};
+use std::env;
+
+/// What a redacted message's content becomes.
+pub const REDACTED_CONTENT: &str = "[sensitive content redacted]";
+
+/// Allowed per-message `redact_after_secs`: one minute to 30 days.
+pub const REDACT_AFTER_RANGE: std::ops::RangeInclusive<i64> = 60..=2_592_000;
+
+/// Server-wide redaction settings.
+///
+/// Environment variables:
+/// - `SENSITIVE_REDACT_SECS` — Seconds before a sensitive message without `redact_after_secs`
+///   is redacted (default: 3600)
+pub struct RedactionConfig {
+    pub default_redact_secs: i64,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self { default_redact_secs: 3600 }
+    }
+}
+
+impl RedactionConfig {
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Ok(val) = env::var("SENSITIVE_REDACT_SECS")
+            && let Ok(n) = val.parse::<i64>()
+            && REDACT_AFTER_RANGE.contains(&n)
+        {
+            config.default_redact_secs = n;
+        }
+        config
+    }
+}
+
+/// Record that `message_id` is to be redacted at `redact_at`.
+pub fn schedule(conn: &Connection, message_id: &str, redact_at: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO sensitive_messages (message_id, redact_at) VALUES (?1, ?2)",
+        params![message_id, redact_at],
+    )?;
+    Ok(())
+}
+
+/// Redact every sensitive message whose time has come. Returns (message_id, room_id, redacted_at)
+/// for each one so callers can publish `message_redacted`.
+pub fn redact_due(conn: &Connection) -> Vec<(String, String, String)> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let due: Vec<(String, String)> = conn
+        .prepare(
+            "SELECT s.message_id, m.room_id FROM sensitive_messages s
+             JOIN messages m ON m.id = s.message_id
+             WHERE s.redacted_at IS NULL AND s.redact_at <= ?1",
+        )
+        .and_then(|mut s| {
+            s.query_map(params![&now], |r| Ok((r.get(0)?, r.get(1)?)))
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .unwrap_or_default();
+
+    due.into_iter()
+        .filter(|(id, _)| redact(conn, id, &now).is_ok())
+        .map(|(id, room_id)| (id, room_id, now.clone()))
+        .collect()
+}
+
+fn redact(conn: &Connection, message_id: &str, now: &str) -> rusqlite::Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "UPDATE messages SET content = ?1,
+             metadata = json_set(CASE WHEN json_valid(metadata) AND json_type(metadata) = 'object'
+                                      THEN metadata ELSE '{}' END,
+                                 '$.sensitive.redacted_at', ?2)
+         WHERE id = ?3",
+        params![REDACTED_CONTENT, now, message_id],
+    )?;
+    // Earlier versions would otherwise still show the secret
+    tx.execute("DELETE FROM message_edits WHERE message_id = ?1", params![message_id])?;
+    tx.execute(
+        "UPDATE sensitive_messages SET redacted_at = ?1 WHERE message_id = ?2",
+        params![now, message_id],
+    )?;
+    crate::db::upsert_fts(&tx, message_id);
+    crate::db::index_mentions(&tx, message_id);
+    tx.commit()
+}
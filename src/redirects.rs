@@ -0,0 +1,128 @@
+use std::io::Cursor;
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::uri::Origin;
+use rocket::http::{ContentType, Header, RawStr, Status};
+use rocket::{Data, Request, Response};
+
+use crate::namespaces::{PathPrefix, ScopedDb};
+
+/// Fairing that turns a 404 on a room-scoped route into a 308 when the room id was merged
+/// into another room, so permalinks held by agents keep working after reorganizations.
+/// The redirect keeps the rest of the path, the query string and any `/ns/<namespace>` prefix;
+/// 308 preserves method and body.
+pub struct RoomRedirectFairing;
+
+#[rocket::async_trait]
+impl Fairing for RoomRedirectFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Room Redirects",
+            kind: Kind::Response,
+        }
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        if res.status() != Status::NotFound {
+            return;
+        }
+        let path = req.uri().path();
+        let Some(rest) = path.as_str().strip_prefix("/api/v1/rooms/") else {
+            return;
+        };
+        let (room_id, tail) = match rest.split_once('/') {
+            Some((id, tail)) => (id, Some(tail)),
+            None => (rest, None),
+        };
+        if room_id.is_empty() {
+            return;
+        }
+        let Some(db) = ScopedDb::of(req) else {
+            return;
+        };
+        let Some(target) = crate::db::resolve_room_redirect(&db.conn(), room_id) else {
+            return;
+        };
+
+        let mut location = format!("{}/api/v1/rooms/{target}", PathPrefix::of(req));
+        if let Some(tail) = tail {
+            location.push('/');
+            location.push_str(tail);
+        }
+        if let Some(query) = req.uri().query() {
+            location.push('?');
+            location.push_str(query.as_str());
+        }
+        let body = serde_json::json!({
+            "error": "Room has moved",
+            "moved_to": target,
+            "location": location,
+        })
+        .to_string();
+        res.set_status(Status::PermanentRedirect);
+        res.set_header(Header::new("Location", location));
+        res.set_header(ContentType::JSON);
+        res.set_sized_body(body.len(), Cursor::new(body));
+    }
+}
+
+/// Path prefix for addressing a room by name instead of id.
+const BY_NAME_PREFIX: &str = "/api/v1/rooms/by-name/";
+
+/// Fairing that lets every room-scoped route be addressed by name:
+/// `/api/v1/rooms/by-name/<name>/...` is rewritten to `/api/v1/rooms/<id>/...` before routing.
+/// Former names (renames, merges) resolve to the room that now holds them. Unknown names are
+/// left alone and fall through to a 404.
+pub struct RoomByNameFairing;
+
+#[rocket::async_trait]
+impl Fairing for RoomByNameFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Room By-Name Addressing",
+            kind: Kind::Request,
+        }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _data: &mut Data<'_>) {
+        let path = req.uri().path().as_str().to_string();
+        let Some(rest) = path.strip_prefix(BY_NAME_PREFIX) else {
+            return;
+        };
+        let (raw_name, tail) = match rest.split_once('/') {
+            Some((name, tail)) => (name, Some(tail)),
+            None => (rest, None),
+        };
+        let name = RawStr::new(raw_name).percent_decode_lossy();
+        let name = name.trim();
+        if name.is_empty() {
+            return;
+        }
+        let room_id = {
+            let Some(db) = ScopedDb::of(req) else {
+                return;
+            };
+            let conn = db.conn();
+            conn.prepare_cached("SELECT id FROM rooms WHERE name = ?1")
+                .and_then(|mut s| s.query_row([name], |r| r.get::<_, String>(0)))
+                .ok()
+                .or_else(|| crate::db::resolve_room_alias(&conn, name))
+        };
+        let Some(room_id) = room_id else {
+            return;
+        };
+
+        let mut uri = format!("/api/v1/rooms/{room_id}");
+        if let Some(tail) = tail {
+            uri.push('/');
+            uri.push_str(tail);
+        }
+        if let Some(query) = req.uri().query() {
+            uri.push('?');
+            uri.push_str(query.as_str());
+        }
+        if let Ok(origin) = Origin::parse_owned(uri) {
+            req.set_uri(origin);
+        }
+    }
+}
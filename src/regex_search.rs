@@ -0,0 +1,186 @@
+//! `GET /api/v1/search?mode=regex`: a bounded scan of message content by regular expression,
+//! for what FTS can't express (log lines, ticket ids like `[A-Z]+-\d+`). The `regex` crate
+//! matches in linear time, so the cost is the scan itself: it runs newest-first and stops at
+//! the result limit, the scan cap or the time limit, whichever comes first.
+
+use std::env;
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+use rusqlite::Connection;
+
+use crate::models::{RegexMatch, SearchResult};
+
+/// Who may run regex searches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegexAccess {
+    Off,
+    /// Requires the server token
+    Admin,
+    Open,
+}
+
+/// Regex search settings.
+///
+/// Environment variables:
+/// - `REGEX_SEARCH` — `off`, `admin` (server token required) or `open` (default: `admin`)
+/// - `REGEX_SEARCH_TIMEOUT_MS` — Wall-clock budget for one search (default: 2000, max 30000)
+/// - `REGEX_SEARCH_MAX_SCAN` — Most messages one search examines (default: 200000)
+#[derive(Debug, Clone)]
+pub struct RegexSearchConfig {
+    pub access: RegexAccess,
+    pub timeout: Duration,
+    pub max_scan: usize,
+}
+
+impl Default for RegexSearchConfig {
+    fn default() -> Self {
+        Self {
+            access: RegexAccess::Admin,
+            timeout: Duration::from_millis(2000),
+            max_scan: 200_000,
+        }
+    }
+}
+
+impl RegexSearchConfig {
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Ok(val) = env::var("REGEX_SEARCH") {
+            match val.trim().to_lowercase().as_str() {
+                "off" | "false" | "0" => config.access = RegexAccess::Off,
+                "admin" => config.access = RegexAccess::Admin,
+                "open" | "true" | "1" => config.access = RegexAccess::Open,
+                _ => eprintln!("⚠️ Ignoring invalid REGEX_SEARCH={val} (use off, admin or open)"),
+            }
+        }
+        if let Ok(val) = env::var("REGEX_SEARCH_TIMEOUT_MS")
+            && let Ok(n) = val.parse::<u64>()
+            && (1..=30_000).contains(&n)
+        {
+            config.timeout = Duration::from_millis(n);
+        }
+        if let Ok(val) = env::var("REGEX_SEARCH_MAX_SCAN")
+            && let Ok(n) = val.parse::<usize>()
+            && n > 0
+        {
+            config.max_scan = n;
+        }
+        config
+    }
+}
+
+/// Most matches reported per message.
+const MAX_MATCHES_PER_MESSAGE: usize = 20;
+
+/// Compiled-program size cap, so a pathological pattern is rejected up front.
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+
+/// Rows fetched between deadline checks.
+const CHECK_EVERY: usize = 256;
+
+/// Compile a user pattern with the size cap.
+pub fn compile(pattern: &str) -> Result<Regex, String> {
+    regex::RegexBuilder::new(pattern)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .build()
+        .map_err(|e| format!("Invalid regex: {e}"))
+}
+
+/// Every match of `re` in `content`, with captures, up to the per-message cap.
+pub fn find_matches(re: &Regex, content: &str) -> Vec<RegexMatch> {
+    let names: Vec<Option<&str>> = re.capture_names().collect();
+    re.captures_iter(content)
+        .take(MAX_MATCHES_PER_MESSAGE)
+        .filter_map(|caps| {
+            let whole = caps.get(0)?;
+            let groups = (1..caps.len()).map(|i| caps.get(i).map(|m| m.as_str().to_string())).collect();
+            let named = names
+                .iter()
+                .enumerate()
+                .filter_map(|(i, name)| Some(((*name)?.to_string(), caps.get(i).map(|m| m.as_str().to_string()))))
+                .collect();
+            Some(RegexMatch {
+                text: whole.as_str().to_string(),
+                start: whole.start(),
+                end: whole.end(),
+                groups,
+                named,
+            })
+        })
+        .collect()
+}
+
+/// Outcome of a scan.
+pub struct RegexScan {
+    /// Newest first, at most `limit + 1` (the extra one signals more)
+    pub results: Vec<SearchResult>,
+    pub scanned: usize,
+    /// Seq of the oldest message examined; continue with `before_seq=` this
+    pub last_scanned_seq: Option<i64>,
+    /// Stopped by the time limit or scan cap before reaching the end
+    pub truncated: bool,
+}
+
+/// Scan messages matching `filter_sql` (a `WHERE` clause over `m`/`r` bound to `params`),
+/// newest first, keeping those whose content matches `re`.
+pub fn scan(
+    conn: &Connection,
+    re: &Regex,
+    filter_sql: &str,
+    params: &[String],
+    limit: usize,
+    config: &RegexSearchConfig,
+) -> rusqlite::Result<RegexScan> {
+    let sql = format!(
+        "SELECT m.id, m.room_id, r.name, m.sender, m.sender_type, m.content, \
+         m.created_at, m.edited_at, m.reply_to, m.seq \
+         FROM messages m JOIN rooms r ON m.room_id = r.id \
+         WHERE {filter_sql} ORDER BY m.seq DESC"
+    );
+    let deadline = Instant::now() + config.timeout;
+    let mut stmt = conn.prepare(&sql)?;
+    let params_refs: Vec<&dyn rusqlite::types::ToSql> =
+        params.iter().map(|v| v as &dyn rusqlite::types::ToSql).collect();
+    let mut rows = stmt.query(params_refs.as_slice())?;
+
+    let mut scan = RegexScan {
+        results: Vec::new(),
+        scanned: 0,
+        last_scanned_seq: None,
+        truncated: false,
+    };
+    while let Some(row) = rows.next()? {
+        if scan.scanned >= config.max_scan
+            || (scan.scanned % CHECK_EVERY == 0 && scan.scanned > 0 && Instant::now() >= deadline)
+        {
+            scan.truncated = true;
+            break;
+        }
+        scan.scanned += 1;
+        let content: String = row.get(5)?;
+        let seq: i64 = row.get(9)?;
+        scan.last_scanned_seq = Some(seq);
+        let matches = find_matches(re, &content);
+        if matches.is_empty() {
+            continue;
+        }
+        scan.results.push(SearchResult {
+            message_id: row.get(0)?,
+            room_id: row.get(1)?,
+            room_name: row.get(2)?,
+            sender: row.get(3)?,
+            sender_type: row.get(4)?,
+            content,
+            created_at: row.get(6)?,
+            edited_at: row.get(7)?,
+            reply_to: row.get(8)?,
+            seq,
+            matches: Some(matches),
+        });
+        if scan.results.len() > limit {
+            break;
+        }
+    }
+    Ok(scan)
+}
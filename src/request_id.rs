@@ -0,0 +1,101 @@
+use std::io::Cursor;
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::{ContentType, Header};
+use rocket::request::{FromRequest, Outcome};
+use rocket::{Data, Request, Response};
+
+/// Header used to correlate a chat call with server logs, SSE events, and webhook deliveries.
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+/// Max accepted length of a client-supplied request id. Longer (or non-printable) ids are replaced.
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// The request's correlation id: the caller's `X-Request-Id` if it is sane, otherwise a fresh UUID.
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    /// The id for this request. Cached per request, so every caller sees the same value.
+    pub fn of(req: &Request<'_>) -> String {
+        req.local_cache(|| {
+            let supplied = req
+                .headers()
+                .get_one(REQUEST_ID_HEADER)
+                .map(|v| v.trim())
+                .filter(|v| {
+                    !v.is_empty()
+                        && v.len() <= MAX_REQUEST_ID_LEN
+                        && v.chars().all(|c| c.is_ascii_graphic())
+                });
+            RequestId(
+                supplied
+                    .map(String::from)
+                    .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            )
+        })
+        .0
+        .clone()
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for RequestId {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(RequestId(RequestId::of(req)))
+    }
+}
+
+/// Fairing that echoes `X-Request-Id` on every response, logs failed requests with it,
+/// and adds `request_id` to JSON error bodies.
+pub struct RequestIdFairing;
+
+#[rocket::async_trait]
+impl Fairing for RequestIdFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request ID",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _data: &mut Data<'_>) {
+        // Resolve early so the id is fixed before any handler or guard reads it
+        RequestId::of(req);
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        let request_id = RequestId::of(req);
+        res.set_header(Header::new(REQUEST_ID_HEADER, request_id.clone()));
+
+        if res.status().code < 400 {
+            return;
+        }
+        eprintln!(
+            "[{}] {} {} -> {}",
+            request_id,
+            req.method(),
+            req.uri(),
+            res.status()
+        );
+
+        if res.content_type() != Some(ContentType::JSON) {
+            return;
+        }
+        let body = match res.body_mut().to_string().await {
+            Ok(b) => b,
+            Err(_) => return,
+        };
+        let body = match serde_json::from_str::<serde_json::Value>(&body) {
+            Ok(serde_json::Value::Object(mut map)) => {
+                map.entry("request_id")
+                    .or_insert(serde_json::Value::String(request_id));
+                serde_json::Value::Object(map).to_string()
+            }
+            _ => body,
+        };
+        res.set_sized_body(body.len(), Cursor::new(body));
+    }
+}
@@ -0,0 +1,174 @@
+//! Messages sent with `requires_response_from` wait on a reply from each listed sender. A reply
+//! anywhere in the message's thread clears that sender; one still missing when the timeout
+//! passes is escalated once with a `response_overdue` event.
+
+use crate::events::{ChatEvent, Published};
+use crate::membership::MemberAccess;
+use crate::models::{Message, PendingResponse};
+use rusqlite::{params, Connection};
+use std::env;
+use tokio::sync::broadcast;
+
+/// Allowed per-message `response_timeout_secs`: one minute to a week.
+pub const RESPONSE_TIMEOUT_RANGE: std::ops::RangeInclusive<i64> = 60..=604_800;
+
+/// Most senders one message can wait on.
+pub const MAX_RESPONDERS: usize = 20;
+
+/// Interval between checks for overdue responses (seconds).
+const ESCALATE_INTERVAL_SECS: u64 = 30;
+
+/// Server-wide response tracking settings.
+///
+/// Environment variables:
+/// - `RESPONSE_TIMEOUT_SECS` — Seconds a message without `response_timeout_secs` waits before
+///   `response_overdue` fires (default: 3600)
+pub struct ResponseConfig {
+    pub default_timeout_secs: i64,
+}
+
+impl Default for ResponseConfig {
+    fn default() -> Self {
+        Self { default_timeout_secs: 3600 }
+    }
+}
+
+impl ResponseConfig {
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Ok(val) = env::var("RESPONSE_TIMEOUT_SECS")
+            && let Ok(n) = val.parse::<i64>()
+            && RESPONSE_TIMEOUT_RANGE.contains(&n)
+        {
+            config.default_timeout_secs = n;
+        }
+        config
+    }
+}
+
+/// Record that `message_id` waits on a reply from each of `responders` (canonical names) until `due_at`.
+pub fn request(conn: &Connection, message_id: &str, responders: &[String], requested_at: &str, due_at: &str) -> rusqlite::Result<()> {
+    for responder in responders {
+        conn.execute(
+            "INSERT OR IGNORE INTO pending_responses (message_id, responder, requested_at, due_at) VALUES (?1, ?2, ?3, ?4)",
+            params![message_id, responder, requested_at, due_at],
+        )?;
+    }
+    Ok(())
+}
+
+/// Clear what `reply` answers: pending responses from its sender on any message up its reply chain.
+pub fn clear_for_reply(conn: &Connection, reply: &Message) -> usize {
+    let Some(ref parent) = reply.reply_to else {
+        return 0;
+    };
+    let responder = crate::db::resolve_sender(conn, &reply.sender);
+    conn.execute(
+        "WITH RECURSIVE ancestors(id) AS (
+             SELECT ?1
+             UNION
+             SELECT m.reply_to FROM messages m JOIN ancestors a ON m.id = a.id WHERE m.reply_to IS NOT NULL
+         )
+         UPDATE pending_responses SET responded_at = ?2, response_id = ?3
+         WHERE responded_at IS NULL AND responder = ?4 AND message_id IN (SELECT id FROM ancestors)",
+        params![parent, &reply.created_at, &reply.id, &responder],
+    )
+    .unwrap_or(0)
+}
+
+const SELECT_PENDING: &str = "SELECT pr.message_id, m.room_id, r.name, m.seq, m.sender, pr.responder, m.content,
+        pr.requested_at, pr.due_at, pr.escalated_at
+     FROM pending_responses pr
+     JOIN messages m ON m.id = pr.message_id
+     JOIN rooms r ON r.id = m.room_id
+     WHERE pr.responded_at IS NULL";
+
+fn read(conn: &Connection, sql: &str, params: &[&dyn rusqlite::ToSql], now: &str) -> Vec<PendingResponse> {
+    let Ok(mut stmt) = conn.prepare(sql) else {
+        return Vec::new();
+    };
+    stmt.query_map(params, |r| {
+        let due_at: String = r.get(8)?;
+        Ok(PendingResponse {
+            message_id: r.get(0)?,
+            room_id: r.get(1)?,
+            room_name: r.get(2)?,
+            seq: r.get(3)?,
+            requested_by: r.get(4)?,
+            responder: r.get(5)?,
+            content: r.get(6)?,
+            requested_at: r.get(7)?,
+            overdue: due_at.as_str() <= now,
+            due_at,
+            escalated_at: r.get(9)?,
+        })
+    })
+    .map(|rows| rows.filter_map(|r| r.ok()).collect())
+    .unwrap_or_default()
+}
+
+/// Unanswered requests, oldest due first. `responder` and `requested_by` match any alias.
+pub fn list(
+    conn: &Connection,
+    responder: Option<&str>,
+    requested_by: Option<&str>,
+    room_id: Option<&str>,
+    access: &MemberAccess,
+) -> Vec<PendingResponse> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let responder = responder.map(|r| crate::db::resolve_sender(conn, r));
+    let requested_by = requested_by.map(|r| crate::db::resolve_sender(conn, r));
+    let sql = format!(
+        "{SELECT_PENDING} AND (?1 IS NULL OR pr.responder = ?1) AND (?2 IS NULL OR {}) AND (?3 IS NULL OR m.room_id = ?3)
+         AND {} ORDER BY pr.due_at ASC, m.seq ASC LIMIT 500",
+        crate::db::sender_identity_sql("m.sender", 2),
+        access.visible_sql("m.room_id")
+    );
+    read(conn, &sql, &[&responder, &requested_by, &room_id], &now)
+}
+
+/// Mark every request past its due time as escalated, once. Returns them so the caller can
+/// publish `response_overdue`.
+pub fn escalate_due(conn: &Connection) -> Vec<PendingResponse> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let due = read(
+        conn,
+        &format!("{SELECT_PENDING} AND pr.escalated_at IS NULL AND pr.due_at <= ?1 ORDER BY pr.due_at ASC"),
+        &[&now],
+        &now,
+    );
+    due.into_iter()
+        .filter(|p| {
+            conn.execute(
+                "UPDATE pending_responses SET escalated_at = ?1 WHERE message_id = ?2 AND responder = ?3 AND escalated_at IS NULL",
+                params![&now, &p.message_id, &p.responder],
+            )
+            .is_ok_and(|n| n > 0)
+        })
+        .map(|p| PendingResponse {
+            escalated_at: Some(now.clone()),
+            ..p
+        })
+        .collect()
+}
+
+/// Escalates overdue response requests in one database; `namespace` tags the events.
+pub fn spawn_escalator(db_path: String, events: broadcast::Sender<Published>, namespace: Option<String>) {
+    tokio::spawn(async move {
+        let conn = match crate::db::open(&db_path) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("⚠️ Response escalator: failed to open DB: {e}");
+                return;
+            }
+        };
+        crate::db::DbConfig::from_env().apply(&conn).ok();
+
+        loop {
+            tokio::time::sleep(std::time::Duration::from_secs(ESCALATE_INTERVAL_SECS)).await;
+            for pending in escalate_due(&conn) {
+                let _ = events.send(Published::in_namespace(ChatEvent::ResponseOverdue(pending), namespace.as_deref()));
+            }
+        }
+    });
+}
@@ -1,5 +1,9 @@
+use crate::events::{ChatEvent, Published};
+use crate::models::{Message, RetentionNotice};
+use crate::telemetry::{self, SpanContext, SpanKind};
 use rusqlite::{params, Connection};
 use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
 
 /// Interval between retention sweeps (seconds).
 const RETENTION_INTERVAL_SECS: u64 = 60;
@@ -18,6 +22,47 @@ pub struct RetentionResult {
     pub rooms_checked: usize,
     pub total_pruned: i64,
     pub details: Vec<RoomRetentionDetail>,
+    /// Files removed because their `expires_at` passed, as (file_id, room_id)
+    pub expired_files: Vec<(String, String)>,
+    /// Notices issued this sweep for rooms with `retention_notice_secs` (their purge is deferred)
+    pub notices: Vec<RetentionNotice>,
+    /// Sensitive messages whose content was redacted, as (message_id, room_id, redacted_at)
+    pub redacted: Vec<(String, String, String)>,
+    /// Streamed messages whose lease lapsed, finalized as they stood
+    pub finalized_streams: Vec<Message>,
+    /// Empty placeholders of lapsed streams, removed, as (message_id, room_id)
+    pub abandoned_streams: Vec<(String, String)>,
+}
+
+impl RetentionResult {
+    /// `file_expired`, `message_redacted`, `retention_pending`, and (for lapsed streams)
+    /// `message_finalized` / `message_deleted` events for this sweep.
+    pub fn events(&self) -> impl Iterator<Item = ChatEvent> + '_ {
+        self.expired_files
+            .iter()
+            .map(|(id, room_id)| ChatEvent::FileExpired {
+                id: id.clone(),
+                room_id: room_id.clone(),
+            })
+            .chain(self.redacted.iter().map(|(id, room_id, redacted_at)| ChatEvent::MessageRedacted {
+                id: id.clone(),
+                room_id: room_id.clone(),
+                redacted_at: redacted_at.clone(),
+            }))
+            .chain(self.notices.iter().cloned().map(ChatEvent::RetentionPending))
+            .chain(self.finalized_streams.iter().cloned().map(ChatEvent::MessageFinalized))
+            .chain(self.abandoned_streams.iter().map(|(id, room_id)| ChatEvent::MessageDeleted {
+                id: id.clone(),
+                room_id: room_id.clone(),
+            }))
+    }
+}
+
+/// A message retention would remove.
+struct Candidate {
+    id: String,
+    seq: i64,
+    created_at: String,
 }
 
 /// Spawns a background task that periodically prunes messages based on room retention settings.
@@ -28,9 +73,18 @@ pub struct RetentionResult {
 ///
 /// Both settings can be combined. Pruning also cleans up the FTS index.
 /// CASCADE deletes handle reactions automatically.
-pub fn spawn_retention_task(db_path: String) {
+///
+/// Each sweep also removes files past their `expires_at` and publishes `file_expired` for them,
+/// and redacts sensitive messages that are due (`message_redacted`). Streamed messages whose
+/// sender went quiet past the lease are finalized, or removed if nothing was streamed.
+///
+/// Rooms with `retention_notice_secs` get a `retention_pending` event first; messages up to the
+/// announced cutoff are purged once the notice period (plus any one-time postponement) is over.
+///
+/// One task runs per database; `namespace` tags the events it publishes.
+pub fn spawn_retention_task(db_path: String, events: broadcast::Sender<Published>, namespace: Option<String>) {
     tokio::spawn(async move {
-        let conn = Arc::new(Mutex::new(match Connection::open(&db_path) {
+        let conn = Arc::new(Mutex::new(match crate::db::open(&db_path) {
             Ok(c) => c,
             Err(e) => {
                 eprintln!("⚠️ Retention task: failed to open DB: {e}");
@@ -39,8 +93,7 @@ pub fn spawn_retention_task(db_path: String) {
         }));
         {
             let db = conn.lock().unwrap_or_else(|e| e.into_inner());
-            db.execute_batch("PRAGMA journal_mode=WAL; PRAGMA foreign_keys=ON;")
-                .ok();
+            crate::db::DbConfig::from_env().apply(&db).ok();
         }
 
         // Initial delay: let the server start up before the first sweep
@@ -52,7 +105,10 @@ pub fn spawn_retention_task(db_path: String) {
                     eprintln!("WARN: Retention task DB mutex poisoned, recovering");
                     e.into_inner()
                 });
-                run_retention(&db);
+                let result = run_retention(&db, None);
+                for event in result.events() {
+                    let _ = events.send(Published::in_namespace(event, namespace.as_deref()));
+                }
             }
             tokio::time::sleep(std::time::Duration::from_secs(RETENTION_INTERVAL_SECS)).await;
         }
@@ -61,23 +117,53 @@ pub fn spawn_retention_task(db_path: String) {
 
 /// Execute one retention sweep across all rooms with retention settings.
 /// Returns structured results for inspection/logging.
-pub fn run_retention(conn: &Connection) -> RetentionResult {
+/// Traced as a `retention.run` span (child of `parent` if given) with one `db.retention.prune` span per room.
+pub fn run_retention(conn: &Connection, parent: Option<&SpanContext>) -> RetentionResult {
+    let mut span = telemetry::start_span("retention.run", SpanKind::Internal, parent);
+    let result = sweep(conn, span.as_ref().map(|s| s.context()));
+    if let Some(ref mut span) = span {
+        span.set_attribute("retention.rooms_checked", result.rooms_checked as i64);
+        span.set_attribute("retention.total_pruned", result.total_pruned);
+    }
+    result
+}
+
+fn sweep(conn: &Connection, parent: Option<&SpanContext>) -> RetentionResult {
+    let (finalized_streams, abandoned_streams) = expire_streams(conn);
     let mut result = RetentionResult {
         rooms_checked: 0,
         total_pruned: 0,
         details: Vec::new(),
+        expired_files: expire_files(conn),
+        notices: Vec::new(),
+        redacted: crate::redaction::redact_due(conn),
+        finalized_streams,
+        abandoned_streams,
     };
+    if !result.expired_files.is_empty() {
+        eprintln!("🧹 Retention: removed {} expired files", result.expired_files.len());
+    }
+    if !result.redacted.is_empty() {
+        eprintln!("🧹 Retention: redacted {} sensitive messages", result.redacted.len());
+    }
+    if !result.finalized_streams.is_empty() || !result.abandoned_streams.is_empty() {
+        eprintln!(
+            "🧹 Retention: closed {} lapsed streams ({} empty, removed)",
+            result.finalized_streams.len() + result.abandoned_streams.len(),
+            result.abandoned_streams.len()
+        );
+    }
 
     // Find rooms with any retention settings
-    let rooms: Vec<(String, Option<i64>, Option<i64>)> = {
+    let rooms: Vec<(String, Option<i64>, Option<i64>, Option<i64>)> = {
         let mut stmt = match conn.prepare(
-            "SELECT id, max_messages, max_message_age_hours FROM rooms
+            "SELECT id, max_messages, max_message_age_hours, retention_notice_secs FROM rooms
              WHERE max_messages IS NOT NULL OR max_message_age_hours IS NOT NULL",
         ) {
             Ok(s) => s,
             Err(_) => return result,
         };
-        match stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))) {
+        match stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))) {
             Ok(rows) => rows.filter_map(|r| r.ok()).collect(),
             Err(_) => return result,
         }
@@ -85,29 +171,52 @@ pub fn run_retention(conn: &Connection) -> RetentionResult {
 
     result.rooms_checked = rooms.len();
 
-    for (room_id, max_messages, max_age_hours) in rooms {
+    for (room_id, max_messages, max_age_hours, notice_secs) in rooms {
+        let mut room_span = telemetry::start_span("db.retention.prune", SpanKind::Internal, parent);
         let mut detail = RoomRetentionDetail {
             room_id: room_id.clone(),
             pruned_by_count: 0,
             pruned_by_age: 0,
         };
 
-        // Prune by max_messages (keep newest N, pinned messages exempt)
-        if let Some(max) = max_messages {
-            detail.pruned_by_count = prune_by_count(conn, &room_id, max);
-        }
+        // Oldest beyond max_messages, and anything older than max_message_age_hours (pinned exempt)
+        let mut by_count = max_messages.map(|max| count_candidates(conn, &room_id, max)).unwrap_or_default();
+        let mut by_age = max_age_hours.map(|hours| age_candidates(conn, &room_id, hours)).unwrap_or_default();
 
-        // Prune by max_message_age_hours (pinned messages exempt)
-        if let Some(hours) = max_age_hours {
-            detail.pruned_by_age = prune_by_age(conn, &room_id, hours);
+        // Rooms that asked for notice only lose what was announced, once the notice is due
+        if let Some(secs) = notice_secs {
+            match notice_due(conn, &room_id, secs, &by_count, &by_age) {
+                NoticeState::Due { cutoff_seq } => {
+                    by_count.retain(|c| c.seq <= cutoff_seq);
+                    by_age.retain(|c| c.seq <= cutoff_seq);
+                }
+                NoticeState::Issued(notice) => {
+                    result.notices.push(notice);
+                    by_count.clear();
+                    by_age.clear();
+                }
+                NoticeState::Waiting => {
+                    by_count.clear();
+                    by_age.clear();
+                }
+            }
         }
+        by_age.retain(|a| !by_count.iter().any(|c| c.id == a.id));
+        detail.pruned_by_count = delete_messages(conn, &ids(&by_count));
+        detail.pruned_by_age = delete_messages(conn, &ids(&by_age));
 
         let room_total = detail.pruned_by_count + detail.pruned_by_age;
+        if let Some(ref mut span) = room_span {
+            span.set_attribute("chat.room_id", room_id.as_str());
+            span.set_attribute("retention.pruned", room_total);
+        }
+        drop(room_span);
         if room_total > 0 {
             eprintln!(
                 "🧹 Retention: pruned {} messages from room {}",
                 room_total, room_id
             );
+            record_purge(conn, &room_id, &detail);
         }
 
         result.total_pruned += room_total;
@@ -117,58 +226,231 @@ pub fn run_retention(conn: &Connection) -> RetentionResult {
     result
 }
 
-/// Delete oldest non-pinned messages beyond the count limit. Returns number pruned.
-fn prune_by_count(conn: &Connection, room_id: &str, max_messages: i64) -> i64 {
-    // Get IDs of non-pinned messages to delete (oldest first, beyond the limit)
-    let ids_to_delete: Vec<String> = {
-        // Count non-pinned messages
-        let non_pinned_count: i64 = conn
-            .query_row(
-                "SELECT COUNT(*) FROM messages WHERE room_id = ?1 AND pinned_at IS NULL",
-                params![room_id],
-                |r| r.get(0),
-            )
-            .unwrap_or(0);
+/// Leave a system message saying what retention removed. Only the latest purge note is kept
+/// per room, so a busy room with a count limit doesn't fill up with notes.
+fn record_purge(conn: &Connection, room_id: &str, detail: &RoomRetentionDetail) {
+    let previous: Vec<String> = conn
+        .prepare(
+            "SELECT id FROM messages WHERE room_id = ?1 AND kind = 'system' \
+             AND json_extract(metadata, '$.event') = 'retention_purged'",
+        )
+        .and_then(|mut s| {
+            s.query_map(params![room_id], |row| row.get::<_, String>(0))
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .unwrap_or_default();
+    delete_messages(conn, &previous);
 
-        if non_pinned_count <= max_messages {
-            return 0;
-        }
+    let total = detail.pruned_by_count + detail.pruned_by_age;
+    crate::db::insert_localized_system_message(
+        conn,
+        room_id,
+        "retention_purged",
+        serde_json::json!({
+            "pruned": total,
+            "pruned_by_count": detail.pruned_by_count,
+            "pruned_by_age": detail.pruned_by_age,
+        }),
+    );
+}
 
-        let excess = non_pinned_count - max_messages;
-        let mut stmt = match conn.prepare(
-            "SELECT id FROM messages WHERE room_id = ?1 AND pinned_at IS NULL ORDER BY seq ASC LIMIT ?2",
-        ) {
-            Ok(s) => s,
-            Err(_) => return 0,
-        };
-        match stmt.query_map(params![room_id, excess], |row| row.get(0)) {
-            Ok(rows) => rows.filter_map(|r| r.ok()).collect(),
-            Err(_) => return 0,
-        }
-    };
+/// Delete files whose `expires_at` has passed. Shared blobs are released by the
+/// `files_release_blob` trigger once their last reference goes.
+fn expire_files(conn: &Connection) -> Vec<(String, String)> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let expired: Vec<(String, String)> = conn
+        .prepare("SELECT id, room_id FROM files WHERE expires_at IS NOT NULL AND expires_at <= ?1")
+        .and_then(|mut s| {
+            s.query_map(params![&now], |r| Ok((r.get(0)?, r.get(1)?)))
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .unwrap_or_default();
+    expired
+        .into_iter()
+        .filter(|(id, _)| {
+            conn.execute("DELETE FROM files WHERE id = ?1", params![id])
+                .map(|n| n > 0)
+                .unwrap_or(false)
+        })
+        .collect()
+}
 
-    delete_messages(conn, &ids_to_delete)
+fn ids(candidates: &[Candidate]) -> Vec<String> {
+    candidates.iter().map(|c| c.id.clone()).collect()
 }
 
-/// Delete non-pinned messages older than the specified hours. Returns number pruned.
-fn prune_by_age(conn: &Connection, room_id: &str, max_age_hours: i64) -> i64 {
-    let cutoff = chrono::Utc::now() - chrono::Duration::hours(max_age_hours);
-    let cutoff_str = cutoff.to_rfc3339();
+fn query_candidates(conn: &Connection, sql: &str, params: impl rusqlite::Params) -> Vec<Candidate> {
+    conn.prepare(sql)
+        .and_then(|mut s| {
+            s.query_map(params, |row| {
+                Ok(Candidate {
+                    id: row.get(0)?,
+                    seq: row.get(1)?,
+                    created_at: row.get(2)?,
+                })
+            })
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .unwrap_or_default()
+}
 
-    let ids_to_delete: Vec<String> = {
-        let mut stmt = match conn.prepare(
-            "SELECT id FROM messages WHERE room_id = ?1 AND pinned_at IS NULL AND created_at < ?2",
-        ) {
-            Ok(s) => s,
-            Err(_) => return 0,
-        };
-        match stmt.query_map(params![room_id, cutoff_str], |row| row.get(0)) {
-            Ok(rows) => rows.filter_map(|r| r.ok()).collect(),
-            Err(_) => return 0,
+/// Oldest non-pinned messages beyond the count limit.
+/// System messages don't count toward the limit (they age out with `max_message_age_hours`).
+fn count_candidates(conn: &Connection, room_id: &str, max_messages: i64) -> Vec<Candidate> {
+    let non_pinned_count: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM messages WHERE room_id = ?1 AND pinned_at IS NULL AND kind != 'system'",
+            params![room_id],
+            |r| r.get(0),
+        )
+        .unwrap_or(0);
+    if non_pinned_count <= max_messages {
+        return Vec::new();
+    }
+    query_candidates(
+        conn,
+        "SELECT id, seq, created_at FROM messages WHERE room_id = ?1 AND pinned_at IS NULL AND kind != 'system' ORDER BY seq ASC LIMIT ?2",
+        params![room_id, non_pinned_count - max_messages],
+    )
+}
+
+/// Non-pinned messages older than the specified hours.
+fn age_candidates(conn: &Connection, room_id: &str, max_age_hours: i64) -> Vec<Candidate> {
+    let cutoff = (chrono::Utc::now() - chrono::Duration::hours(max_age_hours)).to_rfc3339();
+    query_candidates(
+        conn,
+        "SELECT id, seq, created_at FROM messages WHERE room_id = ?1 AND pinned_at IS NULL AND created_at < ?2",
+        params![room_id, cutoff],
+    )
+}
+
+enum NoticeState {
+    /// A notice went out this sweep; nothing is deleted yet
+    Issued(RetentionNotice),
+    /// The notice period (or postponement) is still running
+    Waiting,
+    /// The notice period is over: delete up to the announced cutoff
+    Due { cutoff_seq: i64 },
+}
+
+/// Where a notice-enabled room stands. The first sweep that finds something to purge records and
+/// returns a notice; later sweeps wait until `purge_after`, then purge and clear it.
+fn notice_due(conn: &Connection, room_id: &str, notice_secs: i64, by_count: &[Candidate], by_age: &[Candidate]) -> NoticeState {
+    let existing: Option<(i64, String)> = conn
+        .query_row(
+            "SELECT cutoff_seq, purge_after FROM retention_notices WHERE room_id = ?1",
+            params![room_id],
+            |r| Ok((r.get(0)?, r.get(1)?)),
+        )
+        .ok();
+    let now = chrono::Utc::now();
+    if let Some((cutoff_seq, purge_after)) = existing {
+        let due = chrono::DateTime::parse_from_rfc3339(&purge_after).map_or(true, |t| t <= now);
+        if !due {
+            return NoticeState::Waiting;
         }
+        conn.execute("DELETE FROM retention_notices WHERE room_id = ?1", params![room_id])
+            .ok();
+        return NoticeState::Due { cutoff_seq };
+    }
+
+    let Some(last) = by_count.iter().chain(by_age).max_by_key(|c| c.seq) else {
+        return NoticeState::Waiting;
     };
+    let mut pending: Vec<&str> = by_count.iter().chain(by_age).map(|c| c.id.as_str()).collect();
+    pending.sort_unstable();
+    pending.dedup();
+    let notice = RetentionNotice {
+        room_id: room_id.to_string(),
+        pending_count: pending.len() as i64,
+        cutoff_seq: last.seq,
+        cutoff_at: last.created_at.clone(),
+        notified_at: now.to_rfc3339(),
+        purge_after: (now + chrono::Duration::seconds(notice_secs)).to_rfc3339(),
+        postponed: false,
+    };
+    let stored = conn.execute(
+        "INSERT INTO retention_notices (room_id, pending_count, cutoff_seq, cutoff_at, notified_at, purge_after, postponed)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, 0)",
+        params![
+            &notice.room_id,
+            notice.pending_count,
+            notice.cutoff_seq,
+            &notice.cutoff_at,
+            &notice.notified_at,
+            &notice.purge_after
+        ],
+    );
+    match stored {
+        Ok(_) => NoticeState::Issued(notice),
+        // Never purge without a recorded notice
+        Err(_) => NoticeState::Waiting,
+    }
+}
+
+/// Close streams whose lease lapsed: the message is finalized as it stands (indexed for search
+/// and mentions), or deleted if nothing was streamed into it. Each stream is closed in its own
+/// transaction. Returns (finalized, removed as (message_id, room_id)).
+fn expire_streams(conn: &Connection) -> (Vec<Message>, Vec<(String, String)>) {
+    let lapsed: Vec<(String, String)> = conn
+        .prepare("SELECT message_id, room_id FROM message_streams WHERE lease_expires_at < ?1")
+        .and_then(|mut s| {
+            s.query_map(params![chrono::Utc::now().to_rfc3339()], |r| Ok((r.get(0)?, r.get(1)?)))
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .unwrap_or_default();
 
-    delete_messages(conn, &ids_to_delete)
+    let mut finalized = Vec::new();
+    let mut removed = Vec::new();
+    for (id, room_id) in lapsed {
+        let Ok(tx) = conn.unchecked_transaction() else {
+            continue;
+        };
+        let msg = tx
+            .query_row(
+                "SELECT id, room_id, sender, content, metadata, created_at, edited_at, reply_to, sender_type, seq, pinned_at, pinned_by, kind FROM messages WHERE id = ?1",
+                params![&id],
+                |row| {
+                    Ok(Message {
+                        id: row.get(0)?,
+                        room_id: row.get(1)?,
+                        sender: row.get(2)?,
+                        content: row.get(3)?,
+                        metadata: serde_json::from_str(&row.get::<_, String>(4)?).unwrap_or(serde_json::json!({})),
+                        created_at: row.get(5)?,
+                        edited_at: row.get(6)?,
+                        reply_to: row.get(7)?,
+                        sender_type: row.get(8)?,
+                        seq: row.get(9)?,
+                        pinned_at: row.get(10)?,
+                        pinned_by: row.get(11)?,
+                        edit_count: 0,
+                        kind: row.get(12)?,
+                        local_time: None,
+                    })
+                },
+            )
+            .ok();
+        if tx.execute("DELETE FROM message_streams WHERE message_id = ?1", params![&id]).is_err() {
+            continue;
+        }
+        let keep = msg.filter(|m| !m.content.trim().is_empty());
+        if keep.is_some() {
+            crate::db::upsert_fts(&tx, &id);
+            crate::db::index_mentions(&tx, &id);
+        } else {
+            delete_messages(&tx, std::slice::from_ref(&id));
+        }
+        if let Err(e) = tx.commit() {
+            eprintln!("⚠️ Retention: failed to close lapsed stream {id}: {e}");
+            continue;
+        }
+        match keep {
+            Some(msg) => finalized.push(msg),
+            None => removed.push((id, room_id)),
+        }
+    }
+    (finalized, removed)
 }
 
 /// Delete messages by ID, cleaning up FTS index first. Returns count deleted.
@@ -184,14 +466,13 @@ fn delete_messages(conn: &Connection, ids: &[String]) -> i64 {
         let placeholders: Vec<String> = (0..chunk.len()).map(|i| format!("?{}", i + 1)).collect();
         let placeholder_str = placeholders.join(",");
 
-        // Clean up FTS index
-        let fts_sql = format!(
-            "DELETE FROM messages_fts WHERE message_id IN ({})",
-            placeholder_str
-        );
+        // Clean up FTS indexes
         let params_refs: Vec<&dyn rusqlite::types::ToSql> =
             chunk.iter().map(|s| s as &dyn rusqlite::types::ToSql).collect();
-        conn.execute(&fts_sql, params_refs.as_slice()).ok();
+        for (_, table) in crate::db::FTS_TABLES {
+            let fts_sql = format!("DELETE FROM {table} WHERE message_id IN ({placeholder_str})");
+            conn.execute(&fts_sql, params_refs.as_slice()).ok();
+        }
 
         // Delete messages (CASCADE handles reactions)
         let del_sql = format!(
@@ -0,0 +1,20 @@
+use crate::admin_lockout::AdminKeyLocked;
+use crate::rate_limit::{RateLimitConfig, RateLimitInfo, RateLimitedError};
+use rocket::{State, get};
+
+/// Where [`crate::admin_lockout::AdminKeyLockoutFairing`] sends admin keys from a locked-out IP.
+#[get("/api/v1/admin-key/locked")]
+pub fn admin_key_locked(locked: AdminKeyLocked, config: &State<RateLimitConfig>) -> RateLimitedError {
+    RateLimitedError {
+        info: RateLimitInfo {
+            allowed: false,
+            limit: config.admin_key_max_failures,
+            remaining: 0,
+            retry_after_secs: locked.0,
+        },
+        message: format!(
+            "Too many wrong admin keys for this room from your address. Try again in {} seconds.",
+            locked.0
+        ),
+    }
+}
@@ -1,16 +1,17 @@
-use crate::db::Db;
-use crate::events::{ChatEvent, EventBus};
+use crate::membership::MemberAccess;
+use crate::namespaces::ScopedDb;
+use crate::events::{ChatEvent, Events};
 use crate::models::*;
 use rocket::http::Status;
 use rocket::serde::json::Json;
-use rocket::{delete, get, put, State};
+use rocket::{delete, get, put};
 use rusqlite::params;
 
 /// PUT /api/v1/rooms/<room_id>/bookmark — Add a bookmark
 #[put("/api/v1/rooms/<room_id>/bookmark", format = "json", data = "<body>")]
 pub fn add_bookmark(
-    db: &State<Db>,
-    events: &State<EventBus>,
+    db: ScopedDb<'_>,
+    events: Events<'_>,
     room_id: &str,
     body: Json<BookmarkAction>,
 ) -> Result<Json<serde_json::Value>, (Status, Json<serde_json::Value>)> {
@@ -76,8 +77,8 @@ pub fn add_bookmark(
 /// DELETE /api/v1/rooms/<room_id>/bookmark?sender=<sender> — Remove a bookmark
 #[delete("/api/v1/rooms/<room_id>/bookmark?<sender>")]
 pub fn remove_bookmark(
-    db: &State<Db>,
-    events: &State<EventBus>,
+    db: ScopedDb<'_>,
+    events: Events<'_>,
     room_id: &str,
     sender: &str,
 ) -> Result<Json<serde_json::Value>, (Status, Json<serde_json::Value>)> {
@@ -118,11 +119,13 @@ pub fn remove_bookmark(
     })))
 }
 
-/// GET /api/v1/bookmarks?sender=<sender> — List sender's bookmarked rooms
+/// GET /api/v1/bookmarks?sender=<sender> — List sender's bookmarked rooms (private rooms only
+/// with a member token for them)
 #[get("/api/v1/bookmarks?<sender>")]
 pub fn list_bookmarks(
-    db: &State<Db>,
+    db: ScopedDb<'_>,
     sender: &str,
+    access: MemberAccess,
 ) -> Result<Json<BookmarksResponse>, (Status, Json<serde_json::Value>)> {
     let sender = sender.trim();
     if sender.is_empty() {
@@ -134,17 +137,19 @@ pub fn list_bookmarks(
 
     let conn = db.conn();
 
+    let sql = format!(
+        "SELECT r.id, r.name, r.description, r.created_at,
+                b.created_at as bookmarked_at,
+                (SELECT COUNT(*) FROM messages WHERE room_id = r.id) as message_count,
+                (SELECT MAX(created_at) FROM messages WHERE room_id = r.id) as last_activity
+         FROM bookmarks b
+         JOIN rooms r ON r.id = b.room_id
+         WHERE b.sender = ?1 AND {}
+         ORDER BY b.created_at DESC",
+        access.visible_sql("r.id")
+    );
     let mut stmt = conn
-        .prepare(
-            "SELECT r.id, r.name, r.description, r.created_at,
-                    b.created_at as bookmarked_at,
-                    (SELECT COUNT(*) FROM messages WHERE room_id = r.id) as message_count,
-                    (SELECT MAX(created_at) FROM messages WHERE room_id = r.id) as last_activity
-             FROM bookmarks b
-             JOIN rooms r ON r.id = b.room_id
-             WHERE b.sender = ?1
-             ORDER BY b.created_at DESC",
-        )
+        .prepare(&sql)
         .map_err(|_e| {
             (
                 Status::InternalServerError,
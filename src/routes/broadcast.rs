@@ -1,7 +1,9 @@
-use crate::db::Db;
-use crate::events::{ChatEvent, EventBus};
+use crate::membership::MemberAccess;
+use crate::namespaces::ScopedDb;
+use crate::events::{ChatEvent, Events};
 use crate::models::*;
-use crate::rate_limit::RateLimiter;
+use crate::rate_limit::{RateLimitConfig, RateLimiter};
+use crate::senders::{SenderPolicy, ServerToken};
 use rocket::http::Status;
 use rocket::serde::json::Json;
 use rocket::{post, State};
@@ -21,11 +23,16 @@ use super::ClientIp;
 /// Rate limit: 10 broadcasts/minute per IP.
 /// Max 20 rooms per broadcast.
 #[post("/api/v1/broadcast", format = "json", data = "<body>")]
+#[allow(clippy::too_many_arguments)]
 pub fn broadcast_message(
-    db: &State<Db>,
-    events: &State<EventBus>,
+    db: ScopedDb<'_>,
+    events: Events<'_>,
     rate_limiter: &State<RateLimiter>,
+    rate_config: &State<RateLimitConfig>,
+    sender_policy: &State<SenderPolicy>,
+    server_token: ServerToken,
     ip: ClientIp,
+    access: MemberAccess,
     body: Json<BroadcastMessage>,
 ) -> Result<Json<BroadcastResponse>, (Status, Json<serde_json::Value>)> {
     // Rate limit: 10 broadcasts/min per IP
@@ -50,6 +57,7 @@ pub fn broadcast_message(
             Json(serde_json::json!({"error": "Sender must be 1-100 characters"})),
         ));
     }
+    sender_policy.check(&sender, &server_token)?;
 
     // Validate content
     let content = body.content.trim().to_string();
@@ -79,6 +87,9 @@ pub fn broadcast_message(
     let now = chrono::Utc::now().to_rfc3339();
 
     let conn = db.conn();
+    // The whole broadcast must fit in the sender's daily budget; a partial fan-out would be
+    // harder to reason about than a clean refusal
+    crate::quotas::check(&conn, rate_config, &sender, body.room_ids.len() as i64, 0)?;
     let mut results: Vec<BroadcastDelivery> = Vec::with_capacity(body.room_ids.len());
 
     for room_id in &body.room_ids {
@@ -114,6 +125,15 @@ pub fn broadcast_message(
             });
             continue;
         }
+        if !access.can_see(&conn, room_id) {
+            results.push(BroadcastDelivery {
+                room_id: room_id.to_string(),
+                success: false,
+                message_id: None,
+                error: Some("This room is private: only its members can read or post".to_string()),
+            });
+            continue;
+        }
 
         // Insert message
         let msg_id = uuid::Uuid::new_v4().to_string();
@@ -139,6 +159,8 @@ pub fn broadcast_message(
 
         match insert_result {
             Ok(_) => {
+                crate::quotas::record(&conn, &sender, 1, 0);
+
                 // Update room updated_at
                 conn.execute(
                     "UPDATE rooms SET updated_at = ?1 WHERE id = ?2",
@@ -146,8 +168,9 @@ pub fn broadcast_message(
                 )
                 .ok();
 
-                // Update FTS index
+                // Update FTS and mention indexes
                 crate::db::upsert_fts(&conn, &msg_id);
+                crate::db::index_mentions(&conn, &msg_id);
 
                 // Fire SSE event
                 let msg = Message {
@@ -164,6 +187,8 @@ pub fn broadcast_message(
                     pinned_at: None,
                     pinned_by: None,
                     edit_count: 0,
+                    kind: "message".to_string(),
+                    local_time: None,
                 };
                 events.publish(ChatEvent::NewMessage(msg));
 
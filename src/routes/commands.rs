@@ -0,0 +1,245 @@
+use crate::namespaces::ScopedDb;
+use crate::models::{CommandSpec, RegisterCommands, RoomCommand, RoomCommandsResponse};
+use rocket::http::{ContentType, Status};
+use rocket::serde::json::Json;
+use rocket::{delete, get, put};
+use rusqlite::{params, Connection};
+
+/// Most commands one sender may register per room.
+const MAX_COMMANDS: usize = 50;
+
+fn err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+fn room_exists(conn: &Connection, room_id: &str) -> bool {
+    conn.query_row("SELECT COUNT(*) FROM rooms WHERE id = ?1", params![room_id], |r| r.get::<_, i64>(0))
+        .map(|c| c > 0)
+        .unwrap_or(false)
+}
+
+fn load_commands(conn: &Connection, room_id: &str) -> Vec<RoomCommand> {
+    conn.prepare(
+        "SELECT sender, name, description, usage, updated_at FROM room_commands
+         WHERE room_id = ?1 ORDER BY sender COLLATE NOCASE, name",
+    )
+    .and_then(|mut s| {
+        s.query_map(params![room_id], |r| {
+            Ok(RoomCommand {
+                sender: r.get(0)?,
+                command: CommandSpec {
+                    name: r.get(1)?,
+                    description: r.get(2)?,
+                    usage: r.get(3)?,
+                },
+                updated_at: r.get(4)?,
+            })
+        })
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+    })
+    .unwrap_or_default()
+}
+
+/// Markdown bullets for a room's commands, grouped under each bot (display name and bio from its profile).
+fn render_commands(conn: &Connection, commands: &[RoomCommand], heading: &str) -> String {
+    let mut md = String::new();
+    let mut current: Option<&str> = None;
+    for cmd in commands {
+        if current != Some(cmd.sender.as_str()) {
+            current = Some(cmd.sender.as_str());
+            let (display_name, bio): (Option<String>, Option<String>) = conn
+                .query_row(
+                    "SELECT display_name, bio FROM profiles WHERE sender = ?1",
+                    params![&cmd.sender],
+                    |r| Ok((r.get(0)?, r.get(1)?)),
+                )
+                .unwrap_or((None, None));
+            match display_name {
+                Some(name) => md.push_str(&format!("\n{heading} {name} (@{})\n", cmd.sender)),
+                None => md.push_str(&format!("\n{heading} @{}\n", cmd.sender)),
+            }
+            if let Some(bio) = bio.filter(|b| !b.trim().is_empty()) {
+                md.push_str(&format!("{}\n", bio.trim()));
+            }
+            md.push('\n');
+        }
+        let usage = cmd.command.usage.as_deref().map(|u| format!(" {u}")).unwrap_or_default();
+        md.push_str(&format!("- `/{}{usage}` — {}\n", cmd.command.name, cmd.command.description));
+    }
+    md
+}
+
+/// The "registered agent commands" section appended to llms.txt: every unarchived room where
+/// bots have registered commands. None when there are none.
+pub(super) fn llms_commands_section(conn: &Connection) -> Option<String> {
+    let rooms: Vec<(String, String)> = conn
+        .prepare(
+            "SELECT r.id, r.name FROM rooms r
+             WHERE r.archived_at IS NULL AND EXISTS (SELECT 1 FROM room_commands c WHERE c.room_id = r.id)
+             ORDER BY r.name",
+        )
+        .and_then(|mut s| {
+            s.query_map([], |r| Ok((r.get(0)?, r.get(1)?)))
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .unwrap_or_default();
+    if rooms.is_empty() {
+        return None;
+    }
+    let mut md = String::from(
+        "\n## Registered Agent Commands\nBots in these rooms answer the commands below; GET /api/v1/rooms/{id}/help has the full room guide.\n",
+    );
+    for (room_id, name) in rooms {
+        md.push_str(&format!("\n### #{name} ({room_id})\n"));
+        md.push_str(&render_commands(conn, &load_commands(conn, &room_id), "####"));
+    }
+    Some(md)
+}
+
+/// PUT /api/v1/rooms/<room_id>/commands/<sender> — register the commands a bot answers to in this
+/// room, replacing its previous set (an empty list clears it).
+#[put("/api/v1/rooms/<room_id>/commands/<sender>", format = "json", data = "<body>")]
+pub fn register_commands(
+    db: ScopedDb<'_>,
+    room_id: &str,
+    sender: &str,
+    body: Json<RegisterCommands>,
+) -> Result<Json<RoomCommandsResponse>, (Status, Json<serde_json::Value>)> {
+    let sender = sender.trim();
+    if sender.is_empty() || sender.len() > 100 {
+        return Err(err(Status::BadRequest, "Sender must be 1-100 characters"));
+    }
+    if body.commands.len() > MAX_COMMANDS {
+        return Err(err(Status::BadRequest, &format!("At most {MAX_COMMANDS} commands per sender")));
+    }
+    let mut commands = Vec::with_capacity(body.commands.len());
+    for spec in &body.commands {
+        let name = spec.name.trim().trim_start_matches('/').to_lowercase();
+        let valid = !name.is_empty()
+            && name.len() <= 32
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(err(
+                Status::BadRequest,
+                &format!("Invalid command name '{}': use 1-32 letters, digits, '-' or '_'", spec.name),
+            ));
+        }
+        let description = spec.description.trim();
+        if description.is_empty() || description.len() > 500 {
+            return Err(err(Status::BadRequest, &format!("/{name}: description must be 1-500 characters")));
+        }
+        let usage = spec.usage.as_deref().map(str::trim).filter(|u| !u.is_empty());
+        if usage.is_some_and(|u| u.len() > 200) {
+            return Err(err(Status::BadRequest, &format!("/{name}: usage must be at most 200 characters")));
+        }
+        if commands.iter().any(|c: &CommandSpec| c.name == name) {
+            return Err(err(Status::BadRequest, &format!("Duplicate command /{name}")));
+        }
+        commands.push(CommandSpec {
+            name,
+            description: description.to_string(),
+            usage: usage.map(str::to_string),
+        });
+    }
+
+    let conn = db.conn();
+    if !room_exists(&conn, room_id) {
+        return Err(err(Status::NotFound, "Room not found"));
+    }
+    let now = chrono::Utc::now().to_rfc3339();
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+    tx.execute(
+        "DELETE FROM room_commands WHERE room_id = ?1 AND sender = ?2",
+        params![room_id, sender],
+    )
+    .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+    for cmd in &commands {
+        tx.execute(
+            "INSERT INTO room_commands (room_id, sender, name, description, usage, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![room_id, sender, &cmd.name, &cmd.description, &cmd.usage, &now],
+        )
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+    }
+    tx.commit()
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+
+    Ok(Json(RoomCommandsResponse {
+        room_id: room_id.to_string(),
+        commands: load_commands(&conn, room_id)
+            .into_iter()
+            .filter(|c| c.sender == sender)
+            .collect(),
+    }))
+}
+
+/// GET /api/v1/rooms/<room_id>/commands — every command registered in the room, grouped by sender.
+#[get("/api/v1/rooms/<room_id>/commands")]
+pub fn list_commands(
+    db: ScopedDb<'_>,
+    room_id: &str,
+) -> Result<Json<RoomCommandsResponse>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    if !room_exists(&conn, room_id) {
+        return Err(err(Status::NotFound, "Room not found"));
+    }
+    Ok(Json(RoomCommandsResponse {
+        room_id: room_id.to_string(),
+        commands: load_commands(&conn, room_id),
+    }))
+}
+
+/// DELETE /api/v1/rooms/<room_id>/commands/<sender> — drop a bot's commands from the room.
+#[delete("/api/v1/rooms/<room_id>/commands/<sender>")]
+pub fn delete_commands(
+    db: ScopedDb<'_>,
+    room_id: &str,
+    sender: &str,
+) -> Result<Json<serde_json::Value>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    let deleted = conn
+        .execute(
+            "DELETE FROM room_commands WHERE room_id = ?1 AND sender = ?2",
+            params![room_id, sender],
+        )
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+    if deleted == 0 {
+        return Err(err(Status::NotFound, "No commands registered by this sender in this room"));
+    }
+    Ok(Json(serde_json::json!({"deleted": deleted, "room_id": room_id, "sender": sender})))
+}
+
+/// GET /api/v1/rooms/<room_id>/help — a Markdown guide to the room for newly joined agents:
+/// what it's for, and which bots answer which commands.
+#[get("/api/v1/rooms/<room_id>/help")]
+pub fn room_help(
+    db: ScopedDb<'_>,
+    room_id: &str,
+) -> Result<(ContentType, String), (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    let (name, description): (String, Option<String>) = conn
+        .query_row("SELECT name, description FROM rooms WHERE id = ?1", params![room_id], |r| {
+            Ok((r.get(0)?, r.get(1)?))
+        })
+        .map_err(|_| err(Status::NotFound, "Room not found"))?;
+
+    let mut md = format!("# #{name} — help\n\n");
+    if let Some(description) = description.filter(|d| !d.trim().is_empty()) {
+        md.push_str(&format!("{}\n\n", description.trim()));
+    }
+    md.push_str("## Commands\n");
+    let commands = load_commands(&conn, room_id);
+    if commands.is_empty() {
+        md.push_str(&format!(
+            "\nNo bots have registered commands here yet. Register yours with `PUT /api/v1/rooms/{room_id}/commands/<sender>`.\n"
+        ));
+    } else {
+        md.push_str(&render_commands(&conn, &commands, "###"));
+    }
+    md.push_str(&format!(
+        "\n## Getting started\n\n- Read recent messages: `GET /api/v1/rooms/{room_id}/messages?latest=50`\n- Post: `POST /api/v1/rooms/{room_id}/messages` with `{{\"sender\": \"<you>\", \"content\": \"...\"}}`\n- Full API reference: `/llms.txt`\n"
+    ));
+    Ok((ContentType::Markdown, md))
+}
@@ -0,0 +1,180 @@
+use crate::db::parse_mentions;
+use crate::namespaces::ScopedDb;
+use crate::models::{Conversation, ConversationsResponse};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::get;
+use rusqlite::params;
+
+/// Default silence (seconds) that ends a conversation.
+const DEFAULT_GAP_SECS: i64 = 300;
+/// Max messages clustered per request.
+const MAX_MESSAGES: i64 = 1000;
+
+fn err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+struct Row {
+    id: String,
+    seq: i64,
+    sender: String,
+    content: String,
+    reply_to: Option<String>,
+    created_at: String,
+    at: chrono::DateTime<chrono::FixedOffset>,
+}
+
+struct Cluster {
+    rows: Vec<Row>,
+    participants: Vec<String>,
+}
+
+impl Cluster {
+    fn last_at(&self) -> chrono::DateTime<chrono::FixedOffset> {
+        self.rows.last().map(|r| r.at).expect("clusters are never empty")
+    }
+
+    fn has_participant(&self, name: &str) -> bool {
+        self.participants.iter().any(|p| p.eq_ignore_ascii_case(name))
+    }
+
+    fn push(&mut self, row: Row) {
+        if !self.has_participant(&row.sender) {
+            self.participants.push(row.sender.clone());
+        }
+        self.rows.push(row);
+    }
+}
+
+/// Pick the conversation a message belongs to, most specific signal first:
+/// 1. it replies to a message in a conversation;
+/// 2. it @mentions someone active in a conversation that's still open (within the gap);
+/// 3. its sender is already talking in an open conversation;
+/// 4. the most recent conversation is still open.
+///
+/// Otherwise it starts a new one.
+fn assign(clusters: &[Cluster], row: &Row, gap: chrono::Duration) -> Option<usize> {
+    if let Some(ref parent) = row.reply_to
+        && let Some(i) = clusters.iter().rposition(|c| c.rows.iter().any(|r| &r.id == parent))
+    {
+        return Some(i);
+    }
+    let open = |c: &Cluster| row.at - c.last_at() <= gap;
+    let mentions = parse_mentions(&row.content);
+    if let Some(i) = clusters
+        .iter()
+        .rposition(|c| open(c) && mentions.iter().any(|m| c.has_participant(m)))
+    {
+        return Some(i);
+    }
+    if let Some(i) = clusters.iter().rposition(|c| open(c) && c.has_participant(&row.sender)) {
+        return Some(i);
+    }
+    clusters
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, c)| c.last_at())
+        .filter(|(_, c)| open(c))
+        .map(|(i, _)| i)
+}
+
+/// GET /api/v1/rooms/<room_id>/conversations?since=&after=&gap_secs=300&limit=500 — group recent
+/// messages into conversations using reply links, @mentions, and silences longer than `gap_secs`.
+#[get("/api/v1/rooms/<room_id>/conversations?<since>&<after>&<gap_secs>&<limit>")]
+pub fn room_conversations(
+    db: ScopedDb<'_>,
+    room_id: &str,
+    since: Option<&str>,
+    after: Option<i64>,
+    gap_secs: Option<i64>,
+    limit: Option<i64>,
+) -> Result<Json<ConversationsResponse>, (Status, Json<serde_json::Value>)> {
+    let gap_secs = gap_secs.unwrap_or(DEFAULT_GAP_SECS);
+    if !(10..=86_400).contains(&gap_secs) {
+        return Err(err(Status::BadRequest, "gap_secs must be between 10 and 86400"));
+    }
+    let limit = limit.unwrap_or(500).clamp(1, MAX_MESSAGES);
+
+    let conn = db.conn();
+    let exists: bool = conn
+        .query_row("SELECT COUNT(*) FROM rooms WHERE id = ?1", params![room_id], |r| r.get::<_, i64>(0))
+        .map(|c| c > 0)
+        .unwrap_or(false);
+    if !exists {
+        return Err(err(Status::NotFound, "Room not found"));
+    }
+
+    // Without a cursor, cluster the most recent `limit` messages
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, seq, sender, content, reply_to, created_at FROM (
+                 SELECT * FROM messages
+                 WHERE room_id = ?1 AND kind != 'system'
+                   AND (?2 IS NULL OR created_at > ?2) AND (?3 IS NULL OR seq > ?3)
+                 ORDER BY CASE WHEN ?2 IS NULL AND ?3 IS NULL THEN -seq ELSE seq END
+                 LIMIT ?4
+             ) ORDER BY seq ASC",
+        )
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+    let rows: Vec<Row> = stmt
+        .query_map(params![room_id, since, after, limit], |r| {
+            Ok((
+                r.get::<_, String>(0)?,
+                r.get::<_, i64>(1)?,
+                r.get::<_, String>(2)?,
+                r.get::<_, String>(3)?,
+                r.get::<_, Option<String>>(4)?,
+                r.get::<_, String>(5)?,
+            ))
+        })
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?
+        .filter_map(|r| r.ok())
+        .filter_map(|(id, seq, sender, content, reply_to, created_at)| {
+            let at = chrono::DateTime::parse_from_rfc3339(&created_at).ok()?;
+            Some(Row { id, seq, sender, content, reply_to, created_at, at })
+        })
+        .collect();
+
+    let gap = chrono::Duration::seconds(gap_secs);
+    let mut clusters: Vec<Cluster> = Vec::new();
+    for row in rows {
+        match assign(&clusters, &row, gap) {
+            Some(i) => clusters[i].push(row),
+            None => {
+                let mut cluster = Cluster { rows: Vec::new(), participants: Vec::new() };
+                cluster.push(row);
+                clusters.push(cluster);
+            }
+        }
+    }
+
+    let conversations: Vec<Conversation> = clusters
+        .into_iter()
+        .map(|c| {
+            let first = &c.rows[0];
+            let last = c.rows.last().unwrap_or(first);
+            let preview = match first.content.char_indices().nth(140) {
+                Some((i, _)) => format!("{}…", &first.content[..i]),
+                None => first.content.clone(),
+            };
+            Conversation {
+                first_seq: first.seq,
+                last_seq: last.seq,
+                started_at: first.created_at.clone(),
+                ended_at: last.created_at.clone(),
+                message_count: c.rows.len(),
+                preview,
+                message_ids: c.rows.iter().map(|r| r.id.clone()).collect(),
+                participants: c.participants,
+            }
+        })
+        .collect();
+
+    Ok(Json(ConversationsResponse {
+        room_id: room_id.to_string(),
+        gap_secs,
+        count: conversations.len(),
+        conversations,
+    }))
+}
@@ -0,0 +1,133 @@
+use crate::membership::MemberAccess;
+use crate::models::{CostGroup, CostReport, CostTotals};
+use crate::namespaces::ScopedDb;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::get;
+use rusqlite::params;
+
+const GROUP_BY: [&str; 3] = ["sender", "room", "day"];
+
+fn err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+/// An RFC 3339 timestamp or a bare `YYYY-MM-DD` (midnight UTC), normalized to the UTC
+/// RFC 3339 form `created_at` is stored in so it compares as a string.
+fn parse_bound(value: Option<&str>, name: &str) -> Result<Option<String>, (Status, Json<serde_json::Value>)> {
+    let Some(value) = value.map(str::trim).filter(|v| !v.is_empty()) else {
+        return Ok(None);
+    };
+    if let Ok(at) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Ok(Some(at.with_timezone(&chrono::Utc).to_rfc3339()));
+    }
+    chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|d| Some(d.and_utc().to_rfc3339()))
+        .ok_or_else(|| err(Status::BadRequest, &format!("{name} must be an RFC 3339 timestamp or YYYY-MM-DD")))
+}
+
+/// `metadata.usage.<field>` when it's a number, else 0 — a malformed usage block shouldn't
+/// poison the totals.
+fn usage_field(field: &str) -> String {
+    format!(
+        "CASE WHEN json_type(m.metadata, '$.usage.{field}') IN ('integer', 'real') \
+         THEN json_extract(m.metadata, '$.usage.{field}') ELSE 0 END"
+    )
+}
+
+/// GET /api/v1/stats/costs?group_by=sender|room|day — token counts and spend that agents
+/// report in `metadata.usage = {prompt_tokens, completion_tokens, cost_usd}`, summed per
+/// group. Optional `since`/`until` (until is exclusive), `room_id` and `sender` filters. Private
+/// rooms only count with a member token for them.
+#[get("/api/v1/stats/costs?<group_by>&<since>&<until>&<room_id>&<sender>")]
+pub fn cost_stats(
+    db: ScopedDb<'_>,
+    group_by: Option<&str>,
+    since: Option<&str>,
+    until: Option<&str>,
+    room_id: Option<&str>,
+    sender: Option<&str>,
+    access: MemberAccess,
+) -> Result<Json<CostReport>, (Status, Json<serde_json::Value>)> {
+    let group_by = group_by.map(str::trim).filter(|g| !g.is_empty()).unwrap_or("sender");
+    if !GROUP_BY.contains(&group_by) {
+        return Err(err(Status::BadRequest, "group_by must be one of: sender, room, day"));
+    }
+    let since = parse_bound(since, "since")?;
+    let until = parse_bound(until, "until")?;
+    let room_id = room_id.map(str::trim).filter(|r| !r.is_empty());
+
+    let conn = db.conn();
+    let sender = sender
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| crate::db::resolve_sender(&conn, s));
+
+    // Aliases report under their profile, like everywhere else senders are aggregated
+    let key = match group_by {
+        "room" => "m.room_id",
+        "day" => "substr(m.created_at, 1, 10)",
+        _ => "COALESCE((SELECT sa.sender FROM sender_aliases sa WHERE sa.alias_key = LOWER(m.sender)), m.sender)",
+    };
+    let order = if group_by == "day" { "key ASC" } else { "cost_usd DESC, total_tokens DESC, key ASC" };
+    let (prompt, completion, cost) = (
+        usage_field("prompt_tokens"),
+        usage_field("completion_tokens"),
+        usage_field("cost_usd"),
+    );
+    let sql = format!(
+        "SELECT {key} AS key, MAX(r.name), COUNT(*),
+                CAST(TOTAL({prompt}) AS INTEGER), CAST(TOTAL({completion}) AS INTEGER),
+                CAST(TOTAL({prompt}) + TOTAL({completion}) AS INTEGER) AS total_tokens,
+                TOTAL({cost}) AS cost_usd
+         FROM messages m LEFT JOIN rooms r ON r.id = m.room_id
+         WHERE json_type(m.metadata, '$.usage') = 'object'
+           AND (?1 IS NULL OR m.created_at >= ?1)
+           AND (?2 IS NULL OR m.created_at < ?2)
+           AND (?3 IS NULL OR m.room_id = ?3)
+           AND (?4 IS NULL OR {identity})
+           AND {visible}
+         GROUP BY key
+         ORDER BY {order}",
+        identity = crate::db::sender_identity_sql("m.sender", 4),
+        visible = access.visible_sql("m.room_id"),
+    );
+    let groups: Vec<CostGroup> = conn
+        .prepare(&sql)
+        .and_then(|mut s| {
+            s.query_map(params![&since, &until, room_id, &sender], |r| {
+                Ok(CostGroup {
+                    key: r.get(0)?,
+                    room_name: if group_by == "room" { r.get(1)? } else { None },
+                    totals: CostTotals {
+                        messages: r.get(2)?,
+                        prompt_tokens: r.get(3)?,
+                        completion_tokens: r.get(4)?,
+                        total_tokens: r.get(5)?,
+                        cost_usd: r.get(6)?,
+                    },
+                })
+            })
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+
+    let mut totals = CostTotals::default();
+    for g in &groups {
+        totals.messages += g.totals.messages;
+        totals.prompt_tokens += g.totals.prompt_tokens;
+        totals.completion_tokens += g.totals.completion_tokens;
+        totals.total_tokens += g.totals.total_tokens;
+        totals.cost_usd += g.totals.cost_usd;
+    }
+
+    Ok(Json(CostReport {
+        group_by: group_by.to_string(),
+        since,
+        until,
+        totals,
+        groups,
+    }))
+}
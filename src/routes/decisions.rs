@@ -0,0 +1,200 @@
+use crate::namespaces::ScopedDb;
+use crate::models::{Decision, DecisionApproval, DecisionLog, Message};
+use rocket::http::{ContentType, Status};
+use rocket::serde::json::Json;
+use rocket::{get, Either};
+use rusqlite::{params, Connection};
+
+use super::threads::fetch_message;
+
+/// The label that marks a message as a decision.
+pub const DECISION_LABEL: &str = "decision";
+
+/// Reactions that count as approving a decision (stored normalized, see `crate::emoji`).
+const APPROVAL_SHORTCODES: &[&str] = &[":+1:", ":white_check_mark:", ":heavy_check_mark:", ":100:"];
+
+fn err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+/// Walk `reply_to` up to the thread root; None when the message isn't a reply.
+fn thread_root(conn: &Connection, message: &Message) -> Option<Message> {
+    let mut root: Option<Message> = None;
+    let mut visited = std::collections::HashSet::new();
+    visited.insert(message.id.clone());
+    let mut parent_id = message.reply_to.clone();
+    while let Some(id) = parent_id {
+        if !visited.insert(id.clone()) {
+            break;
+        }
+        match fetch_message(conn, &id, &message.room_id) {
+            Ok(parent) => {
+                parent_id = parent.reply_to.clone();
+                root = Some(parent);
+            }
+            Err(_) => break,
+        }
+    }
+    root
+}
+
+/// Replies anywhere below `root_id`.
+fn count_replies(conn: &Connection, root_id: &str, room_id: &str) -> i64 {
+    conn.query_row(
+        "WITH RECURSIVE thread(id) AS (
+             SELECT ?1
+             UNION
+             SELECT m.id FROM messages m JOIN thread t ON m.reply_to = t.id WHERE m.room_id = ?2
+         )
+         SELECT COUNT(*) - 1 FROM thread",
+        params![root_id, room_id],
+        |r| r.get(0),
+    )
+    .unwrap_or(0)
+}
+
+fn approvals(conn: &Connection, message_id: &str) -> Vec<DecisionApproval> {
+    let emoji: Vec<String> = APPROVAL_SHORTCODES.iter().map(|c| crate::emoji::normalize(c)).collect();
+    conn.prepare(
+        "SELECT sender, emoji, created_at FROM message_reactions
+         WHERE message_id = ?1 AND emoji IN (?2, ?3, ?4, ?5)
+         ORDER BY created_at",
+    )
+    .and_then(|mut s| {
+        s.query_map(params![message_id, &emoji[0], &emoji[1], &emoji[2], &emoji[3]], |r| {
+            Ok(DecisionApproval {
+                sender: r.get(0)?,
+                emoji: r.get(1)?,
+                created_at: r.get(2)?,
+            })
+        })
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+    })
+    .unwrap_or_default()
+}
+
+/// GET /api/v1/rooms/<room_id>/decisions?after_date=&before_date=&format=json|markdown — the
+/// room's decision log: every message labeled `decision`, oldest first, with its thread and
+/// who approved it. `format=markdown` returns a document ready to paste into notes.
+#[get("/api/v1/rooms/<room_id>/decisions?<after_date>&<before_date>&<format>")]
+pub fn room_decisions(
+    db: ScopedDb<'_>,
+    room_id: &str,
+    after_date: Option<&str>,
+    before_date: Option<&str>,
+    format: Option<&str>,
+) -> Result<Either<Json<DecisionLog>, (ContentType, String)>, (Status, Json<serde_json::Value>)> {
+    let format = format.unwrap_or("json");
+    if !matches!(format, "json" | "markdown") {
+        return Err(err(Status::BadRequest, "Invalid format. Supported: json, markdown"));
+    }
+
+    let conn = db.conn();
+    let room_name: String = conn
+        .query_row("SELECT name FROM rooms WHERE id = ?1", params![room_id], |r| r.get(0))
+        .map_err(|_| err(Status::NotFound, "Room not found"))?;
+
+    let labeled: Vec<(String, String, String)> = conn
+        .prepare(
+            "SELECT ml.message_id, ml.added_by, ml.created_at
+             FROM message_labels ml JOIN messages m ON m.id = ml.message_id
+             WHERE m.room_id = ?1 AND ml.label = ?2
+               AND (?3 IS NULL OR m.created_at > ?3)
+               AND (?4 IS NULL OR m.created_at < ?4)
+             ORDER BY m.seq",
+        )
+        .and_then(|mut s| {
+            s.query_map(params![room_id, DECISION_LABEL, after_date, before_date], |r| {
+                Ok((r.get(0)?, r.get(1)?, r.get(2)?))
+            })
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+
+    let mut decisions = Vec::with_capacity(labeled.len());
+    for (message_id, labeled_by, labeled_at) in labeled {
+        let Ok(message) = fetch_message(&conn, &message_id, room_id) else {
+            continue;
+        };
+        let thread_root = thread_root(&conn, &message);
+        let root_id = thread_root.as_ref().map(|r| r.id.as_str()).unwrap_or(&message.id);
+        let thread_replies = count_replies(&conn, root_id, room_id);
+        let approvals = approvals(&conn, &message.id);
+        decisions.push(Decision {
+            message,
+            labeled_by,
+            labeled_at,
+            thread_root,
+            thread_replies,
+            approvals,
+        });
+    }
+
+    let log = DecisionLog {
+        room_id: room_id.to_string(),
+        room_name,
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        count: decisions.len(),
+        decisions,
+        after_date: after_date.map(String::from),
+        before_date: before_date.map(String::from),
+    };
+    if format == "markdown" {
+        return Ok(Either::Right((ContentType::new("text", "markdown"), render_markdown(&log))));
+    }
+    Ok(Either::Left(Json(log)))
+}
+
+/// Shorten thread context to one readable line.
+fn excerpt(content: &str) -> String {
+    let line = content.lines().next().unwrap_or("").trim();
+    if line.chars().count() > 120 {
+        format!("{}…", line.chars().take(120).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
+fn render_markdown(log: &DecisionLog) -> String {
+    let mut md = String::new();
+    md.push_str(&format!("# Decisions — #{}\n\n", log.room_name));
+    md.push_str(&format!("> {} decisions, generated {}\n", log.count, log.generated_at));
+    if log.after_date.is_some() || log.before_date.is_some() {
+        md.push_str(&format!(
+            "> Period: {} → {}\n",
+            log.after_date.as_deref().unwrap_or("start"),
+            log.before_date.as_deref().unwrap_or("now")
+        ));
+    }
+    md.push_str("\n---\n\n");
+
+    let mut current_date = "";
+    for d in &log.decisions {
+        let date = d.message.created_at.get(..10).unwrap_or(&d.message.created_at);
+        if date != current_date {
+            md.push_str(&format!("## {date}\n\n"));
+            current_date = date;
+        }
+        let time = d.message.created_at.get(11..19).unwrap_or(&d.message.created_at);
+        md.push_str(&format!("### [{time}] {}\n\n{}\n\n", d.message.sender, d.message.content));
+        if let Some(ref root) = d.thread_root {
+            md.push_str(&format!(
+                "- Thread: \"{}\" — {} ({} replies)\n",
+                excerpt(&root.content),
+                root.sender,
+                d.thread_replies
+            ));
+        } else if d.thread_replies > 0 {
+            md.push_str(&format!("- Thread: {} replies\n", d.thread_replies));
+        }
+        md.push_str(&format!("- Labeled by {}\n", d.labeled_by));
+        if d.approvals.is_empty() {
+            md.push_str("- Approved by: nobody yet\n");
+        } else {
+            let who: Vec<String> = d.approvals.iter().map(|a| format!("{} {}", a.sender, a.emoji)).collect();
+            md.push_str(&format!("- Approved by: {}\n", who.join(", ")));
+        }
+        md.push('\n');
+    }
+    md
+}
@@ -0,0 +1,44 @@
+use crate::namespaces::ScopedDb;
+use crate::models::SeedSummary;
+use crate::seed::{self, SeedOptions, MAX_SEED_MESSAGES, MAX_SEED_ROOMS};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::post;
+
+/// Generate fixture data (rooms, profiles, threads, reactions, pins, files).
+/// Only mounted when `DEV_ROUTES_ENABLED=true`.
+#[post("/api/v1/dev/seed?<rooms>&<messages>&<seed>")]
+pub fn dev_seed(
+    db: ScopedDb<'_>,
+    rooms: Option<usize>,
+    messages: Option<usize>,
+    seed: Option<u64>,
+) -> Result<Json<SeedSummary>, (Status, Json<serde_json::Value>)> {
+    let defaults = SeedOptions::default();
+    let opts = SeedOptions {
+        rooms: rooms.unwrap_or(defaults.rooms),
+        messages: messages.unwrap_or(defaults.messages),
+        seed: seed.unwrap_or(defaults.seed),
+    };
+    if opts.rooms == 0 || opts.rooms > MAX_SEED_ROOMS {
+        return Err((
+            Status::BadRequest,
+            Json(serde_json::json!({"error": format!("rooms must be between 1 and {MAX_SEED_ROOMS}")})),
+        ));
+    }
+    if opts.messages > MAX_SEED_MESSAGES {
+        return Err((
+            Status::BadRequest,
+            Json(serde_json::json!({"error": format!("messages must be at most {MAX_SEED_MESSAGES}")})),
+        ));
+    }
+
+    let mut conn = db.conn();
+    seed::seed(&mut conn, &opts).map(Json).map_err(|e| {
+        eprintln!("⚠️ Dev seed failed: {e}");
+        (
+            Status::InternalServerError,
+            Json(serde_json::json!({"error": "Internal server error"})),
+        )
+    })
+}
@@ -1,10 +1,13 @@
-use rocket::get;
 use rocket::serde::json::Json;
+use rocket::{get, State};
+
+use crate::capabilities::Capabilities;
 
 /// Service discovery endpoint — returns machine-readable service info
 /// for agents to understand capabilities without prior knowledge.
+/// `features` says what this instance has switched on (auth mode, optional subsystems).
 #[get("/api/v1/discover")]
-pub fn discover() -> Json<serde_json::Value> {
+pub fn discover(capabilities: &State<Capabilities>) -> Json<serde_json::Value> {
     let host = hostname::get()
         .map(|h| h.to_string_lossy().into_owned())
         .unwrap_or_else(|_| "unknown".to_string());
@@ -31,6 +34,12 @@ pub fn discover() -> Json<serde_json::Value> {
         "port": port,
         "protocol": "http",
         "api_base": "/api/v1",
+        "api_versions": {
+            "supported": crate::versioning::SUPPORTED_VERSIONS,
+            "latest": "v2",
+            "frozen": ["v1"],
+            "deprecations": crate::versioning::DEPRECATIONS,
+        },
         "mdns": {
             "enabled": mdns_enabled,
             "service_type": "_agentchat._tcp.local.",
@@ -56,6 +65,7 @@ pub fn discover() -> Json<serde_json::Value> {
             "typing_indicators",
             "markdown_rendering",
         ],
+        "features": capabilities.inner(),
         "endpoints": {
             "health": "/api/v1/health",
             "rooms": "/api/v1/rooms",
@@ -66,9 +76,12 @@ pub fn discover() -> Json<serde_json::Value> {
             "unread": "/api/v1/unread",
             "mentions": "/api/v1/mentions",
             "dm": "/api/v1/dm",
+            "quota": "/api/v1/quota",
+            "subscriptions": "/api/v1/subscriptions/rooms",
             "discover": "/api/v1/discover",
             "openapi": "/api/v1/openapi.json",
             "llms_txt": "/api/v1/llms.txt",
+            "skills_json": "/api/v1/skills.json",
         },
         "auth": {
             "model": "trust-based",
@@ -79,6 +92,7 @@ pub fn discover() -> Json<serde_json::Value> {
             "rooms_per_hour": 10,
             "files_per_min": 10,
             "dms_per_min": 60,
+            "reactions_per_min_per_sender": 30,
         }
     }))
 }
@@ -1,7 +1,9 @@
-use crate::db::{generate_admin_key, upsert_fts, Db};
-use crate::events::{ChatEvent, EventBus};
+use crate::db::{generate_admin_key, index_mentions, upsert_fts};
+use crate::namespaces::ScopedDb;
+use crate::events::{ChatEvent, Events};
 use crate::models::*;
 use crate::rate_limit::{RateLimitConfig, RateLimited, RateLimiter};
+use crate::senders::{SenderPolicy, ServerToken};
 use rocket::http::Status;
 use rocket::serde::json::Json;
 use rocket::{get, post, State};
@@ -36,13 +38,43 @@ fn extract_other_participant(room_name: &str, sender: &str) -> String {
     }
 }
 
+/// Find the DM room between two senders, creating it if needed. Returns (room_id, created).
+pub(crate) fn get_or_create_dm_room(
+    conn: &rusqlite::Connection,
+    sender: &str,
+    recipient: &str,
+) -> rusqlite::Result<(String, bool)> {
+    let room_name = dm_room_name(sender, recipient);
+    let existing_room: Option<String> = conn
+        .query_row(
+            "SELECT id FROM rooms WHERE name = ?1 AND room_type = 'dm'",
+            params![&room_name],
+            |row| row.get(0),
+        )
+        .ok();
+    if let Some(id) = existing_room {
+        return Ok((id, false));
+    }
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let now = chrono::Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO rooms (id, name, description, created_by, created_at, updated_at, admin_key, room_type) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 'dm')",
+        params![&id, &room_name, format!("DM between {} and {}", sender, recipient), sender, &now, &now, generate_admin_key()],
+    )?;
+    Ok((id, true))
+}
+
 /// Send a direct message. Auto-creates the DM room if it doesn't exist.
 #[post("/api/v1/dm", format = "json", data = "<body>")]
+#[allow(clippy::too_many_arguments)]
 pub fn send_dm(
-    db: &State<Db>,
-    events: &State<EventBus>,
+    db: ScopedDb<'_>,
+    events: Events<'_>,
     rate_limiter: &State<RateLimiter>,
     rate_config: &State<RateLimitConfig>,
+    sender_policy: &State<SenderPolicy>,
+    server_token: ServerToken,
     ip: ClientIp,
     body: Json<SendDm>,
 ) -> Result<RateLimited<DmSendResponse>, (Status, Json<serde_json::Value>)> {
@@ -70,6 +102,7 @@ pub fn send_dm(
             Json(serde_json::json!({"error": "Sender and recipient must be 1-100 characters"})),
         ));
     }
+    sender_policy.check(&sender, &server_token)?;
 
     if sender == recipient {
         return Err((
@@ -85,34 +118,12 @@ pub fn send_dm(
         ));
     }
 
-    let room_name = dm_room_name(&sender, &recipient);
     let conn = db.conn();
+    crate::quotas::check(&conn, rate_config, &sender, 1, 0)?;
 
-    // Check if DM room already exists
-    let existing_room: Option<String> = conn
-        .query_row(
-            "SELECT id FROM rooms WHERE name = ?1 AND room_type = 'dm'",
-            params![&room_name],
-            |row| row.get(0),
-        )
-        .ok();
-
-    let (room_id, created) = match existing_room {
-        Some(id) => (id, false),
-        None => {
-            // Create the DM room
-            let id = uuid::Uuid::new_v4().to_string();
-            let now = chrono::Utc::now().to_rfc3339();
-            let admin_key = generate_admin_key();
-            conn.execute(
-                "INSERT INTO rooms (id, name, description, created_by, created_at, updated_at, admin_key, room_type) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 'dm')",
-                params![&id, &room_name, format!("DM between {} and {}", sender, recipient), &sender, &now, &now, &admin_key],
-            ).map_err(|_e| {
-                (Status::InternalServerError, Json(serde_json::json!({"error": "Internal server error"})))
-            })?;
-            (id, true)
-        }
-    };
+    let (room_id, created) = get_or_create_dm_room(&conn, &sender, &recipient).map_err(|_e| {
+        (Status::InternalServerError, Json(serde_json::json!({"error": "Internal server error"})))
+    })?;
 
     // Send the message in the DM room
     let msg_id = uuid::Uuid::new_v4().to_string();
@@ -135,9 +146,11 @@ pub fn send_dm(
     ).map_err(|_e| {
         (Status::InternalServerError, Json(serde_json::json!({"error": "Internal server error"})))
     })?;
+    crate::quotas::record(&conn, &sender, 1, 0);
 
-    // Update FTS index
+    // Update FTS and mention indexes
     upsert_fts(&conn, &msg_id);
+    index_mentions(&conn, &msg_id);
 
     let message = Message {
         id: msg_id,
@@ -153,6 +166,8 @@ pub fn send_dm(
         pinned_at: None,
         pinned_by: None,
         edit_count: 0,
+        kind: "message".to_string(),
+        local_time: None,
     };
 
     // Publish SSE event
@@ -171,7 +186,7 @@ pub fn send_dm(
 /// List DM conversations for a sender
 #[get("/api/v1/dm?<sender>")]
 pub fn list_dm_conversations(
-    db: &State<Db>,
+    db: ScopedDb<'_>,
     sender: &str,
 ) -> Result<Json<DmConversationsResponse>, (Status, Json<serde_json::Value>)> {
     let sender = sender.trim().to_string();
@@ -270,7 +285,7 @@ pub fn list_dm_conversations(
 /// Get a specific DM conversation by room_id (returns room info + validates it's a DM)
 #[get("/api/v1/dm/<room_id>")]
 pub fn get_dm_conversation(
-    db: &State<Db>,
+    db: ScopedDb<'_>,
     room_id: &str,
 ) -> Result<Json<serde_json::Value>, (Status, Json<serde_json::Value>)> {
     let conn = db.conn();
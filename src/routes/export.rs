@@ -1,11 +1,16 @@
 use rocket::http::{ContentType, Header, Status};
 use rocket::response::Responder;
 use rocket::serde::json::Json;
-use rocket::{get, FromForm, Request, Response};
+use rocket::{get, post, FromForm, Request, Response};
+use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::io::Cursor;
 
-use crate::db::Db;
+use crate::events::{ChatEvent, Events};
+use crate::namespaces::ScopedDb;
+
+use super::ndjson::{stream_rows, AcceptNdjson, NdjsonStream};
 
 /// Query parameters for export
 #[derive(Debug, Deserialize, FromForm)]
@@ -22,11 +27,17 @@ pub struct ExportQuery {
     pub limit: Option<i64>,
     /// Include metadata JSON in export (default: false)
     pub include_metadata: Option<bool>,
+    /// JSON only: embed file contents (base64) in the file manifest (default: false)
+    pub include_files: Option<bool>,
 }
 
-/// A single exported message
-#[derive(Debug, Serialize)]
+/// A single exported message. Also the message shape accepted by `POST /api/v1/rooms/import`,
+/// where `seq` is ignored and a missing `id` gets a fresh one.
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ExportedMessage {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
     pub seq: i64,
     pub sender: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -37,21 +48,88 @@ pub struct ExportedMessage {
     pub edited_at: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_to: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub pinned_at: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pinned_by: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub metadata: Option<serde_json::Value>,
+    #[serde(default = "default_kind")]
+    pub kind: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reactions: Vec<ExportedReaction>,
+    /// Earlier versions of the content, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub edits: Vec<ExportedEdit>,
+}
+
+fn default_kind() -> String {
+    "message".to_string()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedReaction {
+    pub sender: String,
+    pub emoji: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedEdit {
+    pub previous_content: String,
+    pub edited_at: String,
+    pub editor: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub patch_format: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub patch: Option<String>,
 }
 
+/// A file in the room's file store. `data` (base64) is only present with `include_files=true`;
+/// on import, files without it are skipped.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedFile {
+    pub id: String,
+    pub sender: String,
+    pub filename: String,
+    pub content_type: String,
+    pub size: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    pub created_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+}
+
+/// A member of a private room. Tokens are never exported; importing issues fresh ones, which
+/// the new room's admin can list.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedMember {
+    pub sender: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub added_by: Option<String>,
+    pub added_at: String,
+}
+
+/// Version of the JSON export schema; bumped when fields are added.
+pub const EXPORT_VERSION: i64 = 3;
+
 /// JSON export response
 #[derive(Debug, Serialize)]
 pub struct JsonExportResponse {
+    pub export_version: i64,
     pub room_id: String,
     pub room_name: String,
+    pub room_description: String,
+    pub room_visibility: String,
     pub exported_at: String,
     pub message_count: usize,
     pub filters: ExportFilters,
     pub messages: Vec<ExportedMessage>,
+    pub files: Vec<ExportedFile>,
+    pub members: Vec<ExportedMember>,
 }
 
 #[derive(Debug, Serialize)]
@@ -71,11 +149,22 @@ pub enum ExportResponse {
     Json(String),
     Markdown(String),
     Csv(String),
+    /// One ExportedMessage per line, streamed (`Accept: application/x-ndjson`)
+    Ndjson(NdjsonStream),
 }
 
 impl<'r> Responder<'r, 'static> for ExportResponse {
-    fn respond_to(self, _: &'r Request<'_>) -> rocket::response::Result<'static> {
+    fn respond_to(self, req: &'r Request<'_>) -> rocket::response::Result<'static> {
         match self {
+            ExportResponse::Ndjson(stream) => {
+                let filename = "chat-export.ndjson";
+                Response::build_from(stream.respond_to(req)?)
+                    .header(Header::new(
+                        "Content-Disposition",
+                        format!("attachment; filename=\"{filename}\""),
+                    ))
+                    .ok()
+            }
             ExportResponse::Json(body) => {
                 let filename = "chat-export.json";
                 Response::build()
@@ -118,16 +207,17 @@ impl<'r> Responder<'r, 'static> for ExportResponse {
 pub fn export_room(
     room_id: &str,
     params: ExportQuery,
-    db: &rocket::State<Db>,
+    db: ScopedDb<'_>,
+    ndjson: AcceptNdjson,
 ) -> Result<ExportResponse, (Status, Json<serde_json::Value>)> {
     let conn = db.conn();
 
     // Verify room exists and get name
-    let room_name: String = conn
+    let (room_name, room_description, room_visibility): (String, String, String) = conn
         .query_row(
-            "SELECT name FROM rooms WHERE id = ?1",
+            "SELECT name, COALESCE(description, ''), visibility FROM rooms WHERE id = ?1",
             rusqlite::params![room_id],
-            |row| row.get(0),
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
         )
         .map_err(|_| {
             (
@@ -144,7 +234,12 @@ pub fn export_room(
         ));
     }
 
-    let limit = params.limit.map(|l| l.clamp(1, 10_000)).unwrap_or(10_000);
+    // Streamed exports read in constant memory, so only buffered formats are capped
+    let limit = match (ndjson.0, params.limit) {
+        (true, Some(l)) => l.max(1),
+        (true, None) => -1, // SQLite: no limit
+        (false, l) => l.map(|l| l.clamp(1, 10_000)).unwrap_or(10_000),
+    };
     let include_metadata = params.include_metadata.unwrap_or(false);
 
     // Build query with filters
@@ -175,7 +270,12 @@ pub fn export_room(
     let where_clause = conditions.join(" AND ");
     let sql = format!(
         "SELECT m.seq, m.sender, m.sender_type, m.content, m.created_at, \
-         m.edited_at, m.reply_to, m.pinned_at, m.pinned_by, m.metadata \
+         m.edited_at, m.reply_to, m.pinned_at, m.pinned_by, m.metadata, m.kind, m.id, \
+         (SELECT json_group_array(json_object('sender', r.sender, 'emoji', r.emoji, 'created_at', r.created_at)) \
+            FROM (SELECT * FROM message_reactions WHERE message_id = m.id ORDER BY created_at, id) r), \
+         (SELECT json_group_array(json_object('previous_content', e.previous_content, 'edited_at', e.edited_at, \
+                 'editor', e.editor, 'patch_format', e.patch_format, 'patch', e.patch)) \
+            FROM (SELECT * FROM message_edits WHERE message_id = m.id ORDER BY edited_at, id) e) \
          FROM messages m WHERE {where_clause} ORDER BY m.seq ASC LIMIT ?{limit_idx}",
         limit_idx = param_values.len() + 1
     );
@@ -186,6 +286,23 @@ pub fn export_room(
         .chain(std::iter::once(&limit as &dyn rusqlite::types::ToSql))
         .collect();
 
+    if ndjson.0 {
+        let reader = db.open_reader().map_err(|_| {
+            (
+                Status::InternalServerError,
+                Json(serde_json::json!({"error": "Internal server error"})),
+            )
+        })?;
+        let mut stream_params = vec![rusqlite::types::Value::Text(room_id.to_string())];
+        for value in [&params.after, &params.before, &params.sender].into_iter().flatten() {
+            stream_params.push(rusqlite::types::Value::Text(value.clone()));
+        }
+        stream_params.push(rusqlite::types::Value::Integer(limit));
+        return Ok(ExportResponse::Ndjson(stream_rows(reader, sql, stream_params, move |row| {
+            exported_message_from_row(row, include_metadata)
+        })));
+    }
+
     let messages: Vec<ExportedMessage> = {
         let mut stmt = conn.prepare(&sql).map_err(|_| {
             (
@@ -195,27 +312,7 @@ pub fn export_room(
         })?;
 
         let rows = stmt
-            .query_map(params_with_limit.as_slice(), |row| {
-                let metadata_str: String = row.get(9)?;
-                let metadata_val: serde_json::Value =
-                    serde_json::from_str(&metadata_str).unwrap_or(serde_json::json!({}));
-
-                Ok(ExportedMessage {
-                    seq: row.get(0)?,
-                    sender: row.get(1)?,
-                    sender_type: row.get(2)?,
-                    content: row.get(3)?,
-                    created_at: row.get(4)?,
-                    edited_at: row.get(5)?,
-                    reply_to: row.get(6)?,
-                    pinned_at: row.get(7)?,
-                    metadata: if include_metadata {
-                        Some(metadata_val)
-                    } else {
-                        None
-                    },
-                })
-            })
+            .query_map(params_with_limit.as_slice(), |row| exported_message_from_row(row, include_metadata))
             .map_err(|_| {
                 (
                     Status::InternalServerError,
@@ -238,9 +335,14 @@ pub fn export_room(
             Ok(ExportResponse::Csv(csv))
         }
         _ => {
+            let files = export_files(&conn, room_id, params.include_files.unwrap_or(false));
+            let members = export_members(&conn, room_id);
             let response = JsonExportResponse {
+                export_version: EXPORT_VERSION,
                 room_id: room_id.to_string(),
                 room_name,
+                room_description,
+                room_visibility,
                 exported_at,
                 message_count: messages.len(),
                 filters: ExportFilters {
@@ -250,6 +352,8 @@ pub fn export_room(
                     limit: params.limit,
                 },
                 messages,
+                files,
+                members,
             };
             let json_str = serde_json::to_string_pretty(&response).unwrap_or_default();
             Ok(ExportResponse::Json(json_str))
@@ -257,6 +361,81 @@ pub fn export_room(
     }
 }
 
+pub(super) fn exported_message_from_row(row: &rusqlite::Row<'_>, include_metadata: bool) -> rusqlite::Result<ExportedMessage> {
+    let metadata_str: String = row.get(9)?;
+    let metadata_val: serde_json::Value =
+        serde_json::from_str(&metadata_str).unwrap_or(serde_json::json!({}));
+
+    let reactions: String = row.get(12)?;
+    let edits: String = row.get(13)?;
+
+    Ok(ExportedMessage {
+        id: row.get(11)?,
+        seq: row.get(0)?,
+        sender: row.get(1)?,
+        sender_type: row.get(2)?,
+        content: row.get(3)?,
+        created_at: row.get(4)?,
+        edited_at: row.get(5)?,
+        reply_to: row.get(6)?,
+        pinned_at: row.get(7)?,
+        pinned_by: row.get(8)?,
+        metadata: if include_metadata {
+            Some(metadata_val)
+        } else {
+            None
+        },
+        kind: row.get(10)?,
+        reactions: serde_json::from_str(&reactions).unwrap_or_default(),
+        edits: serde_json::from_str(&edits).unwrap_or_default(),
+    })
+}
+
+/// The room's file manifest, oldest first, with contents when `include_data` is set.
+fn export_files(conn: &Connection, room_id: &str, include_data: bool) -> Vec<ExportedFile> {
+    use base64::Engine;
+
+    conn.prepare(
+        "SELECT f.id, f.sender, f.filename, f.content_type, f.size, f.sha256, f.created_at, f.expires_at,
+                CASE WHEN ?2 THEN COALESCE(b.data, f.data) END
+         FROM files f LEFT JOIN file_blobs b ON b.sha256 = f.sha256
+         WHERE f.room_id = ?1 ORDER BY f.created_at, f.id",
+    )
+    .and_then(|mut stmt| {
+        stmt.query_map(params![room_id, include_data], |row| {
+            let data: Option<Vec<u8>> = row.get(8)?;
+            Ok(ExportedFile {
+                id: row.get(0)?,
+                sender: row.get(1)?,
+                filename: row.get(2)?,
+                content_type: row.get(3)?,
+                size: row.get(4)?,
+                sha256: row.get(5)?,
+                created_at: row.get(6)?,
+                expires_at: row.get(7)?,
+                data: data.map(|d| base64::engine::general_purpose::STANDARD.encode(d)),
+            })
+        })
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+    })
+    .unwrap_or_default()
+}
+
+fn export_members(conn: &Connection, room_id: &str) -> Vec<ExportedMember> {
+    conn.prepare("SELECT sender, added_by, added_at FROM room_members WHERE room_id = ?1 ORDER BY added_at, sender")
+        .and_then(|mut s| {
+            s.query_map(params![room_id], |r| {
+                Ok(ExportedMember {
+                    sender: r.get(0)?,
+                    added_by: r.get(1)?,
+                    added_at: r.get(2)?,
+                })
+            })
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .unwrap_or_default()
+}
+
 fn render_markdown(
     room_name: &str,
     room_id: &str,
@@ -292,6 +471,12 @@ fn render_markdown(
             .get(11..19)
             .unwrap_or(&msg.created_at);
 
+        // System messages read as narration rather than chat lines
+        if msg.kind == "system" {
+            md.push_str(&format!("*[{time}] — {content}*\n\n", content = msg.content));
+            continue;
+        }
+
         let sender_badge = match msg.sender_type.as_deref() {
             Some("agent") => " 🤖",
             Some("human") => " 👤",
@@ -371,3 +556,218 @@ fn csv_escape(s: &str) -> String {
         s.to_string()
     }
 }
+
+/// Body of `POST /api/v1/rooms/import`: a JSON room export. Export-only fields (`room_id`,
+/// `filters`, `message_count`, …) are ignored.
+#[derive(Debug, Deserialize)]
+pub struct RoomImport {
+    pub room_name: String,
+    #[serde(default)]
+    pub room_description: String,
+    /// Exports older than version 3 have no visibility and import as public
+    #[serde(default = "crate::models::default_visibility")]
+    pub room_visibility: String,
+    #[serde(default)]
+    pub messages: Vec<ExportedMessage>,
+    #[serde(default)]
+    pub files: Vec<ExportedFile>,
+    #[serde(default)]
+    pub members: Vec<ExportedMember>,
+}
+
+/// What `POST /api/v1/rooms/import` created.
+#[derive(Debug, Serialize)]
+pub struct RoomImportSummary {
+    pub room_id: String,
+    pub name: String,
+    pub admin_key: String,
+    pub visibility: String,
+    pub messages: usize,
+    pub reactions: usize,
+    pub edits: usize,
+    pub pins: usize,
+    pub files: usize,
+    /// Manifest entries without `data` (exported without `include_files=true`)
+    pub files_skipped: usize,
+    /// Members re-added with fresh tokens (list them with the admin key)
+    pub members: usize,
+}
+
+fn import_err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+/// Keep an exported id unless this database already uses it (e.g. importing a copy of a room
+/// into the instance it came from).
+fn fresh_id(conn: &Connection, table: &str, id: &str) -> String {
+    let taken = id.is_empty()
+        || conn
+            .query_row(&format!("SELECT 1 FROM {table} WHERE id = ?1"), params![id], |_| Ok(()))
+            .is_ok();
+    if taken {
+        uuid::Uuid::new_v4().to_string()
+    } else {
+        id.to_string()
+    }
+}
+
+/// Create a new room from a JSON export (`?format=json&include_metadata=true&include_files=true`
+/// for a lossless copy). Message and file ids are kept when free, so replies, pins, reactions,
+/// edit history, and attachment references survive the round trip. `?name=` overrides the
+/// exported room name. A private room comes back private, with its members under new tokens.
+#[post("/api/v1/rooms/import?<name>&<created_by>", format = "json", data = "<body>")]
+pub fn import_room(
+    name: Option<&str>,
+    created_by: Option<&str>,
+    body: Json<RoomImport>,
+    db: ScopedDb<'_>,
+    events: Events<'_>,
+) -> Result<Json<RoomImportSummary>, (Status, Json<serde_json::Value>)> {
+    use base64::Engine;
+
+    let name = name.unwrap_or(&body.room_name).trim().to_string();
+    if name.is_empty() || name.len() > 100 {
+        return Err(import_err(Status::BadRequest, "Room name must be 1-100 characters"));
+    }
+    let created_by = created_by.map(str::trim).filter(|c| !c.is_empty()).unwrap_or("import");
+    let visibility = super::rooms::normalize_visibility(&body.room_visibility)
+        .map_err(|e| import_err(Status::BadRequest, &e))?;
+    let db_err = |_| import_err(Status::InternalServerError, "Internal server error");
+
+    let conn = db.conn();
+    let tx = conn.unchecked_transaction().map_err(db_err)?;
+    let exists = tx
+        .query_row("SELECT 1 FROM rooms WHERE name = ?1", params![&name], |_| Ok(()))
+        .is_ok();
+    if exists {
+        return Err(import_err(Status::Conflict, &format!("Room '{name}' already exists")));
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let room_id = uuid::Uuid::new_v4().to_string();
+    let admin_key = crate::db::generate_admin_key();
+    let created_at = body.messages.first().map(|m| m.created_at.clone()).unwrap_or_else(|| now.clone());
+    tx.execute(
+        "INSERT INTO rooms (id, name, description, created_by, created_at, updated_at, admin_key, visibility) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![&room_id, &name, &body.room_description, created_by, &created_at, &now, &admin_key, &visibility],
+    )
+    .map_err(db_err)?;
+    tx.execute("DELETE FROM room_name_aliases WHERE name = ?1", params![&name])
+        .map_err(db_err)?;
+
+    let mut summary = RoomImportSummary {
+        room_id: room_id.clone(),
+        name,
+        admin_key,
+        visibility,
+        messages: 0,
+        reactions: 0,
+        edits: 0,
+        pins: 0,
+        files: 0,
+        files_skipped: 0,
+        members: 0,
+    };
+
+    // Files first, so attachment ids in message metadata can be remapped
+    let mut file_ids: HashMap<&str, String> = HashMap::new();
+    for f in &body.files {
+        let Some(data) = f.data.as_deref() else {
+            summary.files_skipped += 1;
+            continue;
+        };
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(data)
+            .map_err(|_| import_err(Status::BadRequest, &format!("File {}: invalid base64 data", f.id)))?;
+        let id = fresh_id(&tx, "files", &f.id);
+        let sha256 = crate::db::store_file_blob(&tx, &bytes).map_err(db_err)?;
+        tx.execute(
+            "INSERT INTO files (id, room_id, sender, filename, content_type, size, data, created_at, sha256, expires_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, x'', ?7, ?8, ?9)",
+            params![&id, &room_id, &f.sender, &f.filename, &f.content_type, bytes.len() as i64, &f.created_at, &sha256, &f.expires_at],
+        )
+        .map_err(db_err)?;
+        file_ids.insert(&f.id, id);
+        summary.files += 1;
+    }
+
+    let mut seq: i64 = tx
+        .query_row("SELECT COALESCE(MAX(seq), 0) FROM messages", [], |r| r.get(0))
+        .map_err(db_err)?;
+    let mut message_ids: HashMap<&str, String> = HashMap::new();
+    for m in &body.messages {
+        let id = fresh_id(&tx, "messages", &m.id);
+        // Replies to messages outside the export become top-level messages
+        let reply_to = m.reply_to.as_deref().and_then(|r| message_ids.get(r)).cloned();
+        let mut metadata = m.metadata.clone().unwrap_or(serde_json::json!({}));
+        if let Some(attachments) = metadata.get_mut("attachments").and_then(|a| a.as_array_mut()) {
+            for a in attachments.iter_mut() {
+                if let Some(new_id) = a.as_str().and_then(|old| file_ids.get(old)) {
+                    *a = serde_json::json!(new_id);
+                }
+            }
+        }
+        seq += 1;
+        tx.execute(
+            "INSERT INTO messages (id, room_id, sender, content, metadata, created_at, edited_at, reply_to, sender_type, seq, pinned_at, pinned_by, kind)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+            params![
+                &id,
+                &room_id,
+                &m.sender,
+                &m.content,
+                metadata.to_string(),
+                &m.created_at,
+                &m.edited_at,
+                &reply_to,
+                &m.sender_type,
+                seq,
+                &m.pinned_at,
+                &m.pinned_by,
+                &m.kind
+            ],
+        )
+        .map_err(db_err)?;
+        crate::db::upsert_fts(&tx, &id);
+        crate::db::index_mentions(&tx, &id);
+        summary.messages += 1;
+        if m.pinned_at.is_some() {
+            summary.pins += 1;
+        }
+
+        for r in &m.reactions {
+            summary.reactions += tx
+                .execute(
+                    "INSERT OR IGNORE INTO message_reactions (id, message_id, sender, emoji, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![uuid::Uuid::new_v4().to_string(), &id, &r.sender, &r.emoji, &r.created_at],
+                )
+                .map_err(db_err)?;
+        }
+        for e in &m.edits {
+            tx.execute(
+                "INSERT INTO message_edits (id, message_id, previous_content, edited_at, editor, patch_format, patch) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![uuid::Uuid::new_v4().to_string(), &id, &e.previous_content, &e.edited_at, &e.editor, &e.patch_format, &e.patch],
+            )
+            .map_err(db_err)?;
+            summary.edits += 1;
+        }
+        message_ids.insert(&m.id, id);
+    }
+    for member in &body.members {
+        let sender = member.sender.trim();
+        if sender.is_empty() || sender.len() > 100 {
+            continue;
+        }
+        summary.members += tx
+            .execute(
+                "INSERT OR IGNORE INTO room_members (room_id, sender, token, added_by, added_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![&room_id, sender, crate::membership::generate_member_token(), &member.added_by, &member.added_at],
+            )
+            .map_err(db_err)?;
+    }
+    tx.commit().map_err(db_err)?;
+
+    if let Ok(room) = super::rooms::fetch_room_with_stats(&conn, &room_id) {
+        events.publish(ChatEvent::RoomCreated(room));
+    }
+    Ok(Json(summary))
+}
@@ -1,7 +1,9 @@
-use crate::db::Db;
-use crate::events::{ChatEvent, EventBus};
+use crate::membership::MemberAccess;
+use crate::namespaces::ScopedDb;
+use crate::events::{ChatEvent, Events};
 use crate::models::*;
 use crate::rate_limit::{RateLimitConfig, RateLimited, RateLimiter};
+use crate::uploads::UploadConfig;
 use rocket::http::Status;
 use rocket::serde::json::Json;
 use rocket::{delete, get, post, State};
@@ -14,10 +16,11 @@ const MAX_FILE_SIZE: usize = 5 * 1024 * 1024;
 
 #[post("/api/v1/rooms/<room_id>/files", format = "json", data = "<body>")]
 pub fn upload_file(
-    db: &State<Db>,
-    events: &State<EventBus>,
+    db: ScopedDb<'_>,
+    events: Events<'_>,
     rate_limiter: &State<RateLimiter>,
     rate_config: &State<RateLimitConfig>,
+    upload_config: &State<UploadConfig>,
     ip: ClientIp,
     room_id: &str,
     body: Json<FileUpload>,
@@ -69,6 +72,15 @@ pub fn upload_file(
             )
         })?;
 
+    if let Some(secs) = body.expires_in
+        && !super::rooms::FILE_TTL_RANGE.contains(&secs)
+    {
+        return Err((
+            Status::BadRequest,
+            Json(serde_json::json!({"error": "expires_in must be between 60 and 31536000 seconds (1 year)"})),
+        ));
+    }
+
     if decoded.len() > MAX_FILE_SIZE {
         return Err((
             Status::BadRequest,
@@ -78,39 +90,56 @@ pub fn upload_file(
         ));
     }
 
-    let conn = db.conn();
+    // Verify room exists; its file TTL applies when the upload doesn't set one
+    let (room_ttl, policy) = {
+        let conn = db.conn();
+        let room_ttl: Option<i64> = conn
+            .query_row(
+                "SELECT file_ttl_secs FROM rooms WHERE id = ?1",
+                params![room_id],
+                |r| r.get(0),
+            )
+            .map_err(|_| {
+                (
+                    Status::NotFound,
+                    Json(serde_json::json!({"error": "Room not found"})),
+                )
+            })?;
+        crate::quotas::check(&conn, rate_config, &sender, 0, decoded.len() as i64)?;
+        (room_ttl, crate::uploads::room_policy(&conn, room_id))
+    };
 
-    // Verify room exists
-    let room_exists: bool = conn
-        .query_row(
-            "SELECT COUNT(*) FROM rooms WHERE id = ?1",
-            params![room_id],
-            |r| r.get::<_, i64>(0),
-        )
-        .map(|c| c > 0)
-        .unwrap_or(false);
+    // Screened without the DB lock held: a ClamAV scan can take a while
+    crate::uploads::screen(upload_config, policy.as_ref(), &filename, &body.content_type, &decoded)
+        .map_err(|r| (r.status, Json(serde_json::json!({"error": r.message}))))?;
 
-    if !room_exists {
-        return Err((
-            Status::NotFound,
-            Json(serde_json::json!({"error": "Room not found"})),
-        ));
-    }
+    let conn = db.conn();
 
     let id = uuid::Uuid::new_v4().to_string();
-    let now = chrono::Utc::now().to_rfc3339();
+    let created = chrono::Utc::now();
+    let now = created.to_rfc3339();
     let size = decoded.len() as i64;
+    let expires_at = body
+        .expires_in
+        .or(room_ttl)
+        .map(|secs| (created + chrono::Duration::seconds(secs)).to_rfc3339());
 
-    conn.execute(
-        "INSERT INTO files (id, room_id, sender, filename, content_type, size, data, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-        params![&id, room_id, &sender, &filename, &body.content_type, size, &decoded, &now],
-    )
-    .map_err(|_e| {
-        (
-            Status::InternalServerError,
-            Json(serde_json::json!({"error": "Internal server error"})),
-        )
-    })?;
+    // Same bytes uploaded again (re-posted logs, screenshots) reuse the stored blob
+    let sha256 = crate::db::store_file_blob(&conn, &decoded)
+        .and_then(|sha256| {
+            conn.execute(
+                "INSERT INTO files (id, room_id, sender, filename, content_type, size, data, created_at, sha256, expires_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, x'', ?7, ?8, ?9)",
+                params![&id, room_id, &sender, &filename, &body.content_type, size, &now, &sha256, &expires_at],
+            )?;
+            Ok(sha256)
+        })
+        .map_err(|_e| {
+            (
+                Status::InternalServerError,
+                Json(serde_json::json!({"error": "Internal server error"})),
+            )
+        })?;
+    crate::quotas::record(&conn, &sender, 0, size);
 
     let file_info = FileInfo {
         id: id.clone(),
@@ -119,8 +148,10 @@ pub fn upload_file(
         filename,
         content_type: body.content_type.clone(),
         size,
+        sha256: Some(sha256),
         url: format!("/api/v1/files/{}", id),
         created_at: now,
+        expires_at,
     };
 
     events.publish(ChatEvent::FileUploaded(file_info.clone()));
@@ -130,13 +161,19 @@ pub fn upload_file(
 
 #[get("/api/v1/files/<file_id>")]
 pub fn download_file(
-    db: &State<Db>,
+    db: ScopedDb<'_>,
     file_id: &str,
+    access: MemberAccess,
 ) -> Result<(rocket::http::ContentType, Vec<u8>), (Status, Json<serde_json::Value>)> {
     let conn = db.conn();
     conn.query_row(
-        "SELECT content_type, data FROM files WHERE id = ?1",
-        params![file_id],
+        &format!(
+            "SELECT f.content_type, COALESCE(b.data, f.data) FROM files f \
+             LEFT JOIN file_blobs b ON b.sha256 = f.sha256 \
+             WHERE f.id = ?1 AND (f.expires_at IS NULL OR f.expires_at > ?2) AND {}",
+            access.visible_sql("f.room_id")
+        ),
+        params![file_id, chrono::Utc::now().to_rfc3339()],
         |row| {
             let ct: String = row.get(0)?;
             let data: Vec<u8> = row.get(1)?;
@@ -158,13 +195,18 @@ pub fn download_file(
 
 #[get("/api/v1/files/<file_id>/info")]
 pub fn file_info(
-    db: &State<Db>,
+    db: ScopedDb<'_>,
     file_id: &str,
+    access: MemberAccess,
 ) -> Result<Json<FileInfo>, (Status, Json<serde_json::Value>)> {
     let conn = db.conn();
     conn.query_row(
-        "SELECT id, room_id, sender, filename, content_type, size, created_at FROM files WHERE id = ?1",
-        params![file_id],
+        &format!(
+            "SELECT id, room_id, sender, filename, content_type, size, created_at, sha256, expires_at FROM files \
+             WHERE id = ?1 AND (expires_at IS NULL OR expires_at > ?2) AND {}",
+            access.visible_sql("room_id")
+        ),
+        params![file_id, chrono::Utc::now().to_rfc3339()],
         |row| {
             let id: String = row.get(0)?;
             Ok(FileInfo {
@@ -174,8 +216,10 @@ pub fn file_info(
                 filename: row.get(3)?,
                 content_type: row.get(4)?,
                 size: row.get(5)?,
+                sha256: row.get(7)?,
                 url: format!("/api/v1/files/{}", id),
                 created_at: row.get(6)?,
+                expires_at: row.get(8)?,
             })
         },
     )
@@ -188,11 +232,12 @@ pub fn file_info(
     })
 }
 
-#[get("/api/v1/rooms/<room_id>/files")]
+#[get("/api/v1/rooms/<room_id>/files?<envelope>")]
 pub fn list_files(
-    db: &State<Db>,
+    db: ScopedDb<'_>,
     room_id: &str,
-) -> Result<Json<Vec<FileInfo>>, (Status, Json<serde_json::Value>)> {
+    envelope: Option<bool>,
+) -> Result<Json<ListOf<FileInfo>>, (Status, Json<serde_json::Value>)> {
     let conn = db.conn();
 
     // Verify room exists
@@ -213,11 +258,14 @@ pub fn list_files(
     }
 
     let mut stmt = conn
-        .prepare("SELECT id, room_id, sender, filename, content_type, size, created_at FROM files WHERE room_id = ?1 ORDER BY created_at DESC")
+        .prepare(
+            "SELECT id, room_id, sender, filename, content_type, size, created_at, sha256, expires_at FROM files \
+             WHERE room_id = ?1 AND (expires_at IS NULL OR expires_at > ?2) ORDER BY created_at DESC",
+        )
         .map_err(|_| (Status::InternalServerError, Json(serde_json::json!({"error": "Internal server error"}))))?;
 
     let files = stmt
-        .query_map(params![room_id], |row| {
+        .query_map(params![room_id, chrono::Utc::now().to_rfc3339()], |row| {
             let id: String = row.get(0)?;
             Ok(FileInfo {
                 id: id.clone(),
@@ -226,21 +274,23 @@ pub fn list_files(
                 filename: row.get(3)?,
                 content_type: row.get(4)?,
                 size: row.get(5)?,
+                sha256: row.get(7)?,
                 url: format!("/api/v1/files/{}", id),
                 created_at: row.get(6)?,
+                expires_at: row.get(8)?,
             })
         })
         .map_err(|_| (Status::InternalServerError, Json(serde_json::json!({"error": "Internal server error"}))))?
         .filter_map(|r| r.ok())
         .collect();
 
-    Ok(Json(files))
+    Ok(Json(ListOf::complete(files, envelope)))
 }
 
 #[delete("/api/v1/rooms/<room_id>/files/<file_id>?<sender>")]
 pub fn delete_file(
-    db: &State<Db>,
-    events: &State<EventBus>,
+    db: ScopedDb<'_>,
+    events: Events<'_>,
     room_id: &str,
     file_id: &str,
     sender: Option<&str>,
@@ -0,0 +1,242 @@
+use crate::namespaces::ScopedDb;
+use crate::events::{ChatEvent, Events};
+use crate::models::{CreateFlag, FlagQueueResponse, MessageFlag, ResolveFlag};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{get, post};
+use rusqlite::{params, Connection};
+
+use super::AdminKey;
+
+fn err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+fn check_admin(conn: &Connection, room_id: &str, admin: &AdminKey) -> Result<(), (Status, Json<serde_json::Value>)> {
+    let key: Option<String> = conn
+        .query_row("SELECT admin_key FROM rooms WHERE id = ?1", params![room_id], |r| r.get(0))
+        .map_err(|_| err(Status::NotFound, "Room not found"))?;
+    if key.as_deref() != Some(admin.0.as_str()) {
+        return Err(err(Status::Forbidden, "Invalid admin key for this room"));
+    }
+    Ok(())
+}
+
+const FLAG_COLUMNS: &str = "id, room_id, message_id, message_sender, message_content, reporter, reason, status, \
+                            created_at, resolved_at, resolved_by";
+
+fn flag_from_row(r: &rusqlite::Row) -> rusqlite::Result<MessageFlag> {
+    Ok(MessageFlag {
+        id: r.get(0)?,
+        room_id: r.get(1)?,
+        message_id: r.get(2)?,
+        message_sender: r.get(3)?,
+        message_content: r.get(4)?,
+        reporter: r.get(5)?,
+        reason: r.get(6)?,
+        status: r.get(7)?,
+        created_at: r.get(8)?,
+        resolved_at: r.get(9)?,
+        resolved_by: r.get(10)?,
+    })
+}
+
+fn load_flag(conn: &Connection, room_id: &str, flag_id: &str) -> Option<MessageFlag> {
+    conn.query_row(
+        &format!("SELECT {FLAG_COLUMNS} FROM message_flags WHERE id = ?1 AND room_id = ?2"),
+        params![flag_id, room_id],
+        flag_from_row,
+    )
+    .ok()
+}
+
+/// POST /api/v1/rooms/<room_id>/messages/<message_id>/flags — report a message to the room's
+/// moderators. A reporter can hold one open flag per message.
+#[post("/api/v1/rooms/<room_id>/messages/<message_id>/flags", format = "json", data = "<body>")]
+pub fn flag_message(
+    db: ScopedDb<'_>,
+    events: Events<'_>,
+    room_id: &str,
+    message_id: &str,
+    body: Json<CreateFlag>,
+) -> Result<Json<MessageFlag>, (Status, Json<serde_json::Value>)> {
+    let reporter = body.reporter.trim();
+    if reporter.is_empty() || reporter.len() > 100 {
+        return Err(err(Status::BadRequest, "Reporter must be 1-100 characters"));
+    }
+    let reason = body.reason.trim();
+    if reason.is_empty() || reason.len() > 1000 {
+        return Err(err(Status::BadRequest, "Reason must be 1-1000 characters"));
+    }
+
+    let conn = db.conn();
+    let (sender, content): (String, String) = conn
+        .query_row(
+            "SELECT sender, content FROM messages WHERE id = ?1 AND room_id = ?2",
+            params![message_id, room_id],
+            |r| Ok((r.get(0)?, r.get(1)?)),
+        )
+        .map_err(|_| err(Status::NotFound, "Message not found"))?;
+    let already_open: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM message_flags WHERE message_id = ?1 AND reporter = ?2 AND status = 'open'",
+            params![message_id, reporter],
+            |r| r.get::<_, i64>(0),
+        )
+        .map(|c| c > 0)
+        .unwrap_or(false);
+    if already_open {
+        return Err(err(Status::Conflict, "You have already flagged this message"));
+    }
+
+    let flag = MessageFlag {
+        id: uuid::Uuid::new_v4().to_string(),
+        room_id: room_id.to_string(),
+        message_id: message_id.to_string(),
+        message_sender: sender,
+        message_content: content,
+        reporter: reporter.to_string(),
+        reason: reason.to_string(),
+        status: "open".to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        resolved_at: None,
+        resolved_by: None,
+    };
+    conn.execute(
+        &format!("INSERT INTO message_flags ({FLAG_COLUMNS}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, NULL, NULL)"),
+        params![
+            &flag.id,
+            &flag.room_id,
+            &flag.message_id,
+            &flag.message_sender,
+            &flag.message_content,
+            &flag.reporter,
+            &flag.reason,
+            &flag.status,
+            &flag.created_at
+        ],
+    )
+    .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+    drop(conn);
+
+    events.publish(ChatEvent::MessageFlagged(flag.clone()));
+    Ok(Json(flag))
+}
+
+/// GET /api/v1/rooms/<room_id>/flags?status= — the moderation queue (admin key). Defaults to
+/// open flags, oldest first; `status=all` includes resolved ones.
+#[get("/api/v1/rooms/<room_id>/flags?<status>")]
+pub fn list_flags(
+    db: ScopedDb<'_>,
+    room_id: &str,
+    status: Option<&str>,
+    admin: AdminKey,
+) -> Result<Json<FlagQueueResponse>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    check_admin(&conn, room_id, &admin)?;
+    let status = status.unwrap_or("open");
+    if !matches!(status, "open" | "dismissed" | "deleted" | "all") {
+        return Err(err(Status::BadRequest, "status must be one of: open, dismissed, deleted, all"));
+    }
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {FLAG_COLUMNS} FROM message_flags WHERE room_id = ?1 AND (?2 = 'all' OR status = ?2)
+             ORDER BY created_at ASC"
+        ))
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+    let flags: Vec<MessageFlag> = stmt
+        .query_map(params![room_id, status], flag_from_row)
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(Json(FlagQueueResponse {
+        room_id: room_id.to_string(),
+        status: status.to_string(),
+        count: flags.len(),
+        flags,
+    }))
+}
+
+/// POST /api/v1/rooms/<room_id>/flags/<flag_id>/resolve — review a flag (admin key): `dismiss`
+/// keeps the message, `delete` removes it. Either way every open flag on the message is closed.
+#[post("/api/v1/rooms/<room_id>/flags/<flag_id>/resolve", format = "json", data = "<body>")]
+pub fn resolve_flag(
+    db: ScopedDb<'_>,
+    events: Events<'_>,
+    room_id: &str,
+    flag_id: &str,
+    admin: AdminKey,
+    body: Json<ResolveFlag>,
+) -> Result<Json<Vec<MessageFlag>>, (Status, Json<serde_json::Value>)> {
+    let status = match body.action.as_str() {
+        "dismiss" => "dismissed",
+        "delete" => "deleted",
+        _ => return Err(err(Status::BadRequest, "action must be 'dismiss' or 'delete'")),
+    };
+    let moderator = body.moderator.as_deref().map(str::trim).filter(|m| !m.is_empty());
+
+    let conn = db.conn();
+    check_admin(&conn, room_id, &admin)?;
+    let flag = load_flag(&conn, room_id, flag_id).ok_or_else(|| err(Status::NotFound, "Flag not found"))?;
+    if flag.status != "open" {
+        return Err(err(Status::Conflict, &format!("Flag is already {}", flag.status)));
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+    let ids: Vec<String> = tx
+        .prepare("SELECT id FROM message_flags WHERE message_id = ?1 AND room_id = ?2 AND status = 'open'")
+        .and_then(|mut s| {
+            s.query_map(params![&flag.message_id, room_id], |r| r.get(0))
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+    tx.execute(
+        "UPDATE message_flags SET status = ?1, resolved_at = ?2, resolved_by = ?3
+         WHERE message_id = ?4 AND room_id = ?5 AND status = 'open'",
+        params![status, &now, moderator, &flag.message_id, room_id],
+    )
+    .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+    let deleted = status == "deleted"
+        && tx
+            .execute(
+                "DELETE FROM messages WHERE id = ?1 AND room_id = ?2",
+                params![&flag.message_id, room_id],
+            )
+            .map_err(|_| err(Status::InternalServerError, "Internal server error"))?
+            > 0;
+    if deleted {
+        crate::db::delete_fts(&tx, &flag.message_id);
+    }
+    crate::db::record_audit(
+        &tx,
+        &format!("flag_{status}"),
+        room_id,
+        moderator,
+        &serde_json::json!({
+            "message_id": &flag.message_id,
+            "message_sender": &flag.message_sender,
+            "flag_ids": &ids,
+        }),
+    );
+    tx.commit()
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+
+    let resolved: Vec<MessageFlag> = ids.iter().filter_map(|id| load_flag(&conn, room_id, id)).collect();
+    drop(conn);
+
+    if deleted {
+        events.publish(ChatEvent::MessageDeleted {
+            id: flag.message_id.clone(),
+            room_id: room_id.to_string(),
+        });
+    }
+    for f in &resolved {
+        events.publish(ChatEvent::FlagResolved(f.clone()));
+    }
+    Ok(Json(resolved))
+}
@@ -0,0 +1,101 @@
+use crate::namespaces::ScopedDb;
+use crate::models::{ActivityHeatmap, HeatmapPeak};
+use chrono::{Datelike, Timelike};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::get;
+use rusqlite::params;
+
+const DAY_LABELS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+fn err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+/// GET /api/v1/rooms/<room_id>/activity/heatmap?days=30&tz_offset=0 — message counts by
+/// day-of-week × hour-of-day over the last `days` days, split by agents and humans.
+/// `tz_offset` (minutes east of UTC) shifts the buckets into a local day.
+#[get("/api/v1/rooms/<room_id>/activity/heatmap?<days>&<tz_offset>")]
+pub fn activity_heatmap(
+    db: ScopedDb<'_>,
+    room_id: &str,
+    days: Option<i64>,
+    tz_offset: Option<i32>,
+) -> Result<Json<ActivityHeatmap>, (Status, Json<serde_json::Value>)> {
+    let days = days.unwrap_or(30);
+    if !(1..=365).contains(&days) {
+        return Err(err(Status::BadRequest, "days must be between 1 and 365"));
+    }
+    let tz_offset = tz_offset.unwrap_or(0);
+    let offset = Some(tz_offset)
+        .filter(|m| (-720..=840).contains(m))
+        .and_then(|m| chrono::FixedOffset::east_opt(m * 60))
+        .ok_or_else(|| err(Status::BadRequest, "tz_offset must be between -720 and 840 minutes"))?;
+
+    let conn = db.conn();
+    let exists: bool = conn
+        .query_row("SELECT COUNT(*) FROM rooms WHERE id = ?1", params![room_id], |r| r.get::<_, i64>(0))
+        .map(|c| c > 0)
+        .unwrap_or(false);
+    if !exists {
+        return Err(err(Status::NotFound, "Room not found"));
+    }
+
+    let since = (chrono::Utc::now() - chrono::Duration::days(days)).to_rfc3339();
+    let mut counts = vec![vec![0i64; 24]; 7];
+    let mut agents = vec![vec![0i64; 24]; 7];
+    let mut humans = vec![vec![0i64; 24]; 7];
+    let mut total = 0;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT created_at, sender_type FROM messages
+             WHERE room_id = ?1 AND created_at >= ?2 AND kind != 'system'",
+        )
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+    let rows = stmt
+        .query_map(params![room_id, &since], |r| {
+            Ok((r.get::<_, String>(0)?, r.get::<_, Option<String>>(1)?))
+        })
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+    for (created_at, sender_type) in rows.filter_map(|r| r.ok()) {
+        let Ok(at) = chrono::DateTime::parse_from_rfc3339(&created_at) else {
+            continue;
+        };
+        let local = at.with_timezone(&offset);
+        let (day, hour) = (local.weekday().num_days_from_monday() as usize, local.hour() as usize);
+        counts[day][hour] += 1;
+        match sender_type.as_deref() {
+            Some("agent") => agents[day][hour] += 1,
+            Some("human") => humans[day][hour] += 1,
+            _ => {}
+        }
+        total += 1;
+    }
+
+    let peak = counts
+        .iter()
+        .enumerate()
+        .flat_map(|(day, hours)| hours.iter().enumerate().map(move |(hour, &count)| (day, hour, count)))
+        .filter(|&(_, _, count)| count > 0)
+        // Earliest slot wins ties
+        .max_by(|a, b| a.2.cmp(&b.2).then(b.0.cmp(&a.0)).then(b.1.cmp(&a.1)))
+        .map(|(day, hour, count)| HeatmapPeak {
+            day: DAY_LABELS[day].to_string(),
+            hour: hour as u32,
+            count,
+        });
+
+    Ok(Json(ActivityHeatmap {
+        room_id: room_id.to_string(),
+        days,
+        since,
+        tz_offset,
+        day_labels: DAY_LABELS.iter().map(|d| d.to_string()).collect(),
+        total,
+        counts,
+        agents,
+        humans,
+        peak,
+    }))
+}
@@ -0,0 +1,89 @@
+use crate::import;
+use crate::models::{ImportJob, ListOf};
+use crate::namespaces::ScopedDb;
+use crate::senders::{SenderPolicy, ServerToken};
+use rocket::data::{Data, ToByteUnit};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{get, post, State};
+
+fn err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+/// POST /api/v1/admin/import?format=slack|discord — upload an export zip as the raw request body.
+/// The import runs in the background; poll the returned job for progress (server token required).
+#[post("/api/v1/admin/import?<format>&<created_by>", data = "<archive>")]
+pub async fn start_import(
+    format: Option<&str>,
+    created_by: Option<&str>,
+    archive: Data<'_>,
+    db: ScopedDb<'_>,
+    sender_policy: &State<SenderPolicy>,
+    server_token: ServerToken,
+) -> Result<(Status, Json<ImportJob>), (Status, Json<serde_json::Value>)> {
+    sender_policy.check_server_token(&server_token)?;
+    let format = format.map(str::trim).unwrap_or("");
+    if !import::FORMATS.contains(&format) {
+        return Err(err(Status::BadRequest, "format must be one of: slack, discord"));
+    }
+    let created_by = created_by.map(str::trim).filter(|c| !c.is_empty()).unwrap_or("import");
+    if created_by.len() > 100 {
+        return Err(err(Status::BadRequest, "created_by must be at most 100 characters"));
+    }
+
+    let max = import::max_archive_bytes();
+    let upload = archive
+        .open(max.bytes())
+        .into_bytes()
+        .await
+        .map_err(|_| err(Status::BadRequest, "Failed to read the uploaded archive"))?;
+    if !upload.is_complete() {
+        return Err(err(
+            Status::PayloadTooLarge,
+            &format!("Archive exceeds the import limit of {max} bytes (IMPORT_MAX_BYTES)"),
+        ));
+    }
+    let bytes = upload.into_inner();
+    if bytes.is_empty() {
+        return Err(err(Status::BadRequest, "Send the export zip as the request body"));
+    }
+    let archive_size = bytes.len() as i64;
+    let zip = import::open_archive(format, bytes).map_err(|e| err(Status::BadRequest, &e))?;
+
+    let job = import::create_job(&db.conn(), format, created_by, archive_size)
+        .map_err(|_| err(Status::InternalServerError, "Database error"))?;
+    let db_path = db.path.clone();
+    let job_id = job.id.clone();
+    std::thread::Builder::new()
+        .name(format!("import-{}", &job_id[..8]))
+        .spawn(move || import::run_job(&db_path, &job_id, zip))
+        .map_err(|_| err(Status::InternalServerError, "Failed to start the import"))?;
+    Ok((Status::Accepted, Json(job)))
+}
+
+/// GET /api/v1/admin/import/<job_id> — progress and outcome of an import (server token required)
+#[get("/api/v1/admin/import/<job_id>")]
+pub fn get_import(
+    job_id: &str,
+    db: ScopedDb<'_>,
+    sender_policy: &State<SenderPolicy>,
+    server_token: ServerToken,
+) -> Result<Json<ImportJob>, (Status, Json<serde_json::Value>)> {
+    sender_policy.check_server_token(&server_token)?;
+    import::load_job(&db.conn(), job_id)
+        .map(Json)
+        .ok_or_else(|| err(Status::NotFound, "Import not found"))
+}
+
+/// GET /api/v1/admin/import — all imports, newest first (server token required)
+#[get("/api/v1/admin/import?<envelope>")]
+pub fn list_imports(
+    envelope: Option<bool>,
+    db: ScopedDb<'_>,
+    sender_policy: &State<SenderPolicy>,
+    server_token: ServerToken,
+) -> Result<Json<ListOf<ImportJob>>, (Status, Json<serde_json::Value>)> {
+    sender_policy.check_server_token(&server_token)?;
+    Ok(Json(ListOf::complete(import::list_jobs(&db.conn()), envelope)))
+}
@@ -1,7 +1,10 @@
-use crate::db::{self, Db};
-use crate::events::{ChatEvent, EventBus};
+use crate::db;
+use crate::namespaces::ScopedDb;
+use crate::events::{ChatEvent, Events};
 use crate::models::*;
+use crate::quotas::quota_day;
 use crate::rate_limit::{RateLimitConfig, RateLimiter};
+use crate::senders::{SenderPolicy, ServerToken};
 use rocket::http::Status;
 use rocket::serde::json::Json;
 use rocket::{delete, get, post, put, State};
@@ -9,6 +12,72 @@ use rusqlite::params;
 
 use super::{AdminKey, ClientIp};
 
+/// Upper bounds for a hook's own limits.
+const MAX_HOOK_RATE_LIMIT: i64 = 10_000;
+const MAX_HOOK_DAILY_QUOTA: i64 = 10_000_000;
+
+const HOOK_COLUMNS: &str = "id, room_id, name, token, created_by, created_at, active, rate_limit, daily_quota, \
+                            usage_day, messages_today, rejected_today, messages_total, last_used_at";
+
+pub(super) fn validate_limits(rate_limit: Option<i64>, daily_quota: Option<i64>) -> Result<(), (Status, Json<serde_json::Value>)> {
+    if rate_limit.is_some_and(|n| !(1..=MAX_HOOK_RATE_LIMIT).contains(&n)) {
+        return Err((
+            Status::BadRequest,
+            Json(serde_json::json!({"error": format!("rate_limit must be between 1 and {MAX_HOOK_RATE_LIMIT}")})),
+        ));
+    }
+    if daily_quota.is_some_and(|n| !(0..=MAX_HOOK_DAILY_QUOTA).contains(&n)) {
+        return Err((
+            Status::BadRequest,
+            Json(serde_json::json!({"error": format!("daily_quota must be between 0 (unlimited) and {MAX_HOOK_DAILY_QUOTA}")})),
+        ));
+    }
+    Ok(())
+}
+
+fn hook_from_row(row: &rusqlite::Row, config: &RateLimitConfig) -> rusqlite::Result<IncomingWebhook> {
+    let (today, resets_at) = quota_day(chrono::Utc::now());
+    let token: String = row.get(3)?;
+    let rate_limit: Option<i64> = row.get(7)?;
+    let daily_quota: Option<i64> = row.get(8)?;
+    let usage_day: Option<String> = row.get(9)?;
+    let current = usage_day.as_deref() == Some(today.as_str());
+    Ok(IncomingWebhook {
+        id: row.get(0)?,
+        room_id: row.get(1)?,
+        name: row.get(2)?,
+        token: token.clone(),
+        created_by: row.get(4)?,
+        created_at: row.get(5)?,
+        active: row.get::<_, i32>(6)? != 0,
+        url: Some(format!("/api/v1/hook/{}", token)),
+        rate_limit,
+        daily_quota,
+        usage: IncomingWebhookUsage {
+            rate_limit: rate_limit.unwrap_or(config.webhooks_max as i64),
+            daily_quota: Some(daily_quota.unwrap_or(config.webhooks_daily_quota as i64)).filter(|q| *q > 0),
+            messages_today: if current { row.get(10)? } else { 0 },
+            rejected_today: if current { row.get(11)? } else { 0 },
+            messages_total: row.get(12)?,
+            last_used_at: row.get(13)?,
+            resets_at: resets_at.to_rfc3339(),
+        },
+    })
+}
+
+/// Count a refused post against today's usage.
+fn record_rejection(conn: &rusqlite::Connection, hook_id: &str, today: &str) {
+    conn.execute(
+        "UPDATE incoming_webhooks SET
+             messages_today = CASE WHEN usage_day = ?2 THEN messages_today ELSE 0 END,
+             rejected_today = CASE WHEN usage_day = ?2 THEN rejected_today + 1 ELSE 1 END,
+             usage_day = ?2
+         WHERE id = ?1",
+        params![hook_id, today],
+    )
+    .ok();
+}
+
 /// Create an incoming webhook for a room (admin key required).
 #[post(
     "/api/v1/rooms/<room_id>/incoming-webhooks",
@@ -16,7 +85,8 @@ use super::{AdminKey, ClientIp};
     data = "<body>"
 )]
 pub fn create_incoming_webhook(
-    db: &State<Db>,
+    db: ScopedDb<'_>,
+    rate_config: &State<RateLimitConfig>,
     room_id: &str,
     admin: AdminKey,
     body: Json<CreateIncomingWebhook>,
@@ -54,14 +124,15 @@ pub fn create_incoming_webhook(
             Json(serde_json::json!({"error": "Name must be 1-100 characters"})),
         ));
     }
+    validate_limits(body.rate_limit, body.daily_quota)?;
 
     let id = uuid::Uuid::new_v4().to_string();
     let token = db::generate_webhook_token();
     let now = chrono::Utc::now().to_rfc3339();
 
     conn.execute(
-        "INSERT INTO incoming_webhooks (id, room_id, name, token, created_by, created_at, active) VALUES (?1, ?2, ?3, ?4, ?5, ?6, 1)",
-        params![&id, room_id, &name, &token, &body.created_by, &now],
+        "INSERT INTO incoming_webhooks (id, room_id, name, token, created_by, created_at, active, rate_limit, daily_quota) VALUES (?1, ?2, ?3, ?4, ?5, ?6, 1, ?7, ?8)",
+        params![&id, room_id, &name, &token, &body.created_by, &now, body.rate_limit, body.daily_quota],
     )
     .map_err(|_e| {
         (
@@ -70,22 +141,20 @@ pub fn create_incoming_webhook(
         )
     })?;
 
-    Ok(Json(IncomingWebhook {
-        id,
-        room_id: room_id.to_string(),
-        name,
-        token: token.clone(),
-        created_by: body.created_by.clone(),
-        created_at: now,
-        active: true,
-        url: Some(format!("/api/v1/hook/{}", token)),
-    }))
+    conn.query_row(
+        &format!("SELECT {HOOK_COLUMNS} FROM incoming_webhooks WHERE id = ?1"),
+        params![&id],
+        |row| hook_from_row(row, rate_config),
+    )
+    .map(Json)
+    .map_err(|_| (Status::InternalServerError, Json(serde_json::json!({"error": "Internal server error"}))))
 }
 
 /// List incoming webhooks for a room (admin key required).
 #[get("/api/v1/rooms/<room_id>/incoming-webhooks")]
 pub fn list_incoming_webhooks(
-    db: &State<Db>,
+    db: ScopedDb<'_>,
+    rate_config: &State<RateLimitConfig>,
     room_id: &str,
     admin: AdminKey,
 ) -> Result<Json<Vec<IncomingWebhook>>, (Status, Json<serde_json::Value>)> {
@@ -116,25 +185,13 @@ pub fn list_incoming_webhooks(
     }
 
     let mut stmt = conn
-        .prepare(
-            "SELECT id, room_id, name, token, created_by, created_at, active FROM incoming_webhooks WHERE room_id = ?1 ORDER BY created_at DESC",
-        )
+        .prepare(&format!(
+            "SELECT {HOOK_COLUMNS} FROM incoming_webhooks WHERE room_id = ?1 ORDER BY created_at DESC"
+        ))
         .map_err(|_| (Status::InternalServerError, Json(serde_json::json!({"error": "Internal server error"}))))?;
 
     let hooks: Vec<IncomingWebhook> = stmt
-        .query_map(params![room_id], |row| {
-            let token: String = row.get(3)?;
-            Ok(IncomingWebhook {
-                id: row.get(0)?,
-                room_id: row.get(1)?,
-                name: row.get(2)?,
-                token: token.clone(),
-                created_by: row.get(4)?,
-                created_at: row.get(5)?,
-                active: row.get::<_, i32>(6)? != 0,
-                url: Some(format!("/api/v1/hook/{}", token)),
-            })
-        })
+        .query_map(params![room_id], |row| hook_from_row(row, rate_config))
         .map_err(|_| (Status::InternalServerError, Json(serde_json::json!({"error": "Internal server error"}))))?
         .filter_map(|r| r.ok())
         .collect();
@@ -149,7 +206,7 @@ pub fn list_incoming_webhooks(
     data = "<body>"
 )]
 pub fn update_incoming_webhook(
-    db: &State<Db>,
+    db: ScopedDb<'_>,
     room_id: &str,
     webhook_id: &str,
     admin: AdminKey,
@@ -219,6 +276,17 @@ pub fn update_incoming_webhook(
         values.push(Box::new(active as i32));
         idx += 1;
     }
+    validate_limits(body.rate_limit.flatten(), body.daily_quota.flatten())?;
+    if let Some(rate_limit) = body.rate_limit {
+        updates.push(format!("rate_limit = ?{}", idx));
+        values.push(Box::new(rate_limit));
+        idx += 1;
+    }
+    if let Some(daily_quota) = body.daily_quota {
+        updates.push(format!("daily_quota = ?{}", idx));
+        values.push(Box::new(daily_quota));
+        idx += 1;
+    }
 
     if updates.is_empty() {
         return Err((
@@ -252,7 +320,7 @@ pub fn update_incoming_webhook(
 /// Delete an incoming webhook (admin key required).
 #[delete("/api/v1/rooms/<room_id>/incoming-webhooks/<webhook_id>")]
 pub fn delete_incoming_webhook(
-    db: &State<Db>,
+    db: ScopedDb<'_>,
     room_id: &str,
     webhook_id: &str,
     admin: AdminKey,
@@ -304,37 +372,37 @@ pub fn delete_incoming_webhook(
 
 /// Post a message via incoming webhook token. No auth needed — the token IS the auth.
 #[post("/api/v1/hook/<token>", format = "json", data = "<body>")]
+#[allow(clippy::too_many_arguments)]
 pub fn post_via_hook(
-    db: &State<Db>,
-    events: &State<EventBus>,
+    db: ScopedDb<'_>,
+    events: Events<'_>,
     rate_limiter: &State<RateLimiter>,
     rate_config: &State<RateLimitConfig>,
+    sender_policy: &State<SenderPolicy>,
+    server_token: ServerToken,
     _ip: ClientIp,
     token: &str,
     body: Json<IncomingWebhookMessage>,
 ) -> Result<crate::rate_limit::RateLimited<Message>, (Status, Json<serde_json::Value>)> {
-    // Rate limit per token
-    let rl = rate_limiter.check_with_info(&format!("hook:{}", token), rate_config.webhooks_max, rate_config.webhooks_window_secs);
-    if !rl.allowed {
-        return Err((
-            Status::TooManyRequests,
-            Json(serde_json::json!({
-                "error": format!("Rate limited: max {} messages per minute per webhook", rate_config.webhooks_max),
-                "retry_after_secs": rl.retry_after_secs,
-                "limit": rl.limit,
-                "remaining": 0
-            })),
-        ));
-    }
-
     let conn = db.conn();
 
     // Look up the webhook by token
-    let hook: (String, String, String, i32) = conn
+    let hook = conn
         .query_row(
-            "SELECT id, room_id, name, active FROM incoming_webhooks WHERE token = ?1",
+            "SELECT id, room_id, name, active, rate_limit, daily_quota, usage_day, messages_today FROM incoming_webhooks WHERE token = ?1",
             params![token],
-            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)),
+            |r| {
+                Ok((
+                    r.get::<_, String>(0)?,
+                    r.get::<_, String>(1)?,
+                    r.get::<_, String>(2)?,
+                    r.get::<_, i32>(3)?,
+                    r.get::<_, Option<i64>>(4)?,
+                    r.get::<_, Option<i64>>(5)?,
+                    r.get::<_, Option<String>>(6)?,
+                    r.get::<_, i64>(7)?,
+                ))
+            },
         )
         .map_err(|_| {
             (
@@ -343,7 +411,7 @@ pub fn post_via_hook(
             )
         })?;
 
-    let (_hook_id, room_id, hook_name, active) = hook;
+    let (hook_id, room_id, hook_name, active, rate_limit, daily_quota, usage_day, messages_today) = hook;
 
     if active == 0 {
         return Err((
@@ -352,6 +420,41 @@ pub fn post_via_hook(
         ));
     }
 
+    // Rate limit per token (the hook's own limit, else the server default)
+    let now = chrono::Utc::now();
+    let (today, resets_at) = quota_day(now);
+    let limit = rate_limit.map(|n| n as usize).unwrap_or(rate_config.webhooks_max);
+    let rl = rate_limiter.check_with_info(&format!("hook:{}", token), limit, rate_config.webhooks_window_secs);
+    if !rl.allowed {
+        record_rejection(&conn, &hook_id, &today);
+        return Err((
+            Status::TooManyRequests,
+            Json(serde_json::json!({
+                "error": format!("Rate limited: max {} messages per minute per webhook", limit),
+                "retry_after_secs": rl.retry_after_secs,
+                "limit": rl.limit,
+                "remaining": 0
+            })),
+        ));
+    }
+
+    // Daily quota (UTC day)
+    let quota = daily_quota.unwrap_or(rate_config.webhooks_daily_quota as i64);
+    let used_today = if usage_day.as_deref() == Some(today.as_str()) { messages_today } else { 0 };
+    if quota > 0 && used_today >= quota {
+        record_rejection(&conn, &hook_id, &today);
+        return Err((
+            Status::TooManyRequests,
+            Json(serde_json::json!({
+                "error": format!("Daily quota exhausted: max {} messages per day for this webhook", quota),
+                "retry_after_secs": (resets_at - now).num_seconds().max(1),
+                "quota": quota,
+                "used": used_today,
+                "resets_at": resets_at.to_rfc3339()
+            })),
+        ));
+    }
+
     // Verify room still exists
     let room_exists: bool = conn
         .query_row(
@@ -377,20 +480,23 @@ pub fn post_via_hook(
         ));
     }
 
-    // Use provided sender or fall back to webhook name
-    let sender = body
+    // Use provided sender or fall back to webhook name. The hook's own name was chosen by the
+    // room admin, so only a caller-supplied override is held to the reserved-name rules.
+    let override_sender = body
         .sender
         .as_deref()
         .map(|s| s.trim())
-        .filter(|s| !s.is_empty() && s.len() <= 100)
-        .unwrap_or(&hook_name)
-        .to_string();
+        .filter(|s| !s.is_empty() && s.len() <= 100);
+    if let Some(name) = override_sender {
+        sender_policy.check(name, &server_token)?;
+    }
+    let sender = override_sender.unwrap_or(&hook_name).to_string();
 
     let sender_type = body.sender_type.clone().or(Some("agent".to_string()));
     let metadata = body.metadata.clone().unwrap_or(serde_json::json!({}));
 
     let id = uuid::Uuid::new_v4().to_string();
-    let now = chrono::Utc::now().to_rfc3339();
+    let now = now.to_rfc3339();
 
     // Compute next monotonic seq
     let seq: i64 = conn
@@ -417,8 +523,19 @@ pub fn post_via_hook(
     )
     .ok();
 
-    // Index in FTS
+    // Index in FTS and mentions
     crate::db::upsert_fts(&conn, &id);
+    crate::db::index_mentions(&conn, &id);
+
+    conn.execute(
+        "UPDATE incoming_webhooks SET
+             messages_today = CASE WHEN usage_day = ?2 THEN messages_today + 1 ELSE 1 END,
+             rejected_today = CASE WHEN usage_day = ?2 THEN rejected_today ELSE 0 END,
+             usage_day = ?2, messages_total = messages_total + 1, last_used_at = ?3
+         WHERE id = ?1",
+        params![&hook_id, &today, &now],
+    )
+    .ok();
 
     let msg = Message {
         id,
@@ -434,6 +551,8 @@ pub fn post_via_hook(
         pinned_at: None,
         pinned_by: None,
         edit_count: 0,
+        kind: "message".to_string(),
+        local_time: None,
     };
 
     // Publish event for SSE and outgoing webhooks
@@ -0,0 +1,22 @@
+use crate::ip_policy::IpDenied;
+use rocket::get;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+
+/// Where [`crate::ip_policy::IpPolicyFairing`] sends requests the IP policy refuses.
+#[get("/api/v1/ip-policy/denied")]
+pub fn ip_policy_denied(denied: IpDenied) -> (Status, Json<serde_json::Value>) {
+    let ip = denied.ip.map(|ip| ip.to_string());
+    (
+        Status::Forbidden,
+        Json(serde_json::json!({
+            "error": format!(
+                "{} access is not allowed from {}",
+                denied.access.as_str(),
+                ip.as_deref().unwrap_or("an unknown address")
+            ),
+            "access": denied.access.as_str(),
+            "ip": ip,
+        })),
+    )
+}
@@ -0,0 +1,228 @@
+use crate::namespaces::ScopedDb;
+use crate::events::{ChatEvent, Events};
+use crate::models::{AddLabels, LabelCount, LabelSummary, MessageLabel, MessageLabels};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{delete, get, post};
+use rusqlite::{params, Connection};
+
+/// Most labels one message can carry.
+const MAX_MESSAGE_LABELS: usize = 20;
+
+fn err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+/// Lowercase and trim a label; each is 1-32 of `a-z 0-9 - _ .`, like room tags.
+fn normalize_label(label: &str) -> Result<String, String> {
+    let label = label.trim().to_lowercase();
+    let valid = !label.is_empty()
+        && label.len() <= 32
+        && label.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        return Err(format!("Invalid label '{label}': use 1-32 letters, digits, '-', '_' or '.'"));
+    }
+    Ok(label)
+}
+
+/// `?label=` filter for message listings: the message at `column` carries label `?idx`.
+pub(crate) fn label_filter_sql(column: &str, idx: usize) -> String {
+    format!("EXISTS (SELECT 1 FROM message_labels ml WHERE ml.message_id = {column} AND ml.label = ?{idx})")
+}
+
+/// Normalize a `?label=` query value (case-insensitive, like the stored labels).
+pub(crate) fn label_param(label: &str) -> String {
+    label.trim().to_lowercase()
+}
+
+/// The message's author, if it exists in this room.
+fn message_sender(conn: &Connection, room_id: &str, message_id: &str) -> Result<String, (Status, Json<serde_json::Value>)> {
+    conn.query_row(
+        "SELECT sender FROM messages WHERE id = ?1 AND room_id = ?2",
+        params![message_id, room_id],
+        |r| r.get(0),
+    )
+    .map_err(|_| err(Status::NotFound, "Message not found in this room"))
+}
+
+fn load_labels(conn: &Connection, room_id: &str, message_id: &str) -> MessageLabels {
+    let labels = conn
+        .prepare("SELECT label, added_by, created_at FROM message_labels WHERE message_id = ?1 ORDER BY created_at, label")
+        .and_then(|mut s| {
+            s.query_map(params![message_id], |r| {
+                Ok(MessageLabel {
+                    label: r.get(0)?,
+                    added_by: r.get(1)?,
+                    created_at: r.get(2)?,
+                })
+            })
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .unwrap_or_default();
+    MessageLabels {
+        message_id: message_id.to_string(),
+        room_id: room_id.to_string(),
+        labels,
+    }
+}
+
+/// POST /api/v1/rooms/<room_id>/messages/<message_id>/labels — attach labels to a message.
+/// Labels it already has are left as they were.
+#[post("/api/v1/rooms/<room_id>/messages/<message_id>/labels", format = "json", data = "<body>")]
+pub fn add_labels(
+    db: ScopedDb<'_>,
+    events: Events<'_>,
+    room_id: &str,
+    message_id: &str,
+    body: Json<AddLabels>,
+) -> Result<Json<MessageLabels>, (Status, Json<serde_json::Value>)> {
+    let sender = body.sender.trim();
+    if sender.is_empty() || sender.len() > 100 {
+        return Err(err(Status::BadRequest, "Sender must be 1-100 characters"));
+    }
+    if body.labels.is_empty() {
+        return Err(err(Status::BadRequest, "labels must not be empty"));
+    }
+    let mut labels: Vec<String> = Vec::new();
+    for label in &body.labels {
+        let label = normalize_label(label).map_err(|e| err(Status::BadRequest, &e))?;
+        if !labels.contains(&label) {
+            labels.push(label);
+        }
+    }
+
+    let conn = db.conn();
+    message_sender(&conn, room_id, message_id)?;
+    let existing: Vec<String> = load_labels(&conn, room_id, message_id)
+        .labels
+        .into_iter()
+        .map(|l| l.label)
+        .collect();
+    let added = labels.iter().filter(|l| !existing.contains(l)).count();
+    if added == 0 {
+        return Ok(Json(load_labels(&conn, room_id, message_id)));
+    }
+    if existing.len() + added > MAX_MESSAGE_LABELS {
+        return Err(err(
+            Status::BadRequest,
+            &format!("A message can have at most {MAX_MESSAGE_LABELS} labels"),
+        ));
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    for label in &labels {
+        conn.execute(
+            "INSERT OR IGNORE INTO message_labels (message_id, label, added_by, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![message_id, label, sender, &now],
+        )
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+    }
+    let result = load_labels(&conn, room_id, message_id);
+    drop(conn);
+
+    events.publish(ChatEvent::MessageLabeled(result.clone()));
+    Ok(Json(result))
+}
+
+/// GET /api/v1/rooms/<room_id>/messages/<message_id>/labels
+#[get("/api/v1/rooms/<room_id>/messages/<message_id>/labels")]
+pub fn get_labels(
+    db: ScopedDb<'_>,
+    room_id: &str,
+    message_id: &str,
+) -> Result<Json<MessageLabels>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    message_sender(&conn, room_id, message_id)?;
+    Ok(Json(load_labels(&conn, room_id, message_id)))
+}
+
+/// DELETE /api/v1/rooms/<room_id>/messages/<message_id>/labels/<label>?sender= — remove a
+/// label. Only whoever added it or the message's author may.
+#[delete("/api/v1/rooms/<room_id>/messages/<message_id>/labels/<label>?<sender>")]
+pub fn remove_label(
+    db: ScopedDb<'_>,
+    events: Events<'_>,
+    room_id: &str,
+    message_id: &str,
+    label: &str,
+    sender: &str,
+) -> Result<Json<MessageLabels>, (Status, Json<serde_json::Value>)> {
+    let sender = sender.trim();
+    let label = label_param(label);
+    let conn = db.conn();
+    let author = message_sender(&conn, room_id, message_id)?;
+    let added_by: String = conn
+        .query_row(
+            "SELECT added_by FROM message_labels WHERE message_id = ?1 AND label = ?2",
+            params![message_id, &label],
+            |r| r.get(0),
+        )
+        .map_err(|_| err(Status::NotFound, "Label not found on this message"))?;
+    if sender != added_by && sender != author {
+        return Err(err(
+            Status::Forbidden,
+            "Only the sender who added a label or the message's author can remove it",
+        ));
+    }
+
+    conn.execute(
+        "DELETE FROM message_labels WHERE message_id = ?1 AND label = ?2",
+        params![message_id, &label],
+    )
+    .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+    let result = load_labels(&conn, room_id, message_id);
+    drop(conn);
+
+    events.publish(ChatEvent::MessageLabeled(result.clone()));
+    Ok(Json(result))
+}
+
+/// GET /api/v1/rooms/<room_id>/labels?after_date=&before_date= — how often each label is used
+/// in the room, most used first. The dates bound the labeled messages' creation time, so a
+/// retro can ask "what was decided this week".
+#[get("/api/v1/rooms/<room_id>/labels?<after_date>&<before_date>")]
+pub fn room_label_summary(
+    db: ScopedDb<'_>,
+    room_id: &str,
+    after_date: Option<&str>,
+    before_date: Option<&str>,
+) -> Result<Json<LabelSummary>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    let room_exists: bool = conn
+        .query_row("SELECT COUNT(*) FROM rooms WHERE id = ?1", params![room_id], |r| r.get::<_, i64>(0))
+        .map(|c| c > 0)
+        .unwrap_or(false);
+    if !room_exists {
+        return Err(err(Status::NotFound, "Room not found"));
+    }
+
+    let labels: Vec<LabelCount> = conn
+        .prepare(
+            "SELECT ml.label, COUNT(*), MIN(m.created_at), MAX(m.created_at)
+             FROM message_labels ml JOIN messages m ON m.id = ml.message_id
+             WHERE m.room_id = ?1
+               AND (?2 IS NULL OR m.created_at > ?2)
+               AND (?3 IS NULL OR m.created_at < ?3)
+             GROUP BY ml.label
+             ORDER BY COUNT(*) DESC, ml.label",
+        )
+        .and_then(|mut s| {
+            s.query_map(params![room_id, after_date, before_date], |r| {
+                Ok(LabelCount {
+                    label: r.get(0)?,
+                    count: r.get(1)?,
+                    first_message_at: r.get(2)?,
+                    last_message_at: r.get(3)?,
+                })
+            })
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+
+    Ok(Json(LabelSummary {
+        room_id: room_id.to_string(),
+        labels,
+        after_date: after_date.map(String::from),
+        before_date: before_date.map(String::from),
+    }))
+}
@@ -0,0 +1,219 @@
+use crate::namespaces::ScopedDb;
+use crate::events::{ChatEvent, Events};
+use crate::models::{AcquireLock, ListOf, Lock, ReleaseLock, RenewLock};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{get, post};
+use rusqlite::{params, Connection};
+
+const DEFAULT_TTL_SECONDS: i64 = 60;
+const MAX_TTL_SECONDS: i64 = 86_400;
+
+fn err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+fn check_name(name: &str) -> Result<(), (Status, Json<serde_json::Value>)> {
+    if name.is_empty() || name.len() > 200 {
+        return Err(err(Status::BadRequest, "Lock name must be 1-200 characters"));
+    }
+    Ok(())
+}
+
+fn check_holder(holder: &str) -> Result<&str, (Status, Json<serde_json::Value>)> {
+    let holder = holder.trim();
+    if holder.is_empty() || holder.len() > 100 {
+        return Err(err(Status::BadRequest, "holder must be 1-100 characters"));
+    }
+    Ok(holder)
+}
+
+fn check_ttl(ttl: Option<i64>) -> Result<i64, (Status, Json<serde_json::Value>)> {
+    let ttl = ttl.unwrap_or(DEFAULT_TTL_SECONDS);
+    if !(1..=MAX_TTL_SECONDS).contains(&ttl) {
+        return Err(err(Status::BadRequest, &format!("ttl_seconds must be between 1 and {MAX_TTL_SECONDS}")));
+    }
+    Ok(ttl)
+}
+
+/// The lock's state as of `now`. A lock past its expiry reads as free; the row is only
+/// rewritten by the next acquire.
+fn load_lock(conn: &Connection, name: &str, now: &str) -> Option<Lock> {
+    conn.query_row(
+        "SELECT name, holder, token, acquired_at, expires_at FROM locks WHERE name = ?1",
+        params![name],
+        |r| {
+            let holder: Option<String> = r.get(1)?;
+            let expires_at: Option<String> = r.get(4)?;
+            let held = holder.is_some() && expires_at.as_deref().is_some_and(|e| e > now);
+            Ok(Lock {
+                name: r.get(0)?,
+                held,
+                holder: if held { holder } else { None },
+                token: r.get(2)?,
+                acquired_at: if held { r.get(3)? } else { None },
+                expires_at: if held { expires_at } else { None },
+            })
+        },
+    )
+    .ok()
+}
+
+fn free_lock(name: &str) -> Lock {
+    Lock {
+        name: name.to_string(),
+        held: false,
+        holder: None,
+        token: 0,
+        acquired_at: None,
+        expires_at: None,
+    }
+}
+
+fn conflict(lock: &Lock) -> (Status, Json<serde_json::Value>) {
+    (
+        Status::Conflict,
+        Json(serde_json::json!({
+            "error": format!("Lock '{}' is held by '{}'", lock.name, lock.holder.as_deref().unwrap_or("")),
+            "holder": lock.holder,
+            "expires_at": lock.expires_at,
+        })),
+    )
+}
+
+/// POST /api/v1/locks/<name>/acquire — take a lock for `ttl_seconds`. Each acquisition gets a
+/// new, higher fencing token. Acquiring a lock you already hold extends it and keeps the token.
+#[post("/api/v1/locks/<name>/acquire", format = "json", data = "<body>")]
+pub fn acquire_lock(
+    name: &str,
+    body: Json<AcquireLock>,
+    db: ScopedDb<'_>,
+    events: Events<'_>,
+) -> Result<Json<Lock>, (Status, Json<serde_json::Value>)> {
+    check_name(name)?;
+    let holder = check_holder(&body.holder)?;
+    let ttl = check_ttl(body.ttl_seconds)?;
+
+    let conn = db.conn();
+    let now = chrono::Utc::now();
+    let now_str = now.to_rfc3339();
+    let expires = (now + chrono::Duration::seconds(ttl)).to_rfc3339();
+
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|_| err(Status::InternalServerError, "Database error"))?;
+    let current = load_lock(&tx, name, &now_str);
+    let reacquire = match current {
+        Some(ref lock) if lock.held && lock.holder.as_deref() != Some(holder) => return Err(conflict(lock)),
+        Some(ref lock) => lock.held,
+        None => false,
+    };
+    let written = if reacquire {
+        tx.execute("UPDATE locks SET expires_at = ?1 WHERE name = ?2", params![&expires, name])
+    } else {
+        tx.execute(
+            "INSERT INTO locks (name, holder, token, acquired_at, expires_at) VALUES (?1, ?2, 1, ?3, ?4)
+             ON CONFLICT(name) DO UPDATE SET holder = ?2, token = token + 1, acquired_at = ?3, expires_at = ?4",
+            params![name, holder, &now_str, &expires],
+        )
+    };
+    written.map_err(|_| err(Status::InternalServerError, "Database error"))?;
+    let lock = load_lock(&tx, name, &now_str).ok_or_else(|| err(Status::InternalServerError, "Database error"))?;
+    tx.commit()
+        .map_err(|_| err(Status::InternalServerError, "Database error"))?;
+
+    if !reacquire {
+        events.publish(ChatEvent::LockAcquired(lock.clone()));
+    }
+    Ok(Json(lock))
+}
+
+/// Check that `holder` still holds the lock with `token`.
+fn held_by(conn: &Connection, name: &str, holder: &str, token: i64, now: &str) -> Result<Lock, (Status, Json<serde_json::Value>)> {
+    let lock = load_lock(conn, name, now).ok_or_else(|| err(Status::NotFound, "Lock not found"))?;
+    if !lock.held {
+        return Err(err(Status::Conflict, "Lock is not held (it expired or was released)"));
+    }
+    if lock.holder.as_deref() != Some(holder) {
+        return Err(conflict(&lock));
+    }
+    if lock.token != token {
+        return Err(err(Status::Conflict, "Stale fencing token"));
+    }
+    Ok(lock)
+}
+
+/// POST /api/v1/locks/<name>/renew — extend a held lock (holder and token must match)
+#[post("/api/v1/locks/<name>/renew", format = "json", data = "<body>")]
+pub fn renew_lock(
+    name: &str,
+    body: Json<RenewLock>,
+    db: ScopedDb<'_>,
+) -> Result<Json<Lock>, (Status, Json<serde_json::Value>)> {
+    check_name(name)?;
+    let holder = check_holder(&body.holder)?;
+    let ttl = check_ttl(body.ttl_seconds)?;
+
+    let conn = db.conn();
+    let now = chrono::Utc::now();
+    let now_str = now.to_rfc3339();
+    let mut lock = held_by(&conn, name, holder, body.token, &now_str)?;
+    let expires = (now + chrono::Duration::seconds(ttl)).to_rfc3339();
+    conn.execute("UPDATE locks SET expires_at = ?1 WHERE name = ?2", params![&expires, name])
+        .map_err(|_| err(Status::InternalServerError, "Database error"))?;
+    lock.expires_at = Some(expires);
+    Ok(Json(lock))
+}
+
+/// POST /api/v1/locks/<name>/release — give up a held lock (holder and token must match)
+#[post("/api/v1/locks/<name>/release", format = "json", data = "<body>")]
+pub fn release_lock(
+    name: &str,
+    body: Json<ReleaseLock>,
+    db: ScopedDb<'_>,
+    events: Events<'_>,
+) -> Result<Json<Lock>, (Status, Json<serde_json::Value>)> {
+    check_name(name)?;
+    let holder = check_holder(&body.holder)?;
+
+    let conn = db.conn();
+    let now_str = chrono::Utc::now().to_rfc3339();
+    held_by(&conn, name, holder, body.token, &now_str)?;
+    conn.execute(
+        "UPDATE locks SET holder = NULL, acquired_at = NULL, expires_at = NULL WHERE name = ?1",
+        params![name],
+    )
+    .map_err(|_| err(Status::InternalServerError, "Database error"))?;
+    let lock = load_lock(&conn, name, &now_str).unwrap_or_else(|| free_lock(name));
+    events.publish(ChatEvent::LockReleased {
+        name: name.to_string(),
+        holder: holder.to_string(),
+        token: body.token,
+    });
+    Ok(Json(lock))
+}
+
+/// GET /api/v1/locks/<name> — current state (a never-used name reads as free with token 0)
+#[get("/api/v1/locks/<name>")]
+pub fn get_lock(name: &str, db: ScopedDb<'_>) -> Result<Json<Lock>, (Status, Json<serde_json::Value>)> {
+    check_name(name)?;
+    let conn = db.conn();
+    let now_str = chrono::Utc::now().to_rfc3339();
+    Ok(Json(load_lock(&conn, name, &now_str).unwrap_or_else(|| free_lock(name))))
+}
+
+/// GET /api/v1/locks — locks currently held, by name
+#[get("/api/v1/locks?<envelope>")]
+pub fn list_locks(envelope: Option<bool>, db: ScopedDb<'_>) -> Json<ListOf<Lock>> {
+    let conn = db.conn();
+    let now_str = chrono::Utc::now().to_rfc3339();
+    let names: Vec<String> = conn
+        .prepare("SELECT name FROM locks WHERE holder IS NOT NULL AND expires_at > ?1 ORDER BY name")
+        .and_then(|mut s| {
+            s.query_map(params![&now_str], |r| r.get(0))
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .unwrap_or_default();
+    let locks = names.iter().filter_map(|n| load_lock(&conn, n, &now_str)).collect();
+    Json(ListOf::complete(locks, envelope))
+}
@@ -0,0 +1,53 @@
+use crate::maintenance::{BlockedWrite, Maintenance};
+use crate::models::{MaintenanceStatus, SetMaintenance};
+use crate::senders::{SenderPolicy, ServerToken};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{get, put, State};
+
+/// GET /api/v1/maintenance — whether the server is read-only right now
+#[get("/api/v1/maintenance")]
+pub fn maintenance_status(maintenance: &State<Maintenance>) -> Json<MaintenanceStatus> {
+    Json(maintenance.status())
+}
+
+/// PUT /api/v1/admin/maintenance — switch read-only mode on or off (server token required).
+/// Turning it on again keeps the original `since` and swaps in the new message.
+#[put("/api/v1/admin/maintenance", format = "json", data = "<body>")]
+pub fn set_maintenance(
+    maintenance: &State<Maintenance>,
+    sender_policy: &State<SenderPolicy>,
+    server_token: ServerToken,
+    body: Json<SetMaintenance>,
+) -> Result<Json<MaintenanceStatus>, (Status, Json<serde_json::Value>)> {
+    sender_policy.check_server_token(&server_token)?;
+    if body.message.as_ref().is_some_and(|m| m.len() > 500) {
+        return Err((
+            Status::BadRequest,
+            Json(serde_json::json!({"error": "message must be at most 500 characters"})),
+        ));
+    }
+    if body.enabled {
+        maintenance.enable(body.message.clone());
+    } else {
+        maintenance.disable();
+    }
+    Ok(Json(maintenance.status()))
+}
+
+/// Where [`crate::maintenance::MaintenanceFairing`] sends writes while maintenance is on.
+#[get("/api/v1/maintenance/blocked")]
+pub fn maintenance_blocked(
+    maintenance: &State<Maintenance>,
+    _blocked: BlockedWrite,
+) -> (Status, Json<serde_json::Value>) {
+    let status = maintenance.status();
+    (
+        Status::ServiceUnavailable,
+        Json(serde_json::json!({
+            "error": status.message.unwrap_or_default(),
+            "maintenance": true,
+            "since": status.since,
+        })),
+    )
+}
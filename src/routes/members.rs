@@ -0,0 +1,153 @@
+use crate::membership::{generate_member_token, MembersOnly};
+use crate::models::{AddRoomMember, RoomMember};
+use crate::namespaces::ScopedDb;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{delete, get, post};
+use rusqlite::{params, Connection};
+
+use super::AdminKey;
+
+fn err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+/// The room's admin key, or 404 if there is no such room.
+fn room_admin_key(conn: &Connection, room_id: &str) -> Result<Option<String>, (Status, Json<serde_json::Value>)> {
+    conn.query_row("SELECT admin_key FROM rooms WHERE id = ?1", params![room_id], |r| r.get(0))
+        .map_err(|_| err(Status::NotFound, "Room not found"))
+}
+
+fn check_admin(conn: &Connection, room_id: &str, admin: &AdminKey) -> Result<(), (Status, Json<serde_json::Value>)> {
+    match room_admin_key(conn, room_id)? {
+        Some(ref key) if key == &admin.0 => Ok(()),
+        _ => Err(err(Status::Forbidden, "Invalid admin key for this room")),
+    }
+}
+
+fn load_member(conn: &Connection, room_id: &str, sender: &str) -> Option<RoomMember> {
+    conn.query_row(
+        "SELECT room_id, sender, token, added_by, added_at FROM room_members WHERE room_id = ?1 AND sender = ?2",
+        params![room_id, sender],
+        |r| {
+            Ok(RoomMember {
+                room_id: r.get(0)?,
+                sender: r.get(1)?,
+                token: r.get(2)?,
+                added_by: r.get(3)?,
+                added_at: r.get(4)?,
+            })
+        },
+    )
+    .ok()
+}
+
+/// GET /api/v1/rooms/<room_id>/members — the room's members. Tokens are only included for the
+/// room admin; in a private room only members get this far at all.
+#[get("/api/v1/rooms/<room_id>/members")]
+pub fn list_room_members(
+    db: ScopedDb<'_>,
+    room_id: &str,
+    admin: Option<AdminKey>,
+) -> Result<Json<Vec<RoomMember>>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    let is_admin = room_admin_key(&conn, room_id)?.is_some_and(|key| admin.is_some_and(|a| a.0 == key));
+    let members = conn
+        .prepare(
+            "SELECT room_id, sender, token, added_by, added_at FROM room_members WHERE room_id = ?1
+             ORDER BY sender COLLATE NOCASE",
+        )
+        .and_then(|mut s| {
+            s.query_map(params![room_id], |r| {
+                Ok(RoomMember {
+                    room_id: r.get(0)?,
+                    sender: r.get(1)?,
+                    token: if is_admin { r.get(2)? } else { None },
+                    added_by: r.get(3)?,
+                    added_at: r.get(4)?,
+                })
+            })
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+    Ok(Json(members))
+}
+
+/// POST /api/v1/rooms/<room_id>/members — add a member (room admin only) and return its token.
+/// Aliases resolve to their canonical sender; adding an existing member returns its membership
+/// unchanged.
+#[post("/api/v1/rooms/<room_id>/members", format = "json", data = "<body>")]
+pub fn add_room_member(
+    db: ScopedDb<'_>,
+    room_id: &str,
+    admin: AdminKey,
+    body: Json<AddRoomMember>,
+) -> Result<Json<RoomMember>, (Status, Json<serde_json::Value>)> {
+    let sender = body.sender.trim();
+    if sender.is_empty() || sender.len() > 100 {
+        return Err(err(Status::BadRequest, "Sender must be 1-100 characters"));
+    }
+    let added_by = body.added_by.as_deref().map(str::trim).filter(|a| !a.is_empty());
+    if added_by.is_some_and(|a| a.len() > 100) {
+        return Err(err(Status::BadRequest, "added_by must be at most 100 characters"));
+    }
+
+    let conn = db.conn();
+    check_admin(&conn, room_id, &admin)?;
+    let sender = crate::db::resolve_sender(&conn, sender);
+    if let Some(existing) = load_member(&conn, room_id, &sender) {
+        return Ok(Json(existing));
+    }
+    let now = chrono::Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO room_members (room_id, sender, token, added_by, added_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![room_id, &sender, generate_member_token(), added_by, &now],
+    )
+    .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+    crate::db::record_audit(&conn, "member_added", room_id, added_by, &serde_json::json!({"sender": &sender}));
+
+    load_member(&conn, room_id, &sender)
+        .map(Json)
+        .ok_or_else(|| err(Status::InternalServerError, "Internal server error"))
+}
+
+/// DELETE /api/v1/rooms/<room_id>/members/<sender> — remove a member (room admin only). Its token
+/// stops working immediately.
+#[delete("/api/v1/rooms/<room_id>/members/<sender>")]
+pub fn remove_room_member(
+    db: ScopedDb<'_>,
+    room_id: &str,
+    sender: &str,
+    admin: AdminKey,
+) -> Result<Json<serde_json::Value>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    check_admin(&conn, room_id, &admin)?;
+    let sender = crate::db::resolve_sender(&conn, sender.trim());
+    let deleted = conn
+        .execute(
+            "DELETE FROM room_members WHERE room_id = ?1 AND sender = ?2",
+            params![room_id, &sender],
+        )
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+    if deleted == 0 {
+        return Err(err(Status::NotFound, "Sender is not a member of this room"));
+    }
+    crate::db::record_audit(&conn, "member_removed", room_id, None, &serde_json::json!({"sender": &sender}));
+    Ok(Json(serde_json::json!({"deleted": deleted, "room_id": room_id, "sender": sender})))
+}
+
+/// Where [`crate::membership::RoomMembershipFairing`] sends non-members of a private room.
+#[get("/api/v1/membership/denied")]
+pub fn membership_denied(denied: MembersOnly) -> (Status, Json<serde_json::Value>) {
+    // A wrong admin key reads the same as anywhere else, so the admin key lockout counts it
+    if denied.wrong_admin_key {
+        return err(Status::Forbidden, "Invalid admin key for this room");
+    }
+    (
+        Status::Forbidden,
+        Json(serde_json::json!({
+            "error": "This room is private: only its members can read or post",
+            "visibility": "private",
+        })),
+    )
+}
@@ -1,19 +1,21 @@
-use crate::db::Db;
+use crate::membership::MemberAccess;
+use crate::namespaces::ScopedDb;
 use crate::models::*;
 use rocket::http::Status;
 use rocket::serde::json::Json;
-use rocket::{get, State};
+use rocket::get;
 
 /// GET /api/v1/mentions?target=<name>&after=<seq>&room_id=<uuid>&limit=N
 /// Returns messages that @mention the target sender, with room context.
-/// Uses LIKE pattern matching for @mentions in message content.
+/// Uses the mentions table, populated when messages are created or edited.
 #[get("/api/v1/mentions?<target>&<after>&<room_id>&<limit>")]
 pub fn get_mentions(
-    db: &State<Db>,
+    db: ScopedDb<'_>,
     target: &str,
     after: Option<i64>,
     room_id: Option<&str>,
     limit: Option<i64>,
+    access: MemberAccess,
 ) -> Result<Json<MentionsResponse>, (Status, Json<serde_json::Value>)> {
     let target = target.trim();
     if target.is_empty() {
@@ -32,22 +34,21 @@ pub fn get_mentions(
     let conn = db.conn();
     let limit = limit.unwrap_or(50).clamp(1, 200);
 
-    // Build LIKE pattern for @mention detection
-    // Match @target followed by a word boundary (space, punctuation, end of string)
-    // We use two patterns: "@target " (followed by space/text) and "%@target" at end
-    let mention_pattern = format!(
-        "%@{}%",
-        target.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
-    );
-
-    let mut sql = String::from(
+    // Mention targets are stored lowercased, so matching is case-insensitive.
+    // Mentions of any alias count, and the sender's own messages (under any alias) don't.
+    let canonical = crate::db::resolve_sender(&conn, target);
+    let mut sql = format!(
         "SELECT m.id, m.room_id, r.name, m.sender, m.sender_type, m.content, \
          m.created_at, m.edited_at, m.reply_to, m.seq \
-         FROM messages m JOIN rooms r ON m.room_id = r.id \
-         WHERE m.content LIKE ?1 ESCAPE '\\' \
-         AND m.sender != ?2",
+         FROM mentions mn \
+         JOIN messages m ON mn.message_id = m.id \
+         JOIN rooms r ON m.room_id = r.id \
+         WHERE (mn.target = ?1 OR mn.target IN (SELECT alias_key FROM sender_aliases WHERE sender = ?2)) \
+         AND NOT {} AND {}",
+        crate::db::sender_identity_sql("m.sender", 2),
+        access.visible_sql("m.room_id")
     );
-    let mut param_values: Vec<String> = vec![mention_pattern, target.to_string()];
+    let mut param_values: Vec<String> = vec![canonical.to_lowercase(), canonical];
     let mut idx = 3;
 
     if let Some(after_val) = after {
@@ -102,7 +103,7 @@ pub fn get_mentions(
 /// A mention is "unread" if its seq is greater than the target's last_read_seq for that room.
 #[get("/api/v1/mentions/unread?<target>")]
 pub fn get_unread_mentions(
-    db: &State<Db>,
+    db: ScopedDb<'_>,
     target: &str,
 ) -> Result<Json<UnreadMentionsResponse>, (Status, Json<serde_json::Value>)> {
     let target = target.trim();
@@ -115,26 +116,27 @@ pub fn get_unread_mentions(
 
     let conn = db.conn();
 
-    let mention_pattern = format!(
-        "%@{}%",
-        target.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+    // Get unread mentions per room by comparing against read positions (kept under the
+    // name the reader asked as). Mentions of any alias count, as with GET /mentions.
+    let canonical = crate::db::resolve_sender(&conn, target);
+    let sql = format!(
+        "SELECT m.room_id, r.name, COUNT(*) as mention_count, MIN(m.seq) as oldest_seq, MAX(m.seq) as newest_seq \
+         FROM mentions mn \
+         JOIN messages m ON mn.message_id = m.id \
+         JOIN rooms r ON m.room_id = r.id \
+         LEFT JOIN read_positions rp ON m.room_id = rp.room_id AND rp.sender = ?2 \
+         WHERE (mn.target = ?1 OR mn.target IN (SELECT alias_key FROM sender_aliases WHERE sender = ?3)) \
+         AND NOT {} \
+         AND m.seq > COALESCE(rp.last_read_seq, 0) \
+         GROUP BY m.room_id \
+         ORDER BY newest_seq DESC",
+        crate::db::sender_identity_sql("m.sender", 3)
     );
 
-    // Get unread mentions per room by comparing against read positions
-    let sql = "SELECT m.room_id, r.name, COUNT(*) as mention_count, MIN(m.seq) as oldest_seq, MAX(m.seq) as newest_seq \
-               FROM messages m \
-               JOIN rooms r ON m.room_id = r.id \
-               LEFT JOIN read_positions rp ON m.room_id = rp.room_id AND rp.sender = ?2 \
-               WHERE m.content LIKE ?1 ESCAPE '\\' \
-               AND m.sender != ?2 \
-               AND m.seq > COALESCE(rp.last_read_seq, 0) \
-               GROUP BY m.room_id \
-               ORDER BY newest_seq DESC";
-
-    let mut stmt = conn.prepare(sql).map_err(|_| (Status::InternalServerError, Json(serde_json::json!({"error": "Internal server error"}))))?;
+    let mut stmt = conn.prepare(&sql).map_err(|_| (Status::InternalServerError, Json(serde_json::json!({"error": "Internal server error"}))))?;
     let rooms: Vec<UnreadMentionRoom> = stmt
         .query_map(
-            rusqlite::params![mention_pattern, target],
+            rusqlite::params![canonical.to_lowercase(), target, &canonical],
             |row| {
                 Ok(UnreadMentionRoom {
                     room_id: row.get(0)?,
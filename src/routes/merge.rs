@@ -0,0 +1,279 @@
+use crate::namespaces::ScopedDb;
+use crate::events::{ChatEvent, Events};
+use crate::models::{AuditEntry, MergeRooms, MergeRoomsResponse};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{get, post};
+use rusqlite::{params, Connection};
+use std::collections::HashMap;
+
+use super::rooms::fetch_room_with_stats;
+use super::AdminKey;
+
+fn err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+fn internal(_e: rusqlite::Error) -> (Status, Json<serde_json::Value>) {
+    err(Status::InternalServerError, "Internal server error")
+}
+
+/// (old seq, new seq) pairs for one room, sorted by old seq, with new seqs as a running max
+/// so a read position maps to the newest renumbered message at or before it.
+fn position_map(mut pairs: Vec<(i64, i64)>) -> Vec<(i64, i64)> {
+    pairs.sort_unstable();
+    let mut running = 0;
+    for p in pairs.iter_mut() {
+        running = running.max(p.1);
+        p.1 = running;
+    }
+    pairs
+}
+
+fn remap(map: &[(i64, i64)], old: i64) -> i64 {
+    match map.partition_point(|(o, _)| *o <= old) {
+        0 => 0,
+        i => map[i - 1].1,
+    }
+}
+
+fn room_key_and_type(conn: &Connection, room_id: &str) -> Option<(String, Option<String>, Option<String>)> {
+    conn.query_row(
+        "SELECT name, admin_key, room_type FROM rooms WHERE id = ?1",
+        params![room_id],
+        |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+    )
+    .ok()
+}
+
+/// Merge one room into another. Messages from both rooms are interleaved by timestamp and
+/// renumbered with fresh seqs; files, pins, webhooks, queue items, held and scheduled posts,
+/// subscriptions, roles, members, bookmarks, and read positions follow. A private room can
+/// only be merged into a private room.
+/// The source room is removed, a redirect is recorded for its id, and an audit entry is written.
+#[post("/api/v1/admin/rooms/merge", format = "json", data = "<body>")]
+pub fn merge_rooms(
+    db: ScopedDb<'_>,
+    events: Events<'_>,
+    admin: AdminKey,
+    body: Json<MergeRooms>,
+) -> Result<Json<MergeRoomsResponse>, (Status, Json<serde_json::Value>)> {
+    let target = body.target_room_id.trim();
+    let source = body.source_room_id.trim();
+    if target == source {
+        return Err(err(Status::BadRequest, "Cannot merge a room into itself"));
+    }
+
+    let conn = db.conn();
+    let (_, target_key, target_type) =
+        room_key_and_type(&conn, target).ok_or_else(|| err(Status::NotFound, "Target room not found"))?;
+    let (source_name, source_key, source_type) =
+        room_key_and_type(&conn, source).ok_or_else(|| err(Status::NotFound, "Source room not found"))?;
+    if target_key.as_deref() != Some(admin.0.as_str()) {
+        return Err(err(Status::Forbidden, "Invalid admin key for the target room"));
+    }
+    if source_key.as_deref() != Some(body.source_admin_key.as_str()) {
+        return Err(err(Status::Forbidden, "Invalid source_admin_key for the source room"));
+    }
+    if target_type.as_deref() == Some("dm") || source_type.as_deref() == Some("dm") {
+        return Err(err(Status::BadRequest, "DM conversations cannot be merged"));
+    }
+    // A public target would publish the private room's history to everyone
+    if crate::membership::is_private(&conn, source) && !crate::membership::is_private(&conn, target) {
+        return Err(err(Status::Conflict, "A private room can only be merged into another private room"));
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let tx = conn.unchecked_transaction().map_err(internal)?;
+
+    // Interleave both histories by timestamp and hand out fresh seqs above everything existing
+    let rows: Vec<(String, String, i64)> = {
+        let mut stmt = tx
+            .prepare("SELECT id, room_id, seq FROM messages WHERE room_id IN (?1, ?2) ORDER BY created_at ASC, seq ASC")
+            .map_err(internal)?;
+        stmt.query_map(params![target, source], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))
+            .map_err(internal)?
+            .filter_map(|r| r.ok())
+            .collect()
+    };
+    let base: i64 = tx
+        .query_row("SELECT COALESCE(MAX(seq), 0) FROM messages", [], |r| r.get(0))
+        .map_err(internal)?;
+    let mut pairs: HashMap<&str, Vec<(i64, i64)>> = HashMap::new();
+    {
+        let mut update = tx
+            .prepare("UPDATE messages SET room_id = ?1, seq = ?2 WHERE id = ?3")
+            .map_err(internal)?;
+        for (i, (id, room, old_seq)) in rows.iter().enumerate() {
+            let new_seq = base + i as i64 + 1;
+            update.execute(params![target, new_seq, id]).map_err(internal)?;
+            // Search follows the target room's tokenizer, committed together with the merge
+            crate::db::upsert_fts(&tx, id);
+            let key = if room == target { target } else { source };
+            pairs.entry(key).or_default().push((*old_seq, new_seq));
+        }
+    }
+    let target_map = position_map(pairs.remove(target).unwrap_or_default());
+    let source_map = position_map(pairs.remove(source).unwrap_or_default());
+
+    // Read positions: translate each room's cursor; a sender who read both keeps the earlier
+    // of the two so nothing they haven't seen is marked read
+    let positions: Vec<(String, String, i64)> = {
+        let mut stmt = tx
+            .prepare("SELECT room_id, sender, last_read_seq FROM read_positions WHERE room_id IN (?1, ?2)")
+            .map_err(internal)?;
+        stmt.query_map(params![target, source], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))
+            .map_err(internal)?
+            .filter_map(|r| r.ok())
+            .collect()
+    };
+    let mut merged_positions: HashMap<String, i64> = HashMap::new();
+    for (room, sender, last) in &positions {
+        let mapped = remap(if room == target { &target_map } else { &source_map }, *last);
+        merged_positions
+            .entry(sender.clone())
+            .and_modify(|p| *p = (*p).min(mapped))
+            .or_insert(mapped);
+    }
+    tx.execute("DELETE FROM read_positions WHERE room_id = ?1", params![source])
+        .map_err(internal)?;
+    for (sender, seq) in &merged_positions {
+        tx.execute(
+            "INSERT INTO read_positions (room_id, sender, last_read_seq, updated_at) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(room_id, sender) DO UPDATE SET last_read_seq = excluded.last_read_seq, updated_at = excluded.updated_at",
+            params![target, sender, seq, &now],
+        )
+        .map_err(internal)?;
+    }
+
+    let thread_positions: Vec<(String, String, String, i64)> = {
+        let mut stmt = tx
+            .prepare("SELECT root_id, sender, room_id, last_read_seq FROM thread_read_positions WHERE room_id IN (?1, ?2)")
+            .map_err(internal)?;
+        stmt.query_map(params![target, source], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)))
+            .map_err(internal)?
+            .filter_map(|r| r.ok())
+            .collect()
+    };
+    for (root_id, sender, room, last) in &thread_positions {
+        let mapped = remap(if room == target { &target_map } else { &source_map }, *last);
+        tx.execute(
+            "UPDATE thread_read_positions SET room_id = ?1, last_read_seq = ?2 WHERE root_id = ?3 AND sender = ?4",
+            params![target, mapped, root_id, sender],
+        )
+        .map_err(internal)?;
+    }
+
+    let files_moved = tx
+        .execute("UPDATE files SET room_id = ?1 WHERE room_id = ?2", params![target, source])
+        .map_err(internal)?;
+    for table in ["message_streams", "webhooks", "incoming_webhooks", "pin_history", "queue_items", "deferred_messages"] {
+        tx.execute(&format!("UPDATE {table} SET room_id = ?1 WHERE room_id = ?2"), params![target, source])
+            .map_err(internal)?;
+    }
+    // Per-sender rows the target already has for a sender win; the source's copies go with it
+    for table in ["room_subscriptions", "room_roles", "room_members"] {
+        tx.execute(&format!("UPDATE OR IGNORE {table} SET room_id = ?1 WHERE room_id = ?2"), params![target, source])
+            .map_err(internal)?;
+    }
+    tx.execute(
+        "INSERT OR IGNORE INTO bookmarks (room_id, sender, created_at) SELECT ?1, sender, created_at FROM bookmarks WHERE room_id = ?2",
+        params![target, source],
+    )
+    .map_err(internal)?;
+    // Earlier merges and renames of the source now point at the surviving room
+    tx.execute(
+        "UPDATE room_redirects SET target_room_id = ?1 WHERE target_room_id = ?2",
+        params![target, source],
+    )
+    .map_err(internal)?;
+    tx.execute(
+        "UPDATE room_name_aliases SET room_id = ?1 WHERE room_id = ?2",
+        params![target, source],
+    )
+    .map_err(internal)?;
+
+    tx.execute("DELETE FROM rooms WHERE id = ?1", params![source])
+        .map_err(internal)?;
+    tx.execute(
+        "INSERT OR REPLACE INTO room_redirects (room_id, name, target_room_id, reason, created_at) VALUES (?1, ?2, ?3, 'merged', ?4)",
+        params![source, &source_name, target, &now],
+    )
+    .map_err(internal)?;
+    tx.execute(
+        "INSERT OR REPLACE INTO room_name_aliases (name, room_id, created_at) VALUES (?1, ?2, ?3)",
+        params![&source_name, target, &now],
+    )
+    .map_err(internal)?;
+    tx.execute("UPDATE rooms SET updated_at = ?1 WHERE id = ?2", params![&now, target])
+        .map_err(internal)?;
+
+    let source_count = source_map.len();
+    crate::db::record_audit(
+        &tx,
+        "room_merged",
+        target,
+        body.merged_by.as_deref(),
+        &serde_json::json!({
+            "source_room_id": source,
+            "source_room_name": &source_name,
+            "messages_merged": source_count,
+            "files_moved": files_moved,
+            "read_positions_remapped": positions.len(),
+        }),
+    );
+    tx.commit().map_err(internal)?;
+
+    let room = fetch_room_with_stats(&conn, target).map_err(internal)?;
+    drop(conn);
+    events.publish(ChatEvent::RoomDeleted {
+        id: source.to_string(),
+        name: source_name.clone(),
+    });
+    events.publish(ChatEvent::RoomUpdated(room.clone()));
+
+    Ok(Json(MergeRoomsResponse {
+        room,
+        source_room_id: source.to_string(),
+        source_room_name: source_name,
+        messages_merged: source_count,
+        files_moved,
+        read_positions_remapped: positions.len(),
+    }))
+}
+
+/// Administrative actions recorded against a room, newest first (admin key required).
+#[get("/api/v1/rooms/<room_id>/audit?<limit>")]
+pub fn room_audit_log(
+    db: ScopedDb<'_>,
+    room_id: &str,
+    admin: AdminKey,
+    limit: Option<i64>,
+) -> Result<Json<Vec<AuditEntry>>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    let (_, key, _) = room_key_and_type(&conn, room_id).ok_or_else(|| err(Status::NotFound, "Room not found"))?;
+    if key.as_deref() != Some(admin.0.as_str()) {
+        return Err(err(Status::Forbidden, "Invalid admin key for this room"));
+    }
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, action, room_id, actor, details, created_at FROM audit_log
+             WHERE room_id = ?1 ORDER BY created_at DESC, rowid DESC LIMIT ?2",
+        )
+        .map_err(internal)?;
+    let entries = stmt
+        .query_map(params![room_id, limit.unwrap_or(50).clamp(1, 500)], |r| {
+            Ok(AuditEntry {
+                id: r.get(0)?,
+                action: r.get(1)?,
+                room_id: r.get(2)?,
+                actor: r.get(3)?,
+                details: serde_json::from_str(&r.get::<_, String>(4)?).unwrap_or(serde_json::json!({})),
+                created_at: r.get(5)?,
+            })
+        })
+        .map_err(internal)?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(Json(entries))
+}
@@ -0,0 +1,478 @@
+use crate::namespaces::ScopedDb;
+use crate::events::{ChatEvent, Events};
+use crate::models::*;
+use crate::rate_limit::{RateLimitConfig, RateLimited, RateLimiter};
+use crate::senders::{SenderPolicy, ServerToken};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{patch, post, State};
+use rusqlite::params;
+
+use super::ClientIp;
+
+/// Max content length of a streamed message once all chunks are appended (same as send_message).
+const MAX_CONTENT_LEN: usize = 10_000;
+
+/// Max length of a sent message grown with POST .../append, from `MESSAGE_APPEND_MAX_LEN`.
+fn append_max_len() -> usize {
+    std::env::var("MESSAGE_APPEND_MAX_LEN")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(100_000)
+}
+
+/// How long a stream stays open without a chunk, from `MESSAGE_STREAM_LEASE_SECS`. Once it
+/// lapses the retention sweep finalizes the message (or removes it if still empty).
+fn lease_expires_at() -> String {
+    let secs = std::env::var("MESSAGE_STREAM_LEASE_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|&s: &i64| s > 0)
+        .unwrap_or(300);
+    (chrono::Utc::now() + chrono::Duration::seconds(secs)).to_rfc3339()
+}
+
+/// POST /api/v1/rooms/<room_id>/messages/stream/start
+/// Creates a placeholder message that the sender then grows chunk by chunk.
+/// Published as a regular `message` event so clients can render it immediately.
+/// The placeholder and its stream row are written together, so a stream never lacks either.
+#[post("/api/v1/rooms/<room_id>/messages/stream/start", format = "json", data = "<body>")]
+#[allow(clippy::too_many_arguments)]
+pub fn start_message_stream(
+    db: ScopedDb<'_>,
+    events: Events<'_>,
+    rate_limiter: &State<RateLimiter>,
+    rate_config: &State<RateLimitConfig>,
+    sender_policy: &State<SenderPolicy>,
+    server_token: ServerToken,
+    ip: ClientIp,
+    room_id: &str,
+    body: Json<StartMessageStream>,
+) -> Result<RateLimited<Message>, (Status, Json<serde_json::Value>)> {
+    // Shares the send_message budget: each stream becomes one message
+    let rl = rate_limiter.check_with_info(&format!("send_msg:{}", ip.0), rate_config.messages_max, rate_config.messages_window_secs);
+    if !rl.allowed {
+        return Err((
+            Status::TooManyRequests,
+            Json(serde_json::json!({
+                "error": format!("Rate limited: max {} messages per minute", rate_config.messages_max),
+                "retry_after_secs": rl.retry_after_secs,
+                "limit": rl.limit,
+                "remaining": 0
+            })),
+        ));
+    }
+
+    let sender = body.sender.trim().to_string();
+    if sender.is_empty() || sender.len() > 100 {
+        return Err((
+            Status::BadRequest,
+            Json(serde_json::json!({"error": "Sender must be 1-100 characters"})),
+        ));
+    }
+    sender_policy.check(&sender, &server_token)?;
+    if body.content.len() > MAX_CONTENT_LEN {
+        return Err((
+            Status::BadRequest,
+            Json(serde_json::json!({"error": "Content must be at most 10000 characters"})),
+        ));
+    }
+
+    let conn = db.conn();
+
+    let room_exists: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM rooms WHERE id = ?1",
+            params![room_id],
+            |r| r.get::<_, i64>(0),
+        )
+        .map(|c| c > 0)
+        .unwrap_or(false);
+
+    if !room_exists {
+        return Err((
+            Status::NotFound,
+            Json(serde_json::json!({"error": "Room not found"})),
+        ));
+    }
+    crate::quotas::check(&conn, rate_config, &sender, 1, 0)?;
+
+    let reply_to = body
+        .reply_to
+        .as_deref()
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(String::from);
+    if let Some(ref reply_id) = reply_to {
+        let exists: bool = conn
+            .query_row(
+                "SELECT COUNT(*) FROM messages WHERE id = ?1 AND room_id = ?2",
+                params![reply_id, room_id],
+                |r| r.get::<_, i64>(0),
+            )
+            .map(|c| c > 0)
+            .unwrap_or(false);
+        if !exists {
+            return Err((
+                Status::BadRequest,
+                Json(serde_json::json!({"error": "Referenced reply_to message not found in this room"})),
+            ));
+        }
+    }
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let now = chrono::Utc::now().to_rfc3339();
+    let metadata = body.metadata.clone().unwrap_or(serde_json::json!({}));
+    let internal = |_e: rusqlite::Error| {
+        (
+            Status::InternalServerError,
+            Json(serde_json::json!({"error": "Internal server error"})),
+        )
+    };
+    let tx = conn.unchecked_transaction().map_err(internal)?;
+    let seq: i64 = tx
+        .query_row("SELECT COALESCE(MAX(seq), 0) + 1 FROM messages", [], |r| {
+            r.get(0)
+        })
+        .unwrap_or(1);
+
+    tx.execute(
+        "INSERT INTO messages (id, room_id, sender, content, metadata, created_at, reply_to, sender_type, seq) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![&id, room_id, &sender, &body.content, serde_json::to_string(&metadata).unwrap_or_default(), &now, &reply_to, &body.sender_type, seq],
+    )
+    .map_err(internal)?;
+
+    tx.execute(
+        "INSERT INTO message_streams (message_id, room_id, sender, started_at, lease_expires_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![&id, room_id, &sender, &now, lease_expires_at()],
+    )
+    .map_err(internal)?;
+    crate::quotas::record(&tx, &sender, 1, 0);
+    tx.commit().map_err(internal)?;
+
+    let msg = Message {
+        id,
+        room_id: room_id.to_string(),
+        sender,
+        content: body.content.clone(),
+        metadata,
+        created_at: now,
+        edited_at: None,
+        reply_to,
+        sender_type: body.sender_type.clone(),
+        seq,
+        pinned_at: None,
+        pinned_by: None,
+        edit_count: 0,
+        kind: "message".to_string(),
+        local_time: None,
+    };
+
+    events.publish(ChatEvent::NewMessage(msg.clone()));
+
+    Ok(RateLimited::new(Json(msg), rl))
+}
+
+/// PATCH /api/v1/rooms/<room_id>/messages/stream/<message_id>/append
+/// Appends a chunk to an in-progress streamed message and broadcasts it as `message_chunk`.
+/// Each chunk renews the stream's lease.
+#[patch(
+    "/api/v1/rooms/<room_id>/messages/stream/<message_id>/append",
+    format = "json",
+    data = "<body>"
+)]
+pub fn append_message_stream(
+    db: ScopedDb<'_>,
+    events: Events<'_>,
+    sender_policy: &State<SenderPolicy>,
+    server_token: ServerToken,
+    room_id: &str,
+    message_id: &str,
+    body: Json<AppendMessageChunk>,
+) -> Result<Json<MessageChunk>, (Status, Json<serde_json::Value>)> {
+    if body.chunk.is_empty() {
+        return Err((
+            Status::BadRequest,
+            Json(serde_json::json!({"error": "Chunk must not be empty"})),
+        ));
+    }
+    sender_policy.check(body.sender.trim(), &server_token)?;
+
+    let conn = db.conn();
+    let stream_sender = active_stream_sender(&conn, room_id, message_id)?;
+    if body.sender.trim() != stream_sender {
+        return Err((
+            Status::Forbidden,
+            Json(serde_json::json!({"error": "Only the original sender can append to this message"})),
+        ));
+    }
+
+    let current_len: i64 = conn
+        .query_row(
+            "SELECT LENGTH(CAST(content AS BLOB)) FROM messages WHERE id = ?1",
+            params![message_id],
+            |r| r.get(0),
+        )
+        .unwrap_or(0);
+    if current_len as usize + body.chunk.len() > MAX_CONTENT_LEN {
+        return Err((
+            Status::BadRequest,
+            Json(serde_json::json!({"error": "Content would exceed 10000 characters"})),
+        ));
+    }
+
+    conn.execute(
+        "UPDATE messages SET content = content || ?1 WHERE id = ?2",
+        params![&body.chunk, message_id],
+    )
+    .map_err(|_e| {
+        (
+            Status::InternalServerError,
+            Json(serde_json::json!({"error": "Internal server error"})),
+        )
+    })?;
+
+    let index: i64 = conn
+        .query_row(
+            "UPDATE message_streams SET chunk_count = chunk_count + 1, lease_expires_at = ?2 WHERE message_id = ?1 RETURNING chunk_count",
+            params![message_id, lease_expires_at()],
+            |r| r.get(0),
+        )
+        .map_err(|_e| {
+            (
+                Status::InternalServerError,
+                Json(serde_json::json!({"error": "Internal server error"})),
+            )
+        })?;
+
+    let chunk = MessageChunk {
+        message_id: message_id.to_string(),
+        room_id: room_id.to_string(),
+        sender: stream_sender,
+        chunk: body.chunk.clone(),
+        index,
+    };
+
+    events.publish(ChatEvent::MessageChunk(chunk.clone()));
+
+    Ok(Json(chunk))
+}
+
+/// POST /api/v1/rooms/<room_id>/messages/stream/<message_id>/finalize
+/// Seals a streamed message: indexes it for search/mentions and broadcasts `message_finalized`.
+#[post(
+    "/api/v1/rooms/<room_id>/messages/stream/<message_id>/finalize",
+    format = "json",
+    data = "<body>"
+)]
+pub fn finalize_message_stream(
+    db: ScopedDb<'_>,
+    events: Events<'_>,
+    sender_policy: &State<SenderPolicy>,
+    server_token: ServerToken,
+    room_id: &str,
+    message_id: &str,
+    body: Json<FinalizeMessageStream>,
+) -> Result<Json<Message>, (Status, Json<serde_json::Value>)> {
+    sender_policy.check(body.sender.trim(), &server_token)?;
+    let conn = db.conn();
+    let stream_sender = active_stream_sender(&conn, room_id, message_id)?;
+    if body.sender.trim() != stream_sender {
+        return Err((
+            Status::Forbidden,
+            Json(serde_json::json!({"error": "Only the original sender can finalize this message"})),
+        ));
+    }
+
+    let msg = super::threads::fetch_message(&conn, message_id, room_id)?;
+    if msg.content.trim().is_empty() {
+        return Err((
+            Status::BadRequest,
+            Json(serde_json::json!({"error": "Cannot finalize an empty message"})),
+        ));
+    }
+
+    conn.execute(
+        "DELETE FROM message_streams WHERE message_id = ?1",
+        params![message_id],
+    )
+    .map_err(|_e| {
+        (
+            Status::InternalServerError,
+            Json(serde_json::json!({"error": "Internal server error"})),
+        )
+    })?;
+
+    let now = chrono::Utc::now().to_rfc3339();
+    conn.execute(
+        "UPDATE rooms SET updated_at = ?1 WHERE id = ?2",
+        params![&now, room_id],
+    )
+    .ok();
+
+    // Update FTS and mention indexes now that the content is final
+    crate::db::upsert_fts(&conn, message_id);
+    crate::db::index_mentions(&conn, message_id);
+
+    events.publish(ChatEvent::MessageFinalized(msg.clone()));
+
+    Ok(Json(msg))
+}
+
+/// POST /api/v1/rooms/<room_id>/messages/<message_id>/append
+/// Adds text to the end of a sent message, for agents reporting progress into one message
+/// instead of posting many. Subscribers get only the delta as `message_appended`. Each append
+/// is up to 10,000 bytes; the message may grow to `MESSAGE_APPEND_MAX_LEN` (default 100,000).
+/// Appends are edits: each one is kept in edit history (`patch_format: "append"`) and its event
+/// is recorded in the outbox. A sensitive message that was already redacted can't be appended to.
+#[post(
+    "/api/v1/rooms/<room_id>/messages/<message_id>/append",
+    format = "json",
+    data = "<body>"
+)]
+pub fn append_to_message(
+    db: ScopedDb<'_>,
+    events: Events<'_>,
+    sender_policy: &State<SenderPolicy>,
+    server_token: ServerToken,
+    room_id: &str,
+    message_id: &str,
+    body: Json<AppendToMessage>,
+) -> Result<Json<MessageAppend>, (Status, Json<serde_json::Value>)> {
+    if body.content.is_empty() || body.content.len() > MAX_CONTENT_LEN {
+        return Err((
+            Status::BadRequest,
+            Json(serde_json::json!({"error": "Content must be 1-10000 characters"})),
+        ));
+    }
+    let sender = body.sender.trim().to_string();
+    sender_policy.check(&sender, &server_token)?;
+
+    let conn = db.conn();
+    let (existing_sender, kind, previous_content): (String, String, String) = conn
+        .query_row(
+            "SELECT sender, kind, content FROM messages WHERE id = ?1 AND room_id = ?2",
+            params![message_id, room_id],
+            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+        )
+        .map_err(|_| {
+            (
+                Status::NotFound,
+                Json(serde_json::json!({"error": "Message not found in this room"})),
+            )
+        })?;
+    if sender != existing_sender || kind == "system" {
+        return Err((
+            Status::Forbidden,
+            Json(serde_json::json!({"error": "Only the original sender can append to this message"})),
+        ));
+    }
+    let streaming: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM message_streams WHERE message_id = ?1",
+            params![message_id],
+            |r| r.get::<_, i64>(0),
+        )
+        .map(|c| c > 0)
+        .unwrap_or(false);
+    if streaming {
+        return Err((
+            Status::Conflict,
+            Json(serde_json::json!({"error": format!(
+                "Message is still streaming; use PATCH /api/v1/rooms/{room_id}/messages/stream/{message_id}/append"
+            )})),
+        ));
+    }
+    // The tombstone stays a tombstone: new text would outlive the redaction
+    let redacted: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM sensitive_messages WHERE message_id = ?1 AND redacted_at IS NOT NULL",
+            params![message_id],
+            |r| r.get::<_, i64>(0),
+        )
+        .map(|c| c > 0)
+        .unwrap_or(false);
+    if redacted {
+        return Err((
+            Status::Conflict,
+            Json(serde_json::json!({"error": "Message was redacted and can't be appended to"})),
+        ));
+    }
+    let max_len = append_max_len();
+    let length = previous_content.len() + body.content.len();
+    if length > max_len {
+        return Err((
+            Status::PayloadTooLarge,
+            Json(serde_json::json!({
+                "error": format!("Message would exceed {max_len} bytes"),
+                "length": previous_content.len(),
+                "max_length": max_len,
+            })),
+        ));
+    }
+
+    let content = format!("{previous_content}{}", body.content);
+    let appended = |msg: &Message| MessageAppend {
+        message_id: msg.id.clone(),
+        room_id: msg.room_id.clone(),
+        sender: msg.sender.clone(),
+        delta: body.content.clone(),
+        length,
+        appended_at: msg.edited_at.clone().unwrap_or_default(),
+    };
+    let (msg, event, event_id) = super::messages::save_edit(
+        &conn,
+        &events,
+        message_id,
+        &previous_content,
+        &content,
+        None,
+        &sender,
+        Some(("append", &body.content)),
+        |msg| ChatEvent::MessageAppended(appended(msg)),
+    )?;
+    events.publish_recorded(&conn, event_id, event);
+    drop(conn);
+
+    Ok(Json(appended(&msg)))
+}
+
+/// Return the sender of an in-progress stream, or the appropriate error
+/// (404 if the message doesn't exist, 409 if it was already finalized or never streamed).
+fn active_stream_sender(
+    conn: &rusqlite::Connection,
+    room_id: &str,
+    message_id: &str,
+) -> Result<String, (Status, Json<serde_json::Value>)> {
+    let sender: Option<String> = conn
+        .query_row(
+            "SELECT sender FROM message_streams WHERE message_id = ?1 AND room_id = ?2",
+            params![message_id, room_id],
+            |r| r.get(0),
+        )
+        .ok();
+    if let Some(sender) = sender {
+        return Ok(sender);
+    }
+
+    let message_exists: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM messages WHERE id = ?1 AND room_id = ?2",
+            params![message_id, room_id],
+            |r| r.get::<_, i64>(0),
+        )
+        .map(|c| c > 0)
+        .unwrap_or(false);
+
+    if message_exists {
+        Err((
+            Status::Conflict,
+            Json(serde_json::json!({"error": "Message is not being streamed (already finalized)"})),
+        ))
+    } else {
+        Err((
+            Status::NotFound,
+            Json(serde_json::json!({"error": "Message not found in this room"})),
+        ))
+    }
+}
@@ -1,24 +1,35 @@
-use crate::db::Db;
-use crate::events::{ChatEvent, EventBus};
+use crate::namespaces::ScopedDb;
+use crate::events::{ChatEvent, Events};
+use crate::fields::{Sparse, MESSAGE_FIELDS};
+use crate::i18n::{localize_message, Locale};
 use crate::models::*;
 use crate::rate_limit::{RateLimitConfig, RateLimiter};
+use crate::redaction::{RedactionConfig, REDACT_AFTER_RANGE};
+use crate::responses::{ResponseConfig, MAX_RESPONDERS, RESPONSE_TIMEOUT_RANGE};
+use crate::senders::{SenderPolicy, ServerToken};
 use rocket::http::Status;
 use rocket::serde::json::Json;
-use rocket::{delete, get, post, put, State};
+use rocket::{delete, get, patch, post, put, Either, State};
 use rusqlite::params;
 
+use super::ndjson::{stream_rows, AcceptNdjson, NdjsonStream};
 use super::{AdminKey, ClientIp};
 
 #[post("/api/v1/rooms/<room_id>/messages", format = "json", data = "<body>")]
+#[allow(clippy::too_many_arguments)]
 pub fn send_message(
-    db: &State<Db>,
-    events: &State<EventBus>,
+    db: ScopedDb<'_>,
+    events: Events<'_>,
     rate_limiter: &State<RateLimiter>,
     rate_config: &State<RateLimitConfig>,
+    sender_policy: &State<SenderPolicy>,
+    redaction_config: &State<RedactionConfig>,
+    response_config: &State<ResponseConfig>,
+    server_token: ServerToken,
     ip: ClientIp,
     room_id: &str,
     body: Json<SendMessage>,
-) -> Result<crate::rate_limit::RateLimited<Message>, (Status, Json<serde_json::Value>)> {
+) -> Result<Either<crate::rate_limit::RateLimited<Message>, (Status, Json<DeferredMessage>)>, (Status, Json<serde_json::Value>)> {
     let rl = rate_limiter.check_with_info(&format!("send_msg:{}", ip.0), rate_config.messages_max, rate_config.messages_window_secs);
     if !rl.allowed {
         return Err((
@@ -47,16 +58,60 @@ pub fn send_message(
             Json(serde_json::json!({"error": "Content must be 1-10000 characters"})),
         ));
     }
+    sender_policy.check(&sender, &server_token)?;
+    if let Some(secs) = body.redact_after_secs {
+        if !body.sensitive {
+            return Err((
+                Status::BadRequest,
+                Json(serde_json::json!({"error": "redact_after_secs requires sensitive: true"})),
+            ));
+        }
+        if !REDACT_AFTER_RANGE.contains(&secs) {
+            return Err((
+                Status::BadRequest,
+                Json(serde_json::json!({"error": "redact_after_secs must be between 60 and 2592000 (30 days)"})),
+            ));
+        }
+    }
+    if body.requires_response_from.len() > MAX_RESPONDERS {
+        return Err((
+            Status::BadRequest,
+            Json(serde_json::json!({"error": format!("requires_response_from takes at most {MAX_RESPONDERS} senders")})),
+        ));
+    }
+    if body.requires_response_from.iter().any(|r| r.trim().is_empty() || r.trim().len() > 100) {
+        return Err((
+            Status::BadRequest,
+            Json(serde_json::json!({"error": "requires_response_from entries must be 1-100 characters"})),
+        ));
+    }
+    if let Some(secs) = body.response_timeout_secs {
+        if body.requires_response_from.is_empty() {
+            return Err((
+                Status::BadRequest,
+                Json(serde_json::json!({"error": "response_timeout_secs requires requires_response_from"})),
+            ));
+        }
+        if !RESPONSE_TIMEOUT_RANGE.contains(&secs) {
+            return Err((
+                Status::BadRequest,
+                Json(serde_json::json!({"error": "response_timeout_secs must be between 60 and 604800 (7 days)"})),
+            ));
+        }
+    }
+    let scheduled_at = body
+        .scheduled_at
+        .as_deref()
+        .map(crate::scheduler::parse_scheduled_at)
+        .transpose()
+        .map_err(|e| (Status::BadRequest, Json(serde_json::json!({"error": e}))))?;
 
     let conn = db.conn();
 
     // Verify room exists
     let room_exists: bool = conn
-        .query_row(
-            "SELECT COUNT(*) FROM rooms WHERE id = ?1",
-            params![room_id],
-            |r| r.get::<_, i64>(0),
-        )
+        .prepare_cached("SELECT COUNT(*) FROM rooms WHERE id = ?1")
+        .and_then(|mut s| s.query_row(params![room_id], |r| r.get::<_, i64>(0)))
         .map(|c| c > 0)
         .unwrap_or(false);
 
@@ -66,10 +121,76 @@ pub fn send_message(
             Json(serde_json::json!({"error": "Room not found"})),
         ));
     }
-
-    let id = uuid::Uuid::new_v4().to_string();
-    let now = chrono::Utc::now().to_rfc3339();
-    let metadata = body.metadata.clone().unwrap_or(serde_json::json!({}));
+    crate::quotas::check(&conn, rate_config, &sender, 1, 0)?;
+
+    // Client-supplied IDs make retries safe: a duplicate returns 409 with the existing message
+    let id = match body.id.as_deref().map(|s| s.trim()).filter(|s| !s.is_empty()) {
+        Some(client_id) => {
+            let client_id = uuid::Uuid::parse_str(client_id)
+                .map_err(|_| {
+                    (
+                        Status::BadRequest,
+                        Json(serde_json::json!({"error": "id must be a valid UUID"})),
+                    )
+                })?
+                .hyphenated()
+                .to_string();
+            let taken: bool = conn
+                .query_row(
+                    "SELECT (SELECT COUNT(*) FROM messages WHERE id = ?1) + (SELECT COUNT(*) FROM deferred_messages WHERE id = ?1)",
+                    params![&client_id],
+                    |r| r.get::<_, i64>(0),
+                )
+                .map(|c| c > 0)
+                .unwrap_or(false);
+            if taken {
+                // Only echo the existing message back if it lives in this room
+                let existing = super::threads::fetch_message(&conn, &client_id, room_id).ok();
+                return Err((
+                    Status::Conflict,
+                    Json(serde_json::json!({
+                        "error": "A message with this id already exists",
+                        "message": existing
+                    })),
+                ));
+            }
+            client_id
+        }
+        None => uuid::Uuid::new_v4().to_string(),
+    };
+    let created = chrono::Utc::now();
+    let now = created.to_rfc3339();
+    let mut metadata = body.metadata.clone().unwrap_or(serde_json::json!({}));
+    // Clients see when a sensitive message will lose its content
+    let redact_at = body.sensitive.then(|| {
+        let secs = body.redact_after_secs.unwrap_or(redaction_config.default_redact_secs);
+        (created + chrono::Duration::seconds(secs)).to_rfc3339()
+    });
+    if let Some(ref at) = redact_at {
+        if !metadata.is_object() {
+            metadata = serde_json::json!({});
+        }
+        metadata["sensitive"] = serde_json::json!({"redact_at": at});
+    }
+    // Everyone asked, by canonical name; asking yourself doesn't count
+    let own_name = crate::db::resolve_sender(&conn, &sender);
+    let mut responders: Vec<String> = Vec::new();
+    for name in &body.requires_response_from {
+        let canonical = crate::db::resolve_sender(&conn, name.trim());
+        if canonical != own_name && !responders.contains(&canonical) {
+            responders.push(canonical);
+        }
+    }
+    let respond_by = (!responders.is_empty()).then(|| {
+        let secs = body.response_timeout_secs.unwrap_or(response_config.default_timeout_secs);
+        (created + chrono::Duration::seconds(secs)).to_rfc3339()
+    });
+    if let Some(ref due) = respond_by {
+        if !metadata.is_object() {
+            metadata = serde_json::json!({});
+        }
+        metadata["requires_response"] = serde_json::json!({"from": &responders, "due_at": due});
+    }
     let reply_to = body
         .reply_to
         .as_deref()
@@ -88,11 +209,8 @@ pub fn send_message(
     // Validate reply_to references a real message in this room
     if let Some(ref reply_id) = reply_to {
         let exists: bool = conn
-            .query_row(
-                "SELECT COUNT(*) FROM messages WHERE id = ?1 AND room_id = ?2",
-                params![reply_id, room_id],
-                |r| r.get::<_, i64>(0),
-            )
+            .prepare_cached("SELECT COUNT(*) FROM messages WHERE id = ?1 AND room_id = ?2")
+            .and_then(|mut s| s.query_row(params![reply_id, room_id], |r| r.get::<_, i64>(0)))
             .map(|c| c > 0)
             .unwrap_or(false);
         if !exists {
@@ -103,34 +221,51 @@ pub fn send_message(
         }
     }
 
+    // Scheduled posts wait until they are due, and agent posts made during the room's quiet
+    // hours wait for the window to end
+    let deliver_at = match scheduled_at {
+        Some(at) => Some(at),
+        None if crate::quiet_hours::is_agent(&conn, &own_name, sender_type.as_deref()) => {
+            crate::quiet_hours::active_until(&conn, room_id, created)
+        }
+        None => None,
+    };
+    if let Some(deliver_at) = deliver_at {
+        let held = DeferredMessage {
+            id,
+            room_id: room_id.to_string(),
+            sender,
+            sender_type,
+            content,
+            reply_to,
+            queued_at: now,
+            deliver_at: deliver_at.to_rfc3339(),
+            scheduled_at: scheduled_at.map(|at| at.to_rfc3339()),
+            deferred: true,
+        };
+        let redact_after_secs = body
+            .sensitive
+            .then(|| body.redact_after_secs.unwrap_or(redaction_config.default_redact_secs));
+        let response_timeout_secs = respond_by
+            .is_some()
+            .then(|| body.response_timeout_secs.unwrap_or(response_config.default_timeout_secs));
+        let client_metadata = body.metadata.clone().unwrap_or(serde_json::json!({}));
+        crate::quiet_hours::defer(&conn, &held, &client_metadata, redact_after_secs, &responders, response_timeout_secs)
+            .map_err(|_| {
+                (
+                    Status::InternalServerError,
+                    Json(serde_json::json!({"error": "Internal server error"})),
+                )
+            })?;
+        return Ok(Either::Right((Status::Accepted, Json(held))));
+    }
+
     // Compute next monotonic seq
     let seq: i64 = conn
-        .query_row("SELECT COALESCE(MAX(seq), 0) + 1 FROM messages", [], |r| {
-            r.get(0)
-        })
+        .prepare_cached("SELECT COALESCE(MAX(seq), 0) + 1 FROM messages")
+        .and_then(|mut s| s.query_row([], |r| r.get(0)))
         .unwrap_or(1);
 
-    conn.execute(
-        "INSERT INTO messages (id, room_id, sender, content, metadata, created_at, reply_to, sender_type, seq) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
-        params![&id, room_id, &sender, &content, serde_json::to_string(&metadata).unwrap_or_default(), &now, &reply_to, &sender_type, seq],
-    )
-    .map_err(|_e| {
-        (
-            Status::InternalServerError,
-            Json(serde_json::json!({"error": "Internal server error"})),
-        )
-    })?;
-
-    // Update room's updated_at
-    conn.execute(
-        "UPDATE rooms SET updated_at = ?1 WHERE id = ?2",
-        params![&now, room_id],
-    )
-    .ok();
-
-    // Update FTS index
-    crate::db::upsert_fts(&conn, &id);
-
     let msg = Message {
         id,
         room_id: room_id.to_string(),
@@ -145,12 +280,81 @@ pub fn send_message(
         pinned_at: None,
         pinned_by: None,
         edit_count: 0,
+        kind: "message".to_string(),
+        local_time: None,
+    };
+    let internal = || {
+        (
+            Status::InternalServerError,
+            Json(serde_json::json!({"error": "Internal server error"})),
+        )
+    };
+
+    // The message, its redaction schedule and its event commit together or not at all
+    let tx = conn.unchecked_transaction().map_err(|_| internal())?;
+    tx.prepare_cached(
+        "INSERT INTO messages (id, room_id, sender, content, metadata, created_at, reply_to, sender_type, seq) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+    )
+    .and_then(|mut s| {
+        s.execute(params![
+            &msg.id,
+            room_id,
+            &msg.sender,
+            &msg.content,
+            serde_json::to_string(&msg.metadata).unwrap_or_default(),
+            &msg.created_at,
+            &msg.reply_to,
+            &msg.sender_type,
+            seq
+        ])
+    })
+    .map_err(|_e| internal())?;
+
+    // Never keep a sensitive message we couldn't schedule for redaction
+    if let Some(ref at) = redact_at
+        && let Err(e) = crate::redaction::schedule(&tx, &msg.id, at)
+    {
+        eprintln!("⚠️ Failed to schedule redaction for message {}: {e}", msg.id);
+        return Err(internal());
+    }
+    if let Some(ref due) = respond_by {
+        crate::responses::request(&tx, &msg.id, &responders, &msg.created_at, due).map_err(|_e| internal())?;
+    }
+    let event_id = crate::outbox::record(&tx, &ChatEvent::NewMessage(msg.clone()), Some(events.request_id()))
+        .map_err(|_e| internal())?;
+    tx.commit().map_err(|_e| internal())?;
+    crate::responses::clear_for_reply(&conn, &msg);
+
+    crate::quotas::record(&conn, &msg.sender, 1, 0);
+
+    // Update room's updated_at
+    conn.prepare_cached("UPDATE rooms SET updated_at = ?1 WHERE id = ?2")
+        .and_then(|mut s| s.execute(params![&msg.created_at, room_id]))
+        .ok();
+
+    // Update FTS and mention indexes
+    crate::db::upsert_fts(&conn, &msg.id);
+    crate::db::index_mentions(&conn, &msg.id);
+
+    // A sender's first post in the room triggers the room's welcome, if configured
+    let first_post = conn
+        .prepare_cached("SELECT COUNT(*) FROM messages WHERE room_id = ?1 AND sender = ?2 AND kind = 'message'")
+        .and_then(|mut s| s.query_row(params![room_id, &msg.sender], |r| r.get::<_, i64>(0)))
+        .map(|c| c == 1)
+        .unwrap_or(false);
+    let welcome = if first_post {
+        super::welcome::deliver_welcome(&conn, room_id, &msg.sender)
+    } else {
+        None
     };
 
     // Publish event for SSE
-    events.publish(ChatEvent::NewMessage(msg.clone()));
+    events.publish_recorded(&conn, event_id, ChatEvent::NewMessage(msg.clone()));
+    if let Some(welcome) = welcome {
+        events.publish(ChatEvent::NewMessage(welcome));
+    }
 
-    Ok(crate::rate_limit::RateLimited::new(Json(msg), rl))
+    Ok(Either::Left(crate::rate_limit::RateLimited::new(Json(msg), rl)))
 }
 
 #[put(
@@ -159,8 +363,10 @@ pub fn send_message(
     data = "<body>"
 )]
 pub fn edit_message(
-    db: &State<Db>,
-    events: &State<EventBus>,
+    db: ScopedDb<'_>,
+    events: Events<'_>,
+    sender_policy: &State<SenderPolicy>,
+    server_token: ServerToken,
     room_id: &str,
     message_id: &str,
     body: Json<EditMessage>,
@@ -180,6 +386,7 @@ pub fn edit_message(
             Json(serde_json::json!({"error": "Content must be 1-10000 characters"})),
         ));
     }
+    sender_policy.check(&sender, &server_token)?;
 
     let conn = db.conn();
 
@@ -205,7 +412,6 @@ pub fn edit_message(
         ));
     }
 
-    // Save previous content to edit history
     let previous_content: String = conn
         .query_row(
             "SELECT content FROM messages WHERE id = ?1",
@@ -213,91 +419,195 @@ pub fn edit_message(
             |r| r.get(0),
         )
         .unwrap_or_default();
+    let (msg, event, event_id) = save_edit(
+        &conn,
+        &events,
+        message_id,
+        &previous_content,
+        &content,
+        body.metadata.as_ref(),
+        &sender,
+        None,
+        |msg| ChatEvent::MessageEdited(msg.clone()),
+    )?;
+
+    events.publish_recorded(&conn, event_id, event);
+
+    Ok(Json(msg))
+}
 
+/// Record the previous content in edit history (with the patch, for PATCH edits and appends),
+/// store the new content and optional metadata, reindex, and return the updated message with
+/// the event built by `event` and its outbox id, committed along with the edit.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn save_edit(
+    conn: &rusqlite::Connection,
+    events: &Events<'_>,
+    message_id: &str,
+    previous_content: &str,
+    content: &str,
+    metadata: Option<&serde_json::Value>,
+    editor: &str,
+    patch: Option<(&str, &str)>,
+    event: impl FnOnce(&Message) -> ChatEvent,
+) -> Result<(Message, ChatEvent, i64), (Status, Json<serde_json::Value>)> {
+    let internal = |_e: rusqlite::Error| {
+        (
+            Status::InternalServerError,
+            Json(serde_json::json!({"error": "Internal server error"})),
+        )
+    };
     let now = chrono::Utc::now().to_rfc3339();
 
-    conn.execute(
-        "INSERT INTO message_edits (id, message_id, previous_content, edited_at, editor) VALUES (?1, ?2, ?3, ?4, ?5)",
-        params![uuid::Uuid::new_v4().to_string(), message_id, &previous_content, &now, &sender],
+    let tx = conn.unchecked_transaction().map_err(internal)?;
+    tx.execute(
+        "INSERT INTO message_edits (id, message_id, previous_content, edited_at, editor, patch_format, patch) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            uuid::Uuid::new_v4().to_string(),
+            message_id,
+            previous_content,
+            &now,
+            editor,
+            patch.map(|(format, _)| format),
+            patch.map(|(_, patch)| patch)
+        ],
     ).ok();
 
-    let metadata = body.metadata.clone();
-
     // Update content and edited_at; optionally update metadata
-    if let Some(ref meta) = metadata {
-        conn.execute(
+    if let Some(meta) = metadata {
+        tx.execute(
             "UPDATE messages SET content = ?1, metadata = ?2, edited_at = ?3 WHERE id = ?4",
-            params![
-                &content,
-                serde_json::to_string(meta).unwrap_or_default(),
-                &now,
-                message_id
-            ],
+            params![content, serde_json::to_string(meta).unwrap_or_default(), &now, message_id],
         )
-        .map_err(|_e| {
-            (
-                Status::InternalServerError,
-                Json(serde_json::json!({"error": "Internal server error"})),
-            )
-        })?;
+        .map_err(internal)?;
     } else {
-        conn.execute(
+        tx.execute(
             "UPDATE messages SET content = ?1, edited_at = ?2 WHERE id = ?3",
-            params![&content, &now, message_id],
+            params![content, &now, message_id],
         )
-        .map_err(|_e| {
-            (
-                Status::InternalServerError,
-                Json(serde_json::json!({"error": "Internal server error"})),
-            )
-        })?;
+        .map_err(internal)?;
     }
 
-    // Fetch the updated message
-    let msg = conn
+    let msg = tx
         .query_row(
-            "SELECT m.id, m.room_id, m.sender, m.content, m.metadata, m.created_at, m.edited_at, m.reply_to, m.sender_type, m.seq, m.pinned_at, m.pinned_by, \
-             (SELECT COUNT(*) FROM message_edits WHERE message_id = m.id) \
-             FROM messages m WHERE m.id = ?1",
+            "SELECT id, room_id, sender, content, metadata, created_at, edited_at, reply_to, sender_type, seq, pinned_at, pinned_by, kind, \
+             (SELECT COUNT(*) FROM message_edits WHERE message_id = messages.id) FROM messages WHERE id = ?1",
             params![message_id],
-            |row| {
-                let metadata_str: String = row.get(4)?;
-                Ok(Message {
-                    id: row.get(0)?,
-                    room_id: row.get(1)?,
-                    sender: row.get(2)?,
-                    content: row.get(3)?,
-                    metadata: serde_json::from_str(&metadata_str).unwrap_or(serde_json::json!({})),
-                    created_at: row.get(5)?,
-                    edited_at: row.get(6)?,
-                    reply_to: row.get(7)?,
-                    sender_type: row.get(8)?,
-                    seq: row.get(9)?,
-                    pinned_at: row.get(10)?,
-                    pinned_by: row.get(11)?,
-                    edit_count: row.get(12)?,
-                })
-            },
+            message_from_row,
         )
-        .map_err(|_e| {
-            (
-                Status::InternalServerError,
-                Json(serde_json::json!({"error": "Internal server error"})),
-            )
-        })?;
+        .map_err(internal)?;
+    let event = event(&msg);
+    let event_id = crate::outbox::record(&tx, &event, Some(events.request_id())).map_err(internal)?;
+    tx.commit().map_err(internal)?;
+
+    // Update FTS and mention indexes
+    crate::db::upsert_fts(conn, message_id);
+    crate::db::index_mentions(conn, message_id);
+    Ok((msg, event, event_id))
+}
 
-    // Update FTS index
-    crate::db::upsert_fts(&conn, message_id);
+/// PATCH /api/v1/rooms/<room_id>/messages/<message_id> — edit by patch instead of full
+/// replacement: `json_patch` (RFC 6902 against `{"content": ..., "metadata": {...}}`) or `diff`
+/// (unified diff against the content). The patch is kept in edit history. 409 when it no
+/// longer applies, or when `base_edit_count` shows someone else edited first.
+#[patch(
+    "/api/v1/rooms/<room_id>/messages/<message_id>",
+    format = "json",
+    data = "<body>"
+)]
+#[allow(clippy::too_many_arguments)]
+pub fn patch_message(
+    db: ScopedDb<'_>,
+    events: Events<'_>,
+    sender_policy: &State<SenderPolicy>,
+    server_token: ServerToken,
+    room_id: &str,
+    message_id: &str,
+    body: Json<PatchMessage>,
+) -> Result<Json<Message>, (Status, Json<serde_json::Value>)> {
+    let err = |status: Status, msg: &str| (status, Json(serde_json::json!({"error": msg})));
+    let sender = body.sender.trim().to_string();
+    if sender.is_empty() || sender.len() > 100 {
+        return Err(err(Status::BadRequest, "Sender must be 1-100 characters"));
+    }
+    sender_policy.check(&sender, &server_token)?;
 
-    events.publish(ChatEvent::MessageEdited(msg.clone()));
+    let conn = db.conn();
+    let (existing_sender, previous_content, metadata_str, edit_count): (String, String, String, i64) = conn
+        .query_row(
+            "SELECT sender, content, metadata, (SELECT COUNT(*) FROM message_edits WHERE message_id = messages.id) \
+             FROM messages WHERE id = ?1 AND room_id = ?2",
+            params![message_id, room_id],
+            |r| Ok((r.get(0)?, r.get(1)?, r.get::<_, Option<String>>(2)?.unwrap_or_default(), r.get(3)?)),
+        )
+        .map_err(|_| err(Status::NotFound, "Message not found"))?;
+    if existing_sender != sender {
+        return Err(err(Status::Forbidden, "Only the original sender can edit this message"));
+    }
+    if let Some(base) = body.base_edit_count
+        && base != edit_count
+    {
+        return Err(err(
+            Status::Conflict,
+            &format!("Message has {edit_count} edits, expected {base}; fetch it and rebase the patch"),
+        ));
+    }
+
+    let conflict = |e: crate::patch::PatchError| match e {
+        crate::patch::PatchError::Invalid(m) => err(Status::BadRequest, &format!("Invalid patch: {m}")),
+        crate::patch::PatchError::Conflict(m) => err(Status::Conflict, &format!("Patch does not apply: {m}")),
+    };
+    let (content, metadata, patch) = match (&body.json_patch, &body.diff) {
+        (Some(ops), None) => {
+            let old_metadata: serde_json::Value = serde_json::from_str(&metadata_str).unwrap_or(serde_json::json!({}));
+            let mut doc = serde_json::json!({"content": &previous_content, "metadata": &old_metadata});
+            crate::patch::apply_json_patch(&mut doc, ops).map_err(conflict)?;
+            let content = match doc.get("content") {
+                Some(serde_json::Value::String(c)) => c.clone(),
+                _ => return Err(err(Status::UnprocessableEntity, "Patched document must keep a string 'content'")),
+            };
+            let metadata = doc.get("metadata").cloned().unwrap_or(serde_json::json!({}));
+            if !metadata.is_object() {
+                return Err(err(Status::UnprocessableEntity, "Patched 'metadata' must be an object"));
+            }
+            let metadata = (metadata != old_metadata).then_some(metadata);
+            (content, metadata, ("json-patch", serde_json::to_string(ops).unwrap_or_default()))
+        }
+        (None, Some(diff)) => {
+            let content = crate::patch::apply_unified_diff(&previous_content, diff).map_err(conflict)?;
+            (content, None, ("diff", diff.clone()))
+        }
+        _ => return Err(err(Status::BadRequest, "Provide exactly one of 'json_patch' or 'diff'")),
+    };
+    let content = content.trim().to_string();
+    if content.is_empty() || content.len() > 10_000 {
+        return Err(err(Status::BadRequest, "Content must be 1-10000 characters"));
+    }
+    if patch.1.len() > 100_000 {
+        return Err(err(Status::BadRequest, "Patch must be at most 100KB"));
+    }
+
+    let (msg, event, event_id) = save_edit(
+        &conn,
+        &events,
+        message_id,
+        &previous_content,
+        &content,
+        metadata.as_ref(),
+        &sender,
+        Some((patch.0, &patch.1)),
+        |msg| ChatEvent::MessageEdited(msg.clone()),
+    )?;
+    events.publish_recorded(&conn, event_id, event);
+    drop(conn);
 
     Ok(Json(msg))
 }
 
 #[delete("/api/v1/rooms/<room_id>/messages/<message_id>?<sender>")]
 pub fn delete_message(
-    db: &State<Db>,
-    events: &State<EventBus>,
+    db: ScopedDb<'_>,
+    events: Events<'_>,
     room_id: &str,
     message_id: &str,
     sender: Option<&str>,
@@ -355,31 +665,36 @@ pub fn delete_message(
     // Remove from FTS index before deleting
     crate::db::delete_fts(&conn, message_id);
 
-    conn.execute(
-        "DELETE FROM messages WHERE id = ?1 AND room_id = ?2",
-        params![message_id, room_id],
-    )
-    .map_err(|_e| {
+    let event = ChatEvent::MessageDeleted {
+        id: message_id.to_string(),
+        room_id: room_id.to_string(),
+    };
+    let internal = |_e: rusqlite::Error| {
         (
             Status::InternalServerError,
             Json(serde_json::json!({"error": "Internal server error"})),
         )
-    })?;
+    };
+    let tx = conn.unchecked_transaction().map_err(internal)?;
+    tx.execute(
+        "DELETE FROM messages WHERE id = ?1 AND room_id = ?2",
+        params![message_id, room_id],
+    )
+    .map_err(internal)?;
+    let event_id = crate::outbox::record(&tx, &event, Some(events.request_id())).map_err(internal)?;
+    tx.commit().map_err(internal)?;
 
-    events.publish(ChatEvent::MessageDeleted {
-        id: message_id.to_string(),
-        room_id: room_id.to_string(),
-    });
+    events.publish_recorded(&conn, event_id, event);
 
     Ok(Json(serde_json::json!({"deleted": true})))
 }
 
 #[get(
-    "/api/v1/rooms/<room_id>/messages?<since>&<limit>&<before>&<sender>&<sender_type>&<after>&<exclude_sender>&<before_seq>&<latest>"
+    "/api/v1/rooms/<room_id>/messages?<since>&<limit>&<before>&<sender>&<sender_type>&<after>&<exclude_sender>&<before_seq>&<latest>&<envelope>&<kind>&<fields>&<label>&<tz>"
 )]
 #[allow(clippy::too_many_arguments)]
 pub fn get_messages(
-    db: &State<Db>,
+    db: ScopedDb<'_>,
     room_id: &str,
     since: Option<&str>,
     limit: Option<i64>,
@@ -390,7 +705,16 @@ pub fn get_messages(
     exclude_sender: Option<&str>,
     before_seq: Option<i64>,
     latest: Option<i64>,
-) -> Result<Json<Vec<Message>>, (Status, Json<serde_json::Value>)> {
+    envelope: Option<bool>,
+    kind: Option<&str>,
+    fields: Option<&str>,
+    label: Option<&str>,
+    tz: Option<&str>,
+    locale: Locale,
+    ndjson: AcceptNdjson,
+) -> Result<Either<Json<ListOf<Sparse<Message>>>, NdjsonStream>, (Status, Json<serde_json::Value>)> {
+    let fields = crate::fields::parse(fields, MESSAGE_FIELDS)?;
+
     // ?latest=N is a convenience param: returns the N most recent messages in
     // chronological order. Equivalent to before_seq=i64::MAX&limit=N.
     // If before_seq or after is also set, ?latest is ignored (explicit wins).
@@ -400,14 +724,12 @@ pub fn get_messages(
     };
 
     let conn = db.conn();
+    let tz = crate::timezones::resolve(&conn, tz)?;
 
     // Verify room exists
     let room_exists: bool = conn
-        .query_row(
-            "SELECT COUNT(*) FROM rooms WHERE id = ?1",
-            params![room_id],
-            |r| r.get::<_, i64>(0),
-        )
+        .prepare_cached("SELECT COUNT(*) FROM rooms WHERE id = ?1")
+        .and_then(|mut s| s.query_row(params![room_id], |r| r.get::<_, i64>(0)))
         .map(|c| c > 0)
         .unwrap_or(false);
 
@@ -418,10 +740,12 @@ pub fn get_messages(
         ));
     }
 
+    // NDJSON is for bulk pulls: no default page size or cap
+    let stream_limit = limit.map(|l| l.max(1));
     let limit = limit.unwrap_or(50).clamp(1, 500);
 
     let mut sql = String::from(
-        "SELECT id, room_id, sender, content, metadata, created_at, edited_at, reply_to, sender_type, seq, pinned_at, pinned_by, \
+        "SELECT id, room_id, sender, content, metadata, created_at, edited_at, reply_to, sender_type, seq, pinned_at, pinned_by, kind, \
          (SELECT COUNT(*) FROM message_edits WHERE message_id = messages.id) FROM messages WHERE room_id = ?1",
     );
     let mut param_values: Vec<String> = vec![room_id.to_string()];
@@ -448,8 +772,8 @@ pub fn get_messages(
         idx += 1;
     }
     if let Some(sender_val) = sender {
-        sql.push_str(&format!(" AND sender = ?{idx}"));
-        param_values.push(sender_val.to_string());
+        sql.push_str(&format!(" AND {}", crate::db::sender_identity_sql("sender", idx)));
+        param_values.push(crate::db::resolve_sender(&conn, sender_val));
         idx += 1;
     }
     if let Some(sender_type_val) = sender_type {
@@ -457,6 +781,16 @@ pub fn get_messages(
         param_values.push(sender_type_val.to_string());
         idx += 1;
     }
+    if let Some(kind_val) = kind {
+        sql.push_str(&format!(" AND kind = ?{idx}"));
+        param_values.push(kind_val.to_string());
+        idx += 1;
+    }
+    if let Some(label_val) = label {
+        sql.push_str(&format!(" AND {}", super::label_filter_sql("messages.id", idx)));
+        param_values.push(super::label_param(label_val));
+        idx += 1;
+    }
     if let Some(exclude_val) = exclude_sender {
         // Support comma-separated list: ?exclude_sender=Forge,Drift,Lux
         let excluded: Vec<&str> = exclude_val
@@ -481,14 +815,38 @@ pub fn get_messages(
     // When using before_seq without after, we want the most recent N messages
     // before that seq. Use DESC ordering and reverse the results.
     let use_desc = before_seq.is_some() && after.is_none();
+
+    if ndjson.0 {
+        let reader = db.open_reader().map_err(|_e| {
+            (
+                Status::InternalServerError,
+                Json(serde_json::json!({"error": "Internal server error"})),
+            )
+        })?;
+        let limit_clause = stream_limit.map(|n| format!(" LIMIT {n}")).unwrap_or_default();
+        let sql = if use_desc {
+            format!("SELECT * FROM ({sql} ORDER BY seq DESC{limit_clause}) ORDER BY seq ASC")
+        } else {
+            format!("{sql} ORDER BY seq ASC{limit_clause}")
+        };
+        let params = param_values.into_iter().map(rusqlite::types::Value::Text).collect();
+        let locale = locale.0;
+        return Ok(Either::Right(stream_rows(reader, sql, params, move |row| {
+            let mut msg = message_from_row(row)?;
+            localize_message(&mut msg, locale);
+            crate::timezones::apply(&mut msg, tz);
+            Ok(Sparse::new(msg, &fields))
+        })));
+    }
     if use_desc {
         sql.push_str(&format!(" ORDER BY seq DESC LIMIT ?{idx}"));
     } else {
         sql.push_str(&format!(" ORDER BY seq ASC LIMIT ?{idx}"));
     }
-    param_values.push(limit.to_string());
+    // Fetch limit+1 to detect whether there are more messages in the paging direction
+    param_values.push((limit + 1).to_string());
 
-    let mut stmt = conn.prepare(&sql).map_err(|_e| {
+    let mut stmt = conn.prepare_cached(&sql).map_err(|_e| {
         (
             Status::InternalServerError,
             Json(serde_json::json!({"error": "Internal server error"})),
@@ -501,24 +859,7 @@ pub fn get_messages(
         .collect();
 
     let mut messages: Vec<Message> = stmt
-        .query_map(params_refs.as_slice(), |row| {
-            let metadata_str: String = row.get(4)?;
-            Ok(Message {
-                id: row.get(0)?,
-                room_id: row.get(1)?,
-                sender: row.get(2)?,
-                content: row.get(3)?,
-                metadata: serde_json::from_str(&metadata_str).unwrap_or(serde_json::json!({})),
-                created_at: row.get(5)?,
-                edited_at: row.get(6)?,
-                reply_to: row.get(7)?,
-                sender_type: row.get(8)?,
-                seq: row.get(9)?,
-                pinned_at: row.get(10)?,
-                pinned_by: row.get(11)?,
-                edit_count: row.get(12)?,
-            })
-        })
+        .query_map(params_refs.as_slice(), message_from_row)
         .map_err(|_e| {
             (
                 Status::InternalServerError,
@@ -528,17 +869,62 @@ pub fn get_messages(
         .filter_map(|r| r.ok())
         .collect();
 
+    let has_more = messages.len() as i64 > limit;
+    messages.truncate(limit as usize);
+
     // Reverse DESC results to return in chronological order
     if use_desc {
         messages.reverse();
     }
+    for msg in &mut messages {
+        localize_message(msg, locale.0);
+        crate::timezones::apply(msg, tz);
+    }
+
+    if !envelope.unwrap_or(false) {
+        return Ok(Either::Left(Json(ListResponse::Plain(Sparse::all(messages, &fields)))));
+    }
+
+    // Paging backwards (before_seq/latest) continues from the oldest message; forwards from the newest
+    let next_cursor = if !has_more {
+        None
+    } else if use_desc {
+        messages.first().map(|m| m.seq)
+    } else {
+        messages.last().map(|m| m.seq)
+    };
+    Ok(Either::Left(Json(ListResponse::Envelope(ListEnvelope {
+        items: Sparse::all(messages, &fields),
+        next_cursor,
+        has_more,
+    }))))
+}
 
-    Ok(Json(messages))
+/// Map a `get_messages` row (id … kind, edit count) to a Message.
+pub(super) fn message_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Message> {
+    let metadata_str: String = row.get(4)?;
+    Ok(Message {
+        id: row.get(0)?,
+        room_id: row.get(1)?,
+        sender: row.get(2)?,
+        content: row.get(3)?,
+        metadata: serde_json::from_str(&metadata_str).unwrap_or(serde_json::json!({})),
+        created_at: row.get(5)?,
+        edited_at: row.get(6)?,
+        reply_to: row.get(7)?,
+        sender_type: row.get(8)?,
+        seq: row.get(9)?,
+        pinned_at: row.get(10)?,
+        pinned_by: row.get(11)?,
+        edit_count: row.get(13)?,
+        kind: row.get(12)?,
+        local_time: None,
+    })
 }
 
 #[get("/api/v1/rooms/<room_id>/messages/<message_id>/edits")]
 pub fn get_edit_history(
-    db: &State<Db>,
+    db: ScopedDb<'_>,
     room_id: &str,
     message_id: &str,
 ) -> Result<Json<EditHistoryResponse>, (Status, Json<serde_json::Value>)> {
@@ -561,7 +947,7 @@ pub fn get_edit_history(
     // Fetch edit history (chronological: oldest edit first)
     let mut stmt = conn
         .prepare(
-            "SELECT id, message_id, previous_content, edited_at, editor \
+            "SELECT id, message_id, previous_content, edited_at, editor, patch_format, patch \
              FROM message_edits WHERE message_id = ?1 ORDER BY edited_at ASC",
         )
         .map_err(|_| {
@@ -578,6 +964,8 @@ pub fn get_edit_history(
             previous_content: row.get(2)?,
             edited_at: row.get(3)?,
             editor: row.get(4)?,
+            patch_format: row.get(5)?,
+            patch: row.get(6)?,
         })
     }) {
         Ok(rows) => rows.filter_map(|r| r.ok()).collect(),
@@ -0,0 +1,50 @@
+use crate::migrations::{self, MIGRATIONS};
+use crate::models::{AppliedMigrationInfo, MigrationStatus, PendingMigrationInfo};
+use crate::namespaces::ScopedDb;
+use crate::senders::{SenderPolicy, ServerToken};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{get, State};
+
+fn err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+/// GET /api/v1/admin/migrations — schema version, applied and pending migrations (server token required)
+#[get("/api/v1/admin/migrations")]
+pub fn migration_status(
+    db: ScopedDb<'_>,
+    sender_policy: &State<SenderPolicy>,
+    server_token: ServerToken,
+) -> Result<Json<MigrationStatus>, (Status, Json<serde_json::Value>)> {
+    sender_policy.check_server_token(&server_token)?;
+    let conn = db.conn();
+    let db_err = |_| err(Status::InternalServerError, "Database error");
+    let current_version = migrations::current_version(&conn).map_err(db_err)?;
+    let applied = migrations::applied(&conn)
+        .map_err(db_err)?
+        .into_iter()
+        .map(|m| AppliedMigrationInfo {
+            checksum_ok: MIGRATIONS
+                .iter()
+                .any(|k| k.version == m.version && migrations::checksum(k.sql) == m.checksum),
+            version: m.version,
+            name: m.name,
+            applied_at: m.applied_at,
+        })
+        .collect();
+    let pending = migrations::pending(&conn)
+        .map_err(db_err)?
+        .into_iter()
+        .map(|m| PendingMigrationInfo {
+            version: m.version,
+            name: m.name.to_string(),
+        })
+        .collect();
+    Ok(Json(MigrationStatus {
+        current_version,
+        latest_version: migrations::latest_version(),
+        applied,
+        pending,
+    }))
+}
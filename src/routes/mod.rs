@@ -1,55 +1,139 @@
 // Route module decomposition — each domain area in its own file.
 // Shared types (request guards, trackers) live here; route functions in submodules.
 
+mod admin_lockout;
 mod bookmarks;
+mod conversations;
+mod costs;
+mod decisions;
 mod broadcast;
+mod dev;
 mod discover;
 mod dm;
 mod export;
 mod files;
+mod flags;
+mod heatmap;
+mod import;
 mod incoming_hooks;
+mod ip_policy;
+mod labels;
+mod locks;
+mod maintenance;
+mod members;
 mod mentions;
+mod merge;
+mod message_streams;
 mod messages;
+mod migrations;
+mod moves;
+mod ndjson;
+mod notifications;
 mod participants;
+mod pending_responses;
 mod pins;
 mod presence;
 mod profiles;
+mod queue;
+mod quiet_hours;
+mod scheduled;
+mod quotas;
 mod reactions;
 mod read_positions;
+mod room_stats;
 mod rooms;
+mod sample;
+mod status;
 mod search;
+mod search_index;
+mod sender_export;
+mod server_config;
+mod server_webhooks;
 mod stream;
+mod subscriptions;
 mod system;
 mod typing;
+mod upload_policy;
+mod retention_notices;
+mod snapshots;
+mod commands;
+mod push;
 mod threads;
+mod topics;
 mod webhook_routes;
+mod welcome;
 
 // --- Re-exports (all route functions used by lib.rs mount) ---
 
 pub use bookmarks::{add_bookmark, remove_bookmark, list_bookmarks};
 pub use broadcast::broadcast_message;
+pub use conversations::room_conversations;
+pub use dev::dev_seed;
 pub use discover::discover as service_discover;
-pub use export::export_room;
+pub use export::{export_room, import_room};
+pub use sender_export::export_sender;
 pub use dm::{send_dm, list_dm_conversations, get_dm_conversation};
+pub use locks::{acquire_lock, get_lock, list_locks, release_lock, renew_lock};
+pub use ip_policy::ip_policy_denied;
+pub use admin_lockout::admin_key_locked;
+pub use maintenance::{maintenance_blocked, maintenance_status, set_maintenance};
+pub use members::{add_room_member, list_room_members, membership_denied, remove_room_member};
 pub use mentions::{get_mentions, get_unread_mentions};
+pub use notifications::{mark_reactions_read, reaction_notifications};
+pub use pending_responses::pending_responses;
+pub use merge::{merge_rooms, room_audit_log};
+pub use heatmap::activity_heatmap;
+pub use costs::cost_stats;
+pub use decisions::room_decisions;
+pub use import::{get_import, list_imports, start_import};
 pub use files::{delete_file, download_file, file_info, list_files, upload_file};
-pub use messages::{delete_message, edit_message, get_edit_history, get_messages, send_message};
-pub use participants::room_participants;
-pub use pins::{list_pins, pin_message, unpin_message};
-pub use presence::{global_presence, room_presence};
+pub use flags::{flag_message, list_flags, resolve_flag};
+pub use labels::{add_labels, get_labels, remove_label, room_label_summary};
+pub(crate) use labels::{label_filter_sql, label_param};
+pub use message_streams::{append_message_stream, append_to_message, finalize_message_stream, start_message_stream};
+pub use migrations::migration_status;
+pub use messages::{delete_message, edit_message, get_edit_history, get_messages, patch_message, send_message};
+pub use moves::move_message;
+pub use participants::{delete_room_role, list_room_roles, room_mentionables, room_participants, set_room_role};
+pub use pins::{list_pins, pin_history, pin_message, unpin_message};
+pub use presence::{get_device_state, global_presence, report_device_state, room_presence};
 pub use profiles::{delete_profile, get_profile, list_profiles, upsert_profile};
-pub use read_positions::{get_read_positions, get_unread, update_read_position};
-pub use reactions::{add_reaction, get_reactions, get_room_reactions, remove_reaction};
-pub use rooms::{archive_room, create_room, delete_room, get_room, list_rooms, unarchive_room, update_room};
+pub use queue::{claim_queue_item, complete_queue_item, enqueue_item, list_queue, release_queue_item};
+pub use quotas::{get_quota, set_quota};
+pub use read_positions::{
+    get_message_read_by, get_read_positions, get_unread, get_unread_threads, update_read_position, update_thread_read_position,
+};
+pub use reactions::{add_reaction, bulk_reactions, get_reactions, get_room_reactions, remove_reaction};
+pub use room_stats::room_stats;
+pub use rooms::{
+    archive_room, create_room, delete_room, get_room, list_rooms, room_aliases, unarchive_room, update_room,
+};
+pub(crate) use dm::get_or_create_dm_room;
+pub(crate) use rooms::normalize_tags;
+pub use sample::sample_messages;
+pub use status::{clear_status, get_status, list_statuses, status_history, update_status};
 pub use search::{activity_feed, search_messages};
-pub use stream::message_stream;
-pub use threads::get_thread;
+pub use search_index::{repair_search_index, search_index_status};
+pub use server_webhooks::{create_server_webhook, delete_server_webhook, list_server_webhooks};
+pub use stream::{firehose_stream, list_stream_connections, message_stream};
+pub use subscriptions::{get_room_subscriptions, set_room_subscriptions};
+pub use threads::{get_thread, get_thread_stats};
+pub use topics::{get_topic, set_topic, topic_history};
 pub use system::{
-    health, skill_md, llms_txt_api, llms_txt_root, not_found, openapi_json, run_retention_now, skills_index,
-    skills_skill_md, api_skills_skill_md, spa_fallback, stats, too_many_requests,
+    api_docs, api_docs_enabled, health, skill_md, llms_txt_api, llms_txt_root, not_found, openapi_json, run_retention_now, skills_index,
+    skills_skill_md, skills_json, api_skills_skill_md, slow_queries, spa_fallback, stats, too_many_requests,
 };
 pub use typing::notify_typing;
+pub use upload_policy::{delete_upload_policy, get_upload_policy, set_upload_policy};
+pub use quiet_hours::{delete_quiet_hours, get_quiet_hours, quiet_hours_queue, set_quiet_hours};
+pub use scheduled::{cancel_scheduled, list_scheduled, room_calendar};
+pub use server_config::{export_server_config, import_server_config};
+pub use retention_notices::{get_retention_notice, postpone_retention};
+pub use push::{create_push_subscription, delete_push_subscription, vapid_public_key};
+pub use commands::{delete_commands, list_commands, register_commands, room_help};
+pub use snapshots::{create_snapshot, delete_snapshot_schedule, get_snapshot_schedule, list_snapshots, set_snapshot_schedule};
 pub use webhook_routes::{create_webhook, delete_webhook, get_webhook_deliveries, list_webhooks, update_webhook};
+pub use welcome::{delete_room_welcome, get_room_welcome, set_room_welcome};
 pub use incoming_hooks::{
     create_incoming_webhook, delete_incoming_webhook, list_incoming_webhooks,
     post_via_hook, update_incoming_webhook,
@@ -62,6 +146,9 @@ use rocket::request::{FromRequest, Outcome, Request};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex as StdMutex, RwLock};
 
+use crate::sessions::ClientSession;
+
+/// The client address rate limits are keyed on.
 pub struct ClientIp(pub String);
 
 #[rocket::async_trait]
@@ -69,13 +156,14 @@ impl<'r> FromRequest<'r> for ClientIp {
     type Error = ();
 
     async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        // Same address the IP policy checks: `X-Forwarded-For` only counts behind a trusted
+        // proxy, so clients can't pick a fresh rate-limit bucket per request
         let ip = req
-            .headers()
-            .get_one("X-Forwarded-For")
-            .and_then(|s| s.split(',').next())
-            .map(|s| s.trim().to_string())
-            .or_else(|| req.remote().map(|r| r.ip().to_string()))
-            .unwrap_or_else(|| "unknown".to_string());
+            .rocket()
+            .state::<crate::ip_policy::IpPolicy>()
+            .and_then(|policy| policy.client_ip(req))
+            .or_else(|| req.remote().map(|r| r.ip()))
+            .map_or_else(|| "unknown".to_string(), |ip| ip.to_string());
         Outcome::Success(ClientIp(ip))
     }
 }
@@ -101,8 +189,9 @@ impl<'r> FromRequest<'r> for AdminKey {
 
 // --- Typing Tracker ---
 
-/// In-memory dedup: tracks last typing notification per (room, sender) to avoid spam.
-/// Key: "room_id:sender", Value: timestamp (seconds since epoch).
+/// In-memory dedup: tracks last typing notification per (room, sender, client session) to avoid
+/// spam, so each of a sender's devices is deduplicated on its own.
+/// Key: "room_id:sender:session", Value: timestamp (seconds since epoch).
 pub struct TypingTracker {
     pub last_typing: StdMutex<HashMap<String, u64>>,
 }
@@ -123,17 +212,53 @@ pub(crate) struct PresenceInner {
     sender_type: Option<String>,
     connected_at: String,
     connections: usize,
+    /// Open connections per client session, for those that came with one.
+    sessions: HashMap<String, SessionConnections>,
+}
+
+/// One client session's stream connections to a room.
+struct SessionConnections {
+    connected_at: String,
+    connections: usize,
+}
+
+/// Seconds a client session survives without a request or an open stream.
+pub const SESSION_IDLE_SECS: i64 = 300;
+
+/// A client session (one device or instance of a sender), as last seen.
+#[derive(Clone)]
+pub(crate) struct SessionInfo {
+    sender: String,
+    device: Option<String>,
+    last_seen: chrono::DateTime<chrono::Utc>,
+}
+
+/// Seconds a device-state report stays valid; clients re-report at least this often.
+pub const DEVICE_STATE_TTL_SECS: i64 = 300;
+
+/// Last idle/active report from one of a sender's devices.
+#[derive(Clone)]
+pub(crate) struct DeviceReport {
+    idle: bool,
+    idle_since: Option<chrono::DateTime<chrono::Utc>>,
+    reported_at: chrono::DateTime<chrono::Utc>,
 }
 
 #[derive(Clone)]
 pub struct PresenceTracker {
     pub(crate) inner: Arc<RwLock<HashMap<String, HashMap<String, PresenceInner>>>>,
+    /// Device idle state per sender, keyed by device id.
+    pub(crate) devices: Arc<RwLock<HashMap<String, HashMap<String, DeviceReport>>>>,
+    /// Client sessions by token.
+    pub(crate) sessions: Arc<RwLock<HashMap<String, SessionInfo>>>,
 }
 
 impl Default for PresenceTracker {
     fn default() -> Self {
         Self {
             inner: Arc::new(RwLock::new(HashMap::new())),
+            devices: Arc::new(RwLock::new(HashMap::new())),
+            sessions: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 }
@@ -141,6 +266,14 @@ impl Default for PresenceTracker {
 impl PresenceTracker {
     /// Register a sender as present in a room. Returns true if this is their first connection (new presence).
     pub fn join(&self, room_id: &str, sender: &str, sender_type: Option<&str>) -> bool {
+        self.join_session(room_id, sender, sender_type, None)
+    }
+
+    /// [`Self::join`] from a client session, so the connection is counted against that device.
+    pub fn join_session(&self, room_id: &str, sender: &str, sender_type: Option<&str>, session: Option<&ClientSession>) -> bool {
+        if let Some(session) = session {
+            self.touch_session(session, sender);
+        }
         let mut map = self.inner.write().unwrap_or_else(|e| e.into_inner());
         let room = map.entry(room_id.to_string()).or_default();
         let is_new = !room.contains_key(sender);
@@ -151,8 +284,19 @@ impl PresenceTracker {
                 sender_type: sender_type.map(String::from),
                 connected_at: chrono::Utc::now().to_rfc3339(),
                 connections: 0,
+                sessions: HashMap::new(),
             });
         entry.connections += 1;
+        if let Some(session) = session {
+            entry
+                .sessions
+                .entry(session.token.clone())
+                .or_insert_with(|| SessionConnections {
+                    connected_at: chrono::Utc::now().to_rfc3339(),
+                    connections: 0,
+                })
+                .connections += 1;
+        }
         // Update sender_type if provided and was previously None
         if sender_type.is_some() && entry.sender_type.is_none() {
             entry.sender_type = sender_type.map(String::from);
@@ -162,10 +306,29 @@ impl PresenceTracker {
 
     /// Remove a sender's connection from a room. Returns true if fully disconnected (last connection).
     pub fn leave(&self, room_id: &str, sender: &str) -> bool {
+        self.leave_session(room_id, sender, None)
+    }
+
+    /// [`Self::leave`] for a connection made with [`Self::join_session`]. The session stays
+    /// known until it has been idle for [`SESSION_IDLE_SECS`].
+    pub fn leave_session(&self, room_id: &str, sender: &str, session: Option<&str>) -> bool {
+        if let Some(token) = session
+            && let Some(info) = self.sessions.write().unwrap_or_else(|e| e.into_inner()).get_mut(token)
+        {
+            info.last_seen = chrono::Utc::now();
+        }
         let mut map = self.inner.write().unwrap_or_else(|e| e.into_inner());
         if let Some(room) = map.get_mut(room_id)
             && let Some(entry) = room.get_mut(sender)
         {
+            if let Some(token) = session
+                && let Some(conns) = entry.sessions.get_mut(token)
+            {
+                conns.connections = conns.connections.saturating_sub(1);
+                if conns.connections == 0 {
+                    entry.sessions.remove(token);
+                }
+            }
             entry.connections = entry.connections.saturating_sub(1);
             if entry.connections == 0 {
                 room.remove(sender);
@@ -178,19 +341,168 @@ impl PresenceTracker {
         false
     }
 
+    /// Record a device's idle state for a sender. `idle_secs` is how long the OS has seen no input.
+    pub fn report_device(&self, sender: &str, device: &str, idle: bool, idle_secs: Option<u64>) {
+        let now = chrono::Utc::now();
+        let mut map = self.devices.write().unwrap_or_else(|e| e.into_inner());
+        let devices = map.entry(sender.to_string()).or_default();
+        // Keep the original idle_since across repeated idle reports from the same device.
+        let idle_since = match (idle, devices.get(device)) {
+            (false, _) => None,
+            (true, Some(prev)) if prev.idle && idle_secs.is_none() => prev.idle_since,
+            (true, _) => Some(now - chrono::Duration::seconds(idle_secs.unwrap_or(0) as i64)),
+        };
+        devices.insert(
+            device.to_string(),
+            DeviceReport {
+                idle,
+                idle_since,
+                reported_at: now,
+            },
+        );
+    }
+
+    /// Effective availability for a sender, aggregated over devices with a fresh report:
+    /// "active" if any device is active (or none has reported), "idle" when every device is idle.
+    pub fn availability(&self, sender: &str) -> crate::models::DeviceAvailability {
+        let cutoff = chrono::Utc::now() - chrono::Duration::seconds(DEVICE_STATE_TTL_SECS);
+        let mut map = self.devices.write().unwrap_or_else(|e| e.into_inner());
+        let mut devices: Vec<crate::models::DeviceState> = Vec::new();
+        let mut idle_since = None;
+        if let Some(reports) = map.get_mut(sender) {
+            reports.retain(|_, r| r.reported_at > cutoff);
+            // Idle since the most recently idled device went idle.
+            idle_since = reports.values().filter_map(|r| r.idle_since).max();
+            devices = reports
+                .iter()
+                .map(|(device, r)| crate::models::DeviceState {
+                    device: device.clone(),
+                    state: if r.idle { "idle" } else { "active" }.to_string(),
+                    idle_since: r.idle_since.map(|t| t.to_rfc3339()),
+                    reported_at: r.reported_at.to_rfc3339(),
+                })
+                .collect();
+            if reports.is_empty() {
+                map.remove(sender);
+            }
+        }
+        devices.sort_by(|a, b| a.device.cmp(&b.device));
+        let all_idle = !devices.is_empty() && devices.iter().all(|d| d.state == "idle");
+        crate::models::DeviceAvailability {
+            sender: sender.to_string(),
+            availability: if all_idle { "idle" } else { "active" }.to_string(),
+            idle_since: idle_since.filter(|_| all_idle).map(|t| t.to_rfc3339()),
+            devices,
+            // Filled in by the device-state endpoints; presence entries list their own
+            sessions: Vec::new(),
+        }
+    }
+
+    /// Note a request from a client session: it is the sender's, and alive now.
+    pub fn touch_session(&self, session: &ClientSession, sender: &str) {
+        let now = chrono::Utc::now();
+        let cutoff = now - chrono::Duration::seconds(SESSION_IDLE_SECS);
+        let open = self.open_sessions();
+        let mut map = self.sessions.write().unwrap_or_else(|e| e.into_inner());
+        map.retain(|token, s| s.last_seen > cutoff || open.contains(token));
+        let info = map.entry(session.token.clone()).or_insert_with(|| SessionInfo {
+            sender: sender.to_string(),
+            device: None,
+            last_seen: now,
+        });
+        info.sender = sender.to_string();
+        info.last_seen = now;
+        if session.device.is_some() {
+            info.device = session.device.clone();
+        }
+    }
+
+    /// Tokens of sessions holding a stream connection somewhere.
+    fn open_sessions(&self) -> std::collections::HashSet<String> {
+        let map = self.inner.read().unwrap_or_else(|e| e.into_inner());
+        map.values()
+            .flat_map(|room| room.values())
+            .flat_map(|entry| entry.sessions.keys().cloned())
+            .collect()
+    }
+
+    /// A sender's live client sessions, most recently seen first: those with an open stream
+    /// and those that made a request in the last [`SESSION_IDLE_SECS`].
+    pub fn sessions(&self, sender: &str) -> Vec<crate::models::PresenceSession> {
+        let cutoff = chrono::Utc::now() - chrono::Duration::seconds(SESSION_IDLE_SECS);
+        let open = self.open_sessions();
+        let (connected, connections) = {
+            let map = self.inner.read().unwrap_or_else(|e| e.into_inner());
+            let mut connected: HashMap<String, String> = HashMap::new();
+            let mut connections: HashMap<String, usize> = HashMap::new();
+            for entry in map.values().filter_map(|room| room.get(sender)) {
+                for (token, conns) in &entry.sessions {
+                    let first = connected.entry(token.clone()).or_insert_with(|| conns.connected_at.clone());
+                    if conns.connected_at < *first {
+                        *first = conns.connected_at.clone();
+                    }
+                    *connections.entry(token.clone()).or_default() += conns.connections;
+                }
+            }
+            (connected, connections)
+        };
+        let mut map = self.sessions.write().unwrap_or_else(|e| e.into_inner());
+        map.retain(|token, s| s.last_seen > cutoff || open.contains(token));
+        let mut sessions: Vec<crate::models::PresenceSession> = map
+            .iter()
+            .filter(|(_, s)| s.sender == sender)
+            .map(|(token, s)| crate::models::PresenceSession {
+                session: token.clone(),
+                device: s.device.clone(),
+                connected_at: connected.get(token).cloned(),
+                connections: connections.get(token).copied().unwrap_or(0),
+                last_seen: s.last_seen.to_rfc3339(),
+            })
+            .collect();
+        sessions.sort_by(|a, b| b.last_seen.cmp(&a.last_seen).then_with(|| a.session.cmp(&b.session)));
+        sessions
+    }
+
+    fn entry(&self, e: &PresenceInner) -> crate::models::PresenceEntry {
+        let availability = self.availability(&e.sender);
+        let mut sessions: Vec<crate::models::PresenceSession> = {
+            let known = self.sessions.read().unwrap_or_else(|e| e.into_inner());
+            e.sessions
+                .iter()
+                .map(|(token, conns)| {
+                    let info = known.get(token);
+                    crate::models::PresenceSession {
+                        session: token.clone(),
+                        device: info.and_then(|s| s.device.clone()),
+                        connected_at: Some(conns.connected_at.clone()),
+                        connections: conns.connections,
+                        last_seen: info.map_or_else(|| conns.connected_at.clone(), |s| s.last_seen.to_rfc3339()),
+                    }
+                })
+                .collect()
+        };
+        sessions.sort_by(|a, b| a.connected_at.cmp(&b.connected_at).then_with(|| a.session.cmp(&b.session)));
+        crate::models::PresenceEntry {
+            sender: e.sender.clone(),
+            sender_type: e.sender_type.clone(),
+            connected_at: e.connected_at.clone(),
+            availability: availability.availability,
+            idle_since: availability.idle_since,
+            sessions,
+        }
+    }
+
+    /// Whether the sender holds a stream connection in any room.
+    pub fn is_present(&self, sender: &str) -> bool {
+        let map = self.inner.read().unwrap_or_else(|e| e.into_inner());
+        map.values().any(|room| room.contains_key(sender))
+    }
+
     /// Get all online users in a room.
     pub fn get_room(&self, room_id: &str) -> Vec<crate::models::PresenceEntry> {
         let map = self.inner.read().unwrap_or_else(|e| e.into_inner());
         map.get(room_id)
-            .map(|room| {
-                room.values()
-                    .map(|e| crate::models::PresenceEntry {
-                        sender: e.sender.clone(),
-                        sender_type: e.sender_type.clone(),
-                        connected_at: e.connected_at.clone(),
-                    })
-                    .collect()
-            })
+            .map(|room| room.values().map(|e| self.entry(e)).collect())
             .unwrap_or_default()
     }
 
@@ -201,13 +513,7 @@ impl PresenceTracker {
             .map(|(k, v)| {
                 (
                     k.clone(),
-                    v.values()
-                        .map(|e| crate::models::PresenceEntry {
-                            sender: e.sender.clone(),
-                            sender_type: e.sender_type.clone(),
-                            connected_at: e.connected_at.clone(),
-                        })
-                        .collect(),
+                    v.values().map(|e| self.entry(e)).collect(),
                 )
             })
             .collect()
@@ -219,19 +525,23 @@ pub(crate) struct PresenceGuard {
     pub(crate) tracker: PresenceTracker,
     pub(crate) room_id: String,
     pub(crate) sender: String,
-    pub(crate) events_sender: tokio::sync::broadcast::Sender<crate::events::ChatEvent>,
+    pub(crate) session: Option<String>,
+    pub(crate) events_sender: tokio::sync::broadcast::Sender<crate::events::Published>,
+    pub(crate) namespace: Option<String>,
 }
 
 impl Drop for PresenceGuard {
     fn drop(&mut self) {
-        let fully_left = self.tracker.leave(&self.room_id, &self.sender);
+        let fully_left = self.tracker.leave_session(&self.room_id, &self.sender, self.session.as_deref());
         if fully_left {
-            let _ = self
-                .events_sender
-                .send(crate::events::ChatEvent::PresenceLeft {
+            let _ = self.events_sender.send(crate::events::Published {
+                namespace: self.namespace.clone(),
+                ..crate::events::ChatEvent::PresenceLeft {
                     sender: self.sender.clone(),
                     room_id: self.room_id.clone(),
-                });
+                }
+                .into()
+            });
         }
     }
 }
@@ -0,0 +1,217 @@
+use crate::namespaces::ScopedDb;
+use crate::events::{ChatEvent, Events};
+use crate::membership::MemberAccess;
+use crate::models::{Message, MoveMessage, MoveMessageResponse};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::post;
+use rusqlite::params;
+use std::collections::{HashMap, HashSet};
+
+use super::threads::fetch_message;
+use super::AdminKey;
+
+fn err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+/// Relocate a misplaced message (optionally its whole thread) into another room.
+/// Requires the source room's admin key, plus the target room's `target_admin_key` (a private
+/// target also accepts one of its member tokens); a private room's messages only move into
+/// another private room. Moved messages keep their ids but get new seqs at the
+/// end of the target room; each leaves a system tombstone at its old seq with a `moved_to` pointer.
+#[post(
+    "/api/v1/rooms/<room_id>/messages/<message_id>/move",
+    format = "json",
+    data = "<body>"
+)]
+pub fn move_message(
+    db: ScopedDb<'_>,
+    events: Events<'_>,
+    room_id: &str,
+    message_id: &str,
+    admin: AdminKey,
+    access: MemberAccess,
+    body: Json<MoveMessage>,
+) -> Result<Json<MoveMessageResponse>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    let target_id = body.target_room_id.trim();
+
+    let (stored_key, source_type): (Option<String>, Option<String>) = conn
+        .query_row(
+            "SELECT admin_key, room_type FROM rooms WHERE id = ?1",
+            params![room_id],
+            |r| Ok((r.get(0)?, r.get(1)?)),
+        )
+        .map_err(|_| err(Status::NotFound, "Room not found"))?;
+    match stored_key {
+        Some(ref key) if key == &admin.0 => {}
+        _ => return Err(err(Status::Forbidden, "Invalid admin key for this room")),
+    }
+
+    if target_id == room_id {
+        return Err(err(Status::BadRequest, "target_room_id must differ from the source room"));
+    }
+    let (target_name, target_type, target_archived, target_key): (String, Option<String>, Option<String>, Option<String>) = conn
+        .query_row(
+            "SELECT name, room_type, archived_at, admin_key FROM rooms WHERE id = ?1",
+            params![target_id],
+            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)),
+        )
+        .map_err(|_| err(Status::NotFound, "Target room not found"))?;
+    let target_private = crate::membership::is_private(&conn, target_id);
+    let target_admin = target_key.is_some() && body.target_admin_key.as_deref() == target_key.as_deref();
+    let target_member = target_private && (access.all || access.rooms.iter().any(|r| r == target_id));
+    if !target_admin && !target_member {
+        return Err(err(Status::Forbidden, "Invalid target_admin_key for the target room"));
+    }
+    if !target_private && crate::membership::is_private(&conn, room_id) {
+        return Err(err(Status::Conflict, "Messages in a private room can only move to another private room"));
+    }
+    if source_type.as_deref() == Some("dm") || target_type.as_deref() == Some("dm") {
+        return Err(err(Status::BadRequest, "Messages cannot be moved into or out of DM conversations"));
+    }
+    if target_archived.is_some() {
+        return Err(err(Status::BadRequest, "Target room is archived"));
+    }
+
+    let message = fetch_message(&conn, message_id, room_id)?;
+
+    // Collect what moves: the message alone, or the thread it belongs to (root + all descendants)
+    let to_move: Vec<Message> = if body.include_thread {
+        let mut root = message;
+        let mut visited = HashSet::from([root.id.clone()]);
+        while let Some(parent_id) = root.reply_to.clone() {
+            if !visited.insert(parent_id.clone()) {
+                break;
+            }
+            match fetch_message(&conn, &parent_id, room_id) {
+                Ok(parent) => root = parent,
+                Err(_) => break,
+            }
+        }
+        let ids: Vec<String> = {
+            let mut stmt = conn
+                .prepare(
+                    "WITH RECURSIVE thread(id) AS (
+                         SELECT ?1
+                         UNION
+                         SELECT m.id FROM messages m JOIN thread t ON m.reply_to = t.id WHERE m.room_id = ?2
+                     )
+                     SELECT m.id FROM messages m JOIN thread t ON m.id = t.id ORDER BY m.seq ASC",
+                )
+                .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+            stmt.query_map(params![&root.id, room_id], |r| r.get(0))
+                .map_err(|_| err(Status::InternalServerError, "Internal server error"))?
+                .filter_map(|r| r.ok())
+                .collect()
+        };
+        ids.iter()
+            .filter_map(|id| fetch_message(&conn, id, room_id).ok())
+            .collect()
+    } else {
+        vec![message]
+    };
+
+    let moving: HashSet<&str> = to_move.iter().map(|m| m.id.as_str()).collect();
+    let now = chrono::Utc::now().to_rfc3339();
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+
+    // moved id -> tombstone id, so tombstones keep the source thread's shape
+    let mut tombstone_of: HashMap<String, String> = HashMap::new();
+    let mut moved_ids = Vec::new();
+    let mut tombstone_ids = Vec::new();
+
+    for msg in &to_move {
+        let new_seq: i64 = tx
+            .query_row("SELECT COALESCE(MAX(seq), 0) + 1 FROM messages", [], |r| r.get(0))
+            .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+        // A reply whose parent stays behind becomes top-level in the target room
+        let new_reply_to = msg.reply_to.clone().filter(|p| moving.contains(p.as_str()));
+        let mut metadata = msg.metadata.clone();
+        if !metadata.is_object() {
+            metadata = serde_json::json!({});
+        }
+        metadata["moved_from"] = serde_json::json!({"room_id": room_id, "seq": msg.seq, "moved_at": &now});
+
+        tx.execute(
+            "UPDATE messages SET room_id = ?1, seq = ?2, reply_to = ?3, metadata = ?4 WHERE id = ?5",
+            params![target_id, new_seq, &new_reply_to, metadata.to_string(), &msg.id],
+        )
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+        // Re-indexed under the target room's tokenizer, with the move
+        crate::db::upsert_fts(&tx, &msg.id);
+
+        // The tombstone takes over the original seq so cursors into the source room stay stable
+        let tombstone_id = uuid::Uuid::new_v4().to_string();
+        let tombstone_reply_to = msg
+            .reply_to
+            .as_ref()
+            .map(|p| tombstone_of.get(p).cloned().unwrap_or_else(|| p.clone()));
+        let tombstone_meta = serde_json::json!({
+            "moved_to": {"room_id": target_id, "room_name": &target_name, "message_id": &msg.id}
+        });
+        tx.execute(
+            "INSERT INTO messages (id, room_id, sender, content, metadata, created_at, reply_to, sender_type, seq, kind) VALUES (?1, ?2, 'system', ?3, ?4, ?5, ?6, 'system', ?7, 'system')",
+            params![
+                &tombstone_id,
+                room_id,
+                format!("[message moved to #{target_name}]"),
+                tombstone_meta.to_string(),
+                &msg.created_at,
+                &tombstone_reply_to,
+                msg.seq
+            ],
+        )
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+
+        // Replies left in the source room now hang off the tombstone
+        tx.execute(
+            "UPDATE messages SET reply_to = ?1 WHERE reply_to = ?2 AND room_id = ?3",
+            params![&tombstone_id, &msg.id, room_id],
+        )
+        .ok();
+        tx.execute("DELETE FROM thread_read_positions WHERE root_id = ?1", params![&msg.id])
+            .ok();
+        crate::db::upsert_fts(&tx, &tombstone_id);
+
+        tombstone_of.insert(msg.id.clone(), tombstone_id.clone());
+        moved_ids.push(msg.id.clone());
+        tombstone_ids.push(tombstone_id);
+    }
+
+    tx.execute(
+        "UPDATE rooms SET updated_at = ?1 WHERE id IN (?2, ?3)",
+        params![&now, room_id, target_id],
+    )
+    .ok();
+    tx.commit()
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+
+    let moved: Vec<Message> = moved_ids
+        .iter()
+        .filter_map(|id| fetch_message(&conn, id, target_id).ok())
+        .collect();
+    let tombstones: Vec<Message> = tombstone_ids
+        .iter()
+        .filter_map(|id| fetch_message(&conn, id, room_id).ok())
+        .collect();
+    drop(conn);
+
+    for (m, t) in moved.iter().zip(&tombstones) {
+        events.publish(ChatEvent::MessageDeleted {
+            id: m.id.clone(),
+            room_id: room_id.to_string(),
+        });
+        events.publish(ChatEvent::NewMessage(t.clone()));
+        events.publish(ChatEvent::NewMessage(m.clone()));
+    }
+
+    Ok(Json(MoveMessageResponse {
+        target_room_id: target_id.to_string(),
+        moved,
+        tombstones,
+    }))
+}
@@ -0,0 +1,90 @@
+// Newline-delimited JSON responses for large reads.
+//
+// Rows are read on a dedicated read-only connection in a worker thread and handed to the response
+// through a small bounded channel, so a 100k-message history streams in constant memory and the
+// shared connection stays free for everyone else. A client that disconnects drops the receiver,
+// which stops the worker at its next row.
+
+use rocket::futures::stream::{self, BoxStream, StreamExt};
+use rocket::http::ContentType;
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::response::stream::TextStream;
+use rusqlite::types::Value;
+use rusqlite::{Connection, Row};
+use serde::Serialize;
+
+/// Lines buffered between the reader thread and the socket.
+const CHANNEL_CAPACITY: usize = 256;
+
+/// Whether the client asked for `Accept: application/x-ndjson`.
+pub struct AcceptNdjson(pub bool);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AcceptNdjson {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let wants = req.headers().get("Accept").any(|accept| {
+            accept
+                .split(',')
+                .filter_map(|part| part.split(';').next())
+                .any(|media| media.trim().eq_ignore_ascii_case("application/x-ndjson"))
+        });
+        Outcome::Success(AcceptNdjson(wants))
+    }
+}
+
+pub type NdjsonStream = (ContentType, TextStream<BoxStream<'static, String>>);
+
+pub fn content_type() -> ContentType {
+    ContentType::new("application", "x-ndjson")
+}
+
+/// Stream `sql` row by row as NDJSON, mapping each row with `map`. Errors after the first byte
+/// can't change the status code, so they end the stream with an `{"error": ...}` line.
+pub fn stream_rows<T, F>(conn: Connection, sql: String, params: Vec<Value>, mut map: F) -> NdjsonStream
+where
+    T: Serialize,
+    F: FnMut(&Row<'_>) -> rusqlite::Result<T> + Send + 'static,
+{
+    let (tx, rx) = tokio::sync::mpsc::channel::<String>(CHANNEL_CAPACITY);
+    std::thread::spawn(move || {
+        let error_line = |e: rusqlite::Error| {
+            eprintln!("⚠️ NDJSON stream failed: {e}");
+            format!("{}\n", serde_json::json!({"error": "Internal server error"}))
+        };
+        let mut stmt = match conn.prepare(&sql) {
+            Ok(stmt) => stmt,
+            Err(e) => {
+                tx.blocking_send(error_line(e)).ok();
+                return;
+            }
+        };
+        let rows = match stmt.query_map(rusqlite::params_from_iter(params), |row| map(row)) {
+            Ok(rows) => rows,
+            Err(e) => {
+                tx.blocking_send(error_line(e)).ok();
+                return;
+            }
+        };
+        for row in rows {
+            let line = match row {
+                Ok(item) => match serde_json::to_string(&item) {
+                    Ok(json) => json + "\n",
+                    Err(_) => continue,
+                },
+                Err(e) => {
+                    tx.blocking_send(error_line(e)).ok();
+                    return;
+                }
+            };
+            if tx.blocking_send(line).is_err() {
+                // Client went away
+                return;
+            }
+        }
+    });
+
+    let lines = stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|line| (line, rx)) }).boxed();
+    (content_type(), TextStream(lines))
+}
@@ -0,0 +1,165 @@
+use crate::membership::MemberAccess;
+use crate::namespaces::ScopedDb;
+use crate::models::{MarkReactionsRead, ReactionNotification, ReactionNotificationsResponse, ReactionReadCursor};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{get, post};
+use rusqlite::{params, Connection};
+
+/// Characters of the reacted-to message included as context.
+const PREVIEW_CHARS: usize = 140;
+
+fn err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+fn check_sender(sender: &str) -> Result<&str, (Status, Json<serde_json::Value>)> {
+    let sender = sender.trim();
+    if sender.is_empty() {
+        return Err(err(Status::BadRequest, "Query parameter 'sender' must not be empty"));
+    }
+    if sender.len() > 200 {
+        return Err(err(Status::BadRequest, "Sender name too long (max 200 characters)"));
+    }
+    Ok(sender)
+}
+
+/// Reactions on messages by `?1` (any of its aliases), left by someone else, in rooms the
+/// request may see.
+fn received_sql(access: &MemberAccess) -> String {
+    format!(
+        "FROM message_reactions mr \
+         JOIN messages m ON mr.message_id = m.id \
+         JOIN rooms r ON m.room_id = r.id \
+         WHERE {} AND NOT {} AND {}",
+        crate::db::sender_identity_sql("m.sender", 1),
+        crate::db::sender_identity_sql("mr.sender", 1),
+        access.visible_sql("m.room_id")
+    )
+}
+
+fn read_cursor(conn: &Connection, canonical: &str) -> i64 {
+    conn.query_row(
+        "SELECT last_read_cursor FROM reaction_read_cursors WHERE sender = ?1",
+        params![canonical],
+        |r| r.get(0),
+    )
+    .unwrap_or(0)
+}
+
+fn unread_count(conn: &Connection, canonical: &str, read_cursor: i64, access: &MemberAccess) -> i64 {
+    conn.query_row(
+        &format!("SELECT COUNT(*) {} AND mr.rowid > ?2", received_sql(access)),
+        params![canonical, read_cursor],
+        |r| r.get(0),
+    )
+    .unwrap_or(0)
+}
+
+/// GET /api/v1/notifications/reactions?sender=<name>&after=<cursor>&unread=true&limit=N
+/// Reactions others left on the sender's messages, newest first, with the message they're on.
+/// Each has a `cursor`; `after=` returns only newer ones, `unread=true` only those past the read cursor.
+#[get("/api/v1/notifications/reactions?<sender>&<after>&<unread>&<limit>")]
+pub fn reaction_notifications(
+    db: ScopedDb<'_>,
+    sender: &str,
+    after: Option<i64>,
+    unread: Option<bool>,
+    limit: Option<i64>,
+    access: MemberAccess,
+) -> Result<Json<ReactionNotificationsResponse>, (Status, Json<serde_json::Value>)> {
+    let sender = check_sender(sender)?;
+    let conn = db.conn();
+    let limit = limit.unwrap_or(50).clamp(1, 200);
+    let canonical = crate::db::resolve_sender(&conn, sender);
+    let read_cursor = read_cursor(&conn, &canonical);
+    let floor = match unread {
+        Some(true) => after.unwrap_or(0).max(read_cursor),
+        _ => after.unwrap_or(0),
+    };
+
+    let sql = format!(
+        "SELECT mr.rowid, mr.id, mr.emoji, mr.sender, mr.created_at, m.id, m.seq, m.content, m.room_id, r.name \
+         {} AND mr.rowid > ?2 ORDER BY mr.rowid DESC LIMIT ?3",
+        received_sql(&access)
+    );
+    let mut stmt = conn
+        .prepare(&sql)
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+    let notifications: Vec<ReactionNotification> = stmt
+        .query_map(params![&canonical, floor, limit], |row| {
+            let cursor: i64 = row.get(0)?;
+            let emoji: String = row.get(2)?;
+            let content: String = row.get(7)?;
+            let message_preview = match content.char_indices().nth(PREVIEW_CHARS) {
+                Some((i, _)) => format!("{}…", &content[..i]),
+                None => content,
+            };
+            Ok(ReactionNotification {
+                cursor,
+                reaction_id: row.get(1)?,
+                shortcode: crate::emoji::shortcode(&emoji),
+                emoji,
+                reactor: row.get(3)?,
+                reacted_at: row.get(4)?,
+                message_id: row.get(5)?,
+                message_seq: row.get(6)?,
+                message_preview,
+                room_id: row.get(8)?,
+                room_name: row.get(9)?,
+                unread: cursor > read_cursor,
+            })
+        })
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let count = notifications.len();
+    Ok(Json(ReactionNotificationsResponse {
+        sender: sender.to_string(),
+        notifications,
+        count,
+        unread_count: unread_count(&conn, &canonical, read_cursor, &access),
+        read_cursor,
+    }))
+}
+
+/// POST /api/v1/notifications/reactions/read — mark the sender's reactions read up to `cursor`
+/// (default: the newest). The read cursor never moves backwards.
+#[post("/api/v1/notifications/reactions/read", format = "json", data = "<body>")]
+pub fn mark_reactions_read(
+    db: ScopedDb<'_>,
+    body: Json<MarkReactionsRead>,
+    access: MemberAccess,
+) -> Result<Json<ReactionReadCursor>, (Status, Json<serde_json::Value>)> {
+    let sender = check_sender(&body.sender)?;
+    if body.cursor.is_some_and(|c| c < 0) {
+        return Err(err(Status::BadRequest, "cursor must not be negative"));
+    }
+    let conn = db.conn();
+    let canonical = crate::db::resolve_sender(&conn, sender);
+    let cursor = match body.cursor {
+        Some(c) => c,
+        None => conn
+            .query_row(
+                &format!("SELECT COALESCE(MAX(mr.rowid), 0) {}", received_sql(&access)),
+                params![&canonical],
+                |r| r.get(0),
+            )
+            .map_err(|_| err(Status::InternalServerError, "Internal server error"))?,
+    };
+    conn.execute(
+        "INSERT INTO reaction_read_cursors (sender, last_read_cursor, updated_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT(sender) DO UPDATE SET last_read_cursor = MAX(last_read_cursor, excluded.last_read_cursor),
+         updated_at = excluded.updated_at",
+        params![&canonical, cursor, chrono::Utc::now().to_rfc3339()],
+    )
+    .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+
+    let read_cursor = read_cursor(&conn, &canonical);
+    Ok(Json(ReactionReadCursor {
+        sender: sender.to_string(),
+        read_cursor,
+        unread_count: unread_count(&conn, &canonical, read_cursor, &access),
+    }))
+}
@@ -1,12 +1,50 @@
-use crate::db::Db;
+use crate::namespaces::ScopedDb;
+use crate::models::{RoomRole, SetRoomRole};
 use rocket::http::Status;
 use rocket::serde::json::Json;
-use rocket::{get, State};
-use rusqlite::params;
+use rocket::{delete, get, put};
+use rusqlite::{params, Connection};
+
+use super::AdminKey;
+
+/// Roles a room admin can grant. A sender with none is a member, or the owner if they created the room.
+pub const ROOM_ROLES: &[&str] = &["owner", "moderator", "member", "guest"];
+
+fn err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+fn check_admin(conn: &Connection, room_id: &str, admin: &AdminKey) -> Result<(), (Status, Json<serde_json::Value>)> {
+    let stored_key: Option<String> = conn
+        .query_row("SELECT admin_key FROM rooms WHERE id = ?1", params![room_id], |r| r.get(0))
+        .map_err(|_| err(Status::NotFound, "Room not found"))?;
+    match stored_key {
+        Some(ref key) if key == &admin.0 => Ok(()),
+        _ => Err(err(Status::Forbidden, "Invalid admin key for this room")),
+    }
+}
+
+fn load_roles(conn: &Connection, room_id: &str) -> Vec<RoomRole> {
+    conn.prepare(
+        "SELECT sender, role, granted_at, granted_by FROM room_roles WHERE room_id = ?1 ORDER BY sender COLLATE NOCASE",
+    )
+    .and_then(|mut s| {
+        s.query_map(params![room_id], |r| {
+            Ok(RoomRole {
+                sender: r.get(0)?,
+                role: r.get(1)?,
+                granted_at: r.get(2)?,
+                granted_by: r.get(3)?,
+            })
+        })
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+    })
+    .unwrap_or_default()
+}
 
 #[get("/api/v1/rooms/<room_id>/participants")]
 pub fn room_participants(
-    db: &State<Db>,
+    db: ScopedDb<'_>,
     room_id: &str,
 ) -> Result<Json<Vec<crate::models::EnrichedParticipant>>, (Status, Json<serde_json::Value>)> {
     let conn = db.conn();
@@ -29,12 +67,16 @@ pub fn room_participants(
     }
 
     // Aggregate participants from messages in this room, enriched with profile data.
+    // Aliases fold into their canonical sender.
     // Use the most recent sender_type for each sender (profile overrides message sender_type).
+    // Role comes from room_roles, else the room's creator is its owner and everyone else a member.
     let mut stmt = conn
         .prepare(
-            "SELECT m.sender,
+            "SELECT COALESCE(a.sender, m.sender) as canonical,
                     COALESCE(p.sender_type,
-                      (SELECT m2.sender_type FROM messages m2 WHERE m2.room_id = ?1 AND m2.sender = m.sender AND m2.sender_type IS NOT NULL ORDER BY m2.seq DESC LIMIT 1)
+                      (SELECT m2.sender_type FROM messages m2 LEFT JOIN sender_aliases a2 ON a2.alias_key = LOWER(m2.sender)
+                       WHERE m2.room_id = ?1 AND COALESCE(a2.sender, m2.sender) = COALESCE(a.sender, m.sender)
+                         AND m2.sender_type IS NOT NULL ORDER BY m2.seq DESC LIMIT 1)
                     ) as latest_sender_type,
                     COUNT(*) as message_count,
                     MIN(m.created_at) as first_seen,
@@ -42,11 +84,23 @@ pub fn room_participants(
                     p.display_name,
                     p.avatar_url,
                     p.bio,
-                    p.status_text
+                    p.status_text,
+                    GROUP_CONCAT(DISTINCT m.sender) as names,
+                    COALESCE(
+                      (SELECT rr.role FROM room_roles rr WHERE rr.room_id = ?1 AND rr.sender = COALESCE(a.sender, m.sender)),
+                      CASE WHEN (SELECT created_by FROM rooms WHERE id = ?1) = COALESCE(a.sender, m.sender) THEN 'owner' ELSE 'member' END
+                    ) as role,
+                    MIN(MIN(m.created_at), COALESCE(
+                      (SELECT MIN(j.created_at) FROM messages j WHERE j.room_id = ?1 AND j.kind = 'system'
+                         AND json_extract(j.metadata, '$.event') = 'member_joined'
+                         AND json_extract(j.metadata, '$.sender') = COALESCE(a.sender, m.sender)),
+                      MIN(m.created_at)
+                    )) as joined_at
              FROM messages m
-             LEFT JOIN profiles p ON p.sender = m.sender
-             WHERE m.room_id = ?1
-             GROUP BY m.sender
+             LEFT JOIN sender_aliases a ON a.alias_key = LOWER(m.sender)
+             LEFT JOIN profiles p ON p.sender = COALESCE(a.sender, m.sender)
+             WHERE m.room_id = ?1 AND m.kind != 'system'
+             GROUP BY canonical
              ORDER BY last_seen DESC",
         )
         .map_err(|_e| {
@@ -58,8 +112,11 @@ pub fn room_participants(
 
     let participants = stmt
         .query_map(params![room_id], |row| {
+            let sender: String = row.get(0)?;
+            let names: String = row.get(9)?;
+            let posted_as = names.split(',').filter(|n| *n != sender).map(str::to_string).collect();
             Ok(crate::models::EnrichedParticipant {
-                sender: row.get(0)?,
+                sender,
                 sender_type: row.get(1)?,
                 message_count: row.get(2)?,
                 first_seen: row.get(3)?,
@@ -68,6 +125,9 @@ pub fn room_participants(
                 avatar_url: row.get(6)?,
                 bio: row.get(7)?,
                 status_text: row.get(8)?,
+                posted_as,
+                role: row.get(10)?,
+                joined_at: row.get(11)?,
             })
         })
         .map_err(|_e| {
@@ -81,3 +141,188 @@ pub fn room_participants(
 
     Ok(Json(participants))
 }
+
+/// GET /api/v1/rooms/<room_id>/mentionables?prefix=<text>&limit=N
+/// Candidate senders for @-autocomplete, most recently active first.
+/// Prefix matches sender or profile display_name, case-insensitive.
+#[get("/api/v1/rooms/<room_id>/mentionables?<prefix>&<limit>")]
+pub fn room_mentionables(
+    db: ScopedDb<'_>,
+    room_id: &str,
+    prefix: Option<&str>,
+    limit: Option<i64>,
+) -> Result<Json<crate::models::MentionablesResponse>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+
+    let room_exists: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM rooms WHERE id = ?1",
+            params![room_id],
+            |r| r.get::<_, i64>(0),
+        )
+        .map(|c| c > 0)
+        .unwrap_or(false);
+
+    if !room_exists {
+        return Err((
+            Status::NotFound,
+            Json(serde_json::json!({"error": "Room not found"})),
+        ));
+    }
+
+    let prefix = prefix.map(|p| p.trim().trim_start_matches('@')).unwrap_or("");
+    if prefix.len() > 100 {
+        return Err((
+            Status::BadRequest,
+            Json(serde_json::json!({"error": "Prefix too long (max 100 characters)"})),
+        ));
+    }
+    let limit = limit.unwrap_or(20).clamp(1, 100);
+
+    // SQLite LIKE is case-insensitive for ASCII, which is what we want for autocomplete.
+    let pattern = format!(
+        "{}%",
+        prefix.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+    );
+
+    // Aliases fold into their canonical sender, which is what gets suggested
+    let mut stmt = conn
+        .prepare(
+            "SELECT COALESCE(a.sender, m.sender) as canonical,
+                    p.display_name,
+                    COALESCE(p.sender_type,
+                      (SELECT m2.sender_type FROM messages m2 LEFT JOIN sender_aliases a2 ON a2.alias_key = LOWER(m2.sender)
+                       WHERE m2.room_id = ?1 AND COALESCE(a2.sender, m2.sender) = COALESCE(a.sender, m.sender)
+                         AND m2.sender_type IS NOT NULL ORDER BY m2.seq DESC LIMIT 1)
+                    ) as latest_sender_type,
+                    MAX(m.created_at) as last_seen,
+                    MAX(m.seq) as last_seq
+             FROM messages m
+             LEFT JOIN sender_aliases a ON a.alias_key = LOWER(m.sender)
+             LEFT JOIN profiles p ON p.sender = COALESCE(a.sender, m.sender)
+             WHERE m.room_id = ?1 AND m.kind != 'system'
+             GROUP BY canonical
+             HAVING MAX(COALESCE(a.sender, m.sender) LIKE ?2 ESCAPE '\\' OR m.sender LIKE ?2 ESCAPE '\\'
+                        OR p.display_name LIKE ?2 ESCAPE '\\')
+             ORDER BY last_seq DESC
+             LIMIT ?3",
+        )
+        .map_err(|_e| {
+            (
+                Status::InternalServerError,
+                Json(serde_json::json!({"error": "Internal server error"})),
+            )
+        })?;
+
+    let candidates: Vec<crate::models::Mentionable> = stmt
+        .query_map(params![room_id, pattern, limit], |row| {
+            Ok(crate::models::Mentionable {
+                sender: row.get(0)?,
+                display_name: row.get(1)?,
+                sender_type: row.get(2)?,
+                last_seen: row.get(3)?,
+            })
+        })
+        .map_err(|_e| {
+            (
+                Status::InternalServerError,
+                Json(serde_json::json!({"error": "Internal server error"})),
+            )
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let count = candidates.len();
+    Ok(Json(crate::models::MentionablesResponse {
+        room_id: room_id.to_string(),
+        prefix: prefix.to_string(),
+        candidates,
+        count,
+    }))
+}
+
+/// GET /api/v1/rooms/<room_id>/roles — roles granted in the room (implicit owner and members aren't listed).
+#[get("/api/v1/rooms/<room_id>/roles")]
+pub fn list_room_roles(
+    db: ScopedDb<'_>,
+    room_id: &str,
+) -> Result<Json<Vec<RoomRole>>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    let exists: bool = conn
+        .query_row("SELECT COUNT(*) FROM rooms WHERE id = ?1", params![room_id], |r| r.get::<_, i64>(0))
+        .map(|c| c > 0)
+        .unwrap_or(false);
+    if !exists {
+        return Err(err(Status::NotFound, "Room not found"));
+    }
+    Ok(Json(load_roles(&conn, room_id)))
+}
+
+/// PUT /api/v1/rooms/<room_id>/roles/<sender> — grant a role (room admin only). Aliases resolve to
+/// their canonical sender, so the role follows whichever name they post under.
+#[put("/api/v1/rooms/<room_id>/roles/<sender>", format = "json", data = "<body>")]
+pub fn set_room_role(
+    db: ScopedDb<'_>,
+    room_id: &str,
+    sender: &str,
+    admin: AdminKey,
+    body: Json<SetRoomRole>,
+) -> Result<Json<RoomRole>, (Status, Json<serde_json::Value>)> {
+    let sender = sender.trim();
+    if sender.is_empty() || sender.len() > 100 {
+        return Err(err(Status::BadRequest, "Sender must be 1-100 characters"));
+    }
+    let role = body.role.trim().to_lowercase();
+    if !ROOM_ROLES.contains(&role.as_str()) {
+        return Err((
+            Status::BadRequest,
+            Json(serde_json::json!({"error": "Unknown role", "valid_roles": ROOM_ROLES})),
+        ));
+    }
+    let granted_by = body.granted_by.as_deref().map(str::trim).filter(|g| !g.is_empty());
+    if granted_by.is_some_and(|g| g.len() > 100) {
+        return Err(err(Status::BadRequest, "granted_by must be at most 100 characters"));
+    }
+
+    let conn = db.conn();
+    check_admin(&conn, room_id, &admin)?;
+    let sender = crate::db::resolve_sender(&conn, sender);
+    let now = chrono::Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO room_roles (room_id, sender, role, granted_at, granted_by) VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(room_id, sender) DO UPDATE SET role = excluded.role, granted_at = excluded.granted_at, granted_by = excluded.granted_by",
+        params![room_id, &sender, &role, &now, granted_by],
+    )
+    .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+
+    Ok(Json(RoomRole {
+        sender,
+        role,
+        granted_at: now,
+        granted_by: granted_by.map(str::to_string),
+    }))
+}
+
+/// DELETE /api/v1/rooms/<room_id>/roles/<sender> — revoke a granted role (room admin only); the
+/// sender falls back to member, or owner if they created the room.
+#[delete("/api/v1/rooms/<room_id>/roles/<sender>")]
+pub fn delete_room_role(
+    db: ScopedDb<'_>,
+    room_id: &str,
+    sender: &str,
+    admin: AdminKey,
+) -> Result<Json<serde_json::Value>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    check_admin(&conn, room_id, &admin)?;
+    let sender = crate::db::resolve_sender(&conn, sender.trim());
+    let deleted = conn
+        .execute(
+            "DELETE FROM room_roles WHERE room_id = ?1 AND sender = ?2",
+            params![room_id, &sender],
+        )
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+    if deleted == 0 {
+        return Err(err(Status::NotFound, "No role granted to this sender in this room"));
+    }
+    Ok(Json(serde_json::json!({"deleted": deleted, "room_id": room_id, "sender": sender})))
+}
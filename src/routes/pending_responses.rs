@@ -0,0 +1,37 @@
+use crate::membership::MemberAccess;
+use crate::namespaces::ScopedDb;
+use crate::models::PendingResponsesResponse;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::get;
+
+/// GET /api/v1/pending-responses?sender=<name>&requested_by=<name>&room_id=<uuid>
+/// Messages still waiting on a reply: those `sender` was asked to answer and/or those
+/// `requested_by` is waiting on. Oldest due first. Private rooms need a member token.
+#[get("/api/v1/pending-responses?<sender>&<requested_by>&<room_id>")]
+pub fn pending_responses(
+    db: ScopedDb<'_>,
+    sender: Option<&str>,
+    requested_by: Option<&str>,
+    room_id: Option<&str>,
+    access: MemberAccess,
+) -> Result<Json<PendingResponsesResponse>, (Status, Json<serde_json::Value>)> {
+    let sender = sender.map(str::trim).filter(|s| !s.is_empty());
+    let requested_by = requested_by.map(str::trim).filter(|s| !s.is_empty());
+    if sender.is_none() && requested_by.is_none() {
+        return Err((
+            Status::BadRequest,
+            Json(serde_json::json!({"error": "Pass 'sender', 'requested_by', or both"})),
+        ));
+    }
+    if sender.is_some_and(|s| s.len() > 200) || requested_by.is_some_and(|s| s.len() > 200) {
+        return Err((
+            Status::BadRequest,
+            Json(serde_json::json!({"error": "Sender name too long (max 200 characters)"})),
+        ));
+    }
+
+    let pending = crate::responses::list(&db.conn(), sender, requested_by, room_id, &access);
+    let count = pending.len();
+    Ok(Json(PendingResponsesResponse { pending, count }))
+}
@@ -1,21 +1,46 @@
-use crate::db::Db;
-use crate::events::{ChatEvent, EventBus};
-use crate::models::PinnedMessage;
+use crate::namespaces::ScopedDb;
+use crate::events::{ChatEvent, Events};
+use crate::models::{ListEnvelope, ListOf, ListResponse, PinHistoryEntry, PinnedMessage};
 use rocket::http::Status;
 use rocket::serde::json::Json;
-use rocket::{delete, get, post, State};
-use rusqlite::params;
+use rocket::{delete, get, post};
+use rusqlite::{params, Connection};
 
 use super::AdminKey;
 
-#[post("/api/v1/rooms/<room_id>/messages/<message_id>/pin")]
+/// Who to record in the pin history: `?by=` when given, otherwise "admin".
+fn pin_actor(by: Option<&str>) -> Result<String, (Status, Json<serde_json::Value>)> {
+    match by.map(str::trim).filter(|b| !b.is_empty()) {
+        Some(b) if b.len() > 100 => Err((
+            Status::BadRequest,
+            Json(serde_json::json!({"error": "by must be at most 100 characters"})),
+        )),
+        Some(b) => Ok(b.to_string()),
+        None => Ok("admin".to_string()),
+    }
+}
+
+/// Append a pin or unpin to the room's pin history, copying the message as it is now.
+fn record_pin_history(conn: &Connection, room_id: &str, message_id: &str, action: &str, actor: &str, at: &str) {
+    let _ = conn.execute(
+        "INSERT INTO pin_history (room_id, message_id, action, actor, sender, content, created_at)
+         SELECT room_id, id, ?3, ?4, sender, content, ?5 FROM messages WHERE id = ?1 AND room_id = ?2",
+        params![message_id, room_id, action, actor, at],
+    );
+}
+
+/// POST /api/v1/rooms/<room_id>/messages/<message_id>/pin — pin a message (admin key). `?by=`
+/// names who pinned it in the pin history.
+#[post("/api/v1/rooms/<room_id>/messages/<message_id>/pin?<by>")]
 pub fn pin_message(
-    db: &State<Db>,
-    events: &State<EventBus>,
+    db: ScopedDb<'_>,
+    events: Events<'_>,
     room_id: &str,
     message_id: &str,
+    by: Option<&str>,
     admin: AdminKey,
 ) -> Result<Json<PinnedMessage>, (Status, Json<serde_json::Value>)> {
+    let actor = pin_actor(by)?;
     let conn = db.conn();
 
     // Verify room exists and admin key matches
@@ -87,6 +112,7 @@ pub fn pin_message(
             Json(serde_json::json!({"error": String::from("Internal server error")})),
         )
     })?;
+    record_pin_history(&conn, room_id, message_id, "pinned", &actor, &now);
 
     // Fetch the pinned message
     let pinned = conn
@@ -118,19 +144,33 @@ pub fn pin_message(
             )
         })?;
 
+    let note = crate::db::insert_localized_system_message(
+        &conn,
+        room_id,
+        "message_pinned",
+        serde_json::json!({"message_id": &pinned.id, "message_seq": pinned.seq, "author": &pinned.sender}),
+    );
+
     events.publish(ChatEvent::MessagePinned(pinned.clone()));
+    if let Some(note) = note {
+        events.publish(ChatEvent::NewMessage(note));
+    }
 
     Ok(Json(pinned))
 }
 
-#[delete("/api/v1/rooms/<room_id>/messages/<message_id>/pin")]
+/// DELETE /api/v1/rooms/<room_id>/messages/<message_id>/pin — unpin a message (admin key). The
+/// pin stays in the room's pin history; `?by=` names who unpinned it there.
+#[delete("/api/v1/rooms/<room_id>/messages/<message_id>/pin?<by>")]
 pub fn unpin_message(
-    db: &State<Db>,
-    events: &State<EventBus>,
+    db: ScopedDb<'_>,
+    events: Events<'_>,
     room_id: &str,
     message_id: &str,
+    by: Option<&str>,
     admin: AdminKey,
 ) -> Result<Json<serde_json::Value>, (Status, Json<serde_json::Value>)> {
+    let actor = pin_actor(by)?;
     let conn = db.conn();
 
     // Verify room exists and admin key matches
@@ -190,6 +230,7 @@ pub fn unpin_message(
             Json(serde_json::json!({"error": String::from("Internal server error")})),
         )
     })?;
+    record_pin_history(&conn, room_id, message_id, "unpinned", &actor, &chrono::Utc::now().to_rfc3339());
 
     events.publish(ChatEvent::MessageUnpinned {
         id: message_id.to_string(),
@@ -203,11 +244,12 @@ pub fn unpin_message(
     })))
 }
 
-#[get("/api/v1/rooms/<room_id>/pins")]
+#[get("/api/v1/rooms/<room_id>/pins?<envelope>")]
 pub fn list_pins(
-    db: &State<Db>,
+    db: ScopedDb<'_>,
     room_id: &str,
-) -> Result<Json<Vec<PinnedMessage>>, (Status, Json<serde_json::Value>)> {
+    envelope: Option<bool>,
+) -> Result<Json<ListOf<PinnedMessage>>, (Status, Json<serde_json::Value>)> {
     let conn = db.conn();
 
     // Verify room exists
@@ -264,5 +306,79 @@ pub fn list_pins(
         .filter_map(|r| r.ok())
         .collect();
 
-    Ok(Json(pins))
+    Ok(Json(ListOf::complete(pins, envelope)))
+}
+
+/// GET /api/v1/rooms/<room_id>/pins/history — every pin and unpin in the room, newest first, so
+/// messages unpinned to make room aren't lost. Entries keep the message's sender and content even
+/// after it is deleted. Page with `?before=<id>` (the last entry's id) and `?limit=` (1–200,
+/// default 50).
+#[get("/api/v1/rooms/<room_id>/pins/history?<limit>&<before>&<envelope>")]
+pub fn pin_history(
+    db: ScopedDb<'_>,
+    room_id: &str,
+    limit: Option<i64>,
+    before: Option<i64>,
+    envelope: Option<bool>,
+) -> Result<Json<ListOf<PinHistoryEntry>>, (Status, Json<serde_json::Value>)> {
+    let internal = |_: rusqlite::Error| {
+        (
+            Status::InternalServerError,
+            Json(serde_json::json!({"error": "Internal server error"})),
+        )
+    };
+    let conn = db.conn();
+
+    let room_exists: bool = conn
+        .query_row("SELECT COUNT(*) FROM rooms WHERE id = ?1", params![room_id], |r| r.get::<_, i64>(0))
+        .unwrap_or(0)
+        > 0;
+    if !room_exists {
+        return Err((
+            Status::NotFound,
+            Json(serde_json::json!({"error": "Room not found"})),
+        ));
+    }
+
+    let limit = limit.unwrap_or(50).clamp(1, 200);
+    let mut stmt = conn
+        .prepare(
+            "SELECT h.id, h.room_id, h.message_id, h.action, h.actor, h.created_at, h.sender, h.content,
+                    m.seq, m.pinned_at IS NOT NULL
+             FROM pin_history h
+             LEFT JOIN messages m ON m.id = h.message_id AND m.room_id = h.room_id
+             WHERE h.room_id = ?1 AND h.id < ?2
+             ORDER BY h.id DESC LIMIT ?3",
+        )
+        .map_err(internal)?;
+    let mut entries: Vec<PinHistoryEntry> = stmt
+        .query_map(params![room_id, before.unwrap_or(i64::MAX), limit + 1], |row| {
+            Ok(PinHistoryEntry {
+                id: row.get(0)?,
+                room_id: row.get(1)?,
+                message_id: row.get(2)?,
+                action: row.get(3)?,
+                actor: row.get(4)?,
+                at: row.get(5)?,
+                sender: row.get(6)?,
+                content: row.get(7)?,
+                seq: row.get(8)?,
+                pinned: row.get::<_, Option<bool>>(9)?.unwrap_or(false),
+            })
+        })
+        .map_err(internal)?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let has_more = entries.len() as i64 > limit;
+    entries.truncate(limit as usize);
+    if !envelope.unwrap_or(false) {
+        return Ok(Json(ListResponse::Plain(entries)));
+    }
+    let next_cursor = if has_more { entries.last().map(|e| e.id) } else { None };
+    Ok(Json(ListResponse::Envelope(ListEnvelope {
+        items: entries,
+        next_cursor,
+        has_more,
+    })))
 }
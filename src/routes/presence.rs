@@ -1,14 +1,15 @@
-use crate::db::Db;
+use crate::namespaces::ScopedDb;
 use rocket::http::Status;
 use rocket::serde::json::Json;
-use rocket::{get, State};
+use rocket::{get, put, State};
 use rusqlite::params;
 
 use super::PresenceTracker;
+use crate::sessions::ClientSession;
 
 #[get("/api/v1/rooms/<room_id>/presence")]
 pub fn room_presence(
-    db: &State<Db>,
+    db: ScopedDb<'_>,
     presence: &State<PresenceTracker>,
     room_id: &str,
 ) -> Result<Json<crate::models::RoomPresenceResponse>, (Status, Json<serde_json::Value>)> {
@@ -62,3 +63,51 @@ pub fn global_presence(
         total_online,
     })
 }
+
+/// Longest idle duration a device may report (one year).
+const MAX_IDLE_SECS: u64 = 31_536_000;
+
+/// PUT /api/v1/presence/device-state — a client relays its OS idle state so agents can tell
+/// whether a human is at the keyboard. Reports expire after DEVICE_STATE_TTL_SECS.
+#[put("/api/v1/presence/device-state", format = "json", data = "<body>")]
+pub fn report_device_state(
+    presence: &State<PresenceTracker>,
+    session: ClientSession,
+    body: Json<crate::models::ReportDeviceState>,
+) -> Result<Json<crate::models::DeviceAvailability>, (Status, Json<serde_json::Value>)> {
+    let bad = |msg: &str| (Status::BadRequest, Json(serde_json::json!({"error": msg})));
+    let sender = body.sender.trim();
+    if sender.is_empty() || sender.len() > 100 {
+        return Err(bad("Sender must be 1-100 characters"));
+    }
+    let device = body.device.as_deref().map(str::trim).unwrap_or("default");
+    if device.is_empty() || device.len() > 100 {
+        return Err(bad("Device must be 1-100 characters"));
+    }
+    let idle = match body.state.as_str() {
+        "active" => false,
+        "idle" => true,
+        _ => return Err(bad("state must be 'active' or 'idle'")),
+    };
+    if body.idle_secs.is_some_and(|s| s > MAX_IDLE_SECS) {
+        return Err(bad(&format!("idle_secs must be at most {MAX_IDLE_SECS}")));
+    }
+
+    presence.report_device(sender, device, idle, body.idle_secs);
+    presence.touch_session(&session, sender);
+    let mut availability = presence.availability(sender);
+    availability.sessions = presence.sessions(sender);
+    Ok(Json(availability))
+}
+
+/// GET /api/v1/presence/device-state/<sender> — a sender's effective availability, the devices
+/// behind it and its live client sessions, whether or not they are connected to a room stream.
+#[get("/api/v1/presence/device-state/<sender>")]
+pub fn get_device_state(
+    presence: &State<PresenceTracker>,
+    sender: &str,
+) -> Json<crate::models::DeviceAvailability> {
+    let mut availability = presence.availability(sender);
+    availability.sessions = presence.sessions(sender);
+    Json(availability)
+}
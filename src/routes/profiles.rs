@@ -1,9 +1,9 @@
-use crate::db::Db;
-use crate::events::{ChatEvent, EventBus};
-use crate::models::{Profile, UpsertProfile};
+use crate::namespaces::ScopedDb;
+use crate::events::{ChatEvent, Events};
+use crate::models::{ListOf, Profile, UpsertProfile};
 use rocket::http::Status;
 use rocket::serde::json::Json;
-use rocket::{delete, get, put, State};
+use rocket::{delete, get, put};
 use rusqlite::params;
 
 /// PUT /api/v1/profiles/<sender> — Create or update a profile
@@ -11,8 +11,8 @@ use rusqlite::params;
 pub fn upsert_profile(
     sender: &str,
     body: Json<UpsertProfile>,
-    db: &State<Db>,
-    events: &State<EventBus>,
+    db: ScopedDb<'_>,
+    events: Events<'_>,
 ) -> Result<Json<Profile>, (Status, Json<serde_json::Value>)> {
     // Validate sender (URL path param)
     let sender = sender.trim();
@@ -64,6 +64,70 @@ pub fn upsert_profile(
             Json(serde_json::json!({"error": "sender_type must be 'agent' or 'human'"})),
         ));
     }
+    let requested_locale = match body.locale.as_deref().map(str::trim) {
+        None => None,
+        Some("") => Some(None),
+        Some(tag) => match crate::i18n::supported(tag) {
+            Some(l) => Some(Some(l.to_string())),
+            None => {
+                return Err((
+                    Status::BadRequest,
+                    Json(serde_json::json!({
+                        "error": format!("locale must be one of: {}", crate::i18n::SUPPORTED.join(", "))
+                    })),
+                ));
+            }
+        },
+    };
+    // Some(None) clears the setting
+    let requested_nudge = match body.mention_nudge_minutes {
+        None => None,
+        Some(0) => Some(None),
+        Some(n) if (1..=1440).contains(&n) => Some(Some(n)),
+        Some(_) => {
+            return Err((
+                Status::BadRequest,
+                Json(serde_json::json!({"error": "mention_nudge_minutes must be 0 (off) or 1-1440"})),
+            ));
+        }
+    };
+    let requested_timezone = match body.timezone.as_deref().map(str::trim) {
+        None => None,
+        Some("") => Some(None),
+        Some(name) => match crate::timezones::parse(name) {
+            Some(tz) => Some(Some(tz.name().to_string())),
+            None => {
+                return Err((
+                    Status::BadRequest,
+                    Json(serde_json::json!({"error": format!("Unknown timezone '{name}': use an IANA name like Europe/Berlin")})),
+                ));
+            }
+        },
+    };
+    let requested_aliases: Option<Vec<String>> = match body.aliases {
+        None => None,
+        Some(ref list) => {
+            if list.len() > 20 {
+                return Err((
+                    Status::BadRequest,
+                    Json(serde_json::json!({"error": "At most 20 aliases per profile"})),
+                ));
+            }
+            let mut aliases: Vec<String> = Vec::new();
+            for alias in list.iter().map(|a| a.trim()) {
+                if alias.is_empty() || alias.len() > 100 {
+                    return Err((
+                        Status::BadRequest,
+                        Json(serde_json::json!({"error": "Aliases must be 1-100 characters"})),
+                    ));
+                }
+                if alias != sender && !aliases.iter().any(|a| a.eq_ignore_ascii_case(alias)) {
+                    aliases.push(alias.to_string());
+                }
+            }
+            Some(aliases)
+        }
+    };
     if let Some(ref meta) = body.metadata {
         let meta_str = serde_json::to_string(meta).unwrap_or_default();
         if meta_str.len() > 10_000 {
@@ -77,10 +141,37 @@ pub fn upsert_profile(
     let conn = db.conn();
     let now = chrono::Utc::now().to_rfc3339();
 
+    // A name that already belongs to another profile can't be taken, as a profile or an alias
+    let owner = crate::db::resolve_sender(&conn, sender);
+    if owner != sender {
+        return Err((
+            Status::Conflict,
+            Json(serde_json::json!({"error": format!("'{sender}' is an alias of '{owner}'")})),
+        ));
+    }
+    for alias in requested_aliases.iter().flatten() {
+        let owner = crate::db::resolve_sender(&conn, alias);
+        let taken = (owner != *alias && owner != sender)
+            || conn
+                .query_row(
+                    "SELECT COUNT(*) FROM profiles WHERE LOWER(sender) = LOWER(?1) AND sender != ?2",
+                    params![alias, sender],
+                    |r| r.get::<_, i64>(0),
+                )
+                .map(|c| c > 0)
+                .unwrap_or(false);
+        if taken {
+            return Err((
+                Status::Conflict,
+                Json(serde_json::json!({"error": format!("Alias '{alias}' already belongs to another sender")})),
+            ));
+        }
+    }
+
     // Check if profile already exists
     let existing: Option<Profile> = conn
         .query_row(
-            "SELECT sender, display_name, sender_type, avatar_url, bio, status_text, metadata, created_at, updated_at FROM profiles WHERE sender = ?1",
+            "SELECT sender, display_name, sender_type, avatar_url, bio, status_text, metadata, created_at, updated_at, locale, mention_nudge_minutes, timezone FROM profiles WHERE sender = ?1",
             params![sender],
             |row| {
                 let metadata_str: String = row.get(6)?;
@@ -91,6 +182,10 @@ pub fn upsert_profile(
                     avatar_url: row.get(3)?,
                     bio: row.get(4)?,
                     status_text: row.get(5)?,
+                    locale: row.get(9)?,
+                    mention_nudge_minutes: row.get(10)?,
+                    timezone: row.get(11)?,
+                    aliases: Vec::new(),
                     metadata: serde_json::from_str(&metadata_str).unwrap_or(serde_json::json!({})),
                     created_at: row.get(7)?,
                     updated_at: row.get(8)?,
@@ -125,6 +220,10 @@ pub fn upsert_profile(
         .status_text
         .clone()
         .or_else(|| existing.as_ref().and_then(|p| p.status_text.clone()));
+    let locale = requested_locale.unwrap_or_else(|| existing.as_ref().and_then(|p| p.locale.clone()));
+    let mention_nudge_minutes =
+        requested_nudge.unwrap_or_else(|| existing.as_ref().and_then(|p| p.mention_nudge_minutes));
+    let timezone = requested_timezone.unwrap_or_else(|| existing.as_ref().and_then(|p| p.timezone.clone()));
     let metadata = body
         .metadata
         .clone()
@@ -136,12 +235,15 @@ pub fn upsert_profile(
         });
     let metadata_str = serde_json::to_string(&metadata).unwrap_or_else(|_| "{}".to_string());
 
-    conn.execute(
-        "INSERT INTO profiles (sender, display_name, sender_type, avatar_url, bio, status_text, metadata, created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|_| (Status::InternalServerError, Json(serde_json::json!({"error": "Database error"}))))?;
+    tx.execute(
+        "INSERT INTO profiles (sender, display_name, sender_type, avatar_url, bio, status_text, metadata, created_at, updated_at, locale, mention_nudge_minutes, timezone)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
          ON CONFLICT(sender) DO UPDATE SET
            display_name = ?2, sender_type = ?3, avatar_url = ?4, bio = ?5,
-           status_text = ?6, metadata = ?7, updated_at = ?9",
+           status_text = ?6, metadata = ?7, updated_at = ?9, locale = ?10, mention_nudge_minutes = ?11, timezone = ?12",
         params![
             sender,
             &display_name,
@@ -152,9 +254,26 @@ pub fn upsert_profile(
             &metadata_str,
             &created_at,
             &now,
+            &locale,
+            mention_nudge_minutes,
+            &timezone,
         ],
     )
     .map_err(|_| (Status::InternalServerError, Json(serde_json::json!({"error": "Database error"}))))?;
+    if let Some(ref aliases) = requested_aliases {
+        tx.execute("DELETE FROM sender_aliases WHERE sender = ?1", params![sender])
+            .map_err(|_| (Status::InternalServerError, Json(serde_json::json!({"error": "Database error"}))))?;
+        for alias in aliases {
+            tx.execute(
+                "INSERT INTO sender_aliases (alias_key, alias, sender, created_at) VALUES (?1, ?2, ?3, ?4)",
+                params![alias.to_lowercase(), alias, sender, &now],
+            )
+            .map_err(|_| (Status::InternalServerError, Json(serde_json::json!({"error": "Database error"}))))?;
+        }
+    }
+    tx.commit()
+        .map_err(|_| (Status::InternalServerError, Json(serde_json::json!({"error": "Database error"}))))?;
+    let aliases = crate::db::sender_aliases(&conn, sender);
 
     let profile = Profile {
         sender: sender.to_string(),
@@ -163,6 +282,10 @@ pub fn upsert_profile(
         avatar_url,
         bio,
         status_text,
+        locale,
+        mention_nudge_minutes,
+        timezone,
+        aliases,
         metadata,
         created_at,
         updated_at: now,
@@ -172,13 +295,19 @@ pub fn upsert_profile(
     Ok(Json(profile))
 }
 
-/// GET /api/v1/profiles/<sender> — Get a single profile
+/// GET /api/v1/profiles/<sender> — Get a single profile (an alias returns the canonical profile)
 #[get("/api/v1/profiles/<sender>")]
-pub fn get_profile(sender: &str, db: &State<Db>) -> Result<Json<Profile>, rocket::http::Status> {
+pub fn get_profile(sender: &str, db: ScopedDb<'_>) -> Result<Json<Profile>, rocket::http::Status> {
     let conn = db.conn();
-    let profile = conn
+    let sender = crate::db::resolve_sender(&conn, sender);
+    load_profile(&conn, &sender).map(Json).ok_or(rocket::http::Status::NotFound)
+}
+
+/// A canonical sender's profile with its aliases, if it has one.
+pub(super) fn load_profile(conn: &rusqlite::Connection, sender: &str) -> Option<Profile> {
+    let mut profile = conn
         .query_row(
-            "SELECT sender, display_name, sender_type, avatar_url, bio, status_text, metadata, created_at, updated_at FROM profiles WHERE sender = ?1",
+            "SELECT sender, display_name, sender_type, avatar_url, bio, status_text, metadata, created_at, updated_at, locale, mention_nudge_minutes, timezone FROM profiles WHERE sender = ?1",
             params![sender],
             |row| {
                 let metadata_str: String = row.get(6)?;
@@ -189,43 +318,48 @@ pub fn get_profile(sender: &str, db: &State<Db>) -> Result<Json<Profile>, rocket
                     avatar_url: row.get(3)?,
                     bio: row.get(4)?,
                     status_text: row.get(5)?,
+                    locale: row.get(9)?,
+                    mention_nudge_minutes: row.get(10)?,
+                    timezone: row.get(11)?,
+                    aliases: Vec::new(),
                     metadata: serde_json::from_str(&metadata_str).unwrap_or(serde_json::json!({})),
                     created_at: row.get(7)?,
                     updated_at: row.get(8)?,
                 })
             },
         )
-        .map_err(|_| rocket::http::Status::NotFound)?;
-
-    Ok(Json(profile))
+        .ok()?;
+    profile.aliases = crate::db::sender_aliases(conn, &profile.sender);
+    Some(profile)
 }
 
 /// GET /api/v1/profiles?sender_type=agent — List all profiles
-#[get("/api/v1/profiles?<sender_type>")]
+#[get("/api/v1/profiles?<sender_type>&<envelope>")]
 pub fn list_profiles(
     sender_type: Option<&str>,
-    db: &State<Db>,
-) -> Json<Vec<Profile>> {
+    envelope: Option<bool>,
+    db: ScopedDb<'_>,
+) -> Json<ListOf<Profile>> {
     let conn = db.conn();
 
     let (sql, param_values): (&str, Vec<Box<dyn rusqlite::types::ToSql>>) = if let Some(st) = sender_type {
         (
-            "SELECT sender, display_name, sender_type, avatar_url, bio, status_text, metadata, created_at, updated_at FROM profiles WHERE sender_type = ?1 ORDER BY updated_at DESC",
+            "SELECT sender, display_name, sender_type, avatar_url, bio, status_text, metadata, created_at, updated_at, locale, mention_nudge_minutes, timezone FROM profiles WHERE sender_type = ?1 ORDER BY updated_at DESC",
             vec![Box::new(st.to_string()) as Box<dyn rusqlite::types::ToSql>],
         )
     } else {
         (
-            "SELECT sender, display_name, sender_type, avatar_url, bio, status_text, metadata, created_at, updated_at FROM profiles ORDER BY updated_at DESC",
+            "SELECT sender, display_name, sender_type, avatar_url, bio, status_text, metadata, created_at, updated_at, locale, mention_nudge_minutes, timezone FROM profiles ORDER BY updated_at DESC",
             vec![],
         )
     };
 
     let mut stmt = match conn.prepare(sql) {
         Ok(s) => s,
-        Err(_) => return Json(Vec::new()),
+        Err(_) => return Json(ListOf::complete(Vec::new(), envelope)),
     };
     let params: Vec<&dyn rusqlite::types::ToSql> = param_values.iter().map(|p| p.as_ref()).collect();
-    let profiles = match stmt
+    let mut profiles: Vec<Profile> = match stmt
         .query_map(params.as_slice(), |row| {
             let metadata_str: String = row.get(6)?;
             Ok(Profile {
@@ -235,6 +369,10 @@ pub fn list_profiles(
                 avatar_url: row.get(3)?,
                 bio: row.get(4)?,
                 status_text: row.get(5)?,
+                locale: row.get(9)?,
+                mention_nudge_minutes: row.get(10)?,
+                timezone: row.get(11)?,
+                aliases: Vec::new(),
                 metadata: serde_json::from_str(&metadata_str).unwrap_or(serde_json::json!({})),
                 created_at: row.get(7)?,
                 updated_at: row.get(8)?,
@@ -243,16 +381,19 @@ pub fn list_profiles(
         Ok(rows) => rows.filter_map(|r| r.ok()).collect(),
         Err(_) => Vec::new(),
     };
+    for profile in &mut profiles {
+        profile.aliases = crate::db::sender_aliases(&conn, &profile.sender);
+    }
 
-    Json(profiles)
+    Json(ListOf::complete(profiles, envelope))
 }
 
 /// DELETE /api/v1/profiles/<sender> — Delete a profile
 #[delete("/api/v1/profiles/<sender>")]
 pub fn delete_profile(
     sender: &str,
-    db: &State<Db>,
-    events: &State<EventBus>,
+    db: ScopedDb<'_>,
+    events: Events<'_>,
 ) -> rocket::http::Status {
     let conn = db.conn();
 
@@ -263,6 +404,8 @@ pub fn delete_profile(
     if affected == 0 {
         return rocket::http::Status::NotFound;
     }
+    conn.execute("DELETE FROM sender_aliases WHERE sender = ?1", params![sender])
+        .ok();
 
     events.publish(ChatEvent::ProfileDeleted {
         sender: sender.to_string(),
@@ -0,0 +1,100 @@
+use crate::namespaces::ScopedDb;
+use crate::models::{CreatePushSubscription, PushSubscription};
+use crate::push::PushConfig;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{delete, get, post, State};
+use rusqlite::params;
+
+fn err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+/// Decode a base64url key from the browser (padding optional) and re-encode it unpadded.
+fn normalize_key(field: &str, value: &str, len: usize) -> Result<String, (Status, Json<serde_json::Value>)> {
+    URL_SAFE_NO_PAD
+        .decode(value.trim().trim_end_matches('='))
+        .ok()
+        .filter(|bytes| bytes.len() == len)
+        .map(|bytes| URL_SAFE_NO_PAD.encode(bytes))
+        .ok_or_else(|| err(Status::BadRequest, &format!("keys.{field} must be a base64url {len}-byte key")))
+}
+
+/// GET /api/v1/push/vapid-public-key — the `applicationServerKey` for `pushManager.subscribe()`.
+#[get("/api/v1/push/vapid-public-key")]
+pub fn vapid_public_key(push: &State<Option<PushConfig>>) -> Json<serde_json::Value> {
+    match push.inner() {
+        Some(config) => Json(serde_json::json!({"enabled": true, "public_key": config.public_key})),
+        None => Json(serde_json::json!({"enabled": false, "public_key": null})),
+    }
+}
+
+/// POST /api/v1/push/subscriptions — register a browser push subscription for a sender. Mentions
+/// of that sender and DMs to them are pushed to it. Re-registering an endpoint updates it in place.
+#[post("/api/v1/push/subscriptions", format = "json", data = "<body>")]
+pub fn create_push_subscription(
+    db: ScopedDb<'_>,
+    push: &State<Option<PushConfig>>,
+    body: Json<CreatePushSubscription>,
+) -> Result<Json<PushSubscription>, (Status, Json<serde_json::Value>)> {
+    if push.is_none() {
+        return Err(err(Status::ServiceUnavailable, "Web Push is not available on this server"));
+    }
+    let sender = body.sender.trim();
+    if sender.is_empty() || sender.len() > 100 {
+        return Err(err(Status::BadRequest, "Sender must be 1-100 characters"));
+    }
+    let endpoint = body.endpoint.trim();
+    if !endpoint.starts_with("https://") || endpoint.len() > 2000 {
+        return Err(err(Status::BadRequest, "endpoint must be an https:// URL of at most 2000 characters"));
+    }
+    let p256dh = normalize_key("p256dh", &body.keys.p256dh, 65)?;
+    if p256::PublicKey::from_sec1_bytes(&URL_SAFE_NO_PAD.decode(&p256dh).unwrap_or_default()).is_err() {
+        return Err(err(Status::BadRequest, "keys.p256dh is not a P-256 public key"));
+    }
+    let auth = normalize_key("auth", &body.keys.auth, 16)?;
+
+    let conn = db.conn();
+    let now = chrono::Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO push_subscriptions (id, sender, endpoint, p256dh, auth, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT(endpoint) DO UPDATE SET sender = ?2, p256dh = ?4, auth = ?5",
+        params![uuid::Uuid::new_v4().to_string(), sender, endpoint, &p256dh, &auth, &now],
+    )
+    .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+
+    conn.query_row(
+        "SELECT id, sender, endpoint, created_at FROM push_subscriptions WHERE endpoint = ?1",
+        params![endpoint],
+        |r| {
+            Ok(PushSubscription {
+                id: r.get(0)?,
+                sender: r.get(1)?,
+                endpoint: r.get(2)?,
+                created_at: r.get(3)?,
+            })
+        },
+    )
+    .map(Json)
+    .map_err(|_| err(Status::InternalServerError, "Internal server error"))
+}
+
+/// DELETE /api/v1/push/subscriptions/<id> — unsubscribe. The id is only known to the browser
+/// that registered it.
+#[delete("/api/v1/push/subscriptions/<id>")]
+pub fn delete_push_subscription(
+    db: ScopedDb<'_>,
+    id: &str,
+) -> Result<Json<serde_json::Value>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    let deleted = conn
+        .execute("DELETE FROM push_subscriptions WHERE id = ?1", params![id])
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+    if deleted == 0 {
+        return Err(err(Status::NotFound, "Push subscription not found"));
+    }
+    Ok(Json(serde_json::json!({"deleted": true, "id": id})))
+}
@@ -0,0 +1,313 @@
+use crate::namespaces::ScopedDb;
+use crate::events::{ChatEvent, Events};
+use crate::models::{ClaimQueueItem, CompleteQueueItem, EnqueueItem, QueueClaim, QueueItem, QueueListResponse, ReleaseQueueItem};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{get, post};
+use rusqlite::{params, Connection};
+
+/// Most unfinished (pending or claimed) items one room's queue may hold.
+const MAX_OPEN_ITEMS: i64 = 10_000;
+
+const DEFAULT_LEASE_SECONDS: i64 = 300;
+const MAX_LEASE_SECONDS: i64 = 86_400;
+
+fn err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+fn room_exists(conn: &Connection, room_id: &str) -> bool {
+    conn.query_row("SELECT COUNT(*) FROM rooms WHERE id = ?1", params![room_id], |r| r.get::<_, i64>(0))
+        .map(|c| c > 0)
+        .unwrap_or(false)
+}
+
+fn check_sender(sender: &str) -> Result<&str, (Status, Json<serde_json::Value>)> {
+    let sender = sender.trim();
+    if sender.is_empty() || sender.len() > 100 {
+        return Err(err(Status::BadRequest, "Sender must be 1-100 characters"));
+    }
+    Ok(sender)
+}
+
+/// A JSON object of at most 10KB, serialized.
+fn check_object(value: &serde_json::Value, field: &str) -> Result<String, (Status, Json<serde_json::Value>)> {
+    if !value.is_object() {
+        return Err(err(Status::BadRequest, &format!("{field} must be a JSON object")));
+    }
+    let s = serde_json::to_string(value).unwrap_or_else(|_| "{}".to_string());
+    if s.len() > 10_000 {
+        return Err(err(Status::BadRequest, &format!("{field} must be at most 10KB when serialized")));
+    }
+    Ok(s)
+}
+
+const ITEM_COLUMNS: &str = "id, room_id, task, data, created_by, status, claimed_by, claimed_at, lease_expires_at, \
+                            attempts, completed_at, result, created_at";
+
+fn item_from_row(r: &rusqlite::Row) -> rusqlite::Result<QueueItem> {
+    let data: String = r.get(3)?;
+    let result: Option<String> = r.get(11)?;
+    Ok(QueueItem {
+        id: r.get(0)?,
+        room_id: r.get(1)?,
+        task: r.get(2)?,
+        data: serde_json::from_str(&data).unwrap_or(serde_json::json!({})),
+        created_by: r.get(4)?,
+        status: r.get(5)?,
+        claimed_by: r.get(6)?,
+        claimed_at: r.get(7)?,
+        lease_expires_at: r.get(8)?,
+        attempts: r.get(9)?,
+        completed_at: r.get(10)?,
+        result: result.and_then(|s| serde_json::from_str(&s).ok()),
+        created_at: r.get(12)?,
+    })
+}
+
+fn load_item(conn: &Connection, room_id: &str, item_id: &str) -> Option<QueueItem> {
+    conn.query_row(
+        &format!("SELECT {ITEM_COLUMNS} FROM queue_items WHERE id = ?1 AND room_id = ?2"),
+        params![item_id, room_id],
+        item_from_row,
+    )
+    .ok()
+}
+
+/// Return items whose claim lapsed without completion to the queue.
+fn requeue_expired(conn: &Connection, room_id: &str, now: &str) {
+    conn.execute(
+        "UPDATE queue_items SET status = 'pending', claimed_by = NULL, claimed_at = NULL, lease_expires_at = NULL
+         WHERE room_id = ?1 AND status = 'claimed' AND lease_expires_at < ?2",
+        params![room_id, now],
+    )
+    .ok();
+}
+
+fn pending_count(conn: &Connection, room_id: &str) -> i64 {
+    conn.query_row(
+        "SELECT COUNT(*) FROM queue_items WHERE room_id = ?1 AND status = 'pending'",
+        params![room_id],
+        |r| r.get(0),
+    )
+    .unwrap_or(0)
+}
+
+/// POST /api/v1/rooms/<room_id>/queue — add an item to the back of the room's queue
+#[post("/api/v1/rooms/<room_id>/queue", format = "json", data = "<body>")]
+pub fn enqueue_item(
+    db: ScopedDb<'_>,
+    events: Events<'_>,
+    room_id: &str,
+    body: Json<EnqueueItem>,
+) -> Result<Json<QueueItem>, (Status, Json<serde_json::Value>)> {
+    let sender = check_sender(&body.sender)?;
+    let task = body.task.trim();
+    if task.is_empty() || task.len() > 2000 {
+        return Err(err(Status::BadRequest, "task must be 1-2000 characters"));
+    }
+    let data = body.data.clone().unwrap_or(serde_json::json!({}));
+    let data_str = check_object(&data, "data")?;
+
+    let conn = db.conn();
+    if !room_exists(&conn, room_id) {
+        return Err(err(Status::NotFound, "Room not found"));
+    }
+    let open: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM queue_items WHERE room_id = ?1 AND status != 'done'",
+            params![room_id],
+            |r| r.get(0),
+        )
+        .unwrap_or(0);
+    if open >= MAX_OPEN_ITEMS {
+        return Err(err(
+            Status::UnprocessableEntity,
+            &format!("Queue is full ({MAX_OPEN_ITEMS} unfinished items)"),
+        ));
+    }
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let now = chrono::Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO queue_items (id, room_id, task, data, created_by, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![&id, room_id, task, &data_str, sender, &now],
+    )
+    .map_err(|_| err(Status::InternalServerError, "Database error"))?;
+    let item = load_item(&conn, room_id, &id).ok_or_else(|| err(Status::InternalServerError, "Database error"))?;
+    events.publish(ChatEvent::QueueItemAdded(item.clone()));
+    Ok(Json(item))
+}
+
+/// GET /api/v1/rooms/<room_id>/queue?status= — items in FIFO order (unfinished ones by default)
+#[get("/api/v1/rooms/<room_id>/queue?<status>&<limit>")]
+pub fn list_queue(
+    db: ScopedDb<'_>,
+    room_id: &str,
+    status: Option<&str>,
+    limit: Option<i64>,
+) -> Result<Json<QueueListResponse>, (Status, Json<serde_json::Value>)> {
+    let status = status.unwrap_or("open");
+    if !matches!(status, "open" | "pending" | "claimed" | "done" | "all") {
+        return Err(err(Status::BadRequest, "status must be 'open', 'pending', 'claimed', 'done' or 'all'"));
+    }
+    let limit = limit.unwrap_or(100).clamp(1, 1000);
+    let conn = db.conn();
+    if !room_exists(&conn, room_id) {
+        return Err(err(Status::NotFound, "Room not found"));
+    }
+    requeue_expired(&conn, room_id, &chrono::Utc::now().to_rfc3339());
+
+    let filter = match status {
+        "open" => "AND status != 'done'",
+        "pending" => "AND status = 'pending'",
+        "claimed" => "AND status = 'claimed'",
+        "done" => "AND status = 'done'",
+        _ => "",
+    };
+    let items: Vec<QueueItem> = conn
+        .prepare(&format!(
+            "SELECT {ITEM_COLUMNS} FROM queue_items WHERE room_id = ?1 {filter} ORDER BY seq LIMIT ?2"
+        ))
+        .and_then(|mut s| {
+            s.query_map(params![room_id, limit], item_from_row)
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .unwrap_or_default();
+    let count = items.len();
+    Ok(Json(QueueListResponse {
+        room_id: room_id.to_string(),
+        items,
+        count,
+    }))
+}
+
+/// POST /api/v1/rooms/<room_id>/queue/claim — take the oldest pending item. The select and the
+/// update run in one transaction and the update re-checks the status, so two agents claiming at
+/// once never get the same item.
+#[post("/api/v1/rooms/<room_id>/queue/claim", format = "json", data = "<body>")]
+pub fn claim_queue_item(
+    db: ScopedDb<'_>,
+    events: Events<'_>,
+    room_id: &str,
+    body: Json<ClaimQueueItem>,
+) -> Result<Json<QueueClaim>, (Status, Json<serde_json::Value>)> {
+    let sender = check_sender(&body.sender)?;
+    let lease = body.lease_seconds.unwrap_or(DEFAULT_LEASE_SECONDS);
+    if !(1..=MAX_LEASE_SECONDS).contains(&lease) {
+        return Err(err(
+            Status::BadRequest,
+            &format!("lease_seconds must be between 1 and {MAX_LEASE_SECONDS}"),
+        ));
+    }
+
+    let conn = db.conn();
+    if !room_exists(&conn, room_id) {
+        return Err(err(Status::NotFound, "Room not found"));
+    }
+    let now = chrono::Utc::now();
+    let now_str = now.to_rfc3339();
+    let expires = (now + chrono::Duration::seconds(lease)).to_rfc3339();
+
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|_| err(Status::InternalServerError, "Database error"))?;
+    requeue_expired(&tx, room_id, &now_str);
+    let next: Option<String> = tx
+        .query_row(
+            "SELECT id FROM queue_items WHERE room_id = ?1 AND status = 'pending' ORDER BY seq LIMIT 1",
+            params![room_id],
+            |r| r.get(0),
+        )
+        .ok();
+    let claimed = match next {
+        None => None,
+        Some(id) => {
+            let updated = tx
+                .execute(
+                    "UPDATE queue_items SET status = 'claimed', claimed_by = ?1, claimed_at = ?2,
+                       lease_expires_at = ?3, attempts = attempts + 1
+                     WHERE id = ?4 AND status = 'pending'",
+                    params![sender, &now_str, &expires, &id],
+                )
+                .map_err(|_| err(Status::InternalServerError, "Database error"))?;
+            if updated == 1 { load_item(&tx, room_id, &id) } else { None }
+        }
+    };
+    tx.commit()
+        .map_err(|_| err(Status::InternalServerError, "Database error"))?;
+
+    if let Some(ref item) = claimed {
+        events.publish(ChatEvent::QueueItemClaimed(item.clone()));
+    }
+    Ok(Json(QueueClaim {
+        item: claimed,
+        pending: pending_count(&conn, room_id),
+    }))
+}
+
+/// Load an item that `sender` currently holds, or explain why it can't be finished.
+fn held_item(conn: &Connection, room_id: &str, item_id: &str, sender: &str) -> Result<QueueItem, (Status, Json<serde_json::Value>)> {
+    requeue_expired(conn, room_id, &chrono::Utc::now().to_rfc3339());
+    let item = load_item(conn, room_id, item_id).ok_or_else(|| err(Status::NotFound, "Queue item not found"))?;
+    match item.status.as_str() {
+        "done" => Err(err(Status::Conflict, "Queue item is already completed")),
+        "claimed" if item.claimed_by.as_deref() == Some(sender) => Ok(item),
+        "claimed" => Err(err(Status::Conflict, "Queue item is claimed by another sender")),
+        _ => Err(err(Status::Conflict, "Queue item is not claimed (or its lease expired); claim it first")),
+    }
+}
+
+/// POST /api/v1/rooms/<room_id>/queue/<item_id>/complete — mark a claimed item done (claimer only)
+#[post("/api/v1/rooms/<room_id>/queue/<item_id>/complete", format = "json", data = "<body>")]
+pub fn complete_queue_item(
+    db: ScopedDb<'_>,
+    events: Events<'_>,
+    room_id: &str,
+    item_id: &str,
+    body: Json<CompleteQueueItem>,
+) -> Result<Json<QueueItem>, (Status, Json<serde_json::Value>)> {
+    let sender = check_sender(&body.sender)?;
+    let result = body.result.as_ref().map(|r| check_object(r, "result")).transpose()?;
+
+    let conn = db.conn();
+    held_item(&conn, room_id, item_id, sender)?;
+    let now = chrono::Utc::now().to_rfc3339();
+    let updated = conn
+        .execute(
+            "UPDATE queue_items SET status = 'done', completed_at = ?1, lease_expires_at = NULL, result = ?2
+             WHERE id = ?3 AND status = 'claimed' AND claimed_by = ?4",
+            params![&now, &result, item_id, sender],
+        )
+        .map_err(|_| err(Status::InternalServerError, "Database error"))?;
+    if updated != 1 {
+        return Err(err(Status::Conflict, "Queue item is no longer claimed by this sender"));
+    }
+    let item = load_item(&conn, room_id, item_id).ok_or_else(|| err(Status::InternalServerError, "Database error"))?;
+    events.publish(ChatEvent::QueueItemCompleted(item.clone()));
+    Ok(Json(item))
+}
+
+/// POST /api/v1/rooms/<room_id>/queue/<item_id>/release — give a claimed item back to the queue
+/// (claimer only). It keeps its place at the front.
+#[post("/api/v1/rooms/<room_id>/queue/<item_id>/release", format = "json", data = "<body>")]
+pub fn release_queue_item(
+    db: ScopedDb<'_>,
+    events: Events<'_>,
+    room_id: &str,
+    item_id: &str,
+    body: Json<ReleaseQueueItem>,
+) -> Result<Json<QueueItem>, (Status, Json<serde_json::Value>)> {
+    let sender = check_sender(&body.sender)?;
+    let conn = db.conn();
+    held_item(&conn, room_id, item_id, sender)?;
+    conn.execute(
+        "UPDATE queue_items SET status = 'pending', claimed_by = NULL, claimed_at = NULL, lease_expires_at = NULL
+         WHERE id = ?1 AND status = 'claimed' AND claimed_by = ?2",
+        params![item_id, sender],
+    )
+    .map_err(|_| err(Status::InternalServerError, "Database error"))?;
+    let item = load_item(&conn, room_id, item_id).ok_or_else(|| err(Status::InternalServerError, "Database error"))?;
+    events.publish(ChatEvent::QueueItemReleased(item.clone()));
+    Ok(Json(item))
+}
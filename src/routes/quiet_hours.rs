@@ -0,0 +1,134 @@
+use crate::namespaces::ScopedDb;
+use crate::models::{DeferredMessagesResponse, RoomQuietHours, SetQuietHours};
+use crate::quiet_hours::{parse_time, room_quiet_hours};
+use chrono::NaiveTime;
+use chrono_tz::Tz;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{delete, get, put};
+use rusqlite::{params, Connection};
+
+use super::AdminKey;
+
+fn err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+fn check_admin(conn: &Connection, room_id: &str, admin: &AdminKey) -> Result<(), (Status, Json<serde_json::Value>)> {
+    let key: Option<String> = conn
+        .query_row("SELECT admin_key FROM rooms WHERE id = ?1", params![room_id], |r| r.get(0))
+        .map_err(|_| err(Status::NotFound, "Room not found"))?;
+    if key.as_deref() != Some(admin.0.as_str()) {
+        return Err(err(Status::Forbidden, "Invalid admin key for this room"));
+    }
+    Ok(())
+}
+
+fn check_room(conn: &Connection, room_id: &str) -> Result<(), (Status, Json<serde_json::Value>)> {
+    conn.query_row("SELECT 1 FROM rooms WHERE id = ?1", params![room_id], |_| Ok(()))
+        .map_err(|_| err(Status::NotFound, "Room not found"))
+}
+
+/// Check a window: both times parse, differ, and the timezone (default UTC) is known.
+pub(super) fn validate(body: &SetQuietHours) -> Result<(NaiveTime, NaiveTime, Tz), (Status, Json<serde_json::Value>)> {
+    let (Some(start), Some(end)) = (parse_time(&body.start), parse_time(&body.end)) else {
+        return Err(err(Status::BadRequest, "start and end must be times of day as HH:MM (e.g. 22:00)"));
+    };
+    if start == end {
+        return Err(err(Status::BadRequest, "start and end must differ"));
+    }
+    let timezone = body.timezone.as_deref().map(str::trim).filter(|t| !t.is_empty()).unwrap_or("UTC");
+    let Some(tz) = crate::timezones::parse(timezone) else {
+        return Err(err(
+            Status::BadRequest,
+            &format!("Unknown timezone '{timezone}': use an IANA name like Europe/Berlin"),
+        ));
+    };
+    Ok((start, end, tz))
+}
+
+/// Set or replace the room's quiet hours.
+pub(super) fn save(conn: &Connection, room_id: &str, start: NaiveTime, end: NaiveTime, tz: Tz) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO room_quiet_hours (room_id, start_time, end_time, timezone, updated_at) VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(room_id) DO UPDATE SET start_time = ?2, end_time = ?3, timezone = ?4, updated_at = ?5",
+        params![
+            room_id,
+            start.format("%H:%M").to_string(),
+            end.format("%H:%M").to_string(),
+            tz.name(),
+            chrono::Utc::now().to_rfc3339()
+        ],
+    )?;
+    Ok(())
+}
+
+/// GET /api/v1/rooms/<room_id>/quiet-hours — the room's quiet hours, whether they are in effect,
+/// and how many agent posts are waiting (404 if none are set).
+#[get("/api/v1/rooms/<room_id>/quiet-hours")]
+pub fn get_quiet_hours(
+    db: ScopedDb<'_>,
+    room_id: &str,
+) -> Result<Json<RoomQuietHours>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    check_room(&conn, room_id)?;
+    room_quiet_hours(&conn, room_id)
+        .map(Json)
+        .ok_or_else(|| err(Status::NotFound, "No quiet hours configured for this room"))
+}
+
+/// PUT /api/v1/rooms/<room_id>/quiet-hours — set or replace the room's quiet hours (admin key).
+#[put("/api/v1/rooms/<room_id>/quiet-hours", format = "json", data = "<body>")]
+pub fn set_quiet_hours(
+    db: ScopedDb<'_>,
+    room_id: &str,
+    admin: AdminKey,
+    body: Json<SetQuietHours>,
+) -> Result<Json<RoomQuietHours>, (Status, Json<serde_json::Value>)> {
+    let (start, end, tz) = validate(&body)?;
+
+    let conn = db.conn();
+    check_admin(&conn, room_id, &admin)?;
+    save(&conn, room_id, start, end, tz).map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+
+    room_quiet_hours(&conn, room_id)
+        .map(Json)
+        .ok_or_else(|| err(Status::InternalServerError, "Internal server error"))
+}
+
+/// DELETE /api/v1/rooms/<room_id>/quiet-hours — remove the room's quiet hours (admin key).
+/// Posts still waiting go out on the next delivery pass.
+#[delete("/api/v1/rooms/<room_id>/quiet-hours")]
+pub fn delete_quiet_hours(
+    db: ScopedDb<'_>,
+    room_id: &str,
+    admin: AdminKey,
+) -> Result<Json<serde_json::Value>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    check_admin(&conn, room_id, &admin)?;
+    let removed = conn
+        .execute("DELETE FROM room_quiet_hours WHERE room_id = ?1", params![room_id])
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+    if removed == 0 {
+        return Err(err(Status::NotFound, "No quiet hours configured for this room"));
+    }
+    Ok(Json(serde_json::json!({"deleted": true, "room_id": room_id})))
+}
+
+/// GET /api/v1/rooms/<room_id>/quiet-hours/queue — agent posts waiting for the window to end,
+/// in delivery order.
+#[get("/api/v1/rooms/<room_id>/quiet-hours/queue")]
+pub fn quiet_hours_queue(
+    db: ScopedDb<'_>,
+    room_id: &str,
+) -> Result<Json<DeferredMessagesResponse>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    check_room(&conn, room_id)?;
+    let messages = crate::quiet_hours::queued(&conn, room_id);
+    let count = messages.len();
+    Ok(Json(DeferredMessagesResponse {
+        room_id: room_id.to_string(),
+        messages,
+        count,
+    }))
+}
@@ -0,0 +1,54 @@
+use crate::models::{SenderQuota, SetSenderQuota};
+use crate::namespaces::ScopedDb;
+use crate::quotas;
+use crate::rate_limit::RateLimitConfig;
+use crate::senders::{SenderPolicy, ServerToken};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{get, put, State};
+
+fn err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+fn check_sender(sender: Option<&str>) -> Result<&str, (Status, Json<serde_json::Value>)> {
+    let sender = sender.map(str::trim).unwrap_or_default();
+    if sender.is_empty() || sender.len() > 100 {
+        return Err(err(Status::BadRequest, "sender must be 1-100 characters"));
+    }
+    Ok(sender)
+}
+
+/// GET /api/v1/quota?sender=X — a sender's daily limits, usage and remaining budget
+#[get("/api/v1/quota?<sender>")]
+pub fn get_quota(
+    db: ScopedDb<'_>,
+    rate_config: &State<RateLimitConfig>,
+    sender: Option<&str>,
+) -> Result<Json<SenderQuota>, (Status, Json<serde_json::Value>)> {
+    let sender = check_sender(sender)?;
+    let conn = db.conn();
+    Ok(Json(quotas::status(&conn, rate_config, sender)))
+}
+
+/// PUT /api/v1/quota/<sender> — give a sender its own daily limits (server token required).
+/// Null fields fall back to the server default; 0 means unlimited.
+#[put("/api/v1/quota/<sender>", format = "json", data = "<body>")]
+pub fn set_quota(
+    db: ScopedDb<'_>,
+    rate_config: &State<RateLimitConfig>,
+    sender_policy: &State<SenderPolicy>,
+    server_token: ServerToken,
+    sender: &str,
+    body: Json<SetSenderQuota>,
+) -> Result<Json<SenderQuota>, (Status, Json<serde_json::Value>)> {
+    sender_policy.check_server_token(&server_token)?;
+    let sender = check_sender(Some(sender))?;
+    if body.daily_messages.is_some_and(|n| n < 0) || body.daily_upload_bytes.is_some_and(|n| n < 0) {
+        return Err(err(Status::BadRequest, "Quotas must be 0 (unlimited) or greater"));
+    }
+    let conn = db.conn();
+    quotas::set_limits(&conn, sender, body.daily_messages, body.daily_upload_bytes)
+        .map_err(|_| err(Status::InternalServerError, "Database error"))?;
+    Ok(Json(quotas::status(&conn, rate_config, sender)))
+}
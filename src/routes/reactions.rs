@@ -1,23 +1,31 @@
-use crate::db::Db;
-use crate::events::{ChatEvent, EventBus};
+use crate::membership::MemberAccess;
+use crate::namespaces::ScopedDb;
+use crate::events::{ChatEvent, Events};
 use crate::models::*;
+use crate::rate_limit::{RateLimitConfig, RateLimited, RateLimiter};
 use rocket::http::Status;
 use rocket::serde::json::Json;
 use rocket::{delete, get, post, State};
 use rusqlite::params;
 
+use super::ClientIp;
+
 #[post(
     "/api/v1/rooms/<room_id>/messages/<message_id>/reactions",
     format = "json",
     data = "<body>"
 )]
+#[allow(clippy::too_many_arguments)]
 pub fn add_reaction(
-    db: &State<Db>,
-    events: &State<EventBus>,
+    db: ScopedDb<'_>,
+    events: Events<'_>,
+    rate_limiter: &State<RateLimiter>,
+    rate_config: &State<RateLimitConfig>,
+    ip: ClientIp,
     room_id: &str,
     message_id: &str,
     body: Json<AddReaction>,
-) -> Result<Json<Reaction>, (Status, Json<serde_json::Value>)> {
+) -> Result<RateLimited<Reaction>, (Status, Json<serde_json::Value>)> {
     let sender = body.sender.trim();
     let emoji = body.emoji.trim();
     if sender.is_empty() || sender.len() > 100 {
@@ -39,6 +47,28 @@ pub fn add_reaction(
             Json(serde_json::json!({"error": "Emoji too long (max 32 characters)"})),
         ));
     }
+    // `:thumbsup:`, `:+1:` and 👍 all count as the same reaction
+    let emoji = crate::emoji::normalize(emoji);
+    let emoji = emoji.as_str();
+
+    // Keyed per client and sender: one noisy agent can't flood a shared host's budget, and a
+    // sender name can't be drained from another address
+    let rl = rate_limiter.check_with_info(
+        &format!("reaction:{}|{}", ip.0, sender),
+        rate_config.reactions_max,
+        rate_config.reactions_window_secs,
+    );
+    if !rl.allowed {
+        return Err((
+            Status::TooManyRequests,
+            Json(serde_json::json!({
+                "error": format!("Rate limited: max {} reactions per minute", rate_config.reactions_max),
+                "retry_after_secs": rl.retry_after_secs,
+                "limit": rl.limit,
+                "remaining": 0
+            })),
+        ));
+    }
 
     let conn = db.conn();
 
@@ -80,11 +110,52 @@ pub fn add_reaction(
             room_id: room_id.to_string(),
             sender: sender.to_string(),
             emoji: emoji.to_string(),
+            shortcode: crate::emoji::shortcode(emoji),
             created_at: String::new(),
         };
         events.publish(ChatEvent::ReactionRemoved(reaction.clone()));
         // Return the removed reaction with a note
-        return Ok(Json(reaction));
+        return Ok(RateLimited::new(Json(reaction), rl));
+    }
+
+    // Rooms with an allow-list only take those emoji (toggling off an older reaction still works)
+    let allowed = super::rooms::allowed_reactions(&conn, room_id);
+    if !allowed.is_empty() && !allowed.iter().any(|a| a == emoji) {
+        return Err((
+            Status::BadRequest,
+            Json(serde_json::json!({
+                "error": format!("Reactions in this room are limited to: {}", allowed.join(" ")),
+                "allowed_reactions": allowed
+            })),
+        ));
+    }
+
+    // Cap distinct emoji per message; piling onto an existing emoji is always allowed
+    let emoji_present: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM message_reactions WHERE message_id = ?1 AND emoji = ?2",
+            params![message_id, emoji],
+            |r| r.get::<_, i64>(0),
+        )
+        .map(|c| c > 0)
+        .unwrap_or(false);
+    if !emoji_present {
+        let distinct_emoji: i64 = conn
+            .query_row(
+                "SELECT COUNT(DISTINCT emoji) FROM message_reactions WHERE message_id = ?1",
+                params![message_id],
+                |r| r.get(0),
+            )
+            .unwrap_or(0);
+        if distinct_emoji as usize >= rate_config.reaction_emoji_max {
+            return Err((
+                Status::Conflict,
+                Json(serde_json::json!({
+                    "error": format!("Message already has the maximum of {} distinct reactions", rate_config.reaction_emoji_max),
+                    "max_distinct_emoji": rate_config.reaction_emoji_max
+                })),
+            ));
+        }
     }
 
     // Add new reaction
@@ -108,18 +179,19 @@ pub fn add_reaction(
         room_id: room_id.to_string(),
         sender: sender.to_string(),
         emoji: emoji.to_string(),
+        shortcode: crate::emoji::shortcode(emoji),
         created_at: now,
     };
 
     events.publish(ChatEvent::ReactionAdded(reaction.clone()));
 
-    Ok(Json(reaction))
+    Ok(RateLimited::new(Json(reaction), rl))
 }
 
 #[delete("/api/v1/rooms/<room_id>/messages/<message_id>/reactions?<sender>&<emoji>")]
 pub fn remove_reaction(
-    db: &State<Db>,
-    events: &State<EventBus>,
+    db: ScopedDb<'_>,
+    events: Events<'_>,
     room_id: &str,
     message_id: &str,
     sender: &str,
@@ -133,6 +205,8 @@ pub fn remove_reaction(
             Json(serde_json::json!({"error": "Sender and emoji are required"})),
         ));
     }
+    let emoji = crate::emoji::normalize(emoji);
+    let emoji = emoji.as_str();
 
     let conn = db.conn();
 
@@ -175,6 +249,7 @@ pub fn remove_reaction(
                 room_id: room_id.to_string(),
                 sender: sender.to_string(),
                 emoji: emoji.to_string(),
+                shortcode: crate::emoji::shortcode(emoji),
                 created_at: String::new(),
             };
             events.publish(ChatEvent::ReactionRemoved(reaction));
@@ -190,7 +265,7 @@ pub fn remove_reaction(
 
 #[get("/api/v1/rooms/<room_id>/messages/<message_id>/reactions")]
 pub fn get_reactions(
-    db: &State<Db>,
+    db: ScopedDb<'_>,
     room_id: &str,
     message_id: &str,
 ) -> Result<Json<ReactionsResponse>, (Status, Json<serde_json::Value>)> {
@@ -228,6 +303,7 @@ pub fn get_reactions(
             let count: i64 = row.get(2)?;
             let senders: Vec<String> = senders_str.split(',').map(|s| s.to_string()).collect();
             Ok(ReactionSummary {
+                shortcode: crate::emoji::shortcode(&emoji),
                 emoji,
                 count,
                 senders,
@@ -246,7 +322,7 @@ pub fn get_reactions(
 /// Get all reactions for all messages in a room (bulk fetch)
 #[get("/api/v1/rooms/<room_id>/reactions")]
 pub fn get_room_reactions(
-    db: &State<Db>,
+    db: ScopedDb<'_>,
     room_id: &str,
 ) -> Result<Json<RoomReactionsResponse>, (Status, Json<serde_json::Value>)> {
     let conn = db.conn();
@@ -301,6 +377,7 @@ pub fn get_room_reactions(
             .entry(message_id)
             .or_default()
             .push(ReactionSummary {
+                shortcode: crate::emoji::shortcode(&emoji),
                 emoji,
                 count,
                 senders,
@@ -312,3 +389,91 @@ pub fn get_room_reactions(
         reactions: reactions_map,
     }))
 }
+
+/// Max message ids per bulk reaction request (a few pages of messages).
+const MAX_BULK_REACTION_IDS: usize = 200;
+
+/// Get grouped reactions for an explicit set of messages, which may span rooms. Messages in
+/// private rooms the request can't see are reported as missing.
+#[post("/api/v1/reactions/bulk", format = "json", data = "<body>")]
+pub fn bulk_reactions(
+    db: ScopedDb<'_>,
+    body: Json<BulkReactionsRequest>,
+    access: MemberAccess,
+) -> Result<Json<BulkReactionsResponse>, (Status, Json<serde_json::Value>)> {
+    let mut ids: Vec<String> = Vec::new();
+    for id in &body.message_ids {
+        let id = id.trim();
+        if !id.is_empty() && !ids.iter().any(|seen| seen == id) {
+            ids.push(id.to_string());
+        }
+    }
+    if ids.is_empty() {
+        return Err((
+            Status::BadRequest,
+            Json(serde_json::json!({"error": "message_ids must contain at least one id"})),
+        ));
+    }
+    if ids.len() > MAX_BULK_REACTION_IDS {
+        return Err((
+            Status::BadRequest,
+            Json(serde_json::json!({"error": format!("At most {MAX_BULK_REACTION_IDS} message_ids per request")})),
+        ));
+    }
+
+    let conn = db.conn();
+    let placeholders = (1..=ids.len()).map(|i| format!("?{i}")).collect::<Vec<_>>().join(",");
+    let internal = |_| (Status::InternalServerError, Json(serde_json::json!({"error": "Internal server error"})));
+
+    let mut reactions_map: std::collections::HashMap<String, Vec<ReactionSummary>> =
+        std::collections::HashMap::new();
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT id FROM messages WHERE id IN ({placeholders}) AND {}",
+            access.visible_sql("room_id")
+        ))
+        .map_err(internal)?;
+    let found = stmt
+        .query_map(rusqlite::params_from_iter(&ids), |row| row.get::<_, String>(0))
+        .map_err(internal)?
+        .filter_map(|r| r.ok());
+    for id in found {
+        reactions_map.insert(id, Vec::new());
+    }
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT message_id, emoji, GROUP_CONCAT(sender, ','), COUNT(*) \
+             FROM message_reactions WHERE message_id IN (SELECT id FROM messages WHERE id IN ({placeholders}) AND {}) \
+             GROUP BY message_id, emoji \
+             ORDER BY message_id, MIN(created_at) ASC",
+            access.visible_sql("room_id")
+        ))
+        .map_err(internal)?;
+    let rows: Vec<(String, String, String, i64)> = stmt
+        .query_map(rusqlite::params_from_iter(&ids), |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })
+        .map_err(internal)?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    for (message_id, emoji, senders_str, count) in rows {
+        let senders: Vec<String> = senders_str.split(',').map(|s| s.to_string()).collect();
+        reactions_map
+            .entry(message_id)
+            .or_default()
+            .push(ReactionSummary {
+                shortcode: crate::emoji::shortcode(&emoji),
+                emoji,
+                count,
+                senders,
+            });
+    }
+
+    let missing = ids.into_iter().filter(|id| !reactions_map.contains_key(id)).collect();
+    Ok(Json(BulkReactionsResponse {
+        reactions: reactions_map,
+        missing,
+    }))
+}
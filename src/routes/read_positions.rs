@@ -1,11 +1,18 @@
+use std::collections::{HashMap, HashSet};
+
 use rocket::serde::json::Json;
-use rocket::{get, put, State};
+use rocket::{get, put};
 use rocket::http::Status;
-use rusqlite::params;
+use rusqlite::{params, Connection};
 
-use crate::db::Db;
-use crate::events::{ChatEvent, EventBus};
-use crate::models::{ReadPosition, UnreadInfo, UnreadResponse, UpdateReadPosition};
+use crate::membership::MemberAccess;
+use crate::namespaces::ScopedDb;
+use crate::subscriptions;
+use crate::events::{ChatEvent, Events};
+use crate::models::{
+    MessageReadBy, MessageReader, ReadPosition, ThreadReadPosition, ThreadUnreadInfo, ThreadUnreadResponse, UnreadInfo,
+    UnreadResponse, UpdateReadPosition,
+};
 
 /// PUT /api/v1/rooms/<room_id>/read — Mark room as read up to a seq number.
 /// Upserts the read position for the given sender.
@@ -13,8 +20,8 @@ use crate::models::{ReadPosition, UnreadInfo, UnreadResponse, UpdateReadPosition
 pub fn update_read_position(
     room_id: &str,
     body: Json<UpdateReadPosition>,
-    db: &State<Db>,
-    events: &State<EventBus>,
+    db: ScopedDb<'_>,
+    events: Events<'_>,
 ) -> Result<Json<ReadPosition>, (Status, Json<serde_json::Value>)> {
     let conn = db.conn();
 
@@ -81,7 +88,7 @@ pub fn update_read_position(
 
 /// GET /api/v1/rooms/<room_id>/read — Get all read positions for a room.
 #[get("/api/v1/rooms/<room_id>/read")]
-pub fn get_read_positions(room_id: &str, db: &State<Db>) -> Result<Json<Vec<ReadPosition>>, (Status, Json<serde_json::Value>)> {
+pub fn get_read_positions(room_id: &str, db: ScopedDb<'_>) -> Result<Json<Vec<ReadPosition>>, (Status, Json<serde_json::Value>)> {
     let conn = db.conn();
 
     // Verify room exists
@@ -120,36 +127,166 @@ pub fn get_read_positions(room_id: &str, db: &State<Db>) -> Result<Json<Vec<Read
     Ok(Json(positions))
 }
 
-/// GET /api/v1/unread?sender=<name> — Get unread counts across all rooms for a sender.
-#[get("/api/v1/unread?<sender>")]
-pub fn get_unread(sender: &str, db: &State<Db>) -> Result<Json<UnreadResponse>, (Status, Json<serde_json::Value>)> {
+/// GET /api/v1/rooms/<room_id>/messages/<message_id>/read-by — Who has seen a message: senders
+/// whose room read position, or read position in the message's thread, is at or past its seq.
+/// Everyone else who has posted in the room is listed as `unread_by`. Aliases fold into their
+/// canonical sender, and the message's own sender is left out of both lists.
+#[get("/api/v1/rooms/<room_id>/messages/<message_id>/read-by")]
+pub fn get_message_read_by(
+    room_id: &str,
+    message_id: &str,
+    db: ScopedDb<'_>,
+) -> Result<Json<MessageReadBy>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    let db_err = |_: rusqlite::Error| (Status::InternalServerError, Json(serde_json::json!({"error": "Database error"})));
+
+    let (seq, sender, reply_to): (i64, String, Option<String>) = conn
+        .query_row(
+            "SELECT seq, sender, reply_to FROM messages WHERE id = ?1 AND room_id = ?2",
+            params![message_id, room_id],
+            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+        )
+        .map_err(|_| (Status::NotFound, Json(serde_json::json!({"error": "Message not found in this room"}))))?;
+
+    // Walk up reply_to to the thread root, whose thread read positions also cover this message
+    let mut root = message_id.to_string();
+    let mut parent = reply_to;
+    let mut visited = HashSet::from([root.clone()]);
+    while let Some(parent_id) = parent.take() {
+        if !visited.insert(parent_id.clone()) {
+            break;
+        }
+        if let Ok(next) = conn.query_row(
+            "SELECT reply_to FROM messages WHERE id = ?1 AND room_id = ?2",
+            params![&parent_id, room_id],
+            |r| r.get::<_, Option<String>>(0),
+        ) {
+            root = parent_id;
+            parent = next;
+        }
+    }
+
+    // Furthest position per canonical sender across the room and the thread
+    let mut positions: HashMap<String, MessageReader> = HashMap::new();
+    {
+        let mut stmt = conn
+            .prepare(
+                "SELECT COALESCE(a.sender, p.sender), p.last_read_seq, p.updated_at FROM (
+                     SELECT sender, last_read_seq, updated_at FROM read_positions WHERE room_id = ?1
+                     UNION ALL
+                     SELECT sender, last_read_seq, updated_at FROM thread_read_positions WHERE root_id = ?2
+                 ) p
+                 LEFT JOIN sender_aliases a ON a.alias_key = LOWER(p.sender)",
+            )
+            .map_err(db_err)?;
+        let rows = stmt
+            .query_map(params![room_id, &root], |r| {
+                Ok(MessageReader {
+                    sender: r.get(0)?,
+                    last_read_seq: r.get(1)?,
+                    updated_at: r.get(2)?,
+                })
+            })
+            .map_err(db_err)?;
+        for reader in rows.filter_map(|r| r.ok()) {
+            match positions.get(&reader.sender) {
+                Some(seen) if seen.last_read_seq >= reader.last_read_seq => {}
+                _ => {
+                    positions.insert(reader.sender.clone(), reader);
+                }
+            }
+        }
+    }
+
+    let participants: Vec<String> = conn
+        .prepare(
+            "SELECT DISTINCT COALESCE(a.sender, m.sender) FROM messages m
+             LEFT JOIN sender_aliases a ON a.alias_key = LOWER(m.sender)
+             WHERE m.room_id = ?1 AND m.kind != 'system'",
+        )
+        .and_then(|mut s| {
+            s.query_map(params![room_id], |r| r.get(0))
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .map_err(db_err)?;
+
+    let author = crate::db::resolve_sender(&conn, &sender);
+    let mut read_by: Vec<MessageReader> = positions
+        .into_values()
+        .filter(|p| p.sender != author && p.last_read_seq >= seq)
+        .collect();
+    read_by.sort_by(|a, b| a.updated_at.cmp(&b.updated_at).then_with(|| a.sender.cmp(&b.sender)));
+    let mut unread_by: Vec<String> = participants
+        .into_iter()
+        .filter(|p| *p != author && !read_by.iter().any(|r| r.sender == *p))
+        .collect();
+    unread_by.sort_by_key(|p| p.to_lowercase());
+
+    Ok(Json(MessageReadBy {
+        message_id: message_id.to_string(),
+        room_id: room_id.to_string(),
+        seq,
+        sender,
+        read_by,
+        unread_by,
+    }))
+}
+
+/// GET /api/v1/unread?sender=<name> — Get unread counts across all rooms for a sender, or only
+/// the rooms it subscribes to when it has subscriptions (`all=true` ignores them).
+/// System messages (renames, pins, joins, purges) don't count unless `include_system=true`.
+/// Each room also carries its unread thread replies (as in `/api/v1/unread/threads`).
+/// Private rooms are only listed for their members.
+#[get("/api/v1/unread?<sender>&<include_system>&<all>")]
+pub fn get_unread(
+    sender: &str,
+    include_system: Option<bool>,
+    all: Option<bool>,
+    db: ScopedDb<'_>,
+    access: MemberAccess,
+) -> Result<Json<UnreadResponse>, (Status, Json<serde_json::Value>)> {
     let sender = sender.trim();
     if sender.is_empty() {
         return Err((Status::BadRequest, Json(serde_json::json!({"error": "Sender parameter is required"}))));
     }
 
     let conn = db.conn();
+    let subscribed_only = !all.unwrap_or(false) && !subscriptions::room_ids(&conn, sender).is_empty();
+    let subscriber = crate::db::resolve_sender(&conn, sender);
 
     // Get all rooms with their latest seq, unread count, and the sender's read position.
     // Uses COUNT to compute unread (seq is global, not per-room, so arithmetic won't work).
+    let sql = format!(
+        "SELECT r.id, r.name,
+                COALESCE(MAX(m.seq), 0) as latest_seq,
+                COALESCE(rp.last_read_seq, 0) as last_read_seq,
+                COUNT(CASE WHEN m.seq > COALESCE(rp.last_read_seq, 0) AND (?2 OR m.kind != 'system') THEN 1 END) as unread_count
+         FROM rooms r
+         LEFT JOIN messages m ON m.room_id = r.id
+         LEFT JOIN read_positions rp ON rp.room_id = r.id AND rp.sender = ?1
+         WHERE (NOT ?3 OR {subscribed}) AND {visible}
+         GROUP BY r.id
+         ORDER BY r.name",
+        subscribed = subscriptions::subscribed_sql("r.id", 4),
+        visible = access.visible_sql("r.id"),
+    );
     let mut stmt = conn
-        .prepare(
-            "SELECT r.id, r.name,
-                    COALESCE(MAX(m.seq), 0) as latest_seq,
-                    COALESCE(rp.last_read_seq, 0) as last_read_seq,
-                    COUNT(CASE WHEN m.seq > COALESCE(rp.last_read_seq, 0) THEN 1 END) as unread_count
-             FROM rooms r
-             LEFT JOIN messages m ON m.room_id = r.id
-             LEFT JOIN read_positions rp ON rp.room_id = r.id AND rp.sender = ?1
-             GROUP BY r.id
-             ORDER BY r.name",
-        )
+        .prepare(&sql)
         .map_err(|_| (Status::InternalServerError, Json(serde_json::json!({"error": "Database error"}))))?;
 
+    let mut thread_unread: HashMap<String, i64> = HashMap::new();
+    for thread in unread_threads(&conn, sender, None, subscribed_only, &access)
+        .map_err(|_| (Status::InternalServerError, Json(serde_json::json!({"error": "Database error"}))))?
+    {
+        *thread_unread.entry(thread.room_id).or_default() += thread.unread_count;
+    }
+
     let rooms: Vec<UnreadInfo> = stmt
-        .query_map(params![sender], |row| {
+        .query_map(params![sender, include_system.unwrap_or(false), subscribed_only, &subscriber], |row| {
+            let room_id: String = row.get(0)?;
             Ok(UnreadInfo {
-                room_id: row.get(0)?,
+                thread_unread_count: thread_unread.get(&room_id).copied().unwrap_or(0),
+                room_id,
                 room_name: row.get(1)?,
                 unread_count: row.get(4)?,
                 last_read_seq: row.get(3)?,
@@ -161,10 +298,190 @@ pub fn get_unread(sender: &str, db: &State<Db>) -> Result<Json<UnreadResponse>,
         .collect();
 
     let total_unread: i64 = rooms.iter().map(|r| r.unread_count).sum();
+    let total_thread_unread: i64 = rooms.iter().map(|r| r.thread_unread_count).sum();
 
     Ok(Json(UnreadResponse {
         sender: sender.to_string(),
         rooms,
         total_unread,
+        total_thread_unread,
+        subscribed_only,
+    }))
+}
+
+/// PUT /api/v1/rooms/<room_id>/threads/<root_id>/read — Mark a thread as read up to a seq number.
+/// Thread positions are tracked separately from the room position, so catching up on the
+/// main flow doesn't mark side-threads as read (and vice versa).
+#[put("/api/v1/rooms/<room_id>/threads/<root_id>/read", data = "<body>")]
+pub fn update_thread_read_position(
+    room_id: &str,
+    root_id: &str,
+    body: Json<UpdateReadPosition>,
+    db: ScopedDb<'_>,
+) -> Result<Json<ThreadReadPosition>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+
+    let room_exists: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM rooms WHERE id = ?1",
+            params![room_id],
+            |r| r.get::<_, i64>(0),
+        )
+        .map(|c| c > 0)
+        .unwrap_or(false);
+
+    if !room_exists {
+        return Err((Status::NotFound, Json(serde_json::json!({"error": "Room not found"}))));
+    }
+
+    let root_exists: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM messages WHERE id = ?1 AND room_id = ?2",
+            params![root_id, room_id],
+            |r| r.get::<_, i64>(0),
+        )
+        .map(|c| c > 0)
+        .unwrap_or(false);
+
+    if !root_exists {
+        return Err((Status::NotFound, Json(serde_json::json!({"error": "Thread root message not found"}))));
+    }
+
+    let sender = body.sender.trim();
+    if sender.is_empty() || sender.len() > 100 {
+        return Err((Status::BadRequest, Json(serde_json::json!({"error": "Sender must be 1-100 characters"}))));
+    }
+
+    if body.last_read_seq < 0 {
+        return Err((Status::BadRequest, Json(serde_json::json!({"error": "last_read_seq must be non-negative"}))));
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+
+    // UPSERT: only ever moves forward, same as room read positions
+    conn.execute(
+        "INSERT INTO thread_read_positions (room_id, root_id, sender, last_read_seq, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(root_id, sender) DO UPDATE SET
+           last_read_seq = MAX(thread_read_positions.last_read_seq, excluded.last_read_seq),
+           updated_at = excluded.updated_at
+         WHERE excluded.last_read_seq > thread_read_positions.last_read_seq",
+        params![room_id, root_id, sender, body.last_read_seq, &now],
+    )
+    .map_err(|_| (Status::InternalServerError, Json(serde_json::json!({"error": "Database error"}))))?;
+
+    let position = conn
+        .query_row(
+            "SELECT room_id, root_id, sender, last_read_seq, updated_at FROM thread_read_positions WHERE root_id = ?1 AND sender = ?2",
+            params![root_id, sender],
+            |row| {
+                Ok(ThreadReadPosition {
+                    room_id: row.get(0)?,
+                    root_id: row.get(1)?,
+                    sender: row.get(2)?,
+                    last_read_seq: row.get(3)?,
+                    updated_at: row.get(4)?,
+                })
+            },
+        )
+        .map_err(|_| (Status::InternalServerError, Json(serde_json::json!({"error": "Database error"}))))?;
+
+    Ok(Json(position))
+}
+
+/// Threads with replies `sender` hasn't read, newest activity first. Shared by the thread and
+/// room unread endpoints.
+fn unread_threads(
+    conn: &Connection,
+    sender: &str,
+    room_id: Option<&str>,
+    subscribed_only: bool,
+    access: &MemberAccess,
+) -> rusqlite::Result<Vec<ThreadUnreadInfo>> {
+    let room_filter = if room_id.is_some() {
+        " AND m.room_id = ?2".to_string()
+    } else if subscribed_only {
+        format!(" AND {}", subscriptions::subscribed_sql("m.room_id", 2))
+    } else {
+        String::new()
+    };
+    // Map every reply to its thread root. The walk starts only from roots that have replies
+    // (messages without a surviving parent), and each step stays in the root's room and moves
+    // forward in seq, so it follows the reply_to index instead of rescanning history.
+    let sql = format!(
+        "WITH RECURSIVE thread(id, root_id, room_id, seq, sender) AS (
+             SELECT m.id, m.id, m.room_id, m.seq, m.sender FROM messages m
+             WHERE (m.reply_to IS NULL OR NOT EXISTS (SELECT 1 FROM messages p WHERE p.id = m.reply_to))
+               AND EXISTS (SELECT 1 FROM messages c WHERE c.reply_to = m.id)
+               AND {visible}{room_filter}
+             UNION ALL
+             SELECT m.id, t.root_id, m.room_id, m.seq, m.sender FROM messages m
+             JOIN thread t ON m.reply_to = t.id
+             WHERE m.room_id = t.room_id AND m.seq > t.seq
+         )
+         SELECT t.room_id, r.name, t.root_id,
+                COUNT(*) as unread_count,
+                COALESCE(trp.last_read_seq, 0) as last_read_seq,
+                MAX(t.seq) as latest_seq
+         FROM thread t
+         JOIN rooms r ON r.id = t.room_id
+         LEFT JOIN thread_read_positions trp ON trp.root_id = t.root_id AND trp.sender = ?1
+         WHERE t.id != t.root_id
+           AND t.sender != ?1
+           AND t.seq > COALESCE(trp.last_read_seq, 0)
+         GROUP BY t.root_id
+         ORDER BY latest_seq DESC",
+        visible = access.visible_sql("m.room_id"),
+    );
+
+    let mut stmt = conn.prepare(&sql)?;
+    let map_row = |row: &rusqlite::Row<'_>| {
+        Ok(ThreadUnreadInfo {
+            room_id: row.get(0)?,
+            room_name: row.get(1)?,
+            root_id: row.get(2)?,
+            unread_count: row.get(3)?,
+            last_read_seq: row.get(4)?,
+            latest_seq: row.get(5)?,
+        })
+    };
+    let rows = match room_id {
+        Some(rid) => stmt.query_map(params![sender, rid], map_row)?,
+        None if subscribed_only => stmt.query_map(params![sender, crate::db::resolve_sender(conn, sender)], map_row)?,
+        None => stmt.query_map(params![sender], map_row)?,
+    };
+    Ok(rows.filter_map(|r| r.ok()).collect())
+}
+
+/// GET /api/v1/unread/threads?sender=<name>&room_id=<uuid> — Threads with replies the sender hasn't read.
+/// A reply is unread if its seq is above the sender's thread read position (0 if never marked).
+/// The sender's own replies never count as unread. Without `room_id`, a sender with subscriptions
+/// only sees threads in those rooms unless `all=true`.
+#[get("/api/v1/unread/threads?<sender>&<room_id>&<all>")]
+pub fn get_unread_threads(
+    sender: &str,
+    room_id: Option<&str>,
+    all: Option<bool>,
+    db: ScopedDb<'_>,
+    access: MemberAccess,
+) -> Result<Json<ThreadUnreadResponse>, (Status, Json<serde_json::Value>)> {
+    let sender = sender.trim();
+    if sender.is_empty() {
+        return Err((Status::BadRequest, Json(serde_json::json!({"error": "Sender parameter is required"}))));
+    }
+
+    let conn = db.conn();
+    let subscribed_only =
+        room_id.is_none() && !all.unwrap_or(false) && !subscriptions::room_ids(&conn, sender).is_empty();
+    let threads = unread_threads(&conn, sender, room_id, subscribed_only, &access)
+        .map_err(|_| (Status::InternalServerError, Json(serde_json::json!({"error": "Database error"}))))?;
+
+    let total_unread: i64 = threads.iter().map(|t| t.unread_count).sum();
+
+    Ok(Json(ThreadUnreadResponse {
+        sender: sender.to_string(),
+        threads,
+        total_unread,
+        subscribed_only,
     }))
 }
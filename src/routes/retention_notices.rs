@@ -0,0 +1,101 @@
+use crate::namespaces::ScopedDb;
+use crate::models::RetentionNotice;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{get, post};
+use rusqlite::{params, Connection};
+
+use super::rooms::RETENTION_NOTICE_RANGE;
+use super::AdminKey;
+
+fn err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+fn check_admin(conn: &Connection, room_id: &str, admin: &AdminKey) -> Result<(), (Status, Json<serde_json::Value>)> {
+    let key: Option<String> = conn
+        .query_row("SELECT admin_key FROM rooms WHERE id = ?1", params![room_id], |r| r.get(0))
+        .map_err(|_| err(Status::NotFound, "Room not found"))?;
+    if key.as_deref() != Some(admin.0.as_str()) {
+        return Err(err(Status::Forbidden, "Invalid admin key for this room"));
+    }
+    Ok(())
+}
+
+fn load_notice(conn: &Connection, room_id: &str) -> Option<RetentionNotice> {
+    conn.query_row(
+        "SELECT room_id, pending_count, cutoff_seq, cutoff_at, notified_at, purge_after, postponed
+         FROM retention_notices WHERE room_id = ?1",
+        params![room_id],
+        |r| {
+            Ok(RetentionNotice {
+                room_id: r.get(0)?,
+                pending_count: r.get(1)?,
+                cutoff_seq: r.get(2)?,
+                cutoff_at: r.get(3)?,
+                notified_at: r.get(4)?,
+                purge_after: r.get(5)?,
+                postponed: r.get::<_, i64>(6)? != 0,
+            })
+        },
+    )
+    .ok()
+}
+
+/// GET /api/v1/rooms/<room_id>/retention/pending — the announced purge awaiting its notice period (404 if none).
+#[get("/api/v1/rooms/<room_id>/retention/pending")]
+pub fn get_retention_notice(
+    db: ScopedDb<'_>,
+    room_id: &str,
+) -> Result<Json<RetentionNotice>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    load_notice(&conn, room_id)
+        .map(Json)
+        .ok_or_else(|| err(Status::NotFound, "No retention purge pending for this room"))
+}
+
+/// POST /api/v1/rooms/<room_id>/retention/postpone?secs= — push the pending purge back once (admin key).
+/// Defaults to the room's notice period; 409 if the notice was already postponed.
+#[post("/api/v1/rooms/<room_id>/retention/postpone?<secs>")]
+pub fn postpone_retention(
+    db: ScopedDb<'_>,
+    room_id: &str,
+    admin: AdminKey,
+    secs: Option<i64>,
+) -> Result<Json<RetentionNotice>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    check_admin(&conn, room_id, &admin)?;
+    let notice = load_notice(&conn, room_id)
+        .ok_or_else(|| err(Status::NotFound, "No retention purge pending for this room"))?;
+    if notice.postponed {
+        return Err(err(Status::Conflict, "This retention purge has already been postponed"));
+    }
+    let secs = match secs {
+        Some(secs) => secs,
+        None => conn
+            .query_row("SELECT retention_notice_secs FROM rooms WHERE id = ?1", params![room_id], |r| {
+                r.get::<_, Option<i64>>(0)
+            })
+            .ok()
+            .flatten()
+            .unwrap_or(*RETENTION_NOTICE_RANGE.start()),
+    };
+    if !RETENTION_NOTICE_RANGE.contains(&secs) {
+        return Err(err(Status::BadRequest, "secs must be between 60 and 604800 (7 days)"));
+    }
+
+    // Extend from whichever is later, so postponing an overdue notice still buys the full delay
+    let now = chrono::Utc::now();
+    let base = chrono::DateTime::parse_from_rfc3339(&notice.purge_after)
+        .map(|t| t.with_timezone(&chrono::Utc))
+        .unwrap_or(now)
+        .max(now);
+    let purge_after = (base + chrono::Duration::seconds(secs)).to_rfc3339();
+    conn.execute(
+        "UPDATE retention_notices SET purge_after = ?1, postponed = 1 WHERE room_id = ?2",
+        params![&purge_after, room_id],
+    )
+    .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+
+    Ok(Json(RetentionNotice { purge_after, postponed: true, ..notice }))
+}
@@ -0,0 +1,166 @@
+use crate::namespaces::ScopedDb;
+use crate::models::{
+    DayCount, EmojiCount, RoomFileStats, RoomReactionStats, RoomSenderStats, RoomStats, RoomThreadStats,
+    SenderTypeCounts,
+};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::get;
+use rusqlite::{params, Connection};
+
+fn err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+fn count(conn: &Connection, sql: &str, room_id: &str) -> i64 {
+    conn.query_row(sql, params![room_id], |r| r.get(0)).unwrap_or(0)
+}
+
+/// GET /api/v1/rooms/<room_id>/stats?days=30 — statistics for one room: message volume
+/// (all time and per UTC day over the last `days` days), a per-sender breakdown, file storage,
+/// reactions, threads and pins. System messages are counted separately.
+#[get("/api/v1/rooms/<room_id>/stats?<days>")]
+pub fn room_stats(
+    db: ScopedDb<'_>,
+    room_id: &str,
+    days: Option<i64>,
+) -> Result<Json<RoomStats>, (Status, Json<serde_json::Value>)> {
+    let days = days.unwrap_or(30);
+    if !(1..=365).contains(&days) {
+        return Err(err(Status::BadRequest, "days must be between 1 and 365"));
+    }
+
+    let conn = db.conn();
+    let room_name: String = conn
+        .query_row("SELECT name FROM rooms WHERE id = ?1", params![room_id], |r| r.get(0))
+        .map_err(|_| err(Status::NotFound, "Room not found"))?;
+
+    let messages = count(&conn, "SELECT COUNT(*) FROM messages WHERE room_id = ?1 AND kind != 'system'", room_id);
+    let system_messages = count(&conn, "SELECT COUNT(*) FROM messages WHERE room_id = ?1 AND kind = 'system'", room_id);
+    let agent = count(
+        &conn,
+        "SELECT COUNT(*) FROM messages WHERE room_id = ?1 AND kind != 'system' AND sender_type = 'agent'",
+        room_id,
+    );
+    let human = count(
+        &conn,
+        "SELECT COUNT(*) FROM messages WHERE room_id = ?1 AND kind != 'system' AND sender_type = 'human'",
+        room_id,
+    );
+    let (first_message_at, last_message_at): (Option<String>, Option<String>) = conn
+        .query_row(
+            "SELECT (SELECT created_at FROM messages WHERE room_id = ?1 AND kind != 'system' ORDER BY seq ASC LIMIT 1),
+                    (SELECT created_at FROM messages WHERE room_id = ?1 AND kind != 'system' ORDER BY seq DESC LIMIT 1)",
+            params![room_id],
+            |r| Ok((r.get(0)?, r.get(1)?)),
+        )
+        .unwrap_or((None, None));
+
+    // Per-day counts, zero-filled across the window
+    let today = chrono::Utc::now().date_naive();
+    let start = today - chrono::Days::new((days - 1) as u64);
+    let mut daily: std::collections::HashMap<String, i64> = conn
+        .prepare(
+            "SELECT date(created_at) AS day, COUNT(*) FROM messages
+             WHERE room_id = ?1 AND kind != 'system' AND date(created_at) >= ?2
+             GROUP BY day",
+        )
+        .and_then(|mut s| {
+            s.query_map(params![room_id, start.to_string()], |r| Ok((r.get(0)?, r.get(1)?)))
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+    let by_day: Vec<DayCount> = start
+        .iter_days()
+        .take_while(|d| *d <= today)
+        .map(|d| {
+            let date = d.to_string();
+            DayCount {
+                messages: daily.remove(&date).unwrap_or(0),
+                date,
+            }
+        })
+        .collect();
+
+    let senders: Vec<RoomSenderStats> = conn
+        .prepare(
+            "SELECT m.sender,
+                    (SELECT sender_type FROM messages t WHERE t.room_id = ?1 AND t.sender = m.sender
+                       AND t.sender_type IS NOT NULL ORDER BY t.seq DESC LIMIT 1),
+                    COUNT(*), MIN(m.created_at), MAX(m.created_at),
+                    (SELECT COUNT(*) FROM message_reactions r JOIN messages rm ON rm.id = r.message_id
+                      WHERE rm.room_id = ?1 AND rm.sender = m.sender AND r.sender != m.sender),
+                    (SELECT COUNT(*) FROM files f WHERE f.room_id = ?1 AND f.sender = m.sender)
+             FROM messages m
+             WHERE m.room_id = ?1 AND m.kind != 'system'
+             GROUP BY m.sender
+             ORDER BY COUNT(*) DESC, m.sender ASC",
+        )
+        .and_then(|mut s| {
+            s.query_map(params![room_id], |r| {
+                Ok(RoomSenderStats {
+                    sender: r.get(0)?,
+                    sender_type: r.get(1)?,
+                    messages: r.get(2)?,
+                    first_message_at: r.get(3)?,
+                    last_message_at: r.get(4)?,
+                    reactions_received: r.get(5)?,
+                    files: r.get(6)?,
+                })
+            })
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+
+    let files = RoomFileStats {
+        count: count(&conn, "SELECT COUNT(*) FROM files WHERE room_id = ?1", room_id),
+        total_bytes: count(&conn, "SELECT COALESCE(SUM(size), 0) FROM files WHERE room_id = ?1", room_id),
+    };
+
+    let reaction_total = count(
+        &conn,
+        "SELECT COUNT(*) FROM message_reactions r JOIN messages m ON m.id = r.message_id WHERE m.room_id = ?1",
+        room_id,
+    );
+    let top: Vec<EmojiCount> = conn
+        .prepare(
+            "SELECT r.emoji, COUNT(*) AS n FROM message_reactions r JOIN messages m ON m.id = r.message_id
+             WHERE m.room_id = ?1 GROUP BY r.emoji ORDER BY n DESC, r.emoji ASC LIMIT 10",
+        )
+        .and_then(|mut s| {
+            s.query_map(params![room_id], |r| Ok(EmojiCount { emoji: r.get(0)?, count: r.get(1)? }))
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+
+    let threads = RoomThreadStats {
+        threads: count(
+            &conn,
+            "SELECT COUNT(DISTINCT reply_to) FROM messages
+             WHERE room_id = ?1 AND reply_to IN (SELECT id FROM messages WHERE room_id = ?1)",
+            room_id,
+        ),
+        replies: count(&conn, "SELECT COUNT(*) FROM messages WHERE room_id = ?1 AND reply_to IS NOT NULL", room_id),
+    };
+
+    Ok(Json(RoomStats {
+        room_id: room_id.to_string(),
+        room_name,
+        messages,
+        system_messages,
+        by_sender_type: SenderTypeCounts {
+            agent,
+            human,
+            unspecified: messages - agent - human,
+        },
+        first_message_at,
+        last_message_at,
+        days,
+        by_day,
+        senders,
+        files,
+        reactions: RoomReactionStats { total: reaction_total, top },
+        threads,
+        pinned: count(&conn, "SELECT COUNT(*) FROM messages WHERE room_id = ?1 AND pinned_at IS NOT NULL", room_id),
+    }))
+}
@@ -1,5 +1,8 @@
-use crate::db::{generate_admin_key, Db};
-use crate::events::{ChatEvent, EventBus};
+use crate::db::generate_admin_key;
+use crate::namespaces::ScopedDb;
+use crate::events::{ChatEvent, Events};
+use crate::fields::{Sparse, ROOM_FIELDS};
+use crate::membership::MemberAccess;
 use crate::models::*;
 use crate::rate_limit::{RateLimitConfig, RateLimited, RateLimiter};
 use rocket::http::Status;
@@ -9,16 +12,154 @@ use rusqlite::{params, Connection};
 
 use super::{AdminKey, ClientIp};
 
+/// Allowed file lifetimes, for upload `expires_in` and the room default (1 minute to 1 year).
+pub(super) const FILE_TTL_RANGE: std::ops::RangeInclusive<i64> = 60..=31_536_000;
+
+/// Allowed `max_messages` retention limits.
+pub(super) const MAX_MESSAGES_RANGE: std::ops::RangeInclusive<i64> = 10..=1_000_000;
+
+/// Allowed `max_message_age_hours` retention limits (1 hour to 1 year).
+pub(super) const MESSAGE_AGE_HOURS_RANGE: std::ops::RangeInclusive<i64> = 1..=8760;
+
+/// Allowed retention notice periods and postponements (1 minute to 7 days).
+pub(super) const RETENTION_NOTICE_RANGE: std::ops::RangeInclusive<i64> = 60..=604_800;
+
+/// Resolve a room icon: an image file uploaded to this room (kept as its id), or a single emoji
+/// (`:shortcode:` accepted and stored as unicode).
+pub(super) fn resolve_icon(conn: &Connection, room_id: &str, icon: &str) -> Result<String, String> {
+    let icon = icon.trim();
+    let file_type: Option<String> = conn
+        .query_row(
+            "SELECT content_type FROM files WHERE id = ?1 AND room_id = ?2",
+            params![icon, room_id],
+            |r| r.get(0),
+        )
+        .ok();
+    if let Some(content_type) = file_type {
+        return if content_type.starts_with("image/") {
+            Ok(icon.to_string())
+        } else {
+            Err("icon file must be an image".to_string())
+        };
+    }
+    let emoji = crate::emoji::normalize(icon);
+    let is_emoji = !emoji.is_empty()
+        && emoji.chars().count() <= 16
+        && !emoji.chars().any(|c| c.is_ascii() || c.is_whitespace());
+    if is_emoji {
+        Ok(emoji)
+    } else {
+        Err("icon must be an emoji or the id of an image uploaded to this room".to_string())
+    }
+}
+
+/// Most tags a room can carry.
+const MAX_ROOM_TAGS: usize = 20;
+
+/// Lowercase, trim and dedupe room tags; each is 1-32 of `a-z 0-9 - _ .`.
+pub(crate) fn normalize_tags(tags: &[String]) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        let valid = !tag.is_empty()
+            && tag.len() <= 32
+            && tag.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !valid {
+            return Err(format!("Invalid tag '{tag}': use 1-32 letters, digits, '-', '_' or '.'"));
+        }
+        if !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    if normalized.len() > MAX_ROOM_TAGS {
+        return Err(format!("A room can have at most {MAX_ROOM_TAGS} tags"));
+    }
+    Ok(normalized)
+}
+
+pub(super) fn tags_from_column(tags: Option<String>) -> Vec<String> {
+    tags.and_then(|t| serde_json::from_str(&t).ok()).unwrap_or_default()
+}
+
+/// Most emoji a reaction allow-list can hold.
+const MAX_ALLOWED_REACTIONS: usize = 20;
+
+/// Normalize a reaction allow-list the way `add_reaction` normalizes reactions, so `:+1:` and
+/// 👍 are the same entry. An empty list means "any emoji".
+pub(super) fn normalize_allowed_reactions(emoji: &[String]) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for e in emoji {
+        let e = e.trim();
+        if e.is_empty() || e.len() > 32 {
+            return Err("allowed_reactions entries must be 1-32 characters".to_string());
+        }
+        let e = crate::emoji::normalize(e);
+        if !normalized.contains(&e) {
+            normalized.push(e);
+        }
+    }
+    if normalized.len() > MAX_ALLOWED_REACTIONS {
+        return Err(format!("allowed_reactions can list at most {MAX_ALLOWED_REACTIONS} emoji"));
+    }
+    Ok(normalized)
+}
+
+/// The room's reaction allow-list; empty when reactions are unrestricted.
+pub(crate) fn allowed_reactions(conn: &Connection, room_id: &str) -> Vec<String> {
+    conn.query_row("SELECT allowed_reactions FROM rooms WHERE id = ?1", params![room_id], |r| {
+        r.get::<_, Option<String>>(0)
+    })
+    .map(tags_from_column)
+    .unwrap_or_default()
+}
+
+pub(super) fn allowed_reactions_column(emoji: &[String]) -> Option<String> {
+    if emoji.is_empty() {
+        None
+    } else {
+        serde_json::to_string(emoji).ok()
+    }
+}
+
+/// Normalize `#rgb` / `#rrggbb` to lowercase `#rrggbb`.
+/// `public` or `private`, case-insensitively.
+pub(super) fn normalize_visibility(visibility: &str) -> Result<String, String> {
+    let visibility = visibility.trim().to_lowercase();
+    if crate::membership::VISIBILITIES.contains(&visibility.as_str()) {
+        Ok(visibility)
+    } else {
+        Err("visibility must be public or private".to_string())
+    }
+}
+
+pub(super) fn normalize_color(color: &str) -> Option<String> {
+    let hex = color.trim().strip_prefix('#')?;
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    match hex.len() {
+        6 => Some(format!("#{}", hex.to_lowercase())),
+        3 => Some(format!("#{}", hex.chars().flat_map(|c| [c, c]).collect::<String>().to_lowercase())),
+        _ => None,
+    }
+}
+
 /// Fetch a RoomWithStats from the database by room ID.
-fn fetch_room_with_stats(conn: &Connection, room_id: &str) -> Result<RoomWithStats, rusqlite::Error> {
+pub(super) fn fetch_room_with_stats(conn: &Connection, room_id: &str) -> Result<RoomWithStats, rusqlite::Error> {
     conn.query_row(
         "SELECT r.id, r.name, r.description, r.created_by, r.created_at, r.updated_at,
                 (SELECT COUNT(*) FROM messages WHERE room_id = r.id) as message_count,
                 (SELECT MAX(created_at) FROM messages WHERE room_id = r.id) as last_activity,
                 (SELECT sender FROM messages WHERE room_id = r.id ORDER BY seq DESC LIMIT 1) as last_sender,
                 (SELECT SUBSTR(content, 1, 100) FROM messages WHERE room_id = r.id ORDER BY seq DESC LIMIT 1) as last_preview,
-                r.archived_at, r.max_messages, r.max_message_age_hours
-         FROM rooms r WHERE r.id = ?1",
+                r.archived_at, r.max_messages, r.max_message_age_hours, r.file_ttl_secs,
+                r.icon, (SELECT id FROM files WHERE id = r.icon AND room_id = r.id), r.color,
+                r.retention_notice_secs, r.tags,
+                r.language, CASE WHEN r.language IS NULL THEN NULL ELSE r.search_tokenizer END,
+                r.allowed_reactions,
+                (SELECT NULLIF(topic, '') FROM room_topics WHERE room_id = r.id ORDER BY id DESC LIMIT 1),
+                r.visibility
+         FROM rooms r WHERE r.id = ?1
         params![room_id],
         |row| {
             Ok(RoomWithStats {
@@ -36,6 +177,21 @@ fn fetch_room_with_stats(conn: &Connection, room_id: &str) -> Result<RoomWithSta
                 bookmarked: None,
                 max_messages: row.get(11)?,
                 max_message_age_hours: row.get(12)?,
+                file_ttl_secs: row.get(13)?,
+                retention_notice_secs: row.get(17)?,
+                icon: row.get(14)?,
+                icon_url: row.get::<_, Option<String>>(15)?.map(|id| format!("/api/v1/files/{id}")),
+                color: row.get(16)?,
+                first_seq: None,
+                latest_seq: None,
+                first_message_at: None,
+                participant_count: None,
+                tags: tags_from_column(row.get(18)?),
+                language: row.get(19)?,
+                search_tokenizer: row.get(20)?,
+                topic: row.get(22)?,
+                allowed_reactions: tags_from_column(row.get(21)?),
+                visibility: row.get(23)?,
             })
         },
     )
@@ -43,7 +199,8 @@ fn fetch_room_with_stats(conn: &Connection, room_id: &str) -> Result<RoomWithSta
 
 #[post("/api/v1/rooms", format = "json", data = "<body>")]
 pub fn create_room(
-    db: &State<Db>,
+    db: ScopedDb<'_>,
+    events: Events<'_>,
     rate_limiter: &State<RateLimiter>,
     rate_config: &State<RateLimitConfig>,
     ip: ClientIp,
@@ -71,29 +228,48 @@ pub fn create_room(
     }
 
     // Validate retention settings
-    if let Some(max) = body.max_messages && !(10..=1_000_000).contains(&max) {
+    if let Some(max) = body.max_messages && !MAX_MESSAGES_RANGE.contains(&max) {
         return Err((
             Status::BadRequest,
             Json(serde_json::json!({"error": "max_messages must be between 10 and 1000000"})),
         ));
     }
-    if let Some(hours) = body.max_message_age_hours && !(1..=8760).contains(&hours) {
+    if let Some(hours) = body.max_message_age_hours && !MESSAGE_AGE_HOURS_RANGE.contains(&hours) {
         return Err((
             Status::BadRequest,
             Json(serde_json::json!({"error": "max_message_age_hours must be between 1 and 8760 (1 year)"})),
         ));
     }
 
+    let tags = normalize_tags(&body.tags).map_err(|e| (Status::BadRequest, Json(serde_json::json!({"error": e}))))?;
+    let language = body.language.as_deref().map(str::trim).filter(|l| !l.is_empty());
+    let tokenizer = match language {
+        Some(lang) => crate::db::search_tokenizer_for_language(lang).ok_or_else(|| {
+            (
+                Status::BadRequest,
+                Json(serde_json::json!({"error": "language must be a language code like de, ja or pt-BR"})),
+            )
+        })?,
+        None => "porter",
+    };
+    let allowed = normalize_allowed_reactions(&body.allowed_reactions)
+        .map_err(|e| (Status::BadRequest, Json(serde_json::json!({"error": e}))))?;
+    let visibility = normalize_visibility(body.visibility.as_deref().unwrap_or("public"))
+        .map_err(|e| (Status::BadRequest, Json(serde_json::json!({"error": e}))))?;
+
     let id = uuid::Uuid::new_v4().to_string();
     let now = chrono::Utc::now().to_rfc3339();
     let admin_key = generate_admin_key();
     let conn = db.conn();
 
     match conn.execute(
-        "INSERT INTO rooms (id, name, description, created_by, created_at, updated_at, admin_key, max_messages, max_message_age_hours) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
-        params![&id, &name, &body.description, &body.created_by, &now, &now, &admin_key, &body.max_messages, &body.max_message_age_hours],
+        "INSERT INTO rooms (id, name, description, created_by, created_at, updated_at, admin_key, max_messages, max_message_age_hours, tags, language, search_tokenizer, allowed_reactions, visibility) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+        params![&id, &name, &body.description, &body.created_by, &now, &now, &admin_key, &body.max_messages, &body.max_message_age_hours, serde_json::to_string(&tags).unwrap_or_else(|_| "[]".to_string()), language, tokenizer, allowed_reactions_column(&allowed), &visibility],
     ) {
         Ok(_) => {
+            // A live room now owns this name; it no longer redirects to a renamed/merged room
+            conn.execute("DELETE FROM room_name_aliases WHERE name = ?1", params![&name])
+                .ok();
             let mut response = serde_json::json!({
                 "id": id,
                 "name": name,
@@ -101,7 +277,8 @@ pub fn create_room(
                 "created_by": body.created_by,
                 "admin_key": admin_key,
                 "created_at": now,
-                "updated_at": now
+                "updated_at": now,
+                "visibility": visibility
             });
             if let Some(max) = body.max_messages {
                 response["max_messages"] = serde_json::json!(max);
@@ -109,6 +286,19 @@ pub fn create_room(
             if let Some(hours) = body.max_message_age_hours {
                 response["max_message_age_hours"] = serde_json::json!(hours);
             }
+            if !tags.is_empty() {
+                response["tags"] = serde_json::json!(tags);
+            }
+            if let Some(lang) = language {
+                response["language"] = serde_json::json!(lang);
+                response["search_tokenizer"] = serde_json::json!(tokenizer);
+            }
+            if !allowed.is_empty() {
+                response["allowed_reactions"] = serde_json::json!(allowed);
+            }
+            if let Ok(room) = fetch_room_with_stats(&conn, &id) {
+                events.publish(ChatEvent::RoomCreated(room));
+            }
             Ok(RateLimited::new(Json(response), rl))
         }
         Err(e) if e.to_string().contains("UNIQUE") => Err((
@@ -122,92 +312,59 @@ pub fn create_room(
     }
 }
 
-#[get("/api/v1/rooms?<include_archived>&<sender>")]
-pub fn list_rooms(db: &State<Db>, include_archived: Option<bool>, sender: Option<&str>) -> Json<Vec<RoomWithStats>> {
+#[get("/api/v1/rooms?<include_archived>&<sender>&<fields>&<tag>")]
+pub fn list_rooms(
+    db: ScopedDb<'_>,
+    include_archived: Option<bool>,
+    sender: Option<&str>,
+    fields: Option<&str>,
+    tag: Option<&str>,
+    access: MemberAccess,
+) -> Result<Json<Vec<Sparse<RoomWithStats>>>, (Status, Json<serde_json::Value>)> {
+    let fields = crate::fields::parse(fields, ROOM_FIELDS)?;
     let conn = db.conn();
     let include = include_archived.unwrap_or(false);
 
     // When sender is provided, include bookmark status and sort bookmarked rooms first
-    if let Some(sender_val) = sender {
-        let sender_val = sender_val.trim();
-        if !sender_val.is_empty() {
-            let sql = if include {
-                "SELECT r.id, r.name, r.description, r.created_by, r.created_at, r.updated_at,
-                        (SELECT COUNT(*) FROM messages WHERE room_id = r.id) as message_count,
-                        (SELECT MAX(created_at) FROM messages WHERE room_id = r.id) as last_activity,
-                        (SELECT sender FROM messages WHERE room_id = r.id ORDER BY seq DESC LIMIT 1) as last_sender,
-                        (SELECT SUBSTR(content, 1, 100) FROM messages WHERE room_id = r.id ORDER BY seq DESC LIMIT 1) as last_preview,
-                        r.archived_at,
-                        (SELECT 1 FROM bookmarks WHERE room_id = r.id AND sender = ?1) as is_bookmarked,
-                        r.max_messages, r.max_message_age_hours
-                 FROM rooms r WHERE COALESCE(r.room_type, 'room') != 'dm'
-                 ORDER BY is_bookmarked IS NOT NULL DESC, last_activity IS NULL, last_activity DESC, r.name"
-            } else {
-                "SELECT r.id, r.name, r.description, r.created_by, r.created_at, r.updated_at,
-                        (SELECT COUNT(*) FROM messages WHERE room_id = r.id) as message_count,
-                        (SELECT MAX(created_at) FROM messages WHERE room_id = r.id) as last_activity,
-                        (SELECT sender FROM messages WHERE room_id = r.id ORDER BY seq DESC LIMIT 1) as last_sender,
-                        (SELECT SUBSTR(content, 1, 100) FROM messages WHERE room_id = r.id ORDER BY seq DESC LIMIT 1) as last_preview,
-                        r.archived_at,
-                        (SELECT 1 FROM bookmarks WHERE room_id = r.id AND sender = ?1) as is_bookmarked,
-                        r.max_messages, r.max_message_age_hours
-                 FROM rooms r WHERE COALESCE(r.room_type, 'room') != 'dm' AND r.archived_at IS NULL
-                 ORDER BY is_bookmarked IS NOT NULL DESC, last_activity IS NULL, last_activity DESC, r.name"
-            };
-            let mut stmt = match conn.prepare(sql) {
-                Ok(s) => s,
-                Err(_) => return Json(Vec::new()),
-            };
-            let rooms = match stmt
-                .query_map(params![sender_val], |row| {
-                    let is_bookmarked: Option<i64> = row.get(11)?;
-                    Ok(RoomWithStats {
-                        id: row.get(0)?,
-                        name: row.get(1)?,
-                        description: row.get(2)?,
-                        created_by: row.get(3)?,
-                        created_at: row.get(4)?,
-                        updated_at: row.get(5)?,
-                        message_count: row.get(6)?,
-                        last_activity: row.get(7)?,
-                        last_message_sender: row.get(8)?,
-                        last_message_preview: row.get(9)?,
-                        archived_at: row.get(10)?,
-                        bookmarked: Some(is_bookmarked.is_some()),
-                        max_messages: row.get(12)?,
-                        max_message_age_hours: row.get(13)?,
-                    })
-                }) {
-                Ok(rows) => rows.filter_map(|r| r.ok()).collect(),
-                Err(_) => Vec::new(),
-            };
-            return Json(rooms);
-        }
-    }
-
-    let sql = if include {
-        "SELECT r.id, r.name, r.description, r.created_by, r.created_at, r.updated_at,
-                (SELECT COUNT(*) FROM messages WHERE room_id = r.id) as message_count,
-                (SELECT MAX(created_at) FROM messages WHERE room_id = r.id) as last_activity,
-                (SELECT sender FROM messages WHERE room_id = r.id ORDER BY seq DESC LIMIT 1) as last_sender,
-                (SELECT SUBSTR(content, 1, 100) FROM messages WHERE room_id = r.id ORDER BY seq DESC LIMIT 1) as last_preview,
-                r.archived_at, r.max_messages, r.max_message_age_hours
-         FROM rooms r WHERE COALESCE(r.room_type, 'room') != 'dm' ORDER BY last_activity IS NULL, last_activity DESC, r.name"
-    } else {
-        "SELECT r.id, r.name, r.description, r.created_by, r.created_at, r.updated_at,
-                (SELECT COUNT(*) FROM messages WHERE room_id = r.id) as message_count,
-                (SELECT MAX(created_at) FROM messages WHERE room_id = r.id) as last_activity,
-                (SELECT sender FROM messages WHERE room_id = r.id ORDER BY seq DESC LIMIT 1) as last_sender,
-                (SELECT SUBSTR(content, 1, 100) FROM messages WHERE room_id = r.id ORDER BY seq DESC LIMIT 1) as last_preview,
-                r.archived_at, r.max_messages, r.max_message_age_hours
-         FROM rooms r WHERE COALESCE(r.room_type, 'room') != 'dm' AND r.archived_at IS NULL ORDER BY last_activity IS NULL, last_activity DESC, r.name"
-    };
-    let mut stmt = match conn.prepare(sql) {
+    let sender = sender.map(|s| s.trim()).filter(|s| !s.is_empty());
+
+    // One pass over messages for counts/last activity, then a seq lookup for the latest
+    // message, instead of four correlated subqueries per room. A NULL sender never matches
+    // a bookmark, so the bookmark sort key is a no-op without one.
+    let sql = format!(
+        "WITH stats AS (
+             SELECT room_id, COUNT(*) AS message_count, MAX(created_at) AS last_activity, MAX(seq) AS last_seq
+             FROM messages GROUP BY room_id
+         )
+         SELECT r.id, r.name, r.description, r.created_by, r.created_at, r.updated_at,
+                COALESCE(s.message_count, 0), s.last_activity, lm.sender, SUBSTR(lm.content, 1, 100),
+                r.archived_at, b.room_id IS NOT NULL AS is_bookmarked,
+                r.max_messages, r.max_message_age_hours, r.file_ttl_secs,
+                r.icon, (SELECT id FROM files WHERE id = r.icon AND room_id = r.id), r.color,
+                r.retention_notice_secs, r.tags,
+                r.language, CASE WHEN r.language IS NULL THEN NULL ELSE r.search_tokenizer END,
+                r.allowed_reactions,
+                (SELECT NULLIF(topic, '') FROM room_topics WHERE room_id = r.id ORDER BY id DESC LIMIT 1),
+                r.visibility
+         FROM rooms r
+         LEFT JOIN stats s ON s.room_id = r.id
+         LEFT JOIN messages lm ON lm.seq = s.last_seq
+         LEFT JOIN bookmarks b ON b.room_id = r.id AND b.sender = ?1
+         WHERE COALESCE(r.room_type, 'room') != 'dm'{}
+           AND {}
+           AND (?2 IS NULL OR EXISTS (SELECT 1 FROM json_each(r.tags) WHERE value = ?2))
+         ORDER BY is_bookmarked DESC, s.last_activity IS NULL, s.last_activity DESC, r.name",
+        if include { "" } else { " AND r.archived_at IS NULL" },
+        access.visible_sql("r.id")
+    );
+    let tag = tag.map(|t| t.trim().to_lowercase()).filter(|t| !t.is_empty());
+    let mut stmt = match conn.prepare_cached(&sql) {
         Ok(s) => s,
-        Err(_) => return Json(Vec::new()),
+        Err(_) => return Ok(Json(Vec::new())),
     };
     let rooms = match stmt
-        .query_map([], |row| {
+        .query_map(params![sender, &tag], |row| {
+            let is_bookmarked: bool = row.get(11)?;
             Ok(RoomWithStats {
                 id: row.get(0)?,
                 name: row.get(1)?,
@@ -220,25 +377,56 @@ pub fn list_rooms(db: &State<Db>, include_archived: Option<bool>, sender: Option
                 last_message_sender: row.get(8)?,
                 last_message_preview: row.get(9)?,
                 archived_at: row.get(10)?,
-                bookmarked: None,
-                max_messages: row.get(11)?,
-                max_message_age_hours: row.get(12)?,
+                bookmarked: sender.map(|_| is_bookmarked),
+                max_messages: row.get(12)?,
+                max_message_age_hours: row.get(13)?,
+                file_ttl_secs: row.get(14)?,
+                retention_notice_secs: row.get(18)?,
+                icon: row.get(15)?,
+                icon_url: row.get::<_, Option<String>>(16)?.map(|id| format!("/api/v1/files/{id}")),
+                color: row.get(17)?,
+                first_seq: None,
+                latest_seq: None,
+                first_message_at: None,
+                participant_count: None,
+                tags: tags_from_column(row.get(19)?),
+                language: row.get(20)?,
+                search_tokenizer: row.get(21)?,
+                topic: row.get(23)?,
+                allowed_reactions: tags_from_column(row.get(22)?),
+                visibility: row.get(24)?,
             })
         }) {
         Ok(rows) => rows.filter_map(|r| r.ok()).collect(),
         Err(_) => Vec::new(),
     };
-    Json(rooms)
+    Ok(Json(Sparse::all(rooms, &fields)))
 }
 
 #[get("/api/v1/rooms/<room_id>")]
 pub fn get_room(
-    db: &State<Db>,
+    db: ScopedDb<'_>,
     room_id: &str,
 ) -> Result<Json<RoomWithStats>, (Status, Json<serde_json::Value>)> {
     let conn = db.conn();
     fetch_room_with_stats(&conn, room_id)
-    .map(Json)
+    .map(|mut room| {
+        // Sync clients plan pagination from these instead of probing the messages endpoint
+        if let Ok((first_seq, latest_seq, first_at, participants)) = conn.query_row(
+            "SELECT MIN(seq), MAX(seq),
+                    (SELECT created_at FROM messages WHERE room_id = ?1 ORDER BY seq ASC LIMIT 1),
+                    COUNT(DISTINCT CASE WHEN kind != 'system' THEN sender END)
+             FROM messages WHERE room_id = ?1",
+            params![room_id],
+            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)),
+        ) {
+            room.first_seq = first_seq;
+            room.latest_seq = latest_seq;
+            room.first_message_at = first_at;
+            room.participant_count = Some(participants);
+        }
+        Json(room)
+    })
     .map_err(|_| {
         (
             Status::NotFound,
@@ -247,10 +435,70 @@ pub fn get_room(
     })
 }
 
+/// Former names and merged-away room ids that now resolve to this room.
+#[get("/api/v1/rooms/<room_id>/aliases")]
+pub fn room_aliases(
+    db: ScopedDb<'_>,
+    room_id: &str,
+) -> Result<Json<RoomAliasesResponse>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    let internal = |_| {
+        (
+            Status::InternalServerError,
+            Json(serde_json::json!({"error": "Internal server error"})),
+        )
+    };
+    let exists: bool = conn
+        .query_row("SELECT COUNT(*) FROM rooms WHERE id = ?1", params![room_id], |r| r.get::<_, i64>(0))
+        .map(|c| c > 0)
+        .unwrap_or(false);
+    if !exists {
+        return Err((
+            Status::NotFound,
+            Json(serde_json::json!({"error": "Room not found"})),
+        ));
+    }
+
+    let mut stmt = conn
+        .prepare("SELECT name, created_at FROM room_name_aliases WHERE room_id = ?1 ORDER BY created_at ASC")
+        .map_err(internal)?;
+    let names = stmt
+        .query_map(params![room_id], |r| {
+            Ok(RoomAlias {
+                name: r.get(0)?,
+                created_at: r.get(1)?,
+            })
+        })
+        .map_err(internal)?
+        .filter_map(|r| r.ok())
+        .collect();
+    let mut stmt = conn
+        .prepare("SELECT room_id, name, reason, created_at FROM room_redirects WHERE target_room_id = ?1 ORDER BY created_at ASC")
+        .map_err(internal)?;
+    let redirects = stmt
+        .query_map(params![room_id], |r| {
+            Ok(RoomRedirect {
+                room_id: r.get(0)?,
+                name: r.get(1)?,
+                reason: r.get(2)?,
+                created_at: r.get(3)?,
+            })
+        })
+        .map_err(internal)?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(Json(RoomAliasesResponse {
+        room_id: room_id.to_string(),
+        names,
+        redirects,
+    }))
+}
+
 #[put("/api/v1/rooms/<room_id>", format = "json", data = "<body>")]
 pub fn update_room(
-    db: &State<Db>,
-    events: &State<EventBus>,
+    db: ScopedDb<'_>,
+    events: Events<'_>,
     room_id: &str,
     admin: AdminKey,
     body: Json<UpdateRoom>,
@@ -258,11 +506,11 @@ pub fn update_room(
     let conn = db.conn();
 
     // Verify room exists and admin key matches
-    let stored_key: Option<String> = conn
+    let (stored_key, old_name, old_tokenizer): (Option<String>, String, String) = conn
         .query_row(
-            "SELECT admin_key FROM rooms WHERE id = ?1",
+            "SELECT admin_key, name, search_tokenizer FROM rooms WHERE id = ?1",
             params![room_id],
-            |r| r.get(0),
+            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
         )
         .map_err(|_| {
             (
@@ -293,18 +541,83 @@ pub fn update_room(
     }
 
     // Validate retention settings if provided
-    if let Some(Some(max)) = body.max_messages && !(10..=1_000_000).contains(&max) {
+    if let Some(Some(max)) = body.max_messages && !MAX_MESSAGES_RANGE.contains(&max) {
         return Err((
             Status::BadRequest,
             Json(serde_json::json!({"error": "max_messages must be between 10 and 1000000"})),
         ));
     }
-    if let Some(Some(hours)) = body.max_message_age_hours && !(1..=8760).contains(&hours) {
+    if let Some(Some(hours)) = body.max_message_age_hours && !MESSAGE_AGE_HOURS_RANGE.contains(&hours) {
         return Err((
             Status::BadRequest,
             Json(serde_json::json!({"error": "max_message_age_hours must be between 1 and 8760 (1 year)"})),
         ));
     }
+    if let Some(Some(secs)) = body.file_ttl_secs && !FILE_TTL_RANGE.contains(&secs) {
+        return Err((
+            Status::BadRequest,
+            Json(serde_json::json!({"error": "file_ttl_secs must be between 60 and 31536000 (1 year)"})),
+        ));
+    }
+    if let Some(Some(secs)) = body.retention_notice_secs && !RETENTION_NOTICE_RANGE.contains(&secs) {
+        return Err((
+            Status::BadRequest,
+            Json(serde_json::json!({"error": "retention_notice_secs must be between 60 and 604800 (7 days)"})),
+        ));
+    }
+
+    let icon = match body.icon {
+        Some(Some(ref icon)) => Some(Some(
+            resolve_icon(&conn, room_id, icon)
+                .map_err(|e| (Status::BadRequest, Json(serde_json::json!({"error": e}))))?,
+        )),
+        Some(None) => Some(None),
+        None => None,
+    };
+    let color = match body.color {
+        Some(Some(ref color)) => Some(Some(normalize_color(color).ok_or_else(|| {
+            (
+                Status::BadRequest,
+                Json(serde_json::json!({"error": "color must be a hex color like #3b82f6 or #38f"})),
+            )
+        })?)),
+        Some(None) => Some(None),
+        None => None,
+    };
+    let tags = body
+        .tags
+        .as_deref()
+        .map(normalize_tags)
+        .transpose()
+        .map_err(|e| (Status::BadRequest, Json(serde_json::json!({"error": e}))))?;
+    let language = match body.language {
+        Some(Some(ref lang)) => {
+            let lang = lang.trim();
+            let tokenizer = crate::db::search_tokenizer_for_language(lang).ok_or_else(|| {
+                (
+                    Status::BadRequest,
+                    Json(serde_json::json!({"error": "language must be a language code like de, ja or pt-BR"})),
+                )
+            })?;
+            Some((Some(lang.to_string()), tokenizer))
+        }
+        Some(None) => Some((None, "porter")),
+        None => None,
+    };
+    let allowed = match body.allowed_reactions {
+        Some(Some(ref emoji)) => Some(
+            normalize_allowed_reactions(emoji)
+                .map_err(|e| (Status::BadRequest, Json(serde_json::json!({"error": e}))))?,
+        ),
+        Some(None) => Some(Vec::new()),
+        None => None,
+    };
+    let visibility = body
+        .visibility
+        .as_deref()
+        .map(normalize_visibility)
+        .transpose()
+        .map_err(|e| (Status::BadRequest, Json(serde_json::json!({"error": e}))))?;
 
     // Build dynamic UPDATE
     let now = chrono::Utc::now().to_rfc3339();
@@ -325,6 +638,39 @@ pub fn update_room(
     }
     if body.max_message_age_hours.is_some() {
         updates.push(format!("max_message_age_hours = ?{}", param_idx));
+        param_idx += 1;
+    }
+    if body.file_ttl_secs.is_some() {
+        updates.push(format!("file_ttl_secs = ?{}", param_idx));
+        param_idx += 1;
+    }
+    if body.retention_notice_secs.is_some() {
+        updates.push(format!("retention_notice_secs = ?{}", param_idx));
+        param_idx += 1;
+    }
+    if icon.is_some() {
+        updates.push(format!("icon = ?{}", param_idx));
+        param_idx += 1;
+    }
+    if color.is_some() {
+        updates.push(format!("color = ?{}", param_idx));
+        param_idx += 1;
+    }
+    if tags.is_some() {
+        updates.push(format!("tags = ?{}", param_idx));
+        param_idx += 1;
+    }
+    if allowed.is_some() {
+        updates.push(format!("allowed_reactions = ?{}", param_idx));
+        param_idx += 1;
+    }
+    if visibility.is_some() {
+        updates.push(format!("visibility = ?{}", param_idx));
+        param_idx += 1;
+    }
+    if language.is_some() {
+        updates.push(format!("language = ?{}", param_idx));
+        updates.push(format!("search_tokenizer = ?{}", param_idx + 1));
         let _ = param_idx; // suppress unused warning
     }
 
@@ -343,6 +689,31 @@ pub fn update_room(
     if let Some(ref max_age) = body.max_message_age_hours {
         param_values.push(Box::new(*max_age));
     }
+    if let Some(ref ttl) = body.file_ttl_secs {
+        param_values.push(Box::new(*ttl));
+    }
+    if let Some(ref notice) = body.retention_notice_secs {
+        param_values.push(Box::new(*notice));
+    }
+    if let Some(icon) = icon {
+        param_values.push(Box::new(icon));
+    }
+    if let Some(color) = color {
+        param_values.push(Box::new(color));
+    }
+    if let Some(ref tags) = tags {
+        param_values.push(Box::new(serde_json::to_string(tags).unwrap_or_else(|_| "[]".to_string())));
+    }
+    if let Some(ref allowed) = allowed {
+        param_values.push(Box::new(allowed_reactions_column(allowed)));
+    }
+    if let Some(ref visibility) = visibility {
+        param_values.push(Box::new(visibility.clone()));
+    }
+    if let Some((ref lang, tokenizer)) = language {
+        param_values.push(Box::new(lang.clone()));
+        param_values.push(Box::new(tokenizer.to_string()));
+    }
     param_values.push(Box::new(room_id.to_string()));
 
     let final_sql = format!(
@@ -376,6 +747,35 @@ pub fn update_room(
         }
     }
 
+    // A new tokenizer means the room's messages belong in a different FTS table
+    if let Some((_, tokenizer)) = language
+        && tokenizer != old_tokenizer
+        && let Err(e) = crate::db::reindex_room_fts(&conn, room_id)
+    {
+        eprintln!("⚠️ Failed to reindex room {room_id} for search: {e}");
+    }
+
+    // Keep the old name resolving to this room; the new name stops being anyone's alias
+    let mut rename_note = None;
+    if let Some(ref name) = body.name {
+        let new_name = name.trim();
+        if new_name != old_name {
+            conn.execute("DELETE FROM room_name_aliases WHERE name = ?1", params![new_name])
+                .ok();
+            conn.execute(
+                "INSERT OR REPLACE INTO room_name_aliases (name, room_id, created_at) VALUES (?1, ?2, ?3)",
+                params![&old_name, room_id, &now],
+            )
+            .ok();
+            rename_note = crate::db::insert_localized_system_message(
+                &conn,
+                room_id,
+                "room_renamed",
+                serde_json::json!({"old_name": &old_name, "new_name": new_name}),
+            );
+        }
+    }
+
     // Fetch updated room with stats
     let room = fetch_room_with_stats(&conn, room_id)
         .map_err(|_| {
@@ -387,14 +787,17 @@ pub fn update_room(
 
     // Publish SSE event
     events.publish(ChatEvent::RoomUpdated(room.clone()));
+    if let Some(note) = rename_note {
+        events.publish(ChatEvent::NewMessage(note));
+    }
 
     Ok(Json(room))
 }
 
 #[post("/api/v1/rooms/<room_id>/archive")]
 pub fn archive_room(
-    db: &State<Db>,
-    events: &State<EventBus>,
+    db: ScopedDb<'_>,
+    events: Events<'_>,
     room_id: &str,
     admin: AdminKey,
 ) -> Result<Json<RoomWithStats>, (Status, Json<serde_json::Value>)> {
@@ -460,8 +863,8 @@ pub fn archive_room(
 
 #[post("/api/v1/rooms/<room_id>/unarchive")]
 pub fn unarchive_room(
-    db: &State<Db>,
-    events: &State<EventBus>,
+    db: ScopedDb<'_>,
+    events: Events<'_>,
     room_id: &str,
     admin: AdminKey,
 ) -> Result<Json<RoomWithStats>, (Status, Json<serde_json::Value>)> {
@@ -527,18 +930,19 @@ pub fn unarchive_room(
 
 #[delete("/api/v1/rooms/<room_id>")]
 pub fn delete_room(
-    db: &State<Db>,
+    db: ScopedDb<'_>,
+    events: Events<'_>,
     room_id: &str,
     admin: AdminKey,
 ) -> Result<Json<serde_json::Value>, (Status, Json<serde_json::Value>)> {
     let conn = db.conn();
 
     // Fetch the room's admin key
-    let stored_key: Option<String> = conn
+    let (stored_key, name): (Option<String>, String) = conn
         .query_row(
-            "SELECT admin_key FROM rooms WHERE id = ?1",
+            "SELECT admin_key, name FROM rooms WHERE id = ?1",
             params![room_id],
-            |r| r.get(0),
+            |r| Ok((r.get(0)?, r.get(1)?)),
         )
         .map_err(|_| {
             (
@@ -560,6 +964,10 @@ pub fn delete_room(
 
     conn.execute("DELETE FROM rooms WHERE id = ?1", params![room_id])
         .unwrap_or(0);
+    events.publish(ChatEvent::RoomDeleted {
+        id: room_id.to_string(),
+        name,
+    });
 
     Ok(Json(serde_json::json!({"deleted": true})))
 }
@@ -0,0 +1,115 @@
+use crate::namespaces::ScopedDb;
+use crate::models::{Message, MessageSample};
+use crate::seed::Rng;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::get;
+use rusqlite::params;
+
+use super::messages::message_from_row;
+
+/// Largest sample one request may draw.
+const MAX_SAMPLE: usize = 1000;
+
+fn err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+/// GET /api/v1/rooms/<room_id>/messages/sample?n=100&strategy=uniform|recent-weighted&seed=42 —
+/// draw `n` messages at random without replacement, for evaluation harnesses that grade a
+/// slice of a room instead of exporting all of it. The same seed over the same messages gives
+/// the same sample; without one a seed is chosen and returned. `recent-weighted` halves a
+/// message's weight every `half_life_hours` (default 168) before the newest message.
+/// System messages are never sampled; `sender` and `sender_type` narrow the population.
+#[get("/api/v1/rooms/<room_id>/messages/sample?<n>&<strategy>&<seed>&<half_life_hours>&<sender>&<sender_type>")]
+#[allow(clippy::too_many_arguments)]
+pub fn sample_messages(
+    db: ScopedDb<'_>,
+    room_id: &str,
+    n: Option<usize>,
+    strategy: Option<&str>,
+    seed: Option<u64>,
+    half_life_hours: Option<f64>,
+    sender: Option<&str>,
+    sender_type: Option<&str>,
+) -> Result<Json<MessageSample>, (Status, Json<serde_json::Value>)> {
+    let n = n.unwrap_or(100);
+    if !(1..=MAX_SAMPLE).contains(&n) {
+        return Err(err(Status::BadRequest, &format!("n must be between 1 and {MAX_SAMPLE}")));
+    }
+    let strategy = strategy.unwrap_or("uniform");
+    let half_life_hours = match strategy {
+        "uniform" => None,
+        "recent-weighted" => {
+            let h = half_life_hours.unwrap_or(168.0);
+            if !(h.is_finite() && h > 0.0) {
+                return Err(err(Status::BadRequest, "half_life_hours must be a positive number"));
+            }
+            Some(h)
+        }
+        _ => return Err(err(Status::BadRequest, "strategy must be 'uniform' or 'recent-weighted'")),
+    };
+    let seed = seed.unwrap_or_else(|| rand_core::RngCore::next_u64(&mut rand_core::OsRng));
+
+    let conn = db.conn();
+    let exists: bool = conn
+        .query_row("SELECT COUNT(*) FROM rooms WHERE id = ?1", params![room_id], |r| r.get::<_, i64>(0))
+        .map(|c| c > 0)
+        .unwrap_or(false);
+    if !exists {
+        return Err(err(Status::NotFound, "Room not found"));
+    }
+
+    let population: Vec<(String, String)> = conn
+        .prepare(
+            "SELECT id, created_at FROM messages
+             WHERE room_id = ?1 AND kind != 'system'
+               AND (?2 IS NULL OR sender = ?2) AND (?3 IS NULL OR sender_type = ?3)
+             ORDER BY seq ASC",
+        )
+        .and_then(|mut s| {
+            s.query_map(params![room_id, sender, sender_type], |r| Ok((r.get(0)?, r.get(1)?)))
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+
+    // Weighted sampling without replacement (Efraimidis–Spirakis): keep the n largest
+    // ln(u) / weight. Uniform sampling is the all-weights-equal case.
+    let parse = |t: &str| chrono::DateTime::parse_from_rfc3339(t).ok().map(|d| d.timestamp() as f64);
+    let newest = population.iter().filter_map(|(_, t)| parse(t)).fold(f64::MIN, f64::max);
+    let mut rng = Rng::new(seed);
+    let mut keyed: Vec<(f64, usize)> = population
+        .iter()
+        .enumerate()
+        .map(|(i, (_, created_at))| {
+            let weight = match (half_life_hours, parse(created_at)) {
+                (Some(h), Some(t)) => 0.5f64.powf((newest - t) / 3600.0 / h),
+                _ => 1.0,
+            };
+            (rng.unit().ln() / weight, i)
+        })
+        .collect();
+    keyed.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+    let mut chosen: Vec<usize> = keyed.into_iter().take(n).map(|(_, i)| i).collect();
+    chosen.sort_unstable();
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, room_id, sender, content, metadata, created_at, edited_at, reply_to, sender_type, seq, pinned_at, pinned_by, kind, \
+             (SELECT COUNT(*) FROM message_edits WHERE message_id = messages.id) FROM messages WHERE id = ?1",
+        )
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+    let messages: Vec<Message> = chosen
+        .into_iter()
+        .filter_map(|i| stmt.query_row(params![&population[i].0], message_from_row).ok())
+        .collect();
+
+    Ok(Json(MessageSample {
+        room_id: room_id.to_string(),
+        strategy: strategy.to_string(),
+        seed,
+        half_life_hours,
+        population: population.len(),
+        messages,
+    }))
+}
@@ -0,0 +1,173 @@
+use crate::models::{DeferredMessage, DeferredMessagesResponse};
+use crate::namespaces::ScopedDb;
+use chrono::{DateTime, Utc};
+use rocket::http::{ContentType, Status};
+use rocket::serde::json::Json;
+use rocket::{delete, get};
+use rusqlite::{params, Connection};
+
+use super::AdminKey;
+
+fn err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+fn check_room(conn: &Connection, room_id: &str) -> Result<(), (Status, Json<serde_json::Value>)> {
+    conn.query_row("SELECT 1 FROM rooms WHERE id = ?1", params![room_id], |_| Ok(()))
+        .map_err(|_| err(Status::NotFound, "Room not found"))
+}
+
+/// GET /api/v1/rooms/<room_id>/scheduled — scheduled posts waiting to go out, soonest first
+/// (`?sender=` for one sender's).
+#[get("/api/v1/rooms/<room_id>/scheduled?<sender>")]
+pub fn list_scheduled(
+    db: ScopedDb<'_>,
+    room_id: &str,
+    sender: Option<&str>,
+) -> Result<Json<DeferredMessagesResponse>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    check_room(&conn, room_id)?;
+    let messages = crate::scheduler::pending(&conn, room_id, sender.map(str::trim).filter(|s| !s.is_empty()));
+    let count = messages.len();
+    Ok(Json(DeferredMessagesResponse {
+        room_id: room_id.to_string(),
+        messages,
+        count,
+    }))
+}
+
+/// GET /api/v1/rooms/<room_id>/calendar.ics — the room's scheduled posts and its next
+/// scheduled snapshot as an iCalendar feed, for subscribing from a calendar app.
+#[get("/api/v1/rooms/<room_id>/calendar.ics")]
+pub fn room_calendar(
+    db: ScopedDb<'_>,
+    room_id: &str,
+) -> Result<(ContentType, String), (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    let room_name: String = conn
+        .query_row("SELECT name FROM rooms WHERE id = ?1", params![room_id], |r| r.get(0))
+        .map_err(|_| err(Status::NotFound, "Room not found"))?;
+    let scheduled = crate::scheduler::pending(&conn, room_id, None);
+    let snapshot: Option<(String, String)> = conn
+        .query_row(
+            "SELECT cron, next_run_at FROM room_snapshot_schedules WHERE room_id = ?1 AND next_run_at IS NOT NULL",
+            params![room_id],
+            |r| Ok((r.get(0)?, r.get(1)?)),
+        )
+        .ok();
+    let ics = render_calendar(room_id, &room_name, &scheduled, snapshot.as_ref());
+    Ok((ContentType::new("text", "calendar"), ics))
+}
+
+/// `20260501T090000Z`; unparseable timestamps fall back to now so the feed stays valid.
+fn ics_time(rfc3339: &str) -> String {
+    DateTime::parse_from_rfc3339(rfc3339)
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now())
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+/// Escape a TEXT value (RFC 5545 §3.3.11).
+fn ics_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Append a content line, folded at 75 octets without splitting a UTF-8 character.
+fn push_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+fn render_calendar(
+    room_id: &str,
+    room_name: &str,
+    scheduled: &[DeferredMessage],
+    snapshot: Option<&(String, String)>,
+) -> String {
+    let now = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut out = String::new();
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, "PRODID:-//local-agent-chat//scheduled posts//EN");
+    push_line(&mut out, "CALSCALE:GREGORIAN");
+    push_line(&mut out, &format!("X-WR-CALNAME:{}", ics_text(&format!("#{room_name}"))));
+    for msg in scheduled {
+        let Some(at) = msg.scheduled_at.as_deref() else {
+            continue;
+        };
+        let first_line = msg.content.lines().next().unwrap_or_default();
+        let summary: String = first_line.chars().take(80).collect();
+        push_line(&mut out, "BEGIN:VEVENT");
+        push_line(&mut out, &format!("UID:{}@local-agent-chat", msg.id));
+        push_line(&mut out, &format!("DTSTAMP:{}", ics_time(&msg.queued_at)));
+        push_line(&mut out, &format!("DTSTART:{}", ics_time(at)));
+        push_line(&mut out, &format!("SUMMARY:{}", ics_text(&format!("{}: {summary}", msg.sender))));
+        push_line(&mut out, &format!("DESCRIPTION:{}", ics_text(&msg.content)));
+        push_line(&mut out, "END:VEVENT");
+    }
+    if let Some((cron, next_run_at)) = snapshot {
+        push_line(&mut out, "BEGIN:VEVENT");
+        push_line(&mut out, &format!("UID:snapshot-{room_id}@local-agent-chat"));
+        push_line(&mut out, &format!("DTSTAMP:{now}"));
+        push_line(&mut out, &format!("DTSTART:{}", ics_time(next_run_at)));
+        push_line(&mut out, "SUMMARY:Room snapshot");
+        push_line(&mut out, &format!("DESCRIPTION:{}", ics_text(&format!("Scheduled snapshot ({cron})"))));
+        push_line(&mut out, "END:VEVENT");
+    }
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+/// DELETE /api/v1/rooms/<room_id>/scheduled/<message_id> — cancel a scheduled post before it
+/// goes out (its sender via `?sender=`, or the room admin key).
+#[delete("/api/v1/rooms/<room_id>/scheduled/<message_id>?<sender>")]
+pub fn cancel_scheduled(
+    db: ScopedDb<'_>,
+    room_id: &str,
+    message_id: &str,
+    sender: Option<&str>,
+    admin: Option<AdminKey>,
+) -> Result<Json<serde_json::Value>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    let existing_sender: String = conn
+        .query_row(
+            "SELECT sender FROM deferred_messages WHERE id = ?1 AND room_id = ?2 AND scheduled_at IS NOT NULL",
+            params![message_id, room_id],
+            |r| r.get(0),
+        )
+        .map_err(|_| err(Status::NotFound, "Scheduled message not found"))?;
+
+    let is_room_admin = admin.is_some_and(|key| {
+        conn.query_row("SELECT admin_key FROM rooms WHERE id = ?1", params![room_id], |r| {
+            r.get::<_, Option<String>>(0)
+        })
+        .ok()
+        .flatten()
+        .is_some_and(|stored| stored == key.0)
+    });
+    if !is_room_admin {
+        let sender =
+            sender.ok_or_else(|| err(Status::BadRequest, "Sender query parameter required (or use room admin key)"))?;
+        if sender != existing_sender {
+            return Err(err(Status::Forbidden, "Only the original sender can cancel this message"));
+        }
+    }
+
+    conn.execute("DELETE FROM deferred_messages WHERE id = ?1", params![message_id])
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+    Ok(Json(serde_json::json!({"deleted": true, "id": message_id})))
+}
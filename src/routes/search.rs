@@ -1,12 +1,15 @@
-use crate::db::Db;
+use crate::membership::MemberAccess;
+use crate::namespaces::ScopedDb;
 use crate::models::*;
+use crate::regex_search::{self, RegexAccess, RegexSearchConfig};
+use crate::senders::{SenderPolicy, ServerToken};
 use rocket::http::Status;
 use rocket::serde::json::Json;
 use rocket::{get, State};
-#[get("/api/v1/activity?<since>&<limit>&<room_id>&<sender>&<sender_type>&<after>&<exclude_sender>")]
+#[get("/api/v1/activity?<since>&<limit>&<room_id>&<sender>&<sender_type>&<after>&<exclude_sender>&<label>&<tz>")]
 #[allow(clippy::too_many_arguments)]
 pub fn activity_feed(
-    db: &State<Db>,
+    db: ScopedDb<'_>,
     since: Option<&str>,
     limit: Option<i64>,
     room_id: Option<&str>,
@@ -14,14 +17,19 @@ pub fn activity_feed(
     sender_type: Option<&str>,
     after: Option<i64>,
     exclude_sender: Option<&str>,
-) -> Json<ActivityResponse> {
+    label: Option<&str>,
+    tz: Option<&str>,
+    access: MemberAccess,
+) -> Result<Json<ActivityResponse>, (Status, Json<serde_json::Value>)> {
     let conn = db.conn();
+    let tz = crate::timezones::resolve(&conn, tz)?;
     let limit = limit.unwrap_or(50).clamp(1, 500);
 
     let mut sql = String::from(
         "SELECT m.id, m.room_id, r.name, m.sender, m.sender_type, m.content, m.created_at, m.edited_at, m.reply_to, m.seq \
          FROM messages m JOIN rooms r ON m.room_id = r.id WHERE 1=1",
     );
+    sql.push_str(&format!(" AND {}", access.visible_sql("m.room_id")));
     let mut param_values: Vec<String> = vec![];
     let mut idx = 1;
 
@@ -41,8 +49,8 @@ pub fn activity_feed(
         idx += 1;
     }
     if let Some(sender_val) = sender {
-        sql.push_str(&format!(" AND m.sender = ?{idx}"));
-        param_values.push(sender_val.to_string());
+        sql.push_str(&format!(" AND {}", crate::db::sender_identity_sql("m.sender", idx)));
+        param_values.push(crate::db::resolve_sender(&conn, sender_val));
         idx += 1;
     }
     if let Some(sender_type_val) = sender_type {
@@ -50,6 +58,11 @@ pub fn activity_feed(
         param_values.push(sender_type_val.to_string());
         idx += 1;
     }
+    if let Some(label_val) = label {
+        sql.push_str(&format!(" AND {}", super::label_filter_sql("m.id", idx)));
+        param_values.push(super::label_param(label_val));
+        idx += 1;
+    }
     if let Some(exclude_val) = exclude_sender {
         let excluded: Vec<&str> = exclude_val
             .split(',')
@@ -76,9 +89,9 @@ pub fn activity_feed(
     sql.push_str(&format!(" ORDER BY m.seq DESC LIMIT ?{idx}"));
     param_values.push(limit.to_string());
 
-    let mut stmt = match conn.prepare(&sql) {
+    let mut stmt = match conn.prepare_cached(&sql) {
         Ok(s) => s,
-        Err(_) => return Json(ActivityResponse { events: Vec::new(), count: 0, since: since.map(String::from) }),
+        Err(_) => return Ok(Json(ActivityResponse { events: Vec::new(), count: 0, since: since.map(String::from) })),
     };
     let params_refs: Vec<&dyn rusqlite::types::ToSql> = param_values
         .iter()
@@ -87,6 +100,8 @@ pub fn activity_feed(
 
     let events: Vec<ActivityEvent> = match stmt
         .query_map(params_refs.as_slice(), |row| {
+            let created_at: String = row.get(6)?;
+            let edited_at: Option<String> = row.get(7)?;
             Ok(ActivityEvent {
                 event_type: "message".to_string(),
                 message_id: row.get(0)?,
@@ -95,8 +110,9 @@ pub fn activity_feed(
                 sender: row.get(3)?,
                 sender_type: row.get(4)?,
                 content: row.get(5)?,
-                created_at: row.get(6)?,
-                edited_at: row.get(7)?,
+                local_time: tz.and_then(|tz| crate::timezones::local_time(&created_at, edited_at.as_deref(), tz)),
+                created_at,
+                edited_at,
                 reply_to: row.get(8)?,
                 seq: row.get(9)?,
             })
@@ -106,17 +122,20 @@ pub fn activity_feed(
     };
 
     let count = events.len();
-    Json(ActivityResponse {
+    Ok(Json(ActivityResponse {
         events,
         count,
         since: since.map(String::from),
-    })
+    }))
 }
 
-#[get("/api/v1/search?<q>&<room_id>&<sender>&<sender_type>&<limit>&<after>&<before_seq>&<after_date>&<before_date>")]
+#[get("/api/v1/search?<q>&<room_id>&<sender>&<sender_type>&<limit>&<after>&<before_seq>&<after_date>&<before_date>&<envelope>&<mode>&<label>")]
 #[allow(clippy::too_many_arguments)]
 pub fn search_messages(
-    db: &State<Db>,
+    db: ScopedDb<'_>,
+    regex_config: &State<RegexSearchConfig>,
+    sender_policy: &State<SenderPolicy>,
+    server_token: ServerToken,
     q: &str,
     room_id: Option<&str>,
     sender: Option<&str>,
@@ -126,7 +145,35 @@ pub fn search_messages(
     before_seq: Option<i64>,
     after_date: Option<&str>,
     before_date: Option<&str>,
-) -> Result<Json<SearchResponse>, (Status, Json<serde_json::Value>)> {
+    envelope: Option<bool>,
+    mode: Option<&str>,
+    label: Option<&str>,
+    access: MemberAccess,
+) -> Result<Json<ListResponse<SearchResponse, SearchResult>>, (Status, Json<serde_json::Value>)> {
+    let visible_rooms = access.visible_sql("m.room_id");
+    match mode.map(str::trim).unwrap_or("fts") {
+        "fts" => {}
+        "regex" => {
+            match regex_config.access {
+                RegexAccess::Off => {
+                    return Err((
+                        Status::Forbidden,
+                        Json(serde_json::json!({"error": "Regex search is disabled on this server (REGEX_SEARCH=off)"})),
+                    ));
+                }
+                RegexAccess::Admin => sender_policy.check_server_token(&server_token)?,
+                RegexAccess::Open => {}
+            }
+            let filters = SearchFilters { room_id, sender, sender_type, after, before_seq, after_date, before_date, label, visible_rooms: &visible_rooms };
+            return regex_search_messages(&db, regex_config, q, limit, &filters, envelope);
+        }
+        _ => {
+            return Err((
+                Status::BadRequest,
+                Json(serde_json::json!({"error": "mode must be fts or regex"})),
+            ));
+        }
+    }
     let query = q.trim();
     if query.is_empty() {
         return Err((
@@ -166,13 +213,19 @@ pub fn search_messages(
             .collect::<Vec<_>>()
             .join(" ");
 
-        let mut sql = String::from(
+        // Each room's messages live in the index for its language's tokenizer
+        let matches = crate::db::FTS_TABLES
+            .iter()
+            .map(|(_, table)| format!("SELECT message_id, rank FROM {table} WHERE {table} MATCH ?1"))
+            .collect::<Vec<_>>()
+            .join(" UNION ALL ");
+        let mut sql = format!(
             "SELECT m.id, m.room_id, r.name, m.sender, m.sender_type, m.content, \
              m.created_at, m.edited_at, m.reply_to, m.seq \
-             FROM messages_fts f \
+             FROM ({matches}) f \
              JOIN messages m ON m.id = f.message_id \
              JOIN rooms r ON m.room_id = r.id \
-             WHERE messages_fts MATCH ?1",
+             WHERE {visible_rooms}",
         );
         let mut param_values: Vec<String> = vec![fts_query];
         let mut idx = 2;
@@ -183,8 +236,8 @@ pub fn search_messages(
             idx += 1;
         }
         if let Some(sender_val) = sender {
-            sql.push_str(&format!(" AND m.sender = ?{idx}"));
-            param_values.push(sender_val.to_string());
+            sql.push_str(&format!(" AND {}", crate::db::sender_identity_sql("m.sender", idx)));
+            param_values.push(crate::db::resolve_sender(&conn, sender_val));
             idx += 1;
         }
         if let Some(sender_type_val) = sender_type {
@@ -212,8 +265,13 @@ pub fn search_messages(
             param_values.push(before_date_val.to_string());
             idx += 1;
         }
+        if let Some(label_val) = label {
+            sql.push_str(&format!(" AND {}", super::label_filter_sql("m.id", idx)));
+            param_values.push(super::label_param(label_val));
+            idx += 1;
+        }
 
-        sql.push_str(&format!(" ORDER BY rank LIMIT ?{idx}"));
+        sql.push_str(&format!(" ORDER BY f.rank LIMIT ?{idx}"));
         param_values.push(fetch_limit.to_string());
 
         let mut stmt = conn.prepare(&sql)?;
@@ -235,6 +293,7 @@ pub fn search_messages(
                     edited_at: row.get(7)?,
                     reply_to: row.get(8)?,
                     seq: row.get(9)?,
+                    matches: None,
                 })
             })?
             .filter_map(|r| r.ok())
@@ -258,6 +317,7 @@ pub fn search_messages(
                  FROM messages m JOIN rooms r ON m.room_id = r.id \
                  WHERE m.content LIKE ?1 ESCAPE '\\'",
             );
+            sql.push_str(&format!(" AND {visible_rooms}"));
             let mut param_values: Vec<String> = vec![like_pattern];
             let mut idx = 2;
 
@@ -267,8 +327,8 @@ pub fn search_messages(
                 idx += 1;
             }
             if let Some(sender_val) = sender {
-                sql.push_str(&format!(" AND m.sender = ?{idx}"));
-                param_values.push(sender_val.to_string());
+                sql.push_str(&format!(" AND {}", crate::db::sender_identity_sql("m.sender", idx)));
+                param_values.push(crate::db::resolve_sender(&conn, sender_val));
                 idx += 1;
             }
             if let Some(sender_type_val) = sender_type {
@@ -296,6 +356,11 @@ pub fn search_messages(
                 param_values.push(before_date_val.to_string());
                 idx += 1;
             }
+            if let Some(label_val) = label {
+                sql.push_str(&format!(" AND {}", super::label_filter_sql("m.id", idx)));
+                param_values.push(super::label_param(label_val));
+                idx += 1;
+            }
 
             sql.push_str(&format!(" ORDER BY m.seq DESC LIMIT ?{idx}"));
             param_values.push(fetch_limit.to_string());
@@ -318,6 +383,7 @@ pub fn search_messages(
                     edited_at: row.get(7)?,
                     reply_to: row.get(8)?,
                     seq: row.get(9)?,
+                    matches: None,
                 })
             })
             .map_err(|_| (Status::InternalServerError, Json(serde_json::json!({"error": "Internal server error"}))))?
@@ -328,13 +394,140 @@ pub fn search_messages(
 
     let has_more = results.len() as i64 > limit;
     let results: Vec<SearchResult> = results.into_iter().take(limit as usize).collect();
+    if envelope.unwrap_or(false) {
+        // Results are relevance-ranked, so there is no seq cursor to resume from
+        return Ok(Json(ListResponse::Envelope(ListEnvelope {
+            items: results,
+            next_cursor: None,
+            has_more,
+        })));
+    }
     let count = results.len();
-    Ok(Json(SearchResponse {
+    Ok(Json(ListResponse::Plain(SearchResponse {
         results,
         count,
         query: query.to_string(),
         after_date: after_date.map(String::from),
         before_date: before_date.map(String::from),
         has_more,
-    }))
+        mode: None,
+        scanned: None,
+        truncated: None,
+        next_before_seq: None,
+    })))
+}
+
+/// Filters shared by both search modes.
+struct SearchFilters<'a> {
+    room_id: Option<&'a str>,
+    sender: Option<&'a str>,
+    sender_type: Option<&'a str>,
+    after: Option<i64>,
+    before_seq: Option<i64>,
+    after_date: Option<&'a str>,
+    before_date: Option<&'a str>,
+    label: Option<&'a str>,
+    /// Condition leaving out private rooms the caller isn't a member of
+    visible_rooms: &'a str,
+}
+
+/// `mode=regex`: scan content newest-first, returning each match with its capture groups.
+/// Results come back in seq order (newest first) rather than by relevance, so paging continues
+/// with `before_seq=next_before_seq`.
+fn regex_search_messages(
+    db: &ScopedDb<'_>,
+    config: &RegexSearchConfig,
+    pattern: &str,
+    limit: Option<i64>,
+    filters: &SearchFilters<'_>,
+    envelope: Option<bool>,
+) -> Result<Json<ListResponse<SearchResponse, SearchResult>>, (Status, Json<serde_json::Value>)> {
+    if pattern.is_empty() || pattern.len() > 500 {
+        return Err((
+            Status::BadRequest,
+            Json(serde_json::json!({"error": "Regex pattern must be 1-500 characters"})),
+        ));
+    }
+    let re = regex_search::compile(pattern)
+        .map_err(|e| (Status::BadRequest, Json(serde_json::json!({"error": e}))))?;
+    let limit = limit.unwrap_or(50).clamp(1, 200) as usize;
+
+    let conn = db.conn();
+    let mut sql = format!("m.kind = 'message' AND {}", filters.visible_rooms);
+    let mut param_values: Vec<String> = vec![];
+    let mut idx = 1;
+
+    if let Some(room_val) = filters.room_id {
+        sql.push_str(&format!(" AND m.room_id = ?{idx}"));
+        param_values.push(room_val.to_string());
+        idx += 1;
+    }
+    if let Some(sender_val) = filters.sender {
+        sql.push_str(&format!(" AND {}", crate::db::sender_identity_sql("m.sender", idx)));
+        param_values.push(crate::db::resolve_sender(&conn, sender_val));
+        idx += 1;
+    }
+    if let Some(sender_type_val) = filters.sender_type {
+        sql.push_str(&format!(" AND m.sender_type = ?{idx}"));
+        param_values.push(sender_type_val.to_string());
+        idx += 1;
+    }
+    if let Some(after_val) = filters.after {
+        sql.push_str(&format!(" AND m.seq > ?{idx}"));
+        param_values.push(after_val.to_string());
+        idx += 1;
+    }
+    if let Some(before_val) = filters.before_seq {
+        sql.push_str(&format!(" AND m.seq < ?{idx}"));
+        param_values.push(before_val.to_string());
+        idx += 1;
+    }
+    if let Some(after_date_val) = filters.after_date {
+        sql.push_str(&format!(" AND m.created_at > ?{idx}"));
+        param_values.push(after_date_val.to_string());
+        idx += 1;
+    }
+    if let Some(before_date_val) = filters.before_date {
+        sql.push_str(&format!(" AND m.created_at < ?{idx}"));
+        param_values.push(before_date_val.to_string());
+        idx += 1;
+    }
+    if let Some(label_val) = filters.label {
+        sql.push_str(&format!(" AND {}", super::label_filter_sql("m.id", idx)));
+        param_values.push(super::label_param(label_val));
+    }
+
+    let scan = regex_search::scan(&conn, &re, &sql, &param_values, limit, config)
+        .map_err(|_| (Status::InternalServerError, Json(serde_json::json!({"error": "Internal server error"}))))?;
+    let more_matches = scan.results.len() > limit;
+    let results: Vec<SearchResult> = scan.results.into_iter().take(limit).collect();
+    let next_before_seq = if more_matches {
+        results.last().map(|r| r.seq)
+    } else if scan.truncated {
+        scan.last_scanned_seq
+    } else {
+        None
+    };
+    let has_more = next_before_seq.is_some();
+
+    if envelope.unwrap_or(false) {
+        return Ok(Json(ListResponse::Envelope(ListEnvelope {
+            items: results,
+            next_cursor: next_before_seq,
+            has_more,
+        })));
+    }
+    let count = results.len();
+    Ok(Json(ListResponse::Plain(SearchResponse {
+        results,
+        count,
+        query: pattern.to_string(),
+        after_date: filters.after_date.map(String::from),
+        before_date: filters.before_date.map(String::from),
+        has_more,
+        mode: Some("regex".to_string()),
+        scanned: Some(scan.scanned),
+        truncated: Some(scan.truncated),
+        next_before_seq,
+    })))
 }
@@ -0,0 +1,43 @@
+use crate::models::SearchIndexReport;
+use crate::namespaces::ScopedDb;
+use crate::senders::{SenderPolicy, ServerToken};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{get, post, State};
+
+fn check(
+    db: ScopedDb<'_>,
+    sender_policy: &State<SenderPolicy>,
+    server_token: ServerToken,
+    repair: bool,
+) -> Result<Json<SearchIndexReport>, (Status, Json<serde_json::Value>)> {
+    sender_policy.check_server_token(&server_token)?;
+    crate::db::check_fts_index(&db.conn(), repair).map(Json).map_err(|e| {
+        (
+            Status::InternalServerError,
+            Json(serde_json::json!({"error": format!("Search index check failed: {e}")})),
+        )
+    })
+}
+
+/// GET /api/v1/admin/search-index — compare the search index with the messages without
+/// changing anything (server token required)
+#[get("/api/v1/admin/search-index")]
+pub fn search_index_status(
+    db: ScopedDb<'_>,
+    sender_policy: &State<SenderPolicy>,
+    server_token: ServerToken,
+) -> Result<Json<SearchIndexReport>, (Status, Json<serde_json::Value>)> {
+    check(db, sender_policy, server_token, false)
+}
+
+/// POST /api/v1/admin/search-index/repair — the same check, fixing whatever it finds (server
+/// token required). The report's counts describe the index as it was before the repair.
+#[post("/api/v1/admin/search-index/repair")]
+pub fn repair_search_index(
+    db: ScopedDb<'_>,
+    sender_policy: &State<SenderPolicy>,
+    server_token: ServerToken,
+) -> Result<Json<SearchIndexReport>, (Status, Json<serde_json::Value>)> {
+    check(db, sender_policy, server_token, true)
+}
@@ -0,0 +1,301 @@
+use rocket::http::{ContentType, Header, Status};
+use rocket::response::Responder;
+use rocket::serde::json::Json;
+use rocket::{get, Request, Response};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::io::{Cursor, Write};
+
+use crate::membership::MemberAccess;
+use crate::namespaces::ScopedDb;
+
+use super::export::{exported_message_from_row, ExportedMessage};
+
+/// Version of the sender archive layout; bumped when entries or fields are added.
+pub const SENDER_EXPORT_VERSION: i64 = 1;
+
+/// A message in a sender archive: the room export shape plus where it was posted.
+#[derive(Debug, Serialize)]
+struct SenderMessage {
+    room_id: String,
+    room_name: String,
+    #[serde(flatten)]
+    message: ExportedMessage,
+}
+
+/// A reaction the sender left on someone's (or their own) message.
+#[derive(Debug, Serialize)]
+struct SenderReaction {
+    message_id: String,
+    room_id: String,
+    /// The name the reaction was made under (the sender or one of its aliases)
+    sender: String,
+    emoji: String,
+    created_at: String,
+}
+
+#[derive(Debug, Serialize)]
+struct SenderFile {
+    id: String,
+    room_id: String,
+    sender: String,
+    filename: String,
+    content_type: String,
+    size: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sha256: Option<String>,
+    created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expires_at: Option<String>,
+    /// Path of the contents inside the archive; absent when exported without files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct SenderRoomSummary {
+    room_id: String,
+    room_name: String,
+    messages: i64,
+}
+
+#[derive(Debug, Serialize)]
+struct SenderManifest {
+    export_version: i64,
+    /// Canonical sender; messages posted under its aliases are included
+    sender: String,
+    aliases: Vec<String>,
+    exported_at: String,
+    messages: usize,
+    reactions: usize,
+    files: usize,
+    rooms: Vec<SenderRoomSummary>,
+}
+
+/// A zip download (`application/zip` with a Content-Disposition filename).
+pub struct ZipArchive {
+    filename: String,
+    bytes: Vec<u8>,
+}
+
+impl<'r> Responder<'r, 'static> for ZipArchive {
+    fn respond_to(self, _req: &'r Request<'_>) -> rocket::response::Result<'static> {
+        Response::build()
+            .header(ContentType::ZIP)
+            .header(Header::new(
+                "Content-Disposition",
+                format!("attachment; filename=\"{}\"", self.filename),
+            ))
+            .sized_body(self.bytes.len(), Cursor::new(self.bytes))
+            .ok()
+    }
+}
+
+fn err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+/// Keep archive paths and download names to one safe path segment.
+fn safe_name(name: &str) -> String {
+    let safe: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '_' })
+        .collect();
+    match safe.trim_start_matches('.') {
+        "" => "file".to_string(),
+        s => s.to_string(),
+    }
+}
+
+/// GET /api/v1/senders/<name>/export — a zip of everything a sender posted across rooms, for
+/// data-portability requests or snapshotting an agent's history. Aliases are folded into the
+/// canonical sender. `include_files=false` leaves file contents out (the manifest stays). Private
+/// rooms are left out unless the request carries a member token for them.
+///
+/// Archive entries: `manifest.json`, `profile.json` (null without a profile), `messages.ndjson`
+/// (one message per line, oldest first, with metadata, reactions and edit history),
+/// `reactions.json`, `files.json` and `files/<id>/<filename>`.
+#[get("/api/v1/senders/<name>/export?<include_files>")]
+pub fn export_sender(
+    name: &str,
+    include_files: Option<bool>,
+    db: ScopedDb<'_>,
+    access: MemberAccess,
+) -> Result<ZipArchive, (Status, Json<serde_json::Value>)> {
+    let name = name.trim();
+    if name.is_empty() || name.len() > 100 {
+        return Err(err(Status::BadRequest, "Sender must be 1-100 characters"));
+    }
+    let include_files = include_files.unwrap_or(true);
+    let conn = db.conn();
+    let sender = crate::db::resolve_sender(&conn, name);
+
+    let messages = sender_messages(&conn, &sender, &access);
+    let reactions = sender_reactions(&conn, &sender, &access);
+    let files = sender_files(&conn, &sender, &access);
+    let profile = super::profiles::load_profile(&conn, &sender);
+    if messages.is_empty() && reactions.is_empty() && files.is_empty() && profile.is_none() {
+        return Err(err(Status::NotFound, "No data found for this sender"));
+    }
+
+    let mut rooms: Vec<SenderRoomSummary> = Vec::new();
+    for m in &messages {
+        match rooms.iter_mut().find(|r| r.room_id == m.room_id) {
+            Some(room) => room.messages += 1,
+            None => rooms.push(SenderRoomSummary {
+                room_id: m.room_id.clone(),
+                room_name: m.room_name.clone(),
+                messages: 1,
+            }),
+        }
+    }
+    let manifest = SenderManifest {
+        export_version: SENDER_EXPORT_VERSION,
+        aliases: crate::db::sender_aliases(&conn, &sender),
+        sender: sender.clone(),
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        messages: messages.len(),
+        reactions: reactions.len(),
+        files: files.len(),
+        rooms,
+    };
+
+    let bytes = write_archive(&conn, &manifest, profile.as_ref(), &messages, &reactions, files, include_files)
+        .map_err(|e| {
+            eprintln!("⚠️ Sender export for {sender} failed: {e}");
+            err(Status::InternalServerError, "Internal server error")
+        })?;
+    Ok(ZipArchive {
+        filename: format!("sender-export-{}.zip", safe_name(&sender)),
+        bytes,
+    })
+}
+
+fn sender_messages(conn: &Connection, sender: &str, access: &MemberAccess) -> Vec<SenderMessage> {
+    let sql = format!(
+        "SELECT m.seq, m.sender, m.sender_type, m.content, m.created_at, \
+         m.edited_at, m.reply_to, m.pinned_at, m.pinned_by, m.metadata, m.kind, m.id, \
+         (SELECT json_group_array(json_object('sender', r.sender, 'emoji', r.emoji, 'created_at', r.created_at)) \
+            FROM (SELECT * FROM message_reactions WHERE message_id = m.id ORDER BY created_at, id) r), \
+         (SELECT json_group_array(json_object('previous_content', e.previous_content, 'edited_at', e.edited_at, \
+                 'editor', e.editor, 'patch_format', e.patch_format, 'patch', e.patch)) \
+            FROM (SELECT * FROM message_edits WHERE message_id = m.id ORDER BY edited_at, id) e), \
+         m.room_id, rm.name \
+         FROM messages m JOIN rooms rm ON rm.id = m.room_id \
+         WHERE {identity} AND m.kind = 'message' AND {visible} ORDER BY m.seq ASC",
+        identity = crate::db::sender_identity_sql("m.sender", 1),
+        visible = access.visible_sql("m.room_id")
+    );
+    conn.prepare(&sql)
+        .and_then(|mut s| {
+            s.query_map(params![sender], |row| {
+                Ok(SenderMessage {
+                    message: exported_message_from_row(row, true)?,
+                    room_id: row.get(14)?,
+                    room_name: row.get(15)?,
+                })
+            })
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .unwrap_or_default()
+}
+
+fn sender_reactions(conn: &Connection, sender: &str, access: &MemberAccess) -> Vec<SenderReaction> {
+    let sql = format!(
+        "SELECT m.message_id, msg.room_id, m.sender, m.emoji, m.created_at
+         FROM message_reactions m JOIN messages msg ON msg.id = m.message_id
+         WHERE {} AND {} ORDER BY m.created_at, m.id",
+        crate::db::sender_identity_sql("m.sender", 1),
+        access.visible_sql("msg.room_id")
+    );
+    conn.prepare(&sql)
+        .and_then(|mut s| {
+            s.query_map(params![sender], |row| {
+                Ok(SenderReaction {
+                    message_id: row.get(0)?,
+                    room_id: row.get(1)?,
+                    sender: row.get(2)?,
+                    emoji: row.get(3)?,
+                    created_at: row.get(4)?,
+                })
+            })
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .unwrap_or_default()
+}
+
+fn sender_files(conn: &Connection, sender: &str, access: &MemberAccess) -> Vec<SenderFile> {
+    let sql = format!(
+        "SELECT m.id, m.room_id, m.sender, m.filename, m.content_type, m.size, m.sha256, m.created_at, m.expires_at
+         FROM files m WHERE {} AND {} ORDER BY m.created_at, m.id",
+        crate::db::sender_identity_sql("m.sender", 1),
+        access.visible_sql("m.room_id")
+    );
+    conn.prepare(&sql)
+        .and_then(|mut s| {
+            s.query_map(params![sender], |row| {
+                Ok(SenderFile {
+                    id: row.get(0)?,
+                    room_id: row.get(1)?,
+                    sender: row.get(2)?,
+                    filename: row.get(3)?,
+                    content_type: row.get(4)?,
+                    size: row.get(5)?,
+                    sha256: row.get(6)?,
+                    created_at: row.get(7)?,
+                    expires_at: row.get(8)?,
+                    path: None,
+                })
+            })
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .unwrap_or_default()
+}
+
+fn file_data(conn: &Connection, file_id: &str) -> Option<Vec<u8>> {
+    conn.query_row(
+        "SELECT COALESCE(b.data, f.data) FROM files f LEFT JOIN file_blobs b ON b.sha256 = f.sha256 WHERE f.id = ?1",
+        params![file_id],
+        |r| r.get(0),
+    )
+    .ok()
+}
+
+fn write_archive(
+    conn: &Connection,
+    manifest: &SenderManifest,
+    profile: Option<&crate::models::Profile>,
+    messages: &[SenderMessage],
+    reactions: &[SenderReaction],
+    mut files: Vec<SenderFile>,
+    include_files: bool,
+) -> zip::result::ZipResult<Vec<u8>> {
+    let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+
+    if include_files {
+        for file in &mut files {
+            let Some(data) = file_data(conn, &file.id) else { continue };
+            let path = format!("files/{}/{}", safe_name(&file.id), safe_name(&file.filename));
+            zip.start_file(path.as_str(), options)?;
+            zip.write_all(&data)?;
+            file.path = Some(path);
+        }
+    }
+
+    zip.start_file("manifest.json", options)?;
+    zip.write_all(&serde_json::to_vec_pretty(manifest).unwrap_or_default())?;
+    zip.start_file("profile.json", options)?;
+    zip.write_all(&serde_json::to_vec_pretty(profile).unwrap_or_default())?;
+    zip.start_file("messages.ndjson", options)?;
+    for m in messages {
+        zip.write_all(&serde_json::to_vec(m).unwrap_or_default())?;
+        zip.write_all(b"\n")?;
+    }
+    zip.start_file("reactions.json", options)?;
+    zip.write_all(&serde_json::to_vec_pretty(reactions).unwrap_or_default())?;
+    zip.start_file("files.json", options)?;
+    zip.write_all(&serde_json::to_vec_pretty(&files).unwrap_or_default())?;
+
+    Ok(zip.finish()?.into_inner())
+}
@@ -0,0 +1,751 @@
+use crate::events::{ChatEvent, Events};
+use crate::models::{
+    ConfigApplyResult, IncomingWebhookConfig, RoomConfig, RoomMemberConfig, RoomRetentionConfig, ServerConfig,
+    ServerWebhookConfig, SetQuietHours, SetRoomWelcome, SetUploadPolicy, WebhookConfig,
+};
+use crate::namespaces::ScopedDb;
+use crate::secrets::SecretBox;
+use crate::senders::{SenderPolicy, ServerToken};
+use chrono::NaiveTime;
+use chrono_tz::Tz;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{get, put, State};
+use rusqlite::{params, Connection};
+use std::collections::BTreeMap;
+
+use super::rooms::{
+    allowed_reactions_column, normalize_allowed_reactions, normalize_color, normalize_tags, normalize_visibility,
+    resolve_icon, tags_from_column, FILE_TTL_RANGE, MAX_MESSAGES_RANGE, MESSAGE_AGE_HOURS_RANGE, RETENTION_NOTICE_RANGE,
+};
+use super::webhook_routes::{check_tls, seal_headers, validate_target, ROOM_EVENTS};
+
+/// Format version of the configuration document.
+const CONFIG_VERSION: i64 = 1;
+
+/// `created_by` for rooms and webhooks created from a document.
+const CONFIG_AUTHOR: &str = "config";
+
+type ApiError = (Status, Json<serde_json::Value>);
+
+fn err(status: Status, msg: &str) -> ApiError {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+fn internal(_e: rusqlite::Error) -> ApiError {
+    err(Status::InternalServerError, "Internal server error")
+}
+
+/// Prefix an error with the room it is about.
+fn in_room(name: &str, (status, Json(body)): ApiError) -> ApiError {
+    let msg = body["error"].as_str().unwrap_or_default();
+    err(status, &format!("Room '{name}': {msg}"))
+}
+
+// --- Export ---
+
+fn export_room(conn: &Connection, secrets: &SecretBox, room_id: &str, include_secrets: bool) -> rusqlite::Result<RoomConfig> {
+    #[allow(clippy::type_complexity)]
+    let (name, description, tags, language, icon, color, archived, allowed, retention, visibility): (
+        String,
+        String,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+        bool,
+        Option<String>,
+        RoomRetentionConfig,
+        String,
+    ) = conn.query_row(
+        "SELECT r.name, COALESCE(r.description, ''), r.tags, r.language,
+                CASE WHEN EXISTS (SELECT 1 FROM files WHERE id = r.icon AND room_id = r.id) THEN NULL ELSE r.icon END,
+                r.color, r.archived_at IS NOT NULL, r.allowed_reactions,
+                r.max_messages, r.max_message_age_hours, r.retention_notice_secs, r.file_ttl_secs, r.visibility
+         FROM rooms r WHERE r.id = ?1",
+        params![room_id],
+        |r| {
+            Ok((
+                r.get(0)?,
+                r.get(1)?,
+                r.get(2)?,
+                r.get(3)?,
+                r.get(4)?,
+                r.get(5)?,
+                r.get(6)?,
+                r.get(7)?,
+                RoomRetentionConfig {
+                    max_messages: r.get(8)?,
+                    max_message_age_hours: r.get(9)?,
+                    retention_notice_secs: r.get(10)?,
+                    file_ttl_secs: r.get(11)?,
+                },
+                r.get(12)?,
+            ))
+        },
+    )?;
+
+    let welcome = conn
+        .query_row(
+            "SELECT template, delivery, from_sender FROM room_welcomes WHERE room_id = ?1",
+            params![room_id],
+            |r| {
+                Ok(SetRoomWelcome {
+                    template: r.get(0)?,
+                    delivery: r.get(1)?,
+                    from: r.get(2)?,
+                })
+            },
+        )
+        .ok();
+    let upload_policy = crate::uploads::room_policy(conn, room_id).map(|p| SetUploadPolicy {
+        allowed_types: p.allowed_types,
+        blocked_types: p.blocked_types,
+        allowed_extensions: p.allowed_extensions,
+        blocked_extensions: p.blocked_extensions,
+        verify_content_type: p.verify_content_type,
+    });
+    let quiet_hours = crate::quiet_hours::room_quiet_hours(conn, room_id).map(|q| SetQuietHours {
+        start: q.start,
+        end: q.end,
+        timezone: Some(q.timezone),
+    });
+
+    let webhooks = conn
+        .prepare(
+            "SELECT url, events, active, ordered, ca_cert, secret, headers, client_cert
+             FROM webhooks WHERE room_id = ?1 ORDER BY created_at, rowid",
+        )?
+        .query_map(params![room_id], |r| {
+            let headers: Option<String> = r.get(6)?;
+            let client_cert: Option<String> = r.get(7)?;
+            Ok(WebhookConfig {
+                url: r.get(0)?,
+                events: r.get(1)?,
+                active: r.get::<_, i32>(2)? != 0,
+                ordered: r.get::<_, i32>(3)? != 0,
+                ca_cert: r.get(4)?,
+                secret: if include_secrets { r.get(5)? } else { None },
+                headers: headers
+                    .filter(|_| include_secrets)
+                    .and_then(|sealed| secrets.open(&sealed))
+                    .and_then(|json| serde_json::from_str(&json).ok()),
+                client_cert: client_cert.filter(|_| include_secrets).and_then(|sealed| secrets.open(&sealed)),
+            })
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+    let incoming_webhooks = conn
+        .prepare(
+            "SELECT name, active, rate_limit, daily_quota, token
+             FROM incoming_webhooks WHERE room_id = ?1 ORDER BY created_at, rowid",
+        )?
+        .query_map(params![room_id], |r| {
+            Ok(IncomingWebhookConfig {
+                name: r.get(0)?,
+                active: r.get::<_, i32>(1)? != 0,
+                rate_limit: r.get(2)?,
+                daily_quota: r.get(3)?,
+                token: if include_secrets { r.get(4)? } else { None },
+            })
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+    let members = conn
+        .prepare("SELECT sender, added_by, token FROM room_members WHERE room_id = ?1 ORDER BY added_at, sender")?
+        .query_map(params![room_id], |r| {
+            Ok(RoomMemberConfig {
+                sender: r.get(0)?,
+                added_by: r.get(1)?,
+                token: if include_secrets { r.get(2)? } else { None },
+            })
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(RoomConfig {
+        name,
+        description,
+        tags: tags_from_column(tags),
+        language,
+        icon,
+        color,
+        archived,
+        allowed_reactions: tags_from_column(allowed),
+        retention,
+        welcome,
+        upload_policy,
+        quiet_hours,
+        webhooks,
+        incoming_webhooks,
+        visibility,
+        members,
+    })
+}
+
+/// GET /api/v1/admin/config/export?include_secrets=true — the server's configuration as one
+/// document (server token required): rooms with their settings, visibility and members,
+/// webhooks, incoming webhooks, welcome templates, upload policies, quiet hours and retention.
+/// No messages, files or profiles. Webhook secrets, headers, client certificates, incoming
+/// webhook tokens and member tokens are left out unless `include_secrets=true`.
+#[get("/api/v1/admin/config/export?<include_secrets>")]
+pub fn export_server_config(
+    db: ScopedDb<'_>,
+    secrets: &State<SecretBox>,
+    sender_policy: &State<SenderPolicy>,
+    server_token: ServerToken,
+    include_secrets: Option<bool>,
+) -> Result<Json<ServerConfig>, ApiError> {
+    sender_policy.check_server_token(&server_token)?;
+    let include_secrets = include_secrets.unwrap_or(false);
+    let conn = db.conn();
+
+    let room_ids: Vec<String> = conn
+        .prepare("SELECT id FROM rooms WHERE COALESCE(room_type, 'room') != 'dm' ORDER BY created_at, name")
+        .and_then(|mut s| s.query_map([], |r| r.get(0)).map(|rows| rows.filter_map(|r| r.ok()).collect()))
+        .map_err(internal)?;
+    let rooms = room_ids
+        .iter()
+        .map(|id| export_room(&conn, secrets, id, include_secrets))
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(internal)?;
+    let server_webhooks = conn
+        .prepare("SELECT url, events, active, secret FROM server_webhooks ORDER BY created_at, rowid")
+        .and_then(|mut s| {
+            s.query_map([], |r| {
+                Ok(ServerWebhookConfig {
+                    url: r.get(0)?,
+                    events: r.get(1)?,
+                    active: r.get::<_, i32>(2)? != 0,
+                    secret: if include_secrets { r.get(3)? } else { None },
+                })
+            })
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .map_err(internal)?;
+
+    Ok(Json(ServerConfig {
+        version: CONFIG_VERSION,
+        exported_at: Some(chrono::Utc::now().to_rfc3339()),
+        rooms,
+        server_webhooks: Some(server_webhooks),
+    }))
+}
+
+// --- Apply ---
+
+/// A room webhook that passed validation, with its credentials sealed. `None` credentials
+/// keep the stored value.
+struct CheckedWebhook<'a> {
+    config: &'a WebhookConfig,
+    url: String,
+    events: String,
+    headers: Option<Option<String>>,
+    client_cert: Option<String>,
+    ca_cert: Option<String>,
+}
+
+/// A room whose document passed validation, normalized for storage.
+struct CheckedRoom<'a> {
+    config: &'a RoomConfig,
+    name: String,
+    tags: Vec<String>,
+    language: Option<String>,
+    tokenizer: &'static str,
+    color: Option<String>,
+    allowed_reactions: Vec<String>,
+    welcome: Option<(&'a str, &'a str, &'a str)>,
+    upload_policy: Option<[Vec<String>; 4]>,
+    quiet_hours: Option<(NaiveTime, NaiveTime, Tz)>,
+    webhooks: Vec<CheckedWebhook<'a>>,
+    visibility: String,
+}
+
+fn check_range(field: &str, value: Option<i64>, range: std::ops::RangeInclusive<i64>) -> Result<(), ApiError> {
+    match value {
+        Some(v) if !range.contains(&v) => Err(err(
+            Status::BadRequest,
+            &format!("{field} must be between {} and {}", range.start(), range.end()),
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn check_room<'a>(secrets: &SecretBox, room: &'a RoomConfig) -> Result<CheckedRoom<'a>, ApiError> {
+    let bad = |e: String| err(Status::BadRequest, &e);
+    let tags = normalize_tags(&room.tags).map_err(bad)?;
+    let language = room.language.as_deref().map(str::trim).filter(|l| !l.is_empty());
+    let tokenizer = match language {
+        Some(lang) => crate::db::search_tokenizer_for_language(lang)
+            .ok_or_else(|| err(Status::BadRequest, "language must be a language code like de, ja or pt-BR"))?,
+        None => "porter",
+    };
+    let color = match room.color.as_deref() {
+        Some(c) => Some(normalize_color(c).ok_or_else(|| err(Status::BadRequest, "color must be #rgb or #rrggbb"))?),
+        None => None,
+    };
+    let allowed_reactions = normalize_allowed_reactions(&room.allowed_reactions).map_err(bad)?;
+    let retention = &room.retention;
+    check_range("max_messages", retention.max_messages, MAX_MESSAGES_RANGE)?;
+    check_range("max_message_age_hours", retention.max_message_age_hours, MESSAGE_AGE_HOURS_RANGE)?;
+    check_range("retention_notice_secs", retention.retention_notice_secs, RETENTION_NOTICE_RANGE)?;
+    check_range("file_ttl_secs", retention.file_ttl_secs, FILE_TTL_RANGE)?;
+
+    let welcome = room.welcome.as_ref().map(super::welcome::validate).transpose()?;
+    let upload_policy = room.upload_policy.as_ref().map(super::upload_policy::validate).transpose()?;
+    let quiet_hours = room.quiet_hours.as_ref().map(super::quiet_hours::validate).transpose()?;
+
+    let mut webhooks: Vec<CheckedWebhook> = Vec::new();
+    for hook in &room.webhooks {
+        let (url, events) = validate_target(&hook.url, &hook.events, ROOM_EVENTS)?;
+        if webhooks.iter().any(|w| w.url == url) {
+            return Err(err(Status::BadRequest, &format!("webhook {url} is listed more than once")));
+        }
+        let headers = hook.headers.as_ref().map(|h| seal_headers(secrets, h)).transpose()?;
+        let (client_cert, ca_cert) = check_tls(hook.client_cert.as_deref(), hook.ca_cert.as_deref())?;
+        webhooks.push(CheckedWebhook {
+            config: hook,
+            url,
+            events,
+            headers,
+            client_cert: client_cert.map(|pem| secrets.seal(&pem)),
+            ca_cert,
+        });
+    }
+    let mut hook_names: Vec<&str> = Vec::new();
+    for hook in &room.incoming_webhooks {
+        let name = hook.name.trim();
+        if name.is_empty() || name.len() > 100 {
+            return Err(err(Status::BadRequest, "incoming webhook names must be 1-100 characters"));
+        }
+        if hook_names.contains(&name) {
+            return Err(err(Status::BadRequest, &format!("incoming webhook '{name}' is listed more than once")));
+        }
+        hook_names.push(name);
+        super::incoming_hooks::validate_limits(hook.rate_limit, hook.daily_quota)?;
+        if let Some(token) = hook.token.as_deref() {
+            let valid = (16..=128).contains(&token.len())
+                && token.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+            if !valid {
+                return Err(err(
+                    Status::BadRequest,
+                    &format!("incoming webhook '{name}': token must be 16-128 letters, digits, '_' or '-'"),
+                ));
+            }
+        }
+    }
+
+    let visibility = normalize_visibility(&room.visibility).map_err(bad)?;
+    let mut member_names: Vec<&str> = Vec::new();
+    for member in &room.members {
+        let sender = member.sender.trim();
+        if sender.is_empty() || sender.len() > 100 {
+            return Err(err(Status::BadRequest, "member senders must be 1-100 characters"));
+        }
+        if member_names.contains(&sender) {
+            return Err(err(Status::BadRequest, &format!("member '{sender}' is listed more than once")));
+        }
+        member_names.push(sender);
+        if let Some(token) = member.token.as_deref() {
+            let valid = (16..=128).contains(&token.len())
+                && token.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+            if !valid {
+                return Err(err(
+                    Status::BadRequest,
+                    &format!("member '{sender}': token must be 16-128 letters, digits, '_' or '-'"),
+                ));
+            }
+        }
+    }
+
+    Ok(CheckedRoom {
+        config: room,
+        name: room.name.trim().to_string(),
+        tags,
+        language: language.map(String::from),
+        tokenizer,
+        color,
+        allowed_reactions,
+        welcome,
+        upload_policy,
+        quiet_hours,
+        webhooks,
+        visibility,
+    })
+}
+
+/// Create the room if needed and set it to the document's state. Returns its id, its admin key
+/// if it was created, and whether its search tokenizer changed.
+fn apply_room(conn: &Connection, room: &CheckedRoom) -> Result<(String, Option<String>, bool), ApiError> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let existing: Option<(String, String, Option<String>)> = conn
+        .query_row(
+            "SELECT id, search_tokenizer, archived_at FROM rooms WHERE name = ?1 AND COALESCE(room_type, 'room') != 'dm'",
+            params![&room.name],
+            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+        )
+        .ok();
+    let (room_id, admin_key, retokenized, archived_at) = match existing {
+        Some((id, old_tokenizer, archived_at)) => (id, None, old_tokenizer != room.tokenizer, archived_at),
+        None => {
+            let id = uuid::Uuid::new_v4().to_string();
+            let admin_key = crate::db::generate_admin_key();
+            conn.execute(
+                "INSERT INTO rooms (id, name, created_by, created_at, updated_at, admin_key) VALUES (?1, ?2, ?3, ?4, ?4, ?5)",
+                params![&id, &room.name, CONFIG_AUTHOR, &now, &admin_key],
+            )
+            .map_err(|e| match e.to_string().contains("UNIQUE") {
+                true => err(Status::Conflict, "A room with that name already exists"),
+                false => internal(e),
+            })?;
+            // A live room now owns this name; it no longer redirects to a renamed/merged room
+            conn.execute("DELETE FROM room_name_aliases WHERE name = ?1", params![&room.name])
+                .map_err(internal)?;
+            (id, Some(admin_key), false, None)
+        }
+    };
+
+    let config = room.config;
+    let icon = config
+        .icon
+        .as_deref()
+        .map(|icon| resolve_icon(conn, &room_id, icon))
+        .transpose()
+        .map_err(|e| err(Status::BadRequest, &e))?;
+    let archived_at = match (config.archived, archived_at) {
+        (true, Some(since)) => Some(since),
+        (true, None) => Some(now.clone()),
+        (false, _) => None,
+    };
+    conn.execute(
+        "UPDATE rooms SET description = ?2, tags = ?3, language = ?4, search_tokenizer = ?5, icon = ?6, color = ?7,
+             archived_at = ?8, allowed_reactions = ?9, max_messages = ?10, max_message_age_hours = ?11,
+             retention_notice_secs = ?12, file_ttl_secs = ?13, updated_at = ?14, visibility = ?15
+         WHERE id = ?1",
+        params![
+            &room_id,
+            &config.description,
+            serde_json::to_string(&room.tags).unwrap_or_else(|_| "[]".to_string()),
+            &room.language,
+            room.tokenizer,
+            icon,
+            &room.color,
+            archived_at,
+            allowed_reactions_column(&room.allowed_reactions),
+            config.retention.max_messages,
+            config.retention.max_message_age_hours,
+            config.retention.retention_notice_secs,
+            config.retention.file_ttl_secs,
+            &now,
+            &room.visibility
+        ],
+    )
+    .map_err(internal)?;
+
+    match room.welcome {
+        Some((template, delivery, from)) => super::welcome::save(conn, &room_id, template, delivery, from).map_err(internal)?,
+        None => {
+            conn.execute("DELETE FROM room_welcomes WHERE room_id = ?1", params![&room_id])
+                .map_err(internal)?;
+        }
+    }
+    match (&room.upload_policy, &config.upload_policy) {
+        (Some(lists), Some(policy)) => {
+            super::upload_policy::save(conn, &room_id, lists, policy.verify_content_type).map_err(internal)?
+        }
+        _ => {
+            conn.execute("DELETE FROM room_upload_policies WHERE room_id = ?1", params![&room_id])
+                .map_err(internal)?;
+        }
+    }
+    match room.quiet_hours {
+        Some((start, end, tz)) => super::quiet_hours::save(conn, &room_id, start, end, tz).map_err(internal)?,
+        None => {
+            conn.execute("DELETE FROM room_quiet_hours WHERE room_id = ?1", params![&room_id])
+                .map_err(internal)?;
+        }
+    }
+
+    apply_webhooks(conn, &room_id, &room.webhooks)?;
+    apply_incoming_webhooks(conn, &room_id, &config.incoming_webhooks)?;
+    apply_members(conn, &room_id, &config.members)?;
+    Ok((room_id, admin_key, retokenized))
+}
+
+/// Bring a room's webhooks in line with the document, matching by URL.
+fn apply_webhooks(conn: &Connection, room_id: &str, hooks: &[CheckedWebhook]) -> Result<(), ApiError> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let urls: Vec<&str> = hooks.iter().map(|h| h.url.as_str()).collect();
+    let existing: Vec<(String, String)> = conn
+        .prepare("SELECT id, url FROM webhooks WHERE room_id = ?1")
+        .and_then(|mut s| s.query_map(params![room_id], |r| Ok((r.get(0)?, r.get(1)?))).map(|rows| rows.filter_map(|r| r.ok()).collect()))
+        .map_err(internal)?;
+    for (id, url) in &existing {
+        if !urls.contains(&url.as_str()) {
+            conn.execute("DELETE FROM webhooks WHERE id = ?1", params![id]).map_err(internal)?;
+        }
+    }
+    for hook in hooks {
+        match existing.iter().find(|(_, url)| *url == hook.url) {
+            Some((id, _)) => {
+                conn.execute(
+                    "UPDATE webhooks SET events = ?2, active = ?3, ordered = ?4, ca_cert = ?5,
+                         secret = COALESCE(?6, secret),
+                         headers = CASE WHEN ?7 THEN ?8 ELSE headers END,
+                         client_cert = COALESCE(?9, client_cert)
+                     WHERE id = ?1",
+                    params![
+                        id,
+                        &hook.events,
+                        hook.config.active,
+                        hook.config.ordered,
+                        &hook.ca_cert,
+                        &hook.config.secret,
+                        hook.headers.is_some(),
+                        hook.headers.clone().flatten(),
+                        &hook.client_cert
+                    ],
+                )
+                .map_err(internal)?;
+            }
+            None => {
+                conn.execute(
+                    "INSERT INTO webhooks (id, room_id, url, events, secret, created_by, created_at, active, headers, client_cert, ca_cert, ordered)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                    params![
+                        uuid::Uuid::new_v4().to_string(),
+                        room_id,
+                        &hook.url,
+                        &hook.events,
+                        &hook.config.secret,
+                        CONFIG_AUTHOR,
+                        &now,
+                        hook.config.active,
+                        hook.headers.clone().flatten(),
+                        &hook.client_cert,
+                        &hook.ca_cert,
+                        hook.config.ordered
+                    ],
+                )
+                .map_err(internal)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Bring a room's incoming webhooks in line with the document, matching by name.
+fn apply_incoming_webhooks(conn: &Connection, room_id: &str, hooks: &[IncomingWebhookConfig]) -> Result<(), ApiError> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let names: Vec<&str> = hooks.iter().map(|h| h.name.trim()).collect();
+    let existing: Vec<(String, String)> = conn
+        .prepare("SELECT id, name FROM incoming_webhooks WHERE room_id = ?1")
+        .and_then(|mut s| s.query_map(params![room_id], |r| Ok((r.get(0)?, r.get(1)?))).map(|rows| rows.filter_map(|r| r.ok()).collect()))
+        .map_err(internal)?;
+    for (id, name) in &existing {
+        if !names.contains(&name.as_str()) {
+            conn.execute("DELETE FROM incoming_webhooks WHERE id = ?1", params![id]).map_err(internal)?;
+        }
+    }
+    let token_taken = |e: rusqlite::Error| match e.to_string().contains("UNIQUE") {
+        true => err(Status::Conflict, "An incoming webhook token is already used by another hook"),
+        false => internal(e),
+    };
+    for hook in hooks {
+        let name = hook.name.trim();
+        match existing.iter().find(|(_, n)| n == name) {
+            Some((id, _)) => {
+                conn.execute(
+                    "UPDATE incoming_webhooks SET active = ?2, rate_limit = ?3, daily_quota = ?4, token = COALESCE(?5, token) WHERE id = ?1",
+                    params![id, hook.active, hook.rate_limit, hook.daily_quota, &hook.token],
+                )
+                .map_err(token_taken)?;
+            }
+            None => {
+                conn.execute(
+                    "INSERT INTO incoming_webhooks (id, room_id, name, token, created_by, created_at, active, rate_limit, daily_quota)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                    params![
+                        uuid::Uuid::new_v4().to_string(),
+                        room_id,
+                        name,
+                        hook.token.clone().unwrap_or_else(crate::db::generate_webhook_token),
+                        CONFIG_AUTHOR,
+                        &now,
+                        hook.active,
+                        hook.rate_limit,
+                        hook.daily_quota
+                    ],
+                )
+                .map_err(token_taken)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Bring a room's members in line with the document, matching by (canonical) sender.
+fn apply_members(conn: &Connection, room_id: &str, members: &[RoomMemberConfig]) -> Result<(), ApiError> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let senders: Vec<String> = members.iter().map(|m| crate::db::resolve_sender(conn, m.sender.trim())).collect();
+    let existing: Vec<String> = conn
+        .prepare("SELECT sender FROM room_members WHERE room_id = ?1")
+        .and_then(|mut s| s.query_map(params![room_id], |r| r.get(0)).map(|rows| rows.filter_map(|r| r.ok()).collect()))
+        .map_err(internal)?;
+    for sender in &existing {
+        if !senders.contains(sender) {
+            conn.execute(
+                "DELETE FROM room_members WHERE room_id = ?1 AND sender = ?2",
+                params![room_id, sender],
+            )
+            .map_err(internal)?;
+        }
+    }
+    let token_taken = |e: rusqlite::Error| match e.to_string().contains("UNIQUE") {
+        true => err(Status::Conflict, "A member token is already used by another membership"),
+        false => internal(e),
+    };
+    for (i, (sender, member)) in senders.iter().zip(members).enumerate() {
+        if senders[..i].contains(sender) {
+            continue; // two aliases of one sender
+        }
+        if existing.contains(sender) {
+            conn.execute(
+                "UPDATE room_members SET added_by = ?3, token = COALESCE(?4, token) WHERE room_id = ?1 AND sender = ?2",
+                params![room_id, sender, &member.added_by, &member.token],
+            )
+            .map_err(token_taken)?;
+        } else {
+            conn.execute(
+                "INSERT INTO room_members (room_id, sender, token, added_by, added_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    room_id,
+                    sender,
+                    member.token.clone().unwrap_or_else(crate::membership::generate_member_token),
+                    &member.added_by,
+                    &now
+                ],
+            )
+            .map_err(token_taken)?;
+        }
+    }
+    Ok(())
+}
+
+/// Replace the server-level webhooks with the document's, matching by URL.
+fn apply_server_webhooks(conn: &Connection, hooks: &[(String, String, &ServerWebhookConfig)]) -> Result<(), ApiError> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let existing: Vec<(String, String)> = conn
+        .prepare("SELECT id, url FROM server_webhooks")
+        .and_then(|mut s| s.query_map([], |r| Ok((r.get(0)?, r.get(1)?))).map(|rows| rows.filter_map(|r| r.ok()).collect()))
+        .map_err(internal)?;
+    for (id, url) in &existing {
+        if !hooks.iter().any(|(u, _, _)| u == url) {
+            conn.execute("DELETE FROM server_webhooks WHERE id = ?1", params![id]).map_err(internal)?;
+        }
+    }
+    for (url, events, hook) in hooks {
+        let written = match existing.iter().find(|(_, u)| u == url) {
+            Some((id, _)) => conn.execute(
+                "UPDATE server_webhooks SET events = ?2, active = ?3, secret = COALESCE(?4, secret) WHERE id = ?1",
+                params![id, events, hook.active, &hook.secret],
+            ),
+            None => conn.execute(
+                "INSERT INTO server_webhooks (id, url, events, secret, created_by, created_at, active) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![uuid::Uuid::new_v4().to_string(), url, events, &hook.secret, CONFIG_AUTHOR, &now, hook.active],
+            ),
+        };
+        written.map_err(internal)?;
+    }
+    Ok(())
+}
+
+/// PUT /api/v1/admin/config/export — apply a configuration document (server token required).
+/// Listed rooms are created or set to the document's state; unlisted rooms are left alone.
+/// Everything is validated first and applied in one transaction, so a bad document changes
+/// nothing. Returns what changed, with the admin keys of created rooms.
+#[put("/api/v1/admin/config/export", format = "json", data = "<body>")]
+pub fn import_server_config(
+    db: ScopedDb<'_>,
+    events: Events<'_>,
+    secrets: &State<SecretBox>,
+    sender_policy: &State<SenderPolicy>,
+    server_token: ServerToken,
+    body: Json<ServerConfig>,
+) -> Result<Json<ConfigApplyResult>, ApiError> {
+    sender_policy.check_server_token(&server_token)?;
+    if body.version != CONFIG_VERSION {
+        return Err(err(
+            Status::BadRequest,
+            &format!("Unsupported config version {} (this server reads version {CONFIG_VERSION})", body.version),
+        ));
+    }
+
+    let mut rooms: Vec<CheckedRoom> = Vec::new();
+    for room in &body.rooms {
+        let name = room.name.trim();
+        if name.is_empty() || name.len() > 100 {
+            return Err(err(Status::BadRequest, "Room names must be 1-100 characters"));
+        }
+        if rooms.iter().any(|r| r.name == name) {
+            return Err(err(Status::BadRequest, &format!("Room '{name}' is listed more than once")));
+        }
+        rooms.push(check_room(secrets, room).map_err(|e| in_room(name, e))?);
+    }
+    let server_webhooks = body
+        .server_webhooks
+        .as_ref()
+        .map(|hooks| {
+            let mut checked: Vec<(String, String, &ServerWebhookConfig)> = Vec::new();
+            for hook in hooks {
+                let (url, events) = validate_target(&hook.url, &hook.events, &crate::webhooks::LIFECYCLE_EVENTS)?;
+                if checked.iter().any(|(u, _, _)| *u == url) {
+                    return Err(err(Status::BadRequest, &format!("Server webhook {url} is listed more than once")));
+                }
+                checked.push((url, events, hook));
+            }
+            Ok(checked)
+        })
+        .transpose()?;
+
+    let conn = db.conn();
+    let tx = conn.unchecked_transaction().map_err(internal)?;
+    let mut result = ConfigApplyResult {
+        rooms_created: Vec::new(),
+        rooms_updated: Vec::new(),
+        admin_keys: BTreeMap::new(),
+        webhooks: 0,
+        incoming_webhooks: 0,
+        server_webhooks: server_webhooks.as_ref().map(Vec::len),
+    };
+    let mut applied: Vec<(String, bool, bool)> = Vec::new();
+    for room in &rooms {
+        let (room_id, admin_key, retokenized) = apply_room(&tx, room).map_err(|e| in_room(&room.name, e))?;
+        result.webhooks += room.webhooks.len();
+        result.incoming_webhooks += room.config.incoming_webhooks.len();
+        applied.push((room_id, admin_key.is_some(), retokenized));
+        match admin_key {
+            Some(key) => {
+                result.rooms_created.push(room.name.clone());
+                result.admin_keys.insert(room.name.clone(), key);
+            }
+            None => result.rooms_updated.push(room.name.clone()),
+        }
+    }
+    if let Some(ref hooks) = server_webhooks {
+        apply_server_webhooks(&tx, hooks)?;
+    }
+    tx.commit().map_err(internal)?;
+
+    for (room_id, created, retokenized) in applied {
+        // A new tokenizer means the room's messages belong in a different FTS table
+        if retokenized
+            && let Err(e) = crate::db::reindex_room_fts(&conn, &room_id) {
+            eprintln!("⚠️ Failed to reindex room {room_id} for search: {e}");
+        }
+        if let Ok(room) = super::rooms::fetch_room_with_stats(&conn, &room_id) {
+            events.publish(if created { ChatEvent::RoomCreated(room) } else { ChatEvent::RoomUpdated(room) });
+        }
+    }
+    Ok(Json(result))
+}
@@ -0,0 +1,121 @@
+use crate::namespaces::ScopedDb;
+use crate::models::{CreateServerWebhook, ListOf, ServerWebhook};
+use crate::senders::{SenderPolicy, ServerToken};
+use crate::webhooks::LIFECYCLE_EVENTS;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{delete, get, post, State};
+use rusqlite::{params, Connection};
+
+fn err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+fn webhook_from_row(r: &rusqlite::Row) -> rusqlite::Result<ServerWebhook> {
+    Ok(ServerWebhook {
+        id: r.get(0)?,
+        url: r.get(1)?,
+        events: r.get(2)?,
+        has_secret: r.get::<_, Option<String>>(3)?.is_some(),
+        created_by: r.get(4)?,
+        created_at: r.get(5)?,
+        active: r.get::<_, i32>(6)? != 0,
+        last_delivery_at: r.get(7)?,
+        last_status: r.get(8)?,
+        last_error: r.get(9)?,
+    })
+}
+
+const WEBHOOK_COLUMNS: &str =
+    "id, url, events, secret, created_by, created_at, active, last_delivery_at, last_status, last_error";
+
+fn load_webhook(conn: &Connection, id: &str) -> Option<ServerWebhook> {
+    conn.query_row(
+        &format!("SELECT {WEBHOOK_COLUMNS} FROM server_webhooks WHERE id = ?1"),
+        params![id],
+        webhook_from_row,
+    )
+    .ok()
+}
+
+/// POST /api/v1/admin/webhooks — register a server-level webhook for room lifecycle events
+/// (server token required)
+#[post("/api/v1/admin/webhooks", format = "json", data = "<body>")]
+pub fn create_server_webhook(
+    db: ScopedDb<'_>,
+    sender_policy: &State<SenderPolicy>,
+    server_token: ServerToken,
+    body: Json<CreateServerWebhook>,
+) -> Result<Json<ServerWebhook>, (Status, Json<serde_json::Value>)> {
+    sender_policy.check_server_token(&server_token)?;
+
+    let url = body.url.trim().to_string();
+    if url.is_empty() || (!url.starts_with("http://") && !url.starts_with("https://")) {
+        return Err(err(Status::BadRequest, "Invalid webhook URL: must start with http:// or https://"));
+    }
+    let events = body.events.trim().to_string();
+    if events.is_empty() {
+        return Err(err(Status::BadRequest, "Events filter cannot be empty. Use '*' for all events."));
+    }
+    if events != "*" {
+        for ev in events.split(',').map(|s| s.trim()) {
+            if !LIFECYCLE_EVENTS.contains(&ev) {
+                return Err(err(
+                    Status::BadRequest,
+                    &format!("Unknown event type: '{}'. Valid events: {}", ev, LIFECYCLE_EVENTS.join(", ")),
+                ));
+            }
+        }
+    }
+
+    let conn = db.conn();
+    let id = uuid::Uuid::new_v4().to_string();
+    let now = chrono::Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO server_webhooks (id, url, events, secret, created_by, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![&id, &url, &events, &body.secret, &body.created_by, &now],
+    )
+    .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+    load_webhook(&conn, &id)
+        .map(Json)
+        .ok_or_else(|| err(Status::InternalServerError, "Internal server error"))
+}
+
+/// GET /api/v1/admin/webhooks — server-level webhooks, newest first (server token required)
+#[get("/api/v1/admin/webhooks?<envelope>")]
+pub fn list_server_webhooks(
+    db: ScopedDb<'_>,
+    sender_policy: &State<SenderPolicy>,
+    server_token: ServerToken,
+    envelope: Option<bool>,
+) -> Result<Json<ListOf<ServerWebhook>>, (Status, Json<serde_json::Value>)> {
+    sender_policy.check_server_token(&server_token)?;
+    let conn = db.conn();
+    let webhooks = conn
+        .prepare(&format!("SELECT {WEBHOOK_COLUMNS} FROM server_webhooks ORDER BY created_at DESC"))
+        .and_then(|mut s| {
+            s.query_map([], webhook_from_row)
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .unwrap_or_default();
+    Ok(Json(ListOf::complete(webhooks, envelope)))
+}
+
+/// DELETE /api/v1/admin/webhooks/<webhook_id> — remove a server-level webhook (server token required)
+#[delete("/api/v1/admin/webhooks/<webhook_id>")]
+pub fn delete_server_webhook(
+    db: ScopedDb<'_>,
+    sender_policy: &State<SenderPolicy>,
+    server_token: ServerToken,
+    webhook_id: &str,
+) -> Result<Json<serde_json::Value>, (Status, Json<serde_json::Value>)> {
+    sender_policy.check_server_token(&server_token)?;
+    let conn = db.conn();
+    let deleted = conn
+        .execute("DELETE FROM server_webhooks WHERE id = ?1", params![webhook_id])
+        .unwrap_or(0);
+    if deleted == 0 {
+        return Err(err(Status::NotFound, "Webhook not found"));
+    }
+    Ok(Json(serde_json::json!({"deleted": true})))
+}
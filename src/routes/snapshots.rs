@@ -0,0 +1,197 @@
+use crate::namespaces::ScopedDb;
+use crate::events::{ChatEvent, Events};
+use crate::models::{RoomSnapshot, SetSnapshotSchedule, SnapshotSchedule, SnapshotsResponse};
+use crate::snapshots::{snapshot_dir, take_snapshot, Cron, DEFAULT_KEEP};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{delete, get, post, put};
+use rusqlite::{params, Connection};
+
+use super::AdminKey;
+
+/// Most snapshots a schedule may keep.
+const MAX_KEEP: i64 = 100;
+
+fn err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+fn check_admin(conn: &Connection, room_id: &str, admin: &AdminKey) -> Result<(), (Status, Json<serde_json::Value>)> {
+    let key: Option<String> = conn
+        .query_row("SELECT admin_key FROM rooms WHERE id = ?1", params![room_id], |r| r.get(0))
+        .map_err(|_| err(Status::NotFound, "Room not found"))?;
+    if key.as_deref() != Some(admin.0.as_str()) {
+        return Err(err(Status::Forbidden, "Invalid admin key for this room"));
+    }
+    Ok(())
+}
+
+fn load_schedule(conn: &Connection, room_id: &str) -> Option<SnapshotSchedule> {
+    conn.query_row(
+        "SELECT room_id, cron, destination, keep, last_run_at, next_run_at, created_at
+         FROM room_snapshot_schedules WHERE room_id = ?1",
+        params![room_id],
+        |r| {
+            Ok(SnapshotSchedule {
+                room_id: r.get(0)?,
+                cron: r.get(1)?,
+                destination: r.get(2)?,
+                keep: r.get(3)?,
+                last_run_at: r.get(4)?,
+                next_run_at: r.get(5)?,
+                created_at: r.get(6)?,
+            })
+        },
+    )
+    .ok()
+}
+
+/// GET /api/v1/rooms/<room_id>/snapshots — the room's snapshots (newest first) and its schedule.
+#[get("/api/v1/rooms/<room_id>/snapshots")]
+pub fn list_snapshots(
+    db: ScopedDb<'_>,
+    room_id: &str,
+) -> Result<Json<SnapshotsResponse>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    let exists: bool = conn
+        .query_row("SELECT COUNT(*) FROM rooms WHERE id = ?1", params![room_id], |r| r.get::<_, i64>(0))
+        .map(|c| c > 0)
+        .unwrap_or(false);
+    if !exists {
+        return Err(err(Status::NotFound, "Room not found"));
+    }
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, room_id, taken_at, trigger, message_count, first_seq, last_seq, size, sha256, file_id, path
+             FROM room_snapshots WHERE room_id = ?1 ORDER BY taken_at DESC",
+        )
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+    let snapshots: Vec<RoomSnapshot> = stmt
+        .query_map(params![room_id], |r| {
+            let file_id: Option<String> = r.get(9)?;
+            Ok(RoomSnapshot {
+                id: r.get(0)?,
+                room_id: r.get(1)?,
+                taken_at: r.get(2)?,
+                trigger: r.get(3)?,
+                message_count: r.get(4)?,
+                first_seq: r.get(5)?,
+                last_seq: r.get(6)?,
+                size: r.get(7)?,
+                sha256: r.get(8)?,
+                url: file_id.as_ref().map(|f| format!("/api/v1/files/{f}")),
+                file_id,
+                path: r.get(10)?,
+            })
+        })
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(Json(SnapshotsResponse {
+        room_id: room_id.to_string(),
+        schedule: load_schedule(&conn, room_id),
+        snapshots,
+    }))
+}
+
+/// POST /api/v1/rooms/<room_id>/snapshots — take a snapshot now (admin key), to the schedule's
+/// destination if one is configured, otherwise the room's file store.
+#[post("/api/v1/rooms/<room_id>/snapshots")]
+pub fn create_snapshot(
+    db: ScopedDb<'_>,
+    events: Events<'_>,
+    room_id: &str,
+    admin: AdminKey,
+) -> Result<Json<RoomSnapshot>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    check_admin(&conn, room_id, &admin)?;
+    let destination = load_schedule(&conn, room_id)
+        .map(|s| s.destination)
+        .unwrap_or_else(|| "files".to_string());
+    let (snapshot, file) = take_snapshot(&conn, room_id, &destination, "manual").map_err(|e| {
+        eprintln!("⚠️ Snapshot of room {room_id} failed: {e}");
+        err(Status::InternalServerError, "Snapshot failed")
+    })?;
+    if let Some(file) = file {
+        events.publish(ChatEvent::FileUploaded(file));
+    }
+    Ok(Json(snapshot))
+}
+
+/// GET /api/v1/rooms/<room_id>/snapshot-schedule — the room's snapshot schedule (404 if none).
+#[get("/api/v1/rooms/<room_id>/snapshot-schedule")]
+pub fn get_snapshot_schedule(
+    db: ScopedDb<'_>,
+    room_id: &str,
+) -> Result<Json<SnapshotSchedule>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    load_schedule(&conn, room_id)
+        .map(Json)
+        .ok_or_else(|| err(Status::NotFound, "No snapshot schedule configured for this room"))
+}
+
+/// PUT /api/v1/rooms/<room_id>/snapshot-schedule — set or replace the schedule (admin key).
+#[put("/api/v1/rooms/<room_id>/snapshot-schedule", format = "json", data = "<body>")]
+pub fn set_snapshot_schedule(
+    db: ScopedDb<'_>,
+    room_id: &str,
+    admin: AdminKey,
+    body: Json<SetSnapshotSchedule>,
+) -> Result<Json<SnapshotSchedule>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    check_admin(&conn, room_id, &admin)?;
+
+    let cron_expr = body.cron.trim();
+    let cron = Cron::parse(cron_expr).map_err(|e| err(Status::BadRequest, &format!("Invalid cron: {e}")))?;
+    let now = chrono::Utc::now();
+    let next_run_at = cron
+        .next_after(now)
+        .ok_or_else(|| err(Status::BadRequest, "Invalid cron: the schedule never runs"))?
+        .to_rfc3339();
+    let destination = body.destination.as_deref().unwrap_or("files");
+    match destination {
+        "files" => {}
+        "directory" if snapshot_dir().is_some() => {}
+        "directory" => {
+            return Err(err(Status::BadRequest, "destination 'directory' requires SNAPSHOT_DIR on the server"));
+        }
+        _ => return Err(err(Status::BadRequest, "destination must be 'files' or 'directory'")),
+    }
+    let keep = body.keep.unwrap_or(DEFAULT_KEEP);
+    if !(1..=MAX_KEEP).contains(&keep) {
+        return Err(err(Status::BadRequest, &format!("keep must be between 1 and {MAX_KEEP}")));
+    }
+
+    conn.execute(
+        "INSERT INTO room_snapshot_schedules (room_id, cron, destination, keep, next_run_at, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT(room_id) DO UPDATE SET cron = ?2, destination = ?3, keep = ?4, next_run_at = ?5",
+        params![room_id, cron_expr, destination, keep, &next_run_at, now.to_rfc3339()],
+    )
+    .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+
+    load_schedule(&conn, room_id)
+        .map(Json)
+        .ok_or_else(|| err(Status::InternalServerError, "Internal server error"))
+}
+
+/// DELETE /api/v1/rooms/<room_id>/snapshot-schedule — stop scheduled snapshots (admin key).
+/// Existing snapshots are kept.
+#[delete("/api/v1/rooms/<room_id>/snapshot-schedule")]
+pub fn delete_snapshot_schedule(
+    db: ScopedDb<'_>,
+    room_id: &str,
+    admin: AdminKey,
+) -> Result<Json<serde_json::Value>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    check_admin(&conn, room_id, &admin)?;
+    let deleted = conn
+        .execute("DELETE FROM room_snapshot_schedules WHERE room_id = ?1", params![room_id])
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+    if deleted == 0 {
+        return Err(err(Status::NotFound, "No snapshot schedule configured for this room"));
+    }
+    Ok(Json(serde_json::json!({"deleted": true, "room_id": room_id})))
+}
@@ -0,0 +1,185 @@
+use crate::namespaces::ScopedDb;
+use crate::events::{ChatEvent, Events};
+use crate::models::{ListOf, SenderStatus, UpdateSenderStatus};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{delete, get, put};
+use rusqlite::{params, Connection};
+
+fn err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+/// Updates kept per sender; older ones are pruned on write.
+const HISTORY_LIMIT: i64 = 100;
+
+const STATUS_COLUMNS: &str = "sender, state, task, progress, eta, details, created_at";
+
+fn status_from_row(r: &rusqlite::Row) -> rusqlite::Result<SenderStatus> {
+    let details: String = r.get(5)?;
+    Ok(SenderStatus {
+        sender: r.get(0)?,
+        state: r.get(1)?,
+        task: r.get(2)?,
+        progress: r.get(3)?,
+        eta: r.get(4)?,
+        details: serde_json::from_str(&details).unwrap_or(serde_json::json!({})),
+        updated_at: r.get(6)?,
+    })
+}
+
+fn current_status(conn: &Connection, sender: &str) -> Option<SenderStatus> {
+    conn.query_row(
+        &format!("SELECT {STATUS_COLUMNS} FROM sender_status WHERE sender = ?1 ORDER BY id DESC LIMIT 1"),
+        params![sender],
+        status_from_row,
+    )
+    .ok()
+}
+
+fn non_empty(value: &Option<String>) -> Option<String> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(str::to_string)
+}
+
+/// PUT /api/v1/status/<sender> — publish the sender's current status. Each update is a full
+/// snapshot (omitted fields are cleared, not merged) and is kept in the sender's history.
+#[put("/api/v1/status/<sender>", format = "json", data = "<body>")]
+pub fn update_status(
+    sender: &str,
+    body: Json<UpdateSenderStatus>,
+    db: ScopedDb<'_>,
+    events: Events<'_>,
+) -> Result<Json<SenderStatus>, (Status, Json<serde_json::Value>)> {
+    let sender = sender.trim();
+    if sender.is_empty() || sender.len() > 100 {
+        return Err(err(Status::BadRequest, "Sender must be 1-100 characters"));
+    }
+    let state = non_empty(&body.state);
+    if state.as_ref().is_some_and(|s| s.len() > 50) {
+        return Err(err(Status::BadRequest, "state must be at most 50 characters"));
+    }
+    let task = non_empty(&body.task);
+    if task.as_ref().is_some_and(|t| t.len() > 500) {
+        return Err(err(Status::BadRequest, "task must be at most 500 characters"));
+    }
+    if let Some(progress) = body.progress
+        && !(0.0..=100.0).contains(&progress)
+    {
+        return Err(err(Status::BadRequest, "progress must be between 0 and 100"));
+    }
+    let eta = match non_empty(&body.eta) {
+        None => None,
+        Some(eta) => match chrono::DateTime::parse_from_rfc3339(&eta) {
+            Ok(t) => Some(t.with_timezone(&chrono::Utc).to_rfc3339()),
+            Err(_) => return Err(err(Status::BadRequest, "eta must be an RFC 3339 timestamp")),
+        },
+    };
+    let details = body.details.clone().unwrap_or(serde_json::json!({}));
+    if !details.is_object() {
+        return Err(err(Status::BadRequest, "details must be a JSON object"));
+    }
+    let details_str = serde_json::to_string(&details).unwrap_or_else(|_| "{}".to_string());
+    if details_str.len() > 10_000 {
+        return Err(err(Status::BadRequest, "details must be at most 10KB when serialized"));
+    }
+
+    let conn = db.conn();
+    let sender = crate::db::resolve_sender(&conn, sender);
+    let now = chrono::Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO sender_status (sender, state, task, progress, eta, details, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![&sender, &state, &task, body.progress, &eta, &details_str, &now],
+    )
+    .map_err(|_| err(Status::InternalServerError, "Database error"))?;
+    conn.execute(
+        "DELETE FROM sender_status WHERE sender = ?1 AND id NOT IN
+           (SELECT id FROM sender_status WHERE sender = ?1 ORDER BY id DESC LIMIT ?2)",
+        params![&sender, HISTORY_LIMIT],
+    )
+    .ok();
+
+    let status = SenderStatus {
+        sender,
+        state,
+        task,
+        progress: body.progress,
+        eta,
+        details,
+        updated_at: now,
+    };
+    events.publish(ChatEvent::StatusUpdated(status.clone()));
+    Ok(Json(status))
+}
+
+/// GET /api/v1/status?state=&since= — dashboard: every sender's current status, most recently updated first
+#[get("/api/v1/status?<state>&<since>&<envelope>")]
+pub fn list_statuses(
+    state: Option<&str>,
+    since: Option<&str>,
+    envelope: Option<bool>,
+    db: ScopedDb<'_>,
+) -> Json<ListOf<SenderStatus>> {
+    let conn = db.conn();
+    let statuses: Vec<SenderStatus> = conn
+        .prepare(&format!(
+            "SELECT {STATUS_COLUMNS} FROM sender_status
+             WHERE id IN (SELECT MAX(id) FROM sender_status GROUP BY sender)
+               AND (?1 IS NULL OR state = ?1)
+               AND (?2 IS NULL OR created_at >= ?2)
+             ORDER BY id DESC"
+        ))
+        .and_then(|mut stmt| {
+            stmt.query_map(params![state, since], status_from_row)
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .unwrap_or_default();
+    Json(ListOf::complete(statuses, envelope))
+}
+
+/// GET /api/v1/status/<sender> — the sender's current status (an alias resolves to its sender)
+#[get("/api/v1/status/<sender>")]
+pub fn get_status(sender: &str, db: ScopedDb<'_>) -> Result<Json<SenderStatus>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    let sender = crate::db::resolve_sender(&conn, sender);
+    current_status(&conn, &sender)
+        .map(Json)
+        .ok_or_else(|| err(Status::NotFound, "No status for this sender"))
+}
+
+/// GET /api/v1/status/<sender>/history?limit=N — past updates, newest first
+#[get("/api/v1/status/<sender>/history?<limit>")]
+pub fn status_history(
+    sender: &str,
+    limit: Option<i64>,
+    db: ScopedDb<'_>,
+) -> Json<Vec<SenderStatus>> {
+    let conn = db.conn();
+    let sender = crate::db::resolve_sender(&conn, sender);
+    let limit = limit.unwrap_or(50).clamp(1, HISTORY_LIMIT);
+    let history = conn
+        .prepare(&format!(
+            "SELECT {STATUS_COLUMNS} FROM sender_status WHERE sender = ?1 ORDER BY id DESC LIMIT ?2"
+        ))
+        .and_then(|mut stmt| {
+            stmt.query_map(params![&sender, limit], status_from_row)
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .unwrap_or_default();
+    Json(history)
+}
+
+/// DELETE /api/v1/status/<sender> — clear the sender's status and its history
+#[delete("/api/v1/status/<sender>")]
+pub fn clear_status(sender: &str, db: ScopedDb<'_>, events: Events<'_>) -> Status {
+    let conn = db.conn();
+    let sender = crate::db::resolve_sender(&conn, sender);
+    let affected = conn
+        .execute("DELETE FROM sender_status WHERE sender = ?1", params![&sender])
+        .unwrap_or(0);
+    if affected == 0 {
+        return Status::NotFound;
+    }
+    events.publish(ChatEvent::StatusCleared { sender });
+    Status::NoContent
+}
@@ -1,57 +1,328 @@
-use crate::db::Db;
-use crate::events::{ChatEvent, EventBus};
-use crate::models::Message;
+use crate::membership::MemberAccess;
+use crate::namespaces::{Namespace, ScopedDb};
+use crate::events::{ChatEvent, EventBus, Events};
+use crate::i18n::{localize_message, Locale};
+use crate::models::{ListOf, Message, SseConnection};
+use crate::senders::{SenderPolicy, ServerToken};
+use crate::sessions::ClientSession;
+use crate::sse::{SseConnections, StreamConfig};
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome, Request};
 use rocket::response::stream::{Event, EventStream};
+use rocket::serde::json::Json;
 use rocket::{get, State};
 use rusqlite::params;
 use tokio::time::{interval, Duration};
 
 use super::{PresenceGuard, PresenceTracker};
 
-#[get("/api/v1/rooms/<room_id>/stream?<since>&<after>&<sender>&<sender_type>")]
+/// Every event name the room stream can emit (besides `heartbeat`, which is always sent).
+pub(crate) const SSE_EVENTS: &[&str] = &[
+    "message",
+    "message_edited",
+    "message_deleted",
+    "message_redacted",
+    "message_chunk",
+    "message_finalized",
+    "message_appended",
+    "message_pinned",
+    "message_unpinned",
+    "message_flagged",
+    "flag_resolved",
+    "message_labeled",
+    "topic_changed",
+    "typing",
+    "file_uploaded",
+    "file_deleted",
+    "file_expired",
+    "retention_pending",
+    "response_overdue",
+    "reaction_added",
+    "reaction_removed",
+    "presence_joined",
+    "presence_left",
+    "read_position_updated",
+    "profile_updated",
+    "profile_deleted",
+    "status_updated",
+    "status_cleared",
+    "room_updated",
+    "room_archived",
+    "room_unarchived",
+    "room_deleted",
+    "room_bookmarked",
+    "room_unbookmarked",
+    "webhook_disabled",
+    "queue_item_added",
+    "queue_item_claimed",
+    "queue_item_completed",
+    "queue_item_released",
+    "lock_acquired",
+    "lock_released",
+];
+
+/// Events whose payload is a message: with `from_sender_type`, one with no sender_type is
+/// treated as a mismatch rather than let through.
+const MESSAGE_EVENTS: &[&str] = &["message", "message_edited", "message_finalized"];
+
+/// Server-side filtering for an SSE connection, so consumers only pay for what they use.
+pub(crate) struct EventFilter {
+    events: Option<Vec<String>>,
+    exclude_senders: Vec<String>,
+    sender_type: Option<String>,
+}
+
+impl EventFilter {
+    /// `events` is comma-separated; each entry is an event name or a family prefix
+    /// (`reaction` covers `reaction_added` and `reaction_removed`).
+    pub(crate) fn parse(
+        events: Option<&str>,
+        exclude_sender: Option<&str>,
+        sender_type: Option<&str>,
+    ) -> Result<Self, (Status, Json<serde_json::Value>)> {
+        let events = match events.map(str::trim).filter(|e| !e.is_empty()) {
+            None => None,
+            Some(list) => {
+                let mut wanted = Vec::new();
+                for entry in list.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+                    let family = format!("{entry}_");
+                    let matched: Vec<String> = SSE_EVENTS
+                        .iter()
+                        .filter(|e| **e == entry || e.starts_with(&family))
+                        .map(|e| e.to_string())
+                        .collect();
+                    // An exact name stays exact: `message` doesn't pull in `message_edited`
+                    let matched = if SSE_EVENTS.contains(&entry) { vec![entry.to_string()] } else { matched };
+                    if matched.is_empty() {
+                        return Err((
+                            Status::BadRequest,
+                            Json(serde_json::json!({
+                                "error": format!("Unknown event type: '{entry}'"),
+                                "valid_events": SSE_EVENTS,
+                            })),
+                        ));
+                    }
+                    wanted.extend(matched);
+                }
+                Some(wanted)
+            }
+        };
+        let exclude_senders = exclude_sender
+            .map(|s| s.split(',').map(|n| n.trim().to_string()).filter(|n| !n.is_empty()).collect())
+            .unwrap_or_default();
+        let sender_type = sender_type.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+        Ok(EventFilter { events, exclude_senders, sender_type })
+    }
+
+    pub(crate) fn allows(&self, name: &str, payload: &serde_json::Value) -> bool {
+        if let Some(ref events) = self.events
+            && !events.iter().any(|e| e == name)
+        {
+            return false;
+        }
+        let Some(sender) = payload.get("sender").and_then(|s| s.as_str()) else {
+            return true;
+        };
+        if self.exclude_senders.iter().any(|s| s == sender) {
+            return false;
+        }
+        if let Some(ref want) = self.sender_type
+            && (payload.get("sender_type").is_some() || MESSAGE_EVENTS.contains(&name))
+        {
+            return payload.get("sender_type").and_then(|t| t.as_str()) == Some(want.as_str());
+        }
+        true
+    }
+}
+
+/// Most outbox events replayed when a connection resumes from an event id.
+const OUTBOX_REPLAY_LIMIT: i64 = 500;
+
+/// The `Last-Event-ID` header an EventSource sends when it reconnects.
+pub struct LastEventId(Option<i64>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for LastEventId {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let id = req.headers().get_one("Last-Event-ID").and_then(|v| v.trim().parse().ok());
+        Outcome::Success(LastEventId(id))
+    }
+}
+
+/// Bounds for a connection's own `heartbeat_secs`.
+const HEARTBEAT_RANGE: std::ops::RangeInclusive<u64> = 1..=300;
+
+/// A connection's keepalive interval and lifetime: its own `heartbeat_secs` and
+/// `max_lifetime_secs` where given (the lifetime can only be shortened), else the server's.
+fn connection_limits(
+    stream_config: &StreamConfig,
+    heartbeat_secs: Option<u64>,
+    max_lifetime_secs: Option<u64>,
+) -> Result<(u64, Option<u64>), (Status, Json<serde_json::Value>)> {
+    let heartbeat_secs = heartbeat_secs.unwrap_or(stream_config.heartbeat_secs);
+    if !HEARTBEAT_RANGE.contains(&heartbeat_secs) {
+        return Err((
+            Status::BadRequest,
+            Json(serde_json::json!({"error": "heartbeat_secs must be between 1 and 300"})),
+        ));
+    }
+    if max_lifetime_secs == Some(0) {
+        return Err((
+            Status::BadRequest,
+            Json(serde_json::json!({"error": "max_lifetime_secs must be at least 1"})),
+        ));
+    }
+    let lifetime_secs = match (max_lifetime_secs, stream_config.max_connection_secs) {
+        (Some(asked), 0) => Some(asked),
+        (Some(asked), max) => Some(asked.min(max)),
+        (None, 0) => None,
+        (None, max) => Some(max),
+    };
+    Ok((heartbeat_secs, lifetime_secs))
+}
+
+/// `after_event` (or the `Last-Event-ID` header) resumes from an event id: the room's message
+/// events since then are replayed from the outbox, in place of `after`/`since`. Messages posted
+/// without going through the outbox are replayed by seq alongside them, without an id.
+/// `sender`/`sender_type` register the connection's own presence. `events`, `exclude_sender`
+/// and `from_sender_type` filter what it receives. `heartbeat_secs` and `max_lifetime_secs`
+/// override the server's keepalive interval and connection lifetime for this connection
+/// (the lifetime can only be shortened).
+#[get(
+    "/api/v1/rooms/<room_id>/stream?<since>&<after>&<after_event>&<sender>&<sender_type>&<events>&<exclude_sender>&<from_sender_type>&<heartbeat_secs>&<max_lifetime_secs>"
+)]
 #[allow(clippy::too_many_arguments)]
 pub fn message_stream(
-    db: &State<Db>,
-    events: &State<EventBus>,
+    db: ScopedDb<'_>,
+    bus: &State<EventBus>,
     presence: &State<PresenceTracker>,
+    stream_config: &State<StreamConfig>,
+    connections: &State<SseConnections>,
+    publisher: Events<'_>,
+    namespace: Namespace,
+    last_event_id: LastEventId,
+    session: ClientSession,
     room_id: &str,
     since: Option<&str>,
     after: Option<i64>,
+    after_event: Option<i64>,
     sender: Option<&str>,
     sender_type: Option<&str>,
-) -> EventStream![] {
-    let mut rx = events.sender.subscribe();
+    events: Option<&str>,
+    exclude_sender: Option<&str>,
+    from_sender_type: Option<&str>,
+    heartbeat_secs: Option<u64>,
+    max_lifetime_secs: Option<u64>,
+    locale: Locale,
+) -> Result<EventStream![], (Status, Json<serde_json::Value>)> {
+    let filter = EventFilter::parse(events, exclude_sender, from_sender_type)?;
+    let (heartbeat_secs, lifetime_secs) = connection_limits(stream_config, heartbeat_secs, max_lifetime_secs)?;
+    let mut rx = bus.sender.subscribe();
     let room_id = room_id.to_string();
+    let connected_at = chrono::Utc::now();
+    let connection = connections.register(SseConnection {
+        id: 0,
+        room_id: room_id.clone(),
+        sender: sender.map(|s| s.trim().to_string()),
+        sender_type: sender_type.map(|t| t.trim().to_string()),
+        connected_at: connected_at.to_rfc3339(),
+        expires_at: lifetime_secs.map(|secs| (connected_at + chrono::Duration::seconds(secs as i64)).to_rfc3339()),
+        heartbeat_secs,
+        events: events.map(|e| e.trim().to_string()).filter(|e| !e.is_empty()),
+        events_delivered: 0,
+        last_event_at: None,
+        lagged_events: 0,
+    });
+    // Where a `gap` starts if the connection falls behind before any message is sent
+    let baseline_seq: Option<i64> = match after {
+        Some(a) => Some(a),
+        None => db
+            .conn()
+            .query_row("SELECT MAX(seq) FROM messages WHERE room_id = ?1", params![&room_id], |r| r.get(0))
+            .unwrap_or(None),
+    };
+    let db_path = db.path.clone();
 
     // Register presence if sender is provided
     let guard = sender.map(|s| {
         let s = s.trim().to_string();
         let st = sender_type.map(|v| v.trim().to_string());
-        let is_new = presence.join(&room_id, &s, st.as_deref());
+        let is_new = presence.join_session(&room_id, &s, st.as_deref(), Some(&session));
         if is_new {
-            events.publish(ChatEvent::PresenceJoined {
+            publisher.publish(ChatEvent::PresenceJoined {
                 sender: s.clone(),
                 sender_type: st.clone(),
                 room_id: room_id.clone(),
             });
+            // First-ever connection from this sender leaves a note in the transcript
+            let conn = db.conn();
+            let seen_before: bool = conn
+                .query_row(
+                    "SELECT COUNT(*) FROM messages WHERE room_id = ?1 AND kind = 'system' \
+                     AND json_extract(metadata, '$.event') = 'member_joined' AND json_extract(metadata, '$.sender') = ?2",
+                    params![&room_id, &s],
+                    |r| r.get::<_, i64>(0),
+                )
+                .map(|c| c > 0)
+                .unwrap_or(true);
+            if !seen_before
+                && let Some(note) = crate::db::insert_localized_system_message(
+                    &conn,
+                    &room_id,
+                    "member_joined",
+                    serde_json::json!({"sender": &s, "sender_type": &st}),
+                )
+            {
+                publisher.publish(ChatEvent::NewMessage(note));
+            }
+            // Joining before ever posting also counts as arriving for the welcome
+            let has_posted: bool = conn
+                .query_row(
+                    "SELECT COUNT(*) FROM messages WHERE room_id = ?1 AND sender = ?2 AND kind = 'message'",
+                    params![&room_id, &s],
+                    |r| r.get::<_, i64>(0),
+                )
+                .map(|c| c > 0)
+                .unwrap_or(true);
+            if !has_posted
+                && let Some(welcome) = super::welcome::deliver_welcome(&conn, &room_id, &s)
+            {
+                publisher.publish(ChatEvent::NewMessage(welcome));
+            }
         }
         PresenceGuard {
-            tracker: PresenceTracker {
-                inner: presence.inner.clone(),
-            },
+            tracker: presence.inner().clone(),
             room_id: room_id.clone(),
             sender: s,
-            events_sender: events.sender.clone(),
+            session: Some(session.token.clone()),
+            events_sender: bus.sender.clone(),
+            namespace: namespace.0.clone(),
         }
     });
 
+    // Resuming by event id replays the outbox instead of messages by seq
+    let resume_from = after_event.or(last_event_id.0);
+    let outbox_replay = match resume_from {
+        Some(id) => {
+            let conn = db.conn();
+            let stored = crate::outbox::room_events_after(&conn, &room_id, id, OUTBOX_REPLAY_LIMIT);
+            let missed = missed_outside_outbox(&conn, &room_id, id, &stored);
+            merge_resume(stored, missed)
+        }
+        None => Vec::new(),
+    };
+
     // Replay missed messages if `after` or `since` provided
-    let replay: Vec<Message> = if let Some(after_val) = after {
+    let replay: Vec<Message> = if resume_from.is_some() {
+        vec![]
+    } else if let Some(after_val) = after {
         // Preferred: cursor-based replay using monotonic seq
         let conn = db.conn();
         let mut stmt = conn
             .prepare(
-                "SELECT id, room_id, sender, content, metadata, created_at, edited_at, reply_to, sender_type, seq, pinned_at, pinned_by FROM messages WHERE room_id = ?1 AND seq > ?2 ORDER BY seq ASC LIMIT 100",
+                "SELECT id, room_id, sender, content, metadata, created_at, edited_at, reply_to, sender_type, seq, pinned_at, pinned_by, kind FROM messages WHERE room_id = ?1 AND seq > ?2 ORDER BY seq ASC LIMIT 100",
             )
             .ok();
         if let Some(ref mut s) = stmt {
@@ -72,6 +343,8 @@ pub fn message_stream(
                     pinned_at: row.get(10)?,
                     pinned_by: row.get(11)?,
                     edit_count: 0,
+                    kind: row.get(12)?,
+                    local_time: None,
                 })
             })
             .ok()
@@ -85,7 +358,7 @@ pub fn message_stream(
         let conn = db.conn();
         let mut stmt = conn
             .prepare(
-                "SELECT id, room_id, sender, content, metadata, created_at, edited_at, reply_to, sender_type, seq, pinned_at, pinned_by FROM messages WHERE room_id = ?1 AND created_at > ?2 ORDER BY seq ASC LIMIT 100",
+                "SELECT id, room_id, sender, content, metadata, created_at, edited_at, reply_to, sender_type, seq, pinned_at, pinned_by, kind FROM messages WHERE room_id = ?1 AND created_at > ?2 ORDER BY seq ASC LIMIT 100",
             )
             .ok();
         if let Some(ref mut s) = stmt {
@@ -106,6 +379,8 @@ pub fn message_stream(
                     pinned_at: row.get(10)?,
                     pinned_by: row.get(11)?,
                     edit_count: 0,
+                    kind: row.get(12)?,
+                    local_time: None,
                 })
             })
             .ok()
@@ -118,92 +393,388 @@ pub fn message_stream(
         vec![]
     };
 
-    EventStream! {
+    Ok(EventStream! {
         // Keep presence guard alive for the lifetime of the stream.
         // When the stream is dropped (client disconnects), the guard is dropped,
         // which removes the presence entry and publishes a PresenceLeft event.
         let _presence_guard = guard;
+        // Newest message seq sent, so a forced reconnect can tell the client where to resume
+        let mut last_seq = baseline_seq;
+        // Messages up to this seq were reported in a `gap`; the client replays them instead
+        let mut skip_through: Option<i64> = None;
+        let expiry = lifetime_secs.map(|secs| tokio::time::Instant::now() + Duration::from_secs(secs));
+
+        // Resuming from an event id: everything the room's outbox has after it, ids included,
+        // and messages that never went through the outbox, without one
+        let replayed_through = outbox_replay.iter().filter_map(|(id, _, _)| *id).max();
+        let unrecorded_through = outbox_replay
+            .iter()
+            .filter_map(|(id, event, _)| match event {
+                ChatEvent::NewMessage(m) if id.is_none() => Some(m.seq),
+                _ => None,
+            })
+            .max();
+        for (event_id, event, request_id) in outbox_replay {
+            if let Some((payload, name)) = sse_payload(event, std::slice::from_ref(&room_id), &request_id, locale.0) {
+                if name == "message" {
+                    last_seq = payload.get("seq").and_then(|s| s.as_i64()).or(last_seq);
+                }
+                if filter.allows(name, &payload) {
+                    connection.delivered();
+                    yield with_event_id(Event::json(&payload).event(name), event_id);
+                }
+            }
+        }
 
         // Send replayed messages first
-        for msg in replay {
-            yield Event::json(&msg).event("message");
+        for mut msg in replay {
+            localize_message(&mut msg, locale.0);
+            last_seq = Some(msg.seq);
+            let payload = serde_json::to_value(&msg).unwrap_or_default();
+            if filter.allows("message", &payload) {
+                connection.delivered();
+                yield Event::json(&payload).event("message");
+            }
         }
 
-        let mut heartbeat = interval(Duration::from_secs(15));
+        let mut heartbeat = interval(Duration::from_secs(heartbeat_secs));
 
         loop {
             tokio::select! {
                 msg = rx.recv() => {
-                    match msg {
-                        Ok(ChatEvent::NewMessage(m)) if m.room_id == room_id => {
-                            yield Event::json(&m).event("message");
-                        }
-                        Ok(ChatEvent::MessageEdited(m)) if m.room_id == room_id => {
-                            yield Event::json(&m).event("message_edited");
-                        }
-                        Ok(ChatEvent::MessageDeleted { ref id, room_id: ref rid }) if *rid == room_id => {
-                            yield Event::json(&serde_json::json!({"id": id, "room_id": rid})).event("message_deleted");
-                        }
-                        Ok(ChatEvent::RoomUpdated(ref r)) if r.id == room_id => {
-                            yield Event::json(r).event("room_updated");
-                        }
-                        Ok(ChatEvent::Typing { ref sender, room_id: ref rid }) if *rid == room_id => {
-                            yield Event::json(&serde_json::json!({"sender": sender, "room_id": rid})).event("typing");
-                        }
-                        Ok(ChatEvent::FileUploaded(ref f)) if f.room_id == room_id => {
-                            yield Event::json(f).event("file_uploaded");
-                        }
-                        Ok(ChatEvent::FileDeleted { ref id, room_id: ref rid }) if *rid == room_id => {
-                            yield Event::json(&serde_json::json!({"id": id, "room_id": rid})).event("file_deleted");
-                        }
-                        Ok(ChatEvent::ReactionAdded(ref r)) if r.room_id == room_id => {
-                            yield Event::json(r).event("reaction_added");
-                        }
-                        Ok(ChatEvent::ReactionRemoved(ref r)) if r.room_id == room_id => {
-                            yield Event::json(r).event("reaction_removed");
-                        }
-                        Ok(ChatEvent::MessagePinned(ref p)) if p.room_id == room_id => {
-                            yield Event::json(p).event("message_pinned");
-                        }
-                        Ok(ChatEvent::MessageUnpinned { ref id, room_id: ref rid }) if *rid == room_id => {
-                            yield Event::json(&serde_json::json!({"id": id, "room_id": rid})).event("message_unpinned");
-                        }
-                        Ok(ChatEvent::PresenceJoined { ref sender, ref sender_type, room_id: ref rid }) if *rid == room_id => {
-                            yield Event::json(&serde_json::json!({"sender": sender, "sender_type": sender_type, "room_id": rid})).event("presence_joined");
-                        }
-                        Ok(ChatEvent::PresenceLeft { ref sender, room_id: ref rid }) if *rid == room_id => {
-                            yield Event::json(&serde_json::json!({"sender": sender, "room_id": rid})).event("presence_left");
-                        }
-                        Ok(ChatEvent::ReadPositionUpdated(ref rp)) if rp.room_id == room_id => {
-                            yield Event::json(rp).event("read_position_updated");
-                        }
-                        Ok(ChatEvent::ProfileUpdated(ref p)) => {
-                            yield Event::json(p).event("profile_updated");
+                    let (msg, request_id, event_id) = match msg {
+                        // Another namespace's room with the same id is not this room
+                        Ok(p) if !p.is_in(namespace.as_deref()) => continue,
+                        Ok(p) => (Ok(p.event), p.request_id, p.event_id),
+                        Err(e) => (Err(e), None, None),
+                    };
+                    let out = match msg {
+                        Ok(event) => sse_payload(event, std::slice::from_ref(&room_id), &request_id, locale.0),
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(missed)) => {
+                            connection.lagged(missed);
+                            eprintln!("⚠️ SSE consumer in room {room_id} fell behind and missed {missed} events");
+                            let newest = newest_seq(&db_path, &room_id);
+                            let lost = newest.filter(|&to| to > last_seq.unwrap_or(0));
+                            let from_seq = lost.map(|_| last_seq.unwrap_or(0) + 1);
+                            let gap = serde_json::json!({
+                                "missed_events": missed,
+                                "from_seq": from_seq,
+                                "to_seq": lost,
+                                "replay": from_seq.map(|f| format!("/api/v1/rooms/{room_id}/messages?after={}", f - 1)),
+                            });
+                            if lost.is_some() {
+                                skip_through = lost;
+                                last_seq = lost;
+                            }
+                            Some((gap, "gap"))
                         }
-                        Ok(ChatEvent::ProfileDeleted { ref sender }) => {
-                            yield Event::json(&serde_json::json!({"sender": sender})).event("profile_deleted");
-                        }
-                        Ok(ChatEvent::RoomArchived(ref r)) if r.id == room_id => {
-                            yield Event::json(r).event("room_archived");
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                    };
+                    if let Some((payload, name)) = out {
+                        let seq = if name == "message" { payload.get("seq").and_then(|s| s.as_i64()) } else { None };
+                        let covered = seq.is_some_and(|s| skip_through.is_some_and(|t| s <= t))
+                            || event_id.is_some_and(|id| replayed_through.is_some_and(|t| id <= t))
+                            || (event_id.is_none() && seq.is_some_and(|s| unrecorded_through.is_some_and(|t| s <= t)));
+                        if let Some(s) = seq
+                            && !covered
+                        {
+                            last_seq = Some(last_seq.map_or(s, |l| l.max(s)));
                         }
-                        Ok(ChatEvent::RoomUnarchived(ref r)) if r.id == room_id => {
-                            yield Event::json(r).event("room_unarchived");
+                        if !covered && (name == "gap" || filter.allows(name, &payload)) {
+                            connection.delivered();
+                            yield with_event_id(Event::json(&payload).event(name), event_id);
                         }
-                        Ok(ChatEvent::RoomBookmarked { room_id: ref bk_rid, sender: ref bk_sender }) if *bk_rid == room_id => {
-                            yield Event::json(&serde_json::json!({"room_id": bk_rid, "sender": bk_sender})).event("room_bookmarked");
+                    }
+                }
+                _ = heartbeat.tick() => {
+                    let now = chrono::Utc::now().to_rfc3339();
+                    yield Event::json(&serde_json::json!({"time": now})).event("heartbeat");
+                }
+                _ = async {
+                    match expiry {
+                        Some(at) => tokio::time::sleep_until(at).await,
+                        None => std::future::pending().await,
+                    }
+                } => {
+                    // Tell the client to come back rather than just dropping it, so it can
+                    // resume without a gap
+                    yield Event::json(&serde_json::json!({"reason": "max_lifetime", "after": last_seq})).event("reconnect");
+                    break;
+                }
+            }
+        }
+    })
+}
+
+/// Messages in `room_id` that were posted after outbox event `after_id` without being recorded
+/// there (DMs, broadcasts, incoming hooks, system notes, ...), oldest first. Empty when the
+/// event is no longer retained.
+fn missed_outside_outbox(
+    conn: &rusqlite::Connection,
+    room_id: &str,
+    after_id: i64,
+    recorded: &[crate::outbox::StoredEvent],
+) -> Vec<Message> {
+    let Some(since) = crate::outbox::recorded_at(conn, after_id) else {
+        return Vec::new();
+    };
+    let recorded: std::collections::HashSet<&str> = recorded
+        .iter()
+        .filter_map(|stored| match &stored.event {
+            ChatEvent::NewMessage(m) => Some(m.id.as_str()),
+            _ => None,
+        })
+        .collect();
+    conn.prepare(
+        "SELECT id, room_id, sender, content, metadata, created_at, edited_at, reply_to, sender_type, seq, pinned_at, pinned_by, kind, 0 \
+         FROM messages WHERE room_id = ?1 AND seq > \
+         (SELECT COALESCE(MAX(seq), 0) FROM messages WHERE room_id = ?1 AND created_at <= ?2) \
+         ORDER BY seq ASC LIMIT ?3",
+    )
+    .and_then(|mut stmt| {
+        stmt.query_map(params![room_id, since, OUTBOX_REPLAY_LIMIT], super::messages::message_from_row)
+            .map(|rows| rows.filter_map(|r| r.ok()).collect::<Vec<Message>>())
+    })
+    .unwrap_or_default()
+    .into_iter()
+    .filter(|m| !recorded.contains(m.id.as_str()))
+    .collect()
+}
+
+/// Outbox events (with their ids) and messages the outbox missed (without), as one list in
+/// which messages stay in seq order.
+fn merge_resume(
+    stored: Vec<crate::outbox::StoredEvent>,
+    missed: Vec<Message>,
+) -> Vec<(Option<i64>, ChatEvent, Option<String>)> {
+    let mut missed = missed.into_iter().peekable();
+    let mut merged = Vec::new();
+    for stored in stored {
+        if let ChatEvent::NewMessage(m) = &stored.event {
+            while let Some(msg) = missed.next_if(|missed| missed.seq < m.seq) {
+                merged.push((None, ChatEvent::NewMessage(msg), None));
+            }
+        }
+        merged.push((Some(stored.id), stored.event, stored.request_id));
+    }
+    merged.extend(missed.map(|msg| (None, ChatEvent::NewMessage(msg), None)));
+    merged
+}
+
+/// GET /api/v1/stream — every room's events on one connection, for agents watching many rooms.
+/// `room_id` (comma-separated) narrows it to those rooms; `events`, `exclude_sender` and
+/// `sender_type` filter like the room stream's `events`, `exclude_sender` and `from_sender_type`.
+/// Live only: there is no replay, and a `gap` only counts what was missed, so catch up with
+/// each room's `messages?after=`. Private rooms' events only reach their members, and only the
+/// request's namespace's events are sent. Without `room_id`, a `sender` with room
+/// subscriptions only gets its subscribed rooms unless `all=true`.
+#[get("/api/v1/stream?<room_id>&<sender>&<all>&<events>&<exclude_sender>&<sender_type>&<heartbeat_secs>&<max_lifetime_secs>")]
+#[allow(clippy::too_many_arguments)]
+pub fn firehose_stream(
+    db: ScopedDb<'_>,
+    access: MemberAccess,
+    namespace: Namespace,
+    bus: &State<EventBus>,
+    stream_config: &State<StreamConfig>,
+    connections: &State<SseConnections>,
+    room_id: Option<&str>,
+    sender: Option<&str>,
+    all: Option<bool>,
+    events: Option<&str>,
+    exclude_sender: Option<&str>,
+    sender_type: Option<&str>,
+    heartbeat_secs: Option<u64>,
+    max_lifetime_secs: Option<u64>,
+    locale: Locale,
+) -> Result<EventStream![], (Status, Json<serde_json::Value>)> {
+    let filter = EventFilter::parse(events, exclude_sender, sender_type)?;
+    let (heartbeat_secs, lifetime_secs) = connection_limits(stream_config, heartbeat_secs, max_lifetime_secs)?;
+    let sender = sender.map(str::trim).filter(|s| !s.is_empty());
+    let rooms: Vec<String> = match (room_id, sender) {
+        (Some(r), _) => r.split(',').map(|id| id.trim().to_string()).filter(|id| !id.is_empty()).collect(),
+        (None, Some(s)) if !all.unwrap_or(false) => crate::subscriptions::room_ids(&db.conn(), s),
+        _ => Vec::new(),
+    };
+    // Side connection for checking whether a room is private as its events go by
+    let side = if access.all {
+        None
+    } else {
+        crate::db::open_with_flags(&db.path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .ok()
+            .filter(|c| c.busy_timeout(std::time::Duration::from_secs(5)).is_ok())
+    };
+    let mut rx = bus.sender.subscribe();
+    let connected_at = chrono::Utc::now();
+    let connection = connections.register(SseConnection {
+        id: 0,
+        room_id: if rooms.is_empty() { "*".to_string() } else { rooms.join(",") },
+        sender: sender.map(String::from),
+        sender_type: None,
+        connected_at: connected_at.to_rfc3339(),
+        expires_at: lifetime_secs.map(|secs| (connected_at + chrono::Duration::seconds(secs as i64)).to_rfc3339()),
+        heartbeat_secs,
+        events: events.map(|e| e.trim().to_string()).filter(|e| !e.is_empty()),
+        events_delivered: 0,
+        last_event_at: None,
+        lagged_events: 0,
+    });
+
+    Ok(EventStream! {
+        let expiry = lifetime_secs.map(|secs| tokio::time::Instant::now() + Duration::from_secs(secs));
+        let mut heartbeat = interval(Duration::from_secs(heartbeat_secs));
+
+        loop {
+            tokio::select! {
+                msg = rx.recv() => {
+                    match msg {
+                        Ok(published) if !published.is_in(namespace.as_deref()) => {}
+                        Ok(published) => {
+                            let event_id = published.event_id;
+                            if let Some((payload, name)) = sse_payload(published.event, &rooms, &published.request_id, locale.0)
+                                && filter.allows(name, &payload)
+                                && visible_to(&access, side.as_ref(), &payload, name)
+                            {
+                                connection.delivered();
+                                yield with_event_id(Event::json(&payload).event(name), event_id);
+                            }
                         }
-                        Ok(ChatEvent::RoomUnbookmarked { room_id: ref ubk_rid, sender: ref ubk_sender }) if *ubk_rid == room_id => {
-                            yield Event::json(&serde_json::json!({"room_id": ubk_rid, "sender": ubk_sender})).event("room_unbookmarked");
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(missed)) => {
+                            connection.lagged(missed);
+                            eprintln!("⚠️ Firehose SSE consumer fell behind and missed {missed} events");
+                            yield Event::json(&serde_json::json!({"missed_events": missed})).event("gap");
                         }
                         Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
-                        _ => {} // different room or lagged
                     }
                 }
                 _ = heartbeat.tick() => {
                     let now = chrono::Utc::now().to_rfc3339();
                     yield Event::json(&serde_json::json!({"time": now})).event("heartbeat");
                 }
+                _ = async {
+                    match expiry {
+                        Some(at) => tokio::time::sleep_until(at).await,
+                        None => std::future::pending().await,
+                    }
+                } => {
+                    yield Event::json(&serde_json::json!({"reason": "max_lifetime"})).event("reconnect");
+                    break;
+                }
             }
         }
+    })
+}
+
+/// Whether a firehose event may go to this connection: events of private rooms only reach
+/// their members. Without a side connection to check with, room events are held back.
+fn visible_to(access: &MemberAccess, side: Option<&rusqlite::Connection>, payload: &serde_json::Value, name: &str) -> bool {
+    if access.all {
+        return true;
+    }
+    let room = payload
+        .get("room_id")
+        .or_else(|| name.starts_with("room_").then(|| payload.get("id")).flatten())
+        .and_then(|v| v.as_str());
+    match room {
+        Some(room_id) => side.is_some_and(|conn| access.can_see(conn, room_id)),
+        None => true,
+    }
+}
+
+/// Tag an SSE event with its outbox id, when it has one, so clients can resume after it.
+fn with_event_id(event: Event, event_id: Option<i64>) -> Event {
+    match event_id {
+        Some(id) => event.id(id.to_string()),
+        None => event,
+    }
+}
+
+/// SSE payload and event name for a bus event, or None when it isn't for one of `rooms`
+/// (empty means every room).
+fn sse_payload(
+    event: ChatEvent,
+    rooms: &[String],
+    request_id: &Option<String>,
+    locale: &str,
+) -> Option<(serde_json::Value, &'static str)> {
+    let here = |rid: &str| rooms.is_empty() || rooms.iter().any(|r| r == rid);
+    match event {
+        ChatEvent::NewMessage(mut m) if here(&m.room_id) => {
+            localize_message(&mut m, locale);
+            Some((with_request_id(&m, request_id), "message"))
+        }
+        ChatEvent::MessageEdited(m) if here(&m.room_id) => Some((with_request_id(&m, request_id), "message_edited")),
+        ChatEvent::MessageDeleted { ref id, room_id: ref rid } if here(rid) => Some((with_request_id(&serde_json::json!({"id": id, "room_id": rid}), request_id), "message_deleted")),
+        ChatEvent::MessageRedacted { ref id, room_id: ref rid, ref redacted_at } if here(rid) => Some((with_request_id(&serde_json::json!({"id": id, "room_id": rid, "content": crate::redaction::REDACTED_CONTENT, "redacted_at": redacted_at}), request_id), "message_redacted")),
+        ChatEvent::RoomUpdated(ref r) if here(&r.id) => Some((with_request_id(r, request_id), "room_updated")),
+        ChatEvent::Typing { ref sender, room_id: ref rid, ref session } if here(rid) => Some((with_request_id(&serde_json::json!({"sender": sender, "room_id": rid, "session": session}), request_id), "typing")),
+        ChatEvent::FileUploaded(ref f) if here(&f.room_id) => Some((with_request_id(f, request_id), "file_uploaded")),
+        ChatEvent::FileDeleted { ref id, room_id: ref rid } if here(rid) => Some((with_request_id(&serde_json::json!({"id": id, "room_id": rid}), request_id), "file_deleted")),
+        ChatEvent::FileExpired { ref id, room_id: ref rid } if here(rid) => Some((with_request_id(&serde_json::json!({"id": id, "room_id": rid}), request_id), "file_expired")),
+        ChatEvent::RetentionPending(ref n) if here(&n.room_id) => Some((with_request_id(n, request_id), "retention_pending")),
+        ChatEvent::ResponseOverdue(ref p) if here(&p.room_id) => Some((with_request_id(p, request_id), "response_overdue")),
+        ChatEvent::MessageFlagged(ref f) if here(&f.room_id) => Some((with_request_id(f, request_id), "message_flagged")),
+        ChatEvent::FlagResolved(ref f) if here(&f.room_id) => Some((with_request_id(f, request_id), "flag_resolved")),
+        ChatEvent::MessageLabeled(ref l) if here(&l.room_id) => Some((with_request_id(l, request_id), "message_labeled")),
+        ChatEvent::TopicChanged(ref t) if here(&t.room_id) => Some((with_request_id(t, request_id), "topic_changed")),
+        ChatEvent::WebhookDisabled(ref w) if here(&w.room_id) => Some((with_request_id(w, request_id), "webhook_disabled")),
+        ChatEvent::QueueItemAdded(ref q) if here(&q.room_id) => Some((with_request_id(q, request_id), "queue_item_added")),
+        ChatEvent::QueueItemClaimed(ref q) if here(&q.room_id) => Some((with_request_id(q, request_id), "queue_item_claimed")),
+        ChatEvent::QueueItemCompleted(ref q) if here(&q.room_id) => Some((with_request_id(q, request_id), "queue_item_completed")),
+        ChatEvent::QueueItemReleased(ref q) if here(&q.room_id) => Some((with_request_id(q, request_id), "queue_item_released")),
+        ChatEvent::ReactionAdded(ref r) if here(&r.room_id) => Some((with_request_id(r, request_id), "reaction_added")),
+        ChatEvent::ReactionRemoved(ref r) if here(&r.room_id) => Some((with_request_id(r, request_id), "reaction_removed")),
+        ChatEvent::MessagePinned(ref p) if here(&p.room_id) => Some((with_request_id(p, request_id), "message_pinned")),
+        ChatEvent::MessageUnpinned { ref id, room_id: ref rid } if here(rid) => Some((with_request_id(&serde_json::json!({"id": id, "room_id": rid}), request_id), "message_unpinned")),
+        ChatEvent::PresenceJoined { ref sender, ref sender_type, room_id: ref rid } if here(rid) => Some((with_request_id(&serde_json::json!({"sender": sender, "sender_type": sender_type, "room_id": rid}), request_id), "presence_joined")),
+        ChatEvent::PresenceLeft { ref sender, room_id: ref rid } if here(rid) => Some((with_request_id(&serde_json::json!({"sender": sender, "room_id": rid}), request_id), "presence_left")),
+        ChatEvent::ReadPositionUpdated(ref rp) if here(&rp.room_id) => Some((with_request_id(rp, request_id), "read_position_updated")),
+        ChatEvent::ProfileUpdated(ref p) => Some((with_request_id(p, request_id), "profile_updated")),
+        ChatEvent::ProfileDeleted { ref sender } => Some((with_request_id(&serde_json::json!({"sender": sender}), request_id), "profile_deleted")),
+        ChatEvent::StatusUpdated(ref s) => Some((with_request_id(s, request_id), "status_updated")),
+        ChatEvent::StatusCleared { ref sender } => Some((with_request_id(&serde_json::json!({"sender": sender}), request_id), "status_cleared")),
+        ChatEvent::LockAcquired(ref l) => Some((with_request_id(l, request_id), "lock_acquired")),
+        ChatEvent::LockReleased { ref name, ref holder, token } => Some((with_request_id(&serde_json::json!({"name": name, "holder": holder, "token": token}), request_id), "lock_released")),
+        ChatEvent::RoomDeleted { ref id, ref name } if here(id) => Some((with_request_id(&serde_json::json!({"id": id, "name": name}), request_id), "room_deleted")),
+        ChatEvent::RoomArchived(ref r) if here(&r.id) => Some((with_request_id(r, request_id), "room_archived")),
+        ChatEvent::RoomUnarchived(ref r) if here(&r.id) => Some((with_request_id(r, request_id), "room_unarchived")),
+        ChatEvent::RoomBookmarked { room_id: ref bk_rid, sender: ref bk_sender } if here(bk_rid) => Some((with_request_id(&serde_json::json!({"room_id": bk_rid, "sender": bk_sender}), request_id), "room_bookmarked")),
+        ChatEvent::RoomUnbookmarked { room_id: ref ubk_rid, sender: ref ubk_sender } if here(ubk_rid) => Some((with_request_id(&serde_json::json!({"room_id": ubk_rid, "sender": ubk_sender}), request_id), "room_unbookmarked")),
+        ChatEvent::MessageChunk(ref c) if here(&c.room_id) => Some((with_request_id(c, request_id), "message_chunk")),
+        ChatEvent::MessageFinalized(ref m) if here(&m.room_id) => Some((with_request_id(m, request_id), "message_finalized")),
+        ChatEvent::MessageAppended(ref a) if here(&a.room_id) => Some((with_request_id(a, request_id), "message_appended")),
+        _ => None, // different room
     }
 }
+
+/// Newest message seq in a room, read on a side connection (the stream outlives the request's
+/// database handle).
+fn newest_seq(db_path: &str, room_id: &str) -> Option<i64> {
+    let conn = crate::db::open_with_flags(db_path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY).ok()?;
+    conn.busy_timeout(std::time::Duration::from_secs(5)).ok()?;
+    conn.query_row("SELECT MAX(seq) FROM messages WHERE room_id = ?1", params![room_id], |r| r.get(0))
+        .ok()
+        .flatten()
+}
+
+/// SSE payload for a live event: the event data plus the `request_id` of the call that caused it.
+/// Replayed history and heartbeats have no originating request, so they are sent as-is.
+fn with_request_id<T: serde::Serialize>(data: &T, request_id: &Option<String>) -> serde_json::Value {
+    let mut value = serde_json::to_value(data).unwrap_or_default();
+    if let (Some(rid), Some(obj)) = (request_id, value.as_object_mut()) {
+        obj.insert("request_id".to_string(), serde_json::Value::String(rid.clone()));
+    }
+    value
+}
+
+/// GET /api/v1/admin/streams — open SSE connections with what each has been sent, for
+/// debugging stuck consumers (server token required)
+#[get("/api/v1/admin/streams?<envelope>")]
+pub fn list_stream_connections(
+    connections: &State<SseConnections>,
+    sender_policy: &State<SenderPolicy>,
+    server_token: ServerToken,
+    envelope: Option<bool>,
+) -> Result<Json<ListOf<SseConnection>>, (Status, Json<serde_json::Value>)> {
+    sender_policy.check_server_token(&server_token)?;
+    Ok(Json(ListOf::complete(connections.list(), envelope)))
+}
@@ -0,0 +1,77 @@
+use crate::models::{RoomSubscriptions, SetRoomSubscriptions};
+use crate::namespaces::ScopedDb;
+use crate::subscriptions;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{get, put};
+use rusqlite::params;
+
+/// Most rooms one sender can follow.
+const MAX_SUBSCRIPTIONS: usize = 500;
+
+fn err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+fn check_sender(sender: Option<&str>) -> Result<&str, (Status, Json<serde_json::Value>)> {
+    let sender = sender.map(str::trim).unwrap_or_default();
+    if sender.is_empty() || sender.len() > 100 {
+        return Err(err(Status::BadRequest, "sender must be 1-100 characters"));
+    }
+    Ok(sender)
+}
+
+/// GET /api/v1/subscriptions/rooms?sender=X — the rooms a sender follows
+#[get("/api/v1/subscriptions/rooms?<sender>")]
+pub fn get_room_subscriptions(
+    db: ScopedDb<'_>,
+    sender: Option<&str>,
+) -> Result<Json<RoomSubscriptions>, (Status, Json<serde_json::Value>)> {
+    let sender = check_sender(sender)?;
+    let conn = db.conn();
+    Ok(Json(subscriptions::list(&conn, sender)))
+}
+
+/// PUT /api/v1/subscriptions/rooms — replace the set of rooms a sender follows. Unread counts,
+/// unread threads and the global stream default to these rooms; `[]` goes back to all rooms.
+#[put("/api/v1/subscriptions/rooms", format = "json", data = "<body>")]
+pub fn set_room_subscriptions(
+    db: ScopedDb<'_>,
+    body: Json<SetRoomSubscriptions>,
+) -> Result<Json<RoomSubscriptions>, (Status, Json<serde_json::Value>)> {
+    let sender = check_sender(Some(&body.sender))?;
+    let mut room_ids: Vec<String> = body
+        .room_ids
+        .iter()
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+        .collect();
+    room_ids.sort();
+    room_ids.dedup();
+    if room_ids.len() > MAX_SUBSCRIPTIONS {
+        return Err(err(
+            Status::BadRequest,
+            &format!("A sender can follow at most {MAX_SUBSCRIPTIONS} rooms"),
+        ));
+    }
+
+    let conn = db.conn();
+    let unknown: Vec<&String> = room_ids
+        .iter()
+        .filter(|id| {
+            conn.query_row("SELECT COUNT(*) FROM rooms WHERE id = ?1", params![id], |r| r.get::<_, i64>(0))
+                .map(|c| c == 0)
+                .unwrap_or(true)
+        })
+        .collect();
+    if !unknown.is_empty() {
+        return Err((
+            Status::NotFound,
+            Json(serde_json::json!({"error": "Room not found", "unknown_room_ids": unknown})),
+        ));
+    }
+
+    subscriptions::replace(&conn, sender, &room_ids)
+        .map_err(|_| err(Status::InternalServerError, "Database error"))?;
+    Ok(Json(subscriptions::list(&conn, sender)))
+}
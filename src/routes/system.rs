@@ -1,5 +1,11 @@
-use crate::db::Db;
+use crate::db::{Db, DbConfig};
+use crate::namespaces::ScopedDb;
+use crate::events::Events;
+use crate::models::SlowQueriesResponse;
 use crate::retention;
+use crate::senders::{SenderPolicy, ServerToken};
+use crate::telemetry::TraceContext;
+use rocket::http::Status;
 use rocket::serde::json::Json;
 use rocket::{get, post, State};
 
@@ -13,7 +19,7 @@ pub fn health() -> Json<serde_json::Value> {
 }
 
 #[get("/api/v1/stats")]
-pub fn stats(db: &State<Db>) -> Json<serde_json::Value> {
+pub fn stats(db: ScopedDb<'_>) -> Json<serde_json::Value> {
     let conn = db.conn();
 
     // Core counts
@@ -86,6 +92,14 @@ pub fn stats(db: &State<Db>) -> Json<serde_json::Value> {
     let file_bytes: i64 = conn
         .query_row("SELECT COALESCE(SUM(size), 0) FROM files", [], |r| r.get(0))
         .unwrap_or(0);
+    // Bytes actually on disk: identical uploads share one blob
+    let file_stored_bytes: i64 = conn
+        .query_row(
+            "SELECT (SELECT COALESCE(SUM(size), 0) FROM file_blobs) + (SELECT COALESCE(SUM(size), 0) FROM files WHERE sha256 IS NULL)",
+            [],
+            |r| r.get(0),
+        )
+        .unwrap_or(0);
 
     // Profiles
     let profile_count: i64 = conn
@@ -166,7 +180,8 @@ pub fn stats(db: &State<Db>) -> Json<serde_json::Value> {
         },
         "files": {
             "count": file_count,
-            "total_bytes": file_bytes
+            "total_bytes": file_bytes,
+            "stored_bytes": file_stored_bytes
         },
         "profiles": profile_count,
         "reactions": reaction_count,
@@ -187,9 +202,12 @@ pub fn stats(db: &State<Db>) -> Json<serde_json::Value> {
 /// Manually trigger a retention sweep. Returns details of what was pruned.
 /// Useful for testing and operational management.
 #[post("/api/v1/admin/retention/run")]
-pub fn run_retention_now(db: &State<Db>) -> Json<serde_json::Value> {
+pub fn run_retention_now(db: ScopedDb<'_>, events: Events<'_>, trace: TraceContext) -> Json<serde_json::Value> {
     let conn = db.conn();
-    let result = retention::run_retention(&conn);
+    let result = retention::run_retention(&conn, trace.0.as_ref());
+    for event in result.events() {
+        events.publish(event);
+    }
 
     let details: Vec<serde_json::Value> = result
         .details
@@ -207,24 +225,60 @@ pub fn run_retention_now(db: &State<Db>) -> Json<serde_json::Value> {
     Json(serde_json::json!({
         "rooms_checked": result.rooms_checked,
         "total_pruned": result.total_pruned,
+        "files_expired": result.expired_files.len(),
+        "messages_redacted": result.redacted.len(),
+        "streams_finalized": result.finalized_streams.len(),
+        "streams_removed": result.abandoned_streams.len(),
         "details": details
     }))
 }
 
+/// Statements slower than `DB_SLOW_QUERY_MS`, newest first (last 100 kept). Server token
+/// required: the recorded SQL carries literal values from other rooms.
+/// Returns `enabled: false` with an empty list when profiling is off.
+#[get("/api/v1/diagnostics/slow-queries")]
+pub fn slow_queries(
+    db_config: &State<DbConfig>,
+    sender_policy: &State<SenderPolicy>,
+    server_token: ServerToken,
+) -> Result<Json<SlowQueriesResponse>, (Status, Json<serde_json::Value>)> {
+    sender_policy.check_server_token(&server_token)?;
+    let queries = if db_config.slow_query_ms.is_some() {
+        crate::db::slow_queries()
+    } else {
+        Vec::new()
+    };
+    Ok(Json(SlowQueriesResponse {
+        enabled: db_config.slow_query_ms.is_some(),
+        threshold_ms: db_config.slow_query_ms,
+        count: queries.len(),
+        queries,
+    }))
+}
+
 /// GET /SKILL.md — canonical AI-readable service guide
 #[get("/SKILL.md")]
 pub fn skill_md() -> (rocket::http::ContentType, &'static str) {
     (rocket::http::ContentType::Plain, include_str!("../../SKILL.md"))
 }
 
+/// SKILL.md, followed by the commands bots have registered in each room.
+fn llms_txt(db: &Db) -> String {
+    let mut doc = include_str!("../../SKILL.md").to_string();
+    if let Some(section) = super::commands::llms_commands_section(&db.conn()) {
+        doc.push_str(&section);
+    }
+    doc
+}
+
 #[get("/llms.txt")]
-pub fn llms_txt_root() -> (rocket::http::ContentType, &'static str) {
-    (rocket::http::ContentType::Plain, include_str!("../../SKILL.md"))
+pub fn llms_txt_root(db: ScopedDb<'_>) -> (rocket::http::ContentType, String) {
+    (rocket::http::ContentType::Plain, llms_txt(&db))
 }
 
 #[get("/api/v1/llms.txt")]
-pub fn llms_txt_api() -> (rocket::http::ContentType, &'static str) {
-    (rocket::http::ContentType::Plain, include_str!("../../SKILL.md"))
+pub fn llms_txt_api(db: ScopedDb<'_>) -> (rocket::http::ContentType, String) {
+    (rocket::http::ContentType::Plain, llms_txt(&db))
 }
 
 
@@ -236,6 +290,51 @@ pub fn openapi_json() -> (rocket::http::ContentType, &'static str) {
     )
 }
 
+// --- Interactive API console ---
+
+/// Whether `/api/v1/docs` is mounted. On unless `API_DOCS_ENABLED` is `0`/`false`.
+pub fn api_docs_enabled() -> bool {
+    std::env::var("API_DOCS_ENABLED")
+        .map(|v| v != "0" && !v.eq_ignore_ascii_case("false"))
+        .unwrap_or(true)
+}
+
+/// RapiDoc bundle loaded by the console page. Point `API_DOCS_SCRIPT_URL` at a local copy on
+/// networks without internet access.
+const DEFAULT_API_DOCS_SCRIPT: &str = "https://unpkg.com/rapidoc@9.3.8/dist/rapidoc-min.js";
+
+/// GET /api/v1/docs — RapiDoc console rendering the live openapi.json, with "try it" enabled
+#[get("/api/v1/docs")]
+pub fn api_docs() -> (rocket::http::ContentType, String) {
+    let script = std::env::var("API_DOCS_SCRIPT_URL").unwrap_or_else(|_| DEFAULT_API_DOCS_SCRIPT.to_string());
+    let script = script.replace('"', "%22");
+    let html = format!(
+        r#"<!doctype html>
+<html>
+<head>
+  <meta charset="utf-8">
+  <meta name="viewport" content="width=device-width, initial-scale=1">
+  <title>Local Agent Chat — API Console</title>
+  <script type="module" src="{script}"></script>
+</head>
+<body>
+  <rapi-doc
+    spec-url="/api/v1/openapi.json"
+    server-url=""
+    default-api-server=""
+    render-style="read"
+    allow-try="true"
+    allow-authentication="true"
+    show-header="false"
+    theme="dark"
+  ></rapi-doc>
+</body>
+</html>
+"#
+    );
+    (rocket::http::ContentType::HTML, html)
+}
+
 // --- Well-Known Skills Discovery (Cloudflare RFC) ---
 
 #[get("/.well-known/skills/index.json")]
@@ -248,6 +347,13 @@ pub fn skills_skill_md() -> (rocket::http::ContentType, &'static str) {
     (rocket::http::ContentType::Plain, include_str!("../../SKILL.md"))
 }
 
+/// GET /api/v1/skills.json — the API as callable tool schemas (JSON Schema parameters plus the
+/// HTTP mapping for each), for agent frameworks that register tools automatically
+#[get("/api/v1/skills.json")]
+pub fn skills_json() -> (rocket::http::ContentType, &'static str) {
+    (rocket::http::ContentType::JSON, include_str!("../../skills.json"))
+}
+
 /// GET /skills/SKILL.md — alternate path for agent discoverability
 #[get("/api/v1/skills/SKILL.md")]
 pub fn api_skills_skill_md() -> (rocket::http::ContentType, &'static str) {
@@ -262,7 +368,8 @@ const SKILLS_INDEX_JSON: &str = r#"{
       "url": "/SKILL.md",
       "files": [
         "SKILL.md"
-      ]
+      ],
+      "tools": "/api/v1/skills.json"
     }
   ]
 }"#;
@@ -274,7 +381,10 @@ pub fn too_many_requests() -> Json<serde_json::Value> {
 }
 
 #[rocket::catch(404)]
-pub fn not_found() -> Json<serde_json::Value> {
+pub fn not_found(req: &rocket::Request<'_>) -> Json<serde_json::Value> {
+    if let crate::namespaces::UnknownNamespace(Some(name)) = req.local_cache(|| crate::namespaces::UnknownNamespace(None)) {
+        return Json(serde_json::json!({"error": format!("Unknown namespace '{name}'")}));
+    }
     Json(serde_json::json!({"error": "Not found"}))
 }
 
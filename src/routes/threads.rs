@@ -1,8 +1,8 @@
-use crate::db::Db;
+use crate::namespaces::ScopedDb;
 use crate::models::*;
 use rocket::http::Status;
 use rocket::serde::json::Json;
-use rocket::{get, State};
+use rocket::get;
 use rusqlite::params;
 
 /// Thread response: the root message and all replies in chronological order
@@ -22,15 +22,188 @@ pub struct ThreadMessage {
     pub depth: u32,
 }
 
+/// Activity summary for a thread, used to judge whether a discussion has converged.
+#[derive(Debug, serde::Serialize)]
+pub struct ThreadStats {
+    pub room_id: String,
+    pub root_id: String,
+    /// Root plus replies.
+    pub total_messages: usize,
+    pub total_replies: usize,
+    pub total_reactions: usize,
+    /// Reaction counts by emoji across the whole thread.
+    pub reactions: std::collections::BTreeMap<String, usize>,
+    pub participants: Vec<ThreadParticipant>,
+    pub first_activity_at: String,
+    /// Latest message or reaction in the thread.
+    pub last_activity_at: String,
+    pub resolution: ThreadResolution,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct ThreadParticipant {
+    pub sender: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sender_type: Option<String>,
+    pub messages: usize,
+    /// Reactions left by this sender on thread messages.
+    pub reactions_given: usize,
+    /// Reactions others left on this sender's thread messages.
+    pub reactions_received: usize,
+    pub first_message_at: String,
+    pub last_message_at: String,
+}
+
+/// A thread is resolved by a message carrying `"resolved": true` in its metadata; a later
+/// message with `"resolved": false` reopens it.
+#[derive(Debug, serde::Serialize)]
+pub struct ThreadResolution {
+    /// "open" or "resolved".
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_by: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_id: Option<String>,
+}
+
 /// Get the full thread for a message.
 /// Walks up reply_to chain to find root, then collects all descendants.
 #[get("/api/v1/rooms/<room_id>/messages/<message_id>/thread")]
 pub fn get_thread(
-    db: &State<Db>,
+    db: ScopedDb<'_>,
     room_id: &str,
     message_id: &str,
 ) -> Result<Json<ThreadResponse>, (Status, Json<serde_json::Value>)> {
     let conn = db.conn();
+    let (root, replies) = collect_thread(&conn, room_id, message_id)?;
+    let total_replies = replies.len();
+
+    Ok(Json(ThreadResponse {
+        root,
+        replies,
+        total_replies,
+    }))
+}
+
+/// Per-participant counts, reactions, activity window and resolution status for a thread.
+/// Like the thread endpoint, any message in the thread identifies it.
+#[get("/api/v1/rooms/<room_id>/messages/<root_id>/thread/stats")]
+pub fn get_thread_stats(
+    db: ScopedDb<'_>,
+    room_id: &str,
+    root_id: &str,
+) -> Result<Json<ThreadStats>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    let (root, replies) = collect_thread(&conn, room_id, root_id)?;
+    let messages: Vec<&Message> = std::iter::once(&root)
+        .chain(replies.iter().map(|r| &r.message))
+        .collect();
+    let authors: std::collections::HashMap<&str, &str> =
+        messages.iter().map(|m| (m.id.as_str(), m.sender.as_str())).collect();
+
+    let internal = || (Status::InternalServerError, Json(serde_json::json!({"error": "Internal server error"})));
+
+    // (message author, reacting sender, emoji, created_at) for every reaction on the thread
+    let mut reactions: Vec<(String, String, String, String)> = Vec::new();
+    let mut stmt = conn
+        .prepare("SELECT sender, emoji, created_at FROM message_reactions WHERE message_id = ?1")
+        .map_err(|_| internal())?;
+    for m in &messages {
+        let rows = stmt
+            .query_map(params![&m.id], |r| Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?, r.get::<_, String>(2)?)))
+            .map_err(|_| internal())?;
+        for (sender, emoji, created_at) in rows.filter_map(|r| r.ok()) {
+            reactions.push((authors[m.id.as_str()].to_string(), sender, emoji, created_at));
+        }
+    }
+
+    let parse = |t: &str| chrono::DateTime::parse_from_rfc3339(t).ok();
+    let mut participants: Vec<ThreadParticipant> = Vec::new();
+    for m in &messages {
+        match participants.iter_mut().find(|p| p.sender == m.sender) {
+            Some(p) => {
+                p.messages += 1;
+                if parse(&m.created_at) < parse(&p.first_message_at) {
+                    p.first_message_at = m.created_at.clone();
+                }
+                if parse(&m.created_at) > parse(&p.last_message_at) {
+                    p.last_message_at = m.created_at.clone();
+                }
+                if p.sender_type.is_none() {
+                    p.sender_type = m.sender_type.clone();
+                }
+            }
+            None => participants.push(ThreadParticipant {
+                sender: m.sender.clone(),
+                sender_type: m.sender_type.clone(),
+                messages: 1,
+                reactions_given: 0,
+                reactions_received: 0,
+                first_message_at: m.created_at.clone(),
+                last_message_at: m.created_at.clone(),
+            }),
+        }
+    }
+    let mut by_emoji = std::collections::BTreeMap::new();
+    for (author, sender, emoji, _) in &reactions {
+        *by_emoji.entry(emoji.clone()).or_insert(0) += 1;
+        if let Some(p) = participants.iter_mut().find(|p| &p.sender == author) {
+            p.reactions_received += 1;
+        }
+        if let Some(p) = participants.iter_mut().find(|p| &p.sender == sender) {
+            p.reactions_given += 1;
+        }
+    }
+    participants.sort_by(|a, b| b.messages.cmp(&a.messages).then_with(|| a.sender.cmp(&b.sender)));
+
+    let activity = messages
+        .iter()
+        .map(|m| m.created_at.as_str())
+        .chain(reactions.iter().map(|r| r.3.as_str()));
+    let first_activity_at = activity.clone().min_by_key(|t| parse(t)).unwrap_or(root.created_at.as_str()).to_string();
+    let last_activity_at = activity.max_by_key(|t| parse(t)).unwrap_or(root.created_at.as_str()).to_string();
+
+    let marker = messages
+        .iter()
+        .filter(|m| m.metadata.get("resolved").and_then(|v| v.as_bool()).is_some())
+        .max_by_key(|m| m.seq);
+    let resolution = match marker {
+        Some(m) if m.metadata["resolved"] == serde_json::Value::Bool(true) => ThreadResolution {
+            status: "resolved".to_string(),
+            resolved_by: Some(m.sender.clone()),
+            resolved_at: Some(m.created_at.clone()),
+            message_id: Some(m.id.clone()),
+        },
+        _ => ThreadResolution {
+            status: "open".to_string(),
+            resolved_by: None,
+            resolved_at: None,
+            message_id: None,
+        },
+    };
+
+    Ok(Json(ThreadStats {
+        room_id: room_id.to_string(),
+        root_id: root.id.clone(),
+        total_messages: messages.len(),
+        total_replies: replies.len(),
+        total_reactions: reactions.len(),
+        reactions: by_emoji,
+        participants,
+        first_activity_at,
+        last_activity_at,
+        resolution,
+    }))
+}
+
+/// Resolve the thread containing `message_id`: its root and all replies (by seq, with depth).
+fn collect_thread(
+    conn: &rusqlite::Connection,
+    room_id: &str,
+    message_id: &str,
+) -> Result<(Message, Vec<ThreadMessage>), (Status, Json<serde_json::Value>)> {
 
     // Verify room exists
     let room_exists: bool = conn
@@ -50,7 +223,7 @@ pub fn get_thread(
     }
 
     // Fetch the target message
-    let target = fetch_message(&conn, message_id, room_id)?;
+    let target = fetch_message(conn, message_id, room_id)?;
 
     // Walk up reply_to chain to find the root message
     let mut root = target;
@@ -61,14 +234,14 @@ pub fn get_thread(
             break; // prevent infinite loops
         }
         visited.insert(parent_id.clone());
-        match fetch_message(&conn, parent_id, room_id) {
+        match fetch_message(conn, parent_id, room_id) {
             Ok(parent) => root = parent,
             Err(_) => break, // parent deleted or not found, treat current as root
         }
     }
 
     // Collect all descendants of the root using BFS
-    let all_messages = fetch_all_room_messages(&conn, room_id);
+    let all_messages = fetch_all_room_messages(conn, room_id);
     let mut replies: Vec<ThreadMessage> = Vec::new();
     let mut queue: Vec<(String, u32)> = vec![(root.id.clone(), 0)]; // (parent_id, parent_depth)
     let mut seen = std::collections::HashSet::new();
@@ -94,23 +267,17 @@ pub fn get_thread(
     // Sort replies by seq (chronological order)
     replies.sort_by_key(|r| r.message.seq);
 
-    let total_replies = replies.len();
-
-    Ok(Json(ThreadResponse {
-        root,
-        replies,
-        total_replies,
-    }))
+    Ok((root, replies))
 }
 
 /// Fetch a single message by ID from a specific room
-fn fetch_message(
-    conn: &std::sync::MutexGuard<rusqlite::Connection>,
+pub(super) fn fetch_message(
+    conn: &rusqlite::Connection,
     message_id: &str,
     room_id: &str,
 ) -> Result<Message, (Status, Json<serde_json::Value>)> {
     conn.query_row(
-        "SELECT id, room_id, sender, content, metadata, created_at, edited_at, reply_to, sender_type, seq, pinned_at, pinned_by FROM messages WHERE id = ?1 AND room_id = ?2",
+        "SELECT id, room_id, sender, content, metadata, created_at, edited_at, reply_to, sender_type, seq, pinned_at, pinned_by, kind FROM messages WHERE id = ?1 AND room_id = ?2",
         params![message_id, room_id],
         |row| {
             Ok(Message {
@@ -127,6 +294,8 @@ fn fetch_message(
                 pinned_at: row.get(10)?,
                 pinned_by: row.get(11)?,
                 edit_count: 0,
+                kind: row.get(12)?,
+                local_time: None,
             })
         },
     )
@@ -140,11 +309,11 @@ fn fetch_message(
 
 /// Fetch all messages in a room (for thread tree traversal)
 fn fetch_all_room_messages(
-    conn: &std::sync::MutexGuard<rusqlite::Connection>,
+    conn: &rusqlite::Connection,
     room_id: &str,
 ) -> Vec<Message> {
     let mut stmt = match conn
-        .prepare("SELECT id, room_id, sender, content, metadata, created_at, edited_at, reply_to, sender_type, seq, pinned_at, pinned_by FROM messages WHERE room_id = ?1 ORDER BY seq ASC")
+        .prepare("SELECT id, room_id, sender, content, metadata, created_at, edited_at, reply_to, sender_type, seq, pinned_at, pinned_by, kind FROM messages WHERE room_id = ?1 ORDER BY seq ASC")
     {
         Ok(s) => s,
         Err(_) => return Vec::new(),
@@ -165,6 +334,8 @@ fn fetch_all_room_messages(
             pinned_at: row.get(10)?,
             pinned_by: row.get(11)?,
             edit_count: 0,
+            kind: row.get(12)?,
+            local_time: None,
         })
     }) {
         Ok(rows) => rows.filter_map(|r| r.ok()).collect(),
@@ -0,0 +1,125 @@
+use crate::namespaces::ScopedDb;
+use crate::events::{ChatEvent, Events};
+use crate::models::{RoomTopic, SetTopic};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{get, put};
+use rusqlite::{params, Connection};
+
+fn err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+/// Longest topic, in characters.
+const MAX_TOPIC_CHARS: usize = 300;
+
+/// Changes kept per room; older ones are pruned on write.
+const HISTORY_LIMIT: i64 = 200;
+
+fn topic_from_row(r: &rusqlite::Row) -> rusqlite::Result<RoomTopic> {
+    let topic: String = r.get(1)?;
+    Ok(RoomTopic {
+        room_id: r.get(0)?,
+        topic: Some(topic).filter(|t| !t.is_empty()),
+        set_by: r.get(2)?,
+        set_at: r.get(3)?,
+    })
+}
+
+fn current_topic(conn: &Connection, room_id: &str) -> Option<RoomTopic> {
+    conn.query_row(
+        "SELECT room_id, topic, set_by, created_at FROM room_topics WHERE room_id = ?1 ORDER BY id DESC LIMIT 1",
+        params![room_id],
+        topic_from_row,
+    )
+    .ok()
+}
+
+fn require_room(conn: &Connection, room_id: &str) -> Result<(), (Status, Json<serde_json::Value>)> {
+    conn.query_row("SELECT 1 FROM rooms WHERE id = ?1", params![room_id], |_| Ok(()))
+        .map_err(|_| err(Status::NotFound, "Room not found"))
+}
+
+/// PUT /api/v1/rooms/<room_id>/topic — set the room's topic. Any participant may change it;
+/// an empty topic clears it. Setting the current topic again is a no-op.
+#[put("/api/v1/rooms/<room_id>/topic", format = "json", data = "<body>")]
+pub fn set_topic(
+    db: ScopedDb<'_>,
+    events: Events<'_>,
+    room_id: &str,
+    body: Json<SetTopic>,
+) -> Result<Json<RoomTopic>, (Status, Json<serde_json::Value>)> {
+    let sender = body.sender.trim();
+    if sender.is_empty() || sender.len() > 100 {
+        return Err(err(Status::BadRequest, "Sender must be 1-100 characters"));
+    }
+    let topic = body.topic.split_whitespace().collect::<Vec<_>>().join(" ");
+    if topic.chars().count() > MAX_TOPIC_CHARS {
+        return Err(err(
+            Status::BadRequest,
+            &format!("Topic must be at most {MAX_TOPIC_CHARS} characters"),
+        ));
+    }
+
+    let conn = db.conn();
+    require_room(&conn, room_id)?;
+    match current_topic(&conn, room_id) {
+        Some(current) if current.topic.as_deref().unwrap_or("") == topic => return Ok(Json(current)),
+        None if topic.is_empty() => return Err(err(Status::NotFound, "This room has no topic")),
+        _ => {}
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO room_topics (room_id, topic, set_by, created_at) VALUES (?1, ?2, ?3, ?4)",
+        params![room_id, &topic, sender, &now],
+    )
+    .map_err(|_| err(Status::InternalServerError, "Database error"))?;
+    conn.execute(
+        "DELETE FROM room_topics WHERE room_id = ?1 AND id NOT IN
+           (SELECT id FROM room_topics WHERE room_id = ?1 ORDER BY id DESC LIMIT ?2)",
+        params![room_id, HISTORY_LIMIT],
+    )
+    .ok();
+    drop(conn);
+
+    let result = RoomTopic {
+        room_id: room_id.to_string(),
+        topic: Some(topic).filter(|t| !t.is_empty()),
+        set_by: sender.to_string(),
+        set_at: now,
+    };
+    events.publish(ChatEvent::TopicChanged(result.clone()));
+    Ok(Json(result))
+}
+
+/// GET /api/v1/rooms/<room_id>/topic — the current topic, who set it and when
+#[get("/api/v1/rooms/<room_id>/topic")]
+pub fn get_topic(db: ScopedDb<'_>, room_id: &str) -> Result<Json<RoomTopic>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    require_room(&conn, room_id)?;
+    current_topic(&conn, room_id)
+        .filter(|t| t.topic.is_some())
+        .map(Json)
+        .ok_or_else(|| err(Status::NotFound, "This room has no topic"))
+}
+
+/// GET /api/v1/rooms/<room_id>/topic/history?limit=N — past topics, newest first
+#[get("/api/v1/rooms/<room_id>/topic/history?<limit>")]
+pub fn topic_history(
+    db: ScopedDb<'_>,
+    room_id: &str,
+    limit: Option<i64>,
+) -> Result<Json<Vec<RoomTopic>>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    require_room(&conn, room_id)?;
+    let limit = limit.unwrap_or(50).clamp(1, HISTORY_LIMIT);
+    let history = conn
+        .prepare("SELECT room_id, topic, set_by, created_at FROM room_topics WHERE room_id = ?1 ORDER BY id DESC LIMIT ?2")
+        .and_then(|mut stmt| {
+            stmt.query_map(params![room_id, limit], topic_from_row)
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .unwrap_or_default();
+    Ok(Json(history))
+}
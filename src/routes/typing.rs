@@ -1,18 +1,21 @@
-use crate::db::Db;
-use crate::events::{ChatEvent, EventBus};
+use crate::namespaces::ScopedDb;
+use crate::events::{ChatEvent, Events};
 use crate::models::TypingNotification;
 use rocket::http::Status;
 use rocket::serde::json::Json;
 use rocket::{post, State};
 use rusqlite::params;
 
-use super::TypingTracker;
+use super::{PresenceTracker, TypingTracker};
+use crate::sessions::ClientSession;
 
 #[post("/api/v1/rooms/<room_id>/typing", format = "json", data = "<body>")]
 pub fn notify_typing(
-    db: &State<Db>,
-    events: &State<EventBus>,
+    db: ScopedDb<'_>,
+    events: Events<'_>,
     typing_tracker: &State<TypingTracker>,
+    presence: &State<PresenceTracker>,
+    session: ClientSession,
     room_id: &str,
     body: Json<TypingNotification>,
 ) -> Result<Json<serde_json::Value>, (Status, Json<serde_json::Value>)> {
@@ -42,9 +45,10 @@ pub fn notify_typing(
         ));
     }
     drop(conn);
+    presence.touch_session(&session, &sender);
 
-    // Dedup: only publish if last typing notification was >2 seconds ago
-    let key = format!("{}:{}", room_id, sender);
+    // Dedup per device: only publish if its last typing notification was >2 seconds ago
+    let key = format!("{}:{}:{}", room_id, sender, session.token);
     let now = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap_or_default()
@@ -66,6 +70,7 @@ pub fn notify_typing(
     events.publish(ChatEvent::Typing {
         sender,
         room_id: room_id.to_string(),
+        session: Some(session.token),
     });
 
     Ok(Json(serde_json::json!({"ok": true})))
@@ -0,0 +1,139 @@
+use crate::namespaces::ScopedDb;
+use crate::models::{RoomUploadPolicy, SetUploadPolicy};
+use crate::uploads::{normalize_list, room_policy};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{delete, get, put};
+use rusqlite::{params, Connection};
+
+use super::AdminKey;
+
+/// Max entries per list, to keep policies readable.
+const MAX_POLICY_ENTRIES: usize = 100;
+
+fn err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+fn check_admin(conn: &Connection, room_id: &str, admin: &AdminKey) -> Result<(), (Status, Json<serde_json::Value>)> {
+    let key: Option<String> = conn
+        .query_row("SELECT admin_key FROM rooms WHERE id = ?1", params![room_id], |r| r.get(0))
+        .map_err(|_| err(Status::NotFound, "Room not found"))?;
+    if key.as_deref() != Some(admin.0.as_str()) {
+        return Err(err(Status::Forbidden, "Invalid admin key for this room"));
+    }
+    Ok(())
+}
+
+fn validate_types(field: &str, list: &[String]) -> Result<Vec<String>, (Status, Json<serde_json::Value>)> {
+    let types = normalize_list(list.iter().map(String::as_str));
+    for t in &types {
+        let valid = t
+            .split_once('/')
+            .is_some_and(|(top, sub)| !top.is_empty() && !top.contains('*') && !sub.is_empty() && (sub == "*" || !sub.contains('*')));
+        if !valid {
+            return Err(err(
+                Status::BadRequest,
+                &format!("{field}: '{t}' is not a MIME type (use type/subtype or type/*)"),
+            ));
+        }
+    }
+    Ok(types)
+}
+
+fn validate_extensions(field: &str, list: &[String]) -> Result<Vec<String>, (Status, Json<serde_json::Value>)> {
+    let extensions = normalize_list(list.iter().map(String::as_str));
+    if let Some(bad) = extensions.iter().find(|e| e.len() > 20 || !e.chars().all(|c| c.is_ascii_alphanumeric())) {
+        return Err(err(Status::BadRequest, &format!("{field}: '{bad}' is not a file extension")));
+    }
+    Ok(extensions)
+}
+
+/// Check and normalize a policy's lists: allowed and blocked types, then allowed and blocked extensions.
+pub(super) fn validate(body: &SetUploadPolicy) -> Result<[Vec<String>; 4], (Status, Json<serde_json::Value>)> {
+    let lists = [
+        validate_types("allowed_types", &body.allowed_types)?,
+        validate_types("blocked_types", &body.blocked_types)?,
+        validate_extensions("allowed_extensions", &body.allowed_extensions)?,
+        validate_extensions("blocked_extensions", &body.blocked_extensions)?,
+    ];
+    if lists.iter().any(|l| l.len() > MAX_POLICY_ENTRIES) {
+        return Err(err(
+            Status::BadRequest,
+            &format!("Each list may have at most {MAX_POLICY_ENTRIES} entries"),
+        ));
+    }
+    Ok(lists)
+}
+
+/// Set or replace the room's policy with lists checked by [`validate`].
+pub(super) fn save(conn: &Connection, room_id: &str, lists: &[Vec<String>; 4], verify_content_type: bool) -> rusqlite::Result<()> {
+    let [allowed_types, blocked_types, allowed_extensions, blocked_extensions] = lists;
+    conn.execute(
+        "INSERT INTO room_upload_policies (room_id, allowed_types, blocked_types, allowed_extensions, blocked_extensions, verify_content_type, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT(room_id) DO UPDATE SET allowed_types = ?2, blocked_types = ?3, allowed_extensions = ?4,
+             blocked_extensions = ?5, verify_content_type = ?6, updated_at = ?7",
+        params![
+            room_id,
+            allowed_types.join(","),
+            blocked_types.join(","),
+            allowed_extensions.join(","),
+            blocked_extensions.join(","),
+            verify_content_type,
+            chrono::Utc::now().to_rfc3339()
+        ],
+    )?;
+    Ok(())
+}
+
+/// GET /api/v1/rooms/<room_id>/upload-policy — the room's upload restrictions (404 if none).
+#[get("/api/v1/rooms/<room_id>/upload-policy")]
+pub fn get_upload_policy(
+    db: ScopedDb<'_>,
+    room_id: &str,
+) -> Result<Json<RoomUploadPolicy>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    room_policy(&conn, room_id)
+        .map(Json)
+        .ok_or_else(|| err(Status::NotFound, "No upload policy configured for this room"))
+}
+
+/// PUT /api/v1/rooms/<room_id>/upload-policy — set or replace the room's upload restrictions (admin key).
+#[put("/api/v1/rooms/<room_id>/upload-policy", format = "json", data = "<body>")]
+pub fn set_upload_policy(
+    db: ScopedDb<'_>,
+    room_id: &str,
+    admin: AdminKey,
+    body: Json<SetUploadPolicy>,
+) -> Result<Json<RoomUploadPolicy>, (Status, Json<serde_json::Value>)> {
+    let lists = validate(&body)?;
+
+    let conn = db.conn();
+    check_admin(&conn, room_id, &admin)?;
+    save(&conn, room_id, &lists, body.verify_content_type)
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+
+    room_policy(&conn, room_id)
+        .map(Json)
+        .ok_or_else(|| err(Status::InternalServerError, "Internal server error"))
+}
+
+/// DELETE /api/v1/rooms/<room_id>/upload-policy — lift the room's restrictions (admin key).
+/// Server-wide `UPLOAD_*` settings still apply.
+#[delete("/api/v1/rooms/<room_id>/upload-policy")]
+pub fn delete_upload_policy(
+    db: ScopedDb<'_>,
+    room_id: &str,
+    admin: AdminKey,
+) -> Result<Json<serde_json::Value>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    check_admin(&conn, room_id, &admin)?;
+    let deleted = conn
+        .execute("DELETE FROM room_upload_policies WHERE room_id = ?1", params![room_id])
+        .unwrap_or(0);
+    if deleted == 0 {
+        return Err(err(Status::NotFound, "No upload policy configured for this room"));
+    }
+    Ok(Json(serde_json::json!({"deleted": true, "room_id": room_id})))
+}
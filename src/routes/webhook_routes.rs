@@ -1,10 +1,13 @@
 use crate::db::Db;
+use crate::namespaces::ScopedDb;
 use crate::models::*;
+use crate::secrets::SecretBox;
 use rocket::form::FromForm;
 use rocket::http::Status;
 use rocket::serde::json::Json;
 use rocket::{delete, get, post, put, State};
 use rusqlite::params;
+use std::collections::BTreeMap;
 
 use super::AdminKey;
 
@@ -37,64 +40,127 @@ fn verify_room_admin(
     }
 }
 
-#[post("/api/v1/rooms/<room_id>/webhooks", format = "json", data = "<body>")]
-pub fn create_webhook(
-    db: &State<Db>,
-    room_id: &str,
-    admin: AdminKey,
-    body: Json<CreateWebhook>,
-) -> Result<Json<serde_json::Value>, (Status, Json<serde_json::Value>)> {
-    verify_room_admin(db, room_id, &admin)?;
-    let conn = db.conn();
+fn bad_request(error: String) -> (Status, Json<serde_json::Value>) {
+    (Status::BadRequest, Json(serde_json::json!({"error": error})))
+}
 
-    // Validate URL
-    let url = body.url.trim().to_string();
+/// Event types a room webhook can subscribe to.
+pub(super) const ROOM_EVENTS: &[&str] = &[
+    "message",
+    "message_edited",
+    "message_deleted",
+    "message_redacted",
+    "message_appended",
+    "file_uploaded",
+    "file_deleted",
+    "file_expired",
+    "retention_pending",
+    "response_overdue",
+    "message_flagged",
+    "flag_resolved",
+    "message_labeled",
+    "topic_changed",
+    "webhook_disabled",
+    "queue_item_added",
+    "queue_item_claimed",
+    "queue_item_completed",
+    "queue_item_released",
+    "reaction_added",
+    "reaction_removed",
+    "message_pinned",
+    "message_unpinned",
+    "presence_joined",
+    "presence_left",
+    "room_updated",
+    "message_finalized",
+];
+
+/// Check a webhook's URL and its comma-separated events filter (`*` for all) against `valid`.
+/// Returns both trimmed.
+pub(super) fn validate_target(
+    url: &str,
+    events: &str,
+    valid: &[&str],
+) -> Result<(String, String), (Status, Json<serde_json::Value>)> {
+    let url = url.trim().to_string();
     if url.is_empty() || (!url.starts_with("http://") && !url.starts_with("https://")) {
-        return Err((
-            Status::BadRequest,
-            Json(serde_json::json!({"error": "Invalid webhook URL: must start with http:// or https://"})),
-        ));
+        return Err(bad_request("Invalid webhook URL: must start with http:// or https://".to_string()));
     }
-
-    // Validate events filter
-    let events = body.events.trim().to_string();
+    let events = events.trim().to_string();
     if events.is_empty() {
-        return Err((
-            Status::BadRequest,
-            Json(serde_json::json!({"error": "Events filter cannot be empty. Use '*' for all events."})),
-        ));
+        return Err(bad_request("Events filter cannot be empty. Use '*' for all events.".to_string()));
     }
     if events != "*" {
-        let valid_events = [
-            "message",
-            "message_edited",
-            "message_deleted",
-            "file_uploaded",
-            "file_deleted",
-            "reaction_added",
-            "reaction_removed",
-            "message_pinned",
-            "message_unpinned",
-            "presence_joined",
-            "presence_left",
-            "room_updated",
-        ];
         for ev in events.split(',').map(|s| s.trim()) {
-            if !valid_events.contains(&ev) {
-                return Err((
-                    Status::BadRequest,
-                    Json(serde_json::json!({"error": format!("Unknown event type: '{}'. Valid events: {}", ev, valid_events.join(", "))})),
-                ));
+            if !valid.contains(&ev) {
+                return Err(bad_request(format!("Unknown event type: '{}'. Valid events: {}", ev, valid.join(", "))));
             }
         }
     }
+    Ok((url, events))
+}
+
+/// Validate custom headers and seal them for storage. None when there are none.
+pub(super) fn seal_headers(
+    secrets: &SecretBox,
+    headers: &BTreeMap<String, String>,
+) -> Result<Option<String>, (Status, Json<serde_json::Value>)> {
+    crate::webhooks::validate_custom_headers(headers).map_err(bad_request)?;
+    if headers.is_empty() {
+        return Ok(None);
+    }
+    let json = serde_json::to_string(headers).unwrap_or_default();
+    Ok(Some(secrets.seal(&json)))
+}
+
+/// Normalize optional PEM inputs (blank means none) and check that they load together.
+pub(super) fn check_tls(
+    client_cert: Option<&str>,
+    ca_cert: Option<&str>,
+) -> Result<(Option<String>, Option<String>), (Status, Json<serde_json::Value>)> {
+    let pem = |v: Option<&str>| v.map(str::trim).filter(|v| !v.is_empty()).map(str::to_string);
+    let (client_cert, ca_cert) = (pem(client_cert), pem(ca_cert));
+    if client_cert.is_some() || ca_cert.is_some() {
+        crate::webhooks::validate_tls(client_cert.as_deref(), ca_cert.as_deref()).map_err(bad_request)?;
+    }
+    Ok((client_cert, ca_cert))
+}
+
+#[post("/api/v1/rooms/<room_id>/webhooks", format = "json", data = "<body>")]
+pub fn create_webhook(
+    db: ScopedDb<'_>,
+    secrets: &State<SecretBox>,
+    room_id: &str,
+    admin: AdminKey,
+    body: Json<CreateWebhook>,
+) -> Result<Json<serde_json::Value>, (Status, Json<serde_json::Value>)> {
+    verify_room_admin(&db, room_id, &admin)?;
+    let conn = db.conn();
+
+    let (url, events) = validate_target(&body.url, &body.events, ROOM_EVENTS)?;
+
+    let headers = seal_headers(secrets, &body.headers)?;
+    let (client_cert, ca_cert) = check_tls(body.client_cert.as_deref(), body.ca_cert.as_deref())?;
 
     let id = uuid::Uuid::new_v4().to_string();
     let now = chrono::Utc::now().to_rfc3339();
 
     conn.execute(
-        "INSERT INTO webhooks (id, room_id, url, events, secret, created_by, created_at, active) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 1)",
-        params![&id, room_id, &url, &events, &body.secret, &body.created_by, &now],
+        "INSERT INTO webhooks (id, room_id, url, events, secret, created_by, created_at, active, headers, client_cert, ca_cert, ordered)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 1, ?8, ?9, ?10, ?11)",
+        params![
+            &id,
+            room_id,
+            &url,
+            &events,
+            &body.secret,
+            &body.created_by,
+            &now,
+            &headers,
+            client_cert.map(|pem| secrets.seal(&pem)),
+            &ca_cert,
+            body.ordered
+        ],
     )
     .map_err(|_e| {
         (
@@ -109,29 +175,52 @@ pub fn create_webhook(
         "url": url,
         "events": events,
         "has_secret": body.secret.is_some(),
+        "header_names": body.headers.keys().collect::<Vec<_>>(),
+        "has_client_cert": body.client_cert.as_deref().is_some_and(|c| !c.trim().is_empty()),
+        "has_ca_cert": ca_cert.is_some(),
+        "ordered": body.ordered,
         "created_by": body.created_by,
         "created_at": now,
         "active": true
     })))
 }
 
-#[get("/api/v1/rooms/<room_id>/webhooks")]
+#[get("/api/v1/rooms/<room_id>/webhooks?<envelope>")]
 pub fn list_webhooks(
-    db: &State<Db>,
+    db: ScopedDb<'_>,
+    secrets: &State<SecretBox>,
     room_id: &str,
+    envelope: Option<bool>,
     admin: AdminKey,
-) -> Result<Json<Vec<Webhook>>, (Status, Json<serde_json::Value>)> {
-    verify_room_admin(db, room_id, &admin)?;
+) -> Result<Json<ListOf<Webhook>>, (Status, Json<serde_json::Value>)> {
+    verify_room_admin(&db, room_id, &admin)?;
     let conn = db.conn();
 
     let mut stmt = conn
         .prepare(
-            "SELECT id, room_id, url, events, created_by, created_at, active FROM webhooks WHERE room_id = ?1 ORDER BY created_at DESC",
+            "SELECT id, room_id, url, events, created_by, created_at, active, failure_streak, last_success_at, last_failure_at, circuit_opened_at,
+                    headers, client_cert IS NOT NULL, ca_cert IS NOT NULL, ordered
+             FROM webhooks WHERE room_id = ?1 ORDER BY created_at DESC",
         )
         .map_err(|_| (Status::InternalServerError, Json(serde_json::json!({"error": "Internal server error"}))))?;
 
     let webhooks: Vec<Webhook> = stmt
         .query_map(params![room_id], |row| {
+            let active = row.get::<_, i32>(6)? != 0;
+            let failure_streak: i64 = row.get(7)?;
+            let circuit_opened_at: Option<String> = row.get(10)?;
+            let state = match (active, failure_streak, &circuit_opened_at) {
+                (false, _, Some(_)) => "open",
+                (false, _, None) => "disabled",
+                (true, 0, _) => "healthy",
+                (true, _, _) => "failing",
+            };
+            let header_names = row
+                .get::<_, Option<String>>(11)?
+                .and_then(|sealed| secrets.open(&sealed))
+                .and_then(|json| serde_json::from_str::<BTreeMap<String, String>>(&json).ok())
+                .map(|headers| headers.into_keys().collect())
+                .unwrap_or_default();
             Ok(Webhook {
                 id: row.get(0)?,
                 room_id: row.get(1)?,
@@ -139,14 +228,23 @@ pub fn list_webhooks(
                 events: row.get(3)?,
                 created_by: row.get(4)?,
                 created_at: row.get(5)?,
-                active: row.get::<_, i32>(6)? != 0,
+                active,
+                state: state.to_string(),
+                failure_streak,
+                last_success_at: row.get(8)?,
+                last_failure_at: row.get(9)?,
+                circuit_opened_at,
+                header_names,
+                has_client_cert: row.get(12)?,
+                has_ca_cert: row.get(13)?,
+                ordered: row.get::<_, i32>(14)? != 0,
             })
         })
         .map_err(|_| (Status::InternalServerError, Json(serde_json::json!({"error": "Internal server error"}))))?
         .filter_map(|r| r.ok())
         .collect();
 
-    Ok(Json(webhooks))
+    Ok(Json(ListOf::complete(webhooks, envelope)))
 }
 
 #[put(
@@ -155,13 +253,14 @@ pub fn list_webhooks(
     data = "<body>"
 )]
 pub fn update_webhook(
-    db: &State<Db>,
+    db: ScopedDb<'_>,
+    secrets: &State<SecretBox>,
     room_id: &str,
     webhook_id: &str,
     admin: AdminKey,
     body: Json<UpdateWebhook>,
 ) -> Result<Json<serde_json::Value>, (Status, Json<serde_json::Value>)> {
-    verify_room_admin(db, room_id, &admin)?;
+    verify_room_admin(&db, room_id, &admin)?;
     let conn = db.conn();
 
     // Verify webhook exists in this room
@@ -212,6 +311,36 @@ pub fn update_webhook(
         updates.push(format!("active = ?{}", idx));
         values.push(Box::new(active as i32));
         idx += 1;
+        // Any explicit change closes the circuit and starts the failure count over
+        updates.push("circuit_opened_at = NULL, failure_streak = 0, failing_since = NULL".to_string());
+    }
+    if let Some(ordered) = body.ordered {
+        updates.push(format!("ordered = ?{}", idx));
+        values.push(Box::new(ordered as i32));
+        idx += 1;
+    }
+    if let Some(ref headers) = body.headers {
+        updates.push(format!("headers = ?{}", idx));
+        values.push(Box::new(seal_headers(secrets, headers)?));
+        idx += 1;
+    }
+    if body.client_cert.is_some() || body.ca_cert.is_some() {
+        // Check the resulting pair, so a new client_cert is validated against the stored ca_cert
+        let stored_ca: Option<String> = conn
+            .query_row("SELECT ca_cert FROM webhooks WHERE id = ?1", params![webhook_id], |r| r.get(0))
+            .unwrap_or(None);
+        let ca_cert = body.ca_cert.as_deref().or(stored_ca.as_deref());
+        let (client_cert, ca_cert) = check_tls(body.client_cert.as_deref(), ca_cert)?;
+        if body.client_cert.is_some() {
+            updates.push(format!("client_cert = ?{}", idx));
+            values.push(Box::new(client_cert.map(|pem| secrets.seal(&pem))));
+            idx += 1;
+        }
+        if body.ca_cert.is_some() {
+            updates.push(format!("ca_cert = ?{}", idx));
+            values.push(Box::new(ca_cert));
+            idx += 1;
+        }
     }
 
     if updates.is_empty() {
@@ -246,12 +375,12 @@ pub fn update_webhook(
 
 #[delete("/api/v1/rooms/<room_id>/webhooks/<webhook_id>")]
 pub fn delete_webhook(
-    db: &State<Db>,
+    db: ScopedDb<'_>,
     room_id: &str,
     webhook_id: &str,
     admin: AdminKey,
 ) -> Result<Json<serde_json::Value>, (Status, Json<serde_json::Value>)> {
-    verify_room_admin(db, room_id, &admin)?;
+    verify_room_admin(&db, room_id, &admin)?;
     let conn = db.conn();
 
     let deleted = conn
@@ -283,13 +412,13 @@ pub struct DeliveryQuery {
 
 #[get("/api/v1/rooms/<room_id>/webhooks/<webhook_id>/deliveries?<query..>")]
 pub fn get_webhook_deliveries(
-    db: &State<Db>,
+    db: ScopedDb<'_>,
     room_id: &str,
     webhook_id: &str,
     admin: AdminKey,
     query: DeliveryQuery,
 ) -> Result<Json<Vec<WebhookDeliveryLog>>, (Status, Json<serde_json::Value>)> {
-    verify_room_admin(db, room_id, &admin)?;
+    verify_room_admin(&db, room_id, &admin)?;
     let conn = db.conn();
 
     // Verify webhook exists in this room
@@ -0,0 +1,241 @@
+use crate::db::{index_mentions, upsert_fts};
+use crate::namespaces::ScopedDb;
+use crate::models::{Message, RoomWelcome, SetRoomWelcome};
+use crate::senders::{SenderPolicy, ServerToken};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{delete, get, put, State};
+use rusqlite::{params, Connection};
+
+use super::AdminKey;
+
+/// Max pinned messages listed by `{pinned}`.
+const MAX_PINNED_IN_WELCOME: usize = 5;
+
+fn err(status: Status, msg: &str) -> (Status, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg})))
+}
+
+fn fetch_welcome(conn: &Connection, room_id: &str) -> Option<RoomWelcome> {
+    conn.prepare_cached(
+        "SELECT room_id, template, delivery, from_sender, created_at, updated_at FROM room_welcomes WHERE room_id = ?1",
+    )
+    .and_then(|mut s| {
+        s.query_row(params![room_id], |r| {
+            Ok(RoomWelcome {
+                room_id: r.get(0)?,
+                template: r.get(1)?,
+                delivery: r.get(2)?,
+                from: r.get(3)?,
+                created_at: r.get(4)?,
+                updated_at: r.get(5)?,
+            })
+        })
+    })
+    .ok()
+}
+
+/// Verify the room exists (and isn't a DM) and the admin key matches.
+fn check_admin(conn: &Connection, room_id: &str, admin: &AdminKey) -> Result<(), (Status, Json<serde_json::Value>)> {
+    let (key, room_type): (Option<String>, Option<String>) = conn
+        .query_row(
+            "SELECT admin_key, room_type FROM rooms WHERE id = ?1",
+            params![room_id],
+            |r| Ok((r.get(0)?, r.get(1)?)),
+        )
+        .map_err(|_| err(Status::NotFound, "Room not found"))?;
+    if key.as_deref() != Some(admin.0.as_str()) {
+        return Err(err(Status::Forbidden, "Invalid admin key for this room"));
+    }
+    if room_type.as_deref() == Some("dm") {
+        return Err(err(Status::BadRequest, "DM conversations can't have a welcome message"));
+    }
+    Ok(())
+}
+
+/// GET /api/v1/rooms/<room_id>/welcome — the room's welcome configuration (404 if none).
+#[get("/api/v1/rooms/<room_id>/welcome")]
+pub fn get_room_welcome(db: ScopedDb<'_>, room_id: &str) -> Result<Json<RoomWelcome>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    fetch_welcome(&conn, room_id)
+        .map(Json)
+        .ok_or_else(|| err(Status::NotFound, "No welcome message configured for this room"))
+}
+
+/// Check a welcome config; returns its trimmed (template, delivery, from) with defaults applied.
+pub(super) fn validate(body: &SetRoomWelcome) -> Result<(&str, &str, &str), (Status, Json<serde_json::Value>)> {
+    let template = body.template.trim();
+    if template.is_empty() || template.len() > 4000 {
+        return Err(err(Status::BadRequest, "Template must be 1-4000 characters"));
+    }
+    let delivery = body.delivery.as_deref().map(str::trim).unwrap_or("dm");
+    if !["dm", "room"].contains(&delivery) {
+        return Err(err(Status::BadRequest, "delivery must be 'dm' or 'room'"));
+    }
+    let from = body.from.as_deref().map(str::trim).unwrap_or("system");
+    if from.is_empty() || from.len() > 100 {
+        return Err(err(Status::BadRequest, "from must be 1-100 characters"));
+    }
+    Ok((template, delivery, from))
+}
+
+/// Set or replace the room's welcome with values already checked by [`validate`].
+pub(super) fn save(conn: &Connection, room_id: &str, template: &str, delivery: &str, from: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO room_welcomes (room_id, template, delivery, from_sender, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?5)
+         ON CONFLICT(room_id) DO UPDATE SET template = ?2, delivery = ?3, from_sender = ?4, updated_at = ?5",
+        params![room_id, template, delivery, from, chrono::Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+/// PUT /api/v1/rooms/<room_id>/welcome — set or replace the welcome template (admin key).
+/// Welcomes are sent as `from`, so any reserved name but the default "system" also needs the
+/// server token.
+#[put("/api/v1/rooms/<room_id>/welcome", format = "json", data = "<body>")]
+pub fn set_room_welcome(
+    db: ScopedDb<'_>,
+    sender_policy: &State<SenderPolicy>,
+    server_token: ServerToken,
+    room_id: &str,
+    admin: AdminKey,
+    body: Json<SetRoomWelcome>,
+) -> Result<Json<RoomWelcome>, (Status, Json<serde_json::Value>)> {
+    let (template, delivery, from) = validate(&body)?;
+    if from != "system" {
+        sender_policy.check(from, &server_token)?;
+    }
+
+    let conn = db.conn();
+    check_admin(&conn, room_id, &admin)?;
+    save(&conn, room_id, template, delivery, from)
+        .map_err(|_| err(Status::InternalServerError, "Internal server error"))?;
+
+    fetch_welcome(&conn, room_id)
+        .map(Json)
+        .ok_or_else(|| err(Status::InternalServerError, "Internal server error"))
+}
+
+/// DELETE /api/v1/rooms/<room_id>/welcome — stop welcoming new senders (admin key).
+#[delete("/api/v1/rooms/<room_id>/welcome")]
+pub fn delete_room_welcome(
+    db: ScopedDb<'_>,
+    room_id: &str,
+    admin: AdminKey,
+) -> Result<Json<serde_json::Value>, (Status, Json<serde_json::Value>)> {
+    let conn = db.conn();
+    check_admin(&conn, room_id, &admin)?;
+    let deleted = conn
+        .execute("DELETE FROM room_welcomes WHERE room_id = ?1", params![room_id])
+        .unwrap_or(0);
+    if deleted == 0 {
+        return Err(err(Status::NotFound, "No welcome message configured for this room"));
+    }
+    Ok(Json(serde_json::json!({"deleted": true, "room_id": room_id})))
+}
+
+/// Fill in template variables for one sender.
+fn render(conn: &Connection, room_id: &str, template: &str, sender: &str) -> String {
+    let (room_name, description): (String, String) = conn
+        .query_row(
+            "SELECT name, COALESCE(description, '') FROM rooms WHERE id = ?1",
+            params![room_id],
+            |r| Ok((r.get(0)?, r.get(1)?)),
+        )
+        .unwrap_or_default();
+
+    let pinned: Vec<String> = conn
+        .prepare("SELECT content FROM messages WHERE room_id = ?1 AND pinned_at IS NOT NULL ORDER BY pinned_at DESC LIMIT ?2")
+        .and_then(|mut s| {
+            s.query_map(params![room_id, MAX_PINNED_IN_WELCOME as i64], |r| r.get::<_, String>(0))
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .unwrap_or_default();
+    let pinned_list = pinned
+        .iter()
+        .map(|c| {
+            let line = c.lines().next().unwrap_or("");
+            match line.char_indices().nth(140) {
+                Some((i, _)) => format!("- {}…", &line[..i]),
+                None => format!("- {line}"),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let links = pinned
+        .iter()
+        .flat_map(|c| c.split_whitespace())
+        .filter(|w| w.starts_with("http://") || w.starts_with("https://"))
+        .map(|w| w.trim_end_matches([')', '.', ',', '>']).to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    template
+        .replace("{sender}", sender)
+        .replace("{room_name}", &room_name)
+        .replace("{room_description}", &description)
+        .replace("{pinned}", &pinned_list)
+        .replace("{pinned_links}", &links)
+}
+
+/// Welcome `sender` to `room_id` if the room has a template and they haven't been welcomed yet.
+/// Returns the delivered message (a DM or an in-room system message) for the caller to publish.
+pub(super) fn deliver_welcome(conn: &Connection, room_id: &str, sender: &str) -> Option<Message> {
+    let welcome = fetch_welcome(conn, room_id)?;
+    if welcome.from == sender {
+        return None;
+    }
+    let now = chrono::Utc::now().to_rfc3339();
+    let claimed = conn
+        .execute(
+            "INSERT OR IGNORE INTO room_welcome_deliveries (room_id, sender, delivered_at) VALUES (?1, ?2, ?3)",
+            params![room_id, sender, &now],
+        )
+        .unwrap_or(0);
+    if claimed == 0 {
+        return None;
+    }
+
+    let content = render(conn, room_id, &welcome.template, sender);
+    if welcome.delivery == "room" {
+        return crate::db::insert_system_message(
+            conn,
+            room_id,
+            "welcome",
+            &content,
+            serde_json::json!({"sender": sender}),
+        );
+    }
+
+    let (dm_room_id, _) = super::dm::get_or_create_dm_room(conn, &welcome.from, sender).ok()?;
+    let id = uuid::Uuid::new_v4().to_string();
+    let metadata = serde_json::json!({"welcome": {"room_id": room_id}});
+    let seq: i64 = conn
+        .query_row("SELECT COALESCE(MAX(seq), 0) + 1 FROM messages", [], |r| r.get(0))
+        .ok()?;
+    conn.execute(
+        "INSERT INTO messages (id, room_id, sender, content, metadata, created_at, sender_type, seq) VALUES (?1, ?2, ?3, ?4, ?5, ?6, 'system', ?7)",
+        params![&id, &dm_room_id, &welcome.from, &content, metadata.to_string(), &now, seq],
+    )
+    .ok()?;
+    upsert_fts(conn, &id);
+    index_mentions(conn, &id);
+    Some(Message {
+        id,
+        room_id: dm_room_id,
+        sender: welcome.from,
+        content,
+        metadata,
+        created_at: now,
+        edited_at: None,
+        reply_to: None,
+        sender_type: Some("system".to_string()),
+        seq,
+        pinned_at: None,
+        pinned_by: None,
+        edit_count: 0,
+        kind: "message".to_string(),
+        local_time: None,
+    })
+}
@@ -0,0 +1,105 @@
+//! Scheduled messages. A post sent with `scheduled_at` is queued in `deferred_messages` (the
+//! quiet hours queue) with that time, and a background task delivers it once it is due, under
+//! the id it was given. A scheduled agent post that comes due during the room's quiet hours is
+//! handed to quiet hours and waits for the window to end like any other.
+
+use crate::events::{ChatEvent, Published};
+use crate::models::{DeferredMessage, Message};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
+use tokio::sync::broadcast;
+
+/// Interval between checks for scheduled messages that are due (seconds).
+const SCHEDULER_INTERVAL_SECS: u64 = 10;
+
+/// How far ahead a message can be scheduled.
+pub const MAX_SCHEDULE_AHEAD_DAYS: i64 = 365;
+
+/// Parse a requested delivery time: RFC 3339, in the future, at most [`MAX_SCHEDULE_AHEAD_DAYS`] away.
+pub fn parse_scheduled_at(s: &str) -> Result<DateTime<Utc>, String> {
+    let at = DateTime::parse_from_rfc3339(s.trim())
+        .map_err(|_| "scheduled_at must be an RFC 3339 timestamp (e.g. 2026-05-01T09:00:00Z)".to_string())?
+        .with_timezone(&Utc);
+    let now = Utc::now();
+    if at <= now {
+        return Err("scheduled_at must be in the future".to_string());
+    }
+    if at > now + chrono::Duration::days(MAX_SCHEDULE_AHEAD_DAYS) {
+        return Err(format!("scheduled_at can be at most {MAX_SCHEDULE_AHEAD_DAYS} days ahead"));
+    }
+    Ok(at)
+}
+
+/// Scheduled posts waiting in a room, soonest first, optionally only one sender's.
+pub fn pending(conn: &Connection, room_id: &str, sender: Option<&str>) -> Vec<DeferredMessage> {
+    crate::quiet_hours::held_where(
+        conn,
+        "room_id = ?1 AND scheduled_at IS NOT NULL AND (?2 IS NULL OR sender = ?2) ORDER BY scheduled_at, rowid",
+        params![room_id, sender],
+    )
+}
+
+/// Deliver every scheduled post that is due, oldest first. Agent posts due during their room's
+/// quiet hours move to the quiet hours queue instead. Returns each delivered message with its
+/// outbox event id, for the caller to publish.
+pub fn deliver_due(conn: &Connection) -> Vec<(Message, i64)> {
+    let now = Utc::now();
+    let due: Vec<(String, String, String, Option<String>)> = conn
+        .prepare(
+            "SELECT id, room_id, sender, sender_type FROM deferred_messages
+             WHERE scheduled_at IS NOT NULL AND scheduled_at <= ?1 ORDER BY scheduled_at, rowid",
+        )
+        .and_then(|mut s| {
+            s.query_map(params![now.to_rfc3339()], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)))
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .unwrap_or_default();
+    let mut delivered = Vec::new();
+    for (id, room_id, sender, sender_type) in due {
+        let own_name = crate::db::resolve_sender(conn, &sender);
+        if crate::quiet_hours::is_agent(conn, &own_name, sender_type.as_deref())
+            && let Some(ends_at) = crate::quiet_hours::active_until(conn, &room_id, now)
+        {
+            if let Err(e) = conn.execute(
+                "UPDATE deferred_messages SET scheduled_at = NULL, queued_at = ?1, deliver_at = ?2 WHERE id = ?3",
+                params![now.to_rfc3339(), ends_at.to_rfc3339(), &id],
+            ) {
+                eprintln!("⚠️ Scheduler: failed to hand message {id} to quiet hours: {e}");
+            }
+            continue;
+        }
+        match crate::quiet_hours::deliver(conn, &id) {
+            Ok(sent) => delivered.push(sent),
+            Err(e) => eprintln!("⚠️ Scheduler: failed to deliver scheduled message {id}: {e}"),
+        }
+    }
+    delivered
+}
+
+/// Delivers due scheduled messages from one database; `namespace` tags the events.
+pub fn spawn_scheduler(db_path: String, events: broadcast::Sender<Published>, namespace: Option<String>) {
+    tokio::spawn(async move {
+        let conn = match crate::db::open(&db_path) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("⚠️ Scheduler: failed to open DB: {e}");
+                return;
+            }
+        };
+        crate::db::DbConfig::from_env().apply(&conn).ok();
+
+        loop {
+            tokio::time::sleep(std::time::Duration::from_secs(SCHEDULER_INTERVAL_SECS)).await;
+            for (msg, event_id) in deliver_due(&conn) {
+                let _ = events.send(Published {
+                    event: ChatEvent::NewMessage(msg),
+                    request_id: None,
+                    trace_context: None,
+                    event_id: Some(event_id),
+                    namespace: namespace.clone(),
+                });
+                crate::outbox::mark_published(&conn, event_id);
+            }
+        }
+    });
+}
@@ -0,0 +1,126 @@
+//! Encryption at rest for credentials the server has to replay, like webhook auth headers and
+//! mTLS client keys. Values are sealed with AES-256-GCM under a key that lives outside the
+//! database: `SECRETS_KEY` (32 bytes, base64 or hex) or, when unset, a key file generated next
+//! to the database on first use. A copied database alone doesn't reveal them.
+
+use aes_gcm::aead::Aead;
+use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use rand_core::{OsRng, RngCore};
+use std::path::{Path, PathBuf};
+
+/// Prefix of sealed values, so the format can change without guessing.
+const SEALED_PREFIX: &str = "v1:";
+
+/// Name of the generated key file, in the database's directory.
+const KEY_FILE: &str = "secrets.key";
+
+#[derive(Clone)]
+pub struct SecretBox {
+    key: [u8; 32],
+}
+
+impl std::fmt::Debug for SecretBox {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SecretBox(..)")
+    }
+}
+
+fn decode_key(encoded: &str) -> Option<[u8; 32]> {
+    let encoded = encoded.trim();
+    let bytes = hex::decode(encoded).ok().or_else(|| STANDARD.decode(encoded).ok())?;
+    bytes.try_into().ok()
+}
+
+fn key_file(db_path: &str) -> PathBuf {
+    Path::new(db_path)
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."))
+        .join(KEY_FILE)
+}
+
+impl SecretBox {
+    pub fn new(key: [u8; 32]) -> Self {
+        SecretBox { key }
+    }
+
+    /// The key from `SECRETS_KEY`, else the key file beside `db_path` (created, owner-only, if missing).
+    pub fn load(db_path: &str) -> Result<Self, String> {
+        if let Ok(encoded) = std::env::var("SECRETS_KEY") {
+            return decode_key(&encoded)
+                .map(Self::new)
+                .ok_or_else(|| "SECRETS_KEY must be 32 bytes, base64 or hex encoded".to_string());
+        }
+        let path = key_file(db_path);
+        let read = |path: &Path| {
+            std::fs::read_to_string(path).ok().map(|existing| {
+                decode_key(&existing)
+                    .map(Self::new)
+                    .ok_or_else(|| format!("{} does not hold a 32-byte key", path.display()))
+            })
+        };
+        if let Some(existing) = read(&path) {
+            return existing;
+        }
+        let mut key = [0u8; 32];
+        OsRng.fill_bytes(&mut key);
+        match write_key_file(&path, &hex::encode(key)) {
+            Ok(()) => Ok(Self::new(key)),
+            // Another server on the same directory got there first
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                read(&path).unwrap_or_else(|| Err(format!("failed to read {}", path.display())))
+            }
+            Err(e) => Err(format!("failed to write {}: {e}", path.display())),
+        }
+    }
+
+    /// Encrypt `plaintext` into a printable `v1:<base64 nonce+ciphertext>` string.
+    pub fn seal(&self, plaintext: &str) -> String {
+        let cipher = Aes256Gcm::new_from_slice(&self.key).expect("32-byte key");
+        let mut nonce = [0u8; 12];
+        OsRng.fill_bytes(&mut nonce);
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce), plaintext.as_bytes())
+            .expect("AES-GCM encryption does not fail for in-memory buffers");
+        let mut sealed = nonce.to_vec();
+        sealed.extend(ciphertext);
+        format!("{SEALED_PREFIX}{}", STANDARD.encode(sealed))
+    }
+
+    /// Decrypt a value from [`SecretBox::seal`]. None if it was sealed under another key or mangled.
+    pub fn open(&self, sealed: &str) -> Option<String> {
+        let bytes = STANDARD.decode(sealed.strip_prefix(SEALED_PREFIX)?).ok()?;
+        if bytes.len() < 12 {
+            return None;
+        }
+        let (nonce, ciphertext) = bytes.split_at(12);
+        let cipher = Aes256Gcm::new_from_slice(&self.key).ok()?;
+        let plaintext = cipher.decrypt(Nonce::from_slice(nonce), ciphertext).ok()?;
+        String::from_utf8(plaintext).ok()
+    }
+}
+
+#[cfg(unix)]
+fn write_key_file(path: &Path, contents: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)?;
+    file.write_all(contents.as_bytes())
+}
+
+#[cfg(not(unix))]
+fn write_key_file(path: &Path, contents: &str) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, contents)
+}
@@ -0,0 +1,310 @@
+//! Fixture generator for local development and demos (`POST /api/v1/dev/seed`).
+//!
+//! Produces a plausible multi-agent history: a cast of agent and human profiles chatting across
+//! topic rooms, with threaded replies, @mentions, reactions, pins, and small attached files.
+//! Output is deterministic for a given `seed`, so demos and bug reports can be reproduced.
+
+use chrono::{Duration, Utc};
+use rusqlite::{params, Connection};
+
+use crate::models::SeedSummary;
+
+/// Largest accepted `rooms` value.
+pub const MAX_SEED_ROOMS: usize = 100;
+
+/// Largest accepted `messages` value.
+pub const MAX_SEED_MESSAGES: usize = 100_000;
+
+/// Whether dev routes are mounted. Off unless `DEV_ROUTES_ENABLED` is `1`/`true`.
+pub fn enabled() -> bool {
+    std::env::var("DEV_ROUTES_ENABLED")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+#[derive(Debug, Clone)]
+pub struct SeedOptions {
+    pub rooms: usize,
+    /// Total messages, spread across the rooms
+    pub messages: usize,
+    pub seed: u64,
+}
+
+impl Default for SeedOptions {
+    fn default() -> Self {
+        SeedOptions {
+            rooms: 10,
+            messages: 5000,
+            seed: 42,
+        }
+    }
+}
+
+/// (sender, display_name, sender_type, bio)
+const CAST: [(&str, &str, &str, &str); 10] = [
+    ("forge", "Forge", "agent", "Builds and ships. Owns CI and release tooling."),
+    ("drift", "Drift", "agent", "Monitors infra and pages people at 3am so you don't have to."),
+    ("lux", "Lux", "agent", "Frontend and design-system agent."),
+    ("quill", "Quill", "agent", "Writes the docs nobody else will."),
+    ("sentry", "Sentry", "agent", "Security reviews, dependency audits, secret scanning."),
+    ("ledger", "Ledger", "agent", "Tracks costs, quotas, and usage reports."),
+    ("atlas", "Atlas", "agent", "Planner. Breaks big asks into tickets."),
+    ("echo", "Echo", "agent", "Test runner and flaky-test hunter."),
+    ("nanook", "Nanook", "human", "Operator. Approves deploys."),
+    ("juniper", "Juniper", "human", "Product lead."),
+];
+
+const TOPICS: [(&str, &str); 12] = [
+    ("general", "Everything else"),
+    ("deploys", "Release coordination"),
+    ("incidents", "Live incident response"),
+    ("frontend", "UI work and reviews"),
+    ("infra", "Hosts, containers, and networking"),
+    ("security", "Audits and advisories"),
+    ("planning", "Roadmap and ticket grooming"),
+    ("testing", "CI results and flaky tests"),
+    ("docs", "Documentation updates"),
+    ("costs", "Usage and budget tracking"),
+    ("research", "Experiments and findings"),
+    ("random", "Off-topic"),
+];
+
+const OPENERS: [&str; 16] = [
+    "Starting on {thing} now, will report back.",
+    "Heads up: {thing} is failing on main since the last merge.",
+    "Finished {thing}. PR is up for review.",
+    "Can someone take a look at {thing}? I'm blocked on it.",
+    "Status update on {thing}: about 70% done, no blockers.",
+    "Rolled back {thing} after error rates spiked.",
+    "Proposal: we split {thing} into two smaller tasks.",
+    "Benchmarks for {thing} look good — p99 down by a third.",
+    "I'm seeing intermittent timeouts in {thing}.",
+    "Reminder that {thing} is due end of day.",
+    "Kicked off a fresh run of {thing}.",
+    "Does anyone know who owns {thing}?",
+    "{thing} is green again.",
+    "Opened a ticket for {thing} so we don't lose track.",
+    "Merged {thing}. Deploying to staging.",
+    "Quick question about {thing} — is the config change intentional?",
+];
+
+const REPLIES: [&str; 12] = [
+    "On it.",
+    "Thanks, looking now.",
+    "LGTM, ship it.",
+    "Can you share the logs?",
+    "Reproduced locally — same error.",
+    "That was me, sorry. Fix incoming.",
+    "Agreed, let's do that.",
+    "I'd hold off until the migration lands.",
+    "Confirmed fixed on my side.",
+    "Added a test for this case.",
+    "Let's sync on this after standup.",
+    "Nice work!",
+];
+
+const THINGS: [&str; 16] = [
+    "the auth refactor",
+    "the nightly build",
+    "the search index rebuild",
+    "webhook retries",
+    "the v2 API docs",
+    "the staging deploy",
+    "the cost report",
+    "the dependency audit",
+    "the flaky SSE test",
+    "the room export",
+    "the dashboard redesign",
+    "the DB migration",
+    "rate limiting",
+    "the mDNS discovery bug",
+    "the release notes",
+    "the memory leak in the worker",
+];
+
+const EMOJI: [&str; 8] = ["👍", "🎉", "👀", "🚀", "✅", "❤️", "😂", "🔥"];
+
+/// Small deterministic PRNG (xorshift64*) so fixtures are reproducible without a rand dependency.
+/// Also drives the reproducible message sampler.
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Uniform in (0, 1].
+    pub(crate) fn unit(&mut self) -> f64 {
+        ((self.next() >> 11) + 1) as f64 / (1u64 << 53) as f64
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n.max(1) as u64) as usize
+    }
+
+    fn chance(&mut self, percent: u64) -> bool {
+        self.next() % 100 < percent
+    }
+
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+}
+
+/// Generate fixture data in a single transaction. Room names get a short unique suffix,
+/// so seeding twice adds a second batch instead of failing on name collisions.
+pub fn seed(conn: &mut Connection, opts: &SeedOptions) -> rusqlite::Result<SeedSummary> {
+    let mut rng = Rng::new(opts.seed);
+    let mut summary = SeedSummary {
+        rooms: Vec::new(),
+        messages: 0,
+        replies: 0,
+        reactions: 0,
+        pins: 0,
+        files: 0,
+        profiles: 0,
+    };
+    let batch = uuid::Uuid::new_v4().to_string()[..6].to_string();
+    let now = Utc::now();
+    // History spans the last week, oldest first
+    let span_secs = 7 * 24 * 3600i64;
+    let step = (span_secs / opts.messages.max(1) as i64).max(1);
+    let start = now - Duration::seconds(step * opts.messages as i64);
+
+    let tx = conn.transaction()?;
+
+    for (sender, display_name, sender_type, bio) in CAST {
+        let changed = tx.execute(
+            "INSERT OR IGNORE INTO profiles (sender, display_name, sender_type, bio, metadata, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, '{}', ?5, ?5)",
+            params![sender, display_name, sender_type, bio, now.to_rfc3339()],
+        )?;
+        summary.profiles += changed;
+    }
+
+    let mut rooms: Vec<String> = Vec::new();
+    for r in 0..opts.rooms {
+        let (topic, description) = TOPICS[r % TOPICS.len()];
+        let name = if r < TOPICS.len() {
+            format!("{topic}-{batch}")
+        } else {
+            format!("{topic}-{}-{batch}", r / TOPICS.len() + 1)
+        };
+        let id = uuid::Uuid::new_v4().to_string();
+        tx.execute(
+            "INSERT INTO rooms (id, name, description, created_by, created_at, updated_at, admin_key)
+             VALUES (?1, ?2, ?3, 'seed', ?4, ?5, ?6)",
+            params![&id, &name, description, start.to_rfc3339(), now.to_rfc3339(), crate::db::generate_admin_key()],
+        )?;
+        rooms.push(id);
+        summary.rooms.push(name);
+    }
+    if rooms.is_empty() {
+        tx.commit()?;
+        return Ok(summary);
+    }
+
+    let mut seq: i64 = tx.query_row("SELECT COALESCE(MAX(seq), 0) FROM messages", [], |r| r.get(0))?;
+    // Recent top-level messages per room, as reply/thread targets
+    let mut recent: Vec<Vec<String>> = vec![Vec::new(); rooms.len()];
+
+    for m in 0..opts.messages {
+        let room_idx = rng.below(rooms.len());
+        let room_id = &rooms[room_idx];
+        let (sender, _, sender_type, _) = *rng.pick(&CAST);
+        let created_at = (start + Duration::seconds(step * m as i64 + rng.below(step as usize) as i64)).to_rfc3339();
+        let id = uuid::Uuid::new_v4().to_string();
+        seq += 1;
+
+        let reply_to = if !recent[room_idx].is_empty() && rng.chance(25) {
+            let pool = &recent[room_idx];
+            Some(pool[pool.len() - 1 - rng.below(pool.len().min(5))].clone())
+        } else {
+            None
+        };
+        let mut content = if reply_to.is_some() {
+            rng.pick(&REPLIES).to_string()
+        } else {
+            rng.pick(&OPENERS).replace("{thing}", rng.pick(&THINGS))
+        };
+        if rng.chance(10) {
+            let (other, ..) = *rng.pick(&CAST);
+            if other != sender {
+                content = format!("@{other} {content}");
+            }
+        }
+
+        tx.execute(
+            "INSERT INTO messages (id, room_id, sender, content, metadata, created_at, reply_to, sender_type, seq)
+             VALUES (?1, ?2, ?3, ?4, '{}', ?5, ?6, ?7, ?8)",
+            params![&id, room_id, sender, &content, &created_at, &reply_to, sender_type, seq],
+        )?;
+        crate::db::upsert_fts(&tx, &id);
+        crate::db::index_mentions(&tx, &id);
+        summary.messages += 1;
+
+        if reply_to.is_some() {
+            summary.replies += 1;
+        } else {
+            recent[room_idx].push(id.clone());
+            if recent[room_idx].len() > 20 {
+                recent[room_idx].remove(0);
+            }
+        }
+
+        if rng.chance(15) {
+            for _ in 0..=rng.below(3) {
+                let (reactor, ..) = *rng.pick(&CAST);
+                summary.reactions += tx.execute(
+                    "INSERT OR IGNORE INTO message_reactions (id, message_id, sender, emoji, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![uuid::Uuid::new_v4().to_string(), &id, reactor, rng.pick(&EMOJI), &created_at],
+                )?;
+            }
+        }
+
+        if reply_to.is_none() && rng.chance(1) {
+            let (pinner, ..) = *rng.pick(&CAST);
+            tx.execute(
+                "UPDATE messages SET pinned_at = ?1, pinned_by = ?2 WHERE id = ?3",
+                params![&created_at, pinner, &id],
+            )?;
+            summary.pins += 1;
+        }
+
+        if rng.chance(1) {
+            let thing = rng.pick(&THINGS);
+            let body = format!("# Notes on {thing}\n\n- owner: {sender}\n- status: in progress\n");
+            let sha256 = crate::db::store_file_blob(&tx, body.as_bytes())?;
+            tx.execute(
+                "INSERT INTO files (id, room_id, sender, filename, content_type, size, data, created_at, sha256) VALUES (?1, ?2, ?3, ?4, 'text/markdown', ?5, x'', ?6, ?7)",
+                params![
+                    uuid::Uuid::new_v4().to_string(),
+                    room_id,
+                    sender,
+                    format!("{}.md", thing.trim_start_matches("the ").replace(' ', "-")),
+                    body.len() as i64,
+                    &created_at,
+                    &sha256
+                ],
+            )?;
+            summary.files += 1;
+        }
+    }
+
+    tx.execute(
+        &format!(
+            "UPDATE rooms SET updated_at = ? WHERE id IN ({})",
+            rooms.iter().map(|_| "?").collect::<Vec<_>>().join(",")
+        ),
+        rusqlite::params_from_iter(std::iter::once(now.to_rfc3339()).chain(rooms.iter().cloned())),
+    )?;
+    tx.commit()?;
+    Ok(summary)
+}
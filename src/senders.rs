@@ -0,0 +1,115 @@
+//! Reserved sender names.
+//!
+//! Names like `system` and `admin` carry authority with humans reading a room, so they can only be
+//! posted under when the request carries the server token. Without a configured token they are
+//! unusable over the API entirely; server-generated messages (tombstones, seeds) are unaffected.
+
+use std::env;
+
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::serde::json::Json;
+
+/// Environment variables:
+/// - `PROTECTED_SENDERS` — Comma-separated reserved sender names, matched case-insensitively
+///   (default: `system,admin`; set to an empty string to disable)
+/// - `SERVER_TOKEN` — Token that unlocks reserved names, sent as `X-Server-Token` or
+///   `Authorization: Bearer` (default: unset, reserved names are rejected outright)
+#[derive(Debug, Clone)]
+pub struct SenderPolicy {
+    pub protected: Vec<String>,
+    pub server_token: Option<String>,
+}
+
+impl Default for SenderPolicy {
+    fn default() -> Self {
+        Self {
+            protected: vec!["system".to_string(), "admin".to_string()],
+            server_token: None,
+        }
+    }
+}
+
+impl SenderPolicy {
+    pub fn from_env() -> Self {
+        let mut policy = Self::default();
+        if let Ok(val) = env::var("PROTECTED_SENDERS") {
+            policy.protected = val
+                .split(',')
+                .map(|s| s.trim().to_lowercase())
+                .filter(|s| !s.is_empty())
+                .collect();
+        }
+        if let Ok(val) = env::var("SERVER_TOKEN")
+            && !val.trim().is_empty()
+        {
+            policy.server_token = Some(val.trim().to_string());
+        }
+        policy
+    }
+
+    pub fn is_protected(&self, sender: &str) -> bool {
+        let sender = sender.trim();
+        self.protected.iter().any(|p| p.eq_ignore_ascii_case(sender))
+    }
+
+    /// Reject a reserved sender name unless one of the presented credentials is the server token.
+    pub fn check(&self, sender: &str, token: &ServerToken) -> Result<(), (Status, Json<serde_json::Value>)> {
+        if !self.is_protected(sender) {
+            return Ok(());
+        }
+        let authorized = self
+            .server_token
+            .as_deref()
+            .is_some_and(|expected| token.0.iter().any(|t| t == expected));
+        if authorized {
+            return Ok(());
+        }
+        Err((
+            Status::Forbidden,
+            Json(serde_json::json!({
+                "error": format!("Sender name '{}' is reserved and requires the server token", sender.trim())
+            })),
+        ))
+    }
+
+    /// Require the server token outright, for server-wide admin endpoints.
+    pub fn check_server_token(&self, token: &ServerToken) -> Result<(), (Status, Json<serde_json::Value>)> {
+        let Some(expected) = self.server_token.as_deref() else {
+            return Err((
+                Status::Forbidden,
+                Json(serde_json::json!({"error": "Server admin endpoints are disabled (SERVER_TOKEN is not set)"})),
+            ));
+        };
+        if token.0.iter().any(|t| t == expected) {
+            return Ok(());
+        }
+        Err((
+            Status::Forbidden,
+            Json(serde_json::json!({"error": "This endpoint requires the server token"})),
+        ))
+    }
+}
+
+/// Credentials that might be the server token: `X-Server-Token` and any `Authorization: Bearer`
+/// value. Never fails — routes that don't post as a reserved name ignore it.
+#[derive(Debug, Clone, Default)]
+pub struct ServerToken(pub Vec<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ServerToken {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let mut candidates = Vec::new();
+        if let Some(token) = req.headers().get_one("X-Server-Token") {
+            candidates.push(token.trim().to_string());
+        }
+        if let Some(auth) = req.headers().get_one("Authorization")
+            && let Some(token) = auth.strip_prefix("Bearer ")
+        {
+            candidates.push(token.trim().to_string());
+        }
+        Outcome::Success(ServerToken(candidates))
+    }
+}
@@ -0,0 +1,88 @@
+//! Client session tokens: lightweight, unauthenticated ids that tell a sender's devices and
+//! instances apart, so typing and presence can be tracked per device. A client sends its token
+//! as `X-Client-Session` (or `?session=` where it can't set headers, like `EventSource`); on
+//! first contact it has none, so the server issues one in the `X-Client-Session` response header
+//! for the client to keep. `X-Client-Device` (or `?device=`) optionally names the device.
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::Header;
+use rocket::request::{FromRequest, Outcome};
+use rocket::{Request, Response};
+
+pub const SESSION_HEADER: &str = "X-Client-Session";
+pub const DEVICE_HEADER: &str = "X-Client-Device";
+
+/// Max length of a client-supplied session token or device name.
+const MAX_SESSION_LEN: usize = 100;
+
+/// The request's client session.
+#[derive(Debug, Clone)]
+pub struct ClientSession {
+    pub token: String,
+    pub device: Option<String>,
+    /// Issued by this request (the client sent none), so it is returned in the response
+    pub issued: bool,
+}
+
+fn sane(value: &str) -> Option<&str> {
+    let value = value.trim();
+    (!value.is_empty() && value.len() <= MAX_SESSION_LEN && value.chars().all(|c| c.is_ascii_graphic()))
+        .then_some(value)
+}
+
+impl ClientSession {
+    fn resolve(req: &Request<'_>) -> ClientSession {
+        let supplied = |header: &str, query: &str| {
+            req.headers()
+                .get_one(header)
+                .or_else(|| req.query_value::<&str>(query).and_then(|v| v.ok()))
+                .and_then(sane)
+                .map(String::from)
+        };
+        let device = supplied(DEVICE_HEADER, "device");
+        match supplied(SESSION_HEADER, "session") {
+            Some(token) => ClientSession {
+                token,
+                device,
+                issued: false,
+            },
+            None => ClientSession {
+                token: format!("cs_{}", uuid::Uuid::new_v4().simple()),
+                device,
+                issued: true,
+            },
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ClientSession {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let session = req.local_cache(|| Some(ClientSession::resolve(req))).clone();
+        Outcome::Success(session.unwrap_or_else(|| ClientSession::resolve(req)))
+    }
+}
+
+/// Fairing that hands a newly issued session token back in `X-Client-Session`. Only requests
+/// whose handler took a [`ClientSession`] get one.
+pub struct ClientSessionFairing;
+
+#[rocket::async_trait]
+impl Fairing for ClientSessionFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Client Sessions",
+            kind: Kind::Response,
+        }
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        if let Some(session) = req.local_cache(|| None::<ClientSession>)
+            && session.issued
+        {
+            res.set_header(Header::new(SESSION_HEADER, session.token.clone()));
+        }
+    }
+}
@@ -0,0 +1,346 @@
+//! Scheduled room snapshots: a per-room cron schedule writes the full message history as JSONL
+//! into the room's file store or under `SNAPSHOT_DIR`, giving restorable checkpoints that don't
+//! depend on how long retention keeps messages.
+
+use crate::events::{ChatEvent, Published};
+use crate::models::{FileInfo, RoomSnapshot};
+use chrono::{DateTime, Datelike, Duration, Timelike, Utc};
+use rusqlite::{params, Connection};
+use std::path::PathBuf;
+use tokio::sync::broadcast;
+
+/// Interval between checks for due schedules (seconds).
+const SNAPSHOT_INTERVAL_SECS: u64 = 60;
+
+/// Snapshots kept per room when the schedule doesn't say.
+pub const DEFAULT_KEEP: i64 = 7;
+
+/// Directory for `destination: "directory"` snapshots (`SNAPSHOT_DIR`); None disables that destination.
+pub fn snapshot_dir() -> Option<PathBuf> {
+    std::env::var("SNAPSHOT_DIR")
+        .ok()
+        .filter(|d| !d.trim().is_empty())
+        .map(PathBuf::from)
+}
+
+/// A five-field cron expression (`minute hour day-of-month month day-of-week`, UTC).
+/// Fields take `*`, numbers, ranges `a-b`, steps `*/n` or `a-b/n`, and comma lists; the
+/// `@hourly`, `@daily`, `@weekly`, and `@monthly` shorthands are accepted too. As in classic
+/// cron, when both day fields are restricted a day matching either one runs.
+#[derive(Debug, Clone)]
+pub struct Cron {
+    minutes: u64,
+    hours: u32,
+    days: u32,
+    months: u16,
+    weekdays: u8,
+    days_any: bool,
+    weekdays_any: bool,
+}
+
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step.parse().map_err(|_| format!("invalid step in '{part}'"))?;
+                if step == 0 {
+                    return Err(format!("invalid step in '{part}'"));
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (lo, hi) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            let a: u32 = a.parse().map_err(|_| format!("invalid range '{range}'"))?;
+            let b: u32 = b.parse().map_err(|_| format!("invalid range '{range}'"))?;
+            (a, b)
+        } else {
+            let v: u32 = range.parse().map_err(|_| format!("invalid value '{range}'"))?;
+            // `5/15` means 5, 20, 35, 50
+            (v, if part.contains('/') { max } else { v })
+        };
+        if lo < min || hi > max || lo > hi {
+            return Err(format!("'{part}' is outside {min}-{max}"));
+        }
+        for v in (lo..=hi).step_by(step as usize) {
+            mask |= 1 << v;
+        }
+    }
+    Ok(mask)
+}
+
+impl Cron {
+    pub fn parse(expr: &str) -> Result<Cron, String> {
+        let expr = match expr.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let &[minute, hour, day, month, weekday] = fields.as_slice() else {
+            return Err("cron must have 5 fields: minute hour day-of-month month day-of-week".to_string());
+        };
+        // 7 is Sunday too
+        let weekdays = parse_field(weekday, 0, 7)?;
+        Ok(Cron {
+            minutes: parse_field(minute, 0, 59)?,
+            hours: parse_field(hour, 0, 23)? as u32,
+            days: parse_field(day, 1, 31)? as u32,
+            months: parse_field(month, 1, 12)? as u16,
+            weekdays: ((weekdays | (weekdays >> 7)) & 0x7f) as u8,
+            days_any: day == "*",
+            weekdays_any: weekday == "*",
+        })
+    }
+
+    fn day_matches(&self, t: &DateTime<Utc>) -> bool {
+        let day = self.days & (1 << t.day()) != 0;
+        let weekday = self.weekdays & (1 << t.weekday().num_days_from_sunday()) != 0;
+        match (self.days_any, self.weekdays_any) {
+            (false, false) => day || weekday,
+            _ => day && weekday,
+        }
+    }
+
+    /// The first matching minute strictly after `after`, searching up to about four years ahead
+    /// (None for expressions like `0 0 31 2 *` that never match).
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut t = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = after + Duration::days(366 * 4);
+        while t <= limit {
+            if self.months & (1 << t.month()) == 0 {
+                // Jump to the first minute of next month
+                let (y, m) = if t.month() == 12 { (t.year() + 1, 1) } else { (t.year(), t.month() + 1) };
+                t = t.with_day(1)?.with_hour(0)?.with_minute(0)?.with_month(m)?.with_year(y)?;
+                continue;
+            }
+            if !self.day_matches(&t) {
+                t = t.with_hour(0)?.with_minute(0)? + Duration::days(1);
+                continue;
+            }
+            if self.hours & (1 << t.hour()) == 0 {
+                t = t.with_minute(0)? + Duration::hours(1);
+                continue;
+            }
+            if self.minutes & (1 << t.minute()) == 0 {
+                t += Duration::minutes(1);
+                continue;
+            }
+            return Some(t);
+        }
+        None
+    }
+}
+
+/// Spawns a background task that takes each scheduled snapshot when it falls due and publishes
+/// `file_uploaded` for snapshots stored as room files.
+pub fn spawn_snapshot_task(db_path: String, events: broadcast::Sender<Published>) {
+    tokio::spawn(async move {
+        let conn = match crate::db::open(&db_path) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("⚠️ Snapshot task: failed to open DB: {e}");
+                return;
+            }
+        };
+        crate::db::DbConfig::from_env().apply(&conn).ok();
+
+        loop {
+            tokio::time::sleep(std::time::Duration::from_secs(SNAPSHOT_INTERVAL_SECS)).await;
+            for (snapshot, file) in run_due(&conn, Utc::now()) {
+                eprintln!(
+                    "📸 Snapshot: {} messages from room {}",
+                    snapshot.message_count, snapshot.room_id
+                );
+                if let Some(file) = file {
+                    let _ = events.send(ChatEvent::FileUploaded(file).into());
+                }
+            }
+        }
+    });
+}
+
+/// Take every snapshot whose schedule is due at `now` and advance the schedules.
+pub fn run_due(conn: &Connection, now: DateTime<Utc>) -> Vec<(RoomSnapshot, Option<FileInfo>)> {
+    let due: Vec<(String, String, String, i64)> = conn
+        .prepare(
+            "SELECT room_id, cron, destination, keep FROM room_snapshot_schedules
+             WHERE next_run_at IS NOT NULL AND next_run_at <= ?1",
+        )
+        .and_then(|mut s| {
+            s.query_map(params![now.to_rfc3339()], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)))
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .unwrap_or_default();
+
+    let mut taken = Vec::new();
+    for (room_id, cron, destination, keep) in due {
+        let next = Cron::parse(&cron).ok().and_then(|c| c.next_after(now)).map(|t| t.to_rfc3339());
+        conn.execute(
+            "UPDATE room_snapshot_schedules SET last_run_at = ?1, next_run_at = ?2 WHERE room_id = ?3",
+            params![now.to_rfc3339(), next, &room_id],
+        )
+        .ok();
+        match take_snapshot(conn, &room_id, &destination, "schedule") {
+            Ok(result) => {
+                prune_snapshots(conn, &room_id, keep);
+                taken.push(result);
+            }
+            Err(e) => eprintln!("⚠️ Snapshot of room {room_id} failed: {e}"),
+        }
+    }
+    taken
+}
+
+/// Write the room's full history as JSONL — a header line describing the room, then one line per
+/// message in seq order — to `destination` ("files" or "directory") and record it.
+pub fn take_snapshot(
+    conn: &Connection,
+    room_id: &str,
+    destination: &str,
+    trigger: &str,
+) -> Result<(RoomSnapshot, Option<FileInfo>), String> {
+    let (room_name, description): (String, Option<String>) = conn
+        .query_row("SELECT name, description FROM rooms WHERE id = ?1", params![room_id], |r| {
+            Ok((r.get(0)?, r.get(1)?))
+        })
+        .map_err(|_| "room not found".to_string())?;
+    let now = Utc::now();
+    let taken_at = now.to_rfc3339();
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, seq, sender, sender_type, content, metadata, created_at, edited_at, reply_to,
+                    pinned_at, pinned_by, kind
+             FROM messages WHERE room_id = ?1 ORDER BY seq ASC",
+        )
+        .map_err(|e| e.to_string())?;
+    let messages: Vec<serde_json::Value> = stmt
+        .query_map(params![room_id], |r| {
+            let metadata: String = r.get(5)?;
+            Ok(serde_json::json!({
+                "id": r.get::<_, String>(0)?,
+                "seq": r.get::<_, i64>(1)?,
+                "sender": r.get::<_, String>(2)?,
+                "sender_type": r.get::<_, Option<String>>(3)?,
+                "content": r.get::<_, String>(4)?,
+                "metadata": serde_json::from_str::<serde_json::Value>(&metadata).unwrap_or(serde_json::json!({})),
+                "created_at": r.get::<_, String>(6)?,
+                "edited_at": r.get::<_, Option<String>>(7)?,
+                "reply_to": r.get::<_, Option<String>>(8)?,
+                "pinned_at": r.get::<_, Option<String>>(9)?,
+                "pinned_by": r.get::<_, Option<String>>(10)?,
+                "kind": r.get::<_, String>(11)?,
+            }))
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    let first_seq = messages.first().and_then(|m| m["seq"].as_i64());
+    let last_seq = messages.last().and_then(|m| m["seq"].as_i64());
+
+    let mut body = serde_json::json!({
+        "snapshot": {
+            "room_id": room_id,
+            "room_name": &room_name,
+            "description": description,
+            "taken_at": &taken_at,
+            "message_count": messages.len(),
+        }
+    })
+    .to_string();
+    body.push('\n');
+    for message in &messages {
+        body.push_str(&message.to_string());
+        body.push('\n');
+    }
+    let data = body.into_bytes();
+    let size = data.len() as i64;
+    let id = uuid::Uuid::new_v4().to_string();
+    let filename = format!("snapshot-{room_name}-{}.jsonl", now.format("%Y%m%dT%H%M%SZ"));
+
+    let (sha256, file, path) = match destination {
+        "directory" => {
+            let dir = snapshot_dir().ok_or("SNAPSHOT_DIR is not configured")?.join(room_id);
+            std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+            let path = dir.join(&filename);
+            std::fs::write(&path, &data).map_err(|e| e.to_string())?;
+            use sha2::{Digest, Sha256};
+            (hex::encode(Sha256::digest(&data)), None, Some(path.to_string_lossy().into_owned()))
+        }
+        _ => {
+            let sha256 = crate::db::store_file_blob(conn, &data).map_err(|e| e.to_string())?;
+            let file_id = uuid::Uuid::new_v4().to_string();
+            conn.execute(
+                "INSERT INTO files (id, room_id, sender, filename, content_type, size, data, created_at, sha256) VALUES (?1, ?2, 'system', ?3, 'application/x-ndjson', ?4, x'', ?5, ?6)",
+                params![&file_id, room_id, &filename, size, &taken_at, &sha256],
+            )
+            .map_err(|e| e.to_string())?;
+            let file = FileInfo {
+                id: file_id.clone(),
+                room_id: room_id.to_string(),
+                sender: "system".to_string(),
+                filename: filename.clone(),
+                content_type: "application/x-ndjson".to_string(),
+                size,
+                sha256: Some(sha256.clone()),
+                url: format!("/api/v1/files/{file_id}"),
+                created_at: taken_at.clone(),
+                expires_at: None,
+            };
+            (sha256, Some(file), None)
+        }
+    };
+
+    let file_id = file.as_ref().map(|f| f.id.clone());
+    conn.execute(
+        "INSERT INTO room_snapshots (id, room_id, taken_at, trigger, message_count, first_seq, last_seq, size, sha256, file_id, path)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        params![&id, room_id, &taken_at, trigger, messages.len() as i64, first_seq, last_seq, size, &sha256, &file_id, &path],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let snapshot = RoomSnapshot {
+        id,
+        room_id: room_id.to_string(),
+        taken_at,
+        trigger: trigger.to_string(),
+        message_count: messages.len() as i64,
+        first_seq,
+        last_seq,
+        size,
+        sha256,
+        url: file_id.as_ref().map(|f| format!("/api/v1/files/{f}")),
+        file_id,
+        path,
+    };
+    Ok((snapshot, file))
+}
+
+/// Drop all but the newest `keep` snapshots of a room, with their files.
+pub fn prune_snapshots(conn: &Connection, room_id: &str, keep: i64) {
+    let old: Vec<(String, Option<String>, Option<String>)> = conn
+        .prepare(
+            "SELECT id, file_id, path FROM room_snapshots WHERE room_id = ?1
+             ORDER BY taken_at DESC LIMIT -1 OFFSET ?2",
+        )
+        .and_then(|mut s| {
+            s.query_map(params![room_id, keep], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .unwrap_or_default();
+    for (id, file_id, path) in old {
+        if let Some(file_id) = file_id {
+            conn.execute("DELETE FROM files WHERE id = ?1", params![file_id]).ok();
+        }
+        if let Some(path) = path {
+            std::fs::remove_file(path).ok();
+        }
+        conn.execute("DELETE FROM room_snapshots WHERE id = ?1", params![id]).ok();
+    }
+}
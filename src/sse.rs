@@ -0,0 +1,128 @@
+//! SSE connection settings and a registry of live connections, so an operator can see who is
+//! subscribed where and spot a consumer that has stopped receiving events.
+
+use crate::models::SseConnection;
+use std::collections::HashMap;
+use std::env;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Server-wide SSE defaults.
+///
+/// Environment variables:
+/// - `SSE_HEARTBEAT_SECS` — Seconds between `heartbeat` events (default: 15)
+/// - `SSE_MAX_CONNECTION_SECS` — Close connections after this long with a `reconnect` event
+///   (default: 0 = never)
+pub struct StreamConfig {
+    pub heartbeat_secs: u64,
+    /// 0 = connections live until the client goes away
+    pub max_connection_secs: u64,
+}
+
+impl Default for StreamConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_secs: 15,
+            max_connection_secs: 0,
+        }
+    }
+}
+
+impl StreamConfig {
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Ok(val) = env::var("SSE_HEARTBEAT_SECS")
+            && let Ok(n) = val.parse::<u64>()
+            && n > 0
+        {
+            config.heartbeat_secs = n;
+        }
+        if let Ok(val) = env::var("SSE_MAX_CONNECTION_SECS")
+            && let Ok(n) = val.parse::<u64>()
+        {
+            config.max_connection_secs = n;
+        }
+        config
+    }
+}
+
+struct Entry {
+    info: SseConnection,
+    delivered: AtomicU64,
+    last_event_at: Mutex<Option<String>>,
+    lagged: AtomicU64,
+}
+
+/// Live SSE connections, keyed by a per-process connection id.
+#[derive(Clone, Default)]
+pub struct SseConnections {
+    inner: Arc<Mutex<HashMap<u64, Arc<Entry>>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl SseConnections {
+    /// Track a new connection until the returned guard is dropped. `info.id` is assigned here.
+    pub fn register(&self, mut info: SseConnection) -> ConnectionGuard {
+        info.id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let id = info.id;
+        let entry = Arc::new(Entry {
+            info,
+            delivered: AtomicU64::new(0),
+            last_event_at: Mutex::new(None),
+            lagged: AtomicU64::new(0),
+        });
+        self.inner
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(id, entry.clone());
+        ConnectionGuard {
+            registry: self.clone(),
+            entry,
+        }
+    }
+
+    /// Snapshot of every open connection, oldest first.
+    pub fn list(&self) -> Vec<SseConnection> {
+        let map = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let mut list: Vec<SseConnection> = map
+            .values()
+            .map(|e| SseConnection {
+                events_delivered: e.delivered.load(Ordering::Relaxed),
+                last_event_at: e.last_event_at.lock().unwrap_or_else(|e| e.into_inner()).clone(),
+                lagged_events: e.lagged.load(Ordering::Relaxed),
+                ..e.info.clone()
+            })
+            .collect();
+        list.sort_by_key(|c| c.id);
+        list
+    }
+}
+
+/// Deregisters its connection on drop (client disconnect or forced reconnect).
+pub struct ConnectionGuard {
+    registry: SseConnections,
+    entry: Arc<Entry>,
+}
+
+impl ConnectionGuard {
+    /// Count one event sent to the client (heartbeats aren't counted).
+    pub fn delivered(&self) {
+        self.entry.delivered.fetch_add(1, Ordering::Relaxed);
+        *self.entry.last_event_at.lock().unwrap_or_else(|e| e.into_inner()) = Some(chrono::Utc::now().to_rfc3339());
+    }
+
+    /// Record events the connection missed because it fell behind the broadcast channel.
+    pub fn lagged(&self, missed: u64) {
+        self.entry.lagged.fetch_add(missed, Ordering::Relaxed);
+    }
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.registry
+            .inner
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.entry.info.id);
+    }
+}
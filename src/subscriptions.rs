@@ -0,0 +1,67 @@
+//! Server-side room watch-lists. A sender that declares the rooms it follows gets cross-room
+//! views (unread counts, unread threads, the global stream) narrowed to those rooms by default
+//! instead of passing room lists on every call. A sender with no subscriptions follows every room.
+
+use crate::models::{RoomSubscription, RoomSubscriptions};
+use rusqlite::{params, Connection};
+
+/// SQL condition matching `column` against the subscriptions of the canonical sender bound
+/// at `?{idx}`.
+pub fn subscribed_sql(column: &str, idx: usize) -> String {
+    format!("{column} IN (SELECT room_id FROM room_subscriptions WHERE sender = ?{idx})")
+}
+
+/// Room ids `sender` (resolved to its canonical name) follows; empty means every room.
+pub fn room_ids(conn: &Connection, sender: &str) -> Vec<String> {
+    let sender = crate::db::resolve_sender(conn, sender);
+    conn.prepare_cached("SELECT room_id FROM room_subscriptions WHERE sender = ?1 ORDER BY room_id")
+        .and_then(|mut s| {
+            s.query_map(params![sender], |r| r.get(0))
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .unwrap_or_default()
+}
+
+/// `sender`'s subscriptions with room names, ordered by room name.
+pub fn list(conn: &Connection, sender: &str) -> RoomSubscriptions {
+    let sender = crate::db::resolve_sender(conn, sender);
+    let rooms = conn
+        .prepare(
+            "SELECT s.room_id, r.name, s.created_at FROM room_subscriptions s
+             JOIN rooms r ON r.id = s.room_id
+             WHERE s.sender = ?1 ORDER BY r.name",
+        )
+        .and_then(|mut s| {
+            s.query_map(params![&sender], |r| {
+                Ok(RoomSubscription {
+                    room_id: r.get(0)?,
+                    room_name: r.get(1)?,
+                    subscribed_at: r.get(2)?,
+                })
+            })
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        })
+        .unwrap_or_default();
+    RoomSubscriptions { sender, rooms }
+}
+
+/// Replace `sender`'s subscriptions with `room_ids`. Rooms that stay subscribed keep their
+/// original `subscribed_at`. Callers check the rooms exist.
+pub fn replace(conn: &Connection, sender: &str, room_ids: &[String]) -> rusqlite::Result<()> {
+    let sender = crate::db::resolve_sender(conn, sender);
+    let now = chrono::Utc::now().to_rfc3339();
+    let tx = conn.unchecked_transaction()?;
+    let keep = serde_json::to_string(room_ids).unwrap_or_else(|_| "[]".to_string());
+    tx.execute(
+        "DELETE FROM room_subscriptions
+         WHERE sender = ?1 AND room_id NOT IN (SELECT value FROM json_each(?2))",
+        params![&sender, &keep],
+    )?;
+    for room_id in room_ids {
+        tx.execute(
+            "INSERT OR IGNORE INTO room_subscriptions (sender, room_id, created_at) VALUES (?1, ?2, ?3)",
+            params![&sender, room_id, &now],
+        )?;
+    }
+    tx.commit()
+}
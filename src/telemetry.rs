@@ -0,0 +1,411 @@
+//! Optional OpenTelemetry trace export over OTLP/HTTP (JSON encoding).
+//!
+//! Disabled unless `OTEL_EXPORTER_OTLP_ENDPOINT` (or `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT`) is set.
+//! Incoming W3C `traceparent` headers are honored, so chat calls show up inside the caller's trace.
+//! Spans cover HTTP requests (named by route template, with a child span for each hold of the
+//! database connection), webhook deliveries (which forward `traceparent`), and retention sweeps
+//! including their DB work. When disabled, spans are never built and nothing is sent.
+
+use std::env;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::request::{FromRequest, Outcome};
+use rocket::{Data, Request, Response};
+use tokio::sync::mpsc;
+
+/// Max spans per export request.
+const MAX_BATCH: usize = 512;
+
+/// How often buffered spans are flushed.
+const FLUSH_INTERVAL_SECS: u64 = 5;
+
+static EXPORTER: OnceLock<mpsc::UnboundedSender<FinishedSpan>> = OnceLock::new();
+
+/// W3C trace context of a span (what goes into a `traceparent` header).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanContext {
+    /// 32 lowercase hex chars
+    pub trace_id: String,
+    /// 16 lowercase hex chars
+    pub span_id: String,
+    pub sampled: bool,
+}
+
+impl SpanContext {
+    /// Parse a `traceparent` header (`00-<trace_id>-<span_id>-<flags>`). Returns None if malformed
+    /// or if the trace/span id is all zeros, as the spec requires.
+    pub fn parse_traceparent(value: &str) -> Option<SpanContext> {
+        let parts: Vec<&str> = value.trim().split('-').collect();
+        if parts.len() < 4 {
+            return None;
+        }
+        let (version, trace_id, span_id, flags) = (parts[0], parts[1], parts[2], parts[3]);
+        let is_hex = |s: &str, len: usize| s.len() == len && s.chars().all(|c| c.is_ascii_hexdigit());
+        if !is_hex(version, 2) || version == "ff" || !is_hex(trace_id, 32) || !is_hex(span_id, 16) || !is_hex(flags, 2) {
+            return None;
+        }
+        // Version 00 has exactly four fields; future versions may append more
+        if version == "00" && parts.len() != 4 {
+            return None;
+        }
+        if trace_id.chars().all(|c| c == '0') || span_id.chars().all(|c| c == '0') {
+            return None;
+        }
+        let flags = u8::from_str_radix(flags, 16).ok()?;
+        Some(SpanContext {
+            trace_id: trace_id.to_lowercase(),
+            span_id: span_id.to_lowercase(),
+            sampled: flags & 0x01 == 0x01,
+        })
+    }
+
+    pub fn to_traceparent(&self) -> String {
+        format!(
+            "00-{}-{}-{}",
+            self.trace_id,
+            self.span_id,
+            if self.sampled { "01" } else { "00" }
+        )
+    }
+}
+
+/// OTLP span kind.
+#[derive(Debug, Clone, Copy)]
+pub enum SpanKind {
+    Internal = 1,
+    Server = 2,
+    Client = 3,
+}
+
+/// An in-progress span. Ending (or dropping) it queues it for export.
+#[derive(Debug)]
+pub struct Span {
+    context: SpanContext,
+    parent_span_id: Option<String>,
+    name: String,
+    kind: SpanKind,
+    start_unix_nanos: u128,
+    attributes: Vec<(String, serde_json::Value)>,
+    error: Option<String>,
+    ended: bool,
+}
+
+#[derive(Debug)]
+struct FinishedSpan {
+    context: SpanContext,
+    parent_span_id: Option<String>,
+    name: String,
+    kind: SpanKind,
+    start_unix_nanos: u128,
+    end_unix_nanos: u128,
+    attributes: Vec<(String, serde_json::Value)>,
+    error: Option<String>,
+}
+
+fn now_unix_nanos() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_nanos()
+}
+
+fn new_span_id() -> String {
+    format!("{:032x}", uuid::Uuid::new_v4().as_u128())[..16].to_string()
+}
+
+impl Span {
+    /// Start a span, continuing `parent`'s trace or starting a new (sampled) one.
+    pub fn start(name: &str, kind: SpanKind, parent: Option<&SpanContext>) -> Span {
+        let (trace_id, parent_span_id, sampled) = match parent {
+            Some(p) => (p.trace_id.clone(), Some(p.span_id.clone()), p.sampled),
+            None => (format!("{:032x}", uuid::Uuid::new_v4().as_u128()), None, true),
+        };
+        Span {
+            context: SpanContext {
+                trace_id,
+                span_id: new_span_id(),
+                sampled,
+            },
+            parent_span_id,
+            name: name.to_string(),
+            kind,
+            start_unix_nanos: now_unix_nanos(),
+            attributes: Vec::new(),
+            error: None,
+            ended: false,
+        }
+    }
+
+    pub fn context(&self) -> &SpanContext {
+        &self.context
+    }
+
+    pub fn set_attribute(&mut self, key: &str, value: impl Into<serde_json::Value>) {
+        self.attributes.push((key.to_string(), value.into()));
+    }
+
+    pub fn set_name(&mut self, name: impl Into<String>) {
+        self.name = name.into();
+    }
+
+    pub fn set_error(&mut self, message: impl Into<String>) {
+        self.error = Some(message.into());
+    }
+
+    pub fn end(mut self) {
+        self.finish();
+    }
+
+    fn finish(&mut self) {
+        if self.ended {
+            return;
+        }
+        self.ended = true;
+        if !self.context.sampled {
+            return;
+        }
+        if let Some(exporter) = EXPORTER.get() {
+            let _ = exporter.send(FinishedSpan {
+                context: self.context.clone(),
+                parent_span_id: self.parent_span_id.take(),
+                name: std::mem::take(&mut self.name),
+                kind: self.kind,
+                start_unix_nanos: self.start_unix_nanos,
+                end_unix_nanos: now_unix_nanos(),
+                attributes: std::mem::take(&mut self.attributes),
+                error: self.error.take(),
+            });
+        }
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+/// Whether an exporter is running. Callers use this to skip building spans entirely.
+pub fn enabled() -> bool {
+    EXPORTER.get().is_some()
+}
+
+/// Start a span only when tracing is enabled.
+pub fn start_span(name: &str, kind: SpanKind, parent: Option<&SpanContext>) -> Option<Span> {
+    enabled().then(|| Span::start(name, kind, parent))
+}
+
+/// Start the OTLP exporter if configured. Must be called from within the Tokio runtime.
+///
+/// Environment variables:
+/// - `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT` — full URL for trace export (e.g. `http://collector:4318/v1/traces`)
+/// - `OTEL_EXPORTER_OTLP_ENDPOINT` — base URL; `/v1/traces` is appended (ignored if the above is set)
+/// - `OTEL_SERVICE_NAME` — `service.name` resource attribute (default: local-agent-chat)
+pub fn init_from_env() -> Option<String> {
+    let endpoint = env::var("OTEL_EXPORTER_OTLP_TRACES_ENDPOINT")
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+        .or_else(|| {
+            env::var("OTEL_EXPORTER_OTLP_ENDPOINT")
+                .ok()
+                .filter(|s| !s.trim().is_empty())
+                .map(|base| format!("{}/v1/traces", base.trim().trim_end_matches('/')))
+        })?;
+    let service_name =
+        env::var("OTEL_SERVICE_NAME").unwrap_or_else(|_| "local-agent-chat".to_string());
+
+    let (tx, rx) = mpsc::unbounded_channel();
+    if EXPORTER.set(tx).is_err() {
+        // Already running (e.g. several Rocket instances in one process)
+        return Some(endpoint);
+    }
+    tokio::spawn(export_loop(rx, endpoint.clone(), service_name));
+    Some(endpoint)
+}
+
+async fn export_loop(mut rx: mpsc::UnboundedReceiver<FinishedSpan>, endpoint: String, service_name: String) {
+    let client = match reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+    {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("⚠️ OTLP exporter: failed to create HTTP client: {e}");
+            return;
+        }
+    };
+    let mut buffer: Vec<FinishedSpan> = Vec::new();
+    let mut ticker = tokio::time::interval(Duration::from_secs(FLUSH_INTERVAL_SECS));
+
+    loop {
+        tokio::select! {
+            span = rx.recv() => {
+                match span {
+                    Some(span) => {
+                        buffer.push(span);
+                        if buffer.len() >= MAX_BATCH {
+                            flush(&client, &endpoint, &service_name, &mut buffer).await;
+                        }
+                    }
+                    None => {
+                        flush(&client, &endpoint, &service_name, &mut buffer).await;
+                        break;
+                    }
+                }
+            }
+            _ = ticker.tick() => {
+                flush(&client, &endpoint, &service_name, &mut buffer).await;
+            }
+        }
+    }
+}
+
+async fn flush(client: &reqwest::Client, endpoint: &str, service_name: &str, buffer: &mut Vec<FinishedSpan>) {
+    if buffer.is_empty() {
+        return;
+    }
+    let spans: Vec<FinishedSpan> = std::mem::take(buffer);
+    let body = otlp_payload(service_name, &spans);
+    match client.post(endpoint).json(&body).send().await {
+        Ok(resp) if !resp.status().is_success() => {
+            eprintln!("⚠️ OTLP exporter: collector returned HTTP {} ({} spans dropped)", resp.status(), spans.len());
+        }
+        Err(e) => {
+            eprintln!("⚠️ OTLP exporter: export failed: {e} ({} spans dropped)", spans.len());
+        }
+        Ok(_) => {}
+    }
+}
+
+fn otlp_value(v: &serde_json::Value) -> serde_json::Value {
+    match v {
+        serde_json::Value::Bool(b) => serde_json::json!({"boolValue": b}),
+        serde_json::Value::Number(n) if n.is_i64() || n.is_u64() => serde_json::json!({"intValue": n.to_string()}),
+        serde_json::Value::Number(n) => serde_json::json!({"doubleValue": n.as_f64()}),
+        serde_json::Value::String(s) => serde_json::json!({"stringValue": s}),
+        other => serde_json::json!({"stringValue": other.to_string()}),
+    }
+}
+
+/// OTLP/JSON `ExportTraceServiceRequest` body for a batch of spans.
+fn otlp_payload(service_name: &str, spans: &[FinishedSpan]) -> serde_json::Value {
+    let spans: Vec<serde_json::Value> = spans
+        .iter()
+        .map(|s| {
+            let attributes: Vec<serde_json::Value> = s
+                .attributes
+                .iter()
+                .map(|(k, v)| serde_json::json!({"key": k, "value": otlp_value(v)}))
+                .collect();
+            let mut span = serde_json::json!({
+                "traceId": s.context.trace_id,
+                "spanId": s.context.span_id,
+                "name": s.name,
+                "kind": s.kind as i32,
+                "startTimeUnixNano": s.start_unix_nanos.to_string(),
+                "endTimeUnixNano": s.end_unix_nanos.to_string(),
+                "attributes": attributes,
+            });
+            if let Some(ref parent) = s.parent_span_id {
+                span["parentSpanId"] = serde_json::json!(parent);
+            }
+            if let Some(ref err) = s.error {
+                span["status"] = serde_json::json!({"code": 2, "message": err});
+            }
+            span
+        })
+        .collect();
+
+    serde_json::json!({
+        "resourceSpans": [{
+            "resource": {
+                "attributes": [{"key": "service.name", "value": {"stringValue": service_name}}]
+            },
+            "scopeSpans": [{
+                "scope": {"name": "local-agent-chat", "version": env!("CARGO_PKG_VERSION")},
+                "spans": spans
+            }]
+        }]
+    })
+}
+
+/// The server span for the current request, if tracing is enabled.
+struct RequestSpan(Mutex<Option<Span>>);
+
+/// Trace context of the current request's server span (or of the caller, when tracing is
+/// disabled but a `traceparent` was sent), for parenting spans started inside handlers.
+#[derive(Debug, Clone)]
+pub struct TraceContext(pub Option<SpanContext>);
+
+impl TraceContext {
+    pub fn of(req: &Request<'_>) -> Option<SpanContext> {
+        let span = req.local_cache(|| RequestSpan(Mutex::new(None)));
+        let guard = span.0.lock().unwrap_or_else(|e| e.into_inner());
+        match guard.as_ref() {
+            Some(s) => Some(s.context().clone()),
+            None => incoming_traceparent(req),
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for TraceContext {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(TraceContext(TraceContext::of(req)))
+    }
+}
+
+fn incoming_traceparent(req: &Request<'_>) -> Option<SpanContext> {
+    req.headers()
+        .get_one("traceparent")
+        .and_then(SpanContext::parse_traceparent)
+}
+
+/// Fairing that opens a server span per request (child of any incoming `traceparent`).
+pub struct TracingFairing;
+
+#[rocket::async_trait]
+impl Fairing for TracingFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "OpenTelemetry",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _data: &mut Data<'_>) {
+        if !enabled() {
+            return;
+        }
+        let parent = incoming_traceparent(req);
+        // Renamed after routing: raw paths carry ids, which would make every room its own span name
+        let mut span = Span::start(req.method().as_str(), SpanKind::Server, parent.as_ref());
+        span.set_attribute("http.request.method", req.method().as_str());
+        span.set_attribute("url.path", req.uri().path().as_str());
+        span.set_attribute("http.request_id", crate::request_id::RequestId::of(req));
+        req.local_cache(|| RequestSpan(Mutex::new(Some(span))));
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        let cached = req.local_cache(|| RequestSpan(Mutex::new(None)));
+        let span = cached.0.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(mut span) = span {
+            if let Some(route) = req.route() {
+                let template = route.uri.path();
+                span.set_name(format!("{} {template}", req.method()));
+                span.set_attribute("http.route", template);
+            }
+            let status = res.status().code;
+            span.set_attribute("http.response.status_code", status as i64);
+            if status >= 500 {
+                span.set_error(format!("HTTP {status}"));
+            }
+            span.end();
+        }
+    }
+}
+
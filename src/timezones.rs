@@ -0,0 +1,81 @@
+//! Opt-in local times. Timestamps stay UTC RFC 3339 everywhere; with `?tz=` a listing also
+//! carries each item's time in that zone, pre-formatted, for thin clients without a tz database.
+//! `tz` is an IANA name (`Europe/Berlin`) or `@sender` for that profile's `timezone`.
+
+use crate::models::Message;
+use chrono::DateTime;
+use chrono_tz::Tz;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+/// `display` format: `Mon 2 Mar 2026, 10:00 CET`.
+const DISPLAY_FORMAT: &str = "%a %-d %b %Y, %H:%M %Z";
+
+/// An item's timestamps in the requested zone.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LocalTime {
+    pub tz: String,
+    /// `created_at` with the zone's offset
+    pub created_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edited_at: Option<String>,
+    /// Ready to show as is
+    pub display: String,
+    /// Offset at `created_at`, e.g. `+01:00`
+    pub utc_offset: String,
+}
+
+/// Parse an IANA zone name.
+pub fn parse(name: &str) -> Option<Tz> {
+    name.trim().parse().ok()
+}
+
+/// The zone for a `?tz=` value: an IANA name, or `@sender` for that profile's preference.
+/// None when not asked for; 400 when the zone is unknown or the profile has none.
+pub fn resolve(conn: &Connection, tz: Option<&str>) -> Result<Option<Tz>, (Status, Json<serde_json::Value>)> {
+    let Some(tz) = tz.map(str::trim).filter(|t| !t.is_empty()) else {
+        return Ok(None);
+    };
+    if let Some(sender) = tz.strip_prefix('@') {
+        let sender = crate::db::resolve_sender(conn, sender);
+        let preferred: Option<String> = conn
+            .query_row("SELECT timezone FROM profiles WHERE sender = ?1", params![&sender], |r| r.get(0))
+            .unwrap_or(None);
+        return match preferred.as_deref().and_then(parse) {
+            Some(zone) => Ok(Some(zone)),
+            None => Err((
+                Status::BadRequest,
+                Json(serde_json::json!({"error": format!("'{sender}' has no timezone set on their profile")})),
+            )),
+        };
+    }
+    parse(tz).map(Some).ok_or_else(|| {
+        (
+            Status::BadRequest,
+            Json(serde_json::json!({"error": format!("Unknown timezone '{tz}': use an IANA name like Europe/Berlin")})),
+        )
+    })
+}
+
+/// `created_at` (and `edited_at`) in `tz`. None if the stored timestamp doesn't parse.
+pub fn local_time(created_at: &str, edited_at: Option<&str>, tz: Tz) -> Option<LocalTime> {
+    let created = DateTime::parse_from_rfc3339(created_at).ok()?.with_timezone(&tz);
+    Some(LocalTime {
+        tz: tz.name().to_string(),
+        created_at: created.to_rfc3339(),
+        edited_at: edited_at
+            .and_then(|e| DateTime::parse_from_rfc3339(e).ok())
+            .map(|e| e.with_timezone(&tz).to_rfc3339()),
+        display: created.format(DISPLAY_FORMAT).to_string(),
+        utc_offset: created.format("%:z").to_string(),
+    })
+}
+
+/// Fill in a message's `local_time` when a zone was asked for.
+pub fn apply(msg: &mut Message, tz: Option<Tz>) {
+    if let Some(tz) = tz {
+        msg.local_time = local_time(&msg.created_at, msg.edited_at.as_deref(), tz);
+    }
+}
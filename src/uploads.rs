@@ -0,0 +1,303 @@
+//! Upload screening: MIME/extension allow and block lists, magic-byte sniffing, and an optional
+//! ClamAV scan.
+//!
+//! Server-wide rules come from the environment; rooms can add their own (`room_upload_policies`).
+//! A file must pass both. Type and extension violations are 415, content that contradicts its
+//! declared type or trips the scanner is 422, and an unreachable scanner is 503.
+
+use std::env;
+use std::io::{Read, Write};
+use std::time::Duration;
+
+use rocket::http::Status;
+use rusqlite::{params, Connection};
+
+use crate::models::RoomUploadPolicy;
+
+/// Environment variables (lists are comma-separated; all default to empty/off):
+/// - `UPLOAD_ALLOWED_TYPES` / `UPLOAD_BLOCKED_TYPES` — MIME types, `image/*` wildcards allowed
+/// - `UPLOAD_ALLOWED_EXTENSIONS` / `UPLOAD_BLOCKED_EXTENSIONS` — e.g. `exe,dll,bat`
+/// - `UPLOAD_VERIFY_CONTENT_TYPE` — `true` to check magic bytes against the declared type everywhere
+/// - `CLAMAV_ADDRESS` — clamd socket: `host:port` or `unix:/path/to/clamd.sock`
+/// - `CLAMAV_TIMEOUT_MS` — scan timeout (default 10000)
+#[derive(Debug, Clone, Default)]
+pub struct UploadConfig {
+    pub server: RoomUploadPolicy,
+    pub clamav_address: Option<String>,
+    pub clamav_timeout: Duration,
+}
+
+fn env_list(name: &str) -> Vec<String> {
+    env::var(name).map(|v| normalize_list(v.split(','))).unwrap_or_default()
+}
+
+/// Lowercase, trim, drop empties and leading dots (so `.EXE` and `exe` match the same files).
+pub fn normalize_list<'a>(items: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    items
+        .into_iter()
+        .map(|s| s.trim().trim_start_matches('.').to_lowercase())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+impl UploadConfig {
+    pub fn from_env() -> Self {
+        Self {
+            server: RoomUploadPolicy {
+                allowed_types: env_list("UPLOAD_ALLOWED_TYPES"),
+                blocked_types: env_list("UPLOAD_BLOCKED_TYPES"),
+                allowed_extensions: env_list("UPLOAD_ALLOWED_EXTENSIONS"),
+                blocked_extensions: env_list("UPLOAD_BLOCKED_EXTENSIONS"),
+                verify_content_type: env::var("UPLOAD_VERIFY_CONTENT_TYPE")
+                    .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+                    .unwrap_or(false),
+                ..Default::default()
+            },
+            clamav_address: env::var("CLAMAV_ADDRESS").ok().filter(|v| !v.trim().is_empty()),
+            clamav_timeout: Duration::from_millis(
+                env::var("CLAMAV_TIMEOUT_MS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(10_000),
+            ),
+        }
+    }
+}
+
+/// Why an upload was refused.
+#[derive(Debug)]
+pub struct Rejection {
+    pub status: Status,
+    pub message: String,
+}
+
+impl Rejection {
+    fn new(status: Status, message: impl Into<String>) -> Self {
+        Self { status, message: message.into() }
+    }
+}
+
+/// Load a room's upload policy, if it has one.
+pub fn room_policy(conn: &Connection, room_id: &str) -> Option<RoomUploadPolicy> {
+    let split = |s: String| normalize_list(s.split(','));
+    conn.query_row(
+        "SELECT room_id, allowed_types, blocked_types, allowed_extensions, blocked_extensions, verify_content_type, updated_at
+         FROM room_upload_policies WHERE room_id = ?1",
+        params![room_id],
+        |r| {
+            Ok(RoomUploadPolicy {
+                room_id: r.get(0)?,
+                allowed_types: split(r.get(1)?),
+                blocked_types: split(r.get(2)?),
+                allowed_extensions: split(r.get(3)?),
+                blocked_extensions: split(r.get(4)?),
+                verify_content_type: r.get(5)?,
+                updated_at: r.get(6)?,
+            })
+        },
+    )
+    .ok()
+}
+
+/// The declared MIME type without parameters, lowercased (`Text/Plain; charset=utf-8` → `text/plain`).
+fn essence(content_type: &str) -> String {
+    content_type.split(';').next().unwrap_or("").trim().to_lowercase()
+}
+
+fn type_matches(pattern: &str, mime: &str) -> bool {
+    match pattern.strip_suffix("/*") {
+        Some(top) => mime.split('/').next() == Some(top),
+        None => pattern == mime,
+    }
+}
+
+fn extension(filename: &str) -> Option<String> {
+    let (stem, ext) = filename.rsplit_once('.')?;
+    (!stem.is_empty() && !ext.is_empty()).then(|| ext.to_lowercase())
+}
+
+/// Identify common formats from their leading bytes. None means "no opinion" (plain text, CSV,
+/// JSON and most other text formats have no signature).
+pub fn sniff(data: &[u8]) -> Option<&'static str> {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"%PDF-", "application/pdf"),
+        (b"PK\x03\x04", "application/zip"),
+        (b"\x1f\x8b", "application/gzip"),
+        (b"7z\xbc\xaf\x27\x1c", "application/x-7z-compressed"),
+        (b"\x7fELF", "application/x-executable"),
+        (b"MZ", "application/x-msdownload"),
+        (b"\xcf\xfa\xed\xfe", "application/x-mach-binary"),
+        (b"\xfe\xed\xfa\xcf", "application/x-mach-binary"),
+        (b"\xca\xfe\xba\xbe", "application/x-mach-binary"),
+        (b"\x00asm", "application/wasm"),
+        (b"#!", "text/x-shellscript"),
+    ];
+    if data.len() >= 12 && &data[..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        return Some("image/webp");
+    }
+    SIGNATURES
+        .iter()
+        .find(|(magic, _)| data.starts_with(magic))
+        .map(|(_, mime)| *mime)
+}
+
+/// Whether a sniffed type is consistent with the declared one. Container formats are lenient:
+/// zip covers docx/xlsx/jar/apk, and anything may be declared as a generic binary.
+fn consistent(sniffed: &str, declared: &str) -> bool {
+    if sniffed == declared || declared == "application/octet-stream" {
+        return true;
+    }
+    match sniffed {
+        "image/jpeg" => declared == "image/jpg" || declared == "image/pjpeg",
+        "application/zip" => {
+            declared.contains("zip")
+                || declared.starts_with("application/vnd.openxmlformats")
+                || declared.starts_with("application/vnd.oasis.opendocument")
+                || ["application/java-archive", "application/epub+zip", "application/vnd.android.package-archive"]
+                    .contains(&declared)
+        }
+        "application/gzip" => declared == "application/x-gzip" || declared == "application/x-tar+gzip",
+        "text/x-shellscript" => declared.starts_with("text/") || declared == "application/x-sh",
+        _ => false,
+    }
+}
+
+fn check_policy(policy: &RoomUploadPolicy, scope: &str, ext: Option<&str>, types: &[&str]) -> Result<(), Rejection> {
+    for mime in types {
+        if policy.blocked_types.iter().any(|p| type_matches(p, mime)) {
+            return Err(Rejection::new(
+                Status::UnsupportedMediaType,
+                format!("File type {mime} is blocked by the {scope} upload policy"),
+            ));
+        }
+        if !policy.allowed_types.is_empty() && !policy.allowed_types.iter().any(|p| type_matches(p, mime)) {
+            return Err(Rejection::new(
+                Status::UnsupportedMediaType,
+                format!("File type {mime} is not allowed by the {scope} upload policy"),
+            ));
+        }
+    }
+    let shown = ext.map(|e| format!(".{e}")).unwrap_or_else(|| "(none)".to_string());
+    if let Some(ext) = ext
+        && policy.blocked_extensions.iter().any(|b| b == ext)
+    {
+        return Err(Rejection::new(
+            Status::UnsupportedMediaType,
+            format!("File extension {shown} is blocked by the {scope} upload policy"),
+        ));
+    }
+    if !policy.allowed_extensions.is_empty() && !ext.is_some_and(|e| policy.allowed_extensions.iter().any(|a| a == e)) {
+        return Err(Rejection::new(
+            Status::UnsupportedMediaType,
+            format!("File extension {shown} is not allowed by the {scope} upload policy"),
+        ));
+    }
+    Ok(())
+}
+
+/// Run every check for one upload. Call without holding the database lock: the ClamAV scan can
+/// take a while.
+pub fn screen(
+    config: &UploadConfig,
+    room: Option<&RoomUploadPolicy>,
+    filename: &str,
+    content_type: &str,
+    data: &[u8],
+) -> Result<(), Rejection> {
+    let declared = essence(content_type);
+    let sniffed = sniff(data);
+    let ext = extension(filename);
+
+    // Lists apply to what the file claims to be and to what it actually is
+    let mut types = vec![declared.as_str()];
+    if let Some(s) = sniffed
+        && s != declared
+    {
+        types.push(s);
+    }
+    check_policy(&config.server, "server", ext.as_deref(), &types)?;
+    if let Some(room) = room {
+        check_policy(room, "room", ext.as_deref(), &types)?;
+    }
+
+    let verify = config.server.verify_content_type || room.is_some_and(|r| r.verify_content_type);
+    if verify
+        && let Some(sniffed) = sniffed
+        && !consistent(sniffed, &declared)
+    {
+        return Err(Rejection::new(
+            Status::UnprocessableEntity,
+            format!("File content looks like {sniffed}, not the declared {declared}"),
+        ));
+    }
+
+    if let Some(ref address) = config.clamav_address {
+        match clamav_scan(address, config.clamav_timeout, data) {
+            Ok(None) => {}
+            Ok(Some(signature)) => {
+                return Err(Rejection::new(
+                    Status::UnprocessableEntity,
+                    format!("File rejected by virus scanner: {signature}"),
+                ));
+            }
+            Err(e) => {
+                eprintln!("⚠️ ClamAV scan failed ({address}): {e}");
+                return Err(Rejection::new(Status::ServiceUnavailable, "Virus scanner unavailable, try again later"));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Stream `data` to clamd with INSTREAM. Returns the signature name if something was found.
+fn clamav_scan(address: &str, timeout: Duration, data: &[u8]) -> std::io::Result<Option<String>> {
+    fn exchange(stream: &mut (impl Read + Write), data: &[u8]) -> std::io::Result<String> {
+        stream.write_all(b"zINSTREAM\0")?;
+        for chunk in data.chunks(64 * 1024) {
+            stream.write_all(&(chunk.len() as u32).to_be_bytes())?;
+            stream.write_all(chunk)?;
+        }
+        stream.write_all(&0u32.to_be_bytes())?;
+        stream.flush()?;
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply)?;
+        Ok(reply.trim_end_matches(['\0', '\n']).to_string())
+    }
+
+    let reply = if let Some(path) = address.strip_prefix("unix:") {
+        #[cfg(unix)]
+        {
+            let mut stream = std::os::unix::net::UnixStream::connect(path)?;
+            stream.set_read_timeout(Some(timeout))?;
+            stream.set_write_timeout(Some(timeout))?;
+            exchange(&mut stream, data)?
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = path;
+            return Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "unix sockets unavailable"));
+        }
+    } else {
+        let addr = std::net::ToSocketAddrs::to_socket_addrs(address)?
+            .next()
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "unresolvable address"))?;
+        let mut stream = std::net::TcpStream::connect_timeout(&addr, timeout)?;
+        stream.set_read_timeout(Some(timeout))?;
+        stream.set_write_timeout(Some(timeout))?;
+        exchange(&mut stream, data)?
+    };
+
+    // "stream: OK" or "stream: <Signature> FOUND"
+    let verdict = reply.strip_prefix("stream:").unwrap_or(&reply).trim();
+    if verdict == "OK" {
+        Ok(None)
+    } else if let Some(signature) = verdict.strip_suffix("FOUND") {
+        Ok(Some(signature.trim().to_string()))
+    } else {
+        Err(std::io::Error::other(format!("unexpected clamd reply: {reply}")))
+    }
+}
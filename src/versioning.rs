@@ -0,0 +1,242 @@
+//! API versions. `/api/v1` is frozen: its paths and response shapes don't change. `/api/v2`
+//! serves the same routes with the response-shape fixes v1 can't take:
+//!
+//! - errors are `{"error": {"code", "message", "status", "details"?}}` instead of a bare
+//!   `{"error": "..."}` string plus whatever keys the handler added;
+//! - list endpoints always return the pagination envelope `{"items", "next_cursor", "has_more"}`
+//!   (v1's `?envelope=true`).
+//!
+//! Every response says which version produced it in `API-Version`. v1 routes listed in
+//! [`DEPRECATIONS`] also get `Deprecation` (RFC 9745), `Sunset` (RFC 8594) and a `Warning`
+//! explaining what to use instead; v1 responses link their v2 equivalent with
+//! `Link: <...>; rel="successor-version"`.
+
+use std::io::Cursor;
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::uri::Origin;
+use rocket::http::{Header, Method};
+use rocket::{Data, Request, Response};
+
+/// Versions this server answers, oldest first.
+pub const SUPPORTED_VERSIONS: &[&str] = &["v1", "v2"];
+
+const V1_PREFIX: &str = "/api/v1/";
+const V2_PREFIX: &str = "/api/v2/";
+
+/// A v1 route (or one of its query parameters) scheduled for removal.
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+pub struct Deprecation {
+    #[serde(serialize_with = "serialize_method")]
+    pub method: Method,
+    /// Path with `*` standing for one segment, e.g. `/api/v1/rooms/*/messages`
+    pub path: &'static str,
+    /// Only requests using this query parameter are deprecated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query_param: Option<&'static str>,
+    /// UTC date the deprecation was announced (`YYYY-MM-DD`)
+    pub deprecated_on: &'static str,
+    /// UTC date after which it may stop working (`YYYY-MM-DD`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sunset_on: Option<&'static str>,
+    pub replacement: &'static str,
+}
+
+fn serialize_method<S: serde::Serializer>(method: &Method, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(method.as_str())
+}
+
+/// Deprecated v1 surface. Append when retiring something; remove entries only with the route.
+pub const DEPRECATIONS: &[Deprecation] = &[
+    Deprecation {
+        method: Method::Get,
+        path: "/api/v1/rooms/*/messages",
+        query_param: Some("since"),
+        deprecated_on: "2026-10-16",
+        sunset_on: None,
+        replacement: "Page by seq with after=<seq>; timestamps can tie and skip messages",
+    },
+    Deprecation {
+        method: Method::Get,
+        path: "/api/v1/rooms/*/stream",
+        query_param: Some("since"),
+        deprecated_on: "2026-10-16",
+        sunset_on: None,
+        replacement: "Resume with after=<seq>; timestamps can tie and skip messages",
+    },
+];
+
+fn path_matches(pattern: &str, path: &str) -> bool {
+    let mut pattern = pattern.trim_end_matches('/').split('/');
+    let mut path = path.trim_end_matches('/').split('/');
+    loop {
+        match (pattern.next(), path.next()) {
+            (None, None) => return true,
+            (Some(p), Some(s)) if p == "*" || p == s => continue,
+            _ => return false,
+        }
+    }
+}
+
+/// The deprecation entry that applies to this request, if any.
+pub fn deprecation_for(req: &Request<'_>) -> Option<&'static Deprecation> {
+    let path = req.uri().path();
+    DEPRECATIONS.iter().find(|d| {
+        d.method == req.method()
+            && path_matches(d.path, path.as_str())
+            && d.query_param.is_none_or(|q| req.query_value::<&str>(q).is_some())
+    })
+}
+
+/// Which API version a request was made against; cached by [`ApiVersionPaths`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RequestedVersion(u8);
+
+/// Fairing that routes `/api/v2/...` to the v1 handlers (asking list endpoints for their
+/// envelope) and remembers the request was v2 so [`ApiVersionResponses`] can reshape it.
+/// Attach before fairings that rewrite `/api/v1` paths so they see the mapped path.
+pub struct ApiVersionPaths;
+
+#[rocket::async_trait]
+impl Fairing for ApiVersionPaths {
+    fn info(&self) -> Info {
+        Info {
+            name: "API Version Paths",
+            kind: Kind::Request,
+        }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _data: &mut Data<'_>) {
+        let path = req.uri().path().as_str().to_string();
+        let Some(rest) = path.strip_prefix(V2_PREFIX) else {
+            return;
+        };
+        let mut uri = format!("{V1_PREFIX}{rest}");
+        let query = req.uri().query().map(|q| q.as_str().to_string()).unwrap_or_default();
+        let has_envelope = query.split('&').any(|p| p == "envelope" || p.starts_with("envelope="));
+        let query = match (query.is_empty(), has_envelope) {
+            (_, true) => query,
+            (true, false) => "envelope=true".to_string(),
+            (false, false) => format!("{query}&envelope=true"),
+        };
+        uri.push('?');
+        uri.push_str(&query);
+        if let Ok(origin) = Origin::parse_owned(uri) {
+            req.local_cache(|| RequestedVersion(2));
+            req.set_uri(origin);
+        }
+    }
+}
+
+/// Fairing that labels responses with their API version, adds deprecation headers to v1
+/// responses and applies the v2 error and list envelopes. Attach after fairings that write
+/// error bodies (localization, redirects) so v2 wraps their final form.
+pub struct ApiVersionResponses;
+
+#[rocket::async_trait]
+impl Fairing for ApiVersionResponses {
+    fn info(&self) -> Info {
+        Info {
+            name: "API Version Responses",
+            kind: Kind::Response,
+        }
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        let path = req.uri().path().as_str().to_string();
+        let Some(rest) = path.strip_prefix(V1_PREFIX) else {
+            return;
+        };
+        if req.local_cache(|| RequestedVersion(1)).0 == 2 {
+            res.set_header(Header::new("API-Version", "2"));
+            if let Some(location) = res.headers().get_one("Location").and_then(|l| l.strip_prefix(V1_PREFIX)) {
+                let location = format!("{V2_PREFIX}{location}");
+                res.set_header(Header::new("Location", location));
+            }
+            reshape_v2(res).await;
+            return;
+        }
+
+        res.set_header(Header::new("API-Version", "1"));
+        res.set_header(Header::new("Link", format!("<{V2_PREFIX}{rest}>; rel=\"successor-version\"")));
+        if let Some(d) = deprecation_for(req) {
+            let deprecated_at = http_date_timestamp(d.deprecated_on).unwrap_or_default();
+            res.set_header(Header::new("Deprecation", format!("@{deprecated_at}")));
+            if let Some(sunset) = d.sunset_on.and_then(http_date) {
+                res.set_header(Header::new("Sunset", sunset));
+            }
+            let what = match d.query_param {
+                Some(q) => format!("{q}= on {} {}", d.method, d.path),
+                None => format!("{} {}", d.method, d.path),
+            };
+            res.set_header(Header::new(
+                "Warning",
+                format!("299 local-agent-chat \"{what} is deprecated: {}\"", d.replacement.replace('"', "'")),
+            ));
+        }
+    }
+}
+
+fn midnight_utc(date: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|dt| dt.and_utc())
+}
+
+fn http_date_timestamp(date: &str) -> Option<i64> {
+    midnight_utc(date).map(|dt| dt.timestamp())
+}
+
+fn http_date(date: &str) -> Option<String> {
+    midnight_utc(date).map(|dt| dt.format("%a, %d %b %Y %H:%M:%S GMT").to_string())
+}
+
+async fn reshape_v2(res: &mut Response<'_>) {
+    if res.content_type().is_none_or(|ct| !ct.is_json()) {
+        return;
+    }
+    let status = res.status();
+    let Ok(body) = res.body_mut().to_string().await else { return };
+    let reshaped = serde_json::from_str::<serde_json::Value>(&body).ok().and_then(|value| {
+        if status.code >= 400 {
+            v2_error(status, value)
+        } else if value.is_array() {
+            Some(serde_json::json!({"items": value, "next_cursor": null, "has_more": false}))
+        } else {
+            None
+        }
+    });
+    let text = reshaped.map(|v| v.to_string()).unwrap_or(body);
+    res.set_sized_body(text.len(), Cursor::new(text));
+}
+
+/// `{"error": "msg", "error_code"?: "...", ...rest}` → `{"error": {"code", "message", "status", "details"?}}`
+fn v2_error(status: rocket::http::Status, value: serde_json::Value) -> Option<serde_json::Value> {
+    let serde_json::Value::Object(mut fields) = value else { return None };
+    let message = match fields.remove("error") {
+        Some(serde_json::Value::String(s)) => s,
+        Some(other) => {
+            // Already enveloped (or an unexpected shape); leave it be
+            fields.insert("error".to_string(), other);
+            return None;
+        }
+        None => status.reason().unwrap_or("Error").to_string(),
+    };
+    let code = match fields.remove("error_code") {
+        Some(serde_json::Value::String(code)) => code,
+        _ => status
+            .reason()
+            .map(|r| r.to_lowercase().replace([' ', '-'], "_"))
+            .unwrap_or_else(|| "error".to_string()),
+    };
+    let mut error = serde_json::json!({
+        "code": code,
+        "message": message,
+        "status": status.code,
+    });
+    if !fields.is_empty() {
+        error["details"] = serde_json::Value::Object(fields);
+    }
+    Some(serde_json::json!({ "error": error }))
+}
@@ -1,26 +1,326 @@
-use crate::events::ChatEvent;
-use crate::models::WebhookPayload;
+use crate::events::{ChatEvent, Published};
+use crate::models::{WebhookCircuitOpened, WebhookPayload};
+use crate::secrets::SecretBox;
+use crate::telemetry::{self, SpanContext, SpanKind};
 use hmac::{Hmac, Mac};
 use rusqlite::{params, Connection};
 use sha2::Sha256;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{Arc, Mutex};
-use tokio::sync::broadcast;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, OwnedSemaphorePermit, Semaphore};
 
 type HmacSha256 = Hmac<Sha256>;
 
+/// Request timeout for every delivery.
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long an ordered webhook's queue worker waits for more deliveries before exiting.
+const ORDERED_QUEUE_IDLE: Duration = Duration::from_secs(300);
+
+/// Limits on a webhook's custom headers.
+const MAX_CUSTOM_HEADERS: usize = 20;
+const MAX_HEADER_VALUE_LEN: usize = 4096;
+
+/// Headers the dispatcher sets itself. Custom headers can't replace them, nor anything `X-Chat-*`.
+const RESERVED_HEADERS: &[&str] = &[
+    "content-type",
+    "content-length",
+    "host",
+    "traceparent",
+    "transfer-encoding",
+    "x-request-id",
+];
+
+/// Check a webhook's custom headers: valid HTTP names, nothing reserved, single-line values.
+pub fn validate_custom_headers(headers: &BTreeMap<String, String>) -> Result<(), String> {
+    if headers.len() > MAX_CUSTOM_HEADERS {
+        return Err(format!("At most {MAX_CUSTOM_HEADERS} custom headers are allowed"));
+    }
+    for (name, value) in headers {
+        let lower = name.to_ascii_lowercase();
+        if reqwest::header::HeaderName::from_bytes(name.as_bytes()).is_err() {
+            return Err(format!("Invalid header name: '{name}'"));
+        }
+        if RESERVED_HEADERS.contains(&lower.as_str()) || lower.starts_with("x-chat-") {
+            return Err(format!("Header '{name}' is set by the server and can't be overridden"));
+        }
+        if value.len() > MAX_HEADER_VALUE_LEN {
+            return Err(format!("Header '{name}' must be at most {MAX_HEADER_VALUE_LEN} characters"));
+        }
+        if reqwest::header::HeaderValue::from_str(value).is_err() {
+            return Err(format!("Invalid value for header '{name}'"));
+        }
+    }
+    Ok(())
+}
+
+/// Apply a webhook's TLS settings to `builder`: a PEM client certificate chain plus private
+/// key for mTLS, and/or extra PEM CA certificates to trust.
+fn with_tls(
+    mut builder: reqwest::ClientBuilder,
+    client_cert: Option<&str>,
+    ca_cert: Option<&str>,
+) -> Result<reqwest::ClientBuilder, String> {
+    if let Some(pem) = client_cert {
+        let identity = reqwest::Identity::from_pem(pem.as_bytes())
+            .map_err(|e| format!("Invalid client_cert: expected a PEM certificate chain and private key ({e})"))?;
+        builder = builder.identity(identity);
+    }
+    if let Some(pem) = ca_cert {
+        let certs = reqwest::Certificate::from_pem_bundle(pem.as_bytes())
+            .ok()
+            .filter(|certs| !certs.is_empty())
+            .ok_or_else(|| "Invalid ca_cert: expected one or more PEM certificates".to_string())?;
+        for cert in certs {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+    Ok(builder)
+}
+
+/// Check that a webhook's TLS settings load, the same way the dispatcher will use them.
+pub fn validate_tls(client_cert: Option<&str>, ca_cert: Option<&str>) -> Result<(), String> {
+    with_tls(reqwest::Client::builder(), client_cert, ca_cert)?
+        .build()
+        .map(|_| ())
+        .map_err(|e| format!("Invalid client_cert/ca_cert: {e}"))
+}
+
+/// Connection and concurrency tuning for outgoing deliveries.
+#[derive(Debug, Clone)]
+pub struct DispatcherConfig {
+    /// Requests in flight across all webhooks
+    pub concurrency: usize,
+    /// Requests in flight to any one endpoint (host and port)
+    pub per_host_concurrency: usize,
+    /// How long an idle pooled connection is kept open
+    pub pool_idle_timeout: Duration,
+    /// Idle connections kept per endpoint
+    pub pool_max_idle_per_host: usize,
+}
+
+impl DispatcherConfig {
+    /// `WEBHOOK_CONCURRENCY` (default 32), `WEBHOOK_HOST_CONCURRENCY` (default 4),
+    /// `WEBHOOK_POOL_IDLE_SECS` (default 90) and `WEBHOOK_POOL_MAX_IDLE` (default 8).
+    pub fn from_env() -> Self {
+        let var = |name: &str, default: usize, min: usize| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.trim().parse::<usize>().ok())
+                .filter(|n| *n >= min)
+                .unwrap_or(default)
+        };
+        Self {
+            concurrency: var("WEBHOOK_CONCURRENCY", 32, 1),
+            per_host_concurrency: var("WEBHOOK_HOST_CONCURRENCY", 4, 1),
+            pool_idle_timeout: Duration::from_secs(var("WEBHOOK_POOL_IDLE_SECS", 90, 0) as u64),
+            pool_max_idle_per_host: var("WEBHOOK_POOL_MAX_IDLE", 8, 0),
+        }
+    }
+
+    /// Settings every delivery client shares: pooled keep-alive connections, HTTP/2 when the
+    /// endpoint offers it over TLS, and pings so idle HTTP/2 connections aren't dropped silently.
+    fn client_builder(&self) -> reqwest::ClientBuilder {
+        reqwest::Client::builder()
+            .timeout(DELIVERY_TIMEOUT)
+            .pool_idle_timeout(self.pool_idle_timeout)
+            .pool_max_idle_per_host(self.pool_max_idle_per_host)
+            .tcp_keepalive(Duration::from_secs(60))
+            .http2_keep_alive_interval(Duration::from_secs(30))
+            .http2_keep_alive_while_idle(true)
+    }
+}
+
+/// Caps on requests in flight: all deliveries together, and per endpoint.
+struct Throttle {
+    global: Arc<Semaphore>,
+    per_host: usize,
+    hosts: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+impl Throttle {
+    fn new(config: &DispatcherConfig) -> Self {
+        Throttle {
+            global: Arc::new(Semaphore::new(config.concurrency)),
+            per_host: config.per_host_concurrency,
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Wait for a slot at `url`'s endpoint, then a global one, so a throttled endpoint doesn't
+    /// sit on global slots other endpoints could use.
+    async fn acquire(&self, url: &str) -> (OwnedSemaphorePermit, OwnedSemaphorePermit) {
+        let host = reqwest::Url::parse(url)
+            .ok()
+            .map(|u| format!("{}:{}", u.host_str().unwrap_or_default(), u.port_or_known_default().unwrap_or(0)))
+            .unwrap_or_default();
+        let slots = {
+            let mut hosts = self.hosts.lock().unwrap_or_else(|e| e.into_inner());
+            hosts
+                .entry(host)
+                .or_insert_with(|| Arc::new(Semaphore::new(self.per_host)))
+                .clone()
+        };
+        let host_permit = slots.acquire_owned().await.expect("throttle semaphores are never closed");
+        let global_permit = self.global.clone().acquire_owned().await.expect("throttle semaphores are never closed");
+        (host_permit, global_permit)
+    }
+}
+
+/// Clients for webhooks with TLS settings, keyed by webhook id. The stored (sealed) settings
+/// are kept alongside so an update rebuilds the client on the next delivery.
+type TransportClients = HashMap<String, ((Option<String>, Option<String>), reqwest::Client)>;
+
+/// A room webhook as the dispatcher reads it.
+struct RoomWebhook {
+    id: String,
+    url: String,
+    secret: Option<String>,
+    events: String,
+    headers: Option<String>,
+    client_cert: Option<String>,
+    ca_cert: Option<String>,
+    ordered: bool,
+}
+
+/// One event on its way to one room webhook.
+struct RoomDelivery {
+    webhook_id: String,
+    url: String,
+    secret: Option<String>,
+    headers: BTreeMap<String, String>,
+    client: reqwest::Client,
+    event_name: String,
+    body: String,
+    request_id: Option<String>,
+    parent: Option<SpanContext>,
+}
+
+/// State shared by the dispatcher loop and the delivery tasks it starts.
+struct Dispatcher {
+    conn: Arc<Mutex<Connection>>,
+    client: reqwest::Client,
+    config: DispatcherConfig,
+    breaker: CircuitBreaker,
+    secrets: SecretBox,
+    throttle: Throttle,
+    transport_clients: Mutex<TransportClients>,
+    /// Queues of ordered webhooks with a worker running, keyed by webhook id
+    ordered_queues: Mutex<HashMap<String, mpsc::UnboundedSender<RoomDelivery>>>,
+    events: broadcast::Sender<Published>,
+    /// The namespace whose database this dispatcher serves (None for the main one)
+    namespace: Option<String>,
+}
+
 /// Maximum retry attempts for webhook delivery.
 const MAX_ATTEMPTS: u32 = 3;
 
 /// Backoff durations between retries (attempt 2 waits 2s, attempt 3 waits 4s).
 const RETRY_BACKOFFS_MS: [u64; 2] = [2000, 4000];
 
-/// Spawns a background task that subscribes to the EventBus and delivers webhooks.
-pub fn spawn_dispatcher(mut receiver: broadcast::Receiver<ChatEvent>, db_path: String) {
+/// When a failing webhook is disabled: after at least `min_failures` consecutive failed
+/// deliveries spanning `open_after`. A zero `open_after` turns the breaker off.
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    pub min_failures: i64,
+    pub open_after: chrono::Duration,
+}
+
+impl CircuitBreaker {
+    /// `WEBHOOK_CIRCUIT_FAILURES` (default 5) and `WEBHOOK_CIRCUIT_MINUTES` (default 30, 0 = never disable).
+    pub fn from_env() -> Self {
+        let min_failures = std::env::var("WEBHOOK_CIRCUIT_FAILURES")
+            .ok()
+            .and_then(|v| v.trim().parse::<i64>().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(5);
+        let minutes = std::env::var("WEBHOOK_CIRCUIT_MINUTES")
+            .ok()
+            .and_then(|v| v.trim().parse::<i64>().ok())
+            .filter(|n| *n >= 0)
+            .unwrap_or(30);
+        Self {
+            min_failures,
+            open_after: chrono::Duration::minutes(minutes),
+        }
+    }
+}
+
+/// Update a webhook's health after a delivery (all retries included). A success closes the
+/// failure streak; a failure extends it and, once the breaker's thresholds are met, disables
+/// the webhook. Returns the event to publish when that happens.
+pub fn record_delivery_outcome(
+    conn: &Connection,
+    webhook_id: &str,
+    success: bool,
+    breaker: &CircuitBreaker,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Option<WebhookCircuitOpened> {
+    let now_str = now.to_rfc3339();
+    if success {
+        conn.execute(
+            "UPDATE webhooks SET last_success_at = ?1, failure_streak = 0, failing_since = NULL WHERE id = ?2",
+            params![&now_str, webhook_id],
+        )
+        .ok();
+        return None;
+    }
+
+    conn.execute(
+        "UPDATE webhooks SET last_failure_at = ?1, failure_streak = failure_streak + 1,
+         failing_since = COALESCE(failing_since, ?1) WHERE id = ?2",
+        params![&now_str, webhook_id],
+    )
+    .ok();
+    let (room_id, url, failure_streak, failing_since, active): (String, String, i64, String, bool) = conn
+        .query_row(
+            "SELECT room_id, url, failure_streak, failing_since, active FROM webhooks WHERE id = ?1",
+            params![webhook_id],
+            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get::<_, i32>(4)? != 0)),
+        )
+        .ok()?;
+    if !active || breaker.open_after == chrono::Duration::zero() || failure_streak < breaker.min_failures {
+        return None;
+    }
+    let since = chrono::DateTime::parse_from_rfc3339(&failing_since).ok()?;
+    if now.signed_duration_since(since) < breaker.open_after {
+        return None;
+    }
+    conn.execute(
+        "UPDATE webhooks SET active = 0, circuit_opened_at = ?1 WHERE id = ?2",
+        params![&now_str, webhook_id],
+    )
+    .ok()?;
+    eprintln!(
+        "⚠️ Webhook {webhook_id} ({url}) disabled after {failure_streak} consecutive failed deliveries since {failing_since}"
+    );
+    Some(WebhookCircuitOpened {
+        webhook_id: webhook_id.to_string(),
+        room_id,
+        url,
+        failure_streak,
+        failing_since,
+        circuit_opened_at: now_str,
+    })
+}
+
+/// Spawns a background task that subscribes to the EventBus and delivers webhooks. Each
+/// matching webhook gets its own delivery task, so a slow endpoint or a retry backoff doesn't
+/// hold up the others; [`DispatcherConfig`] caps how many requests are in flight.
+/// Circuit-breaker trips are published back onto the bus as `webhook_disabled`.
+/// One dispatcher runs per database and only delivers events from its own `namespace`.
+pub fn spawn_dispatcher(
+    mut receiver: broadcast::Receiver<Published>,
+    events: broadcast::Sender<Published>,
+    db_path: String,
+    secrets: SecretBox,
+    namespace: Option<String>,
+) {
+    let config = DispatcherConfig::from_env();
+    let breaker = CircuitBreaker::from_env();
     tokio::spawn(async move {
-        let client = match reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(10))
-            .build()
-        {
+        let client = match config.client_builder().build() {
             Ok(c) => c,
             Err(e) => {
                 eprintln!("⚠️ Webhook dispatcher: failed to create HTTP client: {e}");
@@ -28,7 +328,7 @@ pub fn spawn_dispatcher(mut receiver: broadcast::Receiver<ChatEvent>, db_path: S
             }
         };
 
-        let conn = Arc::new(Mutex::new(match Connection::open(&db_path) {
+        let conn = Arc::new(Mutex::new(match crate::db::open(&db_path) {
             Ok(c) => c,
             Err(e) => {
                 eprintln!("⚠️ Webhook dispatcher: failed to open DB: {e}");
@@ -37,15 +337,43 @@ pub fn spawn_dispatcher(mut receiver: broadcast::Receiver<ChatEvent>, db_path: S
         }));
         {
             let db = conn.lock().unwrap_or_else(|e| e.into_inner());
-            db.execute_batch("PRAGMA journal_mode=WAL; PRAGMA foreign_keys=ON;")
-                .ok();
+            crate::db::DbConfig::from_env().apply(&db).ok();
         }
+        let dispatcher = Arc::new(Dispatcher {
+            conn,
+            client,
+            throttle: Throttle::new(&config),
+            config,
+            breaker,
+            secrets,
+            transport_clients: Mutex::new(TransportClients::new()),
+            ordered_queues: Mutex::new(HashMap::new()),
+            events,
+            namespace,
+        });
 
         loop {
             match receiver.recv().await {
-                Ok(event) => {
-                    if let Some((event_name, room_id, data)) = event_to_payload(&event) {
-                        deliver_webhooks(&conn, &client, &event_name, &room_id, data).await;
+                // Other namespaces' hooks live in their own databases
+                Ok(published) if !published.is_in(dispatcher.namespace.as_deref()) => {}
+                Ok(published) => {
+                    if let Some((event_name, room_id, data)) = event_to_payload(&published.event) {
+                        dispatcher.dispatch_room_event(
+                            &event_name,
+                            &room_id,
+                            data,
+                            published.request_id.as_deref(),
+                            published.trace_context.as_ref(),
+                        );
+                    }
+                    if let Some((event_name, room_id, room_name, data)) = lifecycle_payload(&published.event) {
+                        dispatcher.dispatch_server_event(
+                            event_name,
+                            room_id,
+                            room_name,
+                            data,
+                            published.request_id.as_deref(),
+                        );
                     }
                 }
                 Err(broadcast::error::RecvError::Lagged(n)) => {
@@ -78,6 +406,16 @@ fn event_to_payload(event: &ChatEvent) -> Option<(String, String, serde_json::Va
             room_id.clone(),
             serde_json::json!({"id": id, "room_id": room_id}),
         )),
+        ChatEvent::MessageRedacted { id, room_id, redacted_at } => Some((
+            "message_redacted".to_string(),
+            room_id.clone(),
+            serde_json::json!({
+                "id": id,
+                "room_id": room_id,
+                "content": crate::redaction::REDACTED_CONTENT,
+                "redacted_at": redacted_at
+            }),
+        )),
         ChatEvent::FileUploaded(file) => Some((
             "file_uploaded".to_string(),
             file.room_id.clone(),
@@ -88,6 +426,66 @@ fn event_to_payload(event: &ChatEvent) -> Option<(String, String, serde_json::Va
             room_id.clone(),
             serde_json::json!({"id": id, "room_id": room_id}),
         )),
+        ChatEvent::FileExpired { id, room_id } => Some((
+            "file_expired".to_string(),
+            room_id.clone(),
+            serde_json::json!({"id": id, "room_id": room_id}),
+        )),
+        ChatEvent::RetentionPending(notice) => Some((
+            "retention_pending".to_string(),
+            notice.room_id.clone(),
+            serde_json::to_value(notice).unwrap_or_default(),
+        )),
+        ChatEvent::ResponseOverdue(pending) => Some((
+            "response_overdue".to_string(),
+            pending.room_id.clone(),
+            serde_json::to_value(pending).unwrap_or_default(),
+        )),
+        ChatEvent::MessageFlagged(flag) => Some((
+            "message_flagged".to_string(),
+            flag.room_id.clone(),
+            serde_json::to_value(flag).unwrap_or_default(),
+        )),
+        ChatEvent::FlagResolved(flag) => Some((
+            "flag_resolved".to_string(),
+            flag.room_id.clone(),
+            serde_json::to_value(flag).unwrap_or_default(),
+        )),
+        ChatEvent::MessageLabeled(labels) => Some((
+            "message_labeled".to_string(),
+            labels.room_id.clone(),
+            serde_json::to_value(labels).unwrap_or_default(),
+        )),
+        ChatEvent::TopicChanged(topic) => Some((
+            "topic_changed".to_string(),
+            topic.room_id.clone(),
+            serde_json::to_value(topic).unwrap_or_default(),
+        )),
+        ChatEvent::WebhookDisabled(opened) => Some((
+            "webhook_disabled".to_string(),
+            opened.room_id.clone(),
+            serde_json::to_value(opened).unwrap_or_default(),
+        )),
+        ChatEvent::QueueItemAdded(item) => Some((
+            "queue_item_added".to_string(),
+            item.room_id.clone(),
+            serde_json::to_value(item).unwrap_or_default(),
+        )),
+        ChatEvent::QueueItemClaimed(item) => Some((
+            "queue_item_claimed".to_string(),
+            item.room_id.clone(),
+            serde_json::to_value(item).unwrap_or_default(),
+        )),
+        ChatEvent::QueueItemCompleted(item) => Some((
+            "queue_item_completed".to_string(),
+            item.room_id.clone(),
+            serde_json::to_value(item).unwrap_or_default(),
+        )),
+        ChatEvent::QueueItemReleased(item) => Some((
+            "queue_item_released".to_string(),
+            item.room_id.clone(),
+            serde_json::to_value(item).unwrap_or_default(),
+        )),
         ChatEvent::ReactionAdded(reaction) => Some((
             "reaction_added".to_string(),
             reaction.room_id.clone(),
@@ -126,6 +524,10 @@ fn event_to_payload(event: &ChatEvent) -> Option<(String, String, serde_json::Va
         ChatEvent::ReadPositionUpdated(_) => None,
         ChatEvent::ProfileUpdated(_) => None,
         ChatEvent::ProfileDeleted { .. } => None,
+        ChatEvent::StatusUpdated(_) => None,
+        ChatEvent::StatusCleared { .. } => None,
+        ChatEvent::LockAcquired(_) => None,
+        ChatEvent::LockReleased { .. } => None,
         ChatEvent::RoomUpdated(room) => Some((
             "room_updated".to_string(),
             room.id.clone(),
@@ -151,78 +553,281 @@ fn event_to_payload(event: &ChatEvent) -> Option<(String, String, serde_json::Va
             room_id.clone(),
             serde_json::json!({"room_id": room_id, "sender": sender}),
         )),
+        // Chunks are too chatty for webhooks; consumers get the sealed message instead
+        ChatEvent::MessageChunk(_) => None,
+        ChatEvent::MessageFinalized(msg) => Some((
+            "message_finalized".to_string(),
+            msg.room_id.clone(),
+            serde_json::to_value(msg).unwrap_or_default(),
+        )),
+        ChatEvent::MessageAppended(append) => Some((
+            "message_appended".to_string(),
+            append.room_id.clone(),
+            serde_json::to_value(append).unwrap_or_default(),
+        )),
+        // A room has no webhooks before it exists or after it's gone; server webhooks cover these
+        ChatEvent::RoomCreated(_) => None,
+        ChatEvent::RoomDeleted { .. } => None,
     }
 }
 
-/// Look up matching webhooks and deliver with retry + audit logging.
-async fn deliver_webhooks(
-    conn: &Arc<Mutex<Connection>>,
-    client: &reqwest::Client,
-    event_name: &str,
-    room_id: &str,
-    data: serde_json::Value,
-) {
-    // Query matching webhooks (id, url, secret, events filter)
-    let webhooks: Vec<(String, String, Option<String>, String)> = {
-        let db = conn.lock().unwrap_or_else(|e| {
-            eprintln!("WARN: Webhook dispatcher DB mutex poisoned, recovering");
-            e.into_inner()
-        });
-        let mut stmt = match db.prepare(
-            "SELECT id, url, secret, events FROM webhooks WHERE room_id = ?1 AND active = 1",
-        ) {
-            Ok(s) => s,
-            Err(e) => {
-                eprintln!("⚠️ Webhook dispatcher: failed to prepare query: {e}");
-                return;
-            }
-        };
-        match stmt.query_map(params![room_id], |row| {
-            Ok((
-                row.get::<_, String>(0)?,
-                row.get::<_, String>(1)?,
-                row.get::<_, Option<String>>(2)?,
-                row.get::<_, String>(3)?,
-            ))
-        }) {
-            Ok(rows) => rows.filter_map(|r| r.ok()).collect(),
-            Err(e) => {
-                eprintln!("⚠️ Webhook dispatcher: query failed: {e}");
-                return;
-            }
+/// Events server-level webhooks can subscribe to.
+pub const LIFECYCLE_EVENTS: [&str; 5] = ["room_created", "room_updated", "room_archived", "room_unarchived", "room_deleted"];
+
+/// Convert a room lifecycle ChatEvent to (event_name, room_id, room_name, data) for server webhooks.
+fn lifecycle_payload(event: &ChatEvent) -> Option<(&'static str, String, String, serde_json::Value)> {
+    let (name, room) = match event {
+        ChatEvent::RoomCreated(room) => ("room_created", room),
+        ChatEvent::RoomUpdated(room) => ("room_updated", room),
+        ChatEvent::RoomArchived(room) => ("room_archived", room),
+        ChatEvent::RoomUnarchived(room) => ("room_unarchived", room),
+        ChatEvent::RoomDeleted { id, name } => {
+            return Some(("room_deleted", id.clone(), name.clone(), serde_json::json!({"id": id, "name": name})));
         }
+        _ => return None,
     };
+    Some((name, room.id.clone(), room.name.clone(), serde_json::to_value(room).unwrap_or_default()))
+}
 
-    // Get room name for the payload
-    let room_name: String = {
-        let db = conn.lock().unwrap_or_else(|e| e.into_inner());
-        db.query_row(
-            "SELECT name FROM rooms WHERE id = ?1",
-            params![room_id],
-            |r| r.get(0),
-        )
-        .unwrap_or_else(|_| "unknown".to_string())
-    };
+impl Dispatcher {
+    /// Decrypt a webhook's custom headers and pick the client to send with. Errors when the
+    /// stored credentials can't be opened (e.g. the secrets key changed) or no longer parse.
+    fn transport(&self, webhook: &RoomWebhook) -> Result<(BTreeMap<String, String>, reqwest::Client), String> {
+        let undecryptable = |what: &str| format!("Stored {what} could not be decrypted; was SECRETS_KEY changed?");
+        let headers = match &webhook.headers {
+            Some(sealed) => self
+                .secrets
+                .open(sealed)
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .ok_or_else(|| undecryptable("headers"))?,
+            None => BTreeMap::new(),
+        };
+        let mut clients = self.transport_clients.lock().unwrap_or_else(|e| e.into_inner());
+        if webhook.client_cert.is_none() && webhook.ca_cert.is_none() {
+            clients.remove(&webhook.id);
+            return Ok((headers, self.client.clone()));
+        }
+        let tls = (webhook.client_cert.clone(), webhook.ca_cert.clone());
+        if let Some((cached, client)) = clients.get(&webhook.id)
+            && *cached == tls
+        {
+            return Ok((headers, client.clone()));
+        }
+        let client_cert = match &webhook.client_cert {
+            Some(sealed) => Some(self.secrets.open(sealed).ok_or_else(|| undecryptable("client_cert"))?),
+            None => None,
+        };
+        let client = with_tls(self.config.client_builder(), client_cert.as_deref(), webhook.ca_cert.as_deref())?
+            .build()
+            .map_err(|e| format!("Invalid client_cert/ca_cert: {e}"))?;
+        clients.insert(webhook.id.clone(), (tls, client.clone()));
+        Ok((headers, client))
+    }
 
-    for (webhook_id, url, secret, events_str) in webhooks {
-        // Check event filter
-        if events_str != "*" {
-            let allowed: Vec<&str> = events_str.split(',').map(|s| s.trim()).collect();
-            if !allowed.contains(&event_name) {
-                continue;
+    /// Update a webhook's health and announce it if the circuit breaker just disabled it.
+    fn record_outcome(&self, webhook_id: &str, delivered: bool) {
+        let outcome = {
+            let db = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+            record_delivery_outcome(&db, webhook_id, delivered, &self.breaker, chrono::Utc::now())
+        };
+        if let Some(opened) = outcome {
+            let _ = self
+                .events
+                .send(Published::in_namespace(ChatEvent::WebhookDisabled(opened), self.namespace.as_deref()));
+        }
+    }
+
+    /// Look up the room's matching webhooks and start a delivery task for each.
+    fn dispatch_room_event(
+        self: &Arc<Self>,
+        event_name: &str,
+        room_id: &str,
+        data: serde_json::Value,
+        request_id: Option<&str>,
+        trace_context: Option<&SpanContext>,
+    ) {
+        let mut dispatch_span = telemetry::start_span("webhook.dispatch", SpanKind::Internal, trace_context);
+        if let Some(ref mut span) = dispatch_span {
+            span.set_attribute("chat.event", event_name);
+            span.set_attribute("chat.room_id", room_id);
+        }
+        // Children hang off the dispatch span; without an exporter the caller's context is forwarded as-is
+        let parent = dispatch_span.as_ref().map(|s| s.context().clone()).or_else(|| trace_context.cloned());
+
+        // Query the room's active webhooks
+        let db_span = telemetry::start_span("db.webhooks.lookup", SpanKind::Internal, parent.as_ref());
+        let webhooks: Vec<RoomWebhook> = {
+            let db = self.conn.lock().unwrap_or_else(|e| {
+                eprintln!("WARN: Webhook dispatcher DB mutex poisoned, recovering");
+                e.into_inner()
+            });
+            let mut stmt = match db.prepare(
+                "SELECT id, url, secret, events, headers, client_cert, ca_cert, ordered FROM webhooks WHERE room_id = ?1 AND active = 1",
+            ) {
+                Ok(s) => s,
+                Err(e) => {
+                    eprintln!("⚠️ Webhook dispatcher: failed to prepare query: {e}");
+                    return;
+                }
+            };
+            match stmt.query_map(params![room_id], |row| {
+                Ok(RoomWebhook {
+                    id: row.get(0)?,
+                    url: row.get(1)?,
+                    secret: row.get(2)?,
+                    events: row.get(3)?,
+                    headers: row.get(4)?,
+                    client_cert: row.get(5)?,
+                    ca_cert: row.get(6)?,
+                    ordered: row.get::<_, i32>(7)? != 0,
+                })
+            }) {
+                Ok(rows) => rows.filter_map(|r| r.ok()).collect(),
+                Err(e) => {
+                    eprintln!("⚠️ Webhook dispatcher: query failed: {e}");
+                    return;
+                }
+            }
+        };
+
+        // Get room name and latest seq for the payload
+        let (room_name, room_seq): (String, i64) = {
+            let db = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+            let room_name = db
+                .query_row(
+                    "SELECT name FROM rooms WHERE id = ?1",
+                    params![room_id],
+                    |r| r.get(0),
+                )
+                .unwrap_or_else(|_| "unknown".to_string());
+            let room_seq = db
+                .query_row(
+                    "SELECT COALESCE(MAX(seq), 0) FROM messages WHERE room_id = ?1",
+                    params![room_id],
+                    |r| r.get(0),
+                )
+                .unwrap_or(0);
+            (room_name, room_seq)
+        };
+        drop(db_span);
+
+        for webhook in webhooks {
+            // Check event filter
+            if webhook.events != "*" {
+                let allowed: Vec<&str> = webhook.events.split(',').map(|s| s.trim()).collect();
+                if !allowed.contains(&event_name) {
+                    continue;
+                }
+            }
+            let (headers, client) = match self.transport(&webhook) {
+                Ok(transport) => transport,
+                Err(error_msg) => {
+                    // Retrying can't help until the webhook is fixed, so this counts as one failed attempt
+                    eprintln!("⚠️ Webhook {} not delivered: {}", webhook.id, error_msg);
+                    log_delivery(
+                        &self.conn,
+                        &uuid::Uuid::new_v4().to_string(),
+                        &webhook.id,
+                        event_name,
+                        &webhook.url,
+                        1,
+                        "failed",
+                        None,
+                        Some(&error_msg),
+                        0,
+                    );
+                    self.record_outcome(&webhook.id, false);
+                    continue;
+                }
+            };
+
+            let payload = WebhookPayload {
+                event: event_name.to_string(),
+                room_id: room_id.to_string(),
+                room_name: room_name.clone(),
+                data: data.clone(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+                room_seq: Some(room_seq),
+            };
+            let delivery = RoomDelivery {
+                webhook_id: webhook.id,
+                url: webhook.url,
+                secret: webhook.secret,
+                headers,
+                client,
+                event_name: event_name.to_string(),
+                body: serde_json::to_string(&payload).unwrap_or_default(),
+                request_id: request_id.map(str::to_string),
+                parent: parent.clone(),
+            };
+            if webhook.ordered {
+                self.enqueue_ordered(delivery);
+            } else {
+                tokio::spawn(Arc::clone(self).deliver_room(delivery));
             }
         }
+    }
 
-        let payload = WebhookPayload {
-            event: event_name.to_string(),
-            room_id: room_id.to_string(),
-            room_name: room_name.clone(),
-            data: data.clone(),
-            timestamp: chrono::Utc::now().to_rfc3339(),
+    /// Queue a delivery behind the webhook's earlier ones, starting its worker if none is
+    /// running. Events reach here in bus order, so the queue preserves it.
+    fn enqueue_ordered(self: &Arc<Self>, delivery: RoomDelivery) {
+        let mut queues = self.ordered_queues.lock().unwrap_or_else(|e| e.into_inner());
+        let webhook_id = delivery.webhook_id.clone();
+        let delivery = match queues.get(&webhook_id) {
+            Some(queue) => match queue.send(delivery) {
+                Ok(()) => return,
+                Err(mpsc::error::SendError(delivery)) => delivery,
+            },
+            None => delivery,
         };
+        let (queue, receiver) = mpsc::unbounded_channel();
+        queue.send(delivery).ok();
+        queues.insert(webhook_id.clone(), queue);
+        tokio::spawn(Arc::clone(self).run_ordered_queue(webhook_id, receiver));
+    }
 
-        let body = serde_json::to_string(&payload).unwrap_or_default();
+    /// Deliver one ordered webhook's queue one at a time, each with its full retry schedule
+    /// before the next starts. A delivery that exhausts its retries is logged and skipped.
+    /// The worker exits once the queue has been idle for [`ORDERED_QUEUE_IDLE`].
+    async fn run_ordered_queue(
+        self: Arc<Self>,
+        webhook_id: String,
+        mut receiver: mpsc::UnboundedReceiver<RoomDelivery>,
+    ) {
+        loop {
+            match tokio::time::timeout(ORDERED_QUEUE_IDLE, receiver.recv()).await {
+                Ok(Some(delivery)) => Arc::clone(&self).deliver_room(delivery).await,
+                Ok(None) => return,
+                Err(_) => {
+                    // Deliveries are only queued under this lock, so nothing can slip in after the check
+                    let mut queues = self.ordered_queues.lock().unwrap_or_else(|e| e.into_inner());
+                    if receiver.is_empty() {
+                        queues.remove(&webhook_id);
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Deliver to one room webhook with retry + audit logging. A throttle slot is held only
+    /// while a request is in flight, not across the backoff between attempts.
+    async fn deliver_room(self: Arc<Self>, delivery: RoomDelivery) {
+        let RoomDelivery {
+            webhook_id,
+            url,
+            secret,
+            headers,
+            client,
+            event_name,
+            body,
+            request_id,
+            parent,
+        } = delivery;
+        let event_name = event_name.as_str();
+        let request_id = request_id.as_deref();
         let delivery_group = uuid::Uuid::new_v4().to_string();
+        let mut delivered = false;
 
         // Retry loop with exponential backoff
         for attempt in 1..=MAX_ATTEMPTS {
@@ -231,11 +836,26 @@ async fn deliver_webhooks(
                 tokio::time::sleep(std::time::Duration::from_millis(backoff)).await;
             }
 
-            let mut request = client
-                .post(&url)
+            let mut request = client.post(&url);
+            for (name, value) in &headers {
+                request = request.header(name.as_str(), value.as_str());
+            }
+            request = request
                 .header("Content-Type", "application/json")
                 .header("X-Chat-Event", event_name)
                 .header("X-Chat-Webhook-Id", &webhook_id);
+            if let Some(rid) = request_id {
+                request = request.header(crate::request_id::REQUEST_ID_HEADER, rid);
+            }
+            let mut attempt_span = telemetry::start_span("webhook.deliver", SpanKind::Client, parent.as_ref());
+            if let Some(ref mut span) = attempt_span {
+                span.set_attribute("chat.webhook_id", webhook_id.as_str());
+                span.set_attribute("chat.attempt", attempt as i64);
+                span.set_attribute("url.full", url.as_str());
+            }
+            if let Some(ctx) = attempt_span.as_ref().map(|s| s.context()).or(parent.as_ref()) {
+                request = request.header("traceparent", ctx.to_traceparent());
+            }
 
             // HMAC-SHA256 signature if secret is set
             if let Some(ref secret) = secret
@@ -246,16 +866,30 @@ async fn deliver_webhooks(
                 request = request.header("X-Chat-Signature", format!("sha256={}", signature));
             }
 
+            let permits = self.throttle.acquire(&url).await;
             let start = std::time::Instant::now();
-            let result = request.body(body.clone()).send().await;
+            let result = send_drained(request.body(body.clone())).await;
             let elapsed_ms = start.elapsed().as_millis() as i64;
+            drop(permits);
+            if let Some(mut span) = attempt_span {
+                match &result {
+                    Ok(status) => {
+                        span.set_attribute("http.response.status_code", status.as_u16() as i64);
+                        if !status.is_success() {
+                            span.set_error(format!("HTTP {}", status.as_u16()));
+                        }
+                    }
+                    Err(e) => span.set_error(e.to_string()),
+                }
+                span.end();
+            }
 
             match result {
-                Ok(resp) => {
-                    let status_code = resp.status().as_u16() as i64;
-                    if resp.status().is_success() {
+                Ok(status) => {
+                    let status_code = status.as_u16() as i64;
+                    if status.is_success() {
                         log_delivery(
-                            conn,
+                            &self.conn,
                             &delivery_group,
                             &webhook_id,
                             event_name,
@@ -272,11 +906,12 @@ async fn deliver_webhooks(
                                 webhook_id, url, attempt
                             );
                         }
+                        delivered = true;
                         break;
                     } else {
                         let error_msg = format!("HTTP {}", status_code);
                         log_delivery(
-                            conn,
+                            &self.conn,
                             &delivery_group,
                             &webhook_id,
                             event_name,
@@ -289,8 +924,8 @@ async fn deliver_webhooks(
                         );
                         if attempt == MAX_ATTEMPTS {
                             eprintln!(
-                                "⚠️ Webhook {} delivery to {} exhausted after {} attempts (last: {})",
-                                webhook_id, url, MAX_ATTEMPTS, error_msg
+                                "⚠️ Webhook {} delivery to {} exhausted after {} attempts (last: {}, request_id: {})",
+                                webhook_id, url, MAX_ATTEMPTS, error_msg, request_id.unwrap_or("-")
                             );
                         }
                     }
@@ -298,7 +933,7 @@ async fn deliver_webhooks(
                 Err(e) => {
                     let error_msg = format!("{}", e);
                     log_delivery(
-                        conn,
+                        &self.conn,
                         &delivery_group,
                         &webhook_id,
                         event_name,
@@ -311,14 +946,129 @@ async fn deliver_webhooks(
                     );
                     if attempt == MAX_ATTEMPTS {
                         eprintln!(
-                            "⚠️ Webhook {} delivery to {} exhausted after {} attempts (last: {})",
-                            webhook_id, url, MAX_ATTEMPTS, error_msg
+                            "⚠️ Webhook {} delivery to {} exhausted after {} attempts (last: {}, request_id: {})",
+                            webhook_id, url, MAX_ATTEMPTS, error_msg, request_id.unwrap_or("-")
                         );
                     }
                 }
             }
         }
+
+        self.record_outcome(&webhook_id, delivered);
     }
+
+    /// Deliver a lifecycle event to every matching server-level webhook, with the same retry,
+    /// signing, headers and throttling as room webhooks. Only the latest outcome is recorded
+    /// per webhook. `room_created` carries the new room's admin key.
+    fn dispatch_server_event(
+        self: &Arc<Self>,
+        event_name: &'static str,
+        room_id: String,
+        room_name: String,
+        data: serde_json::Value,
+        request_id: Option<&str>,
+    ) {
+        let mut data = data;
+        let webhooks: Vec<(String, String, Option<String>, String)> = {
+            let db = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+            // Server hooks are registered with the server token, so they may configure the rooms they hear about
+            if event_name == "room_created"
+                && let Ok(key) = db.query_row("SELECT admin_key FROM rooms WHERE id = ?1", params![&room_id], |r| r.get::<_, String>(0))
+            {
+                data["admin_key"] = serde_json::json!(key);
+            }
+            db.prepare("SELECT id, url, secret, events FROM server_webhooks WHERE active = 1")
+                .and_then(|mut s| {
+                    s.query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)))
+                        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+                })
+                .unwrap_or_default()
+        };
+        let payload = WebhookPayload {
+            event: event_name.to_string(),
+            room_id,
+            room_name,
+            data,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            room_seq: None,
+        };
+        let body = serde_json::to_string(&payload).unwrap_or_default();
+
+        for (webhook_id, url, secret, events_str) in webhooks {
+            if events_str != "*" && !events_str.split(',').any(|e| e.trim() == event_name) {
+                continue;
+            }
+            let request_id = request_id.map(str::to_string);
+            tokio::spawn(Arc::clone(self).deliver_server(webhook_id, url, secret, event_name, body.clone(), request_id));
+        }
+    }
+
+    async fn deliver_server(
+        self: Arc<Self>,
+        webhook_id: String,
+        url: String,
+        secret: Option<String>,
+        event_name: &'static str,
+        body: String,
+        request_id: Option<String>,
+    ) {
+        let mut last_error = None;
+        for attempt in 1..=MAX_ATTEMPTS {
+            if attempt > 1 {
+                let backoff = RETRY_BACKOFFS_MS[(attempt - 2) as usize];
+                tokio::time::sleep(std::time::Duration::from_millis(backoff)).await;
+            }
+            let mut request = self
+                .client
+                .post(&url)
+                .header("Content-Type", "application/json")
+                .header("X-Chat-Event", event_name)
+                .header("X-Chat-Webhook-Id", &webhook_id);
+            if let Some(ref rid) = request_id {
+                request = request.header(crate::request_id::REQUEST_ID_HEADER, rid);
+            }
+            if let Some(ref secret) = secret
+                && let Ok(mut mac) = HmacSha256::new_from_slice(secret.as_bytes())
+            {
+                mac.update(body.as_bytes());
+                let signature = hex::encode(mac.finalize().into_bytes());
+                request = request.header("X-Chat-Signature", format!("sha256={}", signature));
+            }
+            let permits = self.throttle.acquire(&url).await;
+            last_error = match send_drained(request.body(body.clone())).await {
+                Ok(status) if status.is_success() => None,
+                Ok(status) => Some(format!("HTTP {}", status.as_u16())),
+                Err(e) => Some(e.to_string()),
+            };
+            drop(permits);
+            if last_error.is_none() {
+                break;
+            }
+        }
+        if let Some(ref e) = last_error {
+            eprintln!("⚠️ Server webhook {} delivery to {} exhausted after {} attempts (last: {})", webhook_id, url, MAX_ATTEMPTS, e);
+        }
+        let db = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        db.execute(
+            "UPDATE server_webhooks SET last_delivery_at = ?1, last_status = ?2, last_error = ?3 WHERE id = ?4",
+            params![
+                chrono::Utc::now().to_rfc3339(),
+                if last_error.is_none() { "success" } else { "failed" },
+                &last_error,
+                &webhook_id
+            ],
+        )
+        .ok();
+    }
+}
+
+/// Send a delivery and read the response to the end, so the connection goes back to the pool
+/// instead of being closed with an unread body.
+async fn send_drained(request: reqwest::RequestBuilder) -> Result<reqwest::StatusCode, reqwest::Error> {
+    let response = request.send().await?;
+    let status = response.status();
+    let _ = response.bytes().await;
+    Ok(status)
 }
 
 /// Log a single webhook delivery attempt to the database.
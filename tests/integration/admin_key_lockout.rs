@@ -0,0 +1,82 @@
+use crate::common::{create_test_room, test_client_with_rate_limits};
+use local_agent_chat::rate_limit::RateLimitConfig;
+use rocket::http::{Header, Status};
+use rocket::local::blocking::Client;
+use std::net::SocketAddr;
+
+fn attacker() -> SocketAddr {
+    "10.0.9.66:40000".parse().unwrap()
+}
+
+fn admin_host() -> SocketAddr {
+    "10.0.5.7:40000".parse().unwrap()
+}
+
+fn audit(client: &Client, room_id: &str, key: &str, from: SocketAddr) -> (Status, Option<Vec<serde_json::Value>>) {
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/audit"))
+        .header(Header::new("Authorization", format!("Bearer {key}")))
+        .remote(from)
+        .dispatch();
+    let status = res.status();
+    (status, (status == Status::Ok).then(|| res.into_json().unwrap()))
+}
+
+#[test]
+fn test_wrong_admin_keys_lock_out_the_address() {
+    let client = test_client_with_rate_limits(RateLimitConfig {
+        admin_key_max_failures: 3,
+        ..Default::default()
+    });
+    let (room_id, admin_key) = create_test_room(&client, "lockout");
+
+    for _ in 0..3 {
+        let (status, _) = audit(&client, &room_id, "chat_guess", attacker());
+        assert_eq!(status, Status::Forbidden);
+    }
+
+    // Locked out: even the right key is refused from that address
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/audit"))
+        .header(Header::new("X-Admin-Key", admin_key.clone()))
+        .remote(attacker())
+        .dispatch();
+    assert_eq!(res.status(), Status::TooManyRequests);
+    let retry_after: u64 = res.headers().get_one("Retry-After").unwrap().parse().unwrap();
+    assert!(retry_after > 0 && retry_after <= 60);
+
+    // Reads without a key still work, and other addresses are unaffected
+    let res = client.get(format!("/api/v1/rooms/{room_id}")).remote(attacker()).dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let (status, entries) = audit(&client, &room_id, &admin_key, admin_host());
+    assert_eq!(status, Status::Ok);
+    let entries = entries.unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["action"], "admin_key_lockout");
+    assert_eq!(entries[0]["details"]["ip"], "10.0.9.66");
+    assert_eq!(entries[0]["details"]["locked_for_secs"], 60);
+}
+
+#[test]
+fn test_lockout_is_per_room_and_can_be_disabled() {
+    let client = test_client_with_rate_limits(RateLimitConfig {
+        admin_key_max_failures: 2,
+        ..Default::default()
+    });
+    let (first, _) = create_test_room(&client, "lockout-first");
+    let (second, second_key) = create_test_room(&client, "lockout-second");
+    for _ in 0..2 {
+        audit(&client, &first, "chat_guess", attacker());
+    }
+    assert_eq!(audit(&client, &first, "chat_guess", attacker()).0, Status::TooManyRequests);
+    assert_eq!(audit(&client, &second, &second_key, attacker()).0, Status::Ok);
+
+    let client = test_client_with_rate_limits(RateLimitConfig {
+        admin_key_max_failures: 0,
+        ..Default::default()
+    });
+    let (room_id, _) = create_test_room(&client, "lockout-off");
+    for _ in 0..10 {
+        assert_eq!(audit(&client, &room_id, "chat_guess", attacker()).0, Status::Forbidden);
+    }
+}
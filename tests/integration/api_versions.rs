@@ -0,0 +1,110 @@
+use crate::common::{create_test_room, post_message, test_client};
+use rocket::http::{ContentType, Status};
+use serde_json::json;
+
+#[test]
+fn test_v1_responses_name_version_and_successor() {
+    let client = test_client();
+    let res = client.get("/api/v1/rooms").dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    assert_eq!(res.headers().get_one("API-Version"), Some("1"));
+    assert_eq!(
+        res.headers().get_one("Link"),
+        Some("</api/v2/rooms>; rel=\"successor-version\"")
+    );
+    assert!(res.headers().get_one("Deprecation").is_none());
+    // v1 shape is unchanged: a bare array
+    let rooms: serde_json::Value = res.into_json().unwrap();
+    assert!(rooms.is_array());
+}
+
+#[test]
+fn test_v2_lists_use_envelope() {
+    let client = test_client();
+    let res = client.get("/api/v2/rooms").dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    assert_eq!(res.headers().get_one("API-Version"), Some("2"));
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert!(body["items"].as_array().unwrap().iter().any(|r| r["name"] == "general"));
+    assert_eq!(body["has_more"], false);
+    assert!(body["next_cursor"].is_null());
+}
+
+#[test]
+fn test_v2_messages_keep_real_cursor() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "v2-paging");
+    for i in 0..3 {
+        post_message(&client, &room_id, json!({"sender": "bot", "content": format!("m{i}")}));
+    }
+    let res = client.get(format!("/api/v2/rooms/{room_id}/messages?limit=2")).dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["items"].as_array().unwrap().len(), 2);
+    assert_eq!(body["has_more"], true);
+    assert!(body["next_cursor"].is_i64());
+}
+
+#[test]
+fn test_v2_writes_reach_v1_handlers() {
+    let client = test_client();
+    let res = client
+        .post("/api/v2/rooms")
+        .header(ContentType::JSON)
+        .body(r#"{"name": "made-in-v2"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["name"], "made-in-v2");
+}
+
+#[test]
+fn test_v2_error_envelope() {
+    let client = test_client();
+    let res = client.get("/api/v2/rooms/no-such-room").dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["error"]["code"], "room_not_found");
+    assert_eq!(body["error"]["message"], "Room not found");
+    assert_eq!(body["error"]["status"], 404);
+
+    // Extra keys a handler adds move under details
+    let res = client.get("/api/v2/rooms?fields=bogus").dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["error"]["status"], 400);
+    assert!(body["error"]["message"].is_string());
+    assert!(body["error"]["details"]["valid_fields"].is_array());
+
+    // v1 errors keep their original shape
+    let res = client.get("/api/v1/rooms/no-such-room").dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["error"], "Room not found");
+}
+
+#[test]
+fn test_deprecated_v1_parameter_warns() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "deprecations");
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/messages?since=2020-01-01T00:00:00Z"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let deprecation = res.headers().get_one("Deprecation").unwrap();
+    assert!(deprecation.starts_with('@'));
+    assert!(deprecation[1..].parse::<i64>().is_ok());
+    assert!(res.headers().get_one("Warning").unwrap().starts_with("299 "));
+
+    let res = client.get(format!("/api/v1/rooms/{room_id}/messages?after=0")).dispatch();
+    assert!(res.headers().get_one("Deprecation").is_none());
+}
+
+#[test]
+fn test_discover_lists_versions() {
+    let client = test_client();
+    let res = client.get("/api/v1/discover").dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["api_versions"]["supported"], serde_json::json!(["v1", "v2"]));
+    let deprecations = body["api_versions"]["deprecations"].as_array().unwrap();
+    assert!(deprecations.iter().any(|d| d["query_param"] == "since" && d["method"] == "GET"));
+}
@@ -0,0 +1,116 @@
+use crate::common::{create_test_room, test_client};
+use rocket::http::{ContentType, Header, Status};
+
+#[test]
+fn test_session_issued_on_first_contact() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "sessions-typing");
+
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/typing"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "alice"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let token = res.headers().get_one("X-Client-Session").unwrap().to_string();
+    assert!(token.starts_with("cs_"));
+
+    // A client that sends its token isn't issued another
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/typing"))
+        .header(ContentType::JSON)
+        .header(Header::new("X-Client-Session", token.clone()))
+        .header(Header::new("X-Client-Device", "laptop"))
+        .body(r#"{"sender": "alice"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    assert!(res.headers().get_one("X-Client-Session").is_none());
+
+    // Routes that don't track sessions don't hand them out
+    let res = client.get(format!("/api/v1/rooms/{room_id}")).dispatch();
+    assert!(res.headers().get_one("X-Client-Session").is_none());
+
+    let state: serde_json::Value = client
+        .get("/api/v1/presence/device-state/alice")
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let sessions = state["sessions"].as_array().unwrap();
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0]["session"], token.as_str());
+    assert_eq!(sessions[0]["device"], "laptop");
+    assert_eq!(sessions[0]["connections"], 0);
+}
+
+#[test]
+fn test_presence_per_session() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "sessions-presence");
+
+    let laptop = client
+        .get(format!("/api/v1/rooms/{room_id}/stream?sender=alice&session=cs_laptop&device=laptop"))
+        .dispatch();
+    assert_eq!(laptop.status(), Status::Ok);
+    let phone = client
+        .get(format!("/api/v1/rooms/{room_id}/stream?sender=alice"))
+        .header(Header::new("X-Client-Session", "cs_phone"))
+        .header(Header::new("X-Client-Device", "phone"))
+        .dispatch();
+    assert_eq!(phone.status(), Status::Ok);
+
+    // One sender online, on two devices
+    let body: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/presence"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(body["count"], 1);
+    let sessions = body["online"][0]["sessions"].as_array().unwrap();
+    let mut devices: Vec<&str> = sessions.iter().map(|s| s["device"].as_str().unwrap()).collect();
+    devices.sort();
+    assert_eq!(devices, ["laptop", "phone"]);
+    assert!(sessions.iter().all(|s| s["connections"] == 1));
+
+    // Closing one device's stream leaves the other
+    drop(phone);
+    let body: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/presence"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(body["count"], 1);
+    let sessions = body["online"][0]["sessions"].as_array().unwrap();
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0]["session"], "cs_laptop");
+
+    // The closed session stays listed for the sender until it has been idle a while
+    let state: serde_json::Value = client
+        .get("/api/v1/presence/device-state/alice")
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let phone = state["sessions"].as_array().unwrap().iter().find(|s| s["session"] == "cs_phone").unwrap();
+    assert_eq!(phone["connections"], 0);
+    drop(laptop);
+}
+
+#[test]
+fn test_presence_tracker_sessions_unit() {
+    use local_agent_chat::routes::PresenceTracker;
+    use local_agent_chat::sessions::ClientSession;
+
+    let tracker = PresenceTracker::default();
+    let session = |token: &str| ClientSession {
+        token: token.to_string(),
+        device: None,
+        issued: false,
+    };
+    assert!(tracker.join_session("room1", "alice", None, Some(&session("a"))));
+    assert!(!tracker.join_session("room1", "alice", None, Some(&session("b"))));
+    assert_eq!(tracker.get_room("room1")[0].sessions.len(), 2);
+    assert!(!tracker.leave_session("room1", "alice", Some("a")));
+    assert_eq!(tracker.get_room("room1")[0].sessions.len(), 1);
+    assert!(tracker.leave_session("room1", "alice", Some("b")));
+    assert!(tracker.get_room("room1").is_empty());
+    assert_eq!(tracker.sessions("alice").len(), 2);
+}
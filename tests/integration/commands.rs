@@ -0,0 +1,108 @@
+use rocket::http::{ContentType, Status};
+use crate::common::{test_client, create_test_room, TestClient};
+
+fn register(client: &TestClient, room_id: &str, sender: &str, body: &str) -> (Status, serde_json::Value) {
+    let res = client
+        .put(format!("/api/v1/rooms/{room_id}/commands/{sender}"))
+        .header(ContentType::JSON)
+        .body(body)
+        .dispatch();
+    let status = res.status();
+    (status, res.into_json().unwrap_or_default())
+}
+
+#[test]
+fn test_register_and_list_commands() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "commands-room");
+
+    let (status, body) = register(
+        &client,
+        &room_id,
+        "deploy-bot",
+        r#"{"commands": [
+            {"name": "/Deploy", "description": "Deploy a branch to staging", "usage": "<branch>"},
+            {"name": "status", "description": "Show the current deploy"}
+        ]}"#,
+    );
+    assert_eq!(status, Status::Ok);
+    let commands = body["commands"].as_array().unwrap();
+    assert_eq!(commands.len(), 2);
+    assert_eq!(commands[0]["name"], "deploy");
+    assert_eq!(commands[0]["usage"], "<branch>");
+    assert!(commands[1].get("usage").is_none());
+
+    register(&client, &room_id, "triage-bot", r#"{"commands": [{"name": "label", "description": "Label an issue"}]}"#);
+
+    // Re-registering replaces the sender's set
+    let (_, body) = register(&client, &room_id, "deploy-bot", r#"{"commands": [{"name": "rollback", "description": "Undo the last deploy"}]}"#);
+    assert_eq!(body["commands"].as_array().unwrap().len(), 1);
+
+    let list: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/commands"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let names: Vec<&str> = list["commands"].as_array().unwrap().iter().map(|c| c["name"].as_str().unwrap()).collect();
+    assert_eq!(names, vec!["rollback", "label"]);
+
+    let res = client.delete(format!("/api/v1/rooms/{room_id}/commands/triage-bot")).dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let res = client.delete(format!("/api/v1/rooms/{room_id}/commands/triage-bot")).dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+}
+
+#[test]
+fn test_register_commands_validation() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "commands-validation");
+    for body in [
+        r#"{"commands": [{"name": "has space", "description": "x"}]}"#,
+        r#"{"commands": [{"name": "ok", "description": ""}]}"#,
+        r#"{"commands": [{"name": "dup", "description": "a"}, {"name": "/dup", "description": "b"}]}"#,
+    ] {
+        assert_eq!(register(&client, &room_id, "bot", body).0, Status::BadRequest, "{body}");
+    }
+    assert_eq!(
+        register(&client, "no-such-room", "bot", r#"{"commands": [{"name": "ok", "description": "x"}]}"#).0,
+        Status::NotFound
+    );
+}
+
+#[test]
+fn test_room_help_and_llms_txt_list_commands() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "commands-help");
+    client
+        .put("/api/v1/profiles/deploy-bot")
+        .header(ContentType::JSON)
+        .body(r#"{"display_name": "Deployer", "bio": "Ships things."}"#)
+        .dispatch();
+
+    let res = client.get(format!("/api/v1/rooms/{room_id}/help")).dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    assert!(res.into_string().unwrap().contains("No bots have registered commands"));
+
+    register(
+        &client,
+        &room_id,
+        "deploy-bot",
+        r#"{"commands": [{"name": "deploy", "description": "Deploy a branch to staging", "usage": "<branch>"}]}"#,
+    );
+
+    let res = client.get(format!("/api/v1/rooms/{room_id}/help")).dispatch();
+    assert_eq!(res.content_type(), Some(ContentType::Markdown));
+    let help = res.into_string().unwrap();
+    assert!(help.starts_with("# #commands-help"));
+    assert!(help.contains("### Deployer (@deploy-bot)"));
+    assert!(help.contains("Ships things."));
+    assert!(help.contains("- `/deploy <branch>` — Deploy a branch to staging"));
+
+    let llms = client.get("/llms.txt").dispatch().into_string().unwrap();
+    assert!(llms.contains("## Registered Agent Commands"));
+    assert!(llms.contains(&format!("### #commands-help ({room_id})")));
+    assert!(llms.contains("`/deploy <branch>`"));
+
+    let res = client.get("/api/v1/rooms/no-such-room/help").dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+}
@@ -6,18 +6,46 @@ use rocket::local::blocking::Client;
 pub struct TestClient {
     client: Option<Client>,
     db_path: String,
+    /// Namespace databases created next to `db_path`, removed alongside it
+    namespaces: Vec<String>,
 }
 
 impl Drop for TestClient {
     fn drop(&mut self) {
         // Drop client first to release SQLite connection (WAL mode holds the file)
         drop(self.client.take());
-        let _ = std::fs::remove_file(&self.db_path);
-        let _ = std::fs::remove_file(format!("{}-wal", self.db_path));
-        let _ = std::fs::remove_file(format!("{}-shm", self.db_path));
+        let stem = self.db_path.trim_end_matches(".db");
+        let paths = std::iter::once(self.db_path.clone())
+            .chain(self.namespaces.iter().map(|ns| format!("{stem}.{ns}.db")));
+        for path in paths {
+            cleanup(&path);
+        }
     }
 }
 
+impl TestClient {
+    /// Path of the backing SQLite file, for tests that need to adjust state the API
+    /// can't (e.g. backdating timestamps).
+    pub fn db_path(&self) -> &str {
+        &self.db_path
+    }
+}
+
+/// A unique temp DB path, for tests that open a `Db` directly instead of going through a client.
+pub fn temp_path() -> String {
+    format!(
+        "/tmp/chat_test_{}.db",
+        uuid::Uuid::new_v4().to_string().split('-').next().unwrap()
+    )
+}
+
+/// Remove a temp DB along with its WAL and shared-memory files.
+pub fn cleanup(path: &str) {
+    let _ = std::fs::remove_file(path);
+    let _ = std::fs::remove_file(format!("{path}-wal"));
+    let _ = std::fs::remove_file(format!("{path}-shm"));
+}
+
 impl std::ops::Deref for TestClient {
     type Target = Client;
     fn deref(&self) -> &Client {
@@ -27,27 +55,67 @@ impl std::ops::Deref for TestClient {
 
 pub fn test_client() -> TestClient {
     // Use unique temp DB for each test (avoids parallel test contention)
-    let db_path = format!(
-        "/tmp/chat_test_{}.db",
-        uuid::Uuid::new_v4().to_string().split('-').next().unwrap()
-    );
+    let db_path = temp_path();
 
     let rocket = local_agent_chat::rocket_with_db(&db_path);
     let client = Client::tracked(rocket).expect("valid rocket instance");
-    TestClient { client: Some(client), db_path }
+    TestClient { client: Some(client), db_path, namespaces: Vec::new() }
 }
 
 /// Create a test client with custom rate limit configuration.
 /// Useful for testing configurable rate limits without env var races.
 pub fn test_client_with_rate_limits(config: local_agent_chat::rate_limit::RateLimitConfig) -> TestClient {
-    let db_path = format!(
-        "/tmp/chat_test_{}.db",
-        uuid::Uuid::new_v4().to_string().split('-').next().unwrap()
-    );
+    let db_path = temp_path();
 
     let rocket = local_agent_chat::rocket_with_db_and_config(&db_path, config);
     let client = Client::tracked(rocket).expect("valid rocket instance");
-    TestClient { client: Some(client), db_path }
+    TestClient { client: Some(client), db_path, namespaces: Vec::new() }
+}
+
+/// Create a test client with a custom reserved-sender policy (avoids SERVER_TOKEN env races).
+pub fn test_client_with_sender_policy(policy: local_agent_chat::senders::SenderPolicy) -> TestClient {
+    let db_path = temp_path();
+
+    let rocket = local_agent_chat::rocket_with_db_and_sender_policy(&db_path, policy);
+    let client = Client::tracked(rocket).expect("valid rocket instance");
+    TestClient { client: Some(client), db_path, namespaces: Vec::new() }
+}
+
+/// Create a test client whose server token is "srv_secret", with no reserved names, for
+/// admin endpoints.
+pub fn admin_client() -> TestClient {
+    test_client_with_sender_policy(local_agent_chat::senders::SenderPolicy {
+        protected: vec![],
+        server_token: Some("srv_secret".to_string()),
+    })
+}
+
+/// Sender policy reserving "system", "admin" and "ops-bot", with "srv_secret" as the server
+/// token.
+pub fn policy_with_token() -> local_agent_chat::senders::SenderPolicy {
+    local_agent_chat::senders::SenderPolicy {
+        protected: vec!["system".to_string(), "admin".to_string(), "ops-bot".to_string()],
+        server_token: Some("srv_secret".to_string()),
+    }
+}
+
+/// Create a test client with a custom IP policy (avoids IP_* env races).
+pub fn test_client_with_ip_policy(policy: local_agent_chat::ip_policy::IpPolicy) -> TestClient {
+    let db_path = temp_path();
+
+    let rocket = local_agent_chat::rocket_with_db_and_ip_policy(&db_path, policy);
+    let client = Client::tracked(rocket).expect("valid rocket instance");
+    TestClient { client: Some(client), db_path, namespaces: Vec::new() }
+}
+
+/// Create a test client serving the given namespaces (avoids NAMESPACES env races).
+pub fn test_client_with_namespaces(namespaces: &[&str]) -> TestClient {
+    let db_path = temp_path();
+    let namespaces: Vec<String> = namespaces.iter().map(|n| n.to_string()).collect();
+
+    let rocket = local_agent_chat::rocket_with_db_and_namespaces(&db_path, namespaces.clone());
+    let client = Client::tracked(rocket).expect("valid rocket instance");
+    TestClient { client: Some(client), db_path, namespaces }
 }
 
 /// Helper: create a room and return (room_id, admin_key)
@@ -65,3 +133,76 @@ pub fn create_test_room(client: &Client, name: &str) -> (String, String) {
         body["admin_key"].as_str().unwrap().to_string(),
     )
 }
+
+/// Helper: post a message (`body` is the JSON request, e.g. `{"sender", "content"}`) and return
+/// the created message. Panics unless the post succeeds.
+pub fn post_message(client: &Client, room_id: &str, body: serde_json::Value) -> serde_json::Value {
+    use rocket::http::{ContentType, Status};
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .body(body.to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    res.into_json().unwrap()
+}
+
+/// Helper: POST a JSON body and return (status, response body), with `null` for a non-JSON body.
+pub fn post_json(client: &Client, path: &str, body: serde_json::Value) -> (rocket::http::Status, serde_json::Value) {
+    use rocket::http::ContentType;
+    let res = client.post(path.to_string()).header(ContentType::JSON).body(body.to_string()).dispatch();
+    let status = res.status();
+    (status, res.into_json().unwrap_or(serde_json::Value::Null))
+}
+
+/// Helper: react to a message. Panics unless the reaction is accepted.
+pub fn react(client: &Client, room_id: &str, message_id: &str, sender: &str, emoji: &str) {
+    use rocket::http::{ContentType, Status};
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages/{message_id}/reactions"))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"sender": sender, "emoji": emoji}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+}
+
+/// Helper: upload a small text file as "uploader" and return (status, response body). Fields in
+/// `fields` (e.g. `filename`, `content_type`, `data`, `expires_in`) override the defaults.
+pub fn upload_file(client: &Client, room_id: &str, fields: serde_json::Value) -> (rocket::http::Status, serde_json::Value) {
+    let mut body = serde_json::json!({
+        "sender": "uploader",
+        "filename": "dump.txt",
+        "content_type": "text/plain",
+        "data": "ZHVtcA=="
+    });
+    for (k, v) in fields.as_object().unwrap() {
+        body[k] = v.clone();
+    }
+    post_json(client, &format!("/api/v1/rooms/{room_id}/files"), body)
+}
+
+/// Helper: read an SSE response until an event named `until` arrives (or the stream ends),
+/// returning every complete (event, data) frame so far.
+pub fn read_sse(res: &mut rocket::local::blocking::LocalResponse<'_>, until: &str) -> Vec<(String, serde_json::Value)> {
+    use std::io::Read;
+    let mut text = String::new();
+    let mut buf = [0u8; 8192];
+    loop {
+        let frames: Vec<(String, serde_json::Value)> = text
+            .split("\n\n")
+            .filter_map(|frame| {
+                let name = frame.lines().find_map(|l| l.strip_prefix("event:"))?.trim().to_string();
+                let data = frame.lines().find_map(|l| l.strip_prefix("data:"))?.trim();
+                Some((name, serde_json::from_str(data).ok()?))
+            })
+            .collect();
+        if frames.iter().any(|(name, _)| name == until) {
+            return frames;
+        }
+        let n = res.read(&mut buf).unwrap_or(0);
+        if n == 0 {
+            return frames;
+        }
+        text.push_str(&String::from_utf8_lossy(&buf[..n]));
+    }
+}
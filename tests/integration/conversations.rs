@@ -0,0 +1,80 @@
+use rocket::http::Status;
+use crate::common::{create_test_room, test_client, TestClient};
+
+// --- Conversation clustering ---
+
+/// Insert a message `minutes` after a fixed start, returning its id.
+fn insert(client: &TestClient, room_id: &str, minutes: i64, sender: &str, content: &str, reply_to: Option<&str>) -> String {
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    let id = uuid::Uuid::new_v4().to_string();
+    let at = chrono::DateTime::parse_from_rfc3339("2026-03-02T09:00:00+00:00").unwrap() + chrono::Duration::minutes(minutes);
+    conn.execute(
+        "INSERT INTO messages (id, room_id, sender, content, metadata, created_at, reply_to, seq)
+         VALUES (?1, ?2, ?3, ?4, '{}', ?5, ?6, (SELECT COALESCE(MAX(seq), 0) + 1 FROM messages))",
+        rusqlite::params![&id, room_id, sender, content, at.to_rfc3339(), reply_to],
+    )
+    .unwrap();
+    id
+}
+
+#[test]
+fn test_conversations_split_on_gaps_and_follow_replies() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "conversations");
+
+    // Deploy chat
+    let deploy = insert(&client, &room_id, 0, "forge", "deploying v2 now", None);
+    insert(&client, &room_id, 1, "drift", "@forge need a hand?", None);
+    // A new sender mentioning nobody joins whatever conversation is still open
+    insert(&client, &room_id, 2, "lux", "anyone seen the flaky test?", None);
+    // An hour of silence, then a new conversation...
+    insert(&client, &room_id, 62, "sage", "good morning", None);
+    // ...and a late reply that belongs to the deploy conversation
+    let late = insert(&client, &room_id, 63, "forge", "done, v2 is live", Some(&deploy));
+
+    let res = client.get(format!("/api/v1/rooms/{room_id}/conversations?after=0")).dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["gap_secs"], 300);
+    assert_eq!(body["count"], 2);
+
+    let first = &body["conversations"][0];
+    assert_eq!(first["message_count"], 4);
+    assert_eq!(first["participants"], serde_json::json!(["forge", "drift", "lux"]));
+    assert_eq!(first["preview"], "deploying v2 now");
+    assert_eq!(first["message_ids"][3], late.as_str());
+
+    let second = &body["conversations"][1];
+    assert_eq!(second["message_count"], 1);
+    assert_eq!(second["participants"], serde_json::json!(["sage"]));
+
+    // With a 30s gap every minute-apart message stands alone, except the explicit reply
+    let body: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/conversations?after=0&gap_secs=30"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(body["count"], 4);
+    assert_eq!(body["conversations"][0]["message_count"], 2);
+}
+
+#[test]
+fn test_conversations_mentions_join_open_conversation() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "conversations-mentions");
+    insert(&client, &room_id, 0, "alpha", "starting the migration", None);
+    insert(&client, &room_id, 10, "beta", "build is red", None);
+    // beta's message comes after a 10-minute lull; gamma's @mention ties it to beta's conversation
+    insert(&client, &room_id, 12, "gamma", "@beta which job?", None);
+
+    let body: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/conversations"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(body["count"], 2);
+    assert_eq!(body["conversations"][1]["participants"], serde_json::json!(["beta", "gamma"]));
+
+    let res = client.get(format!("/api/v1/rooms/{room_id}/conversations?gap_secs=1")).dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+}
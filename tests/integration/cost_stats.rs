@@ -0,0 +1,98 @@
+use crate::common::{create_test_room, post_message, test_client, TestClient};
+use rocket::http::{ContentType, Status};
+use serde_json::json;
+
+fn costs(client: &TestClient, query: &str) -> serde_json::Value {
+    let res = client.get(format!("/api/v1/stats/costs{query}")).dispatch();
+    assert_eq!(res.status(), Status::Ok, "{query}");
+    res.into_json().unwrap()
+}
+
+fn usage(prompt: i64, completion: i64, cost: f64) -> serde_json::Value {
+    serde_json::json!({"usage": {"prompt_tokens": prompt, "completion_tokens": completion, "cost_usd": cost}})
+}
+
+#[test]
+fn test_cost_stats_grouping() {
+    let client = test_client();
+    let (a, _) = create_test_room(&client, "costs-a");
+    let (b, _) = create_test_room(&client, "costs-b");
+
+    post_message(&client, &a, json!({"sender": "planner", "content": "done", "metadata": usage(1000, 200, 0.5)}));
+    post_message(&client, &a, json!({"sender": "planner", "content": "done", "metadata": usage(500, 100, 0.25)}));
+    post_message(&client, &b, json!({"sender": "planner", "content": "done", "metadata": usage(100, 50, 0.05)}));
+    post_message(&client, &b, json!({"sender": "coder", "content": "done", "metadata": usage(2000, 800, 1.5)}));
+    // No usage block, or a malformed one, doesn't distort the numbers
+    post_message(&client, &a, json!({"sender": "coder", "content": "done", "metadata": {}}));
+    post_message(
+        &client,
+        &a,
+        json!({"sender": "coder", "content": "done", "metadata": {"usage": {"prompt_tokens": "lots", "cost_usd": 0.1}}}),
+    );
+
+    let body = costs(&client, "");
+    assert_eq!(body["group_by"], "sender");
+    assert_eq!(body["totals"]["messages"], 5);
+    assert_eq!(body["totals"]["prompt_tokens"], 3600);
+    assert_eq!(body["totals"]["completion_tokens"], 1150);
+    assert_eq!(body["totals"]["total_tokens"], 4750);
+    assert!((body["totals"]["cost_usd"].as_f64().unwrap() - 2.4).abs() < 1e-9);
+    let groups = body["groups"].as_array().unwrap();
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0]["key"], "coder");
+    assert_eq!(groups[0]["messages"], 2);
+    assert_eq!(groups[0]["prompt_tokens"], 2000);
+    assert_eq!(groups[1]["key"], "planner");
+    assert_eq!(groups[1]["total_tokens"], 1950);
+    assert!(groups[1].get("room_name").is_none());
+
+    let body = costs(&client, "?group_by=room");
+    let groups = body["groups"].as_array().unwrap();
+    assert_eq!(groups[0]["key"], b.as_str());
+    assert_eq!(groups[0]["room_name"], "costs-b");
+    assert_eq!(groups[1]["key"], a.as_str());
+    assert_eq!(groups[1]["messages"], 3);
+
+    let body = costs(&client, "?group_by=day");
+    let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    assert_eq!(body["groups"][0]["key"], today.as_str());
+    assert_eq!(body["groups"][0]["messages"], 5);
+}
+
+#[test]
+fn test_cost_stats_filters() {
+    let client = test_client();
+    let (a, _) = create_test_room(&client, "costs-filter-a");
+    let (b, _) = create_test_room(&client, "costs-filter-b");
+    let res = client
+        .put("/api/v1/profiles/analyst")
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"aliases": ["analyst-v2"]}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    post_message(&client, &a, json!({"sender": "analyst", "content": "done", "metadata": usage(10, 10, 0.01)}));
+    post_message(&client, &b, json!({"sender": "analyst-v2", "content": "done", "metadata": usage(20, 20, 0.02)}));
+    post_message(&client, &b, json!({"sender": "other", "content": "done", "metadata": usage(40, 40, 0.04)}));
+
+    // Aliases fold into their profile, both as a group and as a filter
+    let body = costs(&client, "?sender=analyst-v2");
+    assert_eq!(body["groups"].as_array().unwrap().len(), 1);
+    assert_eq!(body["groups"][0]["key"], "analyst");
+    assert_eq!(body["totals"]["total_tokens"], 60);
+
+    let body = costs(&client, &format!("?room_id={b}&group_by=sender"));
+    assert_eq!(body["totals"]["messages"], 2);
+
+    let tomorrow = (chrono::Utc::now() + chrono::Duration::days(1)).format("%Y-%m-%d").to_string();
+    let body = costs(&client, &format!("?since={tomorrow}"));
+    assert_eq!(body["totals"]["messages"], 0);
+    assert!(body["since"].as_str().unwrap().starts_with(&tomorrow));
+    let body = costs(&client, &format!("?until={tomorrow}"));
+    assert_eq!(body["totals"]["messages"], 3);
+
+    let res = client.get("/api/v1/stats/costs?group_by=model").dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+    let res = client.get("/api/v1/stats/costs?since=yesterday").dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+}
@@ -464,9 +464,9 @@ fn test_incoming_webhook_respects_room_retention() {
     let result: serde_json::Value = res.into_json().unwrap();
     assert_eq!(result["total_pruned"].as_i64().unwrap(), 3);
 
-    // Only newest 10 messages should remain
+    // Only newest 10 messages should remain (plus the retention note)
     let res = client
-        .get(format!("/api/v1/rooms/{room_id}/messages"))
+        .get(format!("/api/v1/rooms/{room_id}/messages?kind=message"))
         .dispatch();
     let messages: Vec<serde_json::Value> = res.into_json().unwrap();
     assert_eq!(messages.len(), 10);
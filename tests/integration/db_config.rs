@@ -0,0 +1,106 @@
+use crate::common::{cleanup, temp_path};
+use local_agent_chat::db::{Db, DbConfig};
+use rocket::http::{Header, Status};
+
+fn pragma<T: rusqlite::types::FromSql>(db: &Db, name: &str) -> T {
+    db.conn()
+        .query_row(&format!("PRAGMA {name}"), [], |r| r.get(0))
+        .unwrap()
+}
+
+#[test]
+fn test_db_config_defaults_wal_normal() {
+    let path = temp_path();
+    let db = Db::with_config(&path, &DbConfig::default());
+
+    let mode: String = pragma(&db, "journal_mode");
+    assert_eq!(mode.to_lowercase(), "wal");
+    // synchronous: 0=OFF, 1=NORMAL, 2=FULL, 3=EXTRA
+    assert_eq!(pragma::<i64>(&db, "synchronous"), 1);
+    assert_eq!(pragma::<i64>(&db, "cache_size"), -16000);
+    assert_eq!(pragma::<i64>(&db, "busy_timeout"), 5000);
+    assert_eq!(pragma::<i64>(&db, "foreign_keys"), 1);
+
+    drop(db);
+    cleanup(&path);
+}
+
+#[test]
+fn test_db_config_custom_values_applied() {
+    let path = temp_path();
+    let config = DbConfig {
+        journal_mode: "DELETE".to_string(),
+        synchronous: "FULL".to_string(),
+        cache_size: -4000,
+        mmap_size: 0,
+        busy_timeout_ms: 12345,
+        slow_query_ms: None,
+    };
+    let db = Db::with_config(&path, &config);
+
+    let mode: String = pragma(&db, "journal_mode");
+    assert_eq!(mode.to_lowercase(), "delete");
+    assert_eq!(pragma::<i64>(&db, "synchronous"), 2);
+    assert_eq!(pragma::<i64>(&db, "cache_size"), -4000);
+    assert_eq!(pragma::<i64>(&db, "busy_timeout"), 12345);
+
+    drop(db);
+    cleanup(&path);
+}
+
+#[test]
+fn test_slow_query_profiling_records_statements() {
+    let path = temp_path();
+    // Threshold 0 records every statement on this connection
+    let config = DbConfig { slow_query_ms: Some(0), ..Default::default() };
+    let db = Db::with_config(&path, &config);
+
+    let _: i64 = db
+        .conn()
+        .query_row("SELECT COUNT(*) FROM rooms WHERE name = 'profiled-marker'", [], |r| r.get(0))
+        .unwrap();
+
+    let recorded = local_agent_chat::db::slow_queries();
+    let hit = recorded
+        .iter()
+        .find(|q| q.sql.contains("profiled-marker"))
+        .expect("query should be recorded");
+    assert!(hit.duration_ms >= 0.0);
+    assert!(!hit.recorded_at.is_empty());
+
+    drop(db);
+    cleanup(&path);
+}
+
+#[test]
+fn test_slow_queries_endpoint_disabled_by_default() {
+    let client = crate::common::admin_client();
+    let res = client
+        .get("/api/v1/diagnostics/slow-queries")
+        .header(Header::new("X-Server-Token", "srv_secret"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["enabled"], false);
+    assert_eq!(body["count"], 0);
+    assert!(body["queries"].as_array().unwrap().is_empty());
+    assert!(body.get("threshold_ms").is_none());
+}
+
+#[test]
+fn test_slow_queries_endpoint_requires_server_token() {
+    let client = crate::common::admin_client();
+    let res = client.get("/api/v1/diagnostics/slow-queries").dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+
+    let res = client
+        .get("/api/v1/diagnostics/slow-queries")
+        .header(Header::new("X-Server-Token", "wrong"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+
+    // Without SERVER_TOKEN configured the endpoint stays closed
+    let client = crate::common::test_client();
+    let res = client.get("/api/v1/diagnostics/slow-queries").dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+}
@@ -0,0 +1,120 @@
+use crate::common::{create_test_room, post_message, react, test_client};
+use rocket::http::{ContentType, Status};
+use rocket::local::blocking::Client;
+use serde_json::json;
+
+fn mark_decision(client: &Client, room_id: &str, message_id: &str) {
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages/{message_id}/labels"))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"sender": "lead", "labels": ["decision"]}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+}
+
+#[test]
+fn test_decision_log_with_thread_and_approvals() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "decision-log");
+    let root = post_message(
+        &client,
+        &room_id,
+        json!({"sender": "alice", "content": "Which database should we use?"}),
+    )["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    post_message(
+        &client,
+        &room_id,
+        json!({"sender": "bob", "content": "postgres has the features we need", "reply_to": &root}),
+    );
+    let decision = post_message(
+        &client,
+        &room_id,
+        json!({"sender": "alice", "content": "Decided: postgres", "reply_to": &root}),
+    )["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let other = post_message(&client, &room_id, json!({"sender": "carol", "content": "Freeze on Fridays"}))["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    mark_decision(&client, &room_id, &decision);
+    mark_decision(&client, &room_id, &other);
+    react(&client, &room_id, &decision, "bob", ":+1:");
+    react(&client, &room_id, &decision, "carol", "✅");
+    react(&client, &room_id, &decision, "dave", "🎉");
+
+    let res = client.get(format!("/api/v1/rooms/{room_id}/decisions")).dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["count"], 2);
+    assert_eq!(body["room_name"], "decision-log");
+
+    let first = &body["decisions"][0];
+    assert_eq!(first["message"]["id"], decision.as_str());
+    assert_eq!(first["labeled_by"], "lead");
+    assert_eq!(first["thread_root"]["id"], root.as_str());
+    assert_eq!(first["thread_replies"], 2);
+    let approvers: Vec<&str> = first["approvals"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| a["sender"].as_str().unwrap())
+        .collect();
+    assert_eq!(approvers, vec!["bob", "carol"]);
+
+    let second = &body["decisions"][1];
+    assert!(second.get("thread_root").is_none());
+    assert_eq!(second["thread_replies"], 0);
+    assert!(second["approvals"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn test_decision_log_markdown() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "decision-md");
+    let decision = post_message(&client, &room_id, json!({"sender": "alice", "content": "Ship v2 on Monday"}))["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    mark_decision(&client, &room_id, &decision);
+    react(&client, &room_id, &decision, "bob", "👍");
+
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/decisions?format=markdown"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    assert_eq!(res.content_type(), Some(ContentType::new("text", "markdown")));
+    let md = res.into_string().unwrap();
+    assert!(md.starts_with("# Decisions — #decision-md"));
+    assert!(md.contains("Ship v2 on Monday"));
+    assert!(md.contains("Approved by: bob 👍"));
+}
+
+#[test]
+fn test_decision_log_filters_and_errors() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "decision-filters");
+    let decision = post_message(&client, &room_id, json!({"sender": "alice", "content": "Use tabs"}))["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    mark_decision(&client, &room_id, &decision);
+
+    let body: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/decisions?after_date=2999-01-01T00:00:00Z"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(body["count"], 0);
+
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/decisions?format=pdf"))
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+    let res = client.get("/api/v1/rooms/missing/decisions").dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+}
@@ -0,0 +1,83 @@
+use crate::common::test_client;
+use rocket::http::{ContentType, Status};
+
+fn report(client: &crate::common::TestClient, body: serde_json::Value) -> (Status, serde_json::Value) {
+    let res = client
+        .put("/api/v1/presence/device-state")
+        .header(ContentType::JSON)
+        .body(body.to_string())
+        .dispatch();
+    let status = res.status();
+    (status, res.into_json().unwrap_or_default())
+}
+
+#[test]
+fn test_device_state_defaults_to_active() {
+    let client = test_client();
+    let res = client.get("/api/v1/presence/device-state/nobody").dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["availability"], "active");
+    assert!(body["devices"].as_array().unwrap().is_empty());
+    assert!(body.get("idle_since").is_none());
+}
+
+#[test]
+fn test_device_state_idle_and_back() {
+    let client = test_client();
+    let (status, body) = report(&client, serde_json::json!({"sender": "nate", "state": "idle", "idle_secs": 600}));
+    assert_eq!(status, Status::Ok);
+    assert_eq!(body["availability"], "idle");
+    assert_eq!(body["devices"][0]["device"], "default");
+    let idle_since = chrono::DateTime::parse_from_rfc3339(body["idle_since"].as_str().unwrap()).unwrap();
+    let idle_for = chrono::Utc::now().signed_duration_since(idle_since).num_seconds();
+    assert!((599..=610).contains(&idle_for), "idle for {idle_for}s");
+
+    let (_, body) = report(&client, serde_json::json!({"sender": "nate", "state": "active"}));
+    assert_eq!(body["availability"], "active");
+    assert!(body.get("idle_since").is_none());
+}
+
+#[test]
+fn test_device_state_any_active_device_wins() {
+    let client = test_client();
+    report(&client, serde_json::json!({"sender": "nate", "device": "laptop", "state": "idle"}));
+    let (_, body) = report(&client, serde_json::json!({"sender": "nate", "device": "desktop", "state": "active"}));
+    assert_eq!(body["availability"], "active");
+    assert_eq!(body["devices"].as_array().unwrap().len(), 2);
+
+    let (_, body) = report(&client, serde_json::json!({"sender": "nate", "device": "desktop", "state": "idle"}));
+    assert_eq!(body["availability"], "idle");
+}
+
+#[test]
+fn test_device_state_validation() {
+    let client = test_client();
+    let (status, _) = report(&client, serde_json::json!({"sender": "nate", "state": "away"}));
+    assert_eq!(status, Status::BadRequest);
+    let (status, _) = report(&client, serde_json::json!({"sender": " ", "state": "idle"}));
+    assert_eq!(status, Status::BadRequest);
+    let (status, _) = report(&client, serde_json::json!({"sender": "nate", "state": "idle", "idle_secs": 99_999_999}));
+    assert_eq!(status, Status::BadRequest);
+}
+
+#[test]
+fn test_presence_entries_carry_availability() {
+    use local_agent_chat::routes::PresenceTracker;
+
+    let tracker = PresenceTracker::default();
+    tracker.join("room1", "nate", Some("human"));
+    tracker.join("room1", "bot", Some("agent"));
+    tracker.report_device("nate", "laptop", true, Some(30));
+
+    let online = tracker.get_room("room1");
+    let nate = online.iter().find(|e| e.sender == "nate").unwrap();
+    assert_eq!(nate.availability, "idle");
+    assert!(nate.idle_since.is_some());
+    let bot = online.iter().find(|e| e.sender == "bot").unwrap();
+    assert_eq!(bot.availability, "active");
+    assert!(bot.idle_since.is_none());
+
+    let all = tracker.get_all();
+    assert_eq!(all["room1"].iter().find(|e| e.sender == "nate").unwrap().availability, "idle");
+}
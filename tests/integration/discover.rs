@@ -1,6 +1,9 @@
 use rocket::http::Status;
 
-use crate::common::test_client;
+use crate::common::{test_client, test_client_with_sender_policy};
+use local_agent_chat::capabilities::Capabilities;
+use local_agent_chat::regex_search::RegexSearchConfig;
+use local_agent_chat::senders::SenderPolicy;
 
 #[test]
 fn test_discover_returns_service_info() {
@@ -59,6 +62,7 @@ fn test_discover_has_endpoints() {
     assert_eq!(endpoints["discover"], "/api/v1/discover");
     assert_eq!(endpoints["openapi"], "/api/v1/openapi.json");
     assert_eq!(endpoints["llms_txt"], "/api/v1/llms.txt");
+    assert_eq!(endpoints["skills_json"], "/api/v1/skills.json");
 }
 
 #[test]
@@ -116,3 +120,47 @@ fn test_discover_port_is_number() {
 
     assert!(body["port"].is_number(), "port should be a number");
 }
+
+#[test]
+fn test_discover_has_feature_flags() {
+    let client = test_client();
+    let res = client.get("/api/v1/discover").dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+
+    let features = &body["features"];
+    assert_eq!(features["version"], body["version"]);
+    assert_eq!(features["webhooks"], true);
+    assert_eq!(features["files"], true);
+    assert_eq!(features["search"], "fts5");
+    assert_eq!(features["namespaces"], false);
+    assert!(features["protected_senders"].is_array());
+}
+
+#[test]
+fn test_discover_features_reflect_sender_policy() {
+    let client = test_client_with_sender_policy(SenderPolicy {
+        protected: vec!["system".to_string()],
+        server_token: Some("srv_secret".to_string()),
+    });
+    let res = client.get("/api/v1/discover").dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["features"]["auth_mode"], "server_token");
+    assert_eq!(body["features"]["protected_senders"], serde_json::json!(["system"]));
+    assert_eq!(body["features"]["regex_search"], true);
+}
+
+#[test]
+fn test_mdns_txt_properties() {
+    let policy = SenderPolicy {
+        protected: vec![],
+        server_token: None,
+    };
+    let caps = Capabilities::detect(&policy, &RegexSearchConfig::default(), true);
+    let txt = caps.txt_properties();
+    let get = |k: &str| txt.iter().find(|(key, _)| *key == k).map(|(_, v)| v.as_str());
+    assert_eq!(get("version"), Some(env!("CARGO_PKG_VERSION")));
+    assert_eq!(get("auth"), Some("open"));
+    assert_eq!(get("namespaces"), Some("1"));
+    assert_eq!(get("search"), Some("fts5"));
+    assert!(txt.iter().all(|(k, v)| k.len() + v.len() + 1 <= 255));
+}
@@ -0,0 +1,175 @@
+use crate::common::{cleanup, temp_path};
+use local_agent_chat::db::Db;
+use local_agent_chat::email::{
+    deliver_email, parse_email, room_name_from_address, sender_from_address,
+};
+use local_agent_chat::events::{ChatEvent, EventBus};
+use local_agent_chat::senders::SenderPolicy;
+
+fn temp_db() -> (Db, String) {
+    let path = temp_path();
+    (Db::new(&path), path)
+}
+
+#[test]
+fn parse_plain_email() {
+    let raw = "From: Disk Monitor <monitor@host.lan>\r\n\
+               To: general@chat.local\r\n\
+               Subject: Disk usage\r\n\
+               \r\n\
+               /var is at 91%\r\n";
+    let email = parse_email(raw);
+    assert_eq!(email.subject, "Disk usage");
+    assert_eq!(email.body, "/var is at 91%");
+    assert!(email.attachments.is_empty());
+    assert_eq!(sender_from_address(&email.from), "Disk Monitor");
+}
+
+#[test]
+fn parse_multipart_with_attachment() {
+    let raw = "From: cron@build01\n\
+               Subject: Nightly\n\
+               Content-Type: multipart/mixed; boundary=\"XYZ\"\n\
+               \n\
+               --XYZ\n\
+               Content-Type: text/plain\n\
+               Content-Transfer-Encoding: quoted-printable\n\
+               \n\
+               Build =E2=9C=85 passed, see =\n\
+               log\n\
+               --XYZ\n\
+               Content-Type: text/plain; name=\"build.log\"\n\
+               Content-Disposition: attachment; filename=\"build.log\"\n\
+               Content-Transfer-Encoding: base64\n\
+               \n\
+               aGVsbG8gbG9n\n\
+               --XYZ--\n";
+    let email = parse_email(raw);
+    assert_eq!(email.body, "Build ✅ passed, see log");
+    assert_eq!(email.attachments.len(), 1);
+    assert_eq!(email.attachments[0].filename, "build.log");
+    assert_eq!(email.attachments[0].data, b"hello log");
+    assert_eq!(sender_from_address(&email.from), "cron");
+}
+
+#[test]
+fn recipient_mapping_checks_domain() {
+    assert_eq!(
+        room_name_from_address("general@chat.local", "chat.local"),
+        Some("general".to_string())
+    );
+    assert_eq!(room_name_from_address("general@CHAT.LOCAL", "chat.local"), Some("general".to_string()));
+    assert_eq!(room_name_from_address("general@example.com", "chat.local"), None);
+    assert_eq!(room_name_from_address("not-an-address", "chat.local"), None);
+}
+
+#[test]
+fn deliver_email_posts_message_and_files() {
+    let (db, path) = temp_db();
+    let bus = EventBus::new();
+    let raw = "From: \"Backup Job\" <backup@nas>\n\
+               Subject: Backup done\n\
+               Content-Type: multipart/mixed; boundary=b1\n\
+               \n\
+               --b1\n\
+               Content-Type: text/plain\n\
+               \n\
+               All volumes OK\n\
+               --b1\n\
+               Content-Type: application/json\n\
+               Content-Disposition: attachment; filename=report.json\n\
+               \n\
+               {\"ok\":true}\n\
+               --b1--\n";
+    let email = parse_email(raw);
+
+    let msg = {
+        let conn = db.conn();
+        deliver_email(&conn, &bus.sender, &SenderPolicy::default(), &email, "General").unwrap()
+    };
+    assert_eq!(msg.sender, "Backup Job");
+    assert_eq!(msg.content, "**Backup done**\n\nAll volumes OK");
+    assert_eq!(msg.metadata["source"], "email");
+    assert_eq!(msg.metadata["from"], "backup@nas");
+    assert_eq!(msg.metadata["attachments"].as_array().unwrap().len(), 1);
+
+    let conn = db.conn();
+    let files: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM files WHERE room_id = ?1 AND filename = 'report.json'",
+            [&msg.room_id],
+            |r| r.get(0),
+        )
+        .unwrap();
+    assert_eq!(files, 1);
+    // Recorded in the outbox like API posts, so SSE clients can resume past it
+    let recorded = local_agent_chat::outbox::room_events_after(&conn, &msg.room_id, 0, 10);
+    assert!(recorded.iter().any(|e| matches!(&e.event, ChatEvent::NewMessage(m) if m.id == msg.id)));
+    drop(conn);
+    drop(db);
+    cleanup(&path);
+}
+
+#[test]
+fn deliver_email_unknown_room_fails() {
+    let (db, path) = temp_db();
+    let bus = EventBus::new();
+    let email = parse_email("From: a@b\nSubject: hi\n\nbody\n");
+    let result = {
+        let conn = db.conn();
+        deliver_email(&conn, &bus.sender, &SenderPolicy::default(), &email, "no-such-room")
+    };
+    assert!(result.is_err());
+    drop(db);
+    cleanup(&path);
+}
+
+#[test]
+fn deliver_email_rejects_reserved_sender() {
+    let (db, path) = temp_db();
+    let bus = EventBus::new();
+    let email = parse_email("From: \"Admin\" <root@nas>\nSubject: hi\n\nbody\n");
+    let result = {
+        let conn = db.conn();
+        deliver_email(&conn, &bus.sender, &SenderPolicy::default(), &email, "General")
+    };
+    assert!(result.unwrap_err().contains("reserved"));
+
+    let conn = db.conn();
+    let count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM messages WHERE sender = 'Admin'", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(count, 0);
+    drop(conn);
+    drop(db);
+    cleanup(&path);
+}
+
+#[test]
+fn deliver_email_refuses_private_room() {
+    let (db, path) = temp_db();
+    let bus = EventBus::new();
+    {
+        let conn = db.conn();
+        conn.execute(
+            "INSERT INTO rooms (id, name, created_at, updated_at, visibility) VALUES ('priv-1', 'Ops', datetime('now'), datetime('now'), 'private')",
+            [],
+        )
+        .unwrap();
+    }
+    let email = parse_email("From: monitor@nas\nSubject: disk\n\nfull\n");
+    let result = {
+        let conn = db.conn();
+        deliver_email(&conn, &bus.sender, &SenderPolicy::default(), &email, "ops")
+    };
+    assert_eq!(result.unwrap_err(), "Room not found");
+
+    let conn = db.conn();
+    let count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM messages WHERE room_id = 'priv-1'", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(count, 0);
+    drop(conn);
+    drop(db);
+    cleanup(&path);
+}
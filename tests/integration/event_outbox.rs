@@ -0,0 +1,154 @@
+use crate::common::{create_test_room, read_sse, test_client, test_client_with_namespaces};
+use local_agent_chat::events::ChatEvent;
+use rocket::http::{ContentType, Status};
+
+// --- Transactional event outbox ---
+
+#[test]
+fn test_message_events_recorded_in_outbox() {
+    let client = test_client();
+    let rooms: Vec<serde_json::Value> = client.get("/api/v1/rooms").dispatch().into_json().unwrap();
+    let room_id = rooms[0]["id"].as_str().unwrap();
+
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender":"Bot","content":"first"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let msg: serde_json::Value = res.into_json().unwrap();
+    let msg_id = msg["id"].as_str().unwrap();
+
+    let res = client
+        .put(format!("/api/v1/rooms/{room_id}/messages/{msg_id}"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender":"Bot","content":"second"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let res = client
+        .delete(format!("/api/v1/rooms/{room_id}/messages/{msg_id}?sender=Bot"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    // One row per write, all published by the request that made them
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    let events = local_agent_chat::outbox::room_events_after(&conn, room_id, 0, 100);
+    let kinds: Vec<&str> = events
+        .iter()
+        .map(|e| match e.event {
+            ChatEvent::NewMessage(_) => "new",
+            ChatEvent::MessageEdited(_) => "edited",
+            ChatEvent::MessageDeleted { .. } => "deleted",
+            _ => "other",
+        })
+        .collect();
+    assert_eq!(kinds, ["new", "edited", "deleted"]);
+    assert!(events.windows(2).all(|w| w[0].id < w[1].id));
+    let unpublished: i64 = conn
+        .query_row("SELECT COUNT(*) FROM event_outbox WHERE published_at IS NULL", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(unpublished, 0);
+
+    // Resume cursor: only what came after a given event
+    let after_first = local_agent_chat::outbox::room_events_after(&conn, room_id, events[0].id, 100);
+    assert_eq!(after_first.len(), 2);
+}
+
+#[test]
+fn test_outbox_relay_publishes_stranded_events() {
+    let client = test_client();
+    let rooms: Vec<serde_json::Value> = client.get("/api/v1/rooms").dispatch().into_json().unwrap();
+    let room_id = rooms[0]["id"].as_str().unwrap();
+
+    // A delete that committed but whose process died before the broadcast
+    let event = ChatEvent::MessageDeleted {
+        id: "gone".to_string(),
+        room_id: room_id.to_string(),
+    };
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    conn.execute(
+        "INSERT INTO event_outbox (room_id, event, created_at) VALUES (?1, ?2, ?3)",
+        rusqlite::params![
+            room_id,
+            serde_json::to_string(&event).unwrap(),
+            (chrono::Utc::now() - chrono::Duration::minutes(1)).to_rfc3339()
+        ],
+    )
+    .unwrap();
+    let stranded_id = conn.last_insert_rowid();
+
+    let (sender, mut rx) = tokio::sync::broadcast::channel(16);
+    assert_eq!(local_agent_chat::outbox::relay(&conn, &sender, None), 1);
+    let published = rx.try_recv().unwrap();
+    assert_eq!(published.event_id, Some(stranded_id));
+    assert!(matches!(published.event, ChatEvent::MessageDeleted { ref id, .. } if id == "gone"));
+
+    // Marked published, so the next pass leaves it alone
+    assert_eq!(local_agent_chat::outbox::relay(&conn, &sender, None), 0);
+    let published_at: Option<String> = conn
+        .query_row("SELECT published_at FROM event_outbox WHERE id = ?1", [stranded_id], |r| r.get(0))
+        .unwrap();
+    assert!(published_at.is_some());
+}
+
+#[test]
+fn test_resume_replays_messages_posted_outside_the_outbox() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "outbox-resume");
+    let post = |content: &str| {
+        let res = client
+            .post(format!("/api/v1/rooms/{room_id}/messages"))
+            .header(ContentType::JSON)
+            .body(serde_json::json!({"sender": "Bot", "content": content}).to_string())
+            .dispatch();
+        assert_eq!(res.status(), Status::Ok);
+    };
+    post("seen");
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    let seen = local_agent_chat::outbox::room_events_after(&conn, &room_id, 0, 100)[0].id;
+
+    // Broadcasts are written without an outbox event
+    let res = client
+        .post("/api/v1/broadcast")
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"room_ids": [&room_id], "sender": "herald", "content": "broadcast"}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    post("after");
+
+    let mut res = client
+        .get(format!("/api/v1/rooms/{room_id}/stream?after_event={seen}&max_lifetime_secs=1"))
+        .dispatch();
+    let messages: Vec<serde_json::Value> = read_sse(&mut res, "reconnect")
+        .into_iter()
+        .filter(|(name, _)| name == "message")
+        .map(|(_, m)| m)
+        .collect();
+    let contents: Vec<&str> = messages.iter().map(|m| m["content"].as_str().unwrap()).collect();
+    assert_eq!(contents, ["broadcast", "after"]);
+    assert!(messages.windows(2).all(|w| w[0]["seq"].as_i64() < w[1]["seq"].as_i64()));
+}
+
+#[test]
+fn test_outbox_relay_tags_its_namespace() {
+    let client = test_client_with_namespaces(&["alpha"]);
+    let path = format!("{}.alpha.db", client.db_path().trim_end_matches(".db"));
+    let conn = rusqlite::Connection::open(&path).unwrap();
+    let event = ChatEvent::MessageDeleted {
+        id: "gone".to_string(),
+        room_id: "room".to_string(),
+    };
+    conn.execute(
+        "INSERT INTO event_outbox (room_id, event, created_at) VALUES ('room', ?1, ?2)",
+        rusqlite::params![
+            serde_json::to_string(&event).unwrap(),
+            (chrono::Utc::now() - chrono::Duration::minutes(1)).to_rfc3339()
+        ],
+    )
+    .unwrap();
+
+    // Streams in other namespaces drop it by this tag
+    let (sender, mut rx) = tokio::sync::broadcast::channel(16);
+    assert_eq!(local_agent_chat::outbox::relay(&conn, &sender, Some("alpha")), 1);
+    assert_eq!(rx.try_recv().unwrap().namespace.as_deref(), Some("alpha"));
+}
@@ -500,3 +500,221 @@ fn test_export_markdown_date_headers() {
     assert!(body.contains("["));
     assert!(body.contains("]"));
 }
+
+#[test]
+fn test_export_includes_reactions_edits_pins_and_files() {
+    let client = test_client();
+    let (room_id, admin_key) = create_test_room(&client, "export-full");
+
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/files"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "alice", "filename": "notes.txt", "content_type": "text/plain", "data": "aGVsbG8="}"#)
+        .dispatch();
+    let file: serde_json::Value = res.into_json().unwrap();
+    let file_id = file["id"].as_str().unwrap();
+
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"sender": "alice", "content": "first draft", "metadata": {"attachments": [file_id]}}).to_string())
+        .dispatch();
+    let parent: serde_json::Value = res.into_json().unwrap();
+    let parent_id = parent["id"].as_str().unwrap();
+    client
+        .put(format!("/api/v1/rooms/{room_id}/messages/{parent_id}"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "alice", "content": "final version"}"#)
+        .dispatch();
+    client
+        .post(format!("/api/v1/rooms/{room_id}/messages/{parent_id}/pin"))
+        .header(rocket::http::Header::new("Authorization", format!("Bearer {admin_key}")))
+        .dispatch();
+    client
+        .post(format!("/api/v1/rooms/{room_id}/messages/{parent_id}/reactions"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "bob", "emoji": "👍"}"#)
+        .dispatch();
+    client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"sender": "bob", "content": "agreed", "reply_to": parent_id}).to_string())
+        .dispatch();
+
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/export?include_metadata=true&include_files=true"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let export: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(export["export_version"], 3);
+    let msgs = export["messages"].as_array().unwrap();
+    assert_eq!(msgs[0]["id"], parent_id);
+    assert_eq!(msgs[0]["pinned_by"], "admin");
+    assert_eq!(msgs[0]["reactions"][0]["emoji"], "👍");
+    assert_eq!(msgs[0]["reactions"][0]["sender"], "bob");
+    assert_eq!(msgs[0]["edits"][0]["previous_content"], "first draft");
+    assert!(msgs[1].get("reactions").is_none());
+    assert_eq!(export["files"][0]["id"], file_id);
+    assert_eq!(export["files"][0]["data"], "aGVsbG8=");
+
+    // Without include_files the manifest has no contents
+    let export_no_data: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/export"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(export_no_data["files"][0]["filename"], "notes.txt");
+    assert!(export_no_data["files"][0].get("data").is_none());
+}
+
+#[test]
+fn test_import_round_trip() {
+    let client = test_client();
+    let (room_id, admin_key) = create_test_room(&client, "round-trip");
+
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/files"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "alice", "filename": "notes.txt", "content_type": "text/plain", "data": "aGVsbG8="}"#)
+        .dispatch();
+    let file: serde_json::Value = res.into_json().unwrap();
+    let file_id = file["id"].as_str().unwrap();
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"sender": "alice", "content": "v1", "metadata": {"attachments": [file_id]}}).to_string())
+        .dispatch();
+    let parent: serde_json::Value = res.into_json().unwrap();
+    let parent_id = parent["id"].as_str().unwrap();
+    client
+        .put(format!("/api/v1/rooms/{room_id}/messages/{parent_id}"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "alice", "content": "v2"}"#)
+        .dispatch();
+    client
+        .post(format!("/api/v1/rooms/{room_id}/messages/{parent_id}/pin"))
+        .header(rocket::http::Header::new("Authorization", format!("Bearer {admin_key}")))
+        .dispatch();
+    client
+        .post(format!("/api/v1/rooms/{room_id}/messages/{parent_id}/reactions"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "bob", "emoji": "🎉"}"#)
+        .dispatch();
+    client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"sender": "bob", "content": "reply", "reply_to": parent_id}).to_string())
+        .dispatch();
+
+    let export = client
+        .get(format!("/api/v1/rooms/{room_id}/export?include_metadata=true&include_files=true"))
+        .dispatch()
+        .into_string()
+        .unwrap();
+
+    // The source room's name is taken on this instance
+    let res = client
+        .post("/api/v1/rooms/import")
+        .header(ContentType::JSON)
+        .body(export.clone())
+        .dispatch();
+    assert_eq!(res.status(), Status::Conflict);
+
+    let res = client
+        .post("/api/v1/rooms/import?name=round-trip-copy")
+        .header(ContentType::JSON)
+        .body(export)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let summary: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(summary["name"], "round-trip-copy");
+    assert_eq!(summary["messages"], 2);
+    assert_eq!(summary["reactions"], 1);
+    assert_eq!(summary["edits"], 1);
+    assert_eq!(summary["pins"], 1);
+    assert_eq!(summary["files"], 1);
+    assert_eq!(summary["files_skipped"], 0);
+    assert!(summary["admin_key"].as_str().is_some());
+    let copy_id = summary["room_id"].as_str().unwrap();
+
+    let copy: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{copy_id}/export?include_metadata=true&include_files=true"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let msgs = copy["messages"].as_array().unwrap();
+    assert_eq!(msgs.len(), 2);
+    // Ids already used on this instance are replaced, and references follow them
+    let new_parent = msgs[0]["id"].as_str().unwrap();
+    assert_ne!(new_parent, parent_id);
+    assert_eq!(msgs[1]["reply_to"], new_parent);
+    assert_eq!(msgs[0]["content"], "v2");
+    assert!(msgs[0]["edited_at"].is_string());
+    assert_eq!(msgs[0]["edits"][0]["previous_content"], "v1");
+    assert_eq!(msgs[0]["reactions"][0]["emoji"], "🎉");
+    assert!(msgs[0]["pinned_at"].is_string());
+    let new_file = copy["files"][0]["id"].as_str().unwrap();
+    assert_ne!(new_file, file_id);
+    assert_eq!(msgs[0]["metadata"]["attachments"][0], new_file);
+    assert_eq!(copy["files"][0]["data"], "aGVsbG8=");
+
+    let res = client.get(format!("/api/v1/files/{new_file}")).dispatch();
+    assert_eq!(res.into_bytes().unwrap(), b"hello");
+}
+
+#[test]
+fn test_import_keeps_private_rooms_private() {
+    use rocket::http::Header;
+    let client = test_client();
+    let res = client
+        .post("/api/v1/rooms")
+        .header(ContentType::JSON)
+        .body(r#"{"name": "vault", "created_by": "lead", "visibility": "private"}"#)
+        .dispatch();
+    let room: serde_json::Value = res.into_json().unwrap();
+    let room_id = room["id"].as_str().unwrap();
+    let admin_key = room["admin_key"].as_str().unwrap();
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/members"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .body(r#"{"sender": "agent-a", "added_by": "lead"}"#)
+        .dispatch();
+    let member: serde_json::Value = res.into_json().unwrap();
+    let old_token = member["token"].as_str().unwrap().to_string();
+
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/export"))
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let export = res.into_string().unwrap();
+    let parsed: serde_json::Value = serde_json::from_str(&export).unwrap();
+    assert_eq!(parsed["room_visibility"], "private");
+    assert_eq!(parsed["members"][0]["sender"], "agent-a");
+    assert!(!export.contains(&old_token));
+
+    let res = client
+        .post("/api/v1/rooms/import?name=vault-copy")
+        .header(ContentType::JSON)
+        .body(export)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let summary: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(summary["visibility"], "private");
+    assert_eq!(summary["members"], 1);
+    let copy_id = summary["room_id"].as_str().unwrap();
+    let copy_key = summary["admin_key"].as_str().unwrap();
+
+    // Outsiders are kept out of the copy; the member gets a fresh token
+    let res = client.get(format!("/api/v1/rooms/{copy_id}/messages")).dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+    let res = client
+        .get(format!("/api/v1/rooms/{copy_id}/members"))
+        .header(Header::new("Authorization", format!("Bearer {copy_key}")))
+        .dispatch();
+    let members: Vec<serde_json::Value> = res.into_json().unwrap();
+    assert_eq!(members.len(), 1);
+    assert_eq!(members[0]["added_by"], "lead");
+    assert_ne!(members[0]["token"], old_token.as_str());
+}
@@ -0,0 +1,94 @@
+use rocket::http::{ContentType, Header, Status};
+use crate::common::{create_test_room, test_client, upload_file};
+
+// --- File expiry ---
+
+#[test]
+fn test_upload_expires_in_sets_expires_at() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "expiry-upload");
+
+    let (status, file) = upload_file(&client, &room_id, serde_json::json!({"expires_in": 600}));
+    assert_eq!(status, Status::Ok);
+    let expires = chrono::DateTime::parse_from_rfc3339(file["expires_at"].as_str().unwrap()).unwrap();
+    let delta = expires.signed_duration_since(chrono::Utc::now()).num_seconds();
+    assert!((590..=600).contains(&delta), "expires_at should be ~10 minutes out, got {delta}s");
+
+    let info: serde_json::Value = client
+        .get(format!("/api/v1/files/{}/info", file["id"].as_str().unwrap()))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(info["expires_at"], file["expires_at"]);
+
+    // Without expires_in or a room TTL, files live forever
+    let file = upload_file(&client, &room_id, serde_json::json!({})).1;
+    assert!(file.get("expires_at").is_none());
+
+    let (status, _) = upload_file(&client, &room_id, serde_json::json!({"expires_in": 5}));
+    assert_eq!(status, Status::BadRequest);
+}
+
+#[test]
+fn test_room_default_file_ttl() {
+    let client = test_client();
+    let (room_id, key) = create_test_room(&client, "expiry-room-ttl");
+    let res = client
+        .put(format!("/api/v1/rooms/{room_id}"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {key}")))
+        .body(r#"{"file_ttl_secs": 3600}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let room: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(room["file_ttl_secs"], 3600);
+
+    let file = upload_file(&client, &room_id, serde_json::json!({})).1;
+    assert!(file["expires_at"].is_string(), "room TTL should apply");
+    // An explicit expires_in wins
+    let short = upload_file(&client, &room_id, serde_json::json!({"expires_in": 60})).1;
+    assert!(short["expires_at"].as_str().unwrap() < file["expires_at"].as_str().unwrap());
+
+    let res = client
+        .put(format!("/api/v1/rooms/{room_id}"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {key}")))
+        .body(r#"{"file_ttl_secs": 1}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+}
+
+#[test]
+fn test_retention_removes_expired_files() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "expiry-sweep");
+    let doomed = upload_file(&client, &room_id, serde_json::json!({"expires_in": 60})).1;
+    let kept = upload_file(&client, &room_id, serde_json::json!({})).1;
+    let doomed_id = doomed["id"].as_str().unwrap();
+
+    // Backdate the expiry instead of waiting a minute
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    conn.execute(
+        "UPDATE files SET expires_at = '2000-01-01T00:00:00+00:00' WHERE id = ?1",
+        [doomed_id],
+    )
+    .unwrap();
+
+    // Already hidden before the sweep runs
+    let res = client.get(format!("/api/v1/files/{doomed_id}")).dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+
+    let result: serde_json::Value = client.post("/api/v1/admin/retention/run").dispatch().into_json().unwrap();
+    assert_eq!(result["files_expired"], 1);
+
+    let files: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/rooms/{room_id}/files"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0]["id"], kept["id"]);
+    // Identical bytes uploaded without expiry are still downloadable
+    let res = client.get(format!("/api/v1/files/{}", kept["id"].as_str().unwrap())).dispatch();
+    assert_eq!(res.status(), Status::Ok);
+}
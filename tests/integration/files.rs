@@ -327,3 +327,62 @@ fn test_download_nonexistent_file() {
     let res = client.get("/api/v1/files/nonexistent-file-id").dispatch();
     assert_eq!(res.status(), Status::NotFound);
 }
+
+#[test]
+fn test_identical_uploads_share_storage() {
+    use base64::Engine;
+    let client = test_client();
+    let room_a = get_general_room_id(&client);
+    let room_b: serde_json::Value = client
+        .post("/api/v1/rooms")
+        .header(ContentType::JSON)
+        .body(r#"{"name": "dedup-room"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let room_b = room_b["id"].as_str().unwrap().to_string();
+    let data = base64::engine::general_purpose::STANDARD.encode(b"hello world");
+
+    let upload = |room: &str, name: &str| -> serde_json::Value {
+        let res = client
+            .post(format!("/api/v1/rooms/{room}/files"))
+            .header(ContentType::JSON)
+            .body(serde_json::json!({"sender": "logger", "filename": name, "content_type": "text/plain", "data": data}).to_string())
+            .dispatch();
+        assert_eq!(res.status(), Status::Ok);
+        res.into_json().unwrap()
+    };
+    let first = upload(&room_a, "a.log");
+    let second = upload(&room_b, "b.log");
+    let expected = "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9";
+    assert_eq!(first["sha256"], expected);
+    assert_eq!(second["sha256"], expected);
+    assert_ne!(first["id"], second["id"]);
+
+    // Separate metadata rows, one stored copy
+    let info: serde_json::Value = client
+        .get(format!("/api/v1/files/{}/info", second["id"].as_str().unwrap()))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(info["filename"], "b.log");
+    assert_eq!(info["room_id"], room_b.as_str());
+    assert_eq!(info["sha256"], expected);
+    let stats: serde_json::Value = client.get("/api/v1/stats").dispatch().into_json().unwrap();
+    assert_eq!(stats["files"]["total_bytes"], 22);
+    assert_eq!(stats["files"]["stored_bytes"], 11);
+
+    // Deleting one copy leaves the other intact; deleting the last frees the blob
+    let res = client
+        .delete(format!("/api/v1/rooms/{room_a}/files/{}?sender=logger", first["id"].as_str().unwrap()))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let res = client.get(format!("/api/v1/files/{}", second["id"].as_str().unwrap())).dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    assert_eq!(res.into_bytes().unwrap(), b"hello world");
+    client
+        .delete(format!("/api/v1/rooms/{room_b}/files/{}?sender=logger", second["id"].as_str().unwrap()))
+        .dispatch();
+    let stats: serde_json::Value = client.get("/api/v1/stats").dispatch().into_json().unwrap();
+    assert_eq!(stats["files"]["stored_bytes"], 0);
+}
@@ -0,0 +1,112 @@
+use crate::common::{create_test_room, post_message, read_sse, test_client, test_client_with_namespaces};
+use rocket::http::{ContentType, Status};
+use serde_json::json;
+
+#[test]
+fn test_firehose_streams_across_rooms_with_filters() {
+    let client = test_client();
+    let (first, _) = create_test_room(&client, "firehose-a");
+    let (second, _) = create_test_room(&client, "firehose-b");
+    let (other, _) = create_test_room(&client, "firehose-c");
+
+    let mut all = client.get("/api/v1/stream?max_lifetime_secs=1").dispatch();
+    assert_eq!(all.status(), Status::Ok);
+    let mut some = client
+        .get(format!("/api/v1/stream?room_id={first},{second}&sender_type=agent&events=message&max_lifetime_secs=1"))
+        .dispatch();
+    assert_eq!(some.status(), Status::Ok);
+
+    post_message(&client, &first, json!({"sender": "bot-a", "sender_type": "agent", "content": "from a"}));
+    post_message(&client, &second, json!({"sender": "bot-b", "sender_type": "agent", "content": "from b"}));
+    post_message(&client, &second, json!({"sender": "alice", "sender_type": "human", "content": "human in b"}));
+    post_message(&client, &other, json!({"sender": "bot-c", "sender_type": "agent", "content": "from c"}));
+
+    let contents = |frames: Vec<(String, serde_json::Value)>| -> Vec<String> {
+        frames
+            .into_iter()
+            .filter(|(name, _)| name == "message")
+            .map(|(_, m)| m["content"].as_str().unwrap().to_string())
+            .collect()
+    };
+    assert_eq!(
+        contents(read_sse(&mut all, "reconnect")),
+        vec!["from a", "from b", "human in b", "from c"]
+    );
+    assert_eq!(contents(read_sse(&mut some, "reconnect")), vec!["from a", "from b"]);
+}
+
+#[test]
+fn test_firehose_validates_filters() {
+    let client = test_client();
+    for query in ["events=explosion", "heartbeat_secs=0", "max_lifetime_secs=0"] {
+        let res = client.get(format!("/api/v1/stream?{query}")).dispatch();
+        assert_eq!(res.status(), Status::BadRequest, "{query}");
+    }
+}
+
+fn message_contents(frames: Vec<(String, serde_json::Value)>) -> Vec<String> {
+    frames
+        .into_iter()
+        .filter(|(name, _)| name == "message")
+        .map(|(_, m)| m["content"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn test_firehose_stays_in_its_namespace() {
+    let client = test_client_with_namespaces(&["alpha"]);
+    let (home, _) = create_test_room(&client, "firehose-home");
+    let res = client
+        .post("/ns/alpha/api/v1/rooms")
+        .header(ContentType::JSON)
+        .body(r#"{"name": "firehose-away", "created_by": "tester"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let away = res.into_json::<serde_json::Value>().unwrap()["id"].as_str().unwrap().to_string();
+
+    let mut main = client.get("/api/v1/stream?max_lifetime_secs=1").dispatch();
+    assert_eq!(main.status(), Status::Ok);
+    let mut alpha = client.get("/ns/alpha/api/v1/stream?max_lifetime_secs=1").dispatch();
+    assert_eq!(alpha.status(), Status::Ok);
+
+    post_message(&client, &home, json!({"sender": "bot-a", "sender_type": "agent", "content": "main message"}));
+    let res = client
+        .post(format!("/ns/alpha/api/v1/rooms/{away}/messages"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "bot-b", "content": "alpha message"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    assert_eq!(message_contents(read_sse(&mut main, "reconnect")), vec!["main message"]);
+    assert_eq!(message_contents(read_sse(&mut alpha, "reconnect")), vec!["alpha message"]);
+}
+
+#[test]
+fn test_firehose_defaults_to_sender_subscriptions() {
+    let client = test_client();
+    let (followed, _) = create_test_room(&client, "firehose-followed");
+    let (ignored, _) = create_test_room(&client, "firehose-ignored");
+    let res = client
+        .put("/api/v1/subscriptions/rooms")
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"sender": "watcher", "room_ids": [&followed]}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    let mut subscribed = client.get("/api/v1/stream?sender=watcher&max_lifetime_secs=1").dispatch();
+    assert_eq!(subscribed.status(), Status::Ok);
+    let mut everything = client.get("/api/v1/stream?sender=watcher&all=true&max_lifetime_secs=1").dispatch();
+    assert_eq!(everything.status(), Status::Ok);
+    // An explicit room list wins over the subscriptions
+    let mut listed = client
+        .get(format!("/api/v1/stream?sender=watcher&room_id={ignored}&max_lifetime_secs=1"))
+        .dispatch();
+    assert_eq!(listed.status(), Status::Ok);
+
+    post_message(&client, &followed, json!({"sender": "bot-a", "sender_type": "agent", "content": "followed"}));
+    post_message(&client, &ignored, json!({"sender": "bot-b", "sender_type": "agent", "content": "ignored"}));
+
+    assert_eq!(message_contents(read_sse(&mut subscribed, "reconnect")), vec!["followed"]);
+    assert_eq!(message_contents(read_sse(&mut everything, "reconnect")), vec!["followed", "ignored"]);
+    assert_eq!(message_contents(read_sse(&mut listed, "reconnect")), vec!["ignored"]);
+}
@@ -0,0 +1,132 @@
+use crate::common::{create_test_room, post_message, test_client, TestClient};
+use rocket::http::{ContentType, Header, Status};
+use serde_json::json;
+
+fn flag(client: &TestClient, room_id: &str, msg_id: &str, reporter: &str, reason: &str) -> (Status, serde_json::Value) {
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages/{msg_id}/flags"))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"reporter": reporter, "reason": reason}).to_string())
+        .dispatch();
+    let status = res.status();
+    (status, res.into_json().unwrap_or_default())
+}
+
+fn queue(client: &TestClient, room_id: &str, admin_key: &str, query: &str) -> serde_json::Value {
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/flags{query}"))
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    res.into_json().unwrap()
+}
+
+fn resolve(client: &TestClient, room_id: &str, admin_key: &str, flag_id: &str, action: &str) -> (Status, serde_json::Value) {
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/flags/{flag_id}/resolve"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .body(serde_json::json!({"action": action, "moderator": "mod"}).to_string())
+        .dispatch();
+    let status = res.status();
+    (status, res.into_json().unwrap_or_default())
+}
+
+#[test]
+fn test_flag_message_and_queue() {
+    let client = test_client();
+    let (room_id, admin_key) = create_test_room(&client, "flags-queue");
+    let msg_id = post_message(&client, &room_id, json!({"sender": "bot", "content": "something off"}))["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let (status, body) = flag(&client, &room_id, &msg_id, "nate", "Hallucinated figures");
+    assert_eq!(status, Status::Ok);
+    assert_eq!(body["status"], "open");
+    assert_eq!(body["message_sender"], "bot");
+    assert_eq!(body["message_content"], "something off");
+
+    // One open flag per reporter per message
+    assert_eq!(flag(&client, &room_id, &msg_id, "nate", "again").0, Status::Conflict);
+    assert_eq!(flag(&client, &room_id, &msg_id, "ann", "Agreed").0, Status::Ok);
+
+    let body = queue(&client, &room_id, &admin_key, "");
+    assert_eq!(body["count"], 2);
+    assert_eq!(body["flags"][0]["reporter"], "nate");
+}
+
+#[test]
+fn test_flag_validation() {
+    let client = test_client();
+    let (room_id, admin_key) = create_test_room(&client, "flags-validation");
+    let msg_id =
+        post_message(&client, &room_id, json!({"sender": "bot", "content": "hi"}))["id"].as_str().unwrap().to_string();
+    assert_eq!(flag(&client, &room_id, &msg_id, "nate", " ").0, Status::BadRequest);
+    assert_eq!(flag(&client, &room_id, &msg_id, "", "spam").0, Status::BadRequest);
+    assert_eq!(flag(&client, &room_id, "missing", "nate", "spam").0, Status::NotFound);
+
+    let res = client.get(format!("/api/v1/rooms/{room_id}/flags")).dispatch();
+    assert!(res.status() == Status::Unauthorized || res.status() == Status::NotFound);
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/flags"))
+        .header(Header::new("Authorization", "Bearer wrong"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/flags?status=bogus"))
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+}
+
+#[test]
+fn test_dismiss_flag_closes_all_open_flags_on_message() {
+    let client = test_client();
+    let (room_id, admin_key) = create_test_room(&client, "flags-dismiss");
+    let msg_id = post_message(&client, &room_id, json!({"sender": "bot", "content": "fine actually"}))["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let (_, first) = flag(&client, &room_id, &msg_id, "nate", "looks wrong");
+    flag(&client, &room_id, &msg_id, "ann", "same");
+
+    let (status, body) = resolve(&client, &room_id, &admin_key, first["id"].as_str().unwrap(), "dismiss");
+    assert_eq!(status, Status::Ok);
+    let resolved = body.as_array().unwrap();
+    assert_eq!(resolved.len(), 2);
+    assert!(resolved.iter().all(|f| f["status"] == "dismissed" && f["resolved_by"] == "mod"));
+
+    assert_eq!(queue(&client, &room_id, &admin_key, "")["count"], 0);
+    assert_eq!(queue(&client, &room_id, &admin_key, "?status=dismissed")["count"], 2);
+    assert_eq!(resolve(&client, &room_id, &admin_key, first["id"].as_str().unwrap(), "delete").0, Status::Conflict);
+
+    // Message is untouched
+    let res = client.get(format!("/api/v1/rooms/{room_id}/messages")).dispatch();
+    let messages: serde_json::Value = res.into_json().unwrap();
+    assert!(messages.as_array().unwrap().iter().any(|m| m["id"] == msg_id.as_str()));
+}
+
+#[test]
+fn test_delete_flagged_message() {
+    let client = test_client();
+    let (room_id, admin_key) = create_test_room(&client, "flags-delete");
+    let msg_id = post_message(&client, &room_id, json!({"sender": "bot", "content": "leaked secret"}))["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let (_, f) = flag(&client, &room_id, &msg_id, "nate", "contains a token");
+
+    assert_eq!(resolve(&client, &room_id, &admin_key, f["id"].as_str().unwrap(), "ban").0, Status::BadRequest);
+    let (status, body) = resolve(&client, &room_id, &admin_key, f["id"].as_str().unwrap(), "delete");
+    assert_eq!(status, Status::Ok);
+    assert_eq!(body[0]["status"], "deleted");
+
+    let res = client.get(format!("/api/v1/rooms/{room_id}/messages")).dispatch();
+    let messages: serde_json::Value = res.into_json().unwrap();
+    assert!(!messages.as_array().unwrap().iter().any(|m| m["id"] == msg_id.as_str()));
+
+    // The queue keeps the flagged content for the record
+    let body = queue(&client, &room_id, &admin_key, "?status=all");
+    assert_eq!(body["flags"][0]["message_content"], "leaked secret");
+}
@@ -0,0 +1,74 @@
+use rocket::http::Status;
+use crate::common::{create_test_room, test_client};
+
+// --- Activity heatmap ---
+
+fn insert_at(client: &crate::common::TestClient, room_id: &str, created_at: &str, sender_type: &str) {
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    conn.execute(
+        "INSERT INTO messages (id, room_id, sender, content, metadata, created_at, sender_type, seq)
+         VALUES (?1, ?2, 'someone', 'hi', '{}', ?3, ?4, (SELECT COALESCE(MAX(seq), 0) + 1 FROM messages))",
+        rusqlite::params![uuid::Uuid::new_v4().to_string(), room_id, created_at, sender_type],
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_heatmap_buckets_by_weekday_and_hour() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "heatmap");
+
+    // Most recent Wednesday at 09:xx UTC, inside the window
+    let now = chrono::Utc::now();
+    let mut wednesday = now.date_naive() - chrono::Duration::days(1);
+    while chrono::Datelike::weekday(&wednesday) != chrono::Weekday::Wed {
+        wednesday -= chrono::Duration::days(1);
+    }
+    let at = |h: u32, m: u32| format!("{}T{h:02}:{m:02}:00+00:00", wednesday.format("%Y-%m-%d"));
+    insert_at(&client, &room_id, &at(9, 5), "agent");
+    insert_at(&client, &room_id, &at(9, 40), "human");
+    insert_at(&client, &room_id, &at(23, 30), "agent");
+    // Outside the 30-day window
+    insert_at(&client, &room_id, "2020-01-01T09:00:00+00:00", "agent");
+
+    let res = client.get(format!("/api/v1/rooms/{room_id}/activity/heatmap")).dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let map: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(map["days"], 30);
+    assert_eq!(map["total"], 3);
+    assert_eq!(map["day_labels"][2], "Wed");
+    assert_eq!(map["counts"][2][9], 2);
+    assert_eq!(map["agents"][2][9], 1);
+    assert_eq!(map["humans"][2][9], 1);
+    assert_eq!(map["counts"][2][23], 1);
+    assert_eq!(map["peak"], serde_json::json!({"day": "Wed", "hour": 9, "count": 2}));
+
+    // An hour east of UTC pushes 23:30 Wednesday into Thursday 00:30
+    let map: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/activity/heatmap?tz_offset=60"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(map["counts"][3][0], 1);
+    assert_eq!(map["counts"][2][10], 2);
+}
+
+#[test]
+fn test_heatmap_validation() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "heatmap-validation");
+    let res = client.get(format!("/api/v1/rooms/{room_id}/activity/heatmap?days=0")).dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+    let res = client.get(format!("/api/v1/rooms/{room_id}/activity/heatmap?tz_offset=5000")).dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+    let res = client.get("/api/v1/rooms/nope/activity/heatmap").dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+
+    let map: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/activity/heatmap"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(map["total"], 0);
+    assert!(map.get("peak").is_none());
+}
@@ -0,0 +1,238 @@
+use crate::common::{admin_client, test_client, TestClient};
+use rocket::http::{ContentType, Header, Status};
+use serde_json::json;
+use std::io::Write;
+
+fn build_zip(entries: &[(&str, Vec<u8>)]) -> Vec<u8> {
+    let mut w = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    for (name, data) in entries {
+        w.start_file(*name, zip::write::SimpleFileOptions::default()).unwrap();
+        w.write_all(data).unwrap();
+    }
+    w.finish().unwrap().into_inner()
+}
+
+fn slack_export() -> Vec<u8> {
+    let users = json!([
+        {"id": "U1", "name": "alice", "real_name": "Alice Example", "profile": {"display_name": "Alice", "title": "SRE"}},
+        {"id": "U2", "name": "deploybot", "is_bot": true, "profile": {"real_name": "Deploy Bot"}}
+    ]);
+    let channels = json!([
+        {"id": "C1", "name": "eng", "created": 1700000000, "purpose": {"value": "Engineering"}}
+    ]);
+    let day = json!([
+        {"type": "message", "subtype": "channel_join", "user": "U1", "text": "<@U1> has joined the channel", "ts": "1700000000.000050"},
+        {"type": "message", "user": "U1", "text": "deploy ready <@U2>? see <https://ci.example|the run> &amp; <!here>", "ts": "1700000000.000100"},
+        {"type": "message", "user": "U2", "text": "on it", "ts": "1700000060.000200", "thread_ts": "1700000000.000100"},
+        {"type": "message", "user": "U1", "text": "", "ts": "1700000120.000300", "edited": {"ts": "1700000130.000000"},
+         "files": [
+            {"id": "F1", "name": "notes.txt", "mimetype": "text/plain", "url_private": "https://files.slack.com/F1/notes.txt"},
+            {"id": "F2", "name": "big.bin", "url_private": "https://files.slack.com/F2/big.bin"}
+         ]}
+    ]);
+    build_zip(&[
+        ("users.json", users.to_string().into_bytes()),
+        ("channels.json", channels.to_string().into_bytes()),
+        ("eng/2023-11-14.json", day.to_string().into_bytes()),
+        ("__uploads/F1/notes.txt", b"meeting notes".to_vec()),
+    ])
+}
+
+fn start(client: &TestClient, format: &str, body: Vec<u8>) -> serde_json::Value {
+    let res = client
+        .post(format!("/api/v1/admin/import?format={format}"))
+        .header(ContentType::new("application", "zip"))
+        .header(Header::new("X-Server-Token", "srv_secret"))
+        .body(body)
+        .dispatch();
+    assert_eq!(res.status(), Status::Accepted);
+    res.into_json().unwrap()
+}
+
+fn wait_for(client: &TestClient, job_id: &str) -> serde_json::Value {
+    for _ in 0..200 {
+        let job: serde_json::Value = client
+            .get(format!("/api/v1/admin/import/{job_id}"))
+            .header(Header::new("X-Server-Token", "srv_secret"))
+            .dispatch()
+            .into_json()
+            .unwrap();
+        if job["status"] == "completed" || job["status"] == "failed" {
+            return job;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(25));
+    }
+    panic!("import {job_id} did not finish");
+}
+
+#[test]
+fn test_slack_import() {
+    let client = admin_client();
+    let job = start(&client, "slack", slack_export());
+    assert_eq!(job["format"], "slack");
+    let job = wait_for(&client, job["id"].as_str().unwrap());
+    assert_eq!(job["status"], "completed", "{job}");
+    assert_eq!(job["rooms_total"], 1);
+    assert_eq!(job["rooms_done"], 1);
+    assert_eq!(job["messages_imported"], 3);
+    assert_eq!(job["files_imported"], 1);
+    assert_eq!(job["attachments_missing"], 1);
+    assert_eq!(job["profiles_imported"], 2);
+    let room = &job["rooms"][0];
+    assert_eq!(room["source"], "eng");
+    assert_eq!(room["name"], "eng");
+    assert!(room["admin_key"].as_str().unwrap().starts_with("chat_"));
+    let room_id = room["room_id"].as_str().unwrap();
+
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    let (description, created_at): (String, String) = conn
+        .query_row("SELECT description, created_at FROM rooms WHERE id = ?1", [room_id], |r| Ok((r.get(0)?, r.get(1)?)))
+        .unwrap();
+    assert_eq!(description, "Engineering");
+    assert!(created_at.starts_with("2023-11-14T22:13:20"));
+
+    let messages: Vec<(String, String, String, Option<String>, Option<String>, String)> = conn
+        .prepare("SELECT id, sender, content, reply_to, edited_at, metadata FROM messages WHERE room_id = ?1 ORDER BY seq")
+        .unwrap()
+        .query_map([room_id], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?, r.get(5)?)))
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(messages.len(), 3);
+    assert_eq!(messages[0].1, "alice");
+    assert_eq!(messages[0].2, "deploy ready @deploybot? see the run (https://ci.example) & @here");
+    assert_eq!(messages[1].1, "deploybot");
+    assert_eq!(messages[1].3.as_deref(), Some(messages[0].0.as_str()));
+    assert_eq!(messages[2].2, "notes.txt, big.bin");
+    assert!(messages[2].4.is_some());
+    let metadata: serde_json::Value = serde_json::from_str(&messages[2].5).unwrap();
+    assert_eq!(metadata["source"], "slack");
+    assert_eq!(metadata["source_id"], "1700000120.000300");
+    assert_eq!(metadata["attachments"].as_array().unwrap().len(), 1);
+    assert_eq!(metadata["missing_attachments"][0]["url"], "https://files.slack.com/F2/big.bin");
+
+    let file_id = metadata["attachments"][0].as_str().unwrap();
+    let res = client.get(format!("/api/v1/files/{file_id}")).dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    assert_eq!(res.into_bytes().unwrap(), b"meeting notes");
+
+    let sender_type: String = conn
+        .query_row("SELECT sender_type FROM profiles WHERE sender = 'deploybot'", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(sender_type, "agent");
+
+    // Imported history is searchable
+    let body: serde_json::Value = client.get("/api/v1/search?q=ready").dispatch().into_json().unwrap();
+    assert_eq!(body["count"].as_u64().unwrap(), 1);
+}
+
+#[test]
+fn test_import_name_clash_gets_suffix() {
+    let client = admin_client();
+    let first = start(&client, "slack", slack_export());
+    wait_for(&client, first["id"].as_str().unwrap());
+    let second = start(&client, "slack", slack_export());
+    let job = wait_for(&client, second["id"].as_str().unwrap());
+    assert_eq!(job["rooms"][0]["name"], "eng-2");
+    // Profiles that already exist are left alone
+    assert_eq!(job["profiles_imported"], 0);
+
+    let res = client
+        .get("/api/v1/admin/import")
+        .header(Header::new("X-Server-Token", "srv_secret"))
+        .dispatch();
+    let jobs: Vec<serde_json::Value> = res.into_json().unwrap();
+    assert_eq!(jobs.len(), 2);
+}
+
+#[test]
+fn test_discord_import() {
+    let client = admin_client();
+    let export = json!({
+        "guild": {"id": "1", "name": "Old Server"},
+        "channel": {"id": "10", "type": "GuildTextChat", "name": "general-chat", "topic": "Chit chat"},
+        "messages": [
+            {"id": "100", "type": "Default", "timestamp": "2023-05-01T10:00:00+00:00", "content": "hi <@2>",
+             "author": {"id": "1", "name": "bob", "nickname": "Bobby", "isBot": false},
+             "mentions": [{"id": "2", "name": "helper"}],
+             "attachments": [{"id": "a1", "url": "general-chat_Files/pic.png", "fileName": "pic.png", "fileSizeBytes": 8}]},
+            {"id": "101", "type": "GuildMemberJoin", "timestamp": "2023-05-01T10:00:30+00:00", "content": "",
+             "author": {"id": "3", "name": "newbie", "isBot": false}},
+            {"id": "102", "type": "Reply", "timestamp": "2023-05-01T10:01:00+00:00", "timestampEdited": "2023-05-01T10:02:00+00:00",
+             "content": "hello!", "author": {"id": "2", "name": "helper", "isBot": true},
+             "reference": {"messageId": "100", "channelId": "10"}}
+        ]
+    });
+    let png = b"\x89PNG\r\n\x1a\n".to_vec();
+    let body = build_zip(&[
+        ("Old Server - general-chat [10].json", export.to_string().into_bytes()),
+        ("general-chat_Files/pic.png", png),
+    ]);
+    let job = start(&client, "discord", body);
+    let job = wait_for(&client, job["id"].as_str().unwrap());
+    assert_eq!(job["status"], "completed", "{job}");
+    assert_eq!(job["messages_imported"], 2);
+    assert_eq!(job["files_imported"], 1);
+    assert_eq!(job["profiles_imported"], 2);
+    let room_id = job["rooms"][0]["room_id"].as_str().unwrap();
+
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    let rows: Vec<(String, String, Option<String>, String)> = conn
+        .prepare("SELECT id, content, reply_to, sender_type FROM messages WHERE room_id = ?1 ORDER BY seq")
+        .unwrap()
+        .query_map([room_id], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)))
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(rows[0].1, "hi @helper");
+    assert_eq!(rows[1].2.as_deref(), Some(rows[0].0.as_str()));
+    assert_eq!(rows[1].3, "agent");
+    let content_type: String = conn
+        .query_row("SELECT content_type FROM files WHERE room_id = ?1", [room_id], |r| r.get(0))
+        .unwrap();
+    assert_eq!(content_type, "image/png");
+    let display_name: String = conn
+        .query_row("SELECT display_name FROM profiles WHERE sender = 'bob'", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(display_name, "Bobby");
+}
+
+#[test]
+fn test_import_validation() {
+    let client = admin_client();
+    let res = client
+        .post("/api/v1/admin/import?format=teams")
+        .header(Header::new("X-Server-Token", "srv_secret"))
+        .body(slack_export())
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+
+    let res = client
+        .post("/api/v1/admin/import?format=slack")
+        .header(Header::new("X-Server-Token", "srv_secret"))
+        .body("not a zip")
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+
+    let res = client
+        .post("/api/v1/admin/import?format=slack")
+        .header(Header::new("X-Server-Token", "srv_secret"))
+        .body(build_zip(&[("readme.txt", b"hello".to_vec())]))
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+
+    let res = client
+        .get("/api/v1/admin/import/nope")
+        .header(Header::new("X-Server-Token", "srv_secret"))
+        .dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+}
+
+#[test]
+fn test_import_requires_server_token() {
+    let client = test_client();
+    let res = client.post("/api/v1/admin/import?format=slack").body(slack_export()).dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+    let res = client.get("/api/v1/admin/import").dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+}
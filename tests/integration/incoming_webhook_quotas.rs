@@ -0,0 +1,130 @@
+use crate::common::{create_test_room, post_json, test_client, test_client_with_rate_limits, TestClient};
+use local_agent_chat::rate_limit::RateLimitConfig;
+use rocket::http::{ContentType, Header, Status};
+
+fn create_hook(client: &TestClient, room_id: &str, admin_key: &str, body: serde_json::Value) -> (Status, serde_json::Value) {
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/incoming-webhooks"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .body(body.to_string())
+        .dispatch();
+    let status = res.status();
+    (status, res.into_json().unwrap_or_default())
+}
+
+fn fire(client: &TestClient, token: &str) -> (Status, serde_json::Value) {
+    post_json(client, &format!("/api/v1/hook/{token}"), serde_json::json!({"content": "alert"}))
+}
+
+fn list(client: &TestClient, room_id: &str, admin_key: &str) -> serde_json::Value {
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/incoming-webhooks"))
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    res.into_json::<serde_json::Value>().unwrap()[0].clone()
+}
+
+#[test]
+fn test_new_hook_reports_defaults_and_zero_usage() {
+    let client = test_client();
+    let (room_id, admin_key) = create_test_room(&client, "hook-quota-defaults");
+    let (status, hook) = create_hook(&client, &room_id, &admin_key, serde_json::json!({"name": "CI"}));
+    assert_eq!(status, Status::Ok);
+    assert!(hook["rate_limit"].is_null());
+    assert!(hook["daily_quota"].is_null());
+    assert_eq!(hook["usage"]["rate_limit"], 60);
+    assert!(hook["usage"]["daily_quota"].is_null(), "unlimited by default");
+    assert_eq!(hook["usage"]["messages_today"], 0);
+    assert_eq!(hook["usage"]["messages_total"], 0);
+    assert!(hook["usage"]["resets_at"].is_string());
+}
+
+#[test]
+fn test_usage_counters_in_list() {
+    let client = test_client();
+    let (room_id, admin_key) = create_test_room(&client, "hook-quota-usage");
+    let (_, hook) = create_hook(&client, &room_id, &admin_key, serde_json::json!({"name": "CI"}));
+    let token = hook["token"].as_str().unwrap();
+    for _ in 0..3 {
+        assert_eq!(fire(&client, token).0, Status::Ok);
+    }
+    let hook = list(&client, &room_id, &admin_key);
+    assert_eq!(hook["usage"]["messages_today"], 3);
+    assert_eq!(hook["usage"]["messages_total"], 3);
+    assert_eq!(hook["usage"]["rejected_today"], 0);
+    assert!(hook["usage"]["last_used_at"].is_string());
+}
+
+#[test]
+fn test_per_hook_daily_quota() {
+    let client = test_client();
+    let (room_id, admin_key) = create_test_room(&client, "hook-quota-daily");
+    let (_, hook) = create_hook(&client, &room_id, &admin_key, serde_json::json!({"name": "Alerts", "daily_quota": 2}));
+    let token = hook["token"].as_str().unwrap();
+    assert_eq!(fire(&client, token).0, Status::Ok);
+    assert_eq!(fire(&client, token).0, Status::Ok);
+    let (status, body) = fire(&client, token);
+    assert_eq!(status, Status::TooManyRequests);
+    assert_eq!(body["quota"], 2);
+    assert_eq!(body["used"], 2);
+    assert!(body["retry_after_secs"].as_i64().unwrap() > 0);
+
+    let hook = list(&client, &room_id, &admin_key);
+    assert_eq!(hook["usage"]["messages_today"], 2);
+    assert_eq!(hook["usage"]["rejected_today"], 1);
+
+    // Yesterday's usage doesn't count against today
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    conn.execute("UPDATE incoming_webhooks SET usage_day = '2000-01-01'", []).unwrap();
+    assert_eq!(fire(&client, token).0, Status::Ok);
+    let hook = list(&client, &room_id, &admin_key);
+    assert_eq!(hook["usage"]["messages_today"], 1);
+    assert_eq!(hook["usage"]["rejected_today"], 0);
+    assert_eq!(hook["usage"]["messages_total"], 3);
+}
+
+#[test]
+fn test_server_default_daily_quota_and_override() {
+    let client = test_client_with_rate_limits(RateLimitConfig { webhooks_daily_quota: 1, ..Default::default() });
+    let (room_id, admin_key) = create_test_room(&client, "hook-quota-server");
+    let (_, hook) = create_hook(&client, &room_id, &admin_key, serde_json::json!({"name": "Alerts"}));
+    let (id, token) = (hook["id"].as_str().unwrap(), hook["token"].as_str().unwrap());
+    assert_eq!(hook["usage"]["daily_quota"], 1);
+    assert_eq!(fire(&client, token).0, Status::Ok);
+    assert_eq!(fire(&client, token).0, Status::TooManyRequests);
+
+    // 0 lifts the quota for this hook; null reverts to the server default
+    let update = |body: &str| {
+        client
+            .put(format!("/api/v1/rooms/{room_id}/incoming-webhooks/{id}"))
+            .header(ContentType::JSON)
+            .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+            .body(body.to_string())
+            .dispatch()
+            .status()
+    };
+    assert_eq!(update(r#"{"daily_quota": 0}"#), Status::Ok);
+    assert_eq!(fire(&client, token).0, Status::Ok);
+    assert_eq!(update(r#"{"daily_quota": null}"#), Status::Ok);
+    assert_eq!(fire(&client, token).0, Status::TooManyRequests);
+    assert_eq!(update(r#"{"daily_quota": -1}"#), Status::BadRequest);
+}
+
+#[test]
+fn test_per_hook_rate_limit() {
+    let client = test_client();
+    let (room_id, admin_key) = create_test_room(&client, "hook-quota-rate");
+    let (_, hook) = create_hook(&client, &room_id, &admin_key, serde_json::json!({"name": "Noisy", "rate_limit": 2}));
+    let token = hook["token"].as_str().unwrap();
+    assert_eq!(fire(&client, token).0, Status::Ok);
+    assert_eq!(fire(&client, token).0, Status::Ok);
+    let (status, body) = fire(&client, token);
+    assert_eq!(status, Status::TooManyRequests);
+    assert_eq!(body["limit"], 2);
+    assert_eq!(list(&client, &room_id, &admin_key)["usage"]["rejected_today"], 1);
+
+    let (status, _) = create_hook(&client, &room_id, &admin_key, serde_json::json!({"name": "Bad", "rate_limit": 0}));
+    assert_eq!(status, Status::BadRequest);
+}
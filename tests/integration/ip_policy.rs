@@ -0,0 +1,106 @@
+use crate::common::test_client_with_ip_policy;
+use local_agent_chat::ip_policy::{IpPolicy, IpRange, IpRules};
+use rocket::http::{ContentType, Status};
+use std::net::SocketAddr;
+
+fn ranges(entries: &[&str]) -> Vec<IpRange> {
+    entries.iter().map(|e| IpRange::parse(e).unwrap()).collect()
+}
+
+fn agent() -> SocketAddr {
+    "10.0.5.7:40000".parse().unwrap()
+}
+
+fn laptop() -> SocketAddr {
+    "192.168.1.50:40000".parse().unwrap()
+}
+
+#[test]
+fn test_ip_ranges() {
+    let lan = IpRange::parse("192.168.0.0/16").unwrap();
+    assert!(lan.contains("192.168.1.50".parse().unwrap()));
+    assert!(lan.contains("::ffff:192.168.1.50".parse().unwrap()));
+    assert!(!lan.contains("10.0.5.7".parse().unwrap()));
+    let host = IpRange::parse("fd00::7").unwrap();
+    assert!(host.contains("fd00::7".parse().unwrap()));
+    assert!(!host.contains("fd00::8".parse().unwrap()));
+    assert!(IpRange::parse("0.0.0.0/0").unwrap().contains("8.8.8.8".parse().unwrap()));
+    for bad in ["10.0.0.0/33", "not-an-ip", "10.0.0.0/x"] {
+        assert!(IpRange::parse(bad).is_none(), "{bad}");
+    }
+}
+
+#[test]
+fn test_reads_from_lan_writes_only_from_agents() {
+    let client = test_client_with_ip_policy(IpPolicy {
+        read: IpRules { allow: ranges(&["192.168.0.0/16", "10.0.0.0/8"]), deny: vec![] },
+        write: IpRules { allow: ranges(&["10.0.5.0/24"]), deny: ranges(&["10.0.5.99"]) },
+        admin: IpRules { allow: ranges(&["127.0.0.1"]), deny: vec![] },
+        trust_proxy: false,
+    });
+    let res = client
+        .post("/api/v1/rooms")
+        .remote(agent())
+        .header(ContentType::JSON)
+        .body(r#"{"name": "ip-room", "created_by": "bot"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let room_id = res.into_json::<serde_json::Value>().unwrap()["id"].as_str().unwrap().to_string();
+
+    let res = client.get(format!("/api/v1/rooms/{room_id}/messages")).remote(laptop()).dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .remote(laptop())
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "human", "content": "hi"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+    let err: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(err["access"], "write");
+    assert_eq!(err["ip"], "192.168.1.50");
+
+    // Deny beats allow
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .remote("10.0.5.99:1".parse().unwrap())
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "rogue", "content": "hi"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+
+    // Outside the read allow list entirely
+    let res = client.get("/api/v1/rooms").remote("172.16.0.1:1".parse().unwrap()).dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+
+    // Admin routes are checked against the admin lists, whatever the method
+    let res = client.get("/api/v1/admin/streams").remote(agent()).dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+    assert_eq!(res.into_json::<serde_json::Value>().unwrap()["access"], "admin");
+
+    // The denied route isn't reachable directly
+    let res = client.get("/api/v1/ip-policy/denied").remote(laptop()).dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+}
+
+#[test]
+fn test_forwarded_address_only_when_trusted() {
+    let policy = |trust_proxy| IpPolicy {
+        write: IpRules { allow: ranges(&["10.0.5.0/24"]), deny: vec![] },
+        trust_proxy,
+        ..IpPolicy::default()
+    };
+    let proxy: SocketAddr = "127.0.0.1:5000".parse().unwrap();
+    for (trust_proxy, expected) in [(false, Status::Forbidden), (true, Status::Ok)] {
+        let client = test_client_with_ip_policy(policy(trust_proxy));
+        let res = client
+            .post("/api/v1/rooms")
+            .remote(proxy)
+            .header(ContentType::JSON)
+            .header(rocket::http::Header::new("X-Forwarded-For", "6.6.6.6, 10.0.5.7"))
+            .body(r#"{"name": "proxied", "created_by": "bot"}"#)
+            .dispatch();
+        assert_eq!(res.status(), expected, "trust_proxy={trust_proxy}");
+    }
+}
@@ -0,0 +1,170 @@
+use crate::common::{create_test_room, post_message, test_client};
+use rocket::http::{ContentType, Status};
+use rocket::local::blocking::Client;
+use serde_json::json;
+
+fn label(client: &Client, room_id: &str, message_id: &str, sender: &str, labels: &[&str]) -> (Status, serde_json::Value) {
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages/{message_id}/labels"))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"sender": sender, "labels": labels}).to_string())
+        .dispatch();
+    let status = res.status();
+    (status, res.into_json().unwrap_or_default())
+}
+
+fn labels_of(body: &serde_json::Value) -> Vec<String> {
+    body["labels"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|l| l["label"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn test_add_and_get_labels() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "labels-basic");
+    let msg = post_message(&client, &room_id, json!({"sender": "alice", "content": "We'll ship on Friday"}))["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let (status, body) = label(&client, &room_id, &msg, "bob", &["Decision", "action-item", "decision"]);
+    assert_eq!(status, Status::Ok);
+    assert_eq!(labels_of(&body), vec!["action-item", "decision"]);
+    assert_eq!(body["labels"][0]["added_by"], "bob");
+
+    // Re-adding is a no-op
+    let (status, body) = label(&client, &room_id, &msg, "carol", &["decision"]);
+    assert_eq!(status, Status::Ok);
+    assert_eq!(labels_of(&body).len(), 2);
+
+    let body: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/messages/{msg}/labels"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(labels_of(&body), vec!["action-item", "decision"]);
+}
+
+#[test]
+fn test_label_validation() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "labels-invalid");
+    let msg = post_message(&client, &room_id, json!({"sender": "alice", "content": "hello"}))["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    assert_eq!(label(&client, &room_id, &msg, "bob", &["has space"]).0, Status::BadRequest);
+    assert_eq!(label(&client, &room_id, &msg, "bob", &[]).0, Status::BadRequest);
+    assert_eq!(label(&client, &room_id, &msg, "", &["bug"]).0, Status::BadRequest);
+    assert_eq!(label(&client, &room_id, "nope", "bob", &["bug"]).0, Status::NotFound);
+}
+
+#[test]
+fn test_remove_label_permissions() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "labels-remove");
+    let msg = post_message(&client, &room_id, json!({"sender": "alice", "content": "flaky test in CI"}))["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    label(&client, &room_id, &msg, "bob", &["bug", "ci"]);
+
+    let res = client
+        .delete(format!("/api/v1/rooms/{room_id}/messages/{msg}/labels/bug?sender=mallory"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+
+    // The message's author may remove a label someone else added
+    let res = client
+        .delete(format!("/api/v1/rooms/{room_id}/messages/{msg}/labels/bug?sender=alice"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(labels_of(&body), vec!["ci"]);
+
+    let res = client
+        .delete(format!("/api/v1/rooms/{room_id}/messages/{msg}/labels/bug?sender=bob"))
+        .dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+}
+
+#[test]
+fn test_label_filters_on_messages_search_and_activity() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "labels-filter");
+    let decided = post_message(
+        &client,
+        &room_id,
+        json!({"sender": "alice", "content": "decided to use postgres for storage"}),
+    )["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    post_message(&client, &room_id, json!({"sender": "bob", "content": "postgres benchmarks look fine"}));
+    label(&client, &room_id, &decided, "lead", &["decision"]);
+
+    let msgs: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/messages?label=Decision"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(msgs.as_array().unwrap().len(), 1);
+    assert_eq!(msgs[0]["id"], decided.as_str());
+
+    let search: serde_json::Value = client
+        .get(format!("/api/v1/search?q=postgres&room_id={room_id}&label=decision"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(search["count"], 1);
+    assert_eq!(search["results"][0]["message_id"], decided.as_str());
+
+    let activity: serde_json::Value = client
+        .get(format!("/api/v1/activity?room_id={room_id}&label=decision"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(activity["events"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn test_room_label_summary() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "labels-summary");
+    let a = post_message(&client, &room_id, json!({"sender": "alice", "content": "one"}))["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let b = post_message(&client, &room_id, json!({"sender": "alice", "content": "two"}))["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    label(&client, &room_id, &a, "bob", &["decision", "bug"]);
+    label(&client, &room_id, &b, "bob", &["decision"]);
+
+    let body: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/labels"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let labels = body["labels"].as_array().unwrap();
+    assert_eq!(labels.len(), 2);
+    assert_eq!(labels[0]["label"], "decision");
+    assert_eq!(labels[0]["count"], 2);
+    assert_eq!(labels[1]["label"], "bug");
+
+    let body: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/labels?after_date=2999-01-01T00:00:00Z"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert!(body["labels"].as_array().unwrap().is_empty());
+
+    let res = client.get("/api/v1/rooms/missing/labels").dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+}
@@ -0,0 +1,115 @@
+use rocket::http::{ContentType, Header, Status};
+use crate::common::{create_test_room, test_client};
+
+// --- Locale negotiation for server-generated text ---
+
+#[test]
+fn test_errors_follow_accept_language() {
+    let client = test_client();
+    let res = client
+        .get("/api/v1/rooms/nonexistent-room/messages")
+        .header(Header::new("Accept-Language", "fr-CA, en;q=0.5"))
+        .dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+    assert_eq!(res.headers().get_one("Content-Language"), Some("fr"));
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["error"], "Salon introuvable");
+    assert_eq!(body["error_code"], "room_not_found");
+
+    // No header: English, but the stable code is still there
+    let res = client.get("/api/v1/rooms/nonexistent-room/messages").dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["error"], "Room not found");
+    assert_eq!(body["error_code"], "room_not_found");
+
+    // Unsupported languages fall through to the default
+    let res = client
+        .get("/api/v1/does-not-exist")
+        .header(Header::new("Accept-Language", "ja, es;q=0"))
+        .dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["error"], "Not found");
+}
+
+#[test]
+fn test_system_messages_render_per_reader() {
+    let client = test_client();
+    let (room_id, key) = create_test_room(&client, "i18n-before");
+    let res = client
+        .put(format!("/api/v1/rooms/{room_id}"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {key}")))
+        .body(r#"{"name": "i18n-after"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    let notes: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/rooms/{room_id}/messages?kind=system"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(notes[0]["content"], "Room renamed from #i18n-before to #i18n-after");
+
+    let notes: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/rooms/{room_id}/messages?kind=system"))
+        .header(Header::new("Accept-Language", "de-DE,de;q=0.9"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(notes[0]["content"], "Raum umbenannt von #i18n-before in #i18n-after");
+    assert_eq!(notes[0]["metadata"]["event"], "room_renamed");
+}
+
+#[test]
+fn test_profile_locale_is_used_without_header() {
+    let client = test_client();
+    let res = client
+        .put("/api/v1/profiles/lucia")
+        .header(ContentType::JSON)
+        .body(r#"{"locale": "es-MX"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let profile: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(profile["locale"], "es");
+
+    let res = client.get("/api/v1/rooms/nonexistent-room/messages?reader=lucia").dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["error"], "Sala no encontrada");
+
+    // An explicit header still wins over the profile
+    let res = client
+        .get("/api/v1/rooms/nonexistent-room/messages?reader=lucia")
+        .header(Header::new("Accept-Language", "en"))
+        .dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["error"], "Room not found");
+
+    // Updating other fields keeps the locale; an empty string clears it
+    let res = client
+        .put("/api/v1/profiles/lucia")
+        .header(ContentType::JSON)
+        .body(r#"{"bio": "hola"}"#)
+        .dispatch();
+    let profile: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(profile["locale"], "es");
+    let res = client
+        .put("/api/v1/profiles/lucia")
+        .header(ContentType::JSON)
+        .body(r#"{"locale": ""}"#)
+        .dispatch();
+    let profile: serde_json::Value = res.into_json().unwrap();
+    assert!(profile.get("locale").is_none());
+}
+
+#[test]
+fn test_profile_rejects_unknown_locale() {
+    let client = test_client();
+    let res = client
+        .put("/api/v1/profiles/kenji")
+        .header(ContentType::JSON)
+        .body(r#"{"locale": "ja"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert!(body["error"].as_str().unwrap().contains("en, es, de, fr"));
+}
@@ -0,0 +1,101 @@
+use rocket::http::Status;
+use rocket::local::blocking::Client;
+use serde_json::json;
+
+use crate::common::{post_json, test_client};
+
+fn expire(client: &Client, name: &str) {
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    conn.execute(
+        "UPDATE locks SET expires_at = '2000-01-01T00:00:00+00:00' WHERE name = ?1",
+        [name],
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_acquire_is_exclusive() {
+    let client = test_client();
+    let (status, lock) = post_json(&client, "/api/v1/locks/deploy-prod/acquire", json!({"holder": "agent-a", "ttl_seconds": 30}));
+    assert_eq!(status, Status::Ok);
+    assert_eq!(lock["held"], true);
+    assert_eq!(lock["holder"], "agent-a");
+    assert_eq!(lock["token"], 1);
+
+    let (status, body) = post_json(&client, "/api/v1/locks/deploy-prod/acquire", json!({"holder": "agent-b"}));
+    assert_eq!(status, Status::Conflict);
+    assert_eq!(body["holder"], "agent-a");
+    assert!(body["expires_at"].is_string());
+
+    // Re-acquiring your own lock extends it without a new token
+    let (status, lock) = post_json(&client, "/api/v1/locks/deploy-prod/acquire", json!({"holder": "agent-a"}));
+    assert_eq!(status, Status::Ok);
+    assert_eq!(lock["token"], 1);
+
+    let body: serde_json::Value = client.get("/api/v1/locks").dispatch().into_json().unwrap();
+    assert_eq!(body.as_array().unwrap().len(), 1);
+    assert_eq!(body[0]["name"], "deploy-prod");
+}
+
+#[test]
+fn test_release_and_fencing_tokens() {
+    let client = test_client();
+    post_json(&client, "/api/v1/locks/repo/acquire", json!({"holder": "agent-a"}));
+
+    let (status, _) = post_json(&client, "/api/v1/locks/repo/release", json!({"holder": "agent-b", "token": 1}));
+    assert_eq!(status, Status::Conflict);
+    let (status, _) = post_json(&client, "/api/v1/locks/repo/release", json!({"holder": "agent-a", "token": 7}));
+    assert_eq!(status, Status::Conflict);
+    let (status, lock) = post_json(&client, "/api/v1/locks/repo/release", json!({"holder": "agent-a", "token": 1}));
+    assert_eq!(status, Status::Ok);
+    assert_eq!(lock["held"], false);
+    assert!(lock["holder"].is_null());
+
+    let (_, lock) = post_json(&client, "/api/v1/locks/repo/acquire", json!({"holder": "agent-b"}));
+    assert_eq!(lock["token"], 2);
+
+    // An expired lock is free for the next agent, with a higher token
+    expire(&client, "repo");
+    let body: serde_json::Value = client.get("/api/v1/locks/repo").dispatch().into_json().unwrap();
+    assert_eq!(body["held"], false);
+    let (status, lock) = post_json(&client, "/api/v1/locks/repo/acquire", json!({"holder": "agent-c"}));
+    assert_eq!(status, Status::Ok);
+    assert_eq!(lock["token"], 3);
+
+    // The lapsed holder can neither renew nor release
+    let (status, _) = post_json(&client, "/api/v1/locks/repo/renew", json!({"holder": "agent-b", "token": 2}));
+    assert_eq!(status, Status::Conflict);
+    let (status, _) = post_json(&client, "/api/v1/locks/repo/release", json!({"holder": "agent-b", "token": 2}));
+    assert_eq!(status, Status::Conflict);
+}
+
+#[test]
+fn test_renew() {
+    let client = test_client();
+    let (_, lock) = post_json(&client, "/api/v1/locks/build/acquire", json!({"holder": "agent-a", "ttl_seconds": 5}));
+    let first_expiry = lock["expires_at"].as_str().unwrap().to_string();
+    let (status, lock) = post_json(&client, "/api/v1/locks/build/renew", json!({"holder": "agent-a", "token": 1, "ttl_seconds": 3600}));
+    assert_eq!(status, Status::Ok);
+    assert!(lock["expires_at"].as_str().unwrap() > first_expiry.as_str());
+
+    expire(&client, "build");
+    let (status, _) = post_json(&client, "/api/v1/locks/build/renew", json!({"holder": "agent-a", "token": 1}));
+    assert_eq!(status, Status::Conflict);
+    let (status, _) = post_json(&client, "/api/v1/locks/unknown/renew", json!({"holder": "agent-a", "token": 1}));
+    assert_eq!(status, Status::NotFound);
+}
+
+#[test]
+fn test_lock_validation_and_unknown() {
+    let client = test_client();
+    let body: serde_json::Value = client.get("/api/v1/locks/never-used").dispatch().into_json().unwrap();
+    assert_eq!(body["held"], false);
+    assert_eq!(body["token"], 0);
+
+    let (status, _) = post_json(&client, "/api/v1/locks/x/acquire", json!({"holder": ""}));
+    assert_eq!(status, Status::BadRequest);
+    let (status, _) = post_json(&client, "/api/v1/locks/x/acquire", json!({"holder": "a", "ttl_seconds": 0}));
+    assert_eq!(status, Status::BadRequest);
+    let (status, _) = post_json(&client, "/api/v1/locks/x/acquire", json!({"holder": "a", "ttl_seconds": 86_401}));
+    assert_eq!(status, Status::BadRequest);
+}
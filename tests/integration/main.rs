@@ -39,3 +39,78 @@ mod export;
 mod edit_history;
 mod cross_feature_v2;
 mod broadcast;
+mod mentionables;
+mod email_gateway;
+mod message_streams;
+mod db_config;
+mod room_list_bench;
+mod request_id;
+mod telemetry;
+mod pagination_props;
+mod dev_seed;
+mod message_move;
+mod room_merge;
+mod room_redirects;
+mod room_by_name;
+mod reserved_senders;
+mod system_messages;
+mod welcome;
+mod localization;
+mod file_expiry;
+mod upload_policy;
+mod sparse_fields;
+mod ndjson;
+mod heatmap;
+mod conversations;
+mod retention_notice;
+mod snapshots;
+mod commands;
+mod push;
+mod device_state;
+mod thread_stats;
+mod flags;
+mod webhook_circuit;
+mod incoming_webhook_quotas;
+mod room_stats;
+mod message_sample;
+mod sender_aliases;
+mod namespaces;
+mod message_patch;
+mod message_append;
+mod sender_status;
+mod work_queue;
+mod locks;
+mod server_webhooks;
+mod migrations;
+mod import;
+mod sender_quotas;
+mod cost_stats;
+mod sse_filters;
+mod sse_connections;
+mod sse_lag;
+mod room_subscriptions;
+mod sensitive_messages;
+mod sender_export;
+mod room_provisioning;
+mod api_versions;
+mod room_language;
+mod regex_search;
+mod labels;
+mod decisions;
+mod mention_nudges;
+mod room_topics;
+mod maintenance;
+mod search_index;
+mod webhook_transport;
+mod event_outbox;
+mod reaction_notifications;
+mod pending_responses;
+mod timezones;
+mod quiet_hours;
+mod server_config;
+mod firehose;
+mod ip_policy;
+mod admin_key_lockout;
+mod scheduled_messages;
+mod client_sessions;
+mod private_rooms;
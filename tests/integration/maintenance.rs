@@ -0,0 +1,115 @@
+use crate::common::{admin_client, create_test_room, post_message, test_client};
+use rocket::http::{ContentType, Header, Status};
+use rocket::local::blocking::Client;
+use serde_json::json;
+
+fn set_maintenance(client: &Client, body: serde_json::Value) -> (Status, serde_json::Value) {
+    let res = client
+        .put("/api/v1/admin/maintenance")
+        .header(ContentType::JSON)
+        .header(Header::new("X-Server-Token", "srv_secret"))
+        .body(body.to_string())
+        .dispatch();
+    let status = res.status();
+    (status, res.into_json().unwrap_or_default())
+}
+
+#[test]
+fn test_maintenance_blocks_writes_but_serves_reads() {
+    let client = admin_client();
+    let (room_id, _) = create_test_room(&client, "maint-room");
+    post_message(&client, &room_id, json!({"sender": "agent", "content": "hello"}));
+
+    let (status, body) = set_maintenance(
+        &client,
+        serde_json::json!({"enabled": true, "message": "Backing up, back in 5 minutes"}),
+    );
+    assert_eq!(status, Status::Ok);
+    assert_eq!(body["enabled"], true);
+    assert!(body["since"].is_string());
+
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "agent", "content": "blocked"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::ServiceUnavailable);
+    let err: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(err["error"], "Backing up, back in 5 minutes");
+    assert_eq!(err["maintenance"], true);
+    let res = client.delete(format!("/api/v1/rooms/{room_id}")).dispatch();
+    assert_eq!(res.status(), Status::ServiceUnavailable);
+    let res = client
+        .post("/api/v1/rooms")
+        .header(ContentType::JSON)
+        .body(r#"{"name": "maint-new"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::ServiceUnavailable);
+
+    // Reads keep working
+    let res = client.get(format!("/api/v1/rooms/{room_id}/messages")).dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let messages: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(messages.as_array().unwrap().len(), 1);
+    let status: serde_json::Value = client.get("/api/v1/maintenance").dispatch().into_json().unwrap();
+    assert_eq!(status["enabled"], true);
+    assert_eq!(status["message"], "Backing up, back in 5 minutes");
+
+    let (status, body) = set_maintenance(&client, serde_json::json!({"enabled": false}));
+    assert_eq!(status, Status::Ok);
+    assert_eq!(body["enabled"], false);
+    post_message(&client, &room_id, json!({"sender": "agent", "content": "hello"}));
+}
+
+#[test]
+fn test_maintenance_default_message_and_v2_errors() {
+    let client = admin_client();
+    let (room_id, _) = create_test_room(&client, "maint-v2");
+    set_maintenance(&client, serde_json::json!({"enabled": true}));
+
+    let res = client
+        .post(format!("/api/v2/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "agent", "content": "blocked"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::ServiceUnavailable);
+    let err: serde_json::Value = res.into_json().unwrap();
+    assert!(err["error"]["message"].as_str().unwrap().contains("maintenance mode"));
+}
+
+#[test]
+fn test_maintenance_toggle_requires_server_token() {
+    let client = admin_client();
+    let res = client
+        .put("/api/v1/admin/maintenance")
+        .header(ContentType::JSON)
+        .body(r#"{"enabled": true}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+
+    let client = test_client();
+    let res = client
+        .put("/api/v1/admin/maintenance")
+        .header(ContentType::JSON)
+        .body(r#"{"enabled": true}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+    let status: serde_json::Value = client.get("/api/v1/maintenance").dispatch().into_json().unwrap();
+    assert_eq!(status["enabled"], false);
+
+    // Not reachable directly
+    let res = client.get("/api/v1/maintenance/blocked").dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+}
+
+#[test]
+fn test_maintenance_clones_share_the_switch() {
+    // The email gateway holds a clone; toggling via the API must reach it
+    let maintenance = local_agent_chat::maintenance::Maintenance::default();
+    let gateway = maintenance.clone();
+    maintenance.enable(Some("Backing up".to_string()));
+    assert!(gateway.is_enabled());
+    assert_eq!(gateway.status().message.as_deref(), Some("Backing up"));
+    maintenance.disable();
+    assert!(!gateway.is_enabled());
+}
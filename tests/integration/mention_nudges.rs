@@ -0,0 +1,93 @@
+use crate::common::{create_test_room, post_message, test_client};
+use local_agent_chat::nudges;
+use local_agent_chat::routes::PresenceTracker;
+use rocket::http::{ContentType, Status};
+use rocket::local::blocking::Client;
+use serde_json::json;
+
+fn set_nudge(client: &Client, sender: &str, minutes: i64) -> Status {
+    client
+        .put(format!("/api/v1/profiles/{sender}"))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"mention_nudge_minutes": minutes}).to_string())
+        .dispatch()
+        .status()
+}
+
+fn backdate(conn: &rusqlite::Connection, message_id: &str, minutes: i64) {
+    let at = (chrono::Utc::now() - chrono::Duration::minutes(minutes)).to_rfc3339();
+    conn.execute("UPDATE messages SET created_at = ?1 WHERE id = ?2", [&at, message_id])
+        .unwrap();
+}
+
+#[test]
+fn test_profile_nudge_setting() {
+    let client = test_client();
+    assert_eq!(set_nudge(&client, "nora", 15), Status::Ok);
+    let profile: serde_json::Value = client.get("/api/v1/profiles/nora").dispatch().into_json().unwrap();
+    assert_eq!(profile["mention_nudge_minutes"], 15);
+
+    assert_eq!(set_nudge(&client, "nora", 0), Status::Ok);
+    let profile: serde_json::Value = client.get("/api/v1/profiles/nora").dispatch().into_json().unwrap();
+    assert!(profile.get("mention_nudge_minutes").is_none());
+
+    assert_eq!(set_nudge(&client, "nora", 5000), Status::BadRequest);
+}
+
+#[test]
+fn test_unread_mention_is_nudged_once() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "nudge-room");
+    assert_eq!(set_nudge(&client, "nora", 10), Status::Ok);
+    let msg =
+        post_message(&client, &room_id, json!({"sender": "alice", "content": "@nora can you review the deploy plan?"}));
+    let msg_id = msg["id"].as_str().unwrap();
+
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    let presence = PresenceTracker::default();
+
+    // Not due yet
+    assert!(nudges::run_due(&conn, &presence, chrono::Utc::now()).is_empty());
+
+    backdate(&conn, msg_id, 11);
+    let sent = nudges::run_due(&conn, &presence, chrono::Utc::now());
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].sender, "system");
+    assert!(sent[0].content.contains("alice mentioned you in #nudge-room"));
+    assert_eq!(sent[0].metadata["message_id"], msg_id);
+
+    // The DM shows up in nora's conversations, and isn't repeated
+    let dms: serde_json::Value = client.get("/api/v1/dm?sender=nora").dispatch().into_json().unwrap();
+    assert_eq!(dms["conversations"].as_array().unwrap().len(), 1);
+    assert!(nudges::run_due(&conn, &presence, chrono::Utc::now()).is_empty());
+}
+
+#[test]
+fn test_no_nudge_when_read_present_or_opted_out() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "nudge-skip");
+    assert_eq!(set_nudge(&client, "nora", 5), Status::Ok);
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+
+    // Read past the mention
+    let read = post_message(&client, &room_id, json!({"sender": "alice", "content": "@nora first"}));
+    backdate(&conn, read["id"].as_str().unwrap(), 6);
+    let res = client
+        .put(format!("/api/v1/rooms/{room_id}/read"))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"sender": "nora", "last_read_seq": read["seq"]}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    assert!(nudges::run_due(&conn, &PresenceTracker::default(), chrono::Utc::now()).is_empty());
+
+    // Online somewhere
+    let unread = post_message(&client, &room_id, json!({"sender": "alice", "content": "@nora second"}));
+    backdate(&conn, unread["id"].as_str().unwrap(), 6);
+    let presence = PresenceTracker::default();
+    presence.join("elsewhere", "nora", Some("human"));
+    assert!(nudges::run_due(&conn, &presence, chrono::Utc::now()).is_empty());
+
+    // Opted out
+    assert_eq!(set_nudge(&client, "nora", 0), Status::Ok);
+    assert!(nudges::run_due(&conn, &PresenceTracker::default(), chrono::Utc::now()).is_empty());
+}
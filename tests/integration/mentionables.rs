@@ -0,0 +1,98 @@
+use rocket::http::{ContentType, Status};
+use serde_json::json;
+
+use crate::common::{create_test_room, post_message, test_client};
+
+/// Candidates are ordered by most recent activity in the room.
+#[test]
+fn mentionables_recent_first() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "mentionables-order");
+    post_message(&client, &room_id, json!({"sender": "alice", "content": "hi", "sender_type": "human"}));
+    post_message(&client, &room_id, json!({"sender": "albert", "content": "hi", "sender_type": "agent"}));
+    post_message(&client, &room_id, json!({"sender": "bob", "content": "hi", "sender_type": "agent"}));
+
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/mentionables"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body: serde_json::Value = res.into_json().unwrap();
+    let senders: Vec<&str> = body["candidates"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["sender"].as_str().unwrap())
+        .collect();
+    assert_eq!(senders, vec!["bob", "albert", "alice"]);
+    assert_eq!(body["candidates"][0]["sender_type"], "agent");
+}
+
+/// Prefix filters case-insensitively and ignores a leading @.
+#[test]
+fn mentionables_prefix_filter() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "mentionables-prefix");
+    post_message(&client, &room_id, json!({"sender": "alice", "content": "hi", "sender_type": "human"}));
+    post_message(&client, &room_id, json!({"sender": "albert", "content": "hi", "sender_type": "agent"}));
+    post_message(&client, &room_id, json!({"sender": "bob", "content": "hi", "sender_type": "agent"}));
+
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/mentionables?prefix=%40AL"))
+        .dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["count"], 2);
+    assert_eq!(body["prefix"], "AL");
+    assert_eq!(body["candidates"][0]["sender"], "albert");
+}
+
+/// Prefix also matches profile display names, and results include them.
+#[test]
+fn mentionables_matches_display_name() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "mentionables-profile");
+    post_message(&client, &room_id, json!({"sender": "agent-7f3", "content": "hi", "sender_type": "agent"}));
+    client
+        .put("/api/v1/profiles/agent-7f3")
+        .header(ContentType::JSON)
+        .body(json!({"display_name": "Zephyr"}).to_string())
+        .dispatch();
+
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/mentionables?prefix=zep"))
+        .dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["count"], 1);
+    assert_eq!(body["candidates"][0]["sender"], "agent-7f3");
+    assert_eq!(body["candidates"][0]["display_name"], "Zephyr");
+}
+
+/// LIKE wildcards in the prefix are treated literally.
+#[test]
+fn mentionables_prefix_escapes_wildcards() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "mentionables-escape");
+    post_message(&client, &room_id, json!({"sender": "alice", "content": "hi", "sender_type": "human"}));
+
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/mentionables?prefix=%25"))
+        .dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["count"], 0);
+}
+
+#[test]
+fn mentionables_limit_and_missing_room() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "mentionables-limit");
+    for name in ["a1", "a2", "a3"] {
+        post_message(&client, &room_id, json!({"sender": name, "content": "hi", "sender_type": "agent"}));
+    }
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/mentionables?limit=2"))
+        .dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["count"], 2);
+
+    let res = client.get("/api/v1/rooms/nonexistent/mentionables").dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+}
@@ -447,3 +447,67 @@ fn test_unread_mentions_multiple_rooms() {
     assert_eq!(body["rooms"].as_array().unwrap().len(), 1);
     assert_eq!(body["rooms"][0]["room_name"], "unread-b");
 }
+
+#[test]
+fn test_mentions_ignore_email_addresses() {
+    let client = test_client();
+    let (room_id, _) = crate::common::create_test_room(&client, "mention-email");
+
+    client
+        .post(format!("/api/v1/rooms/{}/messages", room_id))
+        .header(ContentType::JSON)
+        .body(r#"{"sender":"alice","content":"Mail ops@nanook.dev for access"}"#)
+        .dispatch();
+
+    let res = client.get("/api/v1/mentions?target=nanook.dev").dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["count"], 0);
+}
+
+#[test]
+fn test_mentions_require_full_name() {
+    let client = test_client();
+    let (room_id, _) = crate::common::create_test_room(&client, "mention-prefix");
+
+    client
+        .post(format!("/api/v1/rooms/{}/messages", room_id))
+        .header(ContentType::JSON)
+        .body(r#"{"sender":"alice","content":"@nanookbot please run the build."}"#)
+        .dispatch();
+    client
+        .post(format!("/api/v1/rooms/{}/messages", room_id))
+        .header(ContentType::JSON)
+        .body(r#"{"sender":"alice","content":"Thanks, @nanook."}"#)
+        .dispatch();
+
+    let res = client.get("/api/v1/mentions?target=nanook").dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["count"], 1);
+    assert_eq!(body["mentions"][0]["content"], "Thanks, @nanook.");
+
+    let res = client.get("/api/v1/mentions?target=nanookbot").dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["count"], 1);
+}
+
+#[test]
+fn test_mentions_removed_with_message() {
+    let client = test_client();
+    let (room_id, _) = crate::common::create_test_room(&client, "mention-delete");
+
+    let res = client
+        .post(format!("/api/v1/rooms/{}/messages", room_id))
+        .header(ContentType::JSON)
+        .body(r#"{"sender":"alice","content":"@forge ping"}"#)
+        .dispatch();
+    let msg: serde_json::Value = res.into_json().unwrap();
+    let msg_id = msg["id"].as_str().unwrap();
+
+    client
+        .delete(format!("/api/v1/rooms/{}/messages/{}?sender=alice", room_id, msg_id))
+        .dispatch();
+
+    let res = client.get("/api/v1/mentions/unread?target=forge").dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["total_unread"], 0);
+}
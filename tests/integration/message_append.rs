@@ -0,0 +1,198 @@
+use rocket::http::{ContentType, Header, Status};
+use rocket::local::blocking::Client;
+use serde_json::json;
+
+use crate::common::{create_test_room, policy_with_token, post_message, test_client, test_client_with_sender_policy};
+
+fn append(client: &Client, room_id: &str, msg_id: &str, sender: &str, content: &str) -> (Status, serde_json::Value) {
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages/{msg_id}/append"))
+        .header(ContentType::JSON)
+        .body(json!({"sender": sender, "content": content}).to_string())
+        .dispatch();
+    let status = res.status();
+    (status, res.into_json().unwrap_or(serde_json::Value::Null))
+}
+
+#[test]
+fn test_append_to_message() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "append-progress");
+    let msg_id = post_message(&client, &room_id, json!({"sender": "builder", "content": "Build started"}))["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let (status, body) = append(&client, &room_id, &msg_id, "builder", "\nstep 1/2: compiled");
+    assert_eq!(status, Status::Ok);
+    assert_eq!(body["delta"], "\nstep 1/2: compiled");
+    assert_eq!(body["sender"], "builder");
+    assert_eq!(body["length"], "Build started\nstep 1/2: compiled".len());
+    let (_, body) = append(&client, &room_id, &msg_id, "builder", "\nstep 2/2: zygomorphic tests passed");
+    assert_eq!(body["length"], "Build started\nstep 1/2: compiled\nstep 2/2: zygomorphic tests passed".len());
+
+    let res = client.get(format!("/api/v1/rooms/{room_id}/messages?latest=1")).dispatch();
+    let msgs: Vec<serde_json::Value> = res.into_json().unwrap();
+    assert_eq!(msgs[0]["content"], "Build started\nstep 1/2: compiled\nstep 2/2: zygomorphic tests passed");
+    // Each append is kept in edit history, with the appended text as its patch
+    assert_eq!(msgs[0]["edit_count"], 2);
+    let history: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/messages/{msg_id}/edits"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let edits = history["edits"].as_array().unwrap();
+    assert_eq!(edits.len(), 2);
+    assert_eq!(edits[0]["previous_content"], "Build started");
+    assert_eq!(edits[0]["patch_format"], "append");
+    assert_eq!(edits[0]["patch"], "\nstep 1/2: compiled");
+    assert_eq!(edits[1]["editor"], "builder");
+
+    // ...and its event is in the outbox, so SSE clients can resume past it
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    let recorded: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM event_outbox WHERE room_id = ?1 AND event LIKE '%\"message_appended\"%'",
+            [&room_id],
+            |r| r.get(0),
+        )
+        .unwrap();
+    assert_eq!(recorded, 2);
+
+    // Appended text is searchable
+    let res = client.get("/api/v1/search?q=zygomorphic").dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["results"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn test_append_rules() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "append-rules");
+    let msg_id = post_message(&client, &room_id, json!({"sender": "builder", "content": "Build started"}))["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let (status, _) = append(&client, &room_id, &msg_id, "intruder", "hijack");
+    assert_eq!(status, Status::Forbidden);
+    let (status, _) = append(&client, &room_id, &msg_id, "builder", "");
+    assert_eq!(status, Status::BadRequest);
+    let (status, _) = append(&client, &room_id, "missing", "builder", "x");
+    assert_eq!(status, Status::NotFound);
+
+    // Still-streaming messages use the stream append
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages/stream/start"))
+        .header(ContentType::JSON)
+        .body(json!({"sender": "builder", "content": "draft"}).to_string())
+        .dispatch();
+    let stream_id = res.into_json::<serde_json::Value>().unwrap()["id"].as_str().unwrap().to_string();
+    let (status, body) = append(&client, &room_id, &stream_id, "builder", " more");
+    assert_eq!(status, Status::Conflict);
+    let hint = format!("use PATCH /api/v1/rooms/{room_id}/messages/stream/{stream_id}/append");
+    assert!(body["error"].as_str().unwrap().contains(&hint));
+}
+
+#[test]
+fn test_append_as_reserved_sender_needs_token() {
+    let client = test_client_with_sender_policy(policy_with_token());
+    let (room_id, _) = create_test_room(&client, "append-reserved");
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .header(Header::new("X-Server-Token", "srv_secret"))
+        .body(json!({"sender": "admin", "content": "Deploy frozen"}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let msg_id = res.into_json::<serde_json::Value>().unwrap()["id"].as_str().unwrap().to_string();
+
+    // Claiming the name isn't enough to add to the admin's message
+    let (status, _) = append(&client, &room_id, &msg_id, "admin", " (lifted, go ahead)");
+    assert_eq!(status, Status::Forbidden);
+
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages/{msg_id}/append"))
+        .header(ContentType::JSON)
+        .header(Header::new("X-Server-Token", "srv_secret"))
+        .body(json!({"sender": "admin", "content": " until 18:00"}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let msgs: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/rooms/{room_id}/messages?latest=1"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(msgs[0]["content"], "Deploy frozen until 18:00");
+}
+
+#[test]
+fn test_append_to_redacted_message_rejected() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "append-redacted");
+    let msg_id =
+        post_message(&client, &room_id, json!({"sender": "oncall", "content": "token abc123", "sensitive": true}))["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+    // Before the redaction, appends are allowed and are wiped along with the rest
+    let (status, _) = append(&client, &room_id, &msg_id, "oncall", " (rotating)");
+    assert_eq!(status, Status::Ok);
+
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    conn.execute(
+        "UPDATE sensitive_messages SET redact_at = '2000-01-01T00:00:00+00:00' WHERE message_id = ?1",
+        [&msg_id],
+    )
+    .unwrap();
+    let result: serde_json::Value = client.post("/api/v1/admin/retention/run").dispatch().into_json().unwrap();
+    assert_eq!(result["messages_redacted"], 1);
+
+    let (status, _) = append(&client, &room_id, &msg_id, "oncall", " new token def456");
+    assert_eq!(status, Status::Conflict);
+    let msgs: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/rooms/{room_id}/messages?latest=1"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(msgs[0]["content"], "[sensitive content redacted]");
+}
+
+#[test]
+fn test_append_size_limit() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "append-limit");
+    let msg_id = post_message(&client, &room_id, json!({"sender": "builder", "content": "log:"}))["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let chunk = "x".repeat(10_000);
+    let (status, _) = append(&client, &room_id, &msg_id, "builder", &format!("{chunk}y"));
+    assert_eq!(status, Status::BadRequest);
+    for _ in 0..9 {
+        let (status, _) = append(&client, &room_id, &msg_id, "builder", &chunk);
+        assert_eq!(status, Status::Ok);
+    }
+    // 4 + 90,000 bytes so far; another 10,000 would pass the default 100,000 cap
+    let (status, body) = append(&client, &room_id, &msg_id, "builder", &chunk);
+    assert_eq!(status, Status::PayloadTooLarge);
+    assert_eq!(body["max_length"], 100_000);
+    assert_eq!(body["length"], 90_004);
+    let (status, _) = append(&client, &room_id, &msg_id, "builder", &"x".repeat(9_996));
+    assert_eq!(status, Status::Ok);
+}
+
+#[test]
+fn test_webhook_accepts_message_appended() {
+    let client = test_client();
+    let (room_id, admin_key) = create_test_room(&client, "append-hooks");
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/webhooks"))
+        .header(ContentType::JSON)
+        .header(rocket::http::Header::new("Authorization", format!("Bearer {admin_key}")))
+        .body(json!({"url": "http://127.0.0.1:9/hook", "events": "message_appended"}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+}
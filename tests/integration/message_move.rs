@@ -0,0 +1,253 @@
+use rocket::http::{ContentType, Header, Status};
+use rocket::local::blocking::Client;
+use crate::common::{create_test_room, post_message, test_client};
+use serde_json::json;
+
+// --- Moving messages between rooms ---
+
+fn move_msg(client: &Client, room_id: &str, msg_id: &str, key: &str, body: serde_json::Value) -> (Status, serde_json::Value) {
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages/{msg_id}/move"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {key}")))
+        .body(body.to_string())
+        .dispatch();
+    let status = res.status();
+    (status, res.into_json().unwrap_or(serde_json::json!(null)))
+}
+
+fn messages(client: &Client, room_id: &str) -> Vec<serde_json::Value> {
+    client
+        .get(format!("/api/v1/rooms/{room_id}/messages"))
+        .dispatch()
+        .into_json()
+        .unwrap()
+}
+
+#[test]
+fn test_move_message_leaves_tombstone_at_old_seq() {
+    let client = test_client();
+    let (src, key) = create_test_room(&client, "move-src");
+    let (dst, dst_key) = create_test_room(&client, "move-dst");
+    let before = post_message(&client, &src, json!({"sender": "agent", "content": "before"}));
+    let misplaced = post_message(&client, &src, json!({"sender": "agent", "content": "wrong room"}));
+    let after = post_message(&client, &src, json!({"sender": "agent", "content": "after"}));
+    post_message(&client, &dst, json!({"sender": "agent", "content": "already here"}));
+    let old_seq = misplaced["seq"].as_i64().unwrap();
+    let id = misplaced["id"].as_str().unwrap();
+
+    let (status, body) = move_msg(&client, &src, id, &key, serde_json::json!({"target_room_id": dst, "target_admin_key": dst_key}));
+    assert_eq!(status, Status::Ok);
+    let moved = &body["moved"][0];
+    assert_eq!(moved["id"], id, "moved messages keep their id");
+    assert_eq!(moved["room_id"], dst.as_str());
+    assert!(moved["seq"].as_i64().unwrap() > after["seq"].as_i64().unwrap());
+    assert_eq!(moved["metadata"]["moved_from"]["room_id"], src.as_str());
+
+    // Source: tombstone in the original slot, pointing at the new location
+    let src_msgs = messages(&client, &src);
+    let seqs: Vec<i64> = src_msgs.iter().map(|m| m["seq"].as_i64().unwrap()).collect();
+    assert_eq!(seqs, vec![before["seq"].as_i64().unwrap(), old_seq, after["seq"].as_i64().unwrap()]);
+    let tombstone = &src_msgs[1];
+    assert_eq!(tombstone["sender"], "system");
+    assert_eq!(tombstone["metadata"]["moved_to"]["room_id"], dst.as_str());
+    assert_eq!(tombstone["metadata"]["moved_to"]["message_id"], id);
+    assert!(src_msgs.iter().all(|m| m["id"] != id));
+
+    // Target: appended after existing history
+    let dst_msgs = messages(&client, &dst);
+    assert_eq!(dst_msgs.len(), 2);
+    assert_eq!(dst_msgs[1]["id"], id);
+    assert_eq!(dst_msgs[1]["content"], "wrong room");
+}
+
+#[test]
+fn test_move_whole_thread() {
+    let client = test_client();
+    let (src, key) = create_test_room(&client, "move-thread-src");
+    let (dst, dst_key) = create_test_room(&client, "move-thread-dst");
+    let root = post_message(&client, &src, json!({"sender": "agent", "content": "root"}));
+    let root_id = root["id"].as_str().unwrap();
+    let r1 = post_message(&client, &src, json!({"sender": "agent", "content": "reply 1", "reply_to": root_id}));
+    let r2 = post_message(
+        &client,
+        &src,
+        json!({"sender": "agent", "content": "reply to reply", "reply_to": r1["id"].as_str().unwrap()}),
+    );
+    post_message(&client, &src, json!({"sender": "agent", "content": "unrelated"}));
+
+    // Moving from any message in the thread takes the whole thread
+    let (status, body) = move_msg(
+        &client,
+        &src,
+        r2["id"].as_str().unwrap(),
+        &key,
+        serde_json::json!({"target_room_id": dst, "target_admin_key": dst_key, "include_thread": true}),
+    );
+    assert_eq!(status, Status::Ok);
+    assert_eq!(body["moved"].as_array().unwrap().len(), 3);
+    assert_eq!(body["tombstones"].as_array().unwrap().len(), 3);
+
+    let res = client
+        .get(format!("/api/v1/rooms/{dst}/messages/{root_id}/thread"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let thread: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(thread["total_replies"], 2);
+
+    let src_msgs = messages(&client, &src);
+    assert_eq!(src_msgs.len(), 4);
+    assert_eq!(src_msgs.iter().filter(|m| m["sender"] == "system").count(), 3);
+    // Tombstones mirror the original thread shape
+    assert_eq!(src_msgs[1]["reply_to"], src_msgs[0]["id"]);
+}
+
+#[test]
+fn test_move_single_reply_repoints_children() {
+    let client = test_client();
+    let (src, key) = create_test_room(&client, "move-child-src");
+    let (dst, dst_key) = create_test_room(&client, "move-child-dst");
+    let root = post_message(&client, &src, json!({"sender": "agent", "content": "root"}));
+    let mid = post_message(
+        &client,
+        &src,
+        json!({"sender": "agent", "content": "mid", "reply_to": root["id"].as_str().unwrap()}),
+    );
+    let leaf = post_message(
+        &client,
+        &src,
+        json!({"sender": "agent", "content": "leaf", "reply_to": mid["id"].as_str().unwrap()}),
+    );
+
+    let (status, body) = move_msg(&client, &src, mid["id"].as_str().unwrap(), &key, serde_json::json!({"target_room_id": dst, "target_admin_key": dst_key}));
+    assert_eq!(status, Status::Ok);
+    // Parent stayed behind, so the moved message is top-level in its new room
+    assert!(body["moved"][0].get("reply_to").is_none());
+
+    let tombstone_id = body["tombstones"][0]["id"].as_str().unwrap();
+    let src_msgs = messages(&client, &src);
+    let leaf_now = src_msgs.iter().find(|m| m["id"] == leaf["id"]).unwrap();
+    assert_eq!(leaf_now["reply_to"], tombstone_id);
+}
+
+#[test]
+fn test_move_message_validation() {
+    let client = test_client();
+    let (src, key) = create_test_room(&client, "move-val-src");
+    let (dst, dst_key) = create_test_room(&client, "move-val-dst");
+    let msg = post_message(&client, &src, json!({"sender": "agent", "content": "hello"}));
+    let id = msg["id"].as_str().unwrap();
+
+    let (status, _) = move_msg(&client, &src, id, &dst_key, serde_json::json!({"target_room_id": dst, "target_admin_key": dst_key}));
+    assert_eq!(status, Status::Forbidden);
+
+    // The source admin alone can't push messages into someone else's room
+    let (status, body) = move_msg(&client, &src, id, &key, serde_json::json!({"target_room_id": dst}));
+    assert_eq!(status, Status::Forbidden);
+    assert!(body["error"].as_str().unwrap().contains("target_admin_key"));
+    let (status, _) = move_msg(&client, &src, id, &key, serde_json::json!({"target_room_id": dst, "target_admin_key": key}));
+    assert_eq!(status, Status::Forbidden);
+
+    let (status, _) = move_msg(&client, &src, id, &key, serde_json::json!({"target_room_id": src, "target_admin_key": key}));
+    assert_eq!(status, Status::BadRequest);
+
+    let (status, _) = move_msg(&client, &src, id, &key, serde_json::json!({"target_room_id": "nope"}));
+    assert_eq!(status, Status::NotFound);
+
+    let (status, _) = move_msg(&client, &src, "missing", &key, serde_json::json!({"target_room_id": dst, "target_admin_key": dst_key}));
+    assert_eq!(status, Status::NotFound);
+
+    let res = client
+        .post(format!("/api/v1/rooms/{src}/messages/{id}/move"))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"target_room_id": dst}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Unauthorized);
+}
+
+#[test]
+fn test_move_into_private_room() {
+    let client = test_client();
+    let (src, key) = create_test_room(&client, "move-priv-src");
+    let (dst, dst_key) = create_test_room(&client, "move-priv-dst");
+    let first = post_message(&client, &src, json!({"sender": "agent", "content": "for members"}));
+    let second = post_message(&client, &src, json!({"sender": "agent", "content": "also for members"}));
+    let res = client
+        .post(format!("/api/v1/rooms/{dst}/members"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {dst_key}")))
+        .body(r#"{"sender": "mover"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let token = res.into_json::<serde_json::Value>().unwrap()["token"].as_str().unwrap().to_string();
+    let res = client
+        .put(format!("/api/v1/rooms/{dst}"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {dst_key}")))
+        .body(r#"{"visibility": "private"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    // A member of a private target may move messages in without its admin key
+    let res = client
+        .post(format!("/api/v1/rooms/{src}/messages/{}/move", first["id"].as_str().unwrap()))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {key}")))
+        .header(Header::new("X-Member-Token", token))
+        .body(serde_json::json!({"target_room_id": dst}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    // Moving out of a private room into a public one would expose it
+    let res = client
+        .put(format!("/api/v1/rooms/{src}"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {key}")))
+        .body(r#"{"visibility": "private"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let (public, public_key) = create_test_room(&client, "move-priv-public");
+    let (status, _) = move_msg(
+        &client,
+        &src,
+        second["id"].as_str().unwrap(),
+        &key,
+        serde_json::json!({"target_room_id": public, "target_admin_key": public_key}),
+    );
+    assert_eq!(status, Status::Conflict);
+}
+
+fn fts_rows(client: &crate::common::TestClient, table: &str, id: &str) -> i64 {
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    conn.query_row(&format!("SELECT COUNT(*) FROM {table} WHERE message_id = ?1"), [id], |r| r.get(0))
+        .unwrap()
+}
+
+#[test]
+fn test_moved_message_is_reindexed_for_target_tokenizer() {
+    let client = test_client();
+    let (src, key) = create_test_room(&client, "move-fts-src");
+    let (dst, dst_key) = create_test_room(&client, "move-fts-dst");
+    let res = client
+        .put(format!("/api/v1/rooms/{dst}"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {dst_key}")))
+        .body(r#"{"language": "ja"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    let msg = post_message(&client, &src, json!({"sender": "agent", "content": "全文検索機能を改善しました"}));
+    let id = msg["id"].as_str().unwrap();
+    assert_eq!(fts_rows(&client, "messages_fts", id), 1);
+
+    let (status, _) = move_msg(&client, &src, id, &key, serde_json::json!({"target_room_id": dst, "target_admin_key": dst_key}));
+    assert_eq!(status, Status::Ok);
+    assert_eq!(fts_rows(&client, "messages_fts", id), 0);
+    assert_eq!(fts_rows(&client, "messages_fts_trigram", id), 1);
+
+    let res = client
+        .get(format!("/api/v1/search?q={}&room_id={dst}", urlencoding::encode("検索")))
+        .dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["results"].as_array().unwrap().len(), 1);
+}
@@ -0,0 +1,129 @@
+use crate::common::{create_test_room, post_message, test_client, TestClient};
+use rocket::http::{ContentType, Status};
+
+const REPORT: &str = "Status report\nbuild: passing\ntests: 41 failing\ndeploy: pending\nowner: forge";
+
+fn patch(client: &TestClient, room_id: &str, msg_id: &str, body: serde_json::Value) -> (Status, serde_json::Value) {
+    let res = client
+        .patch(format!("/api/v1/rooms/{room_id}/messages/{msg_id}"))
+        .header(ContentType::JSON)
+        .body(body.to_string())
+        .dispatch();
+    let status = res.status();
+    (status, res.into_json().unwrap_or(serde_json::Value::Null))
+}
+
+fn setup(client: &TestClient) -> (String, String) {
+    let (room_id, _) = create_test_room(client, "patch-edits");
+    let msg = post_message(
+        client,
+        &room_id,
+        serde_json::json!({"sender": "forge", "content": REPORT, "metadata": {"kind": "report"}}),
+    );
+    (room_id, msg["id"].as_str().unwrap().to_string())
+}
+
+#[test]
+fn test_patch_with_unified_diff() {
+    let client = test_client();
+    let (room_id, msg_id) = setup(&client);
+    let diff = "--- a\n+++ b\n@@ -2,3 +2,3 @@\n build: passing\n-tests: 41 failing\n+tests: all passing\n deploy: pending\n";
+    let (status, body) = patch(&client, &room_id, &msg_id, serde_json::json!({"sender": "forge", "diff": diff}));
+    assert_eq!(status, Status::Ok);
+    assert_eq!(body["content"], REPORT.replace("41 failing", "all passing"));
+    assert_eq!(body["edit_count"], 1);
+    assert_eq!(body["metadata"]["kind"], "report");
+
+    let res = client.get(format!("/api/v1/rooms/{room_id}/messages/{msg_id}/edits")).dispatch();
+    let history: serde_json::Value = res.into_json().unwrap();
+    let edit = &history["edits"][0];
+    assert_eq!(edit["previous_content"], REPORT);
+    assert_eq!(edit["patch_format"], "diff");
+    assert_eq!(edit["patch"], diff);
+}
+
+#[test]
+fn test_patch_diff_with_stale_line_numbers_still_applies() {
+    let client = test_client();
+    let (room_id, msg_id) = setup(&client);
+    // Wrong line number, but the context is unambiguous
+    let diff = "@@ -9,1 +9,1 @@\n-deploy: pending\n+deploy: done\n";
+    let (status, body) = patch(&client, &room_id, &msg_id, serde_json::json!({"sender": "forge", "diff": diff}));
+    assert_eq!(status, Status::Ok);
+    assert!(body["content"].as_str().unwrap().contains("deploy: done"));
+
+    // Context that isn't in the message is a conflict
+    let diff = "@@ -2,1 +2,1 @@\n-build: failing\n+build: fixed\n";
+    let (status, body) = patch(&client, &room_id, &msg_id, serde_json::json!({"sender": "forge", "diff": diff}));
+    assert_eq!(status, Status::Conflict);
+    assert!(body["error"].as_str().unwrap().contains("hunk 1"));
+}
+
+#[test]
+fn test_patch_with_json_patch() {
+    let client = test_client();
+    let (room_id, msg_id) = setup(&client);
+    let ops = serde_json::json!([
+        {"op": "test", "path": "/metadata/kind", "value": "report"},
+        {"op": "replace", "path": "/content", "value": "Status report\nall green"},
+        {"op": "add", "path": "/metadata/reviewed", "value": true}
+    ]);
+    let (status, body) = patch(&client, &room_id, &msg_id, serde_json::json!({"sender": "forge", "json_patch": ops}));
+    assert_eq!(status, Status::Ok);
+    assert_eq!(body["content"], "Status report\nall green");
+    assert_eq!(body["metadata"]["reviewed"], true);
+    assert_eq!(body["metadata"]["kind"], "report");
+
+    let res = client.get(format!("/api/v1/rooms/{room_id}/messages/{msg_id}/edits")).dispatch();
+    let history: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(history["edits"][0]["patch_format"], "json-patch");
+    let stored: serde_json::Value = serde_json::from_str(history["edits"][0]["patch"].as_str().unwrap()).unwrap();
+    assert_eq!(stored, ops);
+
+    // A failed test op rejects the whole patch
+    let ops = serde_json::json!([
+        {"op": "replace", "path": "/content", "value": "should not land"},
+        {"op": "test", "path": "/metadata/kind", "value": "memo"}
+    ]);
+    let (status, _) = patch(&client, &room_id, &msg_id, serde_json::json!({"sender": "forge", "json_patch": ops}));
+    assert_eq!(status, Status::Conflict);
+
+    // Content has to stay a string
+    let ops = serde_json::json!([{"op": "remove", "path": "/content"}]);
+    let (status, _) = patch(&client, &room_id, &msg_id, serde_json::json!({"sender": "forge", "json_patch": ops}));
+    assert_eq!(status, Status::UnprocessableEntity);
+
+    let ops = serde_json::json!([{"op": "frobnicate", "path": "/content"}]);
+    let (status, _) = patch(&client, &room_id, &msg_id, serde_json::json!({"sender": "forge", "json_patch": ops}));
+    assert_eq!(status, Status::BadRequest);
+
+    let res = client.get(format!("/api/v1/rooms/{room_id}/messages/{msg_id}/edits")).dispatch();
+    let history: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(history["current_content"], "Status report\nall green");
+    assert_eq!(history["edit_count"], 1);
+}
+
+#[test]
+fn test_patch_validation() {
+    let client = test_client();
+    let (room_id, msg_id) = setup(&client);
+    let diff = "@@ -5,1 +5,1 @@\n-owner: forge\n+owner: drift\n";
+
+    let (status, _) = patch(&client, &room_id, &msg_id, serde_json::json!({"sender": "drift", "diff": diff}));
+    assert_eq!(status, Status::Forbidden);
+    let (status, _) = patch(&client, &room_id, &msg_id, serde_json::json!({"sender": "forge"}));
+    assert_eq!(status, Status::BadRequest);
+    let (status, _) = patch(&client, &room_id, &msg_id, serde_json::json!({"sender": "forge", "diff": diff, "json_patch": []}));
+    assert_eq!(status, Status::BadRequest);
+    let (status, _) = patch(&client, &room_id, &msg_id, serde_json::json!({"sender": "forge", "diff": "not a diff"}));
+    assert_eq!(status, Status::BadRequest);
+    let (status, _) = patch(&client, &room_id, "missing", serde_json::json!({"sender": "forge", "diff": diff}));
+    assert_eq!(status, Status::NotFound);
+
+    // base_edit_count guards against concurrent edits
+    let (status, _) = patch(&client, &room_id, &msg_id, serde_json::json!({"sender": "forge", "diff": diff, "base_edit_count": 1}));
+    assert_eq!(status, Status::Conflict);
+    let (status, body) = patch(&client, &room_id, &msg_id, serde_json::json!({"sender": "forge", "diff": diff, "base_edit_count": 0}));
+    assert_eq!(status, Status::Ok);
+    assert!(body["content"].as_str().unwrap().ends_with("owner: drift"));
+}
@@ -0,0 +1,129 @@
+use crate::common::{create_test_room, test_client, TestClient};
+use rocket::http::Status;
+
+/// Seed `count` messages one hour apart, oldest first, bypassing the message rate limit.
+fn seed(client: &TestClient, room_id: &str, count: usize) {
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    let start = chrono::DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z").unwrap();
+    for i in 0..count {
+        conn.execute(
+            "INSERT INTO messages (id, room_id, sender, content, metadata, created_at, seq) VALUES (?1, ?2, ?3, ?4, '{}', ?5, (SELECT COALESCE(MAX(seq), 0) + 1 FROM messages))",
+            rusqlite::params![
+                uuid::Uuid::new_v4().to_string(),
+                room_id,
+                if i % 2 == 0 { "even" } else { "odd" },
+                format!("msg {i}"),
+                (start + chrono::Duration::hours(i as i64)).to_rfc3339(),
+            ],
+        )
+        .unwrap();
+    }
+}
+
+fn sample(client: &TestClient, room_id: &str, query: &str) -> serde_json::Value {
+    let res = client.get(format!("/api/v1/rooms/{room_id}/messages/sample{query}")).dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    res.into_json().unwrap()
+}
+
+fn contents(body: &serde_json::Value) -> Vec<String> {
+    body["messages"].as_array().unwrap().iter().map(|m| m["content"].as_str().unwrap().to_string()).collect()
+}
+
+#[test]
+fn test_sample_is_reproducible_with_seed() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "sample-repro");
+    seed(&client, &room_id, 200);
+
+    let a = sample(&client, &room_id, "?n=25&seed=42");
+    let b = sample(&client, &room_id, "?n=25&seed=42");
+    let c = sample(&client, &room_id, "?n=25&seed=43");
+    assert_eq!(a["seed"], 42);
+    assert_eq!(a["strategy"], "uniform");
+    assert_eq!(a["population"], 200);
+    assert_eq!(contents(&a).len(), 25);
+    assert_eq!(contents(&a), contents(&b));
+    assert_ne!(contents(&a), contents(&c));
+
+    // Returned in seq order, without duplicates
+    let seqs: Vec<i64> = a["messages"].as_array().unwrap().iter().map(|m| m["seq"].as_i64().unwrap()).collect();
+    assert!(seqs.windows(2).all(|w| w[0] < w[1]));
+}
+
+#[test]
+fn test_sample_without_seed_returns_one() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "sample-noseed");
+    seed(&client, &room_id, 50);
+
+    let first = sample(&client, &room_id, "?n=10");
+    let seed_value = first["seed"].as_u64().unwrap();
+    let again = sample(&client, &room_id, &format!("?n=10&seed={seed_value}"));
+    assert_eq!(contents(&first), contents(&again));
+}
+
+#[test]
+fn test_sample_smaller_room_than_n() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "sample-small");
+    seed(&client, &room_id, 7);
+
+    let body = sample(&client, &room_id, "?n=100&seed=1");
+    assert_eq!(body["population"], 7);
+    assert_eq!(contents(&body).len(), 7);
+
+    let (empty_room, _) = create_test_room(&client, "sample-empty");
+    let body = sample(&client, &empty_room, "");
+    assert_eq!(body["population"], 0);
+    assert!(body["messages"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn test_sample_sender_filter() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "sample-sender");
+    seed(&client, &room_id, 40);
+
+    let body = sample(&client, &room_id, "?n=10&seed=5&sender=odd");
+    assert_eq!(body["population"], 20);
+    assert!(body["messages"].as_array().unwrap().iter().all(|m| m["sender"] == "odd"));
+}
+
+#[test]
+fn test_sample_recent_weighted_prefers_new_messages() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "sample-recent");
+    seed(&client, &room_id, 400);
+
+    let mean_index = |body: &serde_json::Value| {
+        let idx: Vec<f64> = contents(body)
+            .iter()
+            .map(|c| c.trim_start_matches("msg ").parse::<f64>().unwrap())
+            .collect();
+        idx.iter().sum::<f64>() / idx.len() as f64
+    };
+    let uniform = sample(&client, &room_id, "?n=50&seed=9");
+    let recent = sample(&client, &room_id, "?n=50&seed=9&strategy=recent-weighted&half_life_hours=24");
+    assert_eq!(recent["strategy"], "recent-weighted");
+    assert_eq!(recent["half_life_hours"], 24.0);
+    assert!(uniform.get("half_life_hours").is_none());
+    assert_eq!(contents(&recent).len(), 50);
+    // Messages are an hour apart: a one-day half-life concentrates the sample in the last few days
+    assert!(mean_index(&recent) > 300.0, "recent mean {}", mean_index(&recent));
+    assert!(mean_index(&uniform) < mean_index(&recent));
+}
+
+#[test]
+fn test_sample_validation() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "sample-invalid");
+
+    for query in ["?n=0", "?n=1001", "?strategy=stratified", "?strategy=recent-weighted&half_life_hours=0"] {
+        let res = client.get(format!("/api/v1/rooms/{room_id}/messages/sample{query}")).dispatch();
+        assert_eq!(res.status(), Status::BadRequest, "{query}");
+    }
+
+    let res = client.get("/api/v1/rooms/nonexistent/messages/sample").dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+}
@@ -0,0 +1,225 @@
+use rocket::http::{ContentType, Header, Status};
+use rocket::local::blocking::Client;
+use serde_json::json;
+
+use crate::common::{create_test_room, policy_with_token, test_client, test_client_with_sender_policy};
+
+fn start(client: &Client, room_id: &str, sender: &str, content: &str) -> serde_json::Value {
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages/stream/start"))
+        .header(ContentType::JSON)
+        .body(json!({"sender": sender, "content": content, "sender_type": "agent"}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    res.into_json().unwrap()
+}
+
+fn append(client: &Client, room_id: &str, msg_id: &str, sender: &str, chunk: &str) -> Status {
+    client
+        .patch(format!("/api/v1/rooms/{room_id}/messages/stream/{msg_id}/append"))
+        .header(ContentType::JSON)
+        .body(json!({"sender": sender, "chunk": chunk}).to_string())
+        .dispatch()
+        .status()
+}
+
+#[test]
+fn stream_start_append_finalize() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "stream-compose");
+
+    let msg = start(&client, &room_id, "writer", "");
+    let msg_id = msg["id"].as_str().unwrap();
+    assert_eq!(msg["content"], "");
+
+    let res = client
+        .patch(format!("/api/v1/rooms/{room_id}/messages/stream/{msg_id}/append"))
+        .header(ContentType::JSON)
+        .body(json!({"sender": "writer", "chunk": "Hello, "}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let chunk: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(chunk["index"], 1);
+    assert_eq!(chunk["message_id"], msg_id);
+    assert_eq!(append(&client, &room_id, msg_id, "writer", "world"), Status::Ok);
+
+    // Partial content is visible while streaming
+    let msgs: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/rooms/{room_id}/messages"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(msgs.len(), 1);
+    assert_eq!(msgs[0]["content"], "Hello, world");
+
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages/stream/{msg_id}/finalize"))
+        .header(ContentType::JSON)
+        .body(json!({"sender": "writer"}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let finalized: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(finalized["content"], "Hello, world");
+    assert_eq!(finalized["seq"], msg["seq"]);
+
+    // Sealed: further appends conflict
+    assert_eq!(append(&client, &room_id, msg_id, "writer", "!"), Status::Conflict);
+
+    // Finalized content is searchable
+    let res: serde_json::Value = client
+        .get(format!("/api/v1/search?q=world&room_id={room_id}"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(res["count"], 1);
+}
+
+#[test]
+fn stream_append_only_by_original_sender() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "stream-sender");
+    let msg = start(&client, &room_id, "writer", "Hi");
+    let msg_id = msg["id"].as_str().unwrap();
+
+    assert_eq!(append(&client, &room_id, msg_id, "intruder", "x"), Status::Forbidden);
+    assert_eq!(append(&client, &room_id, msg_id, "writer", ""), Status::BadRequest);
+}
+
+#[test]
+fn stream_append_respects_content_limit() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "stream-limit");
+    let msg = start(&client, &room_id, "writer", &"a".repeat(9_995));
+    let msg_id = msg["id"].as_str().unwrap();
+
+    assert_eq!(append(&client, &room_id, msg_id, "writer", "bbbbb"), Status::Ok);
+    assert_eq!(append(&client, &room_id, msg_id, "writer", "c"), Status::BadRequest);
+}
+
+#[test]
+fn stream_errors_for_unknown_or_regular_messages() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "stream-errors");
+
+    assert_eq!(append(&client, &room_id, "nonexistent", "writer", "x"), Status::NotFound);
+
+    // A regular message was never streamed
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .body(json!({"sender": "writer", "content": "plain"}).to_string())
+        .dispatch();
+    let msg: serde_json::Value = res.into_json().unwrap();
+    let msg_id = msg["id"].as_str().unwrap();
+    assert_eq!(append(&client, &room_id, msg_id, "writer", "x"), Status::Conflict);
+
+    // Empty streams can't be finalized
+    let empty = start(&client, &room_id, "writer", "");
+    let res = client
+        .post(format!(
+            "/api/v1/rooms/{room_id}/messages/stream/{}/finalize",
+            empty["id"].as_str().unwrap()
+        ))
+        .header(ContentType::JSON)
+        .body(json!({"sender": "writer"}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+}
+
+#[test]
+fn lapsed_stream_is_finalized_or_removed_by_retention() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "stream-lease");
+
+    let kept = start(&client, &room_id, "writer", "");
+    let kept_id = kept["id"].as_str().unwrap();
+    assert_eq!(append(&client, &room_id, kept_id, "writer", "half a thought"), Status::Ok);
+    let empty = start(&client, &room_id, "writer", "");
+    let empty_id = empty["id"].as_str().unwrap();
+    let live = start(&client, &room_id, "writer", "still going");
+    let live_id = live["id"].as_str().unwrap();
+
+    // Placeholder and stream row are written together
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    let streams: i64 = conn
+        .query_row("SELECT COUNT(*) FROM message_streams WHERE lease_expires_at IS NOT NULL", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(streams, 3);
+
+    // The writer went away: lapse two of the leases
+    conn.execute(
+        "UPDATE message_streams SET lease_expires_at = '2000-01-01T00:00:00+00:00' WHERE message_id IN (?1, ?2)",
+        rusqlite::params![kept_id, empty_id],
+    )
+    .unwrap();
+
+    let res = client.post("/api/v1/admin/retention/run").dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["streams_finalized"], 1);
+    assert_eq!(body["streams_removed"], 1);
+
+    let msgs: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/rooms/{room_id}/messages"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let ids: Vec<&str> = msgs.iter().map(|m| m["id"].as_str().unwrap()).collect();
+    assert!(ids.contains(&kept_id) && ids.contains(&live_id));
+    assert!(!ids.contains(&empty_id));
+
+    // The finalized one is sealed and searchable; the live one still takes chunks
+    assert_eq!(append(&client, &room_id, kept_id, "writer", "more"), Status::Conflict);
+    assert_eq!(append(&client, &room_id, live_id, "writer", "..."), Status::Ok);
+    let found: serde_json::Value = client
+        .get("/api/v1/search?q=thought")
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert!(found.to_string().contains(kept_id));
+}
+
+#[test]
+fn stream_as_reserved_sender_needs_token_for_every_call() {
+    let client = test_client_with_sender_policy(policy_with_token());
+    let (room_id, _) = create_test_room(&client, "stream-reserved");
+
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages/stream/start"))
+        .header(ContentType::JSON)
+        .header(Header::new("X-Server-Token", "srv_secret"))
+        .body(json!({"sender": "admin", "content": ""}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let msg: serde_json::Value = res.into_json().unwrap();
+    let msg_id = msg["id"].as_str().unwrap();
+
+    // Knowing the stream id and sender name isn't enough to write as "admin"
+    assert_eq!(append(&client, &room_id, msg_id, "admin", "forged"), Status::Forbidden);
+    let res = client
+        .patch(format!("/api/v1/rooms/{room_id}/messages/stream/{msg_id}/append"))
+        .header(ContentType::JSON)
+        .header(Header::new("X-Server-Token", "srv_secret"))
+        .body(json!({"sender": "admin", "chunk": "Maintenance at noon"}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    let finalize = |token: Option<&str>| {
+        let mut req = client
+            .post(format!("/api/v1/rooms/{room_id}/messages/stream/{msg_id}/finalize"))
+            .header(ContentType::JSON)
+            .body(json!({"sender": "admin"}).to_string());
+        if let Some(token) = token {
+            req = req.header(Header::new("X-Server-Token", token.to_string()));
+        }
+        req.dispatch().status()
+    };
+    assert_eq!(finalize(None), Status::Forbidden);
+    assert_eq!(finalize(Some("srv_secret")), Status::Ok);
+
+    let msgs: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/rooms/{room_id}/messages"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(msgs[0]["content"], "Maintenance at noon");
+}
@@ -494,3 +494,65 @@ fn test_messages_returned_in_chronological_order() {
         assert_eq!(msg["content"], format!("Message {}", i + 1));
     }
 }
+
+#[test]
+fn test_send_message_with_client_id() {
+    let client = test_client();
+    let (room_id, _) = crate::common::create_test_room(&client, "client-id");
+    let id = "6F1C1D2E-8A57-4B2B-9C3E-3D0C7E1A2B44";
+
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .body(format!(r#"{{"id": "{id}", "sender": "agent", "content": "job 42 done"}}"#))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let msg: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(msg["id"], id.to_lowercase());
+
+    // Retrying the same send returns 409 with the original message, no duplicate
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .body(format!(r#"{{"id": "{id}", "sender": "agent", "content": "job 42 done"}}"#))
+        .dispatch();
+    assert_eq!(res.status(), Status::Conflict);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["message"]["id"], id.to_lowercase());
+    assert_eq!(body["message"]["seq"], msg["seq"]);
+
+    let res = client.get(format!("/api/v1/rooms/{room_id}/messages")).dispatch();
+    let msgs: Vec<serde_json::Value> = res.into_json().unwrap();
+    assert_eq!(msgs.len(), 1);
+}
+
+#[test]
+fn test_send_message_client_id_validation() {
+    let client = test_client();
+    let (room_a, _) = crate::common::create_test_room(&client, "client-id-a");
+    let (room_b, _) = crate::common::create_test_room(&client, "client-id-b");
+
+    let res = client
+        .post(format!("/api/v1/rooms/{room_a}/messages"))
+        .header(ContentType::JSON)
+        .body(r#"{"id": "job-42", "sender": "agent", "content": "hi"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+
+    let id = uuid::Uuid::new_v4().to_string();
+    client
+        .post(format!("/api/v1/rooms/{room_a}/messages"))
+        .header(ContentType::JSON)
+        .body(format!(r#"{{"id": "{id}", "sender": "agent", "content": "hi"}}"#))
+        .dispatch();
+
+    // Same id in another room conflicts but doesn't leak the other room's message
+    let res = client
+        .post(format!("/api/v1/rooms/{room_b}/messages"))
+        .header(ContentType::JSON)
+        .body(format!(r#"{{"id": "{id}", "sender": "agent", "content": "hi"}}"#))
+        .dispatch();
+    assert_eq!(res.status(), Status::Conflict);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert!(body["message"].is_null());
+}
@@ -0,0 +1,135 @@
+use crate::common::{admin_client, cleanup, temp_path, test_client};
+use local_agent_chat::db::{Db, DbConfig};
+use local_agent_chat::migrations;
+use rocket::http::{Header, Status};
+
+#[test]
+fn test_migration_status_lists_applied() {
+    let client = admin_client();
+    let res = client
+        .get("/api/v1/admin/migrations")
+        .header(Header::new("X-Server-Token", "srv_secret"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body: serde_json::Value = res.into_json().unwrap();
+    let latest = migrations::latest_version();
+    assert_eq!(body["current_version"], latest);
+    assert_eq!(body["latest_version"], latest);
+    let applied = body["applied"].as_array().unwrap();
+    assert_eq!(applied.len(), migrations::MIGRATIONS.len());
+    assert_eq!(applied[0]["version"], 1);
+    assert_eq!(applied[0]["name"], "sender_status");
+    assert!(applied.iter().all(|m| m["checksum_ok"] == true));
+    assert!(body["pending"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn test_migration_status_requires_server_token() {
+    let client = test_client();
+    let res = client.get("/api/v1/admin/migrations").dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+}
+
+#[test]
+fn test_reopen_is_idempotent() {
+    let path = temp_path();
+    drop(Db::with_config(&path, &DbConfig::default()));
+    let db = Db::with_config(&path, &DbConfig::default());
+    let conn = db.conn();
+    let rows: i64 = conn
+        .query_row("SELECT COUNT(*) FROM schema_version", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(rows, migrations::MIGRATIONS.len() as i64);
+    assert!(migrations::pending(&conn).unwrap().is_empty());
+    let general: i64 = conn
+        .query_row("SELECT COUNT(*) FROM rooms WHERE name = 'general'", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(general, 1);
+    drop(conn);
+    drop(db);
+    cleanup(&path);
+}
+
+#[test]
+fn test_pre_migration_database_is_upgraded() {
+    // A database created before versioned migrations: baseline tables, no schema_version
+    let path = temp_path();
+    drop(Db::with_config(&path, &DbConfig::default()));
+    {
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        conn.execute_batch("DROP TABLE schema_version; DROP TABLE locks;").unwrap();
+    }
+    let db = Db::with_config(&path, &DbConfig::default());
+    let conn = db.conn();
+    assert_eq!(migrations::current_version(&conn).unwrap(), migrations::latest_version());
+    conn.execute("INSERT INTO locks (name) VALUES ('x')", []).unwrap();
+    drop(conn);
+    drop(db);
+    cleanup(&path);
+}
+
+#[test]
+fn test_dry_run_applies_nothing() {
+    let path = temp_path();
+    let conn = rusqlite::Connection::open(&path).unwrap();
+    let would_apply = migrations::dry_run(&conn).unwrap();
+    assert_eq!(would_apply.len(), migrations::MIGRATIONS.len());
+    let tables: i64 = conn
+        .query_row("SELECT COUNT(*) FROM sqlite_master WHERE name IN ('schema_version', 'rooms', 'locks')", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(tables, 0);
+    drop(conn);
+    cleanup(&path);
+}
+
+#[test]
+fn test_newer_schema_refuses_to_start() {
+    let path = temp_path();
+    drop(Db::with_config(&path, &DbConfig::default()));
+    {
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        conn.execute(
+            "INSERT INTO schema_version (version, name, checksum, applied_at) VALUES (9999, 'from_the_future', '', '')",
+            [],
+        )
+        .unwrap();
+        let err = migrations::run(&conn).unwrap_err();
+        assert!(matches!(err, migrations::MigrationError::TooNew { current: 9999, .. }));
+        assert!(err.to_string().contains("newer than this build"));
+    }
+    let opened = std::panic::catch_unwind(|| Db::with_config(&path, &DbConfig::default()));
+    assert!(opened.is_err());
+    cleanup(&path);
+}
+
+#[test]
+fn test_mentions_backfilled_once() {
+    let path = temp_path();
+    drop(Db::with_config(&path, &DbConfig::default()));
+    {
+        // A database from before 0026: a message whose mention was never indexed
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        let room_id: String = conn.query_row("SELECT id FROM rooms LIMIT 1", [], |r| r.get(0)).unwrap();
+        conn.execute(
+            "INSERT INTO messages (id, room_id, sender, content, created_at, seq) VALUES ('m1', ?1, 'alice', 'hey @Bob', datetime('now'), 1)",
+            [&room_id],
+        )
+        .unwrap();
+        conn.execute_batch("DELETE FROM mentions; DELETE FROM schema_version WHERE version = 26;").unwrap();
+    }
+    let db = Db::with_config(&path, &DbConfig::default());
+    let target: String = db
+        .conn()
+        .query_row("SELECT target FROM mentions WHERE message_id = 'm1'", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(target, "bob");
+    db.conn().execute("DELETE FROM mentions", []).unwrap();
+    drop(db);
+
+    // Not rebuilt on every startup any more
+    let db = Db::with_config(&path, &DbConfig::default());
+    let count: i64 = db.conn().query_row("SELECT COUNT(*) FROM mentions", [], |r| r.get(0)).unwrap();
+    assert_eq!(count, 0);
+    drop(db);
+    cleanup(&path);
+}
@@ -0,0 +1,166 @@
+use crate::common::{create_test_room, test_client, test_client_with_namespaces, TestClient};
+use rocket::http::{ContentType, Header, Status};
+
+fn ns(name: &str) -> Header<'static> {
+    Header::new("X-Namespace", name.to_string())
+}
+
+fn create_room_in(client: &TestClient, namespace: &str, name: &str) -> String {
+    let res = client
+        .post("/api/v1/rooms")
+        .header(ns(namespace))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"name": name, "created_by": "tester"}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    res.into_json::<serde_json::Value>().unwrap()["id"].as_str().unwrap().to_string()
+}
+
+fn room_names(client: &TestClient, namespace: Option<&str>) -> Vec<String> {
+    let mut req = client.get("/api/v1/rooms");
+    if let Some(namespace) = namespace {
+        req = req.header(ns(namespace));
+    }
+    let res = req.dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let rooms: Vec<serde_json::Value> = res.into_json().unwrap();
+    let mut names: Vec<String> = rooms.iter().map(|r| r["name"].as_str().unwrap().to_string()).collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_namespaces_isolate_rooms() {
+    let client = test_client_with_namespaces(&["alpha", "beta"]);
+    create_test_room(&client, "default-only");
+    let alpha_room = create_room_in(&client, "alpha", "shared");
+    // Same name in another namespace is a different room
+    let beta_room = create_room_in(&client, "beta", "shared");
+    assert_ne!(alpha_room, beta_room);
+
+    assert_eq!(room_names(&client, None), vec!["default-only", "general"]);
+    assert_eq!(room_names(&client, Some("alpha")), vec!["general", "shared"]);
+    assert_eq!(room_names(&client, Some("beta")), vec!["general", "shared"]);
+
+    let res = client
+        .post(format!("/api/v1/rooms/{alpha_room}/messages"))
+        .header(ns("alpha"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "scout", "content": "alpha-only aurora report"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    // The room doesn't exist outside its namespace
+    let res = client.get(format!("/api/v1/rooms/{alpha_room}/messages")).header(ns("beta")).dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+    let res = client.get(format!("/api/v1/rooms/{alpha_room}/messages")).dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+
+    // Search only sees the namespace's messages
+    let res = client.get("/api/v1/search?q=aurora").header(ns("alpha")).dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["results"].as_array().unwrap().len(), 1);
+    let res = client.get("/api/v1/search?q=aurora").dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert!(body["results"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn test_namespaces_isolate_profiles() {
+    let client = test_client_with_namespaces(&["alpha"]);
+    let res = client
+        .put("/api/v1/profiles/scout")
+        .header(ns("alpha"))
+        .header(ContentType::JSON)
+        .body(r#"{"display_name": "Alpha Scout"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    let res = client.get("/api/v1/profiles/scout").header(ns("alpha")).dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let res = client.get("/api/v1/profiles/scout").dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+}
+
+#[test]
+fn test_namespace_path_prefix() {
+    let client = test_client_with_namespaces(&["alpha"]);
+    let room_id = create_room_in(&client, "alpha", "ops");
+
+    let res = client.get("/ns/alpha/api/v1/rooms").dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let rooms: Vec<serde_json::Value> = res.into_json().unwrap();
+    assert!(rooms.iter().any(|r| r["id"] == room_id.as_str()));
+
+    // Composes with by-name addressing, resolved inside the namespace
+    let res = client
+        .post("/ns/alpha/api/v1/rooms/by-name/ops/messages")
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "scout", "content": "via prefix"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["room_id"], room_id.as_str());
+}
+
+#[test]
+fn test_unknown_namespace_is_rejected() {
+    let client = test_client_with_namespaces(&["alpha"]);
+    let res = client.get("/api/v1/rooms").header(ns("gamma")).dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["error"], "Unknown namespace 'gamma'");
+
+    let res = client.get("/ns/gamma/api/v1/rooms").dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+
+    // Without configured namespaces the header is never accepted
+    let plain = test_client();
+    let res = plain.get("/api/v1/rooms").header(ns("alpha")).dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+}
+
+#[test]
+fn test_namespace_database_file() {
+    let client = test_client_with_namespaces(&["alpha"]);
+    create_room_in(&client, "alpha", "on-disk");
+    let path = format!("{}.alpha.db", client.db_path().trim_end_matches(".db"));
+    let conn = rusqlite::Connection::open(&path).unwrap();
+    let count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM rooms WHERE name = 'on-disk'", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(count, 1);
+}
+
+#[test]
+fn test_namespace_databases_ready_for_background_jobs() {
+    // Migrated at startup, before any request touches the namespace, so the per-database
+    // jobs (retention, redaction, ...) find their tables
+    let client = test_client_with_namespaces(&["alpha", "beta"]);
+    let path = format!("{}.beta.db", client.db_path().trim_end_matches(".db"));
+    let conn = rusqlite::Connection::open(&path).unwrap();
+    let tables: i64 = conn
+        .query_row("SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'messages'", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(tables, 1);
+
+    let namespaces = local_agent_chat::namespaces::Namespaces::new(
+        vec!["alpha".to_string(), "beta".to_string()],
+        client.db_path(),
+        &local_agent_chat::db::DbConfig::from_env(),
+    );
+    let databases = namespaces.databases();
+    let names: Vec<Option<&str>> = databases.iter().map(|(name, _)| name.as_deref()).collect();
+    assert_eq!(names, [None, Some("alpha"), Some("beta")]);
+    assert_eq!(databases[0].1, client.db_path());
+    assert_eq!(databases[2].1, path);
+}
+
+#[test]
+fn test_namespace_names_validated() {
+    assert!(local_agent_chat::namespaces::valid_name("team-a_1"));
+    assert!(!local_agent_chat::namespaces::valid_name(""));
+    assert!(!local_agent_chat::namespaces::valid_name("Team"));
+    assert!(!local_agent_chat::namespaces::valid_name("../etc"));
+    assert!(!local_agent_chat::namespaces::valid_name(&"a".repeat(33)));
+}
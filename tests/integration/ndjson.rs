@@ -0,0 +1,95 @@
+use rocket::http::{Accept, ContentType, MediaType, Status};
+use crate::common::{create_test_room, test_client};
+
+// --- NDJSON streaming ---
+
+fn ndjson() -> Accept {
+    Accept::from(MediaType::new("application", "x-ndjson"))
+}
+
+fn parse_lines(body: &str) -> Vec<serde_json::Value> {
+    body.lines().map(|l| serde_json::from_str(l).unwrap()).collect()
+}
+
+#[test]
+fn test_get_messages_ndjson_streams_full_history() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "ndjson-history");
+    // Seeded directly: more than the message rate limit allows through the API
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    for i in 0..620 {
+        conn.execute(
+            "INSERT INTO messages (id, room_id, sender, content, metadata, created_at, seq) VALUES (?1, ?2, 'logger', ?3, '{}', ?4, (SELECT COALESCE(MAX(seq), 0) + 1 FROM messages))",
+            rusqlite::params![
+                uuid::Uuid::new_v4().to_string(),
+                &room_id,
+                format!("line {i}"),
+                format!("2026-01-01T00:{:02}:{:02}Z", i / 60, i % 60),
+            ],
+        )
+        .unwrap();
+    }
+
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ndjson())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    assert_eq!(res.content_type().unwrap().to_string(), "application/x-ndjson");
+    let messages = parse_lines(&res.into_string().unwrap());
+    // Not capped at the 500-message JSON page size, and in seq order
+    assert_eq!(messages.len(), 620);
+    assert_eq!(messages[0]["content"], "line 0");
+    assert_eq!(messages[619]["content"], "line 619");
+    assert!(messages.windows(2).all(|w| w[0]["seq"].as_i64() < w[1]["seq"].as_i64()));
+
+    // Filters, ?latest and ?fields carry over
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/messages?latest=3&fields=content"))
+        .header(ndjson())
+        .dispatch();
+    let messages = parse_lines(&res.into_string().unwrap());
+    assert_eq!(
+        messages,
+        vec![
+            serde_json::json!({"content": "line 617"}),
+            serde_json::json!({"content": "line 618"}),
+            serde_json::json!({"content": "line 619"}),
+        ]
+    );
+
+    // Plain JSON is unchanged
+    let res = client.get(format!("/api/v1/rooms/{room_id}/messages")).dispatch();
+    assert_eq!(res.content_type(), Some(ContentType::JSON));
+    let page: Vec<serde_json::Value> = res.into_json().unwrap();
+    assert_eq!(page.len(), 50);
+}
+
+#[test]
+fn test_export_ndjson() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "ndjson-export");
+    for sender in ["alpha", "beta", "alpha"] {
+        client
+            .post(format!("/api/v1/rooms/{room_id}/messages"))
+            .header(ContentType::JSON)
+            .body(format!(r#"{{"sender": "{sender}", "content": "hi from {sender}"}}"#))
+            .dispatch();
+    }
+
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/export?sender=alpha"))
+        .header(ndjson())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    assert!(res.headers().get_one("Content-Disposition").unwrap().contains("chat-export.ndjson"));
+    let messages = parse_lines(&res.into_string().unwrap());
+    assert_eq!(messages.len(), 2);
+    assert!(messages.iter().all(|m| m["sender"] == "alpha"));
+
+    let res = client
+        .get("/api/v1/rooms/00000000-0000-0000-0000-000000000000/export")
+        .header(ndjson())
+        .dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+}
@@ -621,3 +621,142 @@ fn test_latest_ignored_when_after_also_set() {
     assert_eq!(msgs.len(), 4);
     assert_eq!(msgs[0]["content"], "msg 2");
 }
+
+// --- Opt-in list envelope ---
+
+#[test]
+fn test_messages_envelope_forward_paging() {
+    let client = test_client();
+    let (room_id, _) = crate::common::create_test_room(&client, "envelope-fwd");
+    for i in 0..5 {
+        client
+            .post(format!("/api/v1/rooms/{room_id}/messages"))
+            .header(ContentType::JSON)
+            .body(format!(r#"{{"sender": "bot", "content": "msg {i}"}}"#))
+            .dispatch();
+    }
+
+    // Page 1 of 2-at-a-time
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/messages?after=0&limit=2&envelope=true"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let page: serde_json::Value = res.into_json().unwrap();
+    let items = page["items"].as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["content"], "msg 0");
+    assert_eq!(page["has_more"], true);
+    assert_eq!(page["next_cursor"], items[1]["seq"]);
+
+    // Follow cursors until exhausted
+    let mut cursor = page["next_cursor"].as_i64().unwrap();
+    let mut seen = 2;
+    loop {
+        let page: serde_json::Value = client
+            .get(format!("/api/v1/rooms/{room_id}/messages?after={cursor}&limit=2&envelope=true"))
+            .dispatch()
+            .into_json()
+            .unwrap();
+        seen += page["items"].as_array().unwrap().len();
+        if page["has_more"] == false {
+            assert!(page["next_cursor"].is_null());
+            break;
+        }
+        cursor = page["next_cursor"].as_i64().unwrap();
+    }
+    assert_eq!(seen, 5);
+}
+
+#[test]
+fn test_messages_envelope_backward_paging_with_latest() {
+    let client = test_client();
+    let (room_id, _) = crate::common::create_test_room(&client, "envelope-back");
+    for i in 0..3 {
+        client
+            .post(format!("/api/v1/rooms/{room_id}/messages"))
+            .header(ContentType::JSON)
+            .body(format!(r#"{{"sender": "bot", "content": "msg {i}"}}"#))
+            .dispatch();
+    }
+
+    let page: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/messages?latest=2&envelope=true"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let items = page["items"].as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["content"], "msg 1");
+    assert_eq!(page["has_more"], true);
+    // Backward cursor is the oldest returned seq, to be used as before_seq
+    assert_eq!(page["next_cursor"], items[0]["seq"]);
+
+    let cursor = page["next_cursor"].as_i64().unwrap();
+    let page: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/messages?before_seq={cursor}&limit=2&envelope=true"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(page["items"].as_array().unwrap().len(), 1);
+    assert_eq!(page["items"][0]["content"], "msg 0");
+    assert_eq!(page["has_more"], false);
+}
+
+#[test]
+fn test_messages_without_envelope_stays_array() {
+    let client = test_client();
+    let (room_id, _) = crate::common::create_test_room(&client, "envelope-off");
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/messages?envelope=false"))
+        .dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert!(body.is_array());
+}
+
+#[test]
+fn test_envelope_on_unpaged_lists_and_search() {
+    let client = test_client();
+    let (room_id, admin_key) = crate::common::create_test_room(&client, "envelope-lists");
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "bot", "content": "envelopes are handy"}"#)
+        .dispatch();
+    let msg: serde_json::Value = res.into_json().unwrap();
+    let msg_id = msg["id"].as_str().unwrap();
+    client
+        .post(format!("/api/v1/rooms/{room_id}/messages/{msg_id}/pin"))
+        .header(rocket::http::Header::new("X-Admin-Key", admin_key.clone()))
+        .dispatch();
+
+    let admin = rocket::http::Header::new("X-Admin-Key", admin_key);
+    for url in [
+        format!("/api/v1/rooms/{room_id}/files?envelope=true"),
+        format!("/api/v1/rooms/{room_id}/pins?envelope=true"),
+        "/api/v1/profiles?envelope=true".to_string(),
+        format!("/api/v1/rooms/{room_id}/webhooks?envelope=true"),
+    ] {
+        let res = client.get(url.clone()).header(admin.clone()).dispatch();
+        assert_eq!(res.status(), Status::Ok, "{url}");
+        let body: serde_json::Value = res.into_json().unwrap();
+        assert!(body["items"].is_array(), "{url}");
+        assert_eq!(body["has_more"], false, "{url}");
+        assert!(body["next_cursor"].is_null(), "{url}");
+    }
+
+    let pins: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/pins?envelope=true"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(pins["items"].as_array().unwrap().len(), 1);
+
+    let search: serde_json::Value = client
+        .get(format!("/api/v1/search?q=envelopes&room_id={room_id}&envelope=true"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(search["items"].as_array().unwrap().len(), 1);
+    assert_eq!(search["has_more"], false);
+    assert!(search.get("results").is_none());
+}
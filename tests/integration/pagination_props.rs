@@ -0,0 +1,205 @@
+use proptest::prelude::*;
+use rocket::http::{ContentType, Status};
+use rocket::local::blocking::Client;
+use local_agent_chat::rate_limit::RateLimitConfig;
+use crate::common::{create_test_room, test_client_with_rate_limits};
+
+// --- Property tests: cursor invariants over randomized histories ---
+//
+// Each case posts a random interleaving of messages into two rooms (so per-room seqs have gaps),
+// deletes a random subset, then checks that every way of paging through the target room returns
+// exactly the surviving messages — none lost, none duplicated, always in seq order.
+
+const SENDERS: [&str; 3] = ["alpha", "beta", "gamma"];
+
+/// One posted message: (goes to target room?, sender index, delete afterwards?)
+type Step = (bool, usize, bool);
+
+struct Posted {
+    seq: i64,
+    sender: &'static str,
+    created_at: String,
+}
+
+fn history() -> impl Strategy<Value = Vec<Step>> {
+    prop::collection::vec((any::<bool>(), 0..SENDERS.len(), prop::bool::weighted(0.2)), 0..40)
+}
+
+/// Post the history and return the surviving messages of the target room, in seq order.
+fn build(client: &Client, steps: &[Step]) -> (String, Vec<Posted>) {
+    let (room, _) = create_test_room(client, "props");
+    let (other, _) = create_test_room(client, "props-other");
+    let mut kept = Vec::new();
+    for (i, &(in_target, sender_idx, delete)) in steps.iter().enumerate() {
+        let sender = SENDERS[sender_idx];
+        let room_id = if in_target { &room } else { &other };
+        let res = client
+            .post(format!("/api/v1/rooms/{room_id}/messages"))
+            .header(ContentType::JSON)
+            .body(format!(r#"{{"sender": "{sender}", "content": "m{i}"}}"#))
+            .dispatch();
+        assert_eq!(res.status(), Status::Ok);
+        let msg: serde_json::Value = res.into_json().unwrap();
+        if !in_target {
+            continue;
+        }
+        if delete {
+            let id = msg["id"].as_str().unwrap();
+            let res = client
+                .delete(format!("/api/v1/rooms/{room}/messages/{id}?sender={sender}"))
+                .dispatch();
+            assert_eq!(res.status(), Status::Ok);
+            continue;
+        }
+        kept.push(Posted {
+            seq: msg["seq"].as_i64().unwrap(),
+            sender,
+            created_at: msg["created_at"].as_str().unwrap().to_string(),
+        });
+    }
+    (room, kept)
+}
+
+fn seqs(client: &Client, url: &str) -> Vec<i64> {
+    let res = client.get(url).dispatch();
+    assert_eq!(res.status(), Status::Ok, "GET {url}");
+    let msgs: Vec<serde_json::Value> = res.into_json().unwrap();
+    msgs.iter().map(|m| m["seq"].as_i64().unwrap()).collect()
+}
+
+fn props_client() -> crate::common::TestClient {
+    test_client_with_rate_limits(RateLimitConfig {
+        messages_max: 10_000,
+        ..Default::default()
+    })
+}
+
+fn assert_strictly_increasing(seqs: &[i64]) {
+    assert!(seqs.windows(2).all(|w| w[0] < w[1]), "not in seq order: {seqs:?}");
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(24))]
+
+    #[test]
+    fn prop_after_cursor_pages_cover_history(steps in history(), limit in 1i64..12, filter in prop::option::of(0..SENDERS.len())) {
+        let client = props_client();
+        let (room, kept) = build(&client, &steps);
+        let sender = filter.map(|i| SENDERS[i]);
+        let expected: Vec<i64> = kept.iter().filter(|m| sender.is_none_or(|s| m.sender == s)).map(|m| m.seq).collect();
+        let sender_q = sender.map(|s| format!("&sender={s}")).unwrap_or_default();
+
+        let mut collected = Vec::new();
+        let mut cursor = 0;
+        loop {
+            let page = seqs(&client, &format!("/api/v1/rooms/{room}/messages?after={cursor}&limit={limit}{sender_q}"));
+            prop_assert!(page.len() as i64 <= limit);
+            if page.is_empty() {
+                break;
+            }
+            cursor = *page.last().unwrap();
+            collected.extend(page);
+        }
+        assert_strictly_increasing(&collected);
+        prop_assert_eq!(collected, expected);
+    }
+
+    #[test]
+    fn prop_before_seq_pages_cover_history(steps in history(), limit in 1i64..12) {
+        let client = props_client();
+        let (room, kept) = build(&client, &steps);
+        let expected: Vec<i64> = kept.iter().map(|m| m.seq).collect();
+
+        let mut pages: Vec<Vec<i64>> = Vec::new();
+        let mut cursor = i64::MAX;
+        loop {
+            let page = seqs(&client, &format!("/api/v1/rooms/{room}/messages?before_seq={cursor}&limit={limit}"));
+            prop_assert!(page.len() as i64 <= limit);
+            if page.is_empty() {
+                break;
+            }
+            assert_strictly_increasing(&page);
+            cursor = page[0];
+            pages.push(page);
+        }
+        // Pages come newest-first; each page is itself chronological
+        let collected: Vec<i64> = pages.into_iter().rev().flatten().collect();
+        prop_assert_eq!(collected, expected);
+    }
+
+    #[test]
+    fn prop_envelope_cursor_matches_has_more(steps in history(), limit in 1i64..12, backwards in any::<bool>()) {
+        let client = props_client();
+        let (room, kept) = build(&client, &steps);
+        let expected: Vec<i64> = kept.iter().map(|m| m.seq).collect();
+
+        let mut collected: Vec<Vec<i64>> = Vec::new();
+        let mut cursor: Option<i64> = None;
+        loop {
+            let url = match (backwards, cursor) {
+                (false, c) => format!("/api/v1/rooms/{room}/messages?envelope=true&limit={limit}&after={}", c.unwrap_or(0)),
+                (true, None) => format!("/api/v1/rooms/{room}/messages?envelope=true&latest={limit}"),
+                (true, Some(c)) => format!("/api/v1/rooms/{room}/messages?envelope=true&limit={limit}&before_seq={c}"),
+            };
+            let body: serde_json::Value = client.get(&url).dispatch().into_json().unwrap();
+            let page: Vec<i64> = body["items"].as_array().unwrap().iter().map(|m| m["seq"].as_i64().unwrap()).collect();
+            let has_more = body["has_more"].as_bool().unwrap();
+            prop_assert_eq!(has_more, body["next_cursor"].is_i64(), "next_cursor present iff has_more");
+            collected.push(page);
+            if !has_more {
+                break;
+            }
+            cursor = body["next_cursor"].as_i64();
+        }
+        if backwards {
+            collected.reverse();
+        }
+        let collected: Vec<i64> = collected.into_iter().flatten().collect();
+        prop_assert_eq!(collected, expected);
+    }
+
+    #[test]
+    fn prop_latest_is_tail_of_history(steps in history(), n in 1i64..50) {
+        let client = props_client();
+        let (room, kept) = build(&client, &steps);
+        let all: Vec<i64> = kept.iter().map(|m| m.seq).collect();
+        let expected = all[all.len().saturating_sub(n as usize)..].to_vec();
+        prop_assert_eq!(seqs(&client, &format!("/api/v1/rooms/{room}/messages?latest={n}")), expected);
+    }
+
+    #[test]
+    fn prop_after_and_before_seq_window(steps in history(), a in 0usize..40, b in 0usize..40) {
+        let client = props_client();
+        let (room, kept) = build(&client, &steps);
+        prop_assume!(!kept.is_empty());
+        // Bounds drawn from real seqs (plus the off-by-one neighbours) to hit the edges
+        let lo = kept[a % kept.len()].seq - (a as i64 % 2);
+        let hi = kept[b % kept.len()].seq + (b as i64 % 2);
+        let expected: Vec<i64> = kept.iter().map(|m| m.seq).filter(|&s| s > lo && s < hi).collect();
+        let got = seqs(&client, &format!("/api/v1/rooms/{room}/messages?after={lo}&before_seq={hi}&limit=500"));
+        prop_assert_eq!(got, expected);
+    }
+
+    #[test]
+    fn prop_since_is_strictly_after_timestamp(steps in history(), pick in 0usize..40, limit in 1i64..12) {
+        let client = props_client();
+        let (room, kept) = build(&client, &steps);
+        prop_assume!(!kept.is_empty());
+        let since = kept[pick % kept.len()].created_at.clone();
+        let expected: Vec<i64> = kept.iter().filter(|m| m.created_at > since).map(|m| m.seq).collect();
+        let since_q = urlencoding::encode(&since);
+
+        // since composes with the seq cursor without losing or repeating messages
+        let mut collected = Vec::new();
+        let mut cursor = 0;
+        loop {
+            let page = seqs(&client, &format!("/api/v1/rooms/{room}/messages?since={since_q}&after={cursor}&limit={limit}"));
+            if page.is_empty() {
+                break;
+            }
+            cursor = *page.last().unwrap();
+            collected.extend(page);
+        }
+        prop_assert_eq!(collected, expected);
+    }
+}
@@ -578,3 +578,87 @@ fn test_search_cross_room() {
     assert!(room_names.contains(&"search-cross-1"));
     assert!(room_names.contains(&"search-cross-2"));
 }
+
+// --- Participant Roles ---
+
+#[test]
+fn test_participant_roles_and_join_date() {
+    let client = test_client();
+    let res = client
+        .post("/api/v1/rooms")
+        .header(ContentType::JSON)
+        .body(r#"{"name": "roles-room", "created_by": "Alice"}"#)
+        .dispatch();
+    let room: serde_json::Value = res.into_json().unwrap();
+    let room_id = room["id"].as_str().unwrap();
+    let admin_key = room["admin_key"].as_str().unwrap();
+
+    for sender in ["Alice", "Bob", "Carol"] {
+        client
+            .post(format!("/api/v1/rooms/{room_id}/messages"))
+            .header(ContentType::JSON)
+            .body(format!(r#"{{"sender": "{sender}", "content": "hi"}}"#))
+            .dispatch();
+    }
+
+    // Granting needs the room's admin key and a known role
+    let res = client
+        .put(format!("/api/v1/rooms/{room_id}/roles/Bob"))
+        .header(ContentType::JSON)
+        .header(rocket::http::Header::new("Authorization", "Bearer wrong"))
+        .body(r#"{"role": "moderator"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+    let res = client
+        .put(format!("/api/v1/rooms/{room_id}/roles/Bob"))
+        .header(ContentType::JSON)
+        .header(rocket::http::Header::new("Authorization", format!("Bearer {admin_key}")))
+        .body(r#"{"role": "janitor"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["valid_roles"], serde_json::json!(["owner", "moderator", "member", "guest"]));
+
+    let res = client
+        .put(format!("/api/v1/rooms/{room_id}/roles/Bob"))
+        .header(ContentType::JSON)
+        .header(rocket::http::Header::new("Authorization", format!("Bearer {admin_key}")))
+        .body(r#"{"role": "moderator", "granted_by": "Alice"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let granted: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(granted["role"], "moderator");
+    assert_eq!(granted["granted_by"], "Alice");
+
+    let res = client.get(format!("/api/v1/rooms/{room_id}/participants")).dispatch();
+    let participants: Vec<serde_json::Value> = res.into_json().unwrap();
+    let role_of = |name: &str| {
+        let p = participants.iter().find(|p| p["sender"] == name).unwrap();
+        assert!(p["joined_at"].as_str().is_some_and(|j| j <= p["first_seen"].as_str().unwrap()));
+        p["role"].as_str().unwrap().to_string()
+    };
+    assert_eq!(role_of("Alice"), "owner");
+    assert_eq!(role_of("Bob"), "moderator");
+    assert_eq!(role_of("Carol"), "member");
+
+    let roles: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/rooms/{room_id}/roles"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(roles.len(), 1);
+
+    // Revoking falls back to member
+    let res = client
+        .delete(format!("/api/v1/rooms/{room_id}/roles/Bob"))
+        .header(rocket::http::Header::new("Authorization", format!("Bearer {admin_key}")))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let participants: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/rooms/{room_id}/participants"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let bob = participants.iter().find(|p| p["sender"] == "Bob").unwrap();
+    assert_eq!(bob["role"], "member");
+}
@@ -0,0 +1,99 @@
+use crate::common::{create_test_room, post_message, test_client};
+use rocket::http::{ContentType, Status};
+
+// --- Requires-response tracking ---
+
+fn pending(client: &crate::common::TestClient, query: &str) -> serde_json::Value {
+    let res = client.get(format!("/api/v1/pending-responses?{query}")).dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    res.into_json().unwrap()
+}
+
+#[test]
+fn test_pending_responses_cleared_by_thread_reply() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "needs-answers");
+    let ask = post_message(
+        &client,
+        &room_id,
+        serde_json::json!({"sender": "agent-a", "content": "Can you both confirm?", "requires_response_from": ["agent-b", "agent-c", "agent-a"]}),
+    );
+    let ask_id = ask["id"].as_str().unwrap();
+    assert_eq!(ask["metadata"]["requires_response"]["from"], serde_json::json!(["agent-b", "agent-c"]));
+    assert!(ask["metadata"]["requires_response"]["due_at"].is_string());
+
+    let body = pending(&client, "sender=agent-b");
+    assert_eq!(body["count"], 1);
+    assert_eq!(body["pending"][0]["message_id"], ask_id);
+    assert_eq!(body["pending"][0]["requested_by"], "agent-a");
+    assert_eq!(body["pending"][0]["overdue"], false);
+    assert_eq!(pending(&client, "requested_by=agent-a")["count"], 2);
+
+    // A reply outside the thread doesn't count; one deeper in the thread does
+    post_message(&client, &room_id, serde_json::json!({"sender": "agent-b", "content": "unrelated"}));
+    assert_eq!(pending(&client, "sender=agent-b")["count"], 1);
+    let followup = post_message(
+        &client,
+        &room_id,
+        serde_json::json!({"sender": "agent-c", "content": "which build?", "reply_to": ask_id}),
+    );
+    post_message(
+        &client,
+        &room_id,
+        serde_json::json!({"sender": "agent-b", "content": "confirmed", "reply_to": followup["id"]}),
+    );
+    assert_eq!(pending(&client, "sender=agent-b")["count"], 0);
+    // agent-c's question was a reply too, so it answered as well
+    assert_eq!(pending(&client, "requested_by=agent-a")["count"], 0);
+}
+
+#[test]
+fn test_pending_responses_escalate_once() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "needs-answers-late");
+    let ask = post_message(
+        &client,
+        &room_id,
+        serde_json::json!({"sender": "agent-a", "content": "Status?", "requires_response_from": ["agent-b"], "response_timeout_secs": 60}),
+    );
+
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    assert!(local_agent_chat::responses::escalate_due(&conn).is_empty());
+    conn.execute(
+        "UPDATE pending_responses SET due_at = ?1",
+        [(chrono::Utc::now() - chrono::Duration::minutes(1)).to_rfc3339()],
+    )
+    .unwrap();
+
+    let escalated = local_agent_chat::responses::escalate_due(&conn);
+    assert_eq!(escalated.len(), 1);
+    assert_eq!(escalated[0].message_id, ask["id"].as_str().unwrap());
+    assert_eq!(escalated[0].responder, "agent-b");
+    assert!(escalated[0].escalated_at.is_some());
+    assert!(local_agent_chat::responses::escalate_due(&conn).is_empty());
+
+    // Still listed until answered, now overdue
+    let body = pending(&client, "sender=agent-b");
+    assert_eq!(body["pending"][0]["overdue"], true);
+    assert!(body["pending"][0]["escalated_at"].is_string());
+}
+
+#[test]
+fn test_pending_responses_validation() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "needs-answers-invalid");
+    for body in [
+        r#"{"sender": "a", "content": "x", "response_timeout_secs": 600}"#,
+        r#"{"sender": "a", "content": "x", "requires_response_from": ["b"], "response_timeout_secs": 5}"#,
+        r#"{"sender": "a", "content": "x", "requires_response_from": [" "]}"#,
+    ] {
+        let res = client
+            .post(format!("/api/v1/rooms/{room_id}/messages"))
+            .header(ContentType::JSON)
+            .body(body)
+            .dispatch();
+        assert_eq!(res.status(), Status::BadRequest, "{body}");
+    }
+    let res = client.get("/api/v1/pending-responses").dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+}
@@ -283,3 +283,78 @@ fn test_pin_repin_after_unpin() {
     let body: serde_json::Value = res.into_json().unwrap();
     assert!(body["pinned_at"].as_str().is_some());
 }
+
+#[test]
+fn test_pin_history_records_pins_and_unpins() {
+    let client = test_client();
+    let (room_id, admin_key) = create_room_with_key(&client, "pin-history-test");
+    let msg_id = send_msg(&client, &room_id, "alice", "Runbook: https://example.com/runbook");
+
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages/{msg_id}/pin?by=carol"))
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let res = client
+        .delete(format!("/api/v1/rooms/{room_id}/messages/{msg_id}/pin"))
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    // Newest first; the unpinned link is still there
+    let res = client.get(format!("/api/v1/rooms/{room_id}/pins/history")).dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let history: Vec<serde_json::Value> = res.into_json().unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0]["action"], "unpinned");
+    assert_eq!(history[0]["actor"], "admin");
+    assert_eq!(history[1]["action"], "pinned");
+    assert_eq!(history[1]["actor"], "carol");
+    assert_eq!(history[1]["message_id"], msg_id);
+    assert_eq!(history[1]["content"], "Runbook: https://example.com/runbook");
+    assert_eq!(history[1]["pinned"], false);
+    assert!(history[1]["at"].as_str().is_some());
+
+    // Paging by id
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/pins/history?limit=1&envelope=true"))
+        .dispatch();
+    let page: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(page["items"].as_array().unwrap().len(), 1);
+    assert_eq!(page["has_more"], true);
+    let cursor = page["next_cursor"].as_i64().unwrap();
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/pins/history?before={cursor}&envelope=true"))
+        .dispatch();
+    let page: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(page["items"][0]["action"], "pinned");
+    assert_eq!(page["has_more"], false);
+
+    let res = client.get("/api/v1/rooms/nonexistent/pins/history").dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+}
+
+#[test]
+fn test_pin_history_survives_message_deletion() {
+    let client = test_client();
+    let (room_id, admin_key) = create_room_with_key(&client, "pin-history-delete-test");
+    let msg_id = send_msg(&client, &room_id, "alice", "Soon gone");
+
+    client
+        .post(format!("/api/v1/rooms/{room_id}/messages/{msg_id}/pin"))
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .dispatch();
+    let res = client
+        .delete(format!("/api/v1/rooms/{room_id}/messages/{msg_id}"))
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    let res = client.get(format!("/api/v1/rooms/{room_id}/pins/history")).dispatch();
+    let history: Vec<serde_json::Value> = res.into_json().unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0]["sender"], "alice");
+    assert_eq!(history[0]["content"], "Soon gone");
+    assert!(history[0]["seq"].is_null());
+    assert_eq!(history[0]["pinned"], false);
+}
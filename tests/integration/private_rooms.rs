@@ -0,0 +1,330 @@
+use crate::common::{create_test_room, post_message, test_client};
+use rocket::http::{ContentType, Header, Status};
+use rocket::local::blocking::Client;
+use serde_json::json;
+
+fn create_private_room(client: &Client, name: &str) -> (String, String) {
+    let res = client
+        .post("/api/v1/rooms")
+        .header(ContentType::JSON)
+        .body(json!({"name": name, "created_by": "lead", "visibility": "private"}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["visibility"], "private");
+    (body["id"].as_str().unwrap().to_string(), body["admin_key"].as_str().unwrap().to_string())
+}
+
+fn add_member(client: &Client, room_id: &str, admin_key: &str, sender: &str) -> String {
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/members"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .body(json!({"sender": sender, "added_by": "lead"}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["sender"], sender);
+    body["token"].as_str().unwrap().to_string()
+}
+
+fn room_names(client: &Client, token: Option<&str>) -> Vec<String> {
+    let mut req = client.get("/api/v1/rooms");
+    if let Some(token) = token {
+        req = req.header(Header::new("X-Member-Token", token.to_string()));
+    }
+    let rooms: Vec<serde_json::Value> = req.dispatch().into_json().unwrap();
+    rooms.iter().map(|r| r["name"].as_str().unwrap().to_string()).collect()
+}
+
+#[test]
+fn test_private_room_admits_only_members() {
+    let client = test_client();
+    let (room_id, admin_key) = create_private_room(&client, "team-red");
+    let messages = format!("/api/v1/rooms/{room_id}/messages");
+
+    // Outsiders can neither read nor post
+    let res = client.get(messages.as_str()).dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["visibility"], "private");
+    let res = client
+        .post(messages.as_str())
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "spy", "content": "hi"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+    assert!(!room_names(&client, None).contains(&"team-red".to_string()));
+
+    let token = add_member(&client, &room_id, &admin_key, "agent-a");
+    assert!(token.starts_with("mem_"));
+    // Adding again hands back the same membership
+    assert_eq!(add_member(&client, &room_id, &admin_key, "agent-a"), token);
+
+    let res = client
+        .post(messages.as_str())
+        .header(ContentType::JSON)
+        .header(Header::new("X-Member-Token", token.clone()))
+        .body(r#"{"sender": "agent-a", "content": "plan stays in here"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let res = client.get(format!("{messages}?member_token={token}")).dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let history: Vec<serde_json::Value> = res.into_json().unwrap();
+    assert!(history.iter().any(|m| m["content"] == "plan stays in here"));
+    assert!(room_names(&client, Some(&token)).contains(&"team-red".to_string()));
+
+    // Members see who else is in, but not their tokens
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/members"))
+        .header(Header::new("X-Member-Token", token.clone()))
+        .dispatch();
+    let members: Vec<serde_json::Value> = res.into_json().unwrap();
+    assert_eq!(members.len(), 1);
+    assert!(members[0].get("token").is_none());
+
+    // A removed member's token stops working
+    let res = client
+        .delete(format!("/api/v1/rooms/{room_id}/members/agent-a"))
+        .header(Header::new("X-Admin-Key", admin_key.clone()))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let res = client
+        .get(messages.as_str())
+        .header(Header::new("X-Member-Token", token))
+        .dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+
+    // The admin key always gets in; a wrong one reads as a wrong admin key
+    let res = client
+        .get(messages.as_str())
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let res = client
+        .get(messages.as_str())
+        .header(Header::new("Authorization", "Bearer chat_guess"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["error"], "Invalid admin key for this room");
+}
+
+#[test]
+fn test_private_rooms_stay_out_of_cross_room_reads() {
+    let client = test_client();
+    let (room_id, admin_key) = create_test_room(&client, "team-blue");
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "agent-b", "content": "blueprint rollout"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let token = add_member(&client, &room_id, &admin_key, "agent-b");
+
+    let found = |client: &Client, token: Option<&str>| -> (usize, usize) {
+        let mut search = client.get("/api/v1/search?q=blueprint");
+        let mut activity = client.get("/api/v1/activity");
+        if let Some(token) = token {
+            search = search.header(Header::new("X-Member-Token", token.to_string()));
+            activity = activity.header(Header::new("X-Member-Token", token.to_string()));
+        }
+        let search: serde_json::Value = search.dispatch().into_json().unwrap();
+        let activity: serde_json::Value = activity.dispatch().into_json().unwrap();
+        (search["count"].as_u64().unwrap() as usize, activity["count"].as_u64().unwrap() as usize)
+    };
+    assert_eq!(found(&client, None), (1, 1));
+
+    let res = client
+        .put(format!("/api/v1/rooms/{room_id}"))
+        .header(ContentType::JSON)
+        .header(Header::new("X-Admin-Key", admin_key.clone()))
+        .body(r#"{"visibility": "private"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["visibility"], "private");
+
+    assert_eq!(found(&client, None), (0, 0));
+    assert_eq!(found(&client, Some(&token)), (1, 1));
+
+    let res = client
+        .put(format!("/api/v1/rooms/{room_id}"))
+        .header(ContentType::JSON)
+        .header(Header::new("X-Admin-Key", admin_key))
+        .body(r#"{"visibility": "secret"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+}
+
+/// Helper: a public room with one member, turned private. Returns (room_id, admin_key, token).
+/// Seed it with `setup` while it is still public.
+fn privatized_room(client: &Client, name: &str, setup: impl FnOnce(&str)) -> (String, String, String) {
+    let (room_id, admin_key) = create_test_room(client, name);
+    setup(&room_id);
+    let token = add_member(client, &room_id, &admin_key, "agent-b");
+    let res = client
+        .put(format!("/api/v1/rooms/{room_id}"))
+        .header(ContentType::JSON)
+        .header(Header::new("X-Admin-Key", admin_key.clone()))
+        .body(r#"{"visibility": "private"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    (room_id, admin_key, token)
+}
+
+fn get_json(client: &Client, path: &str, token: Option<&str>) -> serde_json::Value {
+    let mut req = client.get(path.to_string());
+    if let Some(token) = token {
+        req = req.header(Header::new("X-Member-Token", token.to_string()));
+    }
+    let res = req.dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    res.into_json().unwrap()
+}
+
+#[test]
+fn test_private_rooms_stay_out_of_sender_export() {
+    let client = test_client();
+    let (_, _, token) = privatized_room(&client, "export-private", |room_id| {
+        post_message(&client, room_id, json!({"sender": "agent-b", "content": "private notes"}));
+    });
+
+    let res = client.get("/api/v1/senders/agent-b/export").dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+    let res = client
+        .get("/api/v1/senders/agent-b/export")
+        .header(Header::new("X-Member-Token", token))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+}
+
+#[test]
+fn test_private_rooms_stay_out_of_pending_responses() {
+    let client = test_client();
+    let (_, _, token) = privatized_room(&client, "pending-private", |room_id| {
+        post_message(
+            &client,
+            room_id,
+            json!({"sender": "agent-a", "content": "secret ask", "requires_response_from": ["agent-b"]}),
+        );
+    });
+
+    let path = "/api/v1/pending-responses?sender=agent-b";
+    assert_eq!(get_json(&client, path, None)["count"], 0);
+    assert_eq!(get_json(&client, path, Some(&token))["count"], 1);
+}
+
+#[test]
+fn test_private_rooms_stay_out_of_reaction_notifications() {
+    let client = test_client();
+    let (_, _, token) = privatized_room(&client, "reactions-private", |room_id| {
+        let msg_id = post_message(&client, room_id, json!({"sender": "agent-b", "content": "shipped"}))["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        let res = client
+            .post(format!("/api/v1/rooms/{room_id}/messages/{msg_id}/reactions"))
+            .header(ContentType::JSON)
+            .body(r#"{"sender": "agent-a", "emoji": "🎉"}"#)
+            .dispatch();
+        assert_eq!(res.status(), Status::Ok);
+    });
+
+    let path = "/api/v1/notifications/reactions?sender=agent-b";
+    let outsider = get_json(&client, path, None);
+    assert_eq!(outsider["count"], 0);
+    assert_eq!(outsider["unread_count"], 0);
+    let member = get_json(&client, path, Some(&token));
+    assert_eq!(member["count"], 1);
+    assert_eq!(member["unread_count"], 1);
+}
+
+#[test]
+fn test_private_rooms_stay_out_of_unread() {
+    let client = test_client();
+    let (room_id, _, token) = privatized_room(&client, "unread-private", |room_id| {
+        post_message(&client, room_id, json!({"sender": "agent-a", "content": "unread here"}));
+    });
+
+    let listed = |body: serde_json::Value| body["rooms"].as_array().unwrap().iter().any(|r| r["room_id"] == room_id);
+    assert!(!listed(get_json(&client, "/api/v1/unread?sender=agent-c", None)));
+    assert!(listed(get_json(&client, "/api/v1/unread?sender=agent-c", Some(&token))));
+}
+
+#[test]
+fn test_private_rooms_stay_out_of_unread_threads() {
+    let client = test_client();
+    let (room_id, _, token) = privatized_room(&client, "threads-private", |room_id| {
+        let root = post_message(&client, room_id, json!({"sender": "agent-b", "content": "root"}))["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        post_message(&client, room_id, json!({"sender": "agent-a", "content": "reply", "reply_to": root}));
+    });
+
+    let path = format!("/api/v1/unread/threads?sender=agent-b&room_id={room_id}");
+    assert_eq!(get_json(&client, &path, None)["total_unread"], 0);
+    assert_eq!(get_json(&client, &path, Some(&token))["total_unread"], 1);
+}
+
+#[test]
+fn test_private_rooms_stay_out_of_bulk_reactions() {
+    let client = test_client();
+    let mut msg_id = String::new();
+    let (_, _, token) = privatized_room(&client, "bulk-private", |room_id| {
+        msg_id = post_message(&client, room_id, json!({"sender": "agent-b", "content": "react to me"}))["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+    });
+
+    let bulk = |token: Option<&str>| -> serde_json::Value {
+        let mut req = client
+            .post("/api/v1/reactions/bulk")
+            .header(ContentType::JSON)
+            .body(json!({"message_ids": [&msg_id]}).to_string());
+        if let Some(token) = token {
+            req = req.header(Header::new("X-Member-Token", token.to_string()));
+        }
+        req.dispatch().into_json().unwrap()
+    };
+    let outsider = bulk(None);
+    assert_eq!(outsider["missing"], json!([&msg_id]));
+    assert!(outsider["reactions"].get(&msg_id).is_none());
+    let member = bulk(Some(&token));
+    assert_eq!(member["missing"], json!([]));
+}
+
+#[test]
+fn test_private_rooms_stay_out_of_bookmarks() {
+    let client = test_client();
+    let (_, _, token) = privatized_room(&client, "bookmark-private", |room_id| {
+        let res = client
+            .put(format!("/api/v1/rooms/{room_id}/bookmark"))
+            .header(ContentType::JSON)
+            .body(r#"{"sender": "agent-c"}"#)
+            .dispatch();
+        assert_eq!(res.status(), Status::Ok);
+    });
+
+    let path = "/api/v1/bookmarks?sender=agent-c";
+    assert_eq!(get_json(&client, path, None)["count"], 0);
+    assert_eq!(get_json(&client, path, Some(&token))["count"], 1);
+}
+
+#[test]
+fn test_private_rooms_stay_out_of_cost_stats() {
+    let client = test_client();
+    let (_, _, token) = privatized_room(&client, "costs-private", |room_id| {
+        post_message(
+            &client,
+            room_id,
+            json!({"sender": "agent-b", "content": "done", "metadata": {"usage": {"prompt_tokens": 10, "completion_tokens": 5, "cost_usd": 0.25}}}),
+        );
+    });
+
+    let path = "/api/v1/stats/costs?group_by=room";
+    assert_eq!(get_json(&client, path, None)["totals"]["messages"], 0);
+    assert_eq!(get_json(&client, path, Some(&token))["totals"]["messages"], 1);
+}
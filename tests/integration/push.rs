@@ -0,0 +1,105 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rocket::http::{ContentType, Status};
+use crate::common::test_client;
+
+/// The P-256 generator point, uncompressed — a valid `p256dh` key.
+const P256_GENERATOR: &str = "046B17D1F2E12C4247F8BCE6E563A440F277037D812DEB33A0F4A13945D898C2964FE342E2FE1A7F9B8EE7EB4A7C0F9E162BCE33576B315ECECBB6406837BF51F5";
+
+fn p256dh() -> String {
+    URL_SAFE_NO_PAD.encode(hex_decode(P256_GENERATOR))
+}
+
+fn hex_decode(s: &str) -> Vec<u8> {
+    (0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap()).collect()
+}
+
+fn subscription_body(sender: &str, endpoint: &str, p256dh: &str, auth: &str) -> String {
+    serde_json::json!({
+        "sender": sender,
+        "endpoint": endpoint,
+        "expirationTime": null,
+        "keys": {"p256dh": p256dh, "auth": auth},
+    })
+    .to_string()
+}
+
+#[test]
+fn test_vapid_public_key_is_generated_and_stable() {
+    let client = test_client();
+    let first: serde_json::Value = client.get("/api/v1/push/vapid-public-key").dispatch().into_json().unwrap();
+    assert_eq!(first["enabled"], true);
+    let key = URL_SAFE_NO_PAD.decode(first["public_key"].as_str().unwrap()).unwrap();
+    assert_eq!(key.len(), 65);
+    assert_eq!(key[0], 4);
+
+    // Persisted, so a restart against the same database keeps subscriptions valid
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    let stored: i64 = conn.query_row("SELECT COUNT(*) FROM vapid_keys", [], |r| r.get(0)).unwrap();
+    assert_eq!(stored, 1);
+}
+
+#[test]
+fn test_push_subscription_lifecycle() {
+    let client = test_client();
+    let auth = URL_SAFE_NO_PAD.encode([7u8; 16]);
+    let endpoint = "https://push.example.com/send/abc123";
+
+    let res = client
+        .post("/api/v1/push/subscriptions")
+        .header(ContentType::JSON)
+        .body(subscription_body("alice", endpoint, &p256dh(), &auth))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let sub: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(sub["sender"], "alice");
+    let id = sub["id"].as_str().unwrap().to_string();
+
+    // Same endpoint again (e.g. after a rename) updates in place
+    let res = client
+        .post("/api/v1/push/subscriptions")
+        .header(ContentType::JSON)
+        .body(subscription_body("alice-2", endpoint, &p256dh(), &auth))
+        .dispatch();
+    let again: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(again["id"], id.as_str());
+    assert_eq!(again["sender"], "alice-2");
+
+    let res = client.delete(format!("/api/v1/push/subscriptions/{id}")).dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let res = client.delete(format!("/api/v1/push/subscriptions/{id}")).dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+}
+
+#[test]
+fn test_push_subscription_validation() {
+    let client = test_client();
+    let auth = URL_SAFE_NO_PAD.encode([7u8; 16]);
+    let not_on_curve = URL_SAFE_NO_PAD.encode([4u8; 65]);
+    for body in [
+        subscription_body("alice", "http://push.example.com/x", &p256dh(), &auth),
+        subscription_body("", "https://push.example.com/x", &p256dh(), &auth),
+        subscription_body("alice", "https://push.example.com/x", "short", &auth),
+        subscription_body("alice", "https://push.example.com/x", &not_on_curve, &auth),
+        subscription_body("alice", "https://push.example.com/x", &p256dh(), "c2hvcnQ"),
+    ] {
+        let res = client
+            .post("/api/v1/push/subscriptions")
+            .header(ContentType::JSON)
+            .body(body.clone())
+            .dispatch();
+        assert_eq!(res.status(), Status::BadRequest, "{body}");
+    }
+}
+
+#[test]
+fn test_push_payload_encryption_layout() {
+    let payload = br#"{"title":"hi"}"#;
+    let body = local_agent_chat::push::encrypt(&hex_decode(P256_GENERATOR), &[7u8; 16], payload).unwrap();
+    // salt(16) | record size(4) | key id length(1) | ephemeral public key(65) | ciphertext (+1 delimiter, +16 tag)
+    assert_eq!(body.len(), 16 + 4 + 1 + 65 + payload.len() + 1 + 16);
+    assert_eq!(u32::from_be_bytes(body[16..20].try_into().unwrap()), 4096);
+    assert_eq!(body[20], 65);
+    assert_eq!(body[21], 4);
+    assert!(local_agent_chat::push::encrypt(&[4u8; 65], &[7u8; 16], payload).is_err());
+}
@@ -0,0 +1,127 @@
+use crate::common::{create_test_room, test_client};
+use chrono::{TimeZone, Utc};
+use local_agent_chat::quiet_hours::{parse_time, window_end};
+use rocket::http::{ContentType, Header, Status};
+
+// --- Quiet hours ---
+
+#[test]
+fn test_quiet_hours_window() {
+    let (start, end) = (parse_time("22:00").unwrap(), parse_time("07:00").unwrap());
+    let berlin: chrono_tz::Tz = "Europe/Berlin".parse().unwrap();
+
+    // 23:30 Berlin (CET, UTC+1) is inside an overnight window ending 07:00 the next morning
+    let late = Utc.with_ymd_and_hms(2026, 1, 14, 22, 30, 0).unwrap();
+    assert_eq!(window_end(start, end, berlin, late), Some(Utc.with_ymd_and_hms(2026, 1, 15, 6, 0, 0).unwrap()));
+    // 06:00 Berlin, still inside, ending the same morning
+    let early = Utc.with_ymd_and_hms(2026, 1, 15, 5, 0, 0).unwrap();
+    assert_eq!(window_end(start, end, berlin, early), Some(Utc.with_ymd_and_hms(2026, 1, 15, 6, 0, 0).unwrap()));
+    // Midday is outside
+    let noon = Utc.with_ymd_and_hms(2026, 1, 15, 11, 0, 0).unwrap();
+    assert_eq!(window_end(start, end, berlin, noon), None);
+    // Summer time moves the window with the clock
+    let summer = Utc.with_ymd_and_hms(2026, 7, 15, 4, 30, 0).unwrap();
+    assert_eq!(window_end(start, end, berlin, summer), Some(Utc.with_ymd_and_hms(2026, 7, 15, 5, 0, 0).unwrap()));
+}
+
+#[test]
+fn test_quiet_hours_defer_agent_posts() {
+    let client = test_client();
+    let (room_id, key) = create_test_room(&client, "quiet-room");
+
+    let res = client.get(format!("/api/v1/rooms/{room_id}/quiet-hours")).dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+
+    // A window around the current time
+    let now = Utc::now();
+    let window = serde_json::json!({
+        "start": (now - chrono::Duration::hours(1)).format("%H:%M").to_string(),
+        "end": (now + chrono::Duration::hours(1)).format("%H:%M").to_string(),
+    });
+    for bad in [
+        serde_json::json!({"start": "25:00", "end": "07:00"}),
+        serde_json::json!({"start": "07:00", "end": "07:00"}),
+        serde_json::json!({"start": "22:00", "end": "07:00", "timezone": "Moon/Base"}),
+    ] {
+        let res = client
+            .put(format!("/api/v1/rooms/{room_id}/quiet-hours"))
+            .header(ContentType::JSON)
+            .header(Header::new("Authorization", format!("Bearer {key}")))
+            .body(bad.to_string())
+            .dispatch();
+        assert_eq!(res.status(), Status::BadRequest);
+    }
+    let res = client
+        .put(format!("/api/v1/rooms/{room_id}/quiet-hours"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", "Bearer wrong-key"))
+        .body(window.to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+    let res = client
+        .put(format!("/api/v1/rooms/{room_id}/quiet-hours"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {key}")))
+        .body(window.to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let quiet: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(quiet["timezone"], "UTC");
+    assert_eq!(quiet["active"], true);
+
+    // Agents wait, humans don't
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "nightly-bot", "sender_type": "agent", "content": "build report"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Accepted);
+    let held: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(held["deferred"], true);
+    assert_eq!(held["deliver_at"], quiet["ends_at"]);
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "alice", "sender_type": "human", "content": "morning"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    let msgs: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/rooms/{room_id}/messages"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert!(msgs.iter().all(|m| m["sender"] != "nightly-bot"));
+    let queue: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/quiet-hours/queue"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(queue["count"], 1);
+    assert_eq!(queue["messages"][0]["id"], held["id"]);
+
+    // Still quiet: nothing goes out
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    assert!(local_agent_chat::quiet_hours::release_due(&conn).is_empty());
+
+    // Lifting quiet hours delivers it, after the human message, under the id it was given
+    let res = client
+        .delete(format!("/api/v1/rooms/{room_id}/quiet-hours"))
+        .header(Header::new("Authorization", format!("Bearer {key}")))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let delivered = local_agent_chat::quiet_hours::release_due(&conn);
+    assert_eq!(delivered.len(), 1);
+    assert_eq!(delivered[0].0.id, held["id"].as_str().unwrap());
+
+    let msgs: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/rooms/{room_id}/messages"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let last = msgs.last().unwrap();
+    assert_eq!(last["sender"], "nightly-bot");
+    assert_eq!(last["metadata"]["deferred"]["queued_at"], held["queued_at"]);
+    assert!(last["seq"].as_i64() > msgs.iter().find(|m| m["sender"] == "alice").unwrap()["seq"].as_i64());
+    assert!(local_agent_chat::quiet_hours::release_due(&conn).is_empty());
+}
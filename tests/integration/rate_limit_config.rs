@@ -252,4 +252,162 @@ fn test_default_rate_limits_unchanged() {
     assert_eq!(config.dms_window_secs, 60);
     assert_eq!(config.webhooks_max, 60);
     assert_eq!(config.webhooks_window_secs, 60);
+    assert_eq!(config.webhooks_daily_quota, 0);
+}
+
+#[test]
+fn test_custom_reaction_rate_limit_per_sender() {
+    let config = RateLimitConfig { reactions_max: 2, ..Default::default() };
+    let client = test_client_with_rate_limits(config);
+
+    let res = client
+        .post("/api/v1/rooms")
+        .header(ContentType::JSON)
+        .body(r#"{"name": "reaction-rl"}"#)
+        .dispatch();
+    let room: serde_json::Value = res.into_json().unwrap();
+    let room_id = room["id"].as_str().unwrap();
+
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "agent", "content": "react to me"}"#)
+        .dispatch();
+    let msg: serde_json::Value = res.into_json().unwrap();
+    let msg_id = msg["id"].as_str().unwrap();
+
+    for emoji in ["👍", "🎉"] {
+        let res = client
+            .post(format!("/api/v1/rooms/{room_id}/messages/{msg_id}/reactions"))
+            .header(ContentType::JSON)
+            .body(serde_json::json!({"sender": "spammer", "emoji": emoji}).to_string())
+            .dispatch();
+        assert_eq!(res.status(), Status::Ok);
+        assert_eq!(res.headers().get_one("X-RateLimit-Limit"), Some("2"));
+    }
+
+    // Third reaction from the same sender is rate limited
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages/{msg_id}/reactions"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "spammer", "emoji": "🔥"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::TooManyRequests);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["limit"], 2);
+    assert_eq!(body["remaining"], 0);
+    assert!(body["retry_after_secs"].as_u64().is_some());
+
+    // A different sender has its own budget
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages/{msg_id}/reactions"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "polite", "emoji": "🔥"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+}
+
+#[test]
+fn test_reaction_rate_limit_keyed_on_client_and_sender() {
+    let config = RateLimitConfig { reactions_max: 2, ..Default::default() };
+    let client = test_client_with_rate_limits(config);
+
+    let res = client
+        .post("/api/v1/rooms")
+        .header(ContentType::JSON)
+        .body(r#"{"name": "reaction-rl-ip"}"#)
+        .dispatch();
+    let room: serde_json::Value = res.into_json().unwrap();
+    let room_id = room["id"].as_str().unwrap();
+
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "agent", "content": "react to me"}"#)
+        .dispatch();
+    let msg: serde_json::Value = res.into_json().unwrap();
+    let msg_id = msg["id"].as_str().unwrap();
+    let url = format!("/api/v1/rooms/{room_id}/messages/{msg_id}/reactions");
+    let home: std::net::SocketAddr = "10.0.0.1:4000".parse().unwrap();
+    let away: std::net::SocketAddr = "10.0.0.2:4000".parse().unwrap();
+
+    for emoji in ["👍", "🎉"] {
+        let res = client
+            .post(url.as_str())
+            .remote(home)
+            .header(ContentType::JSON)
+            .body(serde_json::json!({"sender": "spammer", "emoji": emoji}).to_string())
+            .dispatch();
+        assert_eq!(res.status(), Status::Ok);
+    }
+
+    // A forged X-Forwarded-For doesn't buy a fresh bucket without a trusted proxy
+    let res = client
+        .post(url.as_str())
+        .remote(home)
+        .header(ContentType::JSON)
+        .header(Header::new("X-Forwarded-For", "203.0.113.9"))
+        .body(r#"{"sender": "spammer", "emoji": "🔥"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::TooManyRequests);
+
+    // The same sender name from another address has its own budget
+    let res = client
+        .post(url.as_str())
+        .remote(away)
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "spammer", "emoji": "🔥"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+}
+
+#[test]
+fn test_max_distinct_reaction_emoji_per_message() {
+    let config = RateLimitConfig { reaction_emoji_max: 2, ..Default::default() };
+    let client = test_client_with_rate_limits(config);
+
+    let res = client
+        .post("/api/v1/rooms")
+        .header(ContentType::JSON)
+        .body(r#"{"name": "reaction-cap"}"#)
+        .dispatch();
+    let room: serde_json::Value = res.into_json().unwrap();
+    let room_id = room["id"].as_str().unwrap();
+
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "agent", "content": "react to me"}"#)
+        .dispatch();
+    let msg: serde_json::Value = res.into_json().unwrap();
+    let msg_id = msg["id"].as_str().unwrap();
+
+    let react = |sender: &str, emoji: &str| {
+        client
+            .post(format!("/api/v1/rooms/{room_id}/messages/{msg_id}/reactions"))
+            .header(ContentType::JSON)
+            .body(serde_json::json!({"sender": sender, "emoji": emoji}).to_string())
+            .dispatch()
+            .status()
+    };
+
+    assert_eq!(react("alice", "👍"), Status::Ok);
+    assert_eq!(react("bob", "🎉"), Status::Ok);
+
+    // A third distinct emoji is rejected
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages/{msg_id}/reactions"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "carol", "emoji": "🔥"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Conflict);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["max_distinct_emoji"], 2);
+
+    // Joining an existing emoji is still allowed
+    assert_eq!(react("carol", "👍"), Status::Ok);
+
+    // Once an emoji is fully removed, its slot frees up
+    assert_eq!(react("bob", "🎉"), Status::Ok); // toggle off
+    assert_eq!(react("carol", "🔥"), Status::Ok);
 }
@@ -0,0 +1,101 @@
+use crate::common::{create_test_room, post_message, react, test_client};
+use rocket::http::{ContentType, Status};
+use serde_json::json;
+
+// --- Reaction Notifications ---
+
+fn feed(client: &crate::common::TestClient, query: &str) -> serde_json::Value {
+    let res = client.get(format!("/api/v1/notifications/reactions?{query}")).dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    res.into_json().unwrap()
+}
+
+#[test]
+fn test_reaction_notifications_feed() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "reaction-feed");
+    let mine = post_message(&client, &room_id, json!({"sender": "Worker", "content": "Deployed build 42"}))["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let theirs = post_message(&client, &room_id, json!({"sender": "Lead", "content": "Please deploy"}))["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    react(&client, &room_id, &mine, "Lead", "👍");
+    react(&client, &room_id, &mine, "Worker", "🎉"); // own reaction: not a notification
+    react(&client, &room_id, &theirs, "Other", "👀"); // someone else's message
+    react(&client, &room_id, &mine, "Reviewer", "✅");
+
+    let body = feed(&client, "sender=Worker");
+    assert_eq!(body["count"], 2);
+    assert_eq!(body["unread_count"], 2);
+    assert_eq!(body["read_cursor"], 0);
+    let items = body["notifications"].as_array().unwrap();
+    // Newest first, with the message for context
+    assert_eq!(items[0]["reactor"], "Reviewer");
+    assert_eq!(items[0]["emoji"], "✅");
+    assert_eq!(items[1]["reactor"], "Lead");
+    assert_eq!(items[1]["message_id"], mine.as_str());
+    assert_eq!(items[1]["message_preview"], "Deployed build 42");
+    assert_eq!(items[1]["room_name"], "reaction-feed");
+    assert!(items.iter().all(|n| n["unread"] == true));
+
+    // `after` returns only newer ones
+    let oldest = items[1]["cursor"].as_i64().unwrap();
+    let body = feed(&client, &format!("sender=Worker&after={oldest}"));
+    assert_eq!(body["count"], 1);
+    assert_eq!(body["notifications"][0]["reactor"], "Reviewer");
+}
+
+#[test]
+fn test_reaction_notifications_unread_cursor() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "reaction-unread");
+    let mine = post_message(&client, &room_id, json!({"sender": "Worker", "content": "Done"}))["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    react(&client, &room_id, &mine, "Lead", "👍");
+    react(&client, &room_id, &mine, "Reviewer", "✅");
+
+    let items = feed(&client, "sender=Worker")["notifications"].as_array().unwrap().clone();
+    let older = items[1]["cursor"].as_i64().unwrap();
+
+    // Mark read up to the older one
+    let res = client
+        .post("/api/v1/notifications/reactions/read")
+        .header(ContentType::JSON)
+        .body(format!(r#"{{"sender": "Worker", "cursor": {older}}}"#))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["read_cursor"], older);
+    assert_eq!(body["unread_count"], 1);
+
+    let body = feed(&client, "sender=Worker&unread=true");
+    assert_eq!(body["count"], 1);
+    assert_eq!(body["notifications"][0]["reactor"], "Reviewer");
+
+    // Without a cursor marks everything read; it never moves backwards
+    let res = client
+        .post("/api/v1/notifications/reactions/read")
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "Worker"}"#)
+        .dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["unread_count"], 0);
+    let newest = body["read_cursor"].as_i64().unwrap();
+    let res = client
+        .post("/api/v1/notifications/reactions/read")
+        .header(ContentType::JSON)
+        .body(format!(r#"{{"sender": "Worker", "cursor": {older}}}"#))
+        .dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["read_cursor"], newest);
+    assert_eq!(feed(&client, "sender=Worker&unread=true")["count"], 0);
+
+    let res = client.get("/api/v1/notifications/reactions?sender=%20").dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+}
@@ -536,3 +536,186 @@ fn test_rooms_sorted_by_last_activity() {
     // #general has no messages, should be last
     assert_eq!(rooms[3]["name"], "general");
 }
+
+#[test]
+fn test_reaction_shortcodes_normalize() {
+    let client = test_client();
+    let room: serde_json::Value = client
+        .post("/api/v1/rooms")
+        .header(ContentType::JSON)
+        .body(r#"{"name": "react-shortcodes"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let room_id = room["id"].as_str().unwrap();
+    let msg: serde_json::Value = client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "Nanook", "content": "ship it?"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let msg_id = msg["id"].as_str().unwrap();
+
+    let react = |sender: &str, emoji: &str| -> serde_json::Value {
+        let res = client
+            .post(format!("/api/v1/rooms/{room_id}/messages/{msg_id}/reactions"))
+            .header(ContentType::JSON)
+            .body(serde_json::json!({"sender": sender, "emoji": emoji}).to_string())
+            .dispatch();
+        assert_eq!(res.status(), Status::Ok);
+        res.into_json().unwrap()
+    };
+
+    let r = react("a", ":thumbsup:");
+    assert_eq!(r["emoji"], "👍");
+    assert_eq!(r["shortcode"], ":thumbsup:");
+    react("b", ":+1:");
+    react("c", "👍");
+    // Heart with and without the variation selector
+    react("a", "❤");
+    react("b", ":HEART:");
+    // Unknown shortcodes are kept (lowercased)
+    let custom = react("a", ":PartyParrot:");
+    assert_eq!(custom["emoji"], ":partyparrot:");
+    assert_eq!(custom["shortcode"], ":partyparrot:");
+
+    let body: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/messages/{msg_id}/reactions"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let reactions = body["reactions"].as_array().unwrap();
+    assert_eq!(reactions.len(), 3);
+    assert_eq!(reactions[0]["emoji"], "👍");
+    assert_eq!(reactions[0]["shortcode"], ":thumbsup:");
+    assert_eq!(reactions[0]["count"], 3);
+    assert_eq!(reactions[1]["emoji"], "❤️");
+    assert_eq!(reactions[1]["shortcode"], ":heart:");
+    assert_eq!(reactions[1]["count"], 2);
+
+    // Toggling with a different encoding removes the same reaction
+    react("c", ":thumbs_up:");
+    let res = client
+        .delete(format!("/api/v1/rooms/{room_id}/messages/{msg_id}/reactions?sender=b&emoji=%3A%2B1%3A"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/reactions"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(body["reactions"][msg_id][0]["count"], 1);
+}
+
+#[test]
+fn test_bulk_reactions_across_rooms() {
+    let client = test_client();
+    let mut ids = Vec::new();
+    for room in ["bulk-react-a", "bulk-react-b"] {
+        let (room_id, _) = crate::common::create_test_room(&client, room);
+        let msg: serde_json::Value = client
+            .post(format!("/api/v1/rooms/{room_id}/messages"))
+            .header(ContentType::JSON)
+            .body(r#"{"sender": "author", "content": "react to me"}"#)
+            .dispatch()
+            .into_json()
+            .unwrap();
+        ids.push((room_id, msg["id"].as_str().unwrap().to_string()));
+    }
+    let (room_a, msg_a) = &ids[0];
+    let (_, msg_b) = &ids[1];
+    for sender in ["x", "y"] {
+        client
+            .post(format!("/api/v1/rooms/{room_a}/messages/{msg_a}/reactions"))
+            .header(ContentType::JSON)
+            .body(format!(r#"{{"sender": "{sender}", "emoji": ":thumbsup:"}}"#))
+            .dispatch();
+    }
+
+    let res = client
+        .post("/api/v1/reactions/bulk")
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"message_ids": [msg_a, msg_b, "no-such-message", msg_a]}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body: serde_json::Value = res.into_json().unwrap();
+    let reactions = body["reactions"].as_object().unwrap();
+    assert_eq!(reactions.len(), 2);
+    assert_eq!(reactions[msg_a.as_str()][0]["emoji"], "👍");
+    assert_eq!(reactions[msg_a.as_str()][0]["count"], 2);
+    assert_eq!(reactions[msg_b.as_str()], serde_json::json!([]));
+    assert_eq!(body["missing"], serde_json::json!(["no-such-message"]));
+
+    let too_many: Vec<String> = (0..201).map(|i| format!("id-{i}")).collect();
+    let res = client
+        .post("/api/v1/reactions/bulk")
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"message_ids": too_many}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+}
+
+// --- Reaction allow-list ---
+
+#[test]
+fn test_room_reaction_allow_list() {
+    use crate::common::create_test_room;
+    use rocket::http::Header;
+
+    let client = test_client();
+    let (room_id, admin_key) = create_test_room(&client, "react-vote");
+    let msg: serde_json::Value = client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "author", "content": "Ship it?"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let msg_id = msg["id"].as_str().unwrap();
+    let react = |sender: &str, emoji: &str| {
+        client
+            .post(format!("/api/v1/rooms/{room_id}/messages/{msg_id}/reactions"))
+            .header(ContentType::JSON)
+            .body(serde_json::json!({"sender": sender, "emoji": emoji}).to_string())
+            .dispatch()
+    };
+    let set_allowed = |allowed: serde_json::Value| {
+        client
+            .put(format!("/api/v1/rooms/{room_id}"))
+            .header(ContentType::JSON)
+            .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+            .body(serde_json::json!({"allowed_reactions": allowed}).to_string())
+            .dispatch()
+    };
+
+    // Reacted before the restriction
+    assert_eq!(react("early", "🎉").status(), Status::Ok);
+
+    let res = set_allowed(serde_json::json!(["✅", ":x:", "🤔", "✅"]));
+    assert_eq!(res.status(), Status::Ok);
+    let room: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(room["allowed_reactions"], serde_json::json!(["✅", "❌", "🤔"]));
+
+    assert_eq!(react("voter", ":white_check_mark:").status(), Status::Ok);
+    let res = react("voter", "👍");
+    assert_eq!(res.status(), Status::BadRequest);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert!(body["error"].as_str().unwrap().contains("✅ ❌ 🤔"));
+    assert_eq!(body["allowed_reactions"], serde_json::json!(["✅", "❌", "🤔"]));
+
+    // An older reaction can still be toggled off
+    assert_eq!(react("early", "🎉").status(), Status::Ok);
+
+    // Non-admins can't change the list; null lifts it
+    let res = client
+        .put(format!("/api/v1/rooms/{room_id}"))
+        .header(ContentType::JSON)
+        .body(r#"{"allowed_reactions": null}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Unauthorized);
+    let res = set_allowed(serde_json::Value::Null);
+    let room: serde_json::Value = res.into_json().unwrap();
+    assert!(room.get("allowed_reactions").is_none());
+    assert_eq!(react("voter", "👍").status(), Status::Ok);
+}
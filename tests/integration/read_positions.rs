@@ -1,6 +1,7 @@
 use rocket::local::blocking::Client;
 use rocket::http::{ContentType, Header, Status};
-use crate::common::{test_client, create_test_room};
+use crate::common::{test_client, create_test_room, post_message};
+use serde_json::json;
 
 // --- Read Positions ---
 
@@ -385,3 +386,229 @@ fn test_unread_multiple_rooms() {
     assert_eq!(b_room["unread_count"], 2);
     assert!(body["total_unread"].as_i64().unwrap() >= 3);
 }
+
+// --- Thread Read Positions ---
+
+#[test]
+fn test_unread_threads_counts_nested_replies() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "thread-unread-1");
+
+    let root = post_message(&client, &room_id, json!({"sender": "alice", "content": "root"}))["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let reply1 = post_message(&client, &room_id, json!({"sender": "bob", "content": "reply", "reply_to": &root}))["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    post_message(&client, &room_id, json!({"sender": "carol", "content": "reply", "reply_to": &reply1}));
+    // Own replies never count as unread
+    post_message(&client, &room_id, json!({"sender": "alice", "content": "reply", "reply_to": &root}));
+
+    let res = client.get("/api/v1/unread/threads?sender=alice").dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body: serde_json::Value = res.into_json().unwrap();
+    let threads = body["threads"].as_array().unwrap();
+    assert_eq!(threads.len(), 1);
+    assert_eq!(threads[0]["root_id"], root);
+    assert_eq!(threads[0]["unread_count"], 2);
+    assert_eq!(body["total_unread"], 2);
+}
+
+#[test]
+fn test_room_read_does_not_mark_threads_read() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "thread-unread-2");
+
+    let root = post_message(&client, &room_id, json!({"sender": "alice", "content": "root"}))["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let seq = post_message(&client, &room_id, json!({"sender": "bob", "content": "reply", "reply_to": &root}))["seq"]
+        .as_i64()
+        .unwrap();
+
+    client
+        .put(format!("/api/v1/rooms/{}/read", room_id))
+        .header(ContentType::JSON)
+        .body(format!(r#"{{"sender": "alice", "last_read_seq": {}}}"#, seq))
+        .dispatch();
+
+    let res = client.get("/api/v1/unread/threads?sender=alice").dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["total_unread"], 1);
+}
+
+#[test]
+fn test_thread_read_position_clears_unread() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "thread-unread-3");
+
+    let root = post_message(&client, &room_id, json!({"sender": "alice", "content": "root"}))["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let seq1 = post_message(&client, &room_id, json!({"sender": "bob", "content": "reply", "reply_to": &root}))["seq"]
+        .as_i64()
+        .unwrap();
+
+    let res = client
+        .put(format!("/api/v1/rooms/{}/threads/{}/read", room_id, root))
+        .header(ContentType::JSON)
+        .body(format!(r#"{{"sender": "alice", "last_read_seq": {}}}"#, seq1))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["root_id"], root);
+    assert_eq!(body["last_read_seq"], seq1);
+
+    let res = client.get("/api/v1/unread/threads?sender=alice").dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["total_unread"], 0);
+
+    // A new reply shows up again
+    post_message(&client, &room_id, json!({"sender": "bob", "content": "reply", "reply_to": &root}));
+    let res = client
+        .get(format!("/api/v1/unread/threads?sender=alice&room_id={}", room_id))
+        .dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["total_unread"], 1);
+    assert_eq!(body["threads"][0]["last_read_seq"], seq1);
+}
+
+#[test]
+fn test_unread_includes_thread_counts_per_room() {
+    let client = test_client();
+    let (room_a, _) = create_test_room(&client, "thread-unread-5a");
+    let (room_b, _) = create_test_room(&client, "thread-unread-5b");
+
+    let root = post_message(&client, &room_a, json!({"sender": "alice", "content": "root"}))["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let msg = post_message(&client, &room_a, json!({"sender": "bob", "content": "reply", "reply_to": &root}));
+    let (reply, seq) = (msg["id"].as_str().unwrap().to_string(), msg["seq"].as_i64().unwrap());
+    post_message(&client, &room_a, json!({"sender": "carol", "content": "reply", "reply_to": &reply}));
+    post_message(&client, &room_b, json!({"sender": "bob", "content": "root"}));
+
+    // Catching up on the room doesn't clear the thread
+    client
+        .put(format!("/api/v1/rooms/{}/read", room_a))
+        .header(ContentType::JSON)
+        .body(format!(r#"{{"sender": "alice", "last_read_seq": {}}}"#, seq + 1))
+        .dispatch();
+
+    let body: serde_json::Value = client
+        .get("/api/v1/unread?sender=alice")
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let rooms = body["rooms"].as_array().unwrap();
+    let a = rooms.iter().find(|r| r["room_id"] == room_a).unwrap();
+    let b = rooms.iter().find(|r| r["room_id"] == room_b).unwrap();
+    assert_eq!(a["unread_count"], 0);
+    assert_eq!(a["thread_unread_count"], 2);
+    assert_eq!(b["thread_unread_count"], 0);
+    assert_eq!(body["total_thread_unread"], 2);
+}
+
+#[test]
+fn test_thread_read_position_unknown_root() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "thread-unread-4");
+
+    let res = client
+        .put(format!("/api/v1/rooms/{}/threads/nonexistent/read", room_id))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "alice", "last_read_seq": 1}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+
+    let res = client.get("/api/v1/unread/threads?sender=").dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+}
+
+fn mark_read(client: &Client, path: String, sender: &str, seq: i64) {
+    let res = client
+        .put(path)
+        .header(ContentType::JSON)
+        .body(format!(r#"{{"sender": "{}", "last_read_seq": {}}}"#, sender, seq))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+}
+
+#[test]
+fn test_message_read_by() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "read-by-1");
+
+    send_test_message(&client, &room_id, "bob", "ready");
+    send_test_message(&client, &room_id, "carol", "ready too");
+    let (instruction, seq) = {
+        let res = client
+            .post(format!("/api/v1/rooms/{}/messages", room_id))
+            .header(ContentType::JSON)
+            .body(r#"{"sender": "alice", "content": "bob: deploy now"}"#)
+            .dispatch();
+        let body: serde_json::Value = res.into_json().unwrap();
+        (body["id"].as_str().unwrap().to_string(), body["seq"].as_i64().unwrap())
+    };
+
+    let read_by = |client: &Client| -> serde_json::Value {
+        let res = client
+            .get(format!("/api/v1/rooms/{}/messages/{}/read-by", room_id, instruction))
+            .dispatch();
+        assert_eq!(res.status(), Status::Ok);
+        res.into_json().unwrap()
+    };
+
+    let body = read_by(&client);
+    assert_eq!(body["seq"], seq);
+    assert_eq!(body["sender"], "alice");
+    assert_eq!(body["read_by"], serde_json::json!([]));
+    assert_eq!(body["unread_by"], serde_json::json!(["bob", "carol"]));
+
+    // Reading up to an earlier message doesn't count; reading past it does
+    mark_read(&client, format!("/api/v1/rooms/{}/read", room_id), "carol", seq - 1);
+    mark_read(&client, format!("/api/v1/rooms/{}/read", room_id), "bob", seq);
+    let body = read_by(&client);
+    let readers = body["read_by"].as_array().unwrap();
+    assert_eq!(readers.len(), 1);
+    assert_eq!(readers[0]["sender"], "bob");
+    assert_eq!(readers[0]["last_read_seq"], seq);
+    assert_eq!(body["unread_by"], serde_json::json!(["carol"]));
+
+    let res = client
+        .get(format!("/api/v1/rooms/{}/messages/nonexistent/read-by", room_id))
+        .dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+}
+
+#[test]
+fn test_message_read_by_counts_thread_position() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "read-by-2");
+
+    let root = post_message(&client, &room_id, json!({"sender": "bob", "content": "root"}))["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let reply =
+        post_message(&client, &room_id, json!({"sender": "alice", "content": "reply", "reply_to": &root}))["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+    let msg = post_message(&client, &room_id, json!({"sender": "alice", "content": "reply", "reply_to": &reply}));
+    let (nested, seq) = (msg["id"].as_str().unwrap().to_string(), msg["seq"].as_i64().unwrap());
+
+    mark_read(&client, format!("/api/v1/rooms/{}/threads/{}/read", room_id, root), "bob", seq);
+
+    let res = client
+        .get(format!("/api/v1/rooms/{}/messages/{}/read-by", room_id, nested))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["read_by"][0]["sender"], "bob");
+    assert_eq!(body["unread_by"], serde_json::json!([]));
+}
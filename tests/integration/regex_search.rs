@@ -0,0 +1,106 @@
+use crate::common::{admin_client, create_test_room, post_message, test_client, test_client_with_sender_policy};
+use local_agent_chat::senders::SenderPolicy;
+use rocket::http::{Header, Status};
+use rocket::local::blocking::Client;
+use serde_json::json;
+
+fn regex_search(client: &Client, query: &str) -> (Status, serde_json::Value) {
+    let res = client
+        .get(format!("/api/v1/search?mode=regex&{query}"))
+        .header(Header::new("X-Server-Token", "srv_secret"))
+        .dispatch();
+    let status = res.status();
+    (status, res.into_json().unwrap_or_default())
+}
+
+#[test]
+fn test_regex_search_returns_capture_groups() {
+    let client = admin_client();
+    let (room_id, _) = create_test_room(&client, "tickets");
+    post_message(&client, &room_id, json!({"sender": "bot", "content": "Fixed OPS-42 and OPS-7 today"}));
+    post_message(&client, &room_id, json!({"sender": "bot", "content": "no ticket here"}));
+    post_message(&client, &room_id, json!({"sender": "bot", "content": "See ENG-1001"}));
+
+    let q = urlencoding::encode(r"(?P<project>[A-Z]+)-(\d+)");
+    let (status, body) = regex_search(&client, &format!("q={q}"));
+    assert_eq!(status, Status::Ok);
+    assert_eq!(body["mode"], "regex");
+    assert_eq!(body["count"], 2);
+    assert_eq!(body["truncated"], false);
+    assert_eq!(body["has_more"], false);
+
+    // Newest first
+    let results = body["results"].as_array().unwrap();
+    assert_eq!(results[0]["content"], "See ENG-1001");
+    let matches = results[1]["matches"].as_array().unwrap();
+    assert_eq!(matches.len(), 2);
+    assert_eq!(matches[0]["text"], "OPS-42");
+    assert_eq!(matches[0]["start"], 6);
+    assert_eq!(matches[0]["end"], 12);
+    assert_eq!(matches[0]["groups"], serde_json::json!(["OPS", "42"]));
+    assert_eq!(matches[0]["named"]["project"], "OPS");
+    assert_eq!(matches[1]["groups"][1], "7");
+}
+
+#[test]
+fn test_regex_search_pages_with_next_before_seq() {
+    let client = admin_client();
+    let (room_id, _) = create_test_room(&client, "logs");
+    for i in 0..5 {
+        post_message(&client, &room_id, json!({"sender": "bot", "content": format!("ERROR code={i}")}));
+    }
+    let q = urlencoding::encode(r"code=(\d)");
+    let (_, first) = regex_search(&client, &format!("q={q}&room_id={room_id}&limit=3"));
+    assert_eq!(first["count"], 3);
+    assert_eq!(first["has_more"], true);
+    let cursor = first["next_before_seq"].as_i64().unwrap();
+
+    let (_, second) = regex_search(&client, &format!("q={q}&room_id={room_id}&limit=3&before_seq={cursor}"));
+    assert_eq!(second["count"], 2);
+    assert_eq!(second["has_more"], false);
+    assert_eq!(second["results"][1]["matches"][0]["groups"][0], "0");
+}
+
+#[test]
+fn test_regex_search_requires_server_token_by_default() {
+    let client = admin_client();
+    let res = client.get("/api/v1/search?mode=regex&q=abc").dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+
+    // Without a configured token the mode is unusable
+    let client = test_client_with_sender_policy(SenderPolicy::default());
+    let res = client
+        .get("/api/v1/search?mode=regex&q=abc")
+        .header(Header::new("X-Server-Token", "srv_secret"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+}
+
+#[test]
+fn test_regex_search_rejects_bad_input() {
+    let client = admin_client();
+    let (status, body) = regex_search(&client, "q=%28unclosed");
+    assert_eq!(status, Status::BadRequest);
+    assert!(body["error"].as_str().unwrap().contains("Invalid regex"));
+
+    let (status, _) = regex_search(&client, "q=");
+    assert_eq!(status, Status::BadRequest);
+
+    let res = test_client().get("/api/v1/search?mode=fuzzy&q=abc").dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+}
+
+#[test]
+fn test_fts_results_have_no_matches_field() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "plain");
+    post_message(&client, &room_id, json!({"sender": "bot", "content": "deploy finished"}));
+    let body: serde_json::Value = client
+        .get("/api/v1/search?q=deploy")
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(body["count"], 1);
+    assert!(body.get("mode").is_none());
+    assert!(body["results"][0].get("matches").is_none());
+}
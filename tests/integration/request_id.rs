@@ -0,0 +1,85 @@
+use rocket::http::{ContentType, Header, Status};
+use crate::common::test_client;
+
+// --- X-Request-Id ---
+
+#[test]
+fn test_request_id_echoed_when_supplied() {
+    let client = test_client();
+    let res = client
+        .get("/api/v1/health")
+        .header(Header::new("X-Request-Id", "pipeline-42.step-3"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    assert_eq!(res.headers().get_one("X-Request-Id"), Some("pipeline-42.step-3"));
+}
+
+#[test]
+fn test_request_id_generated_when_missing() {
+    let client = test_client();
+    let res = client.get("/api/v1/health").dispatch();
+    let rid = res.headers().get_one("X-Request-Id").expect("header present");
+    assert!(uuid::Uuid::parse_str(rid).is_ok(), "generated id should be a UUID: {rid}");
+
+    // Each request gets its own id
+    let res2 = client.get("/api/v1/health").dispatch();
+    assert_ne!(res2.headers().get_one("X-Request-Id"), Some(rid));
+}
+
+#[test]
+fn test_request_id_invalid_value_replaced() {
+    let client = test_client();
+    let too_long = "x".repeat(200);
+    let res = client
+        .get("/api/v1/health")
+        .header(Header::new("X-Request-Id", too_long.clone()))
+        .dispatch();
+    let rid = res.headers().get_one("X-Request-Id").unwrap();
+    assert_ne!(rid, too_long);
+    assert!(uuid::Uuid::parse_str(rid).is_ok());
+
+    let res = client
+        .get("/api/v1/health")
+        .header(Header::new("X-Request-Id", "has spaces"))
+        .dispatch();
+    assert_ne!(res.headers().get_one("X-Request-Id"), Some("has spaces"));
+}
+
+#[test]
+fn test_request_id_in_error_body() {
+    let client = test_client();
+    let res = client
+        .post("/api/v1/rooms/does-not-exist/messages")
+        .header(ContentType::JSON)
+        .header(Header::new("X-Request-Id", "trace-me"))
+        .body(r#"{"sender": "bot", "content": "hi"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+    assert_eq!(res.headers().get_one("X-Request-Id"), Some("trace-me"));
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["error"], "Room not found");
+    assert_eq!(body["request_id"], "trace-me");
+}
+
+#[test]
+fn test_request_id_in_catcher_error_body() {
+    let client = test_client();
+    let res = client
+        .get("/api/v1/definitely/not/a/route")
+        .header(Header::new("X-Request-Id", "catch-404"))
+        .dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["request_id"], "catch-404");
+}
+
+#[test]
+fn test_request_id_not_added_to_success_body() {
+    let client = test_client();
+    let res = client
+        .get("/api/v1/health")
+        .header(Header::new("X-Request-Id", "ok-call"))
+        .dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert!(body.get("request_id").is_none());
+}
@@ -0,0 +1,129 @@
+use rocket::http::{ContentType, Header, Status};
+use local_agent_chat::senders::SenderPolicy;
+use crate::common::{create_test_room, policy_with_token, test_client, test_client_with_sender_policy};
+
+// --- Reserved sender names ---
+
+#[test]
+fn test_reserved_sender_rejected_by_default() {
+    let client = test_client();
+    let (room_id, admin_key) = create_test_room(&client, "reserved-default");
+
+    for sender in ["system", "System", " admin "] {
+        let res = client
+            .post(format!("/api/v1/rooms/{room_id}/messages"))
+            .header(ContentType::JSON)
+            .body(serde_json::json!({"sender": sender, "content": "trust me"}).to_string())
+            .dispatch();
+        assert_eq!(res.status(), Status::Forbidden, "{sender}");
+        let body: serde_json::Value = res.into_json().unwrap();
+        assert!(body["error"].as_str().unwrap().contains("reserved"));
+    }
+
+    // A room admin key is not the server token
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .body(r#"{"sender": "system", "content": "trust me"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+
+    // Names that merely contain a reserved word are fine
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "system-monitor", "content": "all good"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+}
+
+#[test]
+fn test_reserved_sender_allowed_with_server_token() {
+    let client = test_client_with_sender_policy(policy_with_token());
+    let (room_id, _) = create_test_room(&client, "reserved-token");
+
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .header(Header::new("X-Server-Token", "srv_secret"))
+        .body(r#"{"sender": "system", "content": "Maintenance at 02:00"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let msg: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(msg["sender"], "system");
+    let msg_id = msg["id"].as_str().unwrap();
+
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", "Bearer srv_secret"))
+        .body(r#"{"sender": "ops-bot", "content": "Deploy window open"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .header(Header::new("X-Server-Token", "wrong"))
+        .body(r#"{"sender": "ops-bot", "content": "Deploy window open"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+
+    // Editing a reserved-name message also needs the token
+    let res = client
+        .put(format!("/api/v1/rooms/{room_id}/messages/{msg_id}"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "system", "content": "Maintenance cancelled"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+    let res = client
+        .put(format!("/api/v1/rooms/{room_id}/messages/{msg_id}"))
+        .header(ContentType::JSON)
+        .header(Header::new("X-Server-Token", "srv_secret"))
+        .body(r#"{"sender": "system", "content": "Maintenance cancelled"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+}
+
+#[test]
+fn test_reserved_sender_enforced_on_other_post_paths() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "reserved-paths");
+
+    let res = client
+        .post("/api/v1/dm")
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "admin", "recipient": "alice", "content": "reset your password"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+
+    let res = client
+        .post("/api/v1/broadcast")
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"sender": "system", "content": "hi", "room_ids": [room_id]}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages/stream/start"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "system"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+}
+
+#[test]
+fn test_empty_policy_disables_protection() {
+    let client = test_client_with_sender_policy(SenderPolicy {
+        protected: vec![],
+        server_token: None,
+    });
+    let (room_id, _) = create_test_room(&client, "reserved-off");
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "system", "content": "anything goes"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+}
@@ -276,7 +276,7 @@ fn send_messages(client: &impl std::ops::Deref<Target = rocket::local::blocking:
 /// Helper: get all message IDs in a room.
 fn get_message_ids(client: &impl std::ops::Deref<Target = rocket::local::blocking::Client>, room_id: &str) -> Vec<String> {
     let res = client
-        .get(format!("/api/v1/rooms/{room_id}/messages?limit=1000"))
+        .get(format!("/api/v1/rooms/{room_id}/messages?limit=1000&kind=message"))
         .dispatch();
     assert_eq!(res.status(), Status::Ok);
     let msgs: Vec<serde_json::Value> = res.into_json().unwrap();
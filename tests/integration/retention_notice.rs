@@ -0,0 +1,162 @@
+use rocket::http::{ContentType, Header, Status};
+use crate::common::{test_client, create_test_room, post_message, TestClient};
+use serde_json::json;
+
+fn configure(client: &TestClient, room_id: &str, admin_key: &str, body: &str) -> Status {
+    client
+        .put(format!("/api/v1/rooms/{room_id}"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .body(body)
+        .dispatch()
+        .status()
+}
+
+fn run(client: &TestClient) -> serde_json::Value {
+    let res = client.post("/api/v1/admin/retention/run").dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    res.into_json().unwrap()
+}
+
+fn message_count(client: &TestClient, room_id: &str) -> usize {
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/messages?limit=1000&kind=message"))
+        .dispatch();
+    res.into_json::<Vec<serde_json::Value>>().unwrap().len()
+}
+
+fn expire_notice(client: &TestClient, room_id: &str) {
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    let past = (chrono::Utc::now() - chrono::Duration::seconds(1)).to_rfc3339();
+    conn.execute(
+        "UPDATE retention_notices SET purge_after = ?1 WHERE room_id = ?2",
+        rusqlite::params![past, room_id],
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_retention_notice_defers_purge_until_due() {
+    let client = test_client();
+    let (room_id, admin_key) = create_test_room(&client, "retention-notice");
+    assert_eq!(
+        configure(&client, &room_id, &admin_key, r#"{"max_messages": 10, "retention_notice_secs": 3600}"#),
+        Status::Ok
+    );
+    for i in 0..15 {
+        post_message(&client, &room_id, json!({"sender": "archivist", "content": format!("message {i}")}));
+    }
+
+    // First sweep only announces
+    let result = run(&client);
+    assert_eq!(result["total_pruned"], 0);
+    assert_eq!(message_count(&client, &room_id), 15);
+
+    let res = client.get(format!("/api/v1/rooms/{room_id}/retention/pending")).dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let notice: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(notice["pending_count"], 5);
+    assert_eq!(notice["postponed"], false);
+    let cutoff_seq = notice["cutoff_seq"].as_i64().unwrap();
+
+    // Still within the notice period: nothing happens, and no second notice
+    for i in 0..2 {
+        post_message(&client, &room_id, json!({"sender": "archivist", "content": format!("message {i}")}));
+    }
+    assert_eq!(run(&client)["total_pruned"], 0);
+    let res = client.get(format!("/api/v1/rooms/{room_id}/retention/pending")).dispatch();
+    assert_eq!(res.into_json::<serde_json::Value>().unwrap()["cutoff_seq"], cutoff_seq);
+
+    // Once due, only the announced messages go; the two newer overflow messages wait for their own notice
+    expire_notice(&client, &room_id);
+    assert_eq!(run(&client)["total_pruned"], 5);
+    assert_eq!(message_count(&client, &room_id), 12);
+    let res = client.get(format!("/api/v1/rooms/{room_id}/retention/pending")).dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+
+    run(&client);
+    let res = client.get(format!("/api/v1/rooms/{room_id}/retention/pending")).dispatch();
+    assert_eq!(res.into_json::<serde_json::Value>().unwrap()["pending_count"], 2);
+}
+
+#[test]
+fn test_retention_postpone_once() {
+    let client = test_client();
+    let (room_id, admin_key) = create_test_room(&client, "retention-postpone");
+    configure(&client, &room_id, &admin_key, r#"{"max_messages": 10, "retention_notice_secs": 600}"#);
+    for i in 0..12 {
+        post_message(&client, &room_id, json!({"sender": "archivist", "content": format!("message {i}")}));
+    }
+
+    // Nothing pending yet
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/retention/postpone"))
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+
+    run(&client);
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/retention/postpone"))
+        .header(Header::new("Authorization", "Bearer wrong-key"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/retention/postpone?secs=10"))
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+
+    expire_notice(&client, &room_id);
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/retention/postpone?secs=3600"))
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let notice: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(notice["postponed"], true);
+    let purge_after = chrono::DateTime::parse_from_rfc3339(notice["purge_after"].as_str().unwrap()).unwrap();
+    assert!(purge_after > chrono::Utc::now() + chrono::Duration::seconds(3500));
+
+    // Postponed notice holds the purge
+    assert_eq!(run(&client)["total_pruned"], 0);
+
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/retention/postpone"))
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .dispatch();
+    assert_eq!(res.status(), Status::Conflict);
+}
+
+#[test]
+fn test_retention_notice_secs_validation_and_default() {
+    let client = test_client();
+    let (room_id, admin_key) = create_test_room(&client, "retention-notice-validate");
+    assert_eq!(configure(&client, &room_id, &admin_key, r#"{"retention_notice_secs": 5}"#), Status::BadRequest);
+    assert_eq!(configure(&client, &room_id, &admin_key, r#"{"retention_notice_secs": 86400}"#), Status::Ok);
+    let room: serde_json::Value = client.get(format!("/api/v1/rooms/{room_id}")).dispatch().into_json().unwrap();
+    assert_eq!(room["retention_notice_secs"], 86400);
+
+    // Without a notice period, retention purges immediately as before
+    configure(&client, &room_id, &admin_key, r#"{"retention_notice_secs": null, "max_messages": 10}"#);
+    for i in 0..11 {
+        post_message(&client, &room_id, json!({"sender": "archivist", "content": format!("message {i}")}));
+    }
+    assert_eq!(run(&client)["total_pruned"], 1);
+    let res = client.get(format!("/api/v1/rooms/{room_id}/retention/pending")).dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+}
+
+#[test]
+fn test_retention_pending_webhook_event_accepted() {
+    let client = test_client();
+    let (room_id, admin_key) = create_test_room(&client, "retention-notice-webhook");
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/webhooks"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .body(r#"{"url": "http://localhost:9999/hook", "events": "retention_pending", "created_by": "tester"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+}
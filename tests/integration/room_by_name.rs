@@ -0,0 +1,96 @@
+use rocket::http::{ContentType, Header, Status};
+use crate::common::{create_test_room, test_client};
+
+// --- Name-based room addressing ---
+
+#[test]
+fn test_room_scoped_routes_by_name() {
+    let client = test_client();
+    let (id, key) = create_test_room(&client, "ops-room");
+
+    let res = client.get("/api/v1/rooms/by-name/ops-room").dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let room: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(room["id"], id.as_str());
+
+    let res = client
+        .post("/api/v1/rooms/by-name/ops-room/messages")
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "script", "content": "deploy finished"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let msg: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(msg["room_id"], id.as_str());
+    let msg_id = msg["id"].as_str().unwrap();
+
+    let msgs: Vec<serde_json::Value> = client
+        .get("/api/v1/rooms/by-name/ops-room/messages?limit=10")
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(msgs.len(), 1);
+
+    let res = client
+        .post(format!("/api/v1/rooms/by-name/ops-room/messages/{msg_id}/pin"))
+        .header(Header::new("Authorization", format!("Bearer {key}")))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let pins: Vec<serde_json::Value> = client
+        .get("/api/v1/rooms/by-name/ops-room/pins")
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(pins.len(), 1);
+
+    let res = client
+        .post("/api/v1/rooms/by-name/ops-room/files")
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "script", "filename": "log.txt", "content_type": "text/plain", "data": "aGVsbG8="}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let files: Vec<serde_json::Value> = client
+        .get("/api/v1/rooms/by-name/ops-room/files")
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(files.len(), 1);
+}
+
+#[test]
+fn test_by_name_percent_encoded_names() {
+    let client = test_client();
+    let (id, _) = create_test_room(&client, "Team Chat");
+    let res = client.get("/api/v1/rooms/by-name/Team%20Chat").dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let room: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(room["id"], id.as_str());
+}
+
+#[test]
+fn test_by_name_follows_renames() {
+    let client = test_client();
+    let (id, key) = create_test_room(&client, "before-rename");
+    let res = client
+        .put(format!("/api/v1/rooms/{id}"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {key}")))
+        .body(r#"{"name": "after-rename"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    for name in ["before-rename", "after-rename"] {
+        let res = client.get(format!("/api/v1/rooms/by-name/{name}")).dispatch();
+        assert_eq!(res.status(), Status::Ok, "{name}");
+        let room: serde_json::Value = res.into_json().unwrap();
+        assert_eq!(room["id"], id.as_str());
+    }
+}
+
+#[test]
+fn test_by_name_unknown_room_404() {
+    let client = test_client();
+    let res = client.get("/api/v1/rooms/by-name/no-such-room").dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+    let res = client.get("/api/v1/rooms/by-name/no-such-room/messages").dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+}
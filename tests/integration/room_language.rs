@@ -0,0 +1,104 @@
+use crate::common::{create_test_room, post_message, test_client};
+use rocket::http::{ContentType, Header, Status};
+use rocket::local::blocking::Client;
+use serde_json::json;
+
+fn set_language(client: &Client, room_id: &str, key: &str, language: serde_json::Value) -> Status {
+    client
+        .put(format!("/api/v1/rooms/{room_id}"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {key}")))
+        .body(serde_json::json!({"language": language}).to_string())
+        .dispatch()
+        .status()
+}
+
+fn search_hits(client: &Client, room_id: &str, q: &str) -> usize {
+    let res = client
+        .get(format!("/api/v1/search?q={}&room_id={room_id}", urlencoding::encode(q)))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body: serde_json::Value = res.into_json().unwrap();
+    body["results"].as_array().unwrap().len()
+}
+
+#[test]
+fn test_language_selects_tokenizer() {
+    let client = test_client();
+    let (room_id, key) = create_test_room(&client, "lang-room");
+    assert_eq!(set_language(&client, &room_id, &key, serde_json::json!("ja")), Status::Ok);
+    let room: serde_json::Value = client.get(format!("/api/v1/rooms/{room_id}")).dispatch().into_json().unwrap();
+    assert_eq!(room["language"], "ja");
+    assert_eq!(room["search_tokenizer"], "trigram");
+
+    assert_eq!(set_language(&client, &room_id, &key, serde_json::json!("de-AT")), Status::Ok);
+    let room: serde_json::Value = client.get(format!("/api/v1/rooms/{room_id}")).dispatch().into_json().unwrap();
+    assert_eq!(room["search_tokenizer"], "unicode61");
+
+    // null goes back to the default and hides both fields
+    assert_eq!(set_language(&client, &room_id, &key, serde_json::Value::Null), Status::Ok);
+    let room: serde_json::Value = client.get(format!("/api/v1/rooms/{room_id}")).dispatch().into_json().unwrap();
+    assert!(room.get("language").is_none());
+    assert!(room.get("search_tokenizer").is_none());
+
+    assert_eq!(set_language(&client, &room_id, &key, serde_json::json!("not a language")), Status::BadRequest);
+}
+
+#[test]
+fn test_japanese_room_matches_substrings_after_reindex() {
+    let client = test_client();
+    let (room_id, key) = create_test_room(&client, "jp");
+    post_message(&client, &room_id, json!({"sender": "bot", "content": "全文検索機能を改善しました"}));
+    // The default tokenizer sees the whole run as one token
+    assert_eq!(search_hits(&client, &room_id, "検索機能"), 0);
+
+    // Existing messages move to the trigram index when the language changes
+    assert_eq!(set_language(&client, &room_id, &key, serde_json::json!("ja")), Status::Ok);
+    assert_eq!(search_hits(&client, &room_id, "検索機能"), 1);
+
+    // New messages are indexed there too
+    post_message(&client, &room_id, json!({"sender": "bot", "content": "検索機能のテストです"}));
+    assert_eq!(search_hits(&client, &room_id, "検索機能"), 2);
+}
+
+#[test]
+fn test_non_english_room_skips_english_stemming() {
+    let client = test_client();
+    let res = client
+        .post("/api/v1/rooms")
+        .header(ContentType::JSON)
+        .body(r#"{"name": "de-room", "language": "de"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["search_tokenizer"], "unicode61");
+    let de_room = body["id"].as_str().unwrap().to_string();
+    let (en_room, _) = create_test_room(&client, "en-room");
+
+    post_message(&client, &de_room, json!({"sender": "bot", "content": "running the deploys"}));
+    post_message(&client, &en_room, json!({"sender": "bot", "content": "running the deploys"}));
+    assert_eq!(search_hits(&client, &en_room, "run"), 1);
+    assert_eq!(search_hits(&client, &de_room, "run"), 0);
+    assert_eq!(search_hits(&client, &de_room, "running"), 1);
+
+    // Unscoped search covers every index
+    let res = client.get("/api/v1/search?q=running").dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["results"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn test_deleted_messages_leave_every_index() {
+    let client = test_client();
+    let (room_id, key) = create_test_room(&client, "jp-delete");
+    set_language(&client, &room_id, &key, serde_json::json!("ja"));
+    post_message(&client, &room_id, json!({"sender": "bot", "content": "削除されるメッセージ"}));
+    let res = client.get(format!("/api/v1/rooms/{room_id}/messages")).dispatch();
+    let msgs: Vec<serde_json::Value> = res.into_json().unwrap();
+    let id = msgs.last().unwrap()["id"].as_str().unwrap().to_string();
+    let res = client
+        .delete(format!("/api/v1/rooms/{room_id}/messages/{id}?sender=bot"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    assert_eq!(search_hits(&client, &room_id, "メッセージ"), 0);
+}
@@ -0,0 +1,112 @@
+use std::time::Instant;
+
+use crate::common::{cleanup, temp_path};
+use local_agent_chat::db::Db;
+use rocket::local::blocking::Client;
+
+// --- list_rooms at scale ---
+
+/// The pre-aggregation list_rooms query: four correlated subqueries per room.
+const LEGACY_LIST_ROOMS_SQL: &str = "SELECT r.id, r.name,
+        (SELECT COUNT(*) FROM messages WHERE room_id = r.id) as message_count,
+        (SELECT MAX(created_at) FROM messages WHERE room_id = r.id) as last_activity,
+        (SELECT sender FROM messages WHERE room_id = r.id ORDER BY seq DESC LIMIT 1) as last_sender,
+        (SELECT SUBSTR(content, 1, 100) FROM messages WHERE room_id = r.id ORDER BY seq DESC LIMIT 1) as last_preview
+ FROM rooms r WHERE COALESCE(r.room_type, 'room') != 'dm' AND r.archived_at IS NULL
+ ORDER BY last_activity IS NULL, last_activity DESC, r.name";
+
+/// Seed `rooms` rooms with `per_room` messages each directly in SQLite
+/// (going through the API would trip rate limits long before this is interesting).
+fn seed(path: &str, rooms: usize, per_room: usize) {
+    let db = Db::new(path);
+    let mut conn = db.conn();
+    let tx = conn.transaction().unwrap();
+    let mut seq = 1_000i64;
+    for r in 0..rooms {
+        let room_id = format!("bench-room-{r}");
+        tx.execute(
+            "INSERT INTO rooms (id, name, created_by, created_at, updated_at) VALUES (?1, ?2, 'bench', '2026-01-01T00:00:00Z', '2026-01-01T00:00:00Z')",
+            rusqlite::params![&room_id, format!("bench-{r:04}")],
+        )
+        .unwrap();
+        for m in 0..per_room {
+            seq += 1;
+            tx.execute(
+                "INSERT INTO messages (id, room_id, sender, content, metadata, created_at, seq) VALUES (?1, ?2, ?3, ?4, '{}', ?5, ?6)",
+                rusqlite::params![
+                    uuid::Uuid::new_v4().to_string(),
+                    &room_id,
+                    format!("agent-{}", m % 7),
+                    format!("room {r} message {m}"),
+                    format!("2026-01-01T{:02}:{:02}:{:02}Z", (r / 60) % 24, r % 60, m % 60),
+                    seq
+                ],
+            )
+            .unwrap();
+        }
+    }
+    tx.commit().unwrap();
+}
+
+#[test]
+fn test_list_rooms_aggregates_match_per_room_values() {
+    let path = temp_path();
+    seed(&path, 30, 3);
+    let client = Client::tracked(local_agent_chat::rocket_with_db(&path)).unwrap();
+
+    let rooms: Vec<serde_json::Value> = client.get("/api/v1/rooms").dispatch().into_json().unwrap();
+    // 30 seeded rooms + the default general room
+    assert_eq!(rooms.len(), 31);
+
+    let room = rooms.iter().find(|r| r["name"] == "bench-0007").unwrap();
+    assert_eq!(room["message_count"], 3);
+    assert_eq!(room["last_message_preview"], "room 7 message 2");
+    assert_eq!(room["last_message_sender"], "agent-2");
+
+    let general = rooms.iter().find(|r| r["name"] == "general").unwrap();
+    assert_eq!(general["message_count"], 0);
+    assert!(general["last_message_preview"].is_null());
+    assert!(general.get("bookmarked").is_none() || general["bookmarked"].is_null());
+
+    // Most recent activity first; empty rooms last
+    assert_eq!(rooms.last().unwrap()["name"], "general");
+
+    drop(client);
+    cleanup(&path);
+}
+
+/// Benchmark: compares the old correlated-subquery list against the aggregated endpoint.
+/// Run with `cargo test --test integration room_list_bench -- --ignored --nocapture`.
+#[test]
+#[ignore]
+fn bench_list_rooms_hundreds_of_rooms() {
+    let path = temp_path();
+    seed(&path, 500, 200);
+
+    let legacy = {
+        let db = Db::new(&path);
+        let conn = db.conn();
+        let mut stmt = conn.prepare(LEGACY_LIST_ROOMS_SQL).unwrap();
+        let start = Instant::now();
+        for _ in 0..10 {
+            let n = stmt.query_map([], |r| r.get::<_, String>(0)).unwrap().count();
+            assert_eq!(n, 501);
+        }
+        start.elapsed() / 10
+    };
+
+    let client = Client::tracked(local_agent_chat::rocket_with_db(&path)).unwrap();
+    client.get("/api/v1/rooms").dispatch(); // warm the statement cache
+    let start = Instant::now();
+    for _ in 0..10 {
+        let rooms: Vec<serde_json::Value> = client.get("/api/v1/rooms").dispatch().into_json().unwrap();
+        assert_eq!(rooms.len(), 501);
+    }
+    let aggregated = start.elapsed() / 10;
+
+    // The endpoint figure also includes HTTP dispatch and JSON encoding, so it overstates the query cost
+    println!("list_rooms (500 rooms x 200 msgs): legacy query {legacy:?}, aggregated endpoint {aggregated:?}");
+
+    drop(client);
+    cleanup(&path);
+}
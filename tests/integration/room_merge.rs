@@ -0,0 +1,236 @@
+use rocket::http::{ContentType, Header, Status};
+use rocket::local::blocking::Client;
+use crate::common::{create_test_room, post_message, test_client};
+use serde_json::json;
+
+// --- Room merge ---
+
+fn merge(client: &Client, target: &str, target_key: &str, source: &str, source_key: &str) -> (Status, serde_json::Value) {
+    let res = client
+        .post("/api/v1/admin/rooms/merge")
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {target_key}")))
+        .body(
+            serde_json::json!({
+                "target_room_id": target,
+                "source_room_id": source,
+                "source_admin_key": source_key,
+                "merged_by": "ops"
+            })
+            .to_string(),
+        )
+        .dispatch();
+    let status = res.status();
+    (status, res.into_json().unwrap_or(serde_json::json!(null)))
+}
+
+#[test]
+fn test_merge_interleaves_messages_by_timestamp() {
+    let client = test_client();
+    let (a, a_key) = create_test_room(&client, "merge-a");
+    let (b, b_key) = create_test_room(&client, "merge-b");
+    post_message(&client, &a, json!({"sender": "x", "content": "a1"}));
+    post_message(&client, &b, json!({"sender": "y", "content": "b1"}));
+    post_message(&client, &a, json!({"sender": "x", "content": "a2"}));
+    post_message(&client, &b, json!({"sender": "y", "content": "b2"}));
+
+    let (status, body) = merge(&client, &a, &a_key, &b, &b_key);
+    assert_eq!(status, Status::Ok);
+    assert_eq!(body["messages_merged"], 2);
+    assert_eq!(body["room"]["message_count"], 4);
+    assert_eq!(body["source_room_name"], "merge-b");
+
+    let msgs: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/rooms/{a}/messages"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let contents: Vec<&str> = msgs.iter().map(|m| m["content"].as_str().unwrap()).collect();
+    assert_eq!(contents, vec!["a1", "b1", "a2", "b2"]);
+    let seqs: Vec<i64> = msgs.iter().map(|m| m["seq"].as_i64().unwrap()).collect();
+    assert!(seqs.windows(2).all(|w| w[0] < w[1]));
+}
+
+#[test]
+fn test_merge_moves_files_pins_and_read_positions() {
+    let client = test_client();
+    let (a, a_key) = create_test_room(&client, "merge-extras-a");
+    let (b, b_key) = create_test_room(&client, "merge-extras-b");
+    post_message(&client, &a, json!({"sender": "x", "content": "a1"}));
+    let b1 = post_message(&client, &b, json!({"sender": "y", "content": "b1"}));
+    post_message(&client, &b, json!({"sender": "y", "content": "b2"}));
+
+    // Pin in B, file in B, read position in B at b1
+    let b1_id = b1["id"].as_str().unwrap();
+    let res = client
+        .post(format!("/api/v1/rooms/{b}/messages/{b1_id}/pin"))
+        .header(Header::new("Authorization", format!("Bearer {b_key}")))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let res = client
+        .post(format!("/api/v1/rooms/{b}/files"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "y", "filename": "notes.txt", "content_type": "text/plain", "data": "aGVsbG8="}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let res = client
+        .put(format!("/api/v1/rooms/{b}/read"))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"sender": "reader", "last_read_seq": b1["seq"]}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    let (status, body) = merge(&client, &a, &a_key, &b, &b_key);
+    assert_eq!(status, Status::Ok);
+    assert_eq!(body["files_moved"], 1);
+
+    let pins: Vec<serde_json::Value> = client.get(format!("/api/v1/rooms/{a}/pins")).dispatch().into_json().unwrap();
+    assert_eq!(pins.len(), 1);
+    assert_eq!(pins[0]["id"], b1_id);
+
+    let files: Vec<serde_json::Value> = client.get(format!("/api/v1/rooms/{a}/files")).dispatch().into_json().unwrap();
+    assert_eq!(files.len(), 1);
+
+    // reader had read up to b1 — after the merge that is a1, b1 (interleaved), leaving b2 unread
+    let unread: serde_json::Value = client
+        .get("/api/v1/unread?sender=reader")
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let room = unread["rooms"].as_array().unwrap().iter().find(|r| r["room_id"] == a.as_str()).unwrap();
+    assert_eq!(room["unread_count"], 1);
+}
+
+#[test]
+fn test_merged_room_leaves_redirect_and_audit_entry() {
+    let client = test_client();
+    let (a, a_key) = create_test_room(&client, "merge-redirect-a");
+    let (b, b_key) = create_test_room(&client, "merge-redirect-b");
+    post_message(&client, &b, json!({"sender": "y", "content": "b1"}));
+
+    let (status, _) = merge(&client, &a, &a_key, &b, &b_key);
+    assert_eq!(status, Status::Ok);
+
+    let res = client.get(format!("/api/v1/rooms/{b}")).dispatch();
+    assert_eq!(res.status(), Status::PermanentRedirect);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["moved_to"], a.as_str());
+
+    let res = client
+        .get(format!("/api/v1/rooms/{a}/audit"))
+        .header(Header::new("Authorization", format!("Bearer {a_key}")))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let audit: Vec<serde_json::Value> = res.into_json().unwrap();
+    assert_eq!(audit.len(), 1);
+    assert_eq!(audit[0]["action"], "room_merged");
+    assert_eq!(audit[0]["actor"], "ops");
+    assert_eq!(audit[0]["details"]["source_room_id"], b.as_str());
+
+    let res = client
+        .get(format!("/api/v1/rooms/{a}/audit"))
+        .header(Header::new("Authorization", format!("Bearer {b_key}")))
+        .dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+}
+
+#[test]
+fn test_merge_requires_both_admin_keys() {
+    let client = test_client();
+    let (a, a_key) = create_test_room(&client, "merge-auth-a");
+    let (b, b_key) = create_test_room(&client, "merge-auth-b");
+
+    let (status, _) = merge(&client, &a, &b_key, &b, &b_key);
+    assert_eq!(status, Status::Forbidden);
+    let (status, _) = merge(&client, &a, &a_key, &b, &a_key);
+    assert_eq!(status, Status::Forbidden);
+    let (status, _) = merge(&client, &a, &a_key, &a, &a_key);
+    assert_eq!(status, Status::BadRequest);
+    let (status, _) = merge(&client, &a, &a_key, "missing", &b_key);
+    assert_eq!(status, Status::NotFound);
+
+    // Nothing changed
+    assert_eq!(client.get(format!("/api/v1/rooms/{b}")).dispatch().status(), Status::Ok);
+}
+
+#[test]
+fn test_merge_keeps_queue_subscriptions_roles_deferred_posts_and_members() {
+    let client = test_client();
+    let (a, a_key) = create_test_room(&client, "merge-rows-a");
+    let (b, b_key) = create_test_room(&client, "merge-rows-b");
+    let now = chrono::Utc::now().to_rfc3339();
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    conn.execute_batch(&format!(
+        "INSERT INTO queue_items (id, room_id, task, created_by, created_at) VALUES ('q1', '{b}', 'build', 'ops', '{now}');
+         INSERT INTO room_subscriptions (sender, room_id, created_at) VALUES ('watcher', '{b}', '{now}'), ('both', '{a}', '{now}'), ('both', '{b}', '{now}');
+         INSERT INTO room_roles (room_id, sender, role, granted_at) VALUES ('{b}', 'mod', 'moderator', '{now}'), ('{a}', 'dup', 'guest', '{now}'), ('{b}', 'dup', 'moderator', '{now}');
+         INSERT INTO deferred_messages (id, room_id, sender, content, queued_at, deliver_at, scheduled_at) VALUES ('d1', '{b}', 'bot', 'later', '{now}', '{now}', '{now}');
+         UPDATE rooms SET visibility = 'private' WHERE id IN ('{a}', '{b}');
+         INSERT INTO room_members (room_id, sender, token, added_at) VALUES ('{b}', 'member-b', 'token-member-b-0000', '{now}');"
+    ))
+    .unwrap();
+
+    let (status, _) = merge(&client, &a, &a_key, &b, &b_key);
+    assert_eq!(status, Status::Ok);
+
+    let count = |sql: &str| -> i64 { conn.query_row(sql, [], |r| r.get(0)).unwrap() };
+    for table in ["queue_items", "room_subscriptions", "room_roles", "deferred_messages", "room_members"] {
+        assert_eq!(count(&format!("SELECT COUNT(*) FROM {table} WHERE room_id = '{b}'")), 0, "{table}");
+    }
+    assert_eq!(count(&format!("SELECT COUNT(*) FROM queue_items WHERE room_id = '{a}' AND id = 'q1'")), 1);
+    assert_eq!(count(&format!("SELECT COUNT(*) FROM deferred_messages WHERE room_id = '{a}' AND id = 'd1'")), 1);
+    assert_eq!(count(&format!("SELECT COUNT(*) FROM room_subscriptions WHERE room_id = '{a}'")), 2);
+    assert_eq!(count(&format!("SELECT COUNT(*) FROM room_members WHERE room_id = '{a}' AND token = 'token-member-b-0000'")), 1);
+    assert_eq!(count(&format!("SELECT COUNT(*) FROM room_roles WHERE room_id = '{a}' AND sender = 'mod'")), 1);
+    // The target's own role for a sender wins over the source's
+    let role: String = conn
+        .query_row(&format!("SELECT role FROM room_roles WHERE room_id = '{a}' AND sender = 'dup'"), [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(role, "guest");
+}
+
+#[test]
+fn test_private_room_only_merges_into_private_room() {
+    let client = test_client();
+    let (a, a_key) = create_test_room(&client, "merge-public");
+    let (b, b_key) = create_test_room(&client, "merge-private");
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    conn.execute("UPDATE rooms SET visibility = 'private' WHERE id = ?1", [&b]).unwrap();
+
+    let (status, body) = merge(&client, &a, &a_key, &b, &b_key);
+    assert_eq!(status, Status::Conflict);
+    assert!(body["error"].as_str().unwrap().contains("private"));
+
+    // Public into private is fine: the history only gets more restricted
+    let (status, _) = merge(&client, &b, &b_key, &a, &a_key);
+    assert_eq!(status, Status::Ok);
+}
+
+#[test]
+fn test_merged_messages_are_reindexed_for_target_tokenizer() {
+    let client = test_client();
+    let (target, target_key) = create_test_room(&client, "merge-fts-target");
+    let (source, source_key) = create_test_room(&client, "merge-fts-source");
+    let res = client
+        .put(format!("/api/v1/rooms/{target}"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {target_key}")))
+        .body(r#"{"language": "ja"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let msg = post_message(&client, &source, json!({"sender": "agent", "content": "全文検索機能を改善しました"}));
+
+    let (status, _) = merge(&client, &target, &target_key, &source, &source_key);
+    assert_eq!(status, Status::Ok);
+
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    let indexed: (i64, i64) = conn
+        .query_row(
+            "SELECT (SELECT COUNT(*) FROM messages_fts WHERE message_id = ?1),
+                    (SELECT COUNT(*) FROM messages_fts_trigram WHERE message_id = ?1)",
+            [msg["id"].as_str().unwrap()],
+            |r| Ok((r.get(0)?, r.get(1)?)),
+        )
+        .unwrap();
+    assert_eq!(indexed, (0, 1));
+}
@@ -0,0 +1,121 @@
+use crate::common::{cleanup, create_test_room, temp_path, test_client};
+use local_agent_chat::db::{Db, DbConfig};
+use local_agent_chat::provision::{self, DefaultRooms};
+use rocket::http::{ContentType, Header, Status};
+
+const PROJECT_ROOMS: &str = r#"[
+    {"name": "general", "description": "Everything else"},
+    {"name": "deploys", "description": "Release coordination", "tags": ["Ops", "release"],
+     "pins": ["Post a plan before every deploy.", "Rollbacks go in #incidents."]},
+    {"name": "incidents", "tags": ["ops"]}
+]"#;
+
+#[test]
+fn test_default_is_general_only() {
+    let defaults = DefaultRooms::default();
+    assert_eq!(defaults.rooms.len(), 1);
+    assert_eq!(defaults.rooms[0].name, "general");
+}
+
+#[test]
+fn test_parse_rejects_bad_config() {
+    assert!(DefaultRooms::parse("not json").is_err());
+    assert!(DefaultRooms::parse(r#"[{"name": "  "}]"#).is_err());
+    assert!(DefaultRooms::parse(r#"[{"name": "a"}, {"name": "a"}]"#).is_err());
+    assert!(DefaultRooms::parse(r#"[{"name": "a", "tags": ["has space"]}]"#).is_err());
+}
+
+#[test]
+fn test_provision_creates_rooms_tags_and_pins() {
+    let path = temp_path();
+    let db = Db::with_config(&path, &DbConfig::default());
+    let conn = db.conn();
+    // Start from an empty database, as on first boot
+    conn.execute("DELETE FROM rooms", []).unwrap();
+
+    let defaults = DefaultRooms::parse(PROJECT_ROOMS).unwrap();
+    let created = provision::provision(&conn, &defaults);
+    assert_eq!(created, vec!["general", "deploys", "incidents"]);
+
+    let tags: String = conn
+        .query_row("SELECT tags FROM rooms WHERE name = 'deploys'", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(tags, r#"["ops","release"]"#);
+    let pins: Vec<String> = conn
+        .prepare(
+            "SELECT m.content FROM messages m JOIN rooms r ON r.id = m.room_id
+             WHERE r.name = 'deploys' AND m.pinned_at IS NOT NULL AND m.sender = 'system' ORDER BY m.seq",
+        )
+        .unwrap()
+        .query_map([], |r| r.get(0))
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(pins, vec!["Post a plan before every deploy.", "Rollbacks go in #incidents."]);
+
+    // Not first boot any more: nothing is added back
+    conn.execute("DELETE FROM rooms WHERE name = 'incidents'", []).unwrap();
+    assert!(provision::provision(&conn, &defaults).is_empty());
+    let rooms: i64 = conn.query_row("SELECT COUNT(*) FROM rooms", [], |r| r.get(0)).unwrap();
+    assert_eq!(rooms, 2);
+
+    drop(conn);
+    drop(db);
+    cleanup(&path);
+}
+
+#[test]
+fn test_room_tags_create_update_and_filter() {
+    let client = test_client();
+    let res = client
+        .post("/api/v1/rooms")
+        .header(ContentType::JSON)
+        .body(r#"{"name": "tagged", "tags": ["Backend", "backend", "ci"]}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["tags"], serde_json::json!(["backend", "ci"]));
+
+    let (other_id, other_key) = create_test_room(&client, "untagged");
+    let res = client.get("/api/v1/rooms?tag=ci").dispatch();
+    let rooms: Vec<serde_json::Value> = res.into_json().unwrap();
+    assert_eq!(rooms.len(), 1);
+    assert_eq!(rooms[0]["name"], "tagged");
+
+    let res = client
+        .put(format!("/api/v1/rooms/{other_id}"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {other_key}")))
+        .body(r#"{"tags": ["ci"]}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let res = client.get("/api/v1/rooms?tag=CI").dispatch();
+    let rooms: Vec<serde_json::Value> = res.into_json().unwrap();
+    assert_eq!(rooms.len(), 2);
+
+    let res = client.get(format!("/api/v1/rooms/{other_id}")).dispatch();
+    let room: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(room["tags"], serde_json::json!(["ci"]));
+
+    // [] clears; untagged rooms omit the field
+    client
+        .put(format!("/api/v1/rooms/{other_id}"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {other_key}")))
+        .body(r#"{"tags": []}"#)
+        .dispatch();
+    let res = client.get(format!("/api/v1/rooms/{other_id}")).dispatch();
+    let room: serde_json::Value = res.into_json().unwrap();
+    assert!(room.get("tags").is_none());
+}
+
+#[test]
+fn test_invalid_room_tags_rejected() {
+    let client = test_client();
+    let res = client
+        .post("/api/v1/rooms")
+        .header(ContentType::JSON)
+        .body(r#"{"name": "bad-tags", "tags": ["no spaces allowed"]}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+}
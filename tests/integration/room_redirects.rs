@@ -0,0 +1,154 @@
+use rocket::http::{ContentType, Header, Status};
+use rocket::local::blocking::Client;
+use crate::common::{create_test_room, test_client, test_client_with_namespaces};
+
+// --- Redirects for renamed/merged rooms ---
+
+fn merge(client: &Client, target: &str, target_key: &str, source: &str, source_key: &str) {
+    let res = client
+        .post("/api/v1/admin/rooms/merge")
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {target_key}")))
+        .body(
+            serde_json::json!({"target_room_id": target, "source_room_id": source, "source_admin_key": source_key})
+                .to_string(),
+        )
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+}
+
+#[test]
+fn test_merged_room_id_redirects_with_path_and_query() {
+    let client = test_client();
+    let (a, a_key) = create_test_room(&client, "redir-a");
+    let (b, b_key) = create_test_room(&client, "redir-b");
+    merge(&client, &a, &a_key, &b, &b_key);
+
+    let res = client
+        .get(format!("/api/v1/rooms/{b}/messages?limit=5&after=0"))
+        .dispatch();
+    assert_eq!(res.status(), Status::PermanentRedirect);
+    let location = format!("/api/v1/rooms/{a}/messages?limit=5&after=0");
+    assert_eq!(res.headers().get_one("Location"), Some(location.as_str()));
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["moved_to"], a.as_str());
+    assert_eq!(body["location"], location.as_str());
+
+    // Following the redirect (method and body preserved) lands in the surviving room
+    let res = client
+        .post(format!("/api/v1/rooms/{b}/messages"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "late-agent", "content": "posting to an old permalink"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::PermanentRedirect);
+    let location = res.headers().get_one("Location").unwrap().to_string();
+    let res = client
+        .post(location)
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "late-agent", "content": "posting to an old permalink"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let msg: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(msg["room_id"], a.as_str());
+}
+
+#[test]
+fn test_redirect_chains_collapse_on_repeat_merges() {
+    let client = test_client();
+    let (a, a_key) = create_test_room(&client, "chain-a");
+    let (b, b_key) = create_test_room(&client, "chain-b");
+    let (c, c_key) = create_test_room(&client, "chain-c");
+    merge(&client, &b, &b_key, &c, &c_key);
+    merge(&client, &a, &a_key, &b, &b_key);
+
+    // c was merged into b, which was merged into a: c points straight at a
+    let res = client.get(format!("/api/v1/rooms/{c}")).dispatch();
+    assert_eq!(res.status(), Status::PermanentRedirect);
+    assert_eq!(res.headers().get_one("Location"), Some(format!("/api/v1/rooms/{a}").as_str()));
+
+    let aliases: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{a}/aliases"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let redirect_ids: Vec<&str> = aliases["redirects"].as_array().unwrap().iter().map(|r| r["room_id"].as_str().unwrap()).collect();
+    assert!(redirect_ids.contains(&b.as_str()) && redirect_ids.contains(&c.as_str()));
+    let names: Vec<&str> = aliases["names"].as_array().unwrap().iter().map(|n| n["name"].as_str().unwrap()).collect();
+    assert!(names.contains(&"chain-b") && names.contains(&"chain-c"));
+}
+
+#[test]
+fn test_rename_records_old_name_alias() {
+    let client = test_client();
+    let (id, key) = create_test_room(&client, "old-name");
+    let res = client
+        .put(format!("/api/v1/rooms/{id}"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {key}")))
+        .body(r#"{"name": "new-name"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    let aliases: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{id}/aliases"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(aliases["names"][0]["name"], "old-name");
+
+    // Creating a new room with the old name takes the name back
+    create_test_room(&client, "old-name");
+    let aliases: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{id}/aliases"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert!(aliases["names"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn test_unknown_room_still_404() {
+    let client = test_client();
+    let res = client.get("/api/v1/rooms/never-existed/messages").dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+    assert!(res.headers().get_one("Location").is_none());
+}
+
+#[test]
+fn test_redirect_keeps_namespace_prefix() {
+    let client = test_client_with_namespaces(&["alpha"]);
+    let create = |name: &str| -> (String, String) {
+        let res = client
+            .post("/ns/alpha/api/v1/rooms")
+            .header(ContentType::JSON)
+            .body(serde_json::json!({"name": name, "created_by": "tester"}).to_string())
+            .dispatch();
+        assert_eq!(res.status(), Status::Ok);
+        let body: serde_json::Value = res.into_json().unwrap();
+        (body["id"].as_str().unwrap().to_string(), body["admin_key"].as_str().unwrap().to_string())
+    };
+    let (a, a_key) = create("ns-redir-a");
+    let (b, b_key) = create("ns-redir-b");
+    let res = client
+        .post("/ns/alpha/api/v1/admin/rooms/merge")
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {a_key}")))
+        .body(serde_json::json!({"target_room_id": &a, "source_room_id": &b, "source_admin_key": &b_key}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    let res = client.get(format!("/ns/alpha/api/v1/rooms/{b}/messages?limit=5")).dispatch();
+    assert_eq!(res.status(), Status::PermanentRedirect);
+    let location = format!("/ns/alpha/api/v1/rooms/{a}/messages?limit=5");
+    assert_eq!(res.headers().get_one("Location"), Some(location.as_str()));
+    let res = client.get(location).dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    // The header form has no prefix to keep
+    let res = client
+        .get(format!("/api/v1/rooms/{b}"))
+        .header(Header::new("X-Namespace", "alpha"))
+        .dispatch();
+    assert_eq!(res.status(), Status::PermanentRedirect);
+    assert_eq!(res.headers().get_one("Location"), Some(format!("/api/v1/rooms/{a}").as_str()));
+}
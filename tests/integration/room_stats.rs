@@ -0,0 +1,86 @@
+use crate::common::{create_test_room, post_message, test_client, TestClient};
+use rocket::http::{ContentType, Status};
+
+fn stats(client: &TestClient, room_id: &str, query: &str) -> serde_json::Value {
+    let res = client.get(format!("/api/v1/rooms/{room_id}/stats{query}")).dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    res.into_json().unwrap()
+}
+
+#[test]
+fn test_room_stats_empty_room() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "stats-empty");
+    let body = stats(&client, &room_id, "");
+    assert_eq!(body["room_name"], "stats-empty");
+    assert_eq!(body["messages"], 0);
+    assert_eq!(body["days"], 30);
+    let by_day = body["by_day"].as_array().unwrap();
+    assert_eq!(by_day.len(), 30);
+    assert!(by_day.iter().all(|d| d["messages"] == 0));
+    assert!(body["first_message_at"].is_null());
+    assert!(body["senders"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn test_room_stats_counts() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "stats-busy");
+    let (other_room, _) = create_test_room(&client, "stats-other");
+    let root = post_message(
+        &client,
+        &room_id,
+        serde_json::json!({"sender": "bot", "content": "deploying", "sender_type": "agent"}),
+    )["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    post_message(&client, &room_id, serde_json::json!({"sender": "bot", "content": "done", "sender_type": "agent"}));
+    post_message(
+        &client,
+        &room_id,
+        serde_json::json!({"sender": "nate", "content": "thanks", "sender_type": "human", "reply_to": root}),
+    );
+    post_message(&client, &other_room, serde_json::json!({"sender": "bot", "content": "elsewhere"}));
+    for sender in ["nate", "ann"] {
+        let res = client
+            .post(format!("/api/v1/rooms/{room_id}/messages/{root}/reactions"))
+            .header(ContentType::JSON)
+            .body(serde_json::json!({"sender": sender, "emoji": "🚀"}).to_string())
+            .dispatch();
+        assert_eq!(res.status(), Status::Ok);
+    }
+
+    let body = stats(&client, &room_id, "?days=7");
+    assert_eq!(body["messages"], 3);
+    assert_eq!(body["by_sender_type"]["agent"], 2);
+    assert_eq!(body["by_sender_type"]["human"], 1);
+    let by_day = body["by_day"].as_array().unwrap();
+    assert_eq!(by_day.len(), 7);
+    assert_eq!(by_day[6]["date"], chrono::Utc::now().date_naive().to_string());
+    assert_eq!(by_day[6]["messages"], 3);
+
+    let senders = body["senders"].as_array().unwrap();
+    assert_eq!(senders.len(), 2);
+    assert_eq!(senders[0]["sender"], "bot");
+    assert_eq!(senders[0]["messages"], 2);
+    assert_eq!(senders[0]["sender_type"], "agent");
+    assert_eq!(senders[0]["reactions_received"], 2);
+    assert_eq!(senders[1]["sender"], "nate");
+
+    assert_eq!(body["reactions"]["total"], 2);
+    assert_eq!(body["reactions"]["top"][0]["emoji"], "🚀");
+    assert_eq!(body["threads"]["threads"], 1);
+    assert_eq!(body["threads"]["replies"], 1);
+    assert_eq!(body["files"]["count"], 0);
+    assert_eq!(body["pinned"], 0);
+}
+
+#[test]
+fn test_room_stats_validation() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "stats-validation");
+    assert_eq!(client.get(format!("/api/v1/rooms/{room_id}/stats?days=0")).dispatch().status(), Status::BadRequest);
+    assert_eq!(client.get(format!("/api/v1/rooms/{room_id}/stats?days=400")).dispatch().status(), Status::BadRequest);
+    assert_eq!(client.get("/api/v1/rooms/nope/stats").dispatch().status(), Status::NotFound);
+}
@@ -0,0 +1,143 @@
+use crate::common::{create_test_room, post_message, test_client, TestClient};
+use rocket::http::{ContentType, Header, Status};
+use serde_json::json;
+
+fn subscribe(client: &TestClient, sender: &str, room_ids: &[&str]) -> (Status, serde_json::Value) {
+    let res = client
+        .put("/api/v1/subscriptions/rooms")
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"sender": sender, "room_ids": room_ids}).to_string())
+        .dispatch();
+    let status = res.status();
+    (status, res.into_json().unwrap_or(serde_json::Value::Null))
+}
+
+fn unread_rooms(client: &TestClient, query: &str) -> serde_json::Value {
+    let res = client.get(format!("/api/v1/unread?{query}")).dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    res.into_json().unwrap()
+}
+
+#[test]
+fn test_set_and_get_subscriptions() {
+    let client = test_client();
+    let (a, _) = create_test_room(&client, "subs-alpha");
+    let (b, _) = create_test_room(&client, "subs-beta");
+
+    let res = client.get("/api/v1/subscriptions/rooms?sender=watcher").dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["rooms"].as_array().unwrap().len(), 0);
+
+    let (status, body) = subscribe(&client, "watcher", &[&b, &a, &a]);
+    assert_eq!(status, Status::Ok);
+    let rooms = body["rooms"].as_array().unwrap();
+    assert_eq!(rooms.len(), 2);
+    assert_eq!(rooms[0]["room_name"], "subs-alpha");
+    assert_eq!(rooms[1]["room_id"], b.as_str());
+    let subscribed_at = rooms[0]["subscribed_at"].clone();
+
+    // Replacing keeps rooms that stay and drops the rest
+    let (_, body) = subscribe(&client, "watcher", &[&a]);
+    let rooms = body["rooms"].as_array().unwrap();
+    assert_eq!(rooms.len(), 1);
+    assert_eq!(rooms[0]["subscribed_at"], subscribed_at);
+
+    let (status, body) = subscribe(&client, "watcher", &[&a, "no-such-room"]);
+    assert_eq!(status, Status::NotFound);
+    assert_eq!(body["unknown_room_ids"], serde_json::json!(["no-such-room"]));
+
+    let (status, _) = subscribe(&client, "", &[&a]);
+    assert_eq!(status, Status::BadRequest);
+    let res = client.get("/api/v1/subscriptions/rooms").dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+
+    let (_, body) = subscribe(&client, "watcher", &[]);
+    assert_eq!(body["rooms"].as_array().unwrap().len(), 0);
+}
+
+#[test]
+fn test_unread_defaults_to_subscribed_rooms() {
+    let client = test_client();
+    let (a, _) = create_test_room(&client, "subs-unread-a");
+    let (b, b_key) = create_test_room(&client, "subs-unread-b");
+    post_message(&client, &a, json!({"sender": "poster", "content": "hi"}));
+    post_message(&client, &b, json!({"sender": "poster", "content": "hi"}));
+    post_message(&client, &b, json!({"sender": "poster", "content": "hi"}));
+
+    let body = unread_rooms(&client, "sender=reader");
+    assert_eq!(body["subscribed_only"], false);
+    assert_eq!(body["total_unread"], 3);
+
+    subscribe(&client, "reader", &[&b]);
+    let body = unread_rooms(&client, "sender=reader");
+    assert_eq!(body["subscribed_only"], true);
+    let rooms = body["rooms"].as_array().unwrap();
+    assert_eq!(rooms.len(), 1);
+    assert_eq!(rooms[0]["room_id"], b.as_str());
+    assert_eq!(body["total_unread"], 2);
+
+    let body = unread_rooms(&client, "sender=reader&all=true");
+    assert_eq!(body["subscribed_only"], false);
+    assert_eq!(body["total_unread"], 3);
+
+    // Deleting a room drops it from everyone's subscriptions
+    let res = client
+        .delete(format!("/api/v1/rooms/{b}"))
+        .header(Header::new("Authorization", format!("Bearer {b_key}")))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let res = client.get("/api/v1/subscriptions/rooms?sender=reader").dispatch();
+    assert_eq!(res.into_json::<serde_json::Value>().unwrap()["rooms"].as_array().unwrap().len(), 0);
+    assert_eq!(unread_rooms(&client, "sender=reader")["subscribed_only"], false);
+}
+
+#[test]
+fn test_aliases_share_subscriptions() {
+    let client = test_client();
+    let (a, _) = create_test_room(&client, "subs-alias-a");
+    create_test_room(&client, "subs-alias-b");
+    let res = client
+        .put("/api/v1/profiles/subs-bot")
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"aliases": ["subs-bot-v2"]}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    let (_, body) = subscribe(&client, "subs-bot-v2", &[&a]);
+    assert_eq!(body["sender"], "subs-bot");
+
+    let body = unread_rooms(&client, "sender=subs-bot");
+    assert_eq!(body["subscribed_only"], true);
+    assert_eq!(body["rooms"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn test_unread_threads_default_to_subscribed_rooms() {
+    let client = test_client();
+    let (a, _) = create_test_room(&client, "subs-threads-a");
+    let (b, _) = create_test_room(&client, "subs-threads-b");
+    let root_a =
+        post_message(&client, &a, json!({"sender": "asker", "content": "hi"}))["id"].as_str().unwrap().to_string();
+    post_message(&client, &a, json!({"sender": "helper", "content": "hi", "reply_to": &root_a}));
+    let root_b =
+        post_message(&client, &b, json!({"sender": "asker", "content": "hi"}))["id"].as_str().unwrap().to_string();
+    post_message(&client, &b, json!({"sender": "helper", "content": "hi", "reply_to": &root_b}));
+
+    subscribe(&client, "asker", &[&a]);
+    let res = client.get("/api/v1/unread/threads?sender=asker").dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["subscribed_only"], true);
+    let threads = body["threads"].as_array().unwrap();
+    assert_eq!(threads.len(), 1);
+    assert_eq!(threads[0]["root_id"], root_a.as_str());
+
+    // An explicit room or all=true bypasses the subscriptions
+    let res = client.get(format!("/api/v1/unread/threads?sender=asker&room_id={b}")).dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["subscribed_only"], false);
+    assert_eq!(body["threads"][0]["root_id"], root_b.as_str());
+    let res = client.get("/api/v1/unread/threads?sender=asker&all=true").dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["threads"].as_array().unwrap().len(), 2);
+}
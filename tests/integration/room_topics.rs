@@ -0,0 +1,91 @@
+use crate::common::{create_test_room, test_client};
+use rocket::http::{ContentType, Status};
+use rocket::local::blocking::Client;
+
+fn set_topic(client: &Client, room_id: &str, sender: &str, topic: &str) -> (Status, serde_json::Value) {
+    let res = client
+        .put(format!("/api/v1/rooms/{room_id}/topic"))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"sender": sender, "topic": topic}).to_string())
+        .dispatch();
+    let status = res.status();
+    (status, res.into_json().unwrap_or_default())
+}
+
+#[test]
+fn test_any_participant_sets_topic() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "topic-room");
+
+    let res = client.get(format!("/api/v1/rooms/{room_id}/topic")).dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+
+    // No admin key needed
+    let (status, topic) = set_topic(&client, &room_id, "alice", "  Release   freeze until Friday ");
+    assert_eq!(status, Status::Ok);
+    assert_eq!(topic["topic"], "Release freeze until Friday");
+    assert_eq!(topic["set_by"], "alice");
+
+    let current: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/topic"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(current["topic"], "Release freeze until Friday");
+    let room: serde_json::Value = client.get(format!("/api/v1/rooms/{room_id}")).dispatch().into_json().unwrap();
+    assert_eq!(room["topic"], "Release freeze until Friday");
+    assert!(room["description"].as_str().unwrap().is_empty());
+    let rooms: serde_json::Value = client.get("/api/v1/rooms").dispatch().into_json().unwrap();
+    let listed = rooms.as_array().unwrap().iter().find(|r| r["id"] == room_id.as_str()).unwrap();
+    assert_eq!(listed["topic"], "Release freeze until Friday");
+}
+
+#[test]
+fn test_topic_history_and_clearing() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "topic-history");
+    set_topic(&client, &room_id, "alice", "v1 planning");
+    set_topic(&client, &room_id, "bob", "v2 planning");
+    // Same topic again adds nothing
+    let (status, same) = set_topic(&client, &room_id, "carol", "v2 planning");
+    assert_eq!(status, Status::Ok);
+    assert_eq!(same["set_by"], "bob");
+    let (status, cleared) = set_topic(&client, &room_id, "carol", "");
+    assert_eq!(status, Status::Ok);
+    assert!(cleared["topic"].is_null());
+
+    let history: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/topic/history"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let history = history.as_array().unwrap();
+    assert_eq!(history.len(), 3);
+    assert!(history[0]["topic"].is_null());
+    assert_eq!(history[0]["set_by"], "carol");
+    assert_eq!(history[1]["topic"], "v2 planning");
+    assert_eq!(history[2]["topic"], "v1 planning");
+
+    let limited: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/topic/history?limit=1"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(limited.as_array().unwrap().len(), 1);
+
+    let res = client.get(format!("/api/v1/rooms/{room_id}/topic")).dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+    let room: serde_json::Value = client.get(format!("/api/v1/rooms/{room_id}")).dispatch().into_json().unwrap();
+    assert!(room.get("topic").is_none());
+}
+
+#[test]
+fn test_topic_validation() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "topic-errors");
+    assert_eq!(set_topic(&client, &room_id, "", "hi").0, Status::BadRequest);
+    assert_eq!(set_topic(&client, &room_id, "alice", &"x".repeat(301)).0, Status::BadRequest);
+    assert_eq!(set_topic(&client, "missing", "alice", "hi").0, Status::NotFound);
+    let res = client.get("/api/v1/rooms/missing/topic/history").dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+}
@@ -361,3 +361,84 @@ fn test_update_room_updates_timestamp() {
     let updated: serde_json::Value = res.into_json().unwrap();
     assert_ne!(updated["updated_at"].as_str().unwrap(), original_updated);
 }
+
+#[test]
+fn test_room_icon_and_color() {
+    let client = test_client();
+    let (room_id, admin_key) = crate::common::create_test_room(&client, "themed-room");
+    let update = |body: serde_json::Value| {
+        client
+            .put(format!("/api/v1/rooms/{room_id}"))
+            .header(ContentType::JSON)
+            .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+            .body(body.to_string())
+            .dispatch()
+    };
+
+    let res = update(serde_json::json!({"icon": ":rocket:", "color": "#38F"}));
+    assert_eq!(res.status(), Status::Ok);
+    let room: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(room["icon"], "🚀");
+    assert_eq!(room["color"], "#3388ff");
+    assert!(room.get("icon_url").is_none());
+
+    let rooms: Vec<serde_json::Value> = client.get("/api/v1/rooms").dispatch().into_json().unwrap();
+    let listed = rooms.iter().find(|r| r["id"] == room_id.as_str()).unwrap();
+    assert_eq!(listed["icon"], "🚀");
+    assert_eq!(listed["color"], "#3388ff");
+
+    assert_eq!(update(serde_json::json!({"icon": "not an emoji"})).status(), Status::BadRequest);
+    assert_eq!(update(serde_json::json!({"color": "blue"})).status(), Status::BadRequest);
+
+    // An image uploaded to the room can be the icon
+    let file: serde_json::Value = client
+        .post(format!("/api/v1/rooms/{room_id}/files"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "designer", "filename": "logo.png", "content_type": "image/png", "data": "iVBORw0KGgo="}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let file_id = file["id"].as_str().unwrap();
+    let room: serde_json::Value = update(serde_json::json!({"icon": file_id})).into_json().unwrap();
+    assert_eq!(room["icon"], file_id);
+    assert_eq!(room["icon_url"], format!("/api/v1/files/{file_id}"));
+
+    let room: serde_json::Value = update(serde_json::json!({"icon": null, "color": null})).into_json().unwrap();
+    assert!(room.get("icon").is_none());
+    assert!(room.get("color").is_none());
+}
+
+#[test]
+fn test_get_room_seq_range_and_participants() {
+    let client = test_client();
+    let (room_id, _) = crate::common::create_test_room(&client, "seq-range");
+
+    let room: serde_json::Value = client.get(format!("/api/v1/rooms/{room_id}")).dispatch().into_json().unwrap();
+    assert!(room.get("first_seq").is_none());
+    assert!(room.get("first_message_at").is_none());
+    assert_eq!(room["participant_count"], 0);
+
+    let mut sent = Vec::new();
+    for sender in ["alice", "bob", "alice"] {
+        let msg: serde_json::Value = client
+            .post(format!("/api/v1/rooms/{room_id}/messages"))
+            .header(ContentType::JSON)
+            .body(format!(r#"{{"sender": "{sender}", "content": "hello"}}"#))
+            .dispatch()
+            .into_json()
+            .unwrap();
+        sent.push(msg);
+    }
+
+    let room: serde_json::Value = client.get(format!("/api/v1/rooms/{room_id}")).dispatch().into_json().unwrap();
+    assert_eq!(room["first_seq"], sent[0]["seq"]);
+    assert_eq!(room["latest_seq"], sent[2]["seq"]);
+    assert_eq!(room["first_message_at"], sent[0]["created_at"]);
+    assert_eq!(room["participant_count"], 2);
+    assert_eq!(room["message_count"], 3);
+
+    // List entries stay lean
+    let rooms: Vec<serde_json::Value> = client.get("/api/v1/rooms").dispatch().into_json().unwrap();
+    let listed = rooms.iter().find(|r| r["id"] == room_id.as_str()).unwrap();
+    assert!(listed.get("first_seq").is_none());
+}
@@ -0,0 +1,161 @@
+use crate::common::{create_test_room, test_client};
+use chrono::Utc;
+use local_agent_chat::scheduler::deliver_due;
+use rocket::http::{ContentType, Header, Status};
+use rocket::local::blocking::Client;
+
+fn schedule(client: &Client, room_id: &str, sender: &str, sender_type: &str, at: &str) -> (Status, serde_json::Value) {
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .body(
+            serde_json::json!({"sender": sender, "sender_type": sender_type, "content": "standup in 5", "scheduled_at": at})
+                .to_string(),
+        )
+        .dispatch();
+    (res.status(), res.into_json().unwrap())
+}
+
+fn messages(client: &Client, room_id: &str) -> Vec<serde_json::Value> {
+    client
+        .get(format!("/api/v1/rooms/{room_id}/messages"))
+        .dispatch()
+        .into_json()
+        .unwrap()
+}
+
+fn make_due(conn: &rusqlite::Connection, id: &str) {
+    let past = (Utc::now() - chrono::Duration::seconds(5)).to_rfc3339();
+    conn.execute("UPDATE deferred_messages SET scheduled_at = ?1 WHERE id = ?2", rusqlite::params![past, id])
+        .unwrap();
+}
+
+#[test]
+fn test_scheduled_message_delivered_when_due() {
+    let client = test_client();
+    let (room_id, key) = create_test_room(&client, "scheduled-room");
+
+    let later = (Utc::now() + chrono::Duration::hours(1)).to_rfc3339();
+    for bad in [
+        "tomorrow morning".to_string(),
+        (Utc::now() - chrono::Duration::minutes(1)).to_rfc3339(),
+        (Utc::now() + chrono::Duration::days(400)).to_rfc3339(),
+    ] {
+        let (status, _) = schedule(&client, &room_id, "reminder-bot", "agent", &bad);
+        assert_eq!(status, Status::BadRequest, "{bad}");
+    }
+
+    let (status, reminder) = schedule(&client, &room_id, "reminder-bot", "agent", &later);
+    assert_eq!(status, Status::Accepted);
+    assert_eq!(reminder["deferred"], true);
+    assert_eq!(reminder["scheduled_at"], reminder["deliver_at"]);
+    let (_, followup) = schedule(&client, &room_id, "reminder-bot", "agent", &later);
+
+    // Waiting, and not mistaken for a quiet hours hold
+    assert!(messages(&client, &room_id).is_empty());
+    let pending: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/scheduled?sender=reminder-bot"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(pending["count"], 2);
+    let queue: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/quiet-hours/queue"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(queue["count"], 0);
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    assert!(deliver_due(&conn).is_empty());
+
+    // Only the sender or the room admin can cancel
+    let cancel = format!("/api/v1/rooms/{room_id}/scheduled/{}", followup["id"].as_str().unwrap());
+    assert_eq!(client.delete(cancel.as_str()).dispatch().status(), Status::BadRequest);
+    assert_eq!(client.delete(format!("{cancel}?sender=mallory")).dispatch().status(), Status::Forbidden);
+    let res = client
+        .delete(cancel.as_str())
+        .header(Header::new("Authorization", format!("Bearer {key}")))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    assert_eq!(client.delete(format!("{cancel}?sender=reminder-bot")).dispatch().status(), Status::NotFound);
+
+    make_due(&conn, reminder["id"].as_str().unwrap());
+    let delivered = deliver_due(&conn);
+    assert_eq!(delivered.len(), 1);
+    assert_eq!(delivered[0].0.id, reminder["id"].as_str().unwrap());
+    let msgs = messages(&client, &room_id);
+    assert_eq!(msgs.len(), 1);
+    assert_eq!(msgs[0]["metadata"]["scheduled"]["queued_at"], reminder["queued_at"]);
+    assert!(deliver_due(&conn).is_empty());
+}
+
+#[test]
+fn test_scheduled_agent_post_respects_quiet_hours() {
+    let client = test_client();
+    let (room_id, key) = create_test_room(&client, "scheduled-quiet");
+    let now = Utc::now();
+    let res = client
+        .put(format!("/api/v1/rooms/{room_id}/quiet-hours"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {key}")))
+        .body(
+            serde_json::json!({
+                "start": (now - chrono::Duration::hours(1)).format("%H:%M").to_string(),
+                "end": (now + chrono::Duration::hours(2)).format("%H:%M").to_string(),
+            })
+            .to_string(),
+        )
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    let later = (now + chrono::Duration::minutes(30)).to_rfc3339();
+    let (_, agent_post) = schedule(&client, &room_id, "reminder-bot", "agent", &later);
+    let (_, human_post) = schedule(&client, &room_id, "alice", "human", &later);
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    make_due(&conn, agent_post["id"].as_str().unwrap());
+    make_due(&conn, human_post["id"].as_str().unwrap());
+
+    // The human's goes out; the agent's now waits for the window like any agent post
+    let delivered = deliver_due(&conn);
+    assert_eq!(delivered.len(), 1);
+    assert_eq!(delivered[0].0.sender, "alice");
+    let queue: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/quiet-hours/queue"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(queue["count"], 1);
+    assert_eq!(queue["messages"][0]["id"], agent_post["id"]);
+}
+
+#[test]
+fn test_calendar_feed_lists_scheduled_posts_and_snapshots() {
+    let client = test_client();
+    let (room_id, key) = create_test_room(&client, "calendar-room");
+
+    let at = Utc::now() + chrono::Duration::hours(2);
+    let (_, reminder) = schedule(&client, &room_id, "reminder-bot", "agent", &at.to_rfc3339());
+    let res = client
+        .put(format!("/api/v1/rooms/{room_id}/snapshot-schedule"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {key}")))
+        .body(r#"{"cron": "@daily"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    let res = client.get(format!("/api/v1/rooms/{room_id}/calendar.ics")).dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    assert_eq!(res.content_type(), Some(ContentType::new("text", "calendar")));
+    let ics = res.into_string().unwrap();
+    assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+    assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+    assert!(ics.contains(&format!("UID:{}@local-agent-chat", reminder["id"].as_str().unwrap())));
+    assert!(ics.contains(&format!("DTSTART:{}", at.format("%Y%m%dT%H%M%SZ"))));
+    assert!(ics.contains("SUMMARY:reminder-bot: standup in 5"));
+    assert!(ics.contains("SUMMARY:Room snapshot"));
+    assert!(ics.lines().all(|l| l.len() <= 75));
+
+    let res = client.get("/api/v1/rooms/no-such-room/calendar.ics").dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+}
@@ -0,0 +1,117 @@
+use crate::common::{admin_client, create_test_room, post_message};
+use local_agent_chat::db::{Db, DbConfig};
+use rocket::http::{Header, Status};
+use rocket::local::blocking::Client;
+use serde_json::json;
+
+fn search_count(client: &Client, q: &str) -> u64 {
+    let body: serde_json::Value = client
+        .get(format!("/api/v1/search?q={q}"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    body["count"].as_u64().unwrap()
+}
+
+fn index_report(client: &Client, repair: bool) -> serde_json::Value {
+    let req = if repair {
+        client.post("/api/v1/admin/search-index/repair")
+    } else {
+        client.get("/api/v1/admin/search-index")
+    };
+    let res = req.header(Header::new("X-Server-Token", "srv_secret")).dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    res.into_json().unwrap()
+}
+
+/// Knock the index out of step with the messages: one entry dropped, one stale, one orphan.
+fn damage_index(db_path: &str, dropped: &str, edited: &str) {
+    let conn = rusqlite::Connection::open(db_path).unwrap();
+    conn.execute("DELETE FROM messages_fts WHERE message_id = ?1", [dropped]).unwrap();
+    conn.execute("UPDATE messages SET content = 'walrus sighting' WHERE id = ?1", [edited])
+        .unwrap();
+    conn.execute(
+        "INSERT INTO messages_fts (message_id, sender, content) VALUES ('gone', 'ghost', 'phantom entry')",
+        [],
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_search_index_check_and_repair() {
+    let client = admin_client();
+    let (room_id, _) = create_test_room(&client, "fts-repair");
+    let dropped =
+        post_message(&client, &room_id, json!({"sender": "agent", "content": "kangaroo migration report"}))["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+    let edited = post_message(&client, &room_id, json!({"sender": "agent", "content": "platypus notes"}))["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    post_message(&client, &room_id, json!({"sender": "agent", "content": "koala census"}));
+
+    let clean = index_report(&client, false);
+    assert_eq!(clean["ok"], true);
+    assert_eq!(clean["missing"], 0);
+
+    damage_index(client.db_path(), &dropped, &edited);
+    assert_eq!(search_count(&client, "kangaroo"), 0);
+    assert_eq!(search_count(&client, "walrus"), 0);
+
+    let report = index_report(&client, false);
+    assert_eq!(report["ok"], false);
+    assert_eq!(report["repaired"], false);
+    assert_eq!(report["missing"], 1);
+    assert_eq!(report["outdated"], 1);
+    assert_eq!(report["orphaned"], 1);
+    // A check alone changes nothing
+    assert_eq!(search_count(&client, "kangaroo"), 0);
+
+    let repaired = index_report(&client, true);
+    assert_eq!(repaired["repaired"], true);
+    assert_eq!(repaired["ok"], true);
+    assert_eq!(repaired["missing"], 1);
+    assert_eq!(search_count(&client, "kangaroo"), 1);
+    assert_eq!(search_count(&client, "walrus"), 1);
+    assert_eq!(search_count(&client, "platypus"), 0);
+    assert_eq!(search_count(&client, "phantom"), 0);
+
+    let after = index_report(&client, false);
+    assert_eq!(after["ok"], true);
+    assert_eq!(after["missing"], 0);
+    assert_eq!(after["outdated"], 0);
+    assert_eq!(after["orphaned"], 0);
+}
+
+#[test]
+fn test_search_index_repaired_on_startup() {
+    let client = admin_client();
+    let (room_id, _) = create_test_room(&client, "fts-boot");
+    let dropped = post_message(&client, &room_id, json!({"sender": "agent", "content": "narwhal sighting"}))["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let edited = post_message(&client, &room_id, json!({"sender": "agent", "content": "octopus notes"}))["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    damage_index(client.db_path(), &dropped, &edited);
+
+    // Opening the database again runs the startup check
+    let db = Db::with_config(client.db_path(), &DbConfig::default());
+    let report = local_agent_chat::db::check_fts_index(&db.conn(), false).unwrap();
+    assert!(report.ok);
+    assert_eq!(report.missing + report.outdated + report.orphaned, 0);
+    assert_eq!(search_count(&client, "narwhal"), 1);
+}
+
+#[test]
+fn test_search_index_requires_server_token() {
+    let client = admin_client();
+    let res = client.get("/api/v1/admin/search-index").dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+    let res = client.post("/api/v1/admin/search-index/repair").dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+}
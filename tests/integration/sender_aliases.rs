@@ -0,0 +1,139 @@
+use crate::common::{create_test_room, post_message, test_client, TestClient};
+use rocket::http::{ContentType, Status};
+use serde_json::json;
+
+fn put_profile(client: &TestClient, sender: &str, body: serde_json::Value) -> (Status, serde_json::Value) {
+    let res = client
+        .put(format!("/api/v1/profiles/{sender}"))
+        .header(ContentType::JSON)
+        .body(body.to_string())
+        .dispatch();
+    let status = res.status();
+    (status, res.into_json().unwrap_or(serde_json::Value::Null))
+}
+
+fn get_json(client: &TestClient, url: &str) -> serde_json::Value {
+    let res = client.get(url).dispatch();
+    assert_eq!(res.status(), Status::Ok, "{url}");
+    res.into_json().unwrap()
+}
+
+#[test]
+fn test_profile_aliases_roundtrip() {
+    let client = test_client();
+    let (status, body) = put_profile(
+        &client,
+        "nanook",
+        serde_json::json!({"display_name": "Nanook", "aliases": ["Nanook", "nanook-v2", " NANOOK-V2 ", "nanook"]}),
+    );
+    assert_eq!(status, Status::Ok);
+    // Trimmed, deduplicated case-insensitively, and the canonical name itself dropped
+    assert_eq!(body["aliases"], serde_json::json!(["Nanook", "nanook-v2"]));
+
+    // Any alias, in any casing, resolves to the canonical profile
+    let body = get_json(&client, "/api/v1/profiles/NANOOK-v2");
+    assert_eq!(body["sender"], "nanook");
+    assert_eq!(body["aliases"], serde_json::json!(["Nanook", "nanook-v2"]));
+
+    // Updates without `aliases` keep them; [] clears them
+    let (_, body) = put_profile(&client, "nanook", serde_json::json!({"bio": "v3"}));
+    assert_eq!(body["aliases"].as_array().unwrap().len(), 2);
+    let (_, body) = put_profile(&client, "nanook", serde_json::json!({"aliases": []}));
+    assert!(body.get("aliases").is_none());
+    let res = client.get("/api/v1/profiles/nanook-v2").dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+}
+
+#[test]
+fn test_profile_alias_conflicts() {
+    let client = test_client();
+    put_profile(&client, "nanook", serde_json::json!({"aliases": ["nanook-v2"]}));
+    put_profile(&client, "forge", serde_json::json!({}));
+
+    // Another profile can't claim the alias, nor alias an existing profile
+    let (status, _) = put_profile(&client, "drift", serde_json::json!({"aliases": ["Nanook-V2"]}));
+    assert_eq!(status, Status::Conflict);
+    let (status, _) = put_profile(&client, "drift", serde_json::json!({"aliases": ["Forge"]}));
+    assert_eq!(status, Status::Conflict);
+    // An alias can't become a profile of its own
+    let (status, body) = put_profile(&client, "nanook-v2", serde_json::json!({}));
+    assert_eq!(status, Status::Conflict);
+    assert!(body["error"].as_str().unwrap().contains("nanook"));
+
+    let (status, _) = put_profile(&client, "drift", serde_json::json!({"aliases": [""]}));
+    assert_eq!(status, Status::BadRequest);
+    let too_many: Vec<String> = (0..21).map(|i| format!("drift-{i}")).collect();
+    let (status, _) = put_profile(&client, "drift", serde_json::json!({"aliases": too_many}));
+    assert_eq!(status, Status::BadRequest);
+
+    // Deleting the profile frees its aliases
+    assert_eq!(client.delete("/api/v1/profiles/nanook").dispatch().status(), Status::NoContent);
+    let (status, _) = put_profile(&client, "drift", serde_json::json!({"aliases": ["nanook-v2"]}));
+    assert_eq!(status, Status::Ok);
+}
+
+#[test]
+fn test_participants_fold_aliases() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "alias-participants");
+    put_profile(&client, "nanook", serde_json::json!({"display_name": "Nanook", "aliases": ["nanook-v2"]}));
+    post_message(&client, &room_id, json!({"sender": "nanook", "content": "one"}));
+    post_message(&client, &room_id, json!({"sender": "nanook-v2", "content": "two"}));
+    post_message(&client, &room_id, json!({"sender": "Nanook-V2", "content": "three"}));
+    post_message(&client, &room_id, json!({"sender": "forge", "content": "four"}));
+
+    let body = get_json(&client, &format!("/api/v1/rooms/{room_id}/participants"));
+    let participants = body.as_array().unwrap();
+    assert_eq!(participants.len(), 2);
+    let nanook = participants.iter().find(|p| p["sender"] == "nanook").unwrap();
+    assert_eq!(nanook["message_count"], 3);
+    assert_eq!(nanook["display_name"], "Nanook");
+    let mut posted_as: Vec<&str> = nanook["posted_as"].as_array().unwrap().iter().map(|v| v.as_str().unwrap()).collect();
+    posted_as.sort();
+    assert_eq!(posted_as, vec!["Nanook-V2", "nanook-v2"]);
+    let forge = participants.iter().find(|p| p["sender"] == "forge").unwrap();
+    assert!(forge.get("posted_as").is_none());
+
+    // Autocomplete suggests the canonical name when an alias matches
+    let body = get_json(&client, &format!("/api/v1/rooms/{room_id}/mentionables?prefix=nanook-"));
+    let candidates = body["candidates"].as_array().unwrap();
+    assert_eq!(candidates.len(), 1);
+    assert_eq!(candidates[0]["sender"], "nanook");
+}
+
+#[test]
+fn test_sender_filters_resolve_aliases() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "alias-search");
+    put_profile(&client, "nanook", serde_json::json!({"aliases": ["nanook-v2"]}));
+    post_message(&client, &room_id, json!({"sender": "nanook", "content": "glacier report alpha"}));
+    post_message(&client, &room_id, json!({"sender": "nanook-v2", "content": "glacier report beta"}));
+    post_message(&client, &room_id, json!({"sender": "forge", "content": "glacier report gamma"}));
+
+    for name in ["nanook", "nanook-v2"] {
+        let body = get_json(&client, &format!("/api/v1/search?q=glacier&sender={name}"));
+        assert_eq!(body["results"].as_array().unwrap().len(), 2, "search as {name}");
+        let body = get_json(&client, &format!("/api/v1/rooms/{room_id}/messages?sender={name}"));
+        assert_eq!(body.as_array().unwrap().len(), 2, "messages as {name}");
+    }
+    let body = get_json(&client, "/api/v1/search?q=glacier&sender=forge");
+    assert_eq!(body["results"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn test_mentions_resolve_aliases() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "alias-mentions");
+    put_profile(&client, "nanook", serde_json::json!({"aliases": ["nanook-v2"]}));
+    post_message(&client, &room_id, json!({"sender": "forge", "content": "@nanook-v2 can you check the build?"}));
+    post_message(&client, &room_id, json!({"sender": "drift", "content": "thanks @Nanook"}));
+    // Mentioning yourself under another name doesn't count
+    post_message(&client, &room_id, json!({"sender": "nanook-v2", "content": "note to @nanook: done"}));
+
+    for target in ["nanook", "nanook-v2"] {
+        let body = get_json(&client, &format!("/api/v1/mentions?target={target}"));
+        assert_eq!(body["count"], 2, "mentions of {target}");
+    }
+    let body = get_json(&client, "/api/v1/mentions/unread?target=nanook");
+    assert_eq!(body["total_unread"], 2);
+}
@@ -0,0 +1,113 @@
+use crate::common::{create_test_room, post_message, test_client, TestClient};
+use rocket::http::{ContentType, Status};
+use serde_json::json;
+use std::io::Read;
+
+fn entry(zip: &mut zip::ZipArchive<std::io::Cursor<Vec<u8>>>, name: &str) -> Vec<u8> {
+    let mut data = Vec::new();
+    zip.by_name(name).unwrap().read_to_end(&mut data).unwrap();
+    data
+}
+
+fn json_entry(zip: &mut zip::ZipArchive<std::io::Cursor<Vec<u8>>>, name: &str) -> serde_json::Value {
+    serde_json::from_slice(&entry(zip, name)).unwrap()
+}
+
+fn export(client: &TestClient, query: &str) -> zip::ZipArchive<std::io::Cursor<Vec<u8>>> {
+    let res = client.get(format!("/api/v1/senders/{query}")).dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    assert_eq!(res.content_type(), Some(ContentType::ZIP));
+    let disposition = res.headers().get_one("Content-Disposition").unwrap().to_string();
+    assert!(disposition.contains("sender-export-"), "{disposition}");
+    zip::ZipArchive::new(std::io::Cursor::new(res.into_bytes().unwrap())).unwrap()
+}
+
+#[test]
+fn test_sender_export_bundle() {
+    use base64::Engine;
+    let client = test_client();
+    let (a, _) = create_test_room(&client, "export-sender-a");
+    let (b, _) = create_test_room(&client, "export-sender-b");
+
+    let res = client
+        .put("/api/v1/profiles/export-bot")
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"display_name": "Export Bot", "aliases": ["export-bot-v1"]}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    let usage = json!({"usage": {"prompt_tokens": 5}});
+    post_message(&client, &a, json!({"sender": "export-bot", "content": "first in a", "metadata": usage}));
+    post_message(&client, &b, json!({"sender": "export-bot-v1", "content": "posted under an alias", "metadata": usage}));
+    let other = post_message(&client, &a, json!({"sender": "someone-else", "content": "not mine", "metadata": usage}))["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let res = client
+        .post(format!("/api/v1/rooms/{a}/messages/{other}/reactions"))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"sender": "export-bot", "emoji": "👍"}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let res = client
+        .post(format!("/api/v1/rooms/{b}/files"))
+        .header(ContentType::JSON)
+        .body(
+            serde_json::json!({
+                "sender": "export-bot",
+                "filename": "../notes.txt",
+                "content_type": "text/plain",
+                "data": base64::engine::general_purpose::STANDARD.encode(b"agent notes")
+            })
+            .to_string(),
+        )
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    // An alias exports the canonical sender's history
+    let mut zip = export(&client, "export-bot-v1/export");
+    let manifest = json_entry(&mut zip, "manifest.json");
+    assert_eq!(manifest["sender"], "export-bot");
+    assert_eq!(manifest["aliases"], serde_json::json!(["export-bot-v1"]));
+    assert_eq!(manifest["messages"], 2);
+    assert_eq!(manifest["reactions"], 1);
+    assert_eq!(manifest["files"], 1);
+    assert_eq!(manifest["rooms"].as_array().unwrap().len(), 2);
+
+    assert_eq!(json_entry(&mut zip, "profile.json")["display_name"], "Export Bot");
+
+    let lines: Vec<serde_json::Value> = String::from_utf8(entry(&mut zip, "messages.ndjson"))
+        .unwrap()
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["content"], "first in a");
+    assert_eq!(lines[0]["room_id"], a.as_str());
+    assert_eq!(lines[0]["room_name"], "export-sender-a");
+    assert_eq!(lines[0]["metadata"]["usage"]["prompt_tokens"], 5);
+    assert_eq!(lines[1]["sender"], "export-bot-v1");
+
+    let reactions = json_entry(&mut zip, "reactions.json");
+    assert_eq!(reactions[0]["message_id"], other.as_str());
+    assert_eq!(reactions[0]["emoji"], "👍");
+
+    let files = json_entry(&mut zip, "files.json");
+    let path = files[0]["path"].as_str().unwrap().to_string();
+    assert!(!path.contains(".."), "{path}");
+    assert_eq!(entry(&mut zip, &path), b"agent notes");
+
+    // Without file contents the manifest still lists them
+    let mut zip = export(&client, "export-bot/export?include_files=false");
+    let files = json_entry(&mut zip, "files.json");
+    assert_eq!(files[0]["filename"], "../notes.txt");
+    assert!(files[0].get("path").is_none());
+    assert_eq!(zip.file_names().filter(|n| n.starts_with("files/")).count(), 0);
+}
+
+#[test]
+fn test_sender_export_unknown_sender() {
+    let client = test_client();
+    let res = client.get("/api/v1/senders/nobody-here/export").dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+}
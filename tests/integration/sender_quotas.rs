@@ -0,0 +1,177 @@
+use crate::common::{admin_client, create_test_room, test_client, test_client_with_rate_limits, upload_file, TestClient};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use local_agent_chat::rate_limit::RateLimitConfig;
+use rocket::http::{ContentType, Header, Status};
+
+fn send(client: &TestClient, room_id: &str, sender: &str) -> (Status, serde_json::Value) {
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"sender": sender, "content": "hello"}).to_string())
+        .dispatch();
+    let status = res.status();
+    (status, res.into_json().unwrap_or(serde_json::Value::Null))
+}
+
+fn quota(client: &TestClient, sender: &str) -> serde_json::Value {
+    let res = client.get(format!("/api/v1/quota?sender={sender}")).dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    res.into_json().unwrap()
+}
+
+#[test]
+fn test_quota_unlimited_by_default() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "quota-default");
+    for _ in 0..3 {
+        assert_eq!(send(&client, &room_id, "free-agent").0, Status::Ok);
+    }
+    let body = quota(&client, "free-agent");
+    assert_eq!(body["sender"], "free-agent");
+    assert!(body["messages"]["limit"].is_null());
+    assert!(body["messages"]["remaining"].is_null());
+    assert_eq!(body["messages"]["used"], 3);
+    assert_eq!(body["messages"]["overridden"], false);
+    assert!(body["resets_at"].as_str().unwrap().contains("T00:00:00"));
+
+    let res = client.get("/api/v1/quota").dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+}
+
+#[test]
+fn test_daily_message_quota() {
+    let client = test_client_with_rate_limits(RateLimitConfig { sender_daily_messages: 3, ..Default::default() });
+    let (room_id, _) = create_test_room(&client, "quota-messages");
+
+    assert_eq!(send(&client, &room_id, "runaway").0, Status::Ok);
+    // DMs and broadcasts draw from the same budget
+    let res = client
+        .post("/api/v1/dm")
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"sender": "runaway", "recipient": "someone", "content": "hi"}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    let body = quota(&client, "runaway");
+    assert_eq!(body["messages"]["limit"], 3);
+    assert_eq!(body["messages"]["used"], 2);
+    assert_eq!(body["messages"]["remaining"], 1);
+
+    // A broadcast that doesn't fit is refused whole
+    let (other_room, _) = create_test_room(&client, "quota-messages-2");
+    let res = client
+        .post("/api/v1/broadcast")
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"sender": "runaway", "content": "all", "room_ids": [room_id, other_room]}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::TooManyRequests);
+    assert_eq!(quota(&client, "runaway")["messages"]["used"], 2);
+
+    assert_eq!(send(&client, &room_id, "runaway").0, Status::Ok);
+    let (status, body) = send(&client, &room_id, "runaway");
+    assert_eq!(status, Status::TooManyRequests);
+    assert_eq!(body["quota"], "messages");
+    assert_eq!(body["limit"], 3);
+    assert_eq!(body["used"], 3);
+    assert!(body["retry_after_secs"].as_i64().unwrap() > 0);
+    assert!(body["resets_at"].as_str().is_some());
+
+    let body = quota(&client, "runaway");
+    assert_eq!(body["messages"]["remaining"], 0);
+
+    // Other senders are unaffected
+    assert_eq!(send(&client, &room_id, "well-behaved").0, Status::Ok);
+}
+
+#[test]
+fn test_daily_upload_quota() {
+    let client = test_client_with_rate_limits(RateLimitConfig { sender_daily_upload_bytes: 10, ..Default::default() });
+    let (room_id, _) = create_test_room(&client, "quota-uploads");
+
+    assert_eq!(upload_file(&client, &room_id, serde_json::json!({"data": STANDARD.encode(b"123456")})).0, Status::Ok);
+    let (status, body) = upload_file(&client, &room_id, serde_json::json!({"data": STANDARD.encode(b"123456")}));
+    assert_eq!(status, Status::TooManyRequests);
+    assert_eq!(body["quota"], "upload_bytes");
+    assert_eq!(body["limit"], 10);
+    assert_eq!(body["used"], 6);
+
+    // A smaller file still fits
+    assert_eq!(upload_file(&client, &room_id, serde_json::json!({"data": STANDARD.encode(b"1234")})).0, Status::Ok);
+    let body = quota(&client, "uploader");
+    assert_eq!(body["upload_bytes"]["used"], 10);
+    assert_eq!(body["upload_bytes"]["remaining"], 0);
+    // Messages stay unlimited
+    assert!(body["messages"]["limit"].is_null());
+}
+
+#[test]
+fn test_aliases_share_a_budget() {
+    let client = test_client_with_rate_limits(RateLimitConfig { sender_daily_messages: 2, ..Default::default() });
+    let (room_id, _) = create_test_room(&client, "quota-aliases");
+    let res = client
+        .put("/api/v1/profiles/quota-bot")
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"aliases": ["quota-bot-v2"]}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    assert_eq!(send(&client, &room_id, "quota-bot").0, Status::Ok);
+    assert_eq!(send(&client, &room_id, "quota-bot-v2").0, Status::Ok);
+    assert_eq!(send(&client, &room_id, "quota-bot-v2").0, Status::TooManyRequests);
+
+    let body = quota(&client, "quota-bot-v2");
+    assert_eq!(body["sender"], "quota-bot");
+    assert_eq!(body["messages"]["used"], 2);
+}
+
+#[test]
+fn test_per_sender_override() {
+    let client = admin_client();
+    let (room_id, _) = create_test_room(&client, "quota-override");
+
+    let res = client
+        .put("/api/v1/quota/chatty")
+        .header(ContentType::JSON)
+        .body(r#"{"daily_messages": 1}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+
+    let res = client
+        .put("/api/v1/quota/chatty")
+        .header(ContentType::JSON)
+        .header(Header::new("X-Server-Token", "srv_secret"))
+        .body(r#"{"daily_messages": 1}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["messages"]["limit"], 1);
+    assert_eq!(body["messages"]["overridden"], true);
+    assert_eq!(body["upload_bytes"]["overridden"], false);
+
+    assert_eq!(send(&client, &room_id, "chatty").0, Status::Ok);
+    assert_eq!(send(&client, &room_id, "chatty").0, Status::TooManyRequests);
+    // The server default (unlimited) still applies to everyone else
+    assert_eq!(send(&client, &room_id, "quiet").0, Status::Ok);
+    assert_eq!(send(&client, &room_id, "quiet").0, Status::Ok);
+
+    let res = client
+        .put("/api/v1/quota/chatty")
+        .header(ContentType::JSON)
+        .header(Header::new("X-Server-Token", "srv_secret"))
+        .body(r#"{"daily_messages": -1}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+
+    // Clearing the override restores the default and keeps today's usage
+    let res = client
+        .put("/api/v1/quota/chatty")
+        .header(ContentType::JSON)
+        .header(Header::new("X-Server-Token", "srv_secret"))
+        .body(r#"{"daily_messages": null}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert!(body["messages"]["limit"].is_null());
+    assert_eq!(body["messages"]["used"], 1);
+    assert_eq!(send(&client, &room_id, "chatty").0, Status::Ok);
+}
@@ -0,0 +1,126 @@
+use rocket::http::{ContentType, Status};
+use rocket::local::blocking::Client;
+use serde_json::json;
+
+use crate::common::test_client;
+
+fn put_status(client: &Client, sender: &str, body: serde_json::Value) -> (Status, serde_json::Value) {
+    let res = client
+        .put(format!("/api/v1/status/{sender}"))
+        .header(ContentType::JSON)
+        .body(body.to_string())
+        .dispatch();
+    let status = res.status();
+    (status, res.into_json().unwrap_or(serde_json::Value::Null))
+}
+
+#[test]
+fn test_publish_and_get_status() {
+    let client = test_client();
+    let (status, body) = put_status(
+        &client,
+        "migrator",
+        json!({"state": "working", "task": "Migrating billing tables", "progress": 40, "eta": "2030-01-01T12:00:00+02:00", "details": {"table": "invoices"}}),
+    );
+    assert_eq!(status, Status::Ok);
+    assert_eq!(body["sender"], "migrator");
+    assert_eq!(body["progress"], 40.0);
+    assert_eq!(body["eta"], "2030-01-01T10:00:00+00:00");
+
+    let res = client.get("/api/v1/status/migrator").dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["state"], "working");
+    assert_eq!(body["task"], "Migrating billing tables");
+    assert_eq!(body["details"]["table"], "invoices");
+
+    // Updates are snapshots: omitted fields are cleared
+    put_status(&client, "migrator", json!({"state": "done"}));
+    let body: serde_json::Value = client.get("/api/v1/status/migrator").dispatch().into_json().unwrap();
+    assert_eq!(body["state"], "done");
+    assert!(body.get("task").is_none());
+    assert!(body.get("progress").is_none());
+
+    assert_eq!(client.get("/api/v1/status/nobody").dispatch().status(), Status::NotFound);
+}
+
+#[test]
+fn test_status_validation() {
+    let client = test_client();
+    assert_eq!(put_status(&client, "a", json!({"progress": 120})).0, Status::BadRequest);
+    assert_eq!(put_status(&client, "a", json!({"progress": -1})).0, Status::BadRequest);
+    assert_eq!(put_status(&client, "a", json!({"eta": "tomorrow"})).0, Status::BadRequest);
+    assert_eq!(put_status(&client, "a", json!({"details": [1, 2]})).0, Status::BadRequest);
+    assert_eq!(put_status(&client, "a", json!({"task": "x".repeat(501)})).0, Status::BadRequest);
+    assert_eq!(put_status(&client, "a", json!({})).0, Status::Ok);
+}
+
+#[test]
+fn test_status_dashboard() {
+    let client = test_client();
+    put_status(&client, "builder", json!({"state": "working", "task": "build"}));
+    put_status(&client, "tester", json!({"state": "blocked", "task": "waiting on build"}));
+    put_status(&client, "builder", json!({"state": "idle"}));
+
+    let res = client.get("/api/v1/status").dispatch();
+    let list: Vec<serde_json::Value> = res.into_json().unwrap();
+    assert_eq!(list.len(), 2);
+    // One entry per sender, latest first
+    assert_eq!(list[0]["sender"], "builder");
+    assert_eq!(list[0]["state"], "idle");
+    assert_eq!(list[1]["sender"], "tester");
+
+    let list: Vec<serde_json::Value> = client.get("/api/v1/status?state=blocked").dispatch().into_json().unwrap();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0]["sender"], "tester");
+
+    let list: Vec<serde_json::Value> = client.get("/api/v1/status?since=2999-01-01T00:00:00Z").dispatch().into_json().unwrap();
+    assert!(list.is_empty());
+
+    let body: serde_json::Value = client.get("/api/v1/status?envelope=true").dispatch().into_json().unwrap();
+    assert_eq!(body["items"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn test_status_history_and_clear() {
+    let client = test_client();
+    for p in [10, 50, 90] {
+        put_status(&client, "worker", json!({"state": "working", "progress": p}));
+    }
+    let history: Vec<serde_json::Value> = client.get("/api/v1/status/worker/history").dispatch().into_json().unwrap();
+    assert_eq!(history.len(), 3);
+    assert_eq!(history[0]["progress"], 90.0);
+    assert_eq!(history[2]["progress"], 10.0);
+    let history: Vec<serde_json::Value> = client.get("/api/v1/status/worker/history?limit=1").dispatch().into_json().unwrap();
+    assert_eq!(history.len(), 1);
+
+    assert_eq!(client.delete("/api/v1/status/worker").dispatch().status(), Status::NoContent);
+    assert_eq!(client.get("/api/v1/status/worker").dispatch().status(), Status::NotFound);
+    assert_eq!(client.delete("/api/v1/status/worker").dispatch().status(), Status::NotFound);
+}
+
+#[test]
+fn test_status_history_is_capped() {
+    let client = test_client();
+    for p in 0..105 {
+        put_status(&client, "chatty", json!({"progress": p % 100}));
+    }
+    let history: Vec<serde_json::Value> = client.get("/api/v1/status/chatty/history?limit=500").dispatch().into_json().unwrap();
+    assert_eq!(history.len(), 100);
+}
+
+#[test]
+fn test_status_resolves_aliases() {
+    let client = test_client();
+    let res = client
+        .put("/api/v1/profiles/nanook")
+        .header(ContentType::JSON)
+        .body(json!({"aliases": ["nanook-v1"]}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    let (_, body) = put_status(&client, "nanook-v1", json!({"task": "indexing"}));
+    assert_eq!(body["sender"], "nanook");
+    let body: serde_json::Value = client.get("/api/v1/status/nanook").dispatch().into_json().unwrap();
+    assert_eq!(body["task"], "indexing");
+}
@@ -0,0 +1,152 @@
+use crate::common::{create_test_room, test_client, TestClient};
+use rocket::http::{ContentType, Status};
+
+fn send(client: &TestClient, room_id: &str, body: serde_json::Value) -> (Status, serde_json::Value) {
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .body(body.to_string())
+        .dispatch();
+    let status = res.status();
+    (status, res.into_json().unwrap_or(serde_json::Value::Null))
+}
+
+fn seconds_until(at: &serde_json::Value) -> i64 {
+    let at = chrono::DateTime::parse_from_rfc3339(at.as_str().unwrap()).unwrap();
+    at.signed_duration_since(chrono::Utc::now()).num_seconds()
+}
+
+fn message(client: &TestClient, room_id: &str, id: &str) -> serde_json::Value {
+    let messages: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/messages"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    messages.as_array().unwrap().iter().find(|m| m["id"] == id).cloned().unwrap()
+}
+
+#[test]
+fn test_sensitive_sets_redact_at() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "sensitive-schedule");
+
+    let (status, msg) = send(
+        &client,
+        &room_id,
+        serde_json::json!({"sender": "oncall", "content": "temp pw hunter2", "sensitive": true, "redact_after_secs": 600}),
+    );
+    assert_eq!(status, Status::Ok);
+    assert_eq!(msg["content"], "temp pw hunter2");
+    assert!((590..=600).contains(&seconds_until(&msg["metadata"]["sensitive"]["redact_at"])));
+
+    // Without redact_after_secs the server default (an hour) applies
+    let (_, msg) = send(
+        &client,
+        &room_id,
+        serde_json::json!({"sender": "oncall", "content": "token abc", "sensitive": true, "metadata": {"incident": 7}}),
+    );
+    assert!((3590..=3600).contains(&seconds_until(&msg["metadata"]["sensitive"]["redact_at"])));
+    assert_eq!(msg["metadata"]["incident"], 7);
+
+    // Ordinary messages carry no marker
+    let (_, msg) = send(&client, &room_id, serde_json::json!({"sender": "oncall", "content": "all clear"}));
+    assert!(msg["metadata"].get("sensitive").is_none());
+
+    let (status, _) = send(
+        &client,
+        &room_id,
+        serde_json::json!({"sender": "oncall", "content": "x", "redact_after_secs": 600}),
+    );
+    assert_eq!(status, Status::BadRequest);
+    let (status, _) = send(
+        &client,
+        &room_id,
+        serde_json::json!({"sender": "oncall", "content": "x", "sensitive": true, "redact_after_secs": 5}),
+    );
+    assert_eq!(status, Status::BadRequest);
+}
+
+#[test]
+fn test_due_sensitive_message_is_redacted() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "sensitive-redact");
+
+    let (_, secret) = send(
+        &client,
+        &room_id,
+        serde_json::json!({"sender": "oncall", "content": "db password zebra42", "sensitive": true}),
+    );
+    let secret_id = secret["id"].as_str().unwrap();
+    let res = client
+        .put(format!("/api/v1/rooms/{room_id}/messages/{secret_id}"))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"sender": "oncall", "content": "db password zebra43"}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let (_, reply) = send(
+        &client,
+        &room_id,
+        serde_json::json!({"sender": "responder", "content": "thanks, rotating now", "reply_to": secret_id}),
+    );
+
+    // Not due yet: the sweep leaves it alone
+    let result: serde_json::Value = client.post("/api/v1/admin/retention/run").dispatch().into_json().unwrap();
+    assert_eq!(result["messages_redacted"], 0);
+
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    conn.execute(
+        "UPDATE sensitive_messages SET redact_at = '2000-01-01T00:00:00+00:00' WHERE message_id = ?1",
+        [secret_id],
+    )
+    .unwrap();
+    let result: serde_json::Value = client.post("/api/v1/admin/retention/run").dispatch().into_json().unwrap();
+    assert_eq!(result["messages_redacted"], 1);
+
+    let msg = message(&client, &room_id, secret_id);
+    assert_eq!(msg["content"], "[sensitive content redacted]");
+    assert!(msg["metadata"]["sensitive"]["redacted_at"].is_string());
+    assert_eq!(msg["seq"], secret["seq"]);
+    // The thread keeps its shape
+    assert_eq!(message(&client, &room_id, reply["id"].as_str().unwrap())["reply_to"], secret_id);
+
+    // Earlier versions and the search index lose the text too
+    let edits: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/messages/{secret_id}/edits"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(edits["edits"].as_array().unwrap().len(), 0);
+    let search: serde_json::Value = client.get("/api/v1/search?q=password").dispatch().into_json().unwrap();
+    assert_eq!(search["count"], 0);
+
+    // Redaction happens once
+    let result: serde_json::Value = client.post("/api/v1/admin/retention/run").dispatch().into_json().unwrap();
+    assert_eq!(result["messages_redacted"], 0);
+}
+
+#[test]
+fn test_redaction_drops_mentions() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "sensitive-mentions");
+
+    let (_, secret) = send(
+        &client,
+        &room_id,
+        serde_json::json!({"sender": "oncall", "content": "@dba the root password is zebra42", "sensitive": true}),
+    );
+    let mentions: serde_json::Value = client.get("/api/v1/mentions?target=dba").dispatch().into_json().unwrap();
+    assert_eq!(mentions["count"], 1);
+
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    conn.execute(
+        "UPDATE sensitive_messages SET redact_at = '2000-01-01T00:00:00+00:00' WHERE message_id = ?1",
+        [secret["id"].as_str().unwrap()],
+    )
+    .unwrap();
+    let result: serde_json::Value = client.post("/api/v1/admin/retention/run").dispatch().into_json().unwrap();
+    assert_eq!(result["messages_redacted"], 1);
+
+    // The tombstone mentions nobody
+    let mentions: serde_json::Value = client.get("/api/v1/mentions?target=dba").dispatch().into_json().unwrap();
+    assert_eq!(mentions["count"], 0);
+}
@@ -0,0 +1,178 @@
+use crate::common::{policy_with_token, test_client_with_sender_policy};
+use rocket::http::{ContentType, Header, Status};
+use serde_json::json;
+
+fn config() -> serde_json::Value {
+    json!({
+        "version": 1,
+        "rooms": [{
+            "name": "ops",
+            "description": "Operations",
+            "tags": ["Infra"],
+            "color": "#0af",
+            "retention": {"max_messages": 500},
+            "welcome": {"template": "Hi {sender}", "delivery": "room"},
+            "quiet_hours": {"start": "22:00", "end": "06:00", "timezone": "Europe/Berlin"},
+            "webhooks": [{"url": "http://127.0.0.1:9/hook", "events": "message", "secret": "s3cret"}],
+            "incoming_webhooks": [{"name": "ci", "rate_limit": 30, "token": "whk_configured_token_01"}]
+        }],
+        "server_webhooks": [{"url": "http://127.0.0.1:9/provision", "events": "room_created"}]
+    })
+}
+
+fn apply(client: &rocket::local::blocking::Client, body: &serde_json::Value) -> (Status, serde_json::Value) {
+    let res = client
+        .put("/api/v1/admin/config/export")
+        .header(ContentType::JSON)
+        .header(Header::new("X-Server-Token", "srv_secret"))
+        .body(body.to_string())
+        .dispatch();
+    (res.status(), res.into_json().unwrap())
+}
+
+fn export(client: &rocket::local::blocking::Client, query: &str) -> serde_json::Value {
+    let res = client
+        .get(format!("/api/v1/admin/config/export{query}"))
+        .header(Header::new("Authorization", "Bearer srv_secret"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    res.into_json().unwrap()
+}
+
+#[test]
+fn test_config_apply_export_round_trip() {
+    let client = test_client_with_sender_policy(policy_with_token());
+    let (status, result) = apply(&client, &config());
+    assert_eq!(status, Status::Ok);
+    assert_eq!(result["rooms_created"], json!(["ops"]));
+    assert!(result["admin_keys"]["ops"].as_str().unwrap().starts_with("chat_"));
+    assert_eq!(result["webhooks"], 1);
+    assert_eq!(result["server_webhooks"], 1);
+
+    // Applying the same document again only updates
+    let (status, result) = apply(&client, &config());
+    assert_eq!(status, Status::Ok);
+    assert_eq!(result["rooms_created"], json!([]));
+    assert_eq!(result["rooms_updated"], json!(["ops"]));
+    assert!(result["admin_keys"].as_object().unwrap().is_empty());
+
+    // Secrets stay out unless asked for
+    let exported = export(&client, "");
+    assert_eq!(exported["version"], 1);
+    let room = &exported["rooms"][0];
+    assert_eq!(room["name"], "ops");
+    assert_eq!(room["tags"], json!(["infra"]));
+    assert_eq!(room["color"], "#00aaff");
+    assert_eq!(room["retention"]["max_messages"], 500);
+    assert_eq!(room["welcome"]["delivery"], "room");
+    assert_eq!(room["quiet_hours"]["timezone"], "Europe/Berlin");
+    assert_eq!(room["webhooks"].as_array().unwrap().len(), 1);
+    assert!(room["webhooks"][0].get("secret").is_none());
+    assert!(room["incoming_webhooks"][0].get("token").is_none());
+    assert_eq!(room["incoming_webhooks"][0]["rate_limit"], 30);
+
+    let full = export(&client, "?include_secrets=true");
+    assert_eq!(full["rooms"][0]["webhooks"][0]["secret"], "s3cret");
+    assert_eq!(full["rooms"][0]["incoming_webhooks"][0]["token"], "whk_configured_token_01");
+
+    // The full export recreates the same setup on a fresh server
+    let other = test_client_with_sender_policy(policy_with_token());
+    let (status, result) = apply(&other, &full);
+    assert_eq!(status, Status::Ok);
+    assert_eq!(result["rooms_created"], json!(["ops"]));
+    let mut copy = export(&other, "?include_secrets=true");
+    let mut original = full.clone();
+    for doc in [&mut copy, &mut original] {
+        doc.as_object_mut().unwrap().remove("exported_at");
+    }
+    assert_eq!(copy, original);
+
+    // Dropping things from the document removes them from the room
+    let mut trimmed = config();
+    let room = &mut trimmed["rooms"][0];
+    room.as_object_mut().unwrap().remove("welcome");
+    room["webhooks"] = json!([]);
+    let (status, result) = apply(&client, &trimmed);
+    assert_eq!(status, Status::Ok);
+    assert_eq!(result["webhooks"], 0);
+    let exported = export(&client, "");
+    assert!(exported["rooms"][0].get("welcome").is_none());
+    assert_eq!(exported["rooms"][0]["webhooks"], json!([]));
+}
+
+#[test]
+fn test_config_rejects_bad_documents_without_changes() {
+    let client = test_client_with_sender_policy(policy_with_token());
+    let res = client
+        .put("/api/v1/admin/config/export")
+        .header(ContentType::JSON)
+        .body(config().to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+    let res = client.get("/api/v1/admin/config/export").dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+
+    let mut body = config();
+    body["version"] = json!(2);
+    let (status, _) = apply(&client, &body);
+    assert_eq!(status, Status::BadRequest);
+
+    // A bad second room fails the whole document before anything is written
+    let mut body = config();
+    body["rooms"]
+        .as_array_mut()
+        .unwrap()
+        .push(json!({"name": "broken", "webhooks": [{"url": "ftp://nope"}]}));
+    let (status, error) = apply(&client, &body);
+    assert_eq!(status, Status::BadRequest);
+    assert!(error["error"].as_str().unwrap().starts_with("Room 'broken': "));
+    assert_eq!(export(&client, "")["rooms"], json!([]));
+
+    let mut body = config();
+    let room = body["rooms"][0].clone();
+    body["rooms"].as_array_mut().unwrap().push(room);
+    let (status, _) = apply(&client, &body);
+    assert_eq!(status, Status::BadRequest);
+}
+
+#[test]
+fn test_config_round_trips_private_rooms() {
+    let client = test_client_with_sender_policy(policy_with_token());
+    let doc = json!({
+        "version": 1,
+        "rooms": [{
+            "name": "vault",
+            "visibility": "private",
+            "members": [{"sender": "agent-a", "added_by": "lead", "token": "mem_configured_token_01"}]
+        }]
+    });
+    let (status, _) = apply(&client, &doc);
+    assert_eq!(status, Status::Ok);
+
+    let exported = export(&client, "");
+    assert_eq!(exported["rooms"][0]["visibility"], "private");
+    assert_eq!(exported["rooms"][0]["members"][0]["sender"], "agent-a");
+    assert!(exported["rooms"][0]["members"][0].get("token").is_none());
+
+    // A fresh server gets the room back private, with the member's token intact
+    let full = export(&client, "?include_secrets=true");
+    let other = test_client_with_sender_policy(policy_with_token());
+    let (status, result) = apply(&other, &full);
+    assert_eq!(status, Status::Ok);
+    assert_eq!(result["rooms_created"], json!(["vault"]));
+    let rooms: Vec<serde_json::Value> = other
+        .get("/api/v1/rooms")
+        .header(Header::new("X-Member-Token", "mem_configured_token_01"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let room = rooms.iter().find(|r| r["name"] == "vault").unwrap();
+    assert_eq!(room["visibility"], "private");
+    let res = other.get(format!("/api/v1/rooms/{}/messages", room["id"].as_str().unwrap())).dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+
+    // Dropping the members from the document removes them
+    let (status, _) = apply(&client, &json!({"version": 1, "rooms": [{"name": "vault", "visibility": "private"}]}));
+    assert_eq!(status, Status::Ok);
+    assert!(export(&client, "")["rooms"][0].get("members").is_none());
+}
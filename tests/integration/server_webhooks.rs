@@ -0,0 +1,106 @@
+use crate::common::{policy_with_token, test_client, test_client_with_sender_policy};
+use rocket::http::{ContentType, Header, Status};
+use serde_json::json;
+
+#[test]
+fn test_server_webhook_crud() {
+    let client = test_client_with_sender_policy(policy_with_token());
+    let res = client
+        .post("/api/v1/admin/webhooks")
+        .header(ContentType::JSON)
+        .header(Header::new("X-Server-Token", "srv_secret"))
+        .body(json!({"url": "http://127.0.0.1:9/provision", "events": "room_created,room_deleted", "secret": "s", "created_by": "ops"}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let hook: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(hook["events"], "room_created,room_deleted");
+    assert_eq!(hook["has_secret"], true);
+    assert_eq!(hook["active"], true);
+    assert!(hook.get("secret").is_none());
+    let id = hook["id"].as_str().unwrap();
+
+    let res = client
+        .get("/api/v1/admin/webhooks")
+        .header(Header::new("Authorization", "Bearer srv_secret"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let list: Vec<serde_json::Value> = res.into_json().unwrap();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0]["url"], "http://127.0.0.1:9/provision");
+
+    let res = client
+        .delete(format!("/api/v1/admin/webhooks/{id}"))
+        .header(Header::new("X-Server-Token", "srv_secret"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let res = client
+        .delete(format!("/api/v1/admin/webhooks/{id}"))
+        .header(Header::new("X-Server-Token", "srv_secret"))
+        .dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+}
+
+#[test]
+fn test_server_webhooks_require_server_token() {
+    let client = test_client_with_sender_policy(policy_with_token());
+    let body = json!({"url": "http://127.0.0.1:9/provision"}).to_string();
+    let res = client.post("/api/v1/admin/webhooks").header(ContentType::JSON).body(body.clone()).dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+    let res = client
+        .post("/api/v1/admin/webhooks")
+        .header(ContentType::JSON)
+        .header(Header::new("X-Server-Token", "wrong"))
+        .body(body.clone())
+        .dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+    assert_eq!(client.get("/api/v1/admin/webhooks").dispatch().status(), Status::Forbidden);
+
+    // No SERVER_TOKEN configured: the admin API is off entirely
+    let client = test_client();
+    let res = client
+        .post("/api/v1/admin/webhooks")
+        .header(ContentType::JSON)
+        .header(Header::new("X-Server-Token", ""))
+        .body(body)
+        .dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+}
+
+#[test]
+fn test_server_webhook_validation() {
+    let client = test_client_with_sender_policy(policy_with_token());
+    let create = |body: serde_json::Value| {
+        client
+            .post("/api/v1/admin/webhooks")
+            .header(ContentType::JSON)
+            .header(Header::new("X-Server-Token", "srv_secret"))
+            .body(body.to_string())
+            .dispatch()
+            .status()
+    };
+    assert_eq!(create(json!({"url": "ftp://example.com"})), Status::BadRequest);
+    // Only lifecycle events; message events belong to room webhooks
+    assert_eq!(create(json!({"url": "http://127.0.0.1:9/x", "events": "message"})), Status::BadRequest);
+    assert_eq!(create(json!({"url": "http://127.0.0.1:9/x", "events": "room_archived, room_unarchived"})), Status::Ok);
+    assert_eq!(create(json!({"url": "http://127.0.0.1:9/x"})), Status::Ok);
+}
+
+#[test]
+fn test_room_lifecycle_still_works_with_server_webhooks() {
+    let client = test_client_with_sender_policy(policy_with_token());
+    let res = client
+        .post("/api/v1/admin/webhooks")
+        .header(ContentType::JSON)
+        .header(Header::new("X-Server-Token", "srv_secret"))
+        .body(json!({"url": "http://127.0.0.1:9/provision"}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    let (room_id, admin_key) = crate::common::create_test_room(&client, "lifecycle-room");
+    let res = client
+        .delete(format!("/api/v1/rooms/{room_id}"))
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    assert_eq!(client.get(format!("/api/v1/rooms/{room_id}")).dispatch().status(), Status::NotFound);
+}
@@ -0,0 +1,136 @@
+use chrono::{TimeZone, Utc};
+use local_agent_chat::snapshots::{run_due, Cron};
+use rocket::http::{ContentType, Header, Status};
+use crate::common::{test_client, create_test_room, post_message, TestClient};
+use serde_json::json;
+
+fn put_schedule(client: &TestClient, room_id: &str, admin_key: &str, body: &str) -> (Status, serde_json::Value) {
+    let res = client
+        .put(format!("/api/v1/rooms/{room_id}/snapshot-schedule"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .body(body)
+        .dispatch();
+    let status = res.status();
+    (status, res.into_json().unwrap_or_default())
+}
+
+#[test]
+fn test_cron_next_after() {
+    let at = Utc.with_ymd_and_hms(2026, 3, 14, 10, 7, 30).unwrap();
+    let next = |expr: &str| Cron::parse(expr).unwrap().next_after(at).unwrap();
+    assert_eq!(next("*/15 * * * *"), Utc.with_ymd_and_hms(2026, 3, 14, 10, 15, 0).unwrap());
+    assert_eq!(next("@daily"), Utc.with_ymd_and_hms(2026, 3, 15, 0, 0, 0).unwrap());
+    assert_eq!(next("30 2 * * 1"), Utc.with_ymd_and_hms(2026, 3, 16, 2, 30, 0).unwrap());
+    assert_eq!(next("0 0 1 1 *"), Utc.with_ymd_and_hms(2027, 1, 1, 0, 0, 0).unwrap());
+    assert_eq!(next("0 9-17/4 * * *"), Utc.with_ymd_and_hms(2026, 3, 14, 13, 0, 0).unwrap());
+    assert!(Cron::parse("0 0 31 2 *").unwrap().next_after(at).is_none());
+    assert!(Cron::parse("61 * * * *").is_err());
+    assert!(Cron::parse("* * *").is_err());
+}
+
+#[test]
+fn test_snapshot_schedule_crud_and_validation() {
+    let client = test_client();
+    let (room_id, admin_key) = create_test_room(&client, "snapshot-schedule");
+
+    let res = client.get(format!("/api/v1/rooms/{room_id}/snapshot-schedule")).dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+
+    assert_eq!(put_schedule(&client, &room_id, &admin_key, r#"{"cron": "not a cron"}"#).0, Status::BadRequest);
+    assert_eq!(put_schedule(&client, &room_id, &admin_key, r#"{"cron": "@daily", "keep": 0}"#).0, Status::BadRequest);
+    assert_eq!(
+        put_schedule(&client, &room_id, &admin_key, r#"{"cron": "@daily", "destination": "s3"}"#).0,
+        Status::BadRequest
+    );
+    assert_eq!(put_schedule(&client, &room_id, "wrong", r#"{"cron": "@daily"}"#).0, Status::Forbidden);
+
+    let (status, schedule) = put_schedule(&client, &room_id, &admin_key, r#"{"cron": "0 3 * * *", "keep": 2}"#);
+    assert_eq!(status, Status::Ok);
+    assert_eq!(schedule["cron"], "0 3 * * *");
+    assert_eq!(schedule["destination"], "files");
+    assert_eq!(schedule["keep"], 2);
+    assert!(schedule["next_run_at"].as_str().unwrap().contains("T03:00:00"));
+
+    let res = client
+        .delete(format!("/api/v1/rooms/{room_id}/snapshot-schedule"))
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let res = client.get(format!("/api/v1/rooms/{room_id}/snapshot-schedule")).dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+}
+
+#[test]
+fn test_manual_snapshot_is_restorable_jsonl() {
+    let client = test_client();
+    let (room_id, admin_key) = create_test_room(&client, "snapshot-manual");
+    post_message(&client, &room_id, json!({"sender": "scribe", "content": "first"}));
+    post_message(&client, &room_id, json!({"sender": "scribe", "content": "second"}));
+
+    let res = client.post(format!("/api/v1/rooms/{room_id}/snapshots")).dispatch();
+    assert!(res.status() == Status::Unauthorized || res.status() == Status::NotFound);
+
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/snapshots"))
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let snapshot: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(snapshot["trigger"], "manual");
+    assert_eq!(snapshot["message_count"], 2);
+
+    let body = client
+        .get(snapshot["url"].as_str().unwrap())
+        .dispatch()
+        .into_string()
+        .unwrap();
+    let lines: Vec<serde_json::Value> = body.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0]["snapshot"]["room_id"], room_id.as_str());
+    assert_eq!(lines[1]["content"], "first");
+    assert_eq!(lines[2]["content"], "second");
+    assert_eq!(lines[2]["seq"], snapshot["last_seq"]);
+
+    let list: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/snapshots"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(list["snapshots"].as_array().unwrap().len(), 1);
+    assert!(list["schedule"].is_null());
+}
+
+#[test]
+fn test_scheduled_snapshots_run_when_due_and_keep_newest() {
+    let client = test_client();
+    let (room_id, admin_key) = create_test_room(&client, "snapshot-scheduled");
+    post_message(&client, &room_id, json!({"sender": "scribe", "content": "hello"}));
+    assert_eq!(put_schedule(&client, &room_id, &admin_key, r#"{"cron": "@hourly", "keep": 2}"#).0, Status::Ok);
+
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    // Not due yet
+    assert!(run_due(&conn, Utc::now()).is_empty());
+
+    for _ in 0..3 {
+        conn.execute(
+            "UPDATE room_snapshot_schedules SET next_run_at = '2000-01-01T00:00:00+00:00' WHERE room_id = ?1",
+            rusqlite::params![&room_id],
+        )
+        .unwrap();
+        let taken = run_due(&conn, Utc::now());
+        assert_eq!(taken.len(), 1);
+        assert_eq!(taken[0].0.trigger, "schedule");
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+
+    let list: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/snapshots"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(list["snapshots"].as_array().unwrap().len(), 2);
+    assert!(list["schedule"]["last_run_at"].is_string());
+    let next: chrono::DateTime<Utc> = list["schedule"]["next_run_at"].as_str().unwrap().parse().unwrap();
+    assert!(next > Utc::now());
+}
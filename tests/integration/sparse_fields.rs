@@ -0,0 +1,68 @@
+use rocket::http::{ContentType, Status};
+use crate::common::{create_test_room, test_client};
+
+// --- Sparse fieldsets (?fields=) ---
+
+#[test]
+fn test_messages_fields_trims_items() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "sparse-messages");
+    for i in 0..3 {
+        client
+            .post(format!("/api/v1/rooms/{room_id}/messages"))
+            .header(ContentType::JSON)
+            .body(format!(r#"{{"sender": "poller", "content": "tick {i}", "metadata": {{"big": "payload"}}}}"#))
+            .dispatch();
+    }
+
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/messages?fields=id,sender,content,seq"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let messages: Vec<serde_json::Value> = res.into_json().unwrap();
+    assert_eq!(messages.len(), 3);
+    for msg in &messages {
+        let keys: Vec<&String> = msg.as_object().unwrap().keys().collect();
+        assert_eq!(keys.len(), 4, "unexpected keys: {keys:?}");
+        assert!(msg["id"].is_string() && msg["seq"].is_i64());
+        assert_eq!(msg["sender"], "poller");
+    }
+    assert_eq!(messages[2]["content"], "tick 2");
+
+    // Works inside the envelope too, leaving pagination fields alone
+    let body: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/messages?fields=seq&envelope=true&limit=2"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(body["has_more"], true);
+    assert_eq!(body["items"][0], serde_json::json!({"seq": messages[0]["seq"]}));
+}
+
+#[test]
+fn test_rooms_fields_trims_items() {
+    let client = test_client();
+    create_test_room(&client, "sparse-rooms");
+    let res = client.get("/api/v1/rooms?fields=id,name").dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let rooms: Vec<serde_json::Value> = res.into_json().unwrap();
+    assert!(rooms.iter().any(|r| r["name"] == "sparse-rooms"));
+    assert!(rooms.iter().all(|r| r.as_object().unwrap().len() == 2));
+}
+
+#[test]
+fn test_unknown_fields_rejected() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "sparse-unknown");
+
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/messages?fields=id,body"))
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert!(body["error"].as_str().unwrap().contains("body"));
+    assert!(body["valid_fields"].as_array().unwrap().iter().any(|f| f == "content"));
+
+    let res = client.get("/api/v1/rooms?fields=nope").dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+}
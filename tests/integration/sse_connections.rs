@@ -0,0 +1,89 @@
+use crate::common::{admin_client, create_test_room, read_sse, TestClient};
+use rocket::http::{ContentType, Header, Status};
+
+fn connections(client: &TestClient) -> Vec<serde_json::Value> {
+    let res = client
+        .get("/api/v1/admin/streams")
+        .header(Header::new("X-Server-Token", "srv_secret"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    res.into_json::<Vec<serde_json::Value>>().unwrap()
+}
+
+#[test]
+fn test_admin_lists_open_connections() {
+    let client = admin_client();
+    let (room_id, _) = create_test_room(&client, "sse-conns");
+    for content in ["one", "two"] {
+        let res = client
+            .post(format!("/api/v1/rooms/{room_id}/messages"))
+            .header(ContentType::JSON)
+            .body(serde_json::json!({"sender": "writer", "content": content}).to_string())
+            .dispatch();
+        assert_eq!(res.status(), Status::Ok);
+    }
+
+    let res = client.get("/api/v1/admin/streams").dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+    assert!(connections(&client).is_empty());
+
+    let mut stream = client
+        .get(format!("/api/v1/rooms/{room_id}/stream?after=0&sender=reader&sender_type=agent&heartbeat_secs=30&events=message"))
+        .dispatch();
+    assert_eq!(stream.status(), Status::Ok);
+    read_sse(&mut stream, "heartbeat");
+
+    let list = connections(&client);
+    assert_eq!(list.len(), 1);
+    let conn = &list[0];
+    assert_eq!(conn["room_id"], room_id.as_str());
+    assert_eq!(conn["sender"], "reader");
+    assert_eq!(conn["sender_type"], "agent");
+    assert_eq!(conn["heartbeat_secs"], 30);
+    assert_eq!(conn["events"], "message");
+    assert!(conn["expires_at"].is_null());
+    assert!(conn["connected_at"].as_str().is_some());
+    // The two replayed messages; heartbeats aren't counted
+    assert_eq!(conn["events_delivered"], 2);
+    assert!(conn["last_event_at"].as_str().is_some());
+
+    drop(stream);
+    assert!(connections(&client).is_empty());
+}
+
+#[test]
+fn test_max_lifetime_forces_reconnect() {
+    let client = admin_client();
+    let (room_id, _) = create_test_room(&client, "sse-lifetime");
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"sender": "writer", "content": "hi"}).to_string())
+        .dispatch();
+    let seq = res.into_json::<serde_json::Value>().unwrap()["seq"].as_i64().unwrap();
+
+    let mut stream = client
+        .get(format!("/api/v1/rooms/{room_id}/stream?after=0&max_lifetime_secs=1"))
+        .dispatch();
+    read_sse(&mut stream, "heartbeat");
+    let conn = &connections(&client)[0];
+    assert!(conn["expires_at"].as_str().is_some());
+
+    // The server closes the stream after the reconnect event
+    let frames = read_sse(&mut stream, "reconnect");
+    let (_, data) = frames.iter().find(|(name, _)| name == "reconnect").expect("reconnect event");
+    assert!(read_sse(&mut stream, "never").is_empty());
+    assert_eq!(data["reason"], "max_lifetime");
+    assert_eq!(data["after"], seq);
+    assert!(connections(&client).is_empty());
+}
+
+#[test]
+fn test_stream_connection_settings_are_validated() {
+    let client = admin_client();
+    let (room_id, _) = create_test_room(&client, "sse-settings");
+    for query in ["heartbeat_secs=0", "heartbeat_secs=301", "max_lifetime_secs=0"] {
+        let res = client.get(format!("/api/v1/rooms/{room_id}/stream?{query}")).dispatch();
+        assert_eq!(res.status(), Status::BadRequest, "{query}");
+    }
+}
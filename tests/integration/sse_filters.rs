@@ -0,0 +1,64 @@
+use crate::common::{create_test_room, post_message, read_sse, test_client, TestClient};
+use rocket::http::Status;
+use serde_json::json;
+
+/// Events sent before the first heartbeat (the replay), as (event name, data).
+fn replayed(client: &TestClient, url: &str) -> Vec<(String, serde_json::Value)> {
+    let mut res = client.get(url).dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    read_sse(&mut res, "heartbeat")
+        .into_iter()
+        .take_while(|(name, _)| name != "heartbeat")
+        .collect()
+}
+
+#[test]
+fn test_stream_filters_replay() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "sse-filters");
+    let first =
+        post_message(&client, &room_id, json!({"sender": "me", "sender_type": "agent", "content": "mine"}))["seq"]
+            .as_i64()
+            .unwrap();
+    post_message(&client, &room_id, json!({"sender": "alice", "sender_type": "human", "content": "from a human"}));
+    post_message(&client, &room_id, json!({"sender": "bot", "sender_type": "agent", "content": "from another agent"}));
+    post_message(&client, &room_id, json!({"sender": "anon", "content": "no type"}));
+    let after = first - 1;
+
+    let all = replayed(&client, &format!("/api/v1/rooms/{room_id}/stream?after={after}"));
+    assert_eq!(all.len(), 4);
+
+    let others = replayed(&client, &format!("/api/v1/rooms/{room_id}/stream?after={after}&exclude_sender=me,bot"));
+    let senders: Vec<&str> = others.iter().map(|(_, m)| m["sender"].as_str().unwrap()).collect();
+    assert_eq!(senders, vec!["alice", "anon"]);
+
+    let humans = replayed(&client, &format!("/api/v1/rooms/{room_id}/stream?after={after}&from_sender_type=human"));
+    assert_eq!(humans.len(), 1);
+    assert_eq!(humans[0].1["sender"], "alice");
+
+    let none = replayed(&client, &format!("/api/v1/rooms/{room_id}/stream?after={after}&events=reaction,typing"));
+    assert!(none.is_empty());
+    let messages = replayed(&client, &format!("/api/v1/rooms/{room_id}/stream?after={after}&events=message"));
+    assert_eq!(messages.len(), 4);
+
+    // Presence sender_type describes the connection, not a filter (joining also writes a
+    // system note, which is replayed too)
+    let mine = replayed(
+        &client,
+        &format!("/api/v1/rooms/{room_id}/stream?after={after}&sender=watcher&sender_type=human"),
+    );
+    assert_eq!(mine.iter().filter(|(_, m)| m["kind"] == "message").count(), 4);
+}
+
+#[test]
+fn test_stream_rejects_unknown_event_types() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "sse-filters-bad");
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/stream?events=message,explosion"))
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert!(body["error"].as_str().unwrap().contains("explosion"));
+    assert!(body["valid_events"].as_array().unwrap().iter().any(|e| e == "reaction_added"));
+}
@@ -0,0 +1,40 @@
+use crate::common::{create_test_room, read_sse, test_client_with_rate_limits};
+use local_agent_chat::rate_limit::RateLimitConfig;
+use rocket::http::{ContentType, Status};
+
+#[test]
+fn test_lagging_consumer_gets_gap_event() {
+    let client = test_client_with_rate_limits(RateLimitConfig { messages_max: 10_000, ..Default::default() });
+    let (room_id, _) = create_test_room(&client, "sse-lag");
+    let post = |content: String| {
+        let res = client
+            .post(format!("/api/v1/rooms/{room_id}/messages"))
+            .header(ContentType::JSON)
+            .body(serde_json::json!({"sender": "flood", "content": content}).to_string())
+            .dispatch();
+        assert_eq!(res.status(), Status::Ok);
+        res.into_json::<serde_json::Value>().unwrap()["seq"].as_i64().unwrap()
+    };
+    let before = post("before".to_string());
+
+    // Subscribed but never read while the channel (1024 events) overflows
+    let mut stream = client
+        .get(format!("/api/v1/rooms/{room_id}/stream?heartbeat_secs=1"))
+        .dispatch();
+    assert_eq!(stream.status(), Status::Ok);
+    let mut last = before;
+    for i in 0..1100 {
+        last = post(format!("flood {i}"));
+    }
+
+    let frames = read_sse(&mut stream, "gap");
+    let (_, gap) = frames.iter().find(|(name, _)| name == "gap").expect("gap event");
+    assert!(gap["missed_events"].as_u64().unwrap() > 0);
+    assert_eq!(gap["from_seq"], before + 1);
+    assert_eq!(gap["to_seq"], last);
+    assert_eq!(gap["replay"], format!("/api/v1/rooms/{room_id}/messages?after={before}"));
+
+    // Messages in the gap's range aren't sent live as well; the replay covers them
+    let frames = read_sse(&mut stream, "heartbeat");
+    assert!(frames.iter().all(|(name, _)| name != "message"));
+}
@@ -1,4 +1,4 @@
-use rocket::http::Status;
+use rocket::http::{ContentType, Status};
 use crate::common::test_client;
 
 // --- llms.txt ---
@@ -55,6 +55,18 @@ fn test_openapi_json() {
     assert_eq!(body["info"]["title"], "Local Agent Chat API");
 }
 
+#[test]
+fn test_api_docs_console() {
+    // Mounted unless API_DOCS_ENABLED=false
+    let client = test_client();
+    let res = client.get("/api/v1/docs").dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    assert_eq!(res.content_type(), Some(ContentType::HTML));
+    let html = res.into_string().unwrap();
+    assert!(html.contains(r#"spec-url="/api/v1/openapi.json""#));
+    assert!(html.contains("<rapi-doc"));
+}
+
 #[test]
 fn test_openapi_has_paths() {
     let client = test_client();
@@ -92,6 +104,87 @@ fn test_skills_index_json() {
     assert!(files.contains(&serde_json::json!("SKILL.md")));
 }
 
+#[test]
+fn test_skills_json_tool_schemas() {
+    let client = test_client();
+    let res = client.get("/api/v1/skills.json").dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    assert_eq!(res.content_type(), Some(ContentType::JSON));
+    let body: serde_json::Value = res.into_json().unwrap();
+    let tools = body["tools"].as_array().unwrap();
+    assert!(tools.len() >= 10, "Should expose the core operations");
+
+    let mut names = std::collections::HashSet::new();
+    for tool in tools {
+        let name = tool["name"].as_str().unwrap();
+        assert!(names.insert(name), "Duplicate tool name {name}");
+        assert!(name.chars().all(|c| c.is_ascii_lowercase() || c == '_'), "{name} should be snake_case");
+        assert_eq!(tool["parameters"]["type"], "object", "{name}");
+        let props = tool["parameters"]["properties"].as_object().unwrap();
+        let path = tool["endpoint"]["path"].as_str().unwrap();
+        assert!(path.starts_with("/api/v1/"), "{name}");
+        assert!(["GET", "POST", "PUT", "DELETE"].contains(&tool["endpoint"]["method"].as_str().unwrap()));
+        // Every path placeholder is a required parameter
+        for p in tool["endpoint"]["path_params"].as_array().into_iter().flatten() {
+            let p = p.as_str().unwrap();
+            assert!(path.contains(&format!("{{{p}}}")), "{name}: {p} not in path");
+            assert!(props.contains_key(p), "{name}: {p} not a parameter");
+            assert!(tool["parameters"]["required"].as_array().unwrap().contains(&serde_json::json!(p)));
+        }
+    }
+    assert!(names.contains("send_message"));
+    assert!(names.contains("get_messages"));
+
+    // The index points at it
+    let index: serde_json::Value = client.get("/.well-known/skills/index.json").dispatch().into_json().unwrap();
+    assert_eq!(index["skills"][0]["tools"], "/api/v1/skills.json");
+}
+
+#[test]
+fn test_skills_json_mapping_is_callable() {
+    let client = test_client();
+    let body: serde_json::Value = client.get("/api/v1/skills.json").dispatch().into_json().unwrap();
+    let tool = |name: &str| {
+        body["tools"].as_array().unwrap().iter().find(|t| t["name"] == name).unwrap().clone()
+    };
+    let room: serde_json::Value = client
+        .post("/api/v1/rooms")
+        .header(ContentType::JSON)
+        .body(r#"{"name": "skills-json-room"}"#)
+        .dispatch()
+        .into_json()
+        .unwrap();
+
+    // Build the request purely from the tool's endpoint mapping
+    let send = tool("send_message");
+    let args = serde_json::json!({"room_id": room["id"], "sender": "tool-agent", "content": "via tool schema"});
+    let mut path = send["endpoint"]["path"].as_str().unwrap().to_string();
+    for p in send["endpoint"]["path_params"].as_array().unwrap() {
+        let p = p.as_str().unwrap();
+        path = path.replace(&format!("{{{p}}}"), args[p].as_str().unwrap());
+    }
+    let mut json_body = serde_json::Map::new();
+    for p in send["endpoint"]["body"].as_array().unwrap() {
+        let p = p.as_str().unwrap();
+        if !args[p].is_null() {
+            json_body.insert(p.to_string(), args[p].clone());
+        }
+    }
+    assert_eq!(send["endpoint"]["method"], "POST");
+    let res = client
+        .post(path)
+        .header(ContentType::JSON)
+        .body(serde_json::Value::Object(json_body).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    let get = tool("get_messages");
+    let path = get["endpoint"]["path"].as_str().unwrap().replace("{room_id}", room["id"].as_str().unwrap());
+    let msgs: Vec<serde_json::Value> = client.get(format!("{path}?kind=message")).dispatch().into_json().unwrap();
+    assert_eq!(msgs.len(), 1);
+    assert_eq!(msgs[0]["content"], "via tool schema");
+}
+
 #[test]
 fn test_skills_skill_md() {
     let client = test_client();
@@ -0,0 +1,183 @@
+use rocket::http::{ContentType, Header, Status};
+use rocket::local::blocking::Client;
+use crate::common::{create_test_room, post_message, test_client};
+use serde_json::json;
+
+// --- In-room system messages ---
+
+fn system_messages(client: &Client, room_id: &str) -> Vec<serde_json::Value> {
+    client
+        .get(format!("/api/v1/rooms/{room_id}/messages?kind=system"))
+        .dispatch()
+        .into_json()
+        .unwrap()
+}
+
+#[test]
+fn test_rename_and_pin_leave_system_messages() {
+    let client = test_client();
+    let (room_id, key) = create_test_room(&client, "sys-before");
+    let msg = post_message(&client, &room_id, json!({"sender": "alice", "content": "ship it"}));
+    assert_eq!(msg["kind"], "message");
+
+    let res = client
+        .put(format!("/api/v1/rooms/{room_id}"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {key}")))
+        .body(r#"{"name": "sys-after"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/messages/{}/pin", msg["id"].as_str().unwrap()))
+        .header(Header::new("Authorization", format!("Bearer {key}")))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    let notes = system_messages(&client, &room_id);
+    assert_eq!(notes.len(), 2);
+    assert_eq!(notes[0]["kind"], "system");
+    assert_eq!(notes[0]["sender"], "system");
+    assert_eq!(notes[0]["metadata"]["event"], "room_renamed");
+    assert_eq!(notes[0]["metadata"]["old_name"], "sys-before");
+    assert_eq!(notes[0]["metadata"]["new_name"], "sys-after");
+    assert_eq!(notes[1]["metadata"]["event"], "message_pinned");
+    assert_eq!(notes[1]["metadata"]["message_id"], msg["id"]);
+
+    // Full history interleaves both kinds; kind=message leaves only posts
+    let all: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/rooms/{room_id}/messages"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(all.len(), 3);
+    let posts: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/rooms/{room_id}/messages?kind=message"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(posts.len(), 1);
+
+    // Participants only list real senders
+    let participants: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/rooms/{room_id}/participants"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(participants.len(), 1);
+    assert_eq!(participants[0]["sender"], "alice");
+}
+
+#[test]
+fn test_system_messages_excluded_from_unread_by_default() {
+    let client = test_client();
+    let (room_id, key) = create_test_room(&client, "sys-unread");
+    let msg = post_message(&client, &room_id, json!({"sender": "alice", "content": "hello"}));
+    client
+        .put(format!("/api/v1/rooms/{room_id}/read"))
+        .header(ContentType::JSON)
+        .body(serde_json::json!({"sender": "bob", "last_read_seq": msg["seq"]}).to_string())
+        .dispatch();
+
+    client
+        .post(format!("/api/v1/rooms/{room_id}/messages/{}/pin", msg["id"].as_str().unwrap()))
+        .header(Header::new("Authorization", format!("Bearer {key}")))
+        .dispatch();
+
+    let unread: serde_json::Value = client.get("/api/v1/unread?sender=bob").dispatch().into_json().unwrap();
+    let room = unread["rooms"].as_array().unwrap().iter().find(|r| r["room_id"] == room_id.as_str()).unwrap();
+    assert_eq!(room["unread_count"], 0);
+
+    let unread: serde_json::Value = client
+        .get("/api/v1/unread?sender=bob&include_system=true")
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let room = unread["rooms"].as_array().unwrap().iter().find(|r| r["room_id"] == room_id.as_str()).unwrap();
+    assert_eq!(room["unread_count"], 1);
+}
+
+#[test]
+fn test_first_stream_connection_announces_join_once() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "sys-join");
+
+    let stream = client
+        .get(format!("/api/v1/rooms/{room_id}/stream?sender=scout&sender_type=agent"))
+        .dispatch();
+    assert_eq!(stream.status(), Status::Ok);
+    drop(stream);
+    let stream = client
+        .get(format!("/api/v1/rooms/{room_id}/stream?sender=scout&sender_type=agent"))
+        .dispatch();
+    drop(stream);
+
+    let notes = system_messages(&client, &room_id);
+    assert_eq!(notes.len(), 1);
+    assert_eq!(notes[0]["metadata"]["event"], "member_joined");
+    assert_eq!(notes[0]["metadata"]["sender"], "scout");
+    assert_eq!(notes[0]["content"], "scout joined the room");
+}
+
+#[test]
+fn test_retention_keeps_a_single_purge_note() {
+    let client = test_client();
+    let res = client
+        .post("/api/v1/rooms")
+        .header(ContentType::JSON)
+        .body(r#"{"name": "sys-retention", "created_by": "tester", "max_messages": 10}"#)
+        .dispatch();
+    let room: serde_json::Value = res.into_json().unwrap();
+    let room_id = room["id"].as_str().unwrap().to_string();
+
+    for round in 0..2 {
+        for i in 0..12 {
+            post_message(&client, &room_id, json!({"sender": "alice", "content": format!("round {round} msg {i}")}));
+        }
+        let res = client.post("/api/v1/admin/retention/run").dispatch();
+        assert_eq!(res.status(), Status::Ok);
+    }
+
+    let notes = system_messages(&client, &room_id);
+    assert_eq!(notes.len(), 1);
+    assert_eq!(notes[0]["metadata"]["event"], "retention_purged");
+    assert_eq!(notes[0]["metadata"]["pruned_by_count"], 12);
+
+    // The note doesn't count toward max_messages
+    let posts: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/rooms/{room_id}/messages?kind=message&limit=100"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(posts.len(), 10);
+}
+
+#[test]
+fn test_export_includes_system_messages() {
+    let client = test_client();
+    let (room_id, key) = create_test_room(&client, "sys-export");
+    post_message(&client, &room_id, json!({"sender": "alice", "content": "hello"}));
+    client
+        .put(format!("/api/v1/rooms/{room_id}"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {key}")))
+        .body(r#"{"name": "sys-export-renamed"}"#)
+        .dispatch();
+
+    let body: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/export"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let msgs = body["messages"].as_array().unwrap();
+    assert_eq!(msgs.len(), 2);
+    assert_eq!(msgs[0]["kind"], "message");
+    assert_eq!(msgs[1]["kind"], "system");
+
+    let md = client
+        .get(format!("/api/v1/rooms/{room_id}/export?format=markdown"))
+        .dispatch()
+        .into_string()
+        .unwrap();
+    assert!(md.contains("— Room renamed from #sys-export to #sys-export-renamed*"));
+}
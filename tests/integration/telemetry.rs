@@ -0,0 +1,88 @@
+use rocket::http::{Header, Status};
+use local_agent_chat::telemetry::{Span, SpanContext, SpanKind};
+use crate::common::{cleanup, temp_path, test_client};
+
+// --- traceparent parsing ---
+
+#[test]
+fn test_traceparent_parse_valid() {
+    let ctx = SpanContext::parse_traceparent("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01")
+        .expect("valid traceparent");
+    assert_eq!(ctx.trace_id, "4bf92f3577b34da6a3ce929d0e0e4736");
+    assert_eq!(ctx.span_id, "00f067aa0ba902b7");
+    assert!(ctx.sampled);
+    assert_eq!(
+        ctx.to_traceparent(),
+        "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"
+    );
+
+    let unsampled = SpanContext::parse_traceparent("00-4BF92F3577B34DA6A3CE929D0E0E4736-00F067AA0BA902B7-00")
+        .expect("uppercase hex accepted");
+    assert!(!unsampled.sampled);
+    assert_eq!(unsampled.trace_id, "4bf92f3577b34da6a3ce929d0e0e4736");
+}
+
+#[test]
+fn test_traceparent_parse_invalid() {
+    for bad in [
+        "",
+        "garbage",
+        "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+        "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+        "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+        "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+        "00-4bf92f3577b34da6a3ce929d0e0e473-00f067aa0ba902b7-01",
+        "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+        "00-zzf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+    ] {
+        assert!(SpanContext::parse_traceparent(bad).is_none(), "should reject {bad:?}");
+    }
+}
+
+#[test]
+fn test_child_span_continues_trace() {
+    let parent = SpanContext::parse_traceparent("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01").unwrap();
+    let child = Span::start("child", SpanKind::Internal, Some(&parent));
+    assert_eq!(child.context().trace_id, parent.trace_id);
+    assert_ne!(child.context().span_id, parent.span_id);
+    assert_eq!(child.context().span_id.len(), 16);
+
+    let root = Span::start("root", SpanKind::Internal, None);
+    assert_eq!(root.context().trace_id.len(), 32);
+    assert_ne!(root.context().trace_id, parent.trace_id);
+}
+
+#[test]
+fn test_requests_with_traceparent_succeed() {
+    let client = test_client();
+    let res = client
+        .get("/api/v1/health")
+        .header(Header::new("traceparent", "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    // Malformed headers are ignored, not rejected
+    let res = client
+        .post("/api/v1/admin/retention/run")
+        .header(Header::new("traceparent", "not-a-trace"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+}
+
+#[test]
+fn test_traced_conn_is_a_plain_connection_when_tracing_is_off() {
+    let path = temp_path();
+    let db = local_agent_chat::db::Db::new(&path);
+    let parent = SpanContext::parse_traceparent("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01").unwrap();
+    {
+        let mut conn = db.traced_conn(Some(&parent), Some("team-a"));
+        let tx = conn.transaction().unwrap();
+        let rooms: i64 = tx.query_row("SELECT COUNT(*) FROM rooms", [], |r| r.get(0)).unwrap();
+        assert!(rooms >= 1);
+        tx.commit().unwrap();
+    }
+    // Released on drop
+    assert!(db.conn.try_lock().is_ok());
+    drop(db);
+    cleanup(&path);
+}
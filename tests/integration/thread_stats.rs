@@ -0,0 +1,98 @@
+use crate::common::{create_test_room, post_message, react, test_client};
+use rocket::http::Status;
+use rocket::local::blocking::Client;
+
+fn stats(client: &Client, room_id: &str, msg_id: &str) -> serde_json::Value {
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/messages/{msg_id}/thread/stats"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    res.into_json().unwrap()
+}
+
+#[test]
+fn test_thread_stats_counts() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "thread-stats");
+    let root = post_message(&client, &room_id, serde_json::json!({"sender": "alice", "content": "Plan?"}))["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let r1 = post_message(
+        &client,
+        &room_id,
+        serde_json::json!({"sender": "bob", "content": "Option A", "reply_to": root}),
+    )["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    post_message(&client, &room_id, serde_json::json!({"sender": "alice", "content": "Agreed", "reply_to": r1}));
+    post_message(&client, &room_id, serde_json::json!({"sender": "carol", "content": "unrelated"}));
+    react(&client, &room_id, &r1, "alice", "👍");
+    react(&client, &room_id, &r1, "carol", "👍");
+    react(&client, &room_id, &root, "bob", "🎉");
+
+    // Any message in the thread identifies it.
+    let body = stats(&client, &room_id, &r1);
+    assert_eq!(body["root_id"], root.as_str());
+    assert_eq!(body["total_messages"], 3);
+    assert_eq!(body["total_replies"], 2);
+    assert_eq!(body["total_reactions"], 3);
+    assert_eq!(body["reactions"]["👍"], 2);
+    assert_eq!(body["reactions"]["🎉"], 1);
+
+    let participants = body["participants"].as_array().unwrap();
+    assert_eq!(participants.len(), 2, "carol only reacted");
+    assert_eq!(participants[0]["sender"], "alice");
+    assert_eq!(participants[0]["messages"], 2);
+    assert_eq!(participants[0]["reactions_given"], 1);
+    assert_eq!(participants[0]["reactions_received"], 1);
+    assert_eq!(participants[1]["sender"], "bob");
+    assert_eq!(participants[1]["reactions_received"], 2);
+    assert!(body["first_activity_at"].as_str().unwrap() <= body["last_activity_at"].as_str().unwrap());
+    assert_eq!(body["resolution"]["status"], "open");
+}
+
+#[test]
+fn test_thread_stats_resolution() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "thread-resolve");
+    let root = post_message(&client, &room_id, serde_json::json!({"sender": "alice", "content": "Bug?"}))["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let fix = post_message(
+        &client,
+        &room_id,
+        serde_json::json!({"sender": "bob", "content": "Fixed", "reply_to": root, "metadata": {"resolved": true}}),
+    )["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let body = stats(&client, &room_id, &root);
+    assert_eq!(body["resolution"]["status"], "resolved");
+    assert_eq!(body["resolution"]["resolved_by"], "bob");
+    assert_eq!(body["resolution"]["message_id"], fix.as_str());
+
+    post_message(
+        &client,
+        &room_id,
+        serde_json::json!({"sender": "alice", "content": "Still broken", "reply_to": fix, "metadata": {"resolved": false}}),
+    );
+    let body = stats(&client, &room_id, &root);
+    assert_eq!(body["resolution"]["status"], "open");
+    assert!(body["resolution"].get("resolved_by").is_none());
+}
+
+#[test]
+fn test_thread_stats_not_found() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "thread-stats-404");
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/messages/nope/thread/stats"))
+        .dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+    let res = client.get("/api/v1/rooms/nope/messages/nope/thread/stats").dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+}
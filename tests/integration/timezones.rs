@@ -0,0 +1,95 @@
+use crate::common::{create_test_room, test_client};
+use rocket::http::{ContentType, Status};
+
+// --- Localized display timestamps ---
+
+#[test]
+fn test_messages_with_tz_carry_local_time() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "tz-room");
+    client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "Bot", "content": "hello"}"#)
+        .dispatch();
+
+    // Off unless asked for
+    let msgs: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/rooms/{room_id}/messages"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert!(msgs[0].get("local_time").is_none());
+
+    let msgs: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/rooms/{room_id}/messages?tz=Asia/Kolkata"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let local = &msgs[0]["local_time"];
+    assert_eq!(local["tz"], "Asia/Kolkata");
+    assert_eq!(local["utc_offset"], "+05:30");
+    assert!(local["display"].as_str().unwrap().ends_with("IST"));
+    // Same instant as the UTC timestamp
+    let utc = chrono::DateTime::parse_from_rfc3339(msgs[0]["created_at"].as_str().unwrap()).unwrap();
+    let there = chrono::DateTime::parse_from_rfc3339(local["created_at"].as_str().unwrap()).unwrap();
+    assert_eq!(utc, there);
+    assert!(local["created_at"].as_str().unwrap().ends_with("+05:30"));
+
+    // Activity takes the same parameter
+    let res = client.get("/api/v1/activity?tz=Asia/Kolkata").dispatch();
+    let body: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(body["events"][0]["local_time"]["utc_offset"], "+05:30");
+
+    let res = client.get(format!("/api/v1/rooms/{room_id}/messages?tz=Mars/Olympus_Mons")).dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+    let res = client.get("/api/v1/activity?tz=Nowhere").dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+}
+
+#[test]
+fn test_profile_timezone_preference() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "tz-profile");
+    client
+        .post(format!("/api/v1/rooms/{room_id}/messages"))
+        .header(ContentType::JSON)
+        .body(r#"{"sender": "Bot", "content": "hello"}"#)
+        .dispatch();
+
+    // No preference yet
+    let res = client.get(format!("/api/v1/rooms/{room_id}/messages?tz=@reader")).dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+
+    let res = client
+        .put("/api/v1/profiles/reader")
+        .header(ContentType::JSON)
+        .body(r#"{"timezone": "Not/AZone"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+    let res = client
+        .put("/api/v1/profiles/reader")
+        .header(ContentType::JSON)
+        .body(r#"{"timezone": "Asia/Tokyo"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let profile: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(profile["timezone"], "Asia/Tokyo");
+
+    let msgs: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/rooms/{room_id}/messages?tz=@reader"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(msgs[0]["local_time"]["tz"], "Asia/Tokyo");
+    assert_eq!(msgs[0]["local_time"]["utc_offset"], "+09:00");
+
+    // Empty string clears it
+    let res = client
+        .put("/api/v1/profiles/reader")
+        .header(ContentType::JSON)
+        .body(r#"{"timezone": ""}"#)
+        .dispatch();
+    let profile: serde_json::Value = res.into_json().unwrap();
+    assert!(profile.get("timezone").is_none());
+}
@@ -0,0 +1,106 @@
+use rocket::http::{ContentType, Header, Status};
+use rocket::local::blocking::Client;
+use crate::common::{create_test_room, test_client, upload_file};
+use serde_json::json;
+
+// --- Upload policies ---
+
+/// `\x89PNG\r\n\x1a\n`
+const PNG_B64: &str = "iVBORw0KGgo=";
+/// `MZ\x90\x00\x03\x00\x00\x00` — a Windows executable header
+const EXE_B64: &str = "TVqQAAMAAAA=";
+
+/// Status of uploading `data` (base64) under the given name and declared type.
+fn upload(client: &Client, room_id: &str, filename: &str, content_type: &str, data: &str) -> Status {
+    upload_file(client, room_id, json!({"filename": filename, "content_type": content_type, "data": data})).0
+}
+
+fn set_policy(client: &Client, room_id: &str, key: &str, policy: serde_json::Value) -> Status {
+    client
+        .put(format!("/api/v1/rooms/{room_id}/upload-policy"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {key}")))
+        .body(policy.to_string())
+        .dispatch()
+        .status()
+}
+
+#[test]
+fn test_upload_policy_crud() {
+    let client = test_client();
+    let (room_id, key) = create_test_room(&client, "policy-crud");
+
+    let res = client.get(format!("/api/v1/rooms/{room_id}/upload-policy")).dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+
+    let policy = serde_json::json!({"allowed_types": ["Image/*", "text/plain"], "blocked_extensions": [".EXE", "dll"]});
+    let res = client
+        .put(format!("/api/v1/rooms/{room_id}/upload-policy"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", "Bearer wrong-key"))
+        .body(policy.to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+    assert_eq!(set_policy(&client, &room_id, &key, policy), Status::Ok);
+
+    let body: serde_json::Value = client
+        .get(format!("/api/v1/rooms/{room_id}/upload-policy"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(body["allowed_types"], serde_json::json!(["image/*", "text/plain"]));
+    assert_eq!(body["blocked_extensions"], serde_json::json!(["exe", "dll"]));
+    assert_eq!(body["verify_content_type"], false);
+
+    assert_eq!(
+        set_policy(&client, &room_id, &key, serde_json::json!({"blocked_types": ["not a mime"]})),
+        Status::BadRequest
+    );
+
+    let res = client
+        .delete(format!("/api/v1/rooms/{room_id}/upload-policy"))
+        .header(Header::new("Authorization", format!("Bearer {key}")))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let res = client.get(format!("/api/v1/rooms/{room_id}/upload-policy")).dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+}
+
+#[test]
+fn test_upload_policy_type_and_extension_rules() {
+    let client = test_client();
+    let (room_id, key) = create_test_room(&client, "policy-rules");
+    let policy = serde_json::json!({"allowed_types": ["image/*", "text/plain"], "blocked_extensions": ["exe"]});
+    assert_eq!(set_policy(&client, &room_id, &key, policy), Status::Ok);
+
+    assert_eq!(upload(&client, &room_id, "shot.png", "image/png", PNG_B64), Status::Ok);
+    assert_eq!(upload(&client, &room_id, "notes.txt", "text/plain", "aGk="), Status::Ok);
+    assert_eq!(upload(&client, &room_id, "data.json", "application/json", "e30="), Status::UnsupportedMediaType);
+    assert_eq!(upload(&client, &room_id, "tool.exe", "text/plain", "aGk="), Status::UnsupportedMediaType);
+    // Lists also apply to the sniffed type: an executable can't slip in labelled as text
+    assert_eq!(upload(&client, &room_id, "tool.txt", "text/plain", EXE_B64), Status::UnsupportedMediaType);
+
+    // Other rooms are unaffected
+    let (other, _) = create_test_room(&client, "policy-rules-other");
+    assert_eq!(upload(&client, &other, "data.json", "application/json", "e30="), Status::Ok);
+}
+
+#[test]
+fn test_upload_policy_verifies_content_type() {
+    let client = test_client();
+    let (room_id, key) = create_test_room(&client, "policy-sniff");
+
+    // Off by default
+    assert_eq!(upload(&client, &room_id, "fake.pdf", "application/pdf", PNG_B64), Status::Ok);
+
+    assert_eq!(set_policy(&client, &room_id, &key, serde_json::json!({"verify_content_type": true})), Status::Ok);
+    let (status, body) =
+        upload_file(&client, &room_id, json!({"filename": "fake.pdf", "content_type": "application/pdf", "data": PNG_B64}));
+    assert_eq!(status, Status::UnprocessableEntity);
+    assert!(body["error"].as_str().unwrap().contains("image/png"));
+
+    assert_eq!(upload(&client, &room_id, "real.png", "image/png", PNG_B64), Status::Ok);
+    // Formats without a signature, and generic binaries, pass
+    assert_eq!(upload(&client, &room_id, "notes.txt", "text/plain", "aGk="), Status::Ok);
+    assert_eq!(upload(&client, &room_id, "blob.bin", "application/octet-stream", PNG_B64), Status::Ok);
+}
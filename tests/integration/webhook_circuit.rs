@@ -0,0 +1,132 @@
+use crate::common::{create_test_room, test_client, TestClient};
+use local_agent_chat::webhooks::{record_delivery_outcome, CircuitBreaker};
+use rocket::http::{ContentType, Header, Status};
+
+fn create_webhook(client: &TestClient, room_id: &str, admin_key: &str) -> String {
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/webhooks"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .body(r#"{"url": "http://localhost:9999/hook", "created_by": "tester"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body: serde_json::Value = res.into_json().unwrap();
+    body["id"].as_str().unwrap().to_string()
+}
+
+fn list(client: &TestClient, room_id: &str, admin_key: &str) -> serde_json::Value {
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/webhooks"))
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    res.into_json::<serde_json::Value>().unwrap()[0].clone()
+}
+
+fn breaker() -> CircuitBreaker {
+    CircuitBreaker {
+        min_failures: 3,
+        open_after: chrono::Duration::minutes(10),
+    }
+}
+
+#[test]
+fn test_new_webhook_is_healthy() {
+    let client = test_client();
+    let (room_id, admin_key) = create_test_room(&client, "circuit-new");
+    create_webhook(&client, &room_id, &admin_key);
+    let hook = list(&client, &room_id, &admin_key);
+    assert_eq!(hook["state"], "healthy");
+    assert_eq!(hook["failure_streak"], 0);
+    assert!(hook["last_success_at"].is_null());
+}
+
+#[test]
+fn test_success_resets_failure_streak() {
+    let client = test_client();
+    let (room_id, admin_key) = create_test_room(&client, "circuit-reset");
+    let id = create_webhook(&client, &room_id, &admin_key);
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    let now = chrono::Utc::now();
+
+    assert!(record_delivery_outcome(&conn, &id, false, &breaker(), now).is_none());
+    assert!(record_delivery_outcome(&conn, &id, false, &breaker(), now).is_none());
+    let hook = list(&client, &room_id, &admin_key);
+    assert_eq!(hook["state"], "failing");
+    assert_eq!(hook["failure_streak"], 2);
+    assert!(hook["last_failure_at"].is_string());
+
+    assert!(record_delivery_outcome(&conn, &id, true, &breaker(), now).is_none());
+    let hook = list(&client, &room_id, &admin_key);
+    assert_eq!(hook["state"], "healthy");
+    assert_eq!(hook["failure_streak"], 0);
+    assert!(hook["last_success_at"].is_string());
+}
+
+#[test]
+fn test_circuit_opens_after_sustained_failures() {
+    let client = test_client();
+    let (room_id, admin_key) = create_test_room(&client, "circuit-open");
+    let id = create_webhook(&client, &room_id, &admin_key);
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    let start = chrono::Utc::now();
+
+    // Many failures in a short burst don't trip it: the streak must also span open_after.
+    for _ in 0..5 {
+        assert!(record_delivery_outcome(&conn, &id, false, &breaker(), start).is_none());
+    }
+    let opened = record_delivery_outcome(&conn, &id, false, &breaker(), start + chrono::Duration::minutes(11))
+        .expect("circuit should open");
+    assert_eq!(opened.webhook_id, id);
+    assert_eq!(opened.room_id, room_id);
+    assert_eq!(opened.failure_streak, 6);
+
+    let hook = list(&client, &room_id, &admin_key);
+    assert_eq!(hook["state"], "open");
+    assert_eq!(hook["active"], false);
+    assert!(hook["circuit_opened_at"].is_string());
+
+    // Re-enabling closes the circuit and clears the streak
+    let res = client
+        .put(format!("/api/v1/rooms/{room_id}/webhooks/{id}"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .body(r#"{"active": true}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let hook = list(&client, &room_id, &admin_key);
+    assert_eq!(hook["state"], "healthy");
+    assert_eq!(hook["failure_streak"], 0);
+    assert!(hook.get("circuit_opened_at").is_none());
+}
+
+#[test]
+fn test_circuit_disabled_with_zero_window() {
+    let client = test_client();
+    let (room_id, admin_key) = create_test_room(&client, "circuit-off");
+    let id = create_webhook(&client, &room_id, &admin_key);
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    let off = CircuitBreaker {
+        min_failures: 1,
+        open_after: chrono::Duration::zero(),
+    };
+    let later = chrono::Utc::now() + chrono::Duration::days(1);
+    for _ in 0..10 {
+        assert!(record_delivery_outcome(&conn, &id, false, &off, later).is_none());
+    }
+    assert_eq!(list(&client, &room_id, &admin_key)["state"], "failing");
+}
+
+#[test]
+fn test_manually_disabled_state() {
+    let client = test_client();
+    let (room_id, admin_key) = create_test_room(&client, "circuit-manual");
+    let id = create_webhook(&client, &room_id, &admin_key);
+    client
+        .put(format!("/api/v1/rooms/{room_id}/webhooks/{id}"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .body(r#"{"active": false}"#)
+        .dispatch();
+    assert_eq!(list(&client, &room_id, &admin_key)["state"], "disabled");
+}
@@ -0,0 +1,138 @@
+use crate::common::{create_test_room, test_client};
+use local_agent_chat::secrets::SecretBox;
+use rocket::http::{ContentType, Header, Status};
+
+// --- Webhook custom headers and mTLS settings ---
+
+fn create_webhook(
+    client: &crate::common::TestClient,
+    room_id: &str,
+    admin_key: &str,
+    body: &str,
+) -> (Status, serde_json::Value) {
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/webhooks"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .body(body)
+        .dispatch();
+    let status = res.status();
+    (status, res.into_json().unwrap_or_default())
+}
+
+#[test]
+fn test_webhook_custom_headers_stored_encrypted() {
+    let client = test_client();
+    let (room_id, admin_key) = create_test_room(&client, "webhook-headers");
+
+    let (status, body) = create_webhook(
+        &client,
+        &room_id,
+        &admin_key,
+        r#"{"url": "http://localhost:9999/hook", "headers": {"Authorization": "Bearer tok_very_secret", "X-Team": "ops"}}"#,
+    );
+    assert_eq!(status, Status::Ok);
+    assert_eq!(body["header_names"], serde_json::json!(["Authorization", "X-Team"]));
+    assert_eq!(body["has_client_cert"], false);
+    let webhook_id = body["id"].as_str().unwrap().to_string();
+
+    // Listing shows the names but never the values
+    let res = client
+        .get(format!("/api/v1/rooms/{room_id}/webhooks"))
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .dispatch();
+    let text = res.into_string().unwrap();
+    assert!(!text.contains("tok_very_secret"));
+    let list: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(list[0]["header_names"], serde_json::json!(["Authorization", "X-Team"]));
+    assert_eq!(list[0]["has_ca_cert"], false);
+
+    // Nothing readable in the database either
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    let stored: String = conn
+        .query_row("SELECT headers FROM webhooks WHERE id = ?1", [&webhook_id], |r| r.get(0))
+        .unwrap();
+    assert!(stored.starts_with("v1:"));
+    assert!(!stored.contains("tok_very_secret"));
+    assert!(!stored.contains("Authorization"));
+
+    // `{}` clears them
+    let res = client
+        .put(format!("/api/v1/rooms/{room_id}/webhooks/{webhook_id}"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .body(r#"{"headers": {}}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let stored: Option<String> = conn
+        .query_row("SELECT headers FROM webhooks WHERE id = ?1", [&webhook_id], |r| r.get(0))
+        .unwrap();
+    assert!(stored.is_none());
+}
+
+#[test]
+fn test_webhook_custom_headers_validation() {
+    let client = test_client();
+    let (room_id, admin_key) = create_test_room(&client, "webhook-headers-invalid");
+
+    for headers in [
+        r#"{"X-Chat-Event": "spoofed"}"#,
+        r#"{"Content-Type": "text/plain"}"#,
+        r#"{"Bad Name": "x"}"#,
+        r#"{"X-Injected": "a\r\nX-Other: b"}"#,
+    ] {
+        let (status, body) = create_webhook(
+            &client,
+            &room_id,
+            &admin_key,
+            &format!(r#"{{"url": "http://localhost:9999/hook", "headers": {headers}}}"#),
+        );
+        assert_eq!(status, Status::BadRequest, "{headers}");
+        assert!(body["error"].is_string());
+    }
+}
+
+#[test]
+fn test_webhook_rejects_invalid_certificates() {
+    let client = test_client();
+    let (room_id, admin_key) = create_test_room(&client, "webhook-mtls-invalid");
+
+    let (status, body) = create_webhook(
+        &client,
+        &room_id,
+        &admin_key,
+        r#"{"url": "https://localhost:9999/hook", "client_cert": "not a pem"}"#,
+    );
+    assert_eq!(status, Status::BadRequest);
+    assert!(body["error"].as_str().unwrap().contains("client_cert"));
+
+    let (status, body) = create_webhook(
+        &client,
+        &room_id,
+        &admin_key,
+        r#"{"url": "https://localhost:9999/hook", "ca_cert": "-----BEGIN CERTIFICATE-----\ngarbage\n-----END CERTIFICATE-----"}"#,
+    );
+    assert_eq!(status, Status::BadRequest);
+    assert!(body["error"].as_str().unwrap().contains("ca_cert"));
+
+    // Blank values mean "none"
+    let (status, body) = create_webhook(
+        &client,
+        &room_id,
+        &admin_key,
+        r#"{"url": "https://localhost:9999/hook", "client_cert": "  "}"#,
+    );
+    assert_eq!(status, Status::Ok);
+    assert_eq!(body["has_client_cert"], false);
+}
+
+#[test]
+fn test_secret_box_round_trip() {
+    let secrets = SecretBox::new([7u8; 32]);
+    let sealed = secrets.seal("Bearer abc");
+    assert!(sealed.starts_with("v1:"));
+    assert_ne!(sealed, secrets.seal("Bearer abc"), "each seal uses a fresh nonce");
+    assert_eq!(secrets.open(&sealed).as_deref(), Some("Bearer abc"));
+    assert_eq!(SecretBox::new([8u8; 32]).open(&sealed), None);
+    assert_eq!(secrets.open("v1:AAAA"), None);
+}
@@ -334,6 +334,42 @@ fn test_update_webhook() {
     assert_eq!(webhooks[0]["active"], false);
 }
 
+#[test]
+fn test_ordered_webhook_option() {
+    let client = test_client();
+    let (room_id, admin_key) = create_test_room(&client, "webhook-ordered-room");
+
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/webhooks"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .body(r#"{"url": "http://localhost:9999/hook", "ordered": true}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let webhook: serde_json::Value = res.into_json().unwrap();
+    assert_eq!(webhook["ordered"], true);
+    let webhook_id = webhook["id"].as_str().unwrap();
+
+    let list = |client: &crate::common::TestClient| -> Vec<serde_json::Value> {
+        client
+            .get(format!("/api/v1/rooms/{room_id}/webhooks"))
+            .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+            .dispatch()
+            .into_json()
+            .unwrap()
+    };
+    assert_eq!(list(&client)[0]["ordered"], true);
+
+    let res = client
+        .put(format!("/api/v1/rooms/{room_id}/webhooks/{webhook_id}"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {admin_key}")))
+        .body(r#"{"ordered": false}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    assert_eq!(list(&client)[0]["ordered"], false);
+}
+
 #[test]
 fn test_update_webhook_not_found() {
     let client = test_client();
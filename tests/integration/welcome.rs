@@ -0,0 +1,161 @@
+use rocket::http::{ContentType, Header, Status};
+use rocket::local::blocking::Client;
+use crate::common::{create_test_room, policy_with_token, post_message, test_client, test_client_with_sender_policy};
+use serde_json::json;
+
+// --- Room welcome messages ---
+
+fn set_welcome(client: &Client, room_id: &str, key: &str, body: serde_json::Value) -> (Status, serde_json::Value) {
+    let res = client
+        .put(format!("/api/v1/rooms/{room_id}/welcome"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {key}")))
+        .body(body.to_string())
+        .dispatch();
+    let status = res.status();
+    (status, res.into_json().unwrap_or(serde_json::json!(null)))
+}
+
+fn dm_messages(client: &Client, a: &str, b: &str) -> Vec<serde_json::Value> {
+    let convos: serde_json::Value = client.get(format!("/api/v1/dm?sender={a}")).dispatch().into_json().unwrap();
+    let Some(convo) = convos["conversations"].as_array().unwrap().iter().find(|c| c["other_participant"] == b) else {
+        return Vec::new();
+    };
+    client
+        .get(format!("/api/v1/rooms/{}/messages", convo["room_id"].as_str().unwrap()))
+        .dispatch()
+        .into_json()
+        .unwrap()
+}
+
+#[test]
+fn test_welcome_dm_on_first_post_only() {
+    let client = test_client();
+    let (room_id, key) = create_test_room(&client, "welcome-dm");
+    let res = client
+        .put(format!("/api/v1/rooms/{room_id}"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {key}")))
+        .body(r#"{"description": "Release coordination"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let pinned =
+        post_message(&client, &room_id, json!({"sender": "lead", "content": "Runbook: https://wiki.local/deploys"}));
+    client
+        .post(format!("/api/v1/rooms/{room_id}/messages/{}/pin", pinned["id"].as_str().unwrap()))
+        .header(Header::new("Authorization", format!("Bearer {key}")))
+        .dispatch();
+
+    let (status, welcome) = set_welcome(
+        &client,
+        &room_id,
+        &key,
+        serde_json::json!({
+            "template": "Hi {sender}! #{room_name} is for: {room_description}\nStart here:\n{pinned_links}",
+            "from": "greeter"
+        }),
+    );
+    assert_eq!(status, Status::Ok);
+    assert_eq!(welcome["delivery"], "dm");
+    assert_eq!(welcome["from"], "greeter");
+
+    post_message(&client, &room_id, json!({"sender": "newbie", "content": "hello?"}));
+    post_message(&client, &room_id, json!({"sender": "newbie", "content": "anyone here?"}));
+
+    let dms = dm_messages(&client, "newbie", "greeter");
+    assert_eq!(dms.len(), 1);
+    assert_eq!(
+        dms[0]["content"],
+        "Hi newbie! #welcome-dm is for: Release coordination\nStart here:\nhttps://wiki.local/deploys"
+    );
+    assert_eq!(dms[0]["metadata"]["welcome"]["room_id"], room_id.as_str());
+
+    // Senders who posted before the welcome existed aren't retroactively greeted
+    post_message(&client, &room_id, json!({"sender": "lead", "content": "welcome configured"}));
+    assert!(dm_messages(&client, "lead", "greeter").is_empty());
+}
+
+#[test]
+fn test_welcome_posted_in_room_on_join() {
+    let client = test_client();
+    let (room_id, key) = create_test_room(&client, "welcome-room");
+    let (status, _) = set_welcome(
+        &client,
+        &room_id,
+        &key,
+        serde_json::json!({"template": "Welcome, {sender}.", "delivery": "room"}),
+    );
+    assert_eq!(status, Status::Ok);
+
+    drop(client.get(format!("/api/v1/rooms/{room_id}/stream?sender=lurker")).dispatch());
+    post_message(&client, &room_id, json!({"sender": "lurker", "content": "finally saying hi"}));
+
+    let notes: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/rooms/{room_id}/messages?kind=system"))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    let welcomes: Vec<&serde_json::Value> = notes.iter().filter(|m| m["metadata"]["event"] == "welcome").collect();
+    assert_eq!(welcomes.len(), 1);
+    assert_eq!(welcomes[0]["content"], "Welcome, lurker.");
+    assert_eq!(welcomes[0]["metadata"]["sender"], "lurker");
+}
+
+#[test]
+fn test_welcome_config_requires_admin_key() {
+    let client = test_client();
+    let (room_id, key) = create_test_room(&client, "welcome-auth");
+
+    assert_eq!(client.get(format!("/api/v1/rooms/{room_id}/welcome")).dispatch().status(), Status::NotFound);
+
+    let (status, _) = set_welcome(&client, &room_id, "wrong", serde_json::json!({"template": "hi"}));
+    assert_eq!(status, Status::Forbidden);
+    let (status, _) = set_welcome(&client, &room_id, &key, serde_json::json!({"template": "  "}));
+    assert_eq!(status, Status::BadRequest);
+    let (status, _) = set_welcome(&client, &room_id, &key, serde_json::json!({"template": "hi", "delivery": "email"}));
+    assert_eq!(status, Status::BadRequest);
+
+    let (status, _) = set_welcome(&client, &room_id, &key, serde_json::json!({"template": "hi"}));
+    assert_eq!(status, Status::Ok);
+    let got: serde_json::Value = client.get(format!("/api/v1/rooms/{room_id}/welcome")).dispatch().into_json().unwrap();
+    assert_eq!(got["template"], "hi");
+    assert_eq!(got["from"], "system");
+
+    let res = client
+        .delete(format!("/api/v1/rooms/{room_id}/welcome"))
+        .header(Header::new("Authorization", format!("Bearer {key}")))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    post_message(&client, &room_id, json!({"sender": "after-delete", "content": "no welcome for me"}));
+    assert!(dm_messages(&client, "after-delete", "system").is_empty());
+}
+
+#[test]
+fn test_welcome_from_reserved_sender_needs_server_token() {
+    let client = test_client_with_sender_policy(policy_with_token());
+    let (room_id, key) = create_test_room(&client, "welcome-reserved");
+
+    // The default sender is fine with the admin key alone
+    let (status, _) = set_welcome(&client, &room_id, &key, json!({"template": "hi", "from": "system"}));
+    assert_eq!(status, Status::Ok);
+    let (status, _) = set_welcome(&client, &room_id, &key, json!({"template": "hi", "from": "greeter"}));
+    assert_eq!(status, Status::Ok);
+
+    // Other reserved names would let the room admin send DMs as them
+    for from in ["admin", " Ops-Bot "] {
+        let (status, body) = set_welcome(&client, &room_id, &key, json!({"template": "hi", "from": from}));
+        assert_eq!(status, Status::Forbidden);
+        assert!(body["error"].as_str().unwrap().contains("reserved"));
+    }
+    let got: serde_json::Value = client.get(format!("/api/v1/rooms/{room_id}/welcome")).dispatch().into_json().unwrap();
+    assert_eq!(got["from"], "greeter");
+
+    let res = client
+        .put(format!("/api/v1/rooms/{room_id}/welcome"))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {key}")))
+        .header(Header::new("X-Server-Token", "srv_secret"))
+        .body(json!({"template": "hi", "from": "admin"}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+}
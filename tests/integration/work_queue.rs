@@ -0,0 +1,157 @@
+use rocket::http::{ContentType, Status};
+use rocket::local::blocking::Client;
+use serde_json::json;
+
+use crate::common::{create_test_room, post_json, test_client};
+
+fn enqueue(client: &Client, room_id: &str, task: &str) -> String {
+    let (status, body) = post_json(client, &format!("/api/v1/rooms/{room_id}/queue"), json!({"sender": "planner", "task": task}));
+    assert_eq!(status, Status::Ok);
+    body["id"].as_str().unwrap().to_string()
+}
+
+fn claim(client: &Client, room_id: &str, sender: &str) -> serde_json::Value {
+    let (status, body) = post_json(client, &format!("/api/v1/rooms/{room_id}/queue/claim"), json!({"sender": sender}));
+    assert_eq!(status, Status::Ok);
+    body
+}
+
+#[test]
+fn test_queue_is_fifo_with_single_claimer() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "queue-fifo");
+    let first = enqueue(&client, &room_id, "review PR #1");
+    let second = enqueue(&client, &room_id, "review PR #2");
+
+    let body = claim(&client, &room_id, "agent-a");
+    assert_eq!(body["item"]["id"], first.as_str());
+    assert_eq!(body["item"]["status"], "claimed");
+    assert_eq!(body["item"]["claimed_by"], "agent-a");
+    assert_eq!(body["item"]["attempts"], 1);
+    assert_eq!(body["pending"], 1);
+
+    let body = claim(&client, &room_id, "agent-b");
+    assert_eq!(body["item"]["id"], second.as_str());
+    assert_eq!(body["pending"], 0);
+
+    // Nothing left for a third agent
+    let body = claim(&client, &room_id, "agent-c");
+    assert!(body["item"].is_null());
+}
+
+#[test]
+fn test_complete_and_release() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "queue-complete");
+    let id = enqueue(&client, &room_id, "summarize logs");
+    claim(&client, &room_id, "agent-a");
+
+    // Only the claimer may finish it
+    let (status, _) = post_json(&client, &format!("/api/v1/rooms/{room_id}/queue/{id}/complete"), json!({"sender": "agent-b"}));
+    assert_eq!(status, Status::Conflict);
+    let (status, _) = post_json(&client, &format!("/api/v1/rooms/{room_id}/queue/{id}/release"), json!({"sender": "agent-b"}));
+    assert_eq!(status, Status::Conflict);
+
+    let (status, body) = post_json(&client, &format!("/api/v1/rooms/{room_id}/queue/{id}/release"), json!({"sender": "agent-a"}));
+    assert_eq!(status, Status::Ok);
+    assert_eq!(body["status"], "pending");
+    assert!(body["claimed_by"].is_null());
+
+    let body = claim(&client, &room_id, "agent-b");
+    assert_eq!(body["item"]["id"], id.as_str());
+    assert_eq!(body["item"]["attempts"], 2);
+    let (status, body) = post_json(&client, &format!("/api/v1/rooms/{room_id}/queue/{id}/complete"), json!({"sender": "agent-b", "result": {"lines": 120}}));
+    assert_eq!(status, Status::Ok);
+    assert_eq!(body["status"], "done");
+    assert_eq!(body["result"]["lines"], 120);
+    assert!(body["completed_at"].is_string());
+
+    let (status, _) = post_json(&client, &format!("/api/v1/rooms/{room_id}/queue/{id}/complete"), json!({"sender": "agent-b"}));
+    assert_eq!(status, Status::Conflict);
+    let (status, _) = post_json(&client, &format!("/api/v1/rooms/{room_id}/queue/nope/complete"), json!({"sender": "agent-b"}));
+    assert_eq!(status, Status::NotFound);
+}
+
+#[test]
+fn test_expired_lease_returns_item_to_queue() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "queue-lease");
+    let id = enqueue(&client, &room_id, "flaky job");
+    claim(&client, &room_id, "agent-a");
+
+    let conn = rusqlite::Connection::open(client.db_path()).unwrap();
+    conn.execute(
+        "UPDATE queue_items SET lease_expires_at = '2000-01-01T00:00:00+00:00' WHERE id = ?1",
+        [&id],
+    )
+    .unwrap();
+
+    // The lapsed claimer can't complete it any more
+    let (status, _) = post_json(&client, &format!("/api/v1/rooms/{room_id}/queue/{id}/complete"), json!({"sender": "agent-a"}));
+    assert_eq!(status, Status::Conflict);
+
+    let body = claim(&client, &room_id, "agent-b");
+    assert_eq!(body["item"]["id"], id.as_str());
+    assert_eq!(body["item"]["claimed_by"], "agent-b");
+}
+
+#[test]
+fn test_list_queue() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "queue-list");
+    let a = enqueue(&client, &room_id, "a");
+    enqueue(&client, &room_id, "b");
+    enqueue(&client, &room_id, "c");
+    claim(&client, &room_id, "worker");
+    post_json(&client, &format!("/api/v1/rooms/{room_id}/queue/{a}/complete"), json!({"sender": "worker"}));
+    claim(&client, &room_id, "worker");
+
+    let list = |q: &str| -> serde_json::Value {
+        client.get(format!("/api/v1/rooms/{room_id}/queue{q}")).dispatch().into_json().unwrap()
+    };
+    let body = list("");
+    assert_eq!(body["count"], 2);
+    assert_eq!(body["items"][0]["task"], "b");
+    assert_eq!(body["items"][0]["status"], "claimed");
+    assert_eq!(body["items"][1]["task"], "c");
+    assert_eq!(list("?status=done")["count"], 1);
+    assert_eq!(list("?status=pending")["count"], 1);
+    assert_eq!(list("?status=all")["count"], 3);
+    assert_eq!(list("?status=all&limit=1")["count"], 1);
+
+    let res = client.get(format!("/api/v1/rooms/{room_id}/queue?status=bogus")).dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+    let res = client.get("/api/v1/rooms/missing/queue").dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+}
+
+#[test]
+fn test_queue_validation() {
+    let client = test_client();
+    let (room_id, _) = create_test_room(&client, "queue-validation");
+    let path = format!("/api/v1/rooms/{room_id}/queue");
+    assert_eq!(post_json(&client, &path.clone(), json!({"sender": "a", "task": ""})).0, Status::BadRequest);
+    assert_eq!(post_json(&client, &path.clone(), json!({"sender": "", "task": "x"})).0, Status::BadRequest);
+    assert_eq!(post_json(&client, &path.clone(), json!({"sender": "a", "task": "x", "data": "str"})).0, Status::BadRequest);
+    assert_eq!(
+        post_json(&client, &format!("{path}/claim"), json!({"sender": "a", "lease_seconds": 0})).0,
+        Status::BadRequest
+    );
+    assert_eq!(
+        post_json(&client, &"/api/v1/rooms/missing/queue".to_string(), json!({"sender": "a", "task": "x"})).0,
+        Status::NotFound
+    );
+}
+
+#[test]
+fn test_webhook_accepts_queue_events() {
+    let client = test_client();
+    let (room_id, admin_key) = create_test_room(&client, "queue-hooks");
+    let res = client
+        .post(format!("/api/v1/rooms/{room_id}/webhooks"))
+        .header(ContentType::JSON)
+        .header(rocket::http::Header::new("Authorization", format!("Bearer {admin_key}")))
+        .body(json!({"url": "http://127.0.0.1:9/hook", "events": "queue_item_added,queue_item_completed"}).to_string())
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+}
@@ -0,0 +1,383 @@
+//! Synthetic-traffic load harness.
+//!
+//! Boots the real server on a loopback port and drives it over HTTP with N concurrent
+//! agents posting while M agents hold SSE streams open. Ignored by default; run with
+//!
+//!     cargo test --release --test load -- --ignored --nocapture
+//!
+//! Profile and targets are tunable through env vars (see `LoadProfile::from_env` and
+//! `Targets::from_env`). The defaults are the documented targets in README "Performance".
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use local_agent_chat::rate_limit::RateLimitConfig;
+use rocket::tokio;
+
+fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+    std::env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+}
+
+/// Shape of the simulated traffic.
+#[derive(Debug, Clone)]
+struct LoadProfile {
+    /// Concurrent posting agents (LOAD_AGENTS)
+    agents: usize,
+    /// Messages each agent posts (LOAD_MESSAGES)
+    messages_per_agent: usize,
+    /// Concurrent SSE listeners (LOAD_STREAMERS)
+    streamers: usize,
+    /// Rooms the agents are spread over (LOAD_ROOMS)
+    rooms: usize,
+}
+
+impl LoadProfile {
+    fn from_env() -> Self {
+        LoadProfile {
+            agents: env_or("LOAD_AGENTS", 20),
+            messages_per_agent: env_or("LOAD_MESSAGES", 50),
+            streamers: env_or("LOAD_STREAMERS", 10),
+            rooms: env_or::<usize>("LOAD_ROOMS", 2).max(1),
+        }
+    }
+
+    fn total_messages(&self) -> usize {
+        self.agents * self.messages_per_agent
+    }
+}
+
+/// Pass/fail thresholds for a run.
+#[derive(Debug, Clone)]
+struct Targets {
+    /// Minimum sustained posts/sec across all agents (LOAD_MIN_MSGS_PER_SEC)
+    min_msgs_per_sec: f64,
+    /// Maximum p99 POST latency in ms (LOAD_MAX_P99_MS)
+    max_p99_ms: f64,
+    /// Maximum p99 read latency in ms for history/search calls (LOAD_MAX_READ_P99_MS)
+    max_read_p99_ms: f64,
+}
+
+impl Targets {
+    fn from_env() -> Self {
+        Targets {
+            min_msgs_per_sec: env_or("LOAD_MIN_MSGS_PER_SEC", 200.0),
+            max_p99_ms: env_or("LOAD_MAX_P99_MS", 250.0),
+            max_read_p99_ms: env_or("LOAD_MAX_READ_P99_MS", 250.0),
+        }
+    }
+}
+
+/// A server running on a loopback port with its own temp DB.
+struct Server {
+    base: String,
+    db_path: String,
+    shutdown: rocket::Shutdown,
+}
+
+impl Server {
+    async fn start() -> Server {
+        let db_path = format!(
+            "/tmp/chat_load_{}.db",
+            uuid::Uuid::new_v4().to_string().split('-').next().unwrap()
+        );
+        // Grab a free port; the tiny window before Rocket rebinds it is fine for a local harness
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+
+        // Rate limits would dominate the measurement, so lift them out of the way
+        let unlimited = RateLimitConfig {
+            messages_max: usize::MAX,
+            rooms_max: usize::MAX,
+            files_max: usize::MAX,
+            dms_max: usize::MAX,
+            webhooks_max: usize::MAX,
+            reactions_max: usize::MAX,
+            ..Default::default()
+        };
+        let figment = rocket::Config::figment()
+            .merge(("address", "127.0.0.1"))
+            .merge(("port", port))
+            .merge(("log_level", "off"));
+        let rocket = local_agent_chat::rocket_with_db_and_config(&db_path, unlimited)
+            .configure(figment)
+            .ignite()
+            .await
+            .expect("server ignites");
+        let shutdown = rocket.shutdown();
+        tokio::spawn(rocket.launch());
+
+        let base = format!("http://127.0.0.1:{port}");
+        let client = reqwest::Client::new();
+        for _ in 0..100 {
+            if client.get(format!("{base}/api/v1/health")).send().await.is_ok() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        Server { base, db_path, shutdown }
+    }
+
+    async fn create_room(&self, client: &reqwest::Client, name: &str) -> String {
+        let body: serde_json::Value = client
+            .post(format!("{}/api/v1/rooms", self.base))
+            .json(&serde_json::json!({"name": name, "created_by": "load"}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        body["id"].as_str().unwrap().to_string()
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.shutdown.clone().notify();
+        let _ = std::fs::remove_file(&self.db_path);
+        let _ = std::fs::remove_file(format!("{}-wal", self.db_path));
+        let _ = std::fs::remove_file(format!("{}-shm", self.db_path));
+    }
+}
+
+/// Latency summary in milliseconds.
+struct Percentiles {
+    p50: f64,
+    p95: f64,
+    p99: f64,
+    max: f64,
+}
+
+fn percentiles(mut samples: Vec<Duration>) -> Percentiles {
+    assert!(!samples.is_empty(), "no samples recorded");
+    samples.sort();
+    let at = |q: f64| {
+        let idx = ((samples.len() as f64 * q).ceil() as usize).saturating_sub(1);
+        samples[idx.min(samples.len() - 1)].as_secs_f64() * 1000.0
+    };
+    Percentiles {
+        p50: at(0.50),
+        p95: at(0.95),
+        p99: at(0.99),
+        max: samples.last().unwrap().as_secs_f64() * 1000.0,
+    }
+}
+
+/// Hold an SSE stream open, counting `message` events until `expected` arrive or `deadline` passes.
+async fn stream_listener(url: String, expected: usize, deadline: Duration, ready: Arc<AtomicUsize>) -> usize {
+    let client = reqwest::Client::new();
+    let mut resp = client.get(&url).send().await.expect("stream connects");
+    ready.fetch_add(1, Ordering::SeqCst);
+
+    let start = Instant::now();
+    let mut seen = 0usize;
+    let mut pending = String::new();
+    while seen < expected && start.elapsed() < deadline {
+        let remaining = deadline.saturating_sub(start.elapsed());
+        let chunk = match tokio::time::timeout(remaining, resp.chunk()).await {
+            Ok(Ok(Some(c))) => c,
+            _ => break,
+        };
+        pending.push_str(&String::from_utf8_lossy(&chunk));
+        while let Some(pos) = pending.find('\n') {
+            let line: String = pending.drain(..=pos).collect();
+            if line.trim_end() == "event: message" {
+                seen += 1;
+            }
+        }
+    }
+    seen
+}
+
+#[test]
+#[ignore]
+fn load_concurrent_posting_and_streaming() {
+    let profile = LoadProfile::from_env();
+    let targets = Targets::from_env();
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+
+    runtime.block_on(async move {
+        let server = Server::start().await;
+        let client = reqwest::Client::new();
+        let mut rooms = Vec::new();
+        for r in 0..profile.rooms {
+            rooms.push(server.create_room(&client, &format!("load-{r}")).await);
+        }
+
+        // Streamers all watch the first room; every message posted there must reach each of them
+        let first_room_msgs = (0..profile.agents)
+            .filter(|a| a % profile.rooms == 0)
+            .count()
+            * profile.messages_per_agent;
+        let ready = Arc::new(AtomicUsize::new(0));
+        let mut listeners = Vec::new();
+        for s in 0..profile.streamers {
+            let url = format!("{}/api/v1/rooms/{}/stream?sender=watcher-{s}", server.base, rooms[0]);
+            listeners.push(tokio::spawn(stream_listener(
+                url,
+                first_room_msgs,
+                Duration::from_secs(120),
+                ready.clone(),
+            )));
+        }
+        while ready.load(Ordering::SeqCst) < profile.streamers {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let started = Instant::now();
+        let mut posters = Vec::new();
+        for a in 0..profile.agents {
+            let url = format!("{}/api/v1/rooms/{}/messages", server.base, rooms[a % profile.rooms]);
+            let client = client.clone();
+            let count = profile.messages_per_agent;
+            posters.push(tokio::spawn(async move {
+                let mut latencies = Vec::with_capacity(count);
+                let mut failures = 0usize;
+                for m in 0..count {
+                    let t = Instant::now();
+                    let res = client
+                        .post(&url)
+                        .json(&serde_json::json!({
+                            "sender": format!("agent-{a}"),
+                            "sender_type": "agent",
+                            "content": format!("load message {m} from agent {a} about deployment status")
+                        }))
+                        .send()
+                        .await;
+                    latencies.push(t.elapsed());
+                    if !matches!(res, Ok(ref r) if r.status().is_success()) {
+                        failures += 1;
+                    }
+                }
+                (latencies, failures)
+            }));
+        }
+
+        let mut latencies = Vec::new();
+        let mut failures = 0;
+        for p in posters {
+            let (l, f) = p.await.unwrap();
+            latencies.extend(l);
+            failures += f;
+        }
+        let elapsed = started.elapsed();
+        let delivered: Vec<usize> = futures_join(listeners).await;
+
+        let throughput = profile.total_messages() as f64 / elapsed.as_secs_f64();
+        let lat = percentiles(latencies);
+        println!("--- load: {profile:?}");
+        println!(
+            "posted {} msgs in {:.2}s → {:.0} msg/s (target ≥ {:.0})",
+            profile.total_messages(),
+            elapsed.as_secs_f64(),
+            throughput,
+            targets.min_msgs_per_sec
+        );
+        println!(
+            "POST latency ms: p50 {:.1}  p95 {:.1}  p99 {:.1}  max {:.1} (target p99 ≤ {:.0})",
+            lat.p50, lat.p95, lat.p99, lat.max, targets.max_p99_ms
+        );
+        println!("SSE fan-out: {delivered:?} of {first_room_msgs} per streamer");
+
+        assert_eq!(failures, 0, "all posts should succeed");
+        assert!(
+            delivered.iter().all(|&d| d == first_room_msgs),
+            "every streamer should see every message: {delivered:?} of {first_room_msgs}"
+        );
+        assert!(throughput >= targets.min_msgs_per_sec, "throughput {throughput:.0} msg/s below target");
+        assert!(lat.p99 <= targets.max_p99_ms, "p99 {:.1}ms above target", lat.p99);
+    });
+}
+
+#[test]
+#[ignore]
+fn load_mixed_reads_during_writes() {
+    let profile = LoadProfile::from_env();
+    let targets = Targets::from_env();
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+
+    runtime.block_on(async move {
+        let server = Server::start().await;
+        let client = reqwest::Client::new();
+        let room = server.create_room(&client, "load-mixed").await;
+        let messages_url = format!("{}/api/v1/rooms/{room}/messages", server.base);
+
+        let mut writers = Vec::new();
+        for a in 0..profile.agents {
+            let client = client.clone();
+            let url = messages_url.clone();
+            let count = profile.messages_per_agent;
+            writers.push(tokio::spawn(async move {
+                for m in 0..count {
+                    let _ = client
+                        .post(&url)
+                        .json(&serde_json::json!({
+                            "sender": format!("writer-{a}"),
+                            "content": format!("deployment {m} finished with status green on shard {a}")
+                        }))
+                        .send()
+                        .await;
+                }
+            }));
+        }
+
+        // Readers page through history and hit FTS while the writers are busy
+        let mut readers = Vec::new();
+        for r in 0..profile.agents {
+            let client = client.clone();
+            let base = server.base.clone();
+            let url = messages_url.clone();
+            let room = room.clone();
+            let count = profile.messages_per_agent;
+            readers.push(tokio::spawn(async move {
+                let mut latencies = Vec::with_capacity(count);
+                for i in 0..count {
+                    let target = if (r + i) % 2 == 0 {
+                        format!("{url}?latest=50")
+                    } else {
+                        format!("{base}/api/v1/search?q=deployment&room_id={room}&limit=20")
+                    };
+                    let t = Instant::now();
+                    let res = client.get(&target).send().await;
+                    latencies.push(t.elapsed());
+                    assert!(matches!(res, Ok(ref r) if r.status().is_success()), "read failed: {target}");
+                }
+                latencies
+            }));
+        }
+
+        for w in writers {
+            w.await.unwrap();
+        }
+        let mut latencies = Vec::new();
+        for r in readers {
+            latencies.extend(r.await.unwrap());
+        }
+        let lat = percentiles(latencies);
+        println!("--- load (mixed): {profile:?}");
+        println!(
+            "read latency ms: p50 {:.1}  p95 {:.1}  p99 {:.1}  max {:.1} (target p99 ≤ {:.0})",
+            lat.p50, lat.p95, lat.p99, lat.max, targets.max_read_p99_ms
+        );
+        assert!(lat.p99 <= targets.max_read_p99_ms, "read p99 {:.1}ms above target", lat.p99);
+    });
+}
+
+/// Await every handle, in order (avoids pulling in the `futures` crate for join_all).
+async fn futures_join<T>(handles: Vec<tokio::task::JoinHandle<T>>) -> Vec<T> {
+    let mut out = Vec::with_capacity(handles.len());
+    for h in handles {
+        out.push(h.await.unwrap());
+    }
+    out
+}